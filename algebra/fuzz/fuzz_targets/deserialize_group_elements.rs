@@ -0,0 +1,43 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zei_algebra::{
+    bls12_381::{BLSGt, BLSG1, BLSG2},
+    bn254::{BN254G1, BN254G2},
+    jubjub::JubjubPoint,
+    prelude::*,
+    ristretto::{CompressedEdwardsY, CompressedRistretto, RistrettoPoint},
+    secp256k1::SECP256K1G1,
+    secq256k1::SECQ256K1G1,
+};
+
+/// Decode the bytes as a group element through every entry point, none of which may panic,
+/// and check that what is accepted round-trips.
+fn decode<G: Group + ZeiFromToBytes>(data: &[u8]) {
+    if let Ok(p) = G::from_compressed_bytes(data) {
+        assert_eq!(G::from_compressed_bytes(&p.to_compressed_bytes()).unwrap(), p);
+    }
+    let _ = G::from_unchecked_bytes(data);
+    let _ = G::zei_from_bytes(data);
+
+    let _ = bincode::deserialize::<G>(data);
+    if let Ok(string) = core::str::from_utf8(data) {
+        let _ = serde_json::from_str::<G>(string);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<BLSG1>(data);
+    decode::<BLSG2>(data);
+    decode::<BLSGt>(data);
+    decode::<BN254G1>(data);
+    decode::<BN254G2>(data);
+    decode::<JubjubPoint>(data);
+    decode::<RistrettoPoint>(data);
+    decode::<SECP256K1G1>(data);
+    decode::<SECQ256K1G1>(data);
+
+    let _ = CompressedRistretto::zei_from_bytes(data);
+    let _ = CompressedEdwardsY::zei_from_bytes(data);
+    let _ = bincode::deserialize::<CompressedRistretto>(data);
+});
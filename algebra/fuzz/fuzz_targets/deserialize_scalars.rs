@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zei_algebra::{
+    bls12_381::BLSScalar, bn254::BN254Scalar, jubjub::JubjubScalar, prelude::*,
+    ristretto::RistrettoScalar, secp256k1::SECP256K1Scalar, secq256k1::SECQ256K1Scalar,
+};
+
+/// Decode the bytes as a scalar through every entry point, none of which may panic,
+/// and check that what is accepted round-trips.
+fn decode<S: Scalar + ZeiFromToBytes>(data: &[u8]) {
+    if let Ok(s) = S::from_bytes(data) {
+        assert_eq!(S::from_bytes(&s.to_bytes()).unwrap(), s);
+    }
+    if let Ok(s) = S::zei_from_bytes(data) {
+        assert_eq!(S::zei_from_bytes(&s.zei_to_bytes()).unwrap(), s);
+    }
+    let _ = scalar_from_tagged_bytes::<S>(data);
+    let _ = scalar_from_ordered_bytes::<S>(data, ScalarByteOrder::BigEndian);
+
+    let _ = bincode::deserialize::<S>(data);
+    if let Ok(string) = core::str::from_utf8(data) {
+        let _ = serde_json::from_str::<S>(string);
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    decode::<BLSScalar>(data);
+    decode::<BN254Scalar>(data);
+    decode::<JubjubScalar>(data);
+    decode::<RistrettoScalar>(data);
+    decode::<SECP256K1Scalar>(data);
+    decode::<SECQ256K1Scalar>(data);
+});
@@ -1,13 +1,22 @@
-use crate::{errors::AlgebraError, prelude::*, traits::Pairing};
+use crate::{
+    errors::AlgebraError,
+    prelude::*,
+    traits::{impl_group_by_value_ops, impl_scalar_by_value_ops, Pairing},
+};
 use ark_bls12_381::{
-    fr::FrParameters, Bls12_381 as Bls12381pairing, Fq, Fq12Parameters, Fr, G1Affine, G1Projective,
-    G2Affine, G2Projective,
+    fr::FrParameters, Bls12_381 as Bls12381pairing, Fq, Fq12Parameters, Fq6, Fr, G1Affine,
+    G1Projective, G2Affine, G2Projective,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine, ProjectiveCurve,
+    SWModelParameters,
 };
-use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{
     BigInteger, BigInteger256, FftField, FftParameters, Field, Fp12, FpParameters, PrimeField,
 };
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalDeserializeWithFlags, CanonicalSerialize, Flags, SWFlags,
+};
 use ark_std::collections::BTreeMap;
 use ark_std::{
     fmt::{Debug, Display, Formatter},
@@ -72,13 +81,9 @@ impl FromStr for BLSScalar {
     type Err = AlgebraError;
 
     fn from_str(string: &str) -> StdResult<Self, AlgebraError> {
-        let res = Fr::from_str(string);
-
-        if res.is_ok() {
-            Ok(Self(res.unwrap()))
-        } else {
-            Err(AlgebraError::DeserializationError)
-        }
+        Fr::from_str(string)
+            .map(Self)
+            .map_err(|_| AlgebraError::DeserializationError)
     }
 }
 
@@ -227,6 +232,8 @@ impl Neg for BLSScalar {
     }
 }
 
+impl_scalar_by_value_ops!(BLSScalar);
+
 impl From<u32> for BLSScalar {
     #[inline]
     fn from(value: u32) -> Self {
@@ -252,8 +259,19 @@ impl Scalar for BLSScalar {
     where
         D: Digest<OutputSize = U64> + Default,
     {
-        let mut prng = derive_prng_from_hash::<D>(hash);
-        Self::random(&mut prng)
+        // Reducing the 512-bit digest modulo the group order yields a scalar whose
+        // distribution is statistically close to uniform.
+        Self(Fr::from_le_bytes_mod_order(&hash.finalize()))
+    }
+
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(Fr::from(value))
+    }
+
+    #[inline]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(Fr::from_le_bytes_mod_order(bytes))
     }
 
     #[inline]
@@ -337,6 +355,80 @@ impl Scalar for BLSScalar {
     }
 }
 
+/// The checks performed when decoding a point of BLS12-381 from its compressed encoding.
+/// Whether the point is on the curve is always checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointValidation {
+    /// Check that the point is in the prime-order subgroup and is not the identity,
+    /// for the points received from other parties.
+    /// This is the validation of `from_compressed_bytes`.
+    #[default]
+    Full,
+    /// Check that the point is in the prime-order subgroup, the identity being accepted.
+    /// This is the validation of the serialized structures, since honest data may contain the
+    /// identity, e.g. the commitment of a zero polynomial.
+    Subgroup,
+    /// Skip the subgroup check, for trusted contexts such as loading parameters generated locally.
+    Trusted,
+}
+
+/// Decode a compressed point, checking that it is on the curve and validating it.
+fn deserialize_compressed_point<P: SWModelParameters>(
+    bytes: &[u8],
+    validation: PointValidation,
+) -> Result<GroupAffine<P>> {
+    let (x, flags): (P::BaseField, SWFlags) =
+        CanonicalDeserializeWithFlags::deserialize_with_flags(bytes)
+            .map_err(|_| eg!(AlgebraError::DeserializationError))?;
+    let point = if flags.is_infinity() {
+        GroupAffine::<P>::zero()
+    } else {
+        let greatest = flags
+            .is_positive()
+            .ok_or_else(|| eg!(AlgebraError::DeserializationError))?;
+        GroupAffine::<P>::get_point_from_x(x, greatest)
+            .ok_or_else(|| eg!(AlgebraError::DeserializationError))?
+    };
+
+    let valid = match validation {
+        PointValidation::Full => {
+            !point.is_zero() && point.is_in_correct_subgroup_assuming_on_curve()
+        }
+        PointValidation::Subgroup => point.is_in_correct_subgroup_assuming_on_curve(),
+        PointValidation::Trusted => true,
+    };
+    if !valid {
+        return Err(eg!(AlgebraError::DeserializationError));
+    }
+    Ok(point)
+}
+
+impl BLSG1 {
+    /// Decode a point from its compressed encoding with the given validation.
+    pub fn from_compressed_bytes_with_validation(
+        bytes: &[u8],
+        validation: PointValidation,
+    ) -> Result<Self> {
+        if bytes.len() != Self::COMPRESSED_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        deserialize_compressed_point(bytes, validation).map(|affine| Self(affine.into_projective()))
+    }
+}
+
+impl BLSG2 {
+    /// Decode a point from its compressed encoding with the given validation.
+    pub fn from_compressed_bytes_with_validation(
+        bytes: &[u8],
+        validation: PointValidation,
+    ) -> Result<Self> {
+        if bytes.len() != Self::COMPRESSED_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        deserialize_compressed_point(bytes, validation).map(|affine| Self(affine.into_projective()))
+    }
+}
+
 impl Group for BLSG1 {
     type ScalarType = BLSScalar;
     const COMPRESSED_LEN: usize = 48;
@@ -381,28 +473,16 @@ impl Group for BLSG1 {
 
     #[inline]
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
-        let mut reader = ark_std::io::BufReader::new(bytes);
-
-        let affine = G1Affine::deserialize(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        Self::from_compressed_bytes_with_validation(bytes, PointValidation::Full)
     }
 
     #[inline]
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G1Affine::deserialize_unchecked(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G1Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(G1Projective::from(affine)))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
@@ -712,6 +792,8 @@ impl Neg for BLSG1 {
     }
 }
 
+impl_group_by_value_ops!(BLSG1, BLSScalar);
+
 impl Group for BLSG2 {
     type ScalarType = BLSScalar;
     const COMPRESSED_LEN: usize = 96;
@@ -755,28 +837,16 @@ impl Group for BLSG2 {
 
     #[inline]
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
-        let mut reader = ark_std::io::BufReader::new(bytes);
-
-        let affine = G2Affine::deserialize(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(affine.unwrap().into_projective()))
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        Self::from_compressed_bytes_with_validation(bytes, PointValidation::Full)
     }
 
     #[inline]
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G2Affine::deserialize_unchecked(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(affine.unwrap().into_projective()))
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G2Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(affine.into_projective()))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
@@ -803,6 +873,8 @@ impl Neg for BLSG2 {
     }
 }
 
+impl_group_by_value_ops!(BLSG2, BLSScalar);
+
 impl<'a> Add<&'a BLSG2> for BLSG2 {
     type Output = BLSG2;
 
@@ -857,6 +929,18 @@ impl Pairing for BLSPairingEngine {
     fn pairing(a: &Self::G1, b: &Self::G2) -> Self::Gt {
         BLSGt(Bls12381pairing::pairing(a.0, b.0))
     }
+
+    #[inline]
+    fn multi_pairing(pairs: &[(Self::G1, Self::G2)]) -> Self::Gt {
+        let prepared = pairs
+            .iter()
+            .map(|(a, b)| (G1Affine::from(a.0).into(), G2Affine::from(b.0).into()))
+            .collect::<Vec<(
+                <Bls12381pairing as PairingEngine>::G1Prepared,
+                <Bls12381pairing as PairingEngine>::G2Prepared,
+            )>>();
+        BLSGt(Bls12381pairing::product_of_pairings(&prepared))
+    }
 }
 
 impl Neg for BLSGt {
@@ -869,6 +953,8 @@ impl Neg for BLSGt {
     }
 }
 
+impl_group_by_value_ops!(BLSGt, BLSScalar);
+
 impl<'a> Add<&'a BLSGt> for BLSGt {
     type Output = BLSGt;
 
@@ -938,10 +1024,47 @@ impl<'a> SubAssign<&'a BLSGt> for BLSGt {
     }
 }
 
+/// The length of the uncompressed encoding of a `BLSGt` element,
+/// which is still accepted by `BLSGt::from_compressed_bytes`.
+const BLS12_381_GT_LEGACY_LEN: usize = 576;
+
+impl BLSGt {
+    /// Compress the element with the torus-based compression.
+    ///
+    /// Writing `Fp12 = Fp6[w] / (w^2 - v)`, every element of `Gt` other than the identity is
+    /// `(c + w) / (c - w)` for a unique `c` in `Fp6`, namely `c = (1 + a) / b` for the element
+    /// `a + b * w`. The identity, the only element of `Gt` with `b = 0`, is mapped to `c = 0`,
+    /// which would otherwise encode `-1`, an element outside of `Gt`.
+    fn torus_compress(&self) -> Fq6 {
+        match self.0.c1.inverse() {
+            Some(b_inv) => (Fq6::one() + self.0.c0) * b_inv,
+            None => Fq6::zero(),
+        }
+    }
+
+    /// Decompress an element compressed by `torus_compress`.
+    /// The result is in the cyclotomic subgroup, but not necessarily in `Gt`.
+    fn torus_decompress(c: &Fq6) -> Self {
+        if c.is_zero() {
+            return Self::get_identity();
+        }
+        let c = Fp12::<Fq12Parameters>::new(*c, Fq6::zero());
+        let w = Fp12::<Fq12Parameters>::new(Fq6::zero(), Fq6::one());
+        // `c - w` is not zero, so the inverse exists.
+        let den = (c - w).inverse().unwrap_or_default();
+        Self((c + w) * den)
+    }
+
+    /// Check if the element is in `Gt`, i.e., has the order of the scalar field.
+    fn is_in_subgroup(&self) -> bool {
+        self.0.pow(FrParameters::MODULUS) == Fp12::<Fq12Parameters>::one()
+    }
+}
+
 impl Group for BLSGt {
     type ScalarType = BLSScalar;
 
-    const COMPRESSED_LEN: usize = 576;
+    const COMPRESSED_LEN: usize = 288;
 
     #[inline]
     fn double(&self) -> Self {
@@ -965,8 +1088,8 @@ impl Group for BLSGt {
 
     #[inline]
     fn to_compressed_bytes(&self) -> Vec<u8> {
-        let mut buf = Vec::new();
-        self.0.serialize(&mut buf).unwrap();
+        let mut buf = Vec::with_capacity(Self::COMPRESSED_LEN);
+        self.torus_compress().serialize(&mut buf).unwrap();
 
         buf
     }
@@ -981,12 +1104,19 @@ impl Group for BLSGt {
 
     #[inline]
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
-        let mut reader = ark_std::io::BufReader::new(bytes);
-
-        let res = Fp12::<Fq12Parameters>::deserialize(&mut reader);
+        let point = if bytes.len() == Self::COMPRESSED_LEN {
+            let c = Fq6::deserialize(bytes).map_err(|_| eg!(AlgebraError::DeserializationError))?;
+            Self::torus_decompress(&c)
+        } else if bytes.len() == BLS12_381_GT_LEGACY_LEN {
+            let v = Fp12::<Fq12Parameters>::deserialize(bytes)
+                .map_err(|_| eg!(AlgebraError::DeserializationError))?;
+            Self(v)
+        } else {
+            return Err(eg!(AlgebraError::DeserializationError));
+        };
 
-        if res.is_ok() {
-            Ok(Self(res.unwrap()))
+        if point.is_in_subgroup() {
+            Ok(point)
         } else {
             Err(eg!(AlgebraError::DeserializationError))
         }
@@ -996,13 +1126,9 @@ impl Group for BLSGt {
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let res = Fp12::<Fq12Parameters>::deserialize_unchecked(&mut reader);
-
-        if res.is_ok() {
-            Ok(Self(res.unwrap()))
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        Fp12::<Fq12Parameters>::deserialize_unchecked(&mut reader)
+            .map(|res| Self(res))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
@@ -1016,23 +1142,27 @@ impl Group for BLSGt {
     where
         D: Digest<OutputSize = U64> + Default,
     {
-        let mut prng = derive_prng_from_hash::<D>(hash);
-        Self(Fp12::<Fq12Parameters>::rand(&mut prng))
+        Self::get_base().mul(&BLSScalar::from_hash(hash))
     }
 }
 
 #[cfg(test)]
 mod bls12_381_groups_test {
     use crate::{
-        bls12_381::{BLSGt, BLSPairingEngine, BLSScalar, BLSG1, BLSG2},
+        bls12_381::{BLSGt, BLSPairingEngine, BLSScalar, PointValidation, BLSG1, BLSG2},
         prelude::*,
         traits::{
-            group_tests::{test_scalar_operations, test_scalar_serialization},
+            group_tests::{
+                test_multi_pairing, test_scalar_from_hash, test_scalar_operations,
+                test_scalar_serialization,
+            },
             Pairing,
         },
     };
-    use ark_bls12_381::{G1Affine, G2Affine};
-    use ark_ec::ProjectiveCurve;
+    use ark_bls12_381::{g1, g2, Fq12Parameters, G1Affine, G2Affine};
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, ProjectiveCurve, SWModelParameters};
+    use ark_ff::Fp12;
+    use ark_serialize::CanonicalSerialize;
     use ark_std::test_rng;
 
     #[test]
@@ -1045,6 +1175,13 @@ mod bls12_381_groups_test {
         test_scalar_serialization::<BLSScalar>();
     }
 
+    #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<BLSScalar>(
+            "4dda32660ff231eea4352f525116786cb91617f8acdca1bba87c2e408e0bb804",
+        );
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = BLSScalar::from(165747u32);
@@ -1066,6 +1203,11 @@ mod bls12_381_groups_test {
         assert_eq!(base_bls_gt, expected_base);
     }
 
+    #[test]
+    fn multi_pairing() {
+        test_multi_pairing::<BLSPairingEngine>();
+    }
+
     #[test]
     fn bilinear_properties() {
         let identity_g1 = BLSG1::get_identity();
@@ -1178,7 +1320,125 @@ mod bls12_381_groups_test {
 
         let gt = BLSGt::random(&mut prng);
         let gt_bytes = gt.to_compressed_bytes();
+        assert_eq!(gt_bytes.len(), BLSGt::COMPRESSED_LEN);
         let gt_recovered = BLSGt::from_compressed_bytes(&gt_bytes).unwrap();
         assert_eq!(gt, gt_recovered);
     }
+
+    #[test]
+    fn test_compression_of_gt() {
+        let mut prng = test_rng();
+
+        let identity = BLSGt::get_identity();
+        let identity_bytes = identity.to_compressed_bytes();
+        assert_eq!(identity_bytes, vec![0u8; BLSGt::COMPRESSED_LEN]);
+        assert_eq!(
+            BLSGt::from_compressed_bytes(&identity_bytes).unwrap(),
+            identity
+        );
+
+        for _ in 0..4 {
+            let gt = BLSGt::random(&mut prng);
+            assert_eq!(
+                BLSGt::from_compressed_bytes(&gt.to_compressed_bytes()).unwrap(),
+                gt
+            );
+            let neg_gt = gt.neg();
+            assert_eq!(
+                BLSGt::from_compressed_bytes(&neg_gt.to_compressed_bytes()).unwrap(),
+                neg_gt
+            );
+
+            // the uncompressed encoding is still accepted
+            let legacy_bytes = gt.to_unchecked_bytes();
+            assert_eq!(legacy_bytes.len(), 576);
+            assert_eq!(BLSGt::from_compressed_bytes(&legacy_bytes).unwrap(), gt);
+        }
+
+        // elements outside of `Gt` and malformed encodings are rejected
+        let outside = BLSGt(Fp12::<Fq12Parameters>::rand(&mut prng));
+        assert!(BLSGt::from_compressed_bytes(&outside.to_unchecked_bytes()).is_err());
+        assert!(BLSGt::from_compressed_bytes(&outside.to_compressed_bytes()).is_err());
+
+        let gt_bytes = BLSGt::random(&mut prng).to_compressed_bytes();
+        assert!(BLSGt::from_compressed_bytes(&[]).is_err());
+        assert!(BLSGt::from_compressed_bytes(&gt_bytes[1..]).is_err());
+        assert!(BLSGt::from_compressed_bytes(&vec![0xffu8; BLSGt::COMPRESSED_LEN]).is_err());
+    }
+
+    fn point_outside_subgroup<P: SWModelParameters>() -> Vec<u8> {
+        let mut x = P::BaseField::one();
+        loop {
+            if let Some(point) = GroupAffine::<P>::get_point_from_x(x, true) {
+                if !point.is_in_correct_subgroup_assuming_on_curve() {
+                    let mut buf = Vec::new();
+                    point.serialize(&mut buf).unwrap();
+                    return buf;
+                }
+            }
+            x += P::BaseField::one();
+        }
+    }
+
+    #[test]
+    fn test_validation_of_points() {
+        let mut prng = test_rng();
+
+        let g1_bytes = BLSG1::random(&mut prng).to_compressed_bytes();
+        let identity_bytes = BLSG1::get_identity().to_compressed_bytes();
+        let outside_bytes = point_outside_subgroup::<g1::Parameters>();
+        for validation in [
+            PointValidation::Full,
+            PointValidation::Subgroup,
+            PointValidation::Trusted,
+        ] {
+            assert!(BLSG1::from_compressed_bytes_with_validation(&g1_bytes, validation).is_ok());
+        }
+        // the compressed identity is only accepted in the serialized structures
+        assert!(BLSG1::from_compressed_bytes(&identity_bytes).is_err());
+        assert!(BLSG1::from_compressed_bytes_with_validation(
+            &identity_bytes,
+            PointValidation::default()
+        )
+        .is_err());
+        assert_eq!(
+            BLSG1::zei_from_bytes(&identity_bytes).unwrap(),
+            BLSG1::get_identity()
+        );
+        assert!(BLSG1::from_compressed_bytes(&outside_bytes).is_err());
+        assert!(BLSG1::from_compressed_bytes_with_validation(
+            &outside_bytes,
+            PointValidation::Trusted
+        )
+        .is_ok());
+        assert!(BLSG1::from_compressed_bytes(&g1_bytes[1..]).is_err());
+
+        let g2_bytes = BLSG2::random(&mut prng).to_compressed_bytes();
+        let identity_bytes = BLSG2::get_identity().to_compressed_bytes();
+        let outside_bytes = point_outside_subgroup::<g2::Parameters>();
+        for validation in [
+            PointValidation::Full,
+            PointValidation::Subgroup,
+            PointValidation::Trusted,
+        ] {
+            assert!(BLSG2::from_compressed_bytes_with_validation(&g2_bytes, validation).is_ok());
+        }
+        // the compressed identity is only accepted in the serialized structures
+        assert!(BLSG2::from_compressed_bytes(&identity_bytes).is_err());
+        assert!(BLSG2::from_compressed_bytes_with_validation(
+            &identity_bytes,
+            PointValidation::default()
+        )
+        .is_err());
+        assert_eq!(
+            BLSG2::zei_from_bytes(&identity_bytes).unwrap(),
+            BLSG2::get_identity()
+        );
+        assert!(BLSG2::from_compressed_bytes(&outside_bytes).is_err());
+        assert!(BLSG2::from_compressed_bytes_with_validation(
+            &outside_bytes,
+            PointValidation::Trusted
+        )
+        .is_ok());
+    }
 }
@@ -0,0 +1,1012 @@
+use crate::{
+    errors::AlgebraError,
+    prelude::*,
+    traits::{impl_group_by_value_ops, impl_scalar_by_value_ops, Pairing},
+};
+use ark_bn254::{
+    fq::FqParameters, fr::FrParameters, Bn254 as Bn254pairing, Fq, Fq12Parameters, Fq2, Fr,
+    G1Affine, G1Projective, G2Affine, G2Projective,
+};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{BigInteger, FftField, FftParameters, Field, Fp12, FpParameters, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{
+    fmt::{Debug, Display, Formatter},
+    result::Result as StdResult,
+    str::FromStr,
+};
+use digest::{generic_array::typenum::U64, Digest};
+use num_bigint::BigUint;
+use wasm_bindgen::prelude::*;
+
+/// The number of bytes for a scalar value over BN254
+pub const BN254_SCALAR_LEN: usize = 32;
+
+/// The wrapped struct for `ark_bn254::Fr`
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
+pub struct BN254Scalar(pub(crate) Fr);
+
+impl Debug for BN254Scalar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <BigUint as Debug>::fmt(&self.0.into_repr().into(), f)
+    }
+}
+
+/// The wrapped struct for `ark_bn254::G1Projective`
+#[wasm_bindgen]
+#[derive(Copy, Default, Clone, PartialEq, Eq)]
+pub struct BN254G1(pub(crate) G1Projective);
+
+impl Debug for BN254G1 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <G1Affine as Display>::fmt(&self.0.into_affine(), f)
+    }
+}
+
+/// The wrapped struct for `ark_bn254::G2Projective`
+#[wasm_bindgen]
+#[derive(Copy, Default, Clone, PartialEq, Eq)]
+pub struct BN254G2(pub(crate) G2Projective);
+
+impl Debug for BN254G2 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <G2Affine as Display>::fmt(&self.0.into_affine(), f)
+    }
+}
+
+/// The wrapped struct for `Fp12<ark_bn254::Fq12Parameters>`, which is the pairing result
+#[wasm_bindgen]
+#[derive(Copy, Default, Clone, PartialEq, Eq, Debug)]
+pub struct BN254Gt(pub(crate) Fp12<Fq12Parameters>);
+
+impl FromStr for BN254Scalar {
+    type Err = AlgebraError;
+
+    fn from_str(string: &str) -> StdResult<Self, AlgebraError> {
+        Fr::from_str(string)
+            .map(Self)
+            .map_err(|_| AlgebraError::DeserializationError)
+    }
+}
+
+impl Into<BigUint> for BN254Scalar {
+    #[inline]
+    fn into(self) -> BigUint {
+        self.0.into_repr().into()
+    }
+}
+
+impl<'a> From<&'a BigUint> for BN254Scalar {
+    #[inline]
+    fn from(src: &BigUint) -> Self {
+        Self(Fr::from(src.clone()))
+    }
+}
+
+impl One for BN254Scalar {
+    #[inline]
+    fn one() -> Self {
+        BN254Scalar(Fr::one())
+    }
+}
+
+impl Zero for BN254Scalar {
+    #[inline]
+    fn zero() -> Self {
+        Self(Fr::zero())
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl Add for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl Mul for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self(self.0.mul(&rhs.0))
+    }
+}
+
+impl Sum<BN254Scalar> for BN254Scalar {
+    #[inline]
+    fn sum<I: Iterator<Item = BN254Scalar>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl<'a> Add<&'a BN254Scalar> for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn add(self, rhs: &Self) -> Self::Output {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl<'a> AddAssign<&'a BN254Scalar> for BN254Scalar {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Self) {
+        (self.0).add_assign(&rhs.0);
+    }
+}
+
+impl<'a> Sub<&'a BN254Scalar> for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn sub(self, rhs: &Self) -> Self::Output {
+        Self(self.0.sub(&rhs.0))
+    }
+}
+
+impl<'a> SubAssign<&'a BN254Scalar> for BN254Scalar {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &Self) {
+        (self.0).sub_assign(&rhs.0);
+    }
+}
+
+impl<'a> Mul<&'a BN254Scalar> for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn mul(self, rhs: &Self) -> Self::Output {
+        Self(self.0.mul(&rhs.0))
+    }
+}
+
+impl<'a> MulAssign<&'a BN254Scalar> for BN254Scalar {
+    #[inline]
+    fn mul_assign(&mut self, rhs: &Self) {
+        (self.0).mul_assign(&rhs.0);
+    }
+}
+
+impl<'a> Sum<&'a BN254Scalar> for BN254Scalar {
+    #[inline]
+    fn sum<I: Iterator<Item = &'a BN254Scalar>>(iter: I) -> Self {
+        iter.fold(Self::zero(), Add::add)
+    }
+}
+
+impl Neg for BN254Scalar {
+    type Output = BN254Scalar;
+
+    #[inline]
+    fn neg(self) -> Self {
+        Self(self.0.neg())
+    }
+}
+
+impl_scalar_by_value_ops!(BN254Scalar);
+
+impl From<u32> for BN254Scalar {
+    #[inline]
+    fn from(value: u32) -> Self {
+        Self::from(value as u64)
+    }
+}
+
+impl From<u64> for BN254Scalar {
+    #[inline]
+    fn from(value: u64) -> Self {
+        Self(Fr::from(value))
+    }
+}
+
+impl Scalar for BN254Scalar {
+    #[inline]
+    fn random<R: CryptoRng + RngCore>(rng: &mut R) -> Self {
+        Self(Fr::rand(rng))
+    }
+
+    #[inline]
+    fn from_hash<D>(hash: D) -> Self
+    where
+        D: Digest<OutputSize = U64> + Default,
+    {
+        // Reducing the 512-bit digest modulo the group order yields a scalar whose
+        // distribution is statistically close to uniform.
+        Self(Fr::from_le_bytes_mod_order(&hash.finalize()))
+    }
+
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(Fr::from(value))
+    }
+
+    #[inline]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(Fr::from_le_bytes_mod_order(bytes))
+    }
+
+    #[inline]
+    fn capacity() -> usize {
+        FrParameters::CAPACITY as usize
+    }
+
+    #[inline]
+    fn multiplicative_generator() -> Self {
+        Self(Fr::multiplicative_generator())
+    }
+
+    #[inline]
+    fn get_field_size_biguint() -> BigUint {
+        FrParameters::MODULUS.into()
+    }
+
+    #[inline]
+    fn get_field_size_le_bytes() -> Vec<u8> {
+        FrParameters::MODULUS.to_bytes_le()
+    }
+
+    #[inline]
+    fn get_little_endian_u64(&self) -> Vec<u64> {
+        self.0.into_repr().0.to_vec()
+    }
+
+    #[inline]
+    fn bytes_len() -> usize {
+        BN254_SCALAR_LEN
+    }
+
+    #[inline]
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.into_repr().to_bytes_le()
+    }
+
+    #[inline]
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > Self::bytes_len() {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        Ok(Self(Fr::from_le_bytes_mod_order(bytes)))
+    }
+
+    #[inline]
+    fn inv(&self) -> Result<Self> {
+        self.0
+            .inverse()
+            .map(Self)
+            .c(d!(AlgebraError::GroupInversionError))
+    }
+
+    #[inline]
+    fn pow(&self, exponent: &[u64]) -> Self {
+        let len = exponent.len();
+        let mut array = [0u64; 4];
+        array[..len].copy_from_slice(exponent);
+        Self(self.0.pow(&array))
+    }
+
+    fn square(&self) -> Self {
+        Self(self.0.square())
+    }
+}
+
+/// The length of a field element in the encodings of the Ethereum precompiles.
+pub const ETHEREUM_FIELD_LEN: usize = 32;
+/// The length of a point of G1 in the encodings of the Ethereum precompiles.
+pub const ETHEREUM_G1_LEN: usize = 2 * ETHEREUM_FIELD_LEN;
+/// The length of a point of G2 in the encodings of the Ethereum precompiles.
+pub const ETHEREUM_G2_LEN: usize = 4 * ETHEREUM_FIELD_LEN;
+/// The length of a pair of points in the input of the Ethereum pairing precompile.
+pub const ETHEREUM_PAIR_LEN: usize = ETHEREUM_G1_LEN + ETHEREUM_G2_LEN;
+
+// Encode an element of the base field as 32 big-endian bytes.
+fn fq_to_ethereum_bytes(x: &Fq) -> Vec<u8> {
+    x.into_repr().to_bytes_be()
+}
+
+// Decode an element of the base field from 32 big-endian bytes,
+// rejecting values not smaller than the field size.
+fn fq_from_ethereum_bytes(bytes: &[u8]) -> Result<Fq> {
+    let value = BigUint::from_bytes_be(bytes);
+    if value >= BigUint::from(FqParameters::MODULUS) {
+        return Err(eg!(AlgebraError::DeserializationError));
+    }
+    Ok(Fq::from(value))
+}
+
+impl BN254G1 {
+    /// Encode the point as `x || y` in 32-byte big-endian coordinates, the layout of
+    /// the Ethereum precompiles for BN254 (alt_bn128). The identity is encoded as zeros.
+    pub fn to_ethereum_bytes(&self) -> Vec<u8> {
+        let affine = G1Affine::from(self.0);
+        if affine.infinity {
+            return vec![0u8; ETHEREUM_G1_LEN];
+        }
+        let mut bytes = fq_to_ethereum_bytes(&affine.x);
+        bytes.extend_from_slice(&fq_to_ethereum_bytes(&affine.y));
+        bytes
+    }
+
+    /// Decode a point from the encoding of the Ethereum precompiles, see
+    /// [`BN254G1::to_ethereum_bytes`]. Points that are not on the curve are rejected.
+    pub fn from_ethereum_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != ETHEREUM_G1_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Self::get_identity());
+        }
+        let x = fq_from_ethereum_bytes(&bytes[..ETHEREUM_FIELD_LEN]).c(d!())?;
+        let y = fq_from_ethereum_bytes(&bytes[ETHEREUM_FIELD_LEN..]).c(d!())?;
+        let affine = G1Affine::new(x, y, false);
+        if !affine.is_on_curve() {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        Ok(Self(affine.into_projective()))
+    }
+}
+
+impl BN254G2 {
+    /// Encode the point as `x.c1 || x.c0 || y.c1 || y.c0` in 32-byte big-endian coordinates,
+    /// the layout of the Ethereum pairing precompile, which puts the imaginary part of
+    /// each coordinate first. The identity is encoded as zeros.
+    pub fn to_ethereum_bytes(&self) -> Vec<u8> {
+        let affine = G2Affine::from(self.0);
+        if affine.infinity {
+            return vec![0u8; ETHEREUM_G2_LEN];
+        }
+        let mut bytes = vec![];
+        for c in [affine.x.c1, affine.x.c0, affine.y.c1, affine.y.c0].iter() {
+            bytes.extend_from_slice(&fq_to_ethereum_bytes(c));
+        }
+        bytes
+    }
+
+    /// Decode a point from the encoding of the Ethereum pairing precompile, see
+    /// [`BN254G2::to_ethereum_bytes`]. Points that are not on the curve, or not in
+    /// the prime-order subgroup, are rejected, as the precompile does.
+    pub fn from_ethereum_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != ETHEREUM_G2_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        if bytes.iter().all(|b| *b == 0) {
+            return Ok(Self::get_identity());
+        }
+        let coordinates = bytes
+            .chunks(ETHEREUM_FIELD_LEN)
+            .map(fq_from_ethereum_bytes)
+            .collect::<Result<Vec<Fq>>>()
+            .c(d!())?;
+        let x = Fq2::new(coordinates[1], coordinates[0]);
+        let y = Fq2::new(coordinates[3], coordinates[2]);
+        let affine = G2Affine::new(x, y, false);
+        if !affine.is_on_curve() || !affine.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        Ok(Self(affine.into_projective()))
+    }
+}
+
+/// Encode the pairs as the input of the Ethereum pairing precompile (at address `0x08`),
+/// which checks that the product of their pairings is the identity.
+pub fn to_ethereum_pairing_input(pairs: &[(BN254G1, BN254G2)]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(pairs.len() * ETHEREUM_PAIR_LEN);
+    for (a, b) in pairs.iter() {
+        bytes.extend_from_slice(&a.to_ethereum_bytes());
+        bytes.extend_from_slice(&b.to_ethereum_bytes());
+    }
+    bytes
+}
+
+/// Run the check of the Ethereum pairing precompile on its input: return whether the product
+/// of the pairings of the encoded pairs is the identity, or an error where the precompile fails,
+/// i.e., if the length is not a multiple of the length of a pair, or if a point is invalid.
+/// This allows checking off-chain what a contract will accept.
+pub fn ethereum_pairing_check(input: &[u8]) -> Result<bool> {
+    if input.len() % ETHEREUM_PAIR_LEN != 0 {
+        return Err(eg!(AlgebraError::DeserializationError));
+    }
+    let pairs = input
+        .chunks(ETHEREUM_PAIR_LEN)
+        .map(|pair| {
+            Ok((
+                BN254G1::from_ethereum_bytes(&pair[..ETHEREUM_G1_LEN]).c(d!())?,
+                BN254G2::from_ethereum_bytes(&pair[ETHEREUM_G1_LEN..]).c(d!())?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(BN254PairingEngine::multi_pairing(&pairs) == BN254Gt::get_identity())
+}
+
+impl Group for BN254G1 {
+    type ScalarType = BN254Scalar;
+    const COMPRESSED_LEN: usize = 32;
+
+    #[inline]
+    fn double(&self) -> Self {
+        Self(self.0.double())
+    }
+
+    #[inline]
+    fn get_identity() -> Self {
+        Self(G1Projective::zero())
+    }
+
+    #[inline]
+    fn get_base() -> Self {
+        Self(G1Projective::prime_subgroup_generator())
+    }
+
+    #[inline]
+    fn random<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        Self::get_base().mul(&BN254Scalar::random(prng))
+    }
+
+    #[inline]
+    fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        G1Affine::from(self.0).serialize(&mut buf).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn to_unchecked_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        G1Affine::from(self.0)
+            .serialize_unchecked(&mut buf)
+            .unwrap();
+        buf
+    }
+
+    #[inline]
+    fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        G1Affine::deserialize(&mut reader)
+            .map(|affine| Self(affine.into_projective()))
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        G1Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(affine.into_projective()))
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn unchecked_size() -> usize {
+        G1Affine::from(Self::get_base().0).uncompressed_size()
+    }
+
+    #[inline]
+    fn from_hash<D>(hash: D) -> Self
+    where
+        D: Digest<OutputSize = U64> + Default,
+    {
+        let mut prng = derive_prng_from_hash::<D>(hash);
+        Self(G1Projective::rand(&mut prng))
+    }
+
+    #[inline]
+    fn multi_exp(scalars: &[&Self::ScalarType], points: &[&Self]) -> Self {
+        let scalars_raw = scalars
+            .iter()
+            .map(|r| r.0.into_repr())
+            .collect::<Vec<<FrParameters as FftParameters>::BigInt>>();
+        let points_raw = G1Projective::batch_normalization_into_affine(
+            &points.iter().map(|r| r.0).collect::<Vec<G1Projective>>(),
+        );
+
+        Self(ark_ec::msm::VariableBase::msm(&points_raw, &scalars_raw))
+    }
+}
+
+impl<'a> Add<&'a BN254G1> for BN254G1 {
+    type Output = BN254G1;
+
+    #[inline]
+    fn add(self, rhs: &Self) -> Self::Output {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl<'a> Sub<&'a BN254G1> for BN254G1 {
+    type Output = BN254G1;
+
+    #[inline]
+    fn sub(self, rhs: &Self) -> Self::Output {
+        Self(self.0.sub(&rhs.0))
+    }
+}
+
+impl<'a> Mul<&'a BN254Scalar> for BN254G1 {
+    type Output = BN254G1;
+
+    #[inline]
+    fn mul(self, rhs: &BN254Scalar) -> Self::Output {
+        Self(self.0.mul(&rhs.0.into_repr()))
+    }
+}
+
+impl<'a> AddAssign<&'a BN254G1> for BN254G1 {
+    #[inline]
+    fn add_assign(&mut self, rhs: &'a BN254G1) {
+        self.0.add_assign(&rhs.0)
+    }
+}
+
+impl<'a> SubAssign<&'a BN254G1> for BN254G1 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &'a BN254G1) {
+        self.0.sub_assign(&rhs.0)
+    }
+}
+
+impl Neg for BN254G1 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.neg())
+    }
+}
+
+impl_group_by_value_ops!(BN254G1, BN254Scalar);
+
+impl Group for BN254G2 {
+    type ScalarType = BN254Scalar;
+    const COMPRESSED_LEN: usize = 64;
+
+    #[inline]
+    fn double(&self) -> Self {
+        Self(self.0.double())
+    }
+
+    #[inline]
+    fn get_identity() -> Self {
+        Self(G2Projective::zero())
+    }
+
+    #[inline]
+    fn get_base() -> Self {
+        Self(G2Projective::prime_subgroup_generator())
+    }
+
+    #[inline]
+    fn random<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        Self::get_base().mul(&BN254Scalar::random(prng))
+    }
+
+    #[inline]
+    fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        G2Affine::from(self.0).serialize(&mut buf).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn to_unchecked_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        G2Affine::from(self.0)
+            .serialize_unchecked(&mut buf)
+            .unwrap();
+        buf
+    }
+
+    #[inline]
+    fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        G2Affine::deserialize(&mut reader)
+            .map(|affine| Self(affine.into_projective()))
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        G2Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(affine.into_projective()))
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn unchecked_size() -> usize {
+        G2Affine::from(Self::get_base().0).uncompressed_size()
+    }
+
+    #[inline]
+    fn from_hash<D>(hash: D) -> Self
+    where
+        D: Digest<OutputSize = U64> + Default,
+    {
+        let mut prng = derive_prng_from_hash::<D>(hash);
+        Self(G2Projective::rand(&mut prng))
+    }
+}
+
+impl Neg for BN254G2 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(self.0.neg())
+    }
+}
+
+impl_group_by_value_ops!(BN254G2, BN254Scalar);
+
+impl<'a> Add<&'a BN254G2> for BN254G2 {
+    type Output = BN254G2;
+
+    #[inline]
+    fn add(self, rhs: &'a Self) -> Self::Output {
+        Self(self.0.add(&rhs.0))
+    }
+}
+
+impl<'a> Sub<&'a BN254G2> for BN254G2 {
+    type Output = BN254G2;
+
+    #[inline]
+    fn sub(self, rhs: &'a Self) -> Self::Output {
+        Self(self.0.sub(&rhs.0))
+    }
+}
+
+impl<'a> Mul<&'a BN254Scalar> for BN254G2 {
+    type Output = BN254G2;
+
+    #[inline]
+    fn mul(self, rhs: &'a BN254Scalar) -> Self::Output {
+        Self(self.0.mul(&rhs.0.into_repr()))
+    }
+}
+
+impl<'a> AddAssign<&'a BN254G2> for BN254G2 {
+    #[inline]
+    fn add_assign(&mut self, rhs: &BN254G2) {
+        self.0.add_assign(&rhs.0)
+    }
+}
+
+impl<'a> SubAssign<&'a BN254G2> for BN254G2 {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &BN254G2) {
+        self.0.sub_assign(&rhs.0)
+    }
+}
+
+/// The pairing engine for BN254
+pub struct BN254PairingEngine;
+
+impl Pairing for BN254PairingEngine {
+    type ScalarField = BN254Scalar;
+    type G1 = BN254G1;
+    type G2 = BN254G2;
+    type Gt = BN254Gt;
+
+    #[inline]
+    fn pairing(a: &Self::G1, b: &Self::G2) -> Self::Gt {
+        BN254Gt(Bn254pairing::pairing(a.0, b.0))
+    }
+
+    #[inline]
+    fn multi_pairing(pairs: &[(Self::G1, Self::G2)]) -> Self::Gt {
+        let prepared = pairs
+            .iter()
+            .map(|(a, b)| (G1Affine::from(a.0).into(), G2Affine::from(b.0).into()))
+            .collect::<Vec<(
+                <Bn254pairing as PairingEngine>::G1Prepared,
+                <Bn254pairing as PairingEngine>::G2Prepared,
+            )>>();
+        BN254Gt(Bn254pairing::product_of_pairings(&prepared))
+    }
+}
+
+impl Neg for BN254Gt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        let mut v = self.0;
+        v.conjugate();
+        Self(v)
+    }
+}
+
+impl_group_by_value_ops!(BN254Gt, BN254Scalar);
+
+impl<'a> Add<&'a BN254Gt> for BN254Gt {
+    type Output = BN254Gt;
+
+    #[inline]
+    fn add(self, rhs: &'a BN254Gt) -> Self::Output {
+        Self(self.0.mul(&rhs.0))
+    }
+}
+
+impl<'a> Sub<&'a BN254Gt> for BN254Gt {
+    type Output = BN254Gt;
+
+    #[inline]
+    fn sub(self, rhs: &'a BN254Gt) -> Self::Output {
+        let mut rhs_inverse = rhs.0;
+        rhs_inverse.conjugate();
+
+        Self(self.0.mul(&rhs_inverse))
+    }
+}
+
+impl<'a> Mul<&'a BN254Scalar> for BN254Gt {
+    type Output = BN254Gt;
+
+    fn mul(self, rhs: &'a BN254Scalar) -> Self::Output {
+        let mut acc = Self::get_identity();
+
+        // This is a simple double-and-add implementation of group element
+        // multiplication, moving from most significant to least
+        // significant bit of the scalar.
+        //
+        // We skip the leading bit because it's always unset for Fr
+        // elements.
+        for bit in rhs
+            .0
+            .into_repr()
+            .to_bytes_le()
+            .iter()
+            .rev()
+            .flat_map(|byte| (0..8).rev().map(move |i| ((byte >> i) & 1u8) == 1u8))
+            .skip(1)
+        {
+            acc = acc.double();
+            if bit {
+                acc = acc.add(&self)
+            }
+        }
+
+        acc
+    }
+}
+
+impl<'a> AddAssign<&'a BN254Gt> for BN254Gt {
+    #[inline]
+    fn add_assign(&mut self, rhs: &'a BN254Gt) {
+        self.0.mul_assign(&rhs.0)
+    }
+}
+
+impl<'a> SubAssign<&'a BN254Gt> for BN254Gt {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &'a BN254Gt) {
+        let mut rhs_inverse = rhs.0;
+        rhs_inverse.conjugate();
+
+        self.0.mul_assign(&rhs_inverse)
+    }
+}
+
+impl Group for BN254Gt {
+    type ScalarType = BN254Scalar;
+
+    const COMPRESSED_LEN: usize = 384;
+
+    #[inline]
+    fn double(&self) -> Self {
+        Self(self.0.mul(&self.0))
+    }
+
+    #[inline]
+    fn get_identity() -> Self {
+        Self(Fp12::<Fq12Parameters>::one())
+    }
+
+    #[inline]
+    fn get_base() -> Self {
+        BN254PairingEngine::pairing(&BN254G1::get_base(), &BN254G2::get_base())
+    }
+
+    #[inline]
+    fn random<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        Self::get_base().mul(&BN254Scalar::random(prng))
+    }
+
+    #[inline]
+    fn to_compressed_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.0.serialize(&mut buf).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn to_unchecked_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.0.serialize_unchecked(&mut buf).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        Fp12::<Fq12Parameters>::deserialize(&mut reader)
+            .map(Self)
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ark_std::io::BufReader::new(bytes);
+        Fp12::<Fq12Parameters>::deserialize_unchecked(&mut reader)
+            .map(Self)
+            .c(d!(AlgebraError::DeserializationError))
+    }
+
+    #[inline]
+    fn unchecked_size() -> usize {
+        Self::get_base().0.uncompressed_size()
+    }
+
+    #[inline]
+    fn from_hash<D>(hash: D) -> Self
+    where
+        D: Digest<OutputSize = U64> + Default,
+    {
+        Self::get_base().mul(&BN254Scalar::from_hash(hash))
+    }
+}
+
+#[cfg(test)]
+mod bn254_groups_test {
+    use crate::{
+        bn254::{
+            ethereum_pairing_check, to_ethereum_pairing_input, BN254Gt, BN254PairingEngine,
+            BN254Scalar, BN254G1, BN254G2, ETHEREUM_G1_LEN,
+        },
+        prelude::*,
+        traits::{
+            group_tests::{
+                test_multi_pairing, test_scalar_from_hash, test_scalar_operations,
+                test_scalar_serialization,
+            },
+            Pairing,
+        },
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_scalar_ops() {
+        test_scalar_operations::<BN254Scalar>();
+    }
+
+    #[test]
+    fn scalar_deser() {
+        test_scalar_serialization::<BN254Scalar>();
+    }
+
+    #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<BN254Scalar>(
+            "1577087dfa6db004a0696237a7423d000006174c52a42c7b77a74ba7177c7028",
+        );
+    }
+
+    #[test]
+    fn multi_pairing() {
+        test_multi_pairing::<BN254PairingEngine>();
+    }
+
+    #[test]
+    fn bilinear_properties() {
+        let identity_gt =
+            BN254PairingEngine::pairing(&BN254G1::get_identity(), &BN254G2::get_base());
+        assert_eq!(identity_gt, BN254Gt::get_identity());
+
+        let mut prng = test_rng();
+        let s1 = BN254Scalar::random(&mut prng);
+        let s2 = BN254Scalar::random(&mut prng);
+        let base_g1 = BN254G1::get_base();
+        let base_g2 = BN254G2::get_base();
+        let base_gt = BN254Gt::get_base();
+
+        let gt_mapped_element = BN254PairingEngine::pairing(&base_g1.mul(&s1), &base_g2.mul(&s2));
+        assert_eq!(
+            gt_mapped_element,
+            BN254PairingEngine::pairing(&base_g1, &base_g2.mul(&s2)).mul(&s1)
+        );
+        assert_eq!(gt_mapped_element, base_gt.mul(&s1).mul(&s2));
+        assert_eq!(
+            gt_mapped_element.sub(&base_gt.mul(&s1.mul(&s2))),
+            BN254Gt::get_identity()
+        );
+    }
+
+    #[test]
+    fn test_serialization_of_points() {
+        let mut prng = test_rng();
+
+        let g1 = BN254G1::random(&mut prng);
+        let g1_bytes = g1.to_compressed_bytes();
+        assert_eq!(g1_bytes.len(), BN254G1::COMPRESSED_LEN);
+        assert_eq!(BN254G1::from_compressed_bytes(&g1_bytes).unwrap(), g1);
+
+        let g2 = BN254G2::random(&mut prng);
+        let g2_bytes = g2.to_compressed_bytes();
+        assert_eq!(g2_bytes.len(), BN254G2::COMPRESSED_LEN);
+        assert_eq!(BN254G2::from_compressed_bytes(&g2_bytes).unwrap(), g2);
+
+        let gt = BN254Gt::random(&mut prng);
+        let gt_bytes = gt.to_compressed_bytes();
+        assert_eq!(BN254Gt::from_compressed_bytes(&gt_bytes).unwrap(), gt);
+    }
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ethereum_encoding() {
+        // the generators, as in EIP-197
+        let mut g1 = vec![0u8; 64];
+        g1[31] = 1;
+        g1[63] = 2;
+        assert_eq!(BN254G1::get_base().to_ethereum_bytes(), g1);
+        let g2 = hex_to_bytes(concat!(
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+            "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+            "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        ));
+        assert_eq!(BN254G2::get_base().to_ethereum_bytes(), g2);
+
+        let mut prng = test_rng();
+        for p in [BN254G1::get_identity(), BN254G1::random(&mut prng)] {
+            assert_eq!(
+                BN254G1::from_ethereum_bytes(&p.to_ethereum_bytes()).unwrap(),
+                p
+            );
+        }
+        for p in [BN254G2::get_identity(), BN254G2::random(&mut prng)] {
+            assert_eq!(
+                BN254G2::from_ethereum_bytes(&p.to_ethereum_bytes()).unwrap(),
+                p
+            );
+        }
+
+        // points off the curve, coordinates not smaller than the field size, and wrong lengths
+        let mut off_curve = g1.clone();
+        off_curve[63] = 3;
+        assert!(BN254G1::from_ethereum_bytes(&off_curve).is_err());
+        assert!(BN254G1::from_ethereum_bytes(&[0xffu8; ETHEREUM_G1_LEN]).is_err());
+        assert!(BN254G1::from_ethereum_bytes(&g1[1..]).is_err());
+        let mut off_curve = g2;
+        off_curve[127] ^= 1;
+        assert!(BN254G2::from_ethereum_bytes(&off_curve).is_err());
+    }
+
+    #[test]
+    fn ethereum_pairing_precompile() {
+        let mut prng = test_rng();
+        let a = BN254G1::random(&mut prng);
+        let b = BN254G2::random(&mut prng);
+        let s = BN254Scalar::random(&mut prng);
+
+        // e(s * a, b) * e(-a, s * b) = 1
+        let input = to_ethereum_pairing_input(&[(a.mul(&s), b), (a.neg(), b.mul(&s))]);
+        assert_eq!(input.len(), 2 * 192);
+        assert!(ethereum_pairing_check(&input).unwrap());
+        let input = to_ethereum_pairing_input(&[(a.mul(&s), b), (a, b.mul(&s))]);
+        assert!(!ethereum_pairing_check(&input).unwrap());
+
+        // the empty input succeeds, and truncated inputs fail
+        assert!(ethereum_pairing_check(&[]).unwrap());
+        assert!(ethereum_pairing_check(&input[..191]).is_err());
+    }
+}
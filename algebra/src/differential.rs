@@ -0,0 +1,401 @@
+//! Differential testing of the arithmetic against a second implementation.
+//!
+//! The BLS12-381 and Jubjub operations, which are implemented over arkworks, are compared
+//! with the ones of the `bls12_381` and `jubjub` crates on edge cases, e.g., zero, one and
+//! minus one, and on random inputs. The checks are randomized and return an error describing
+//! the first operation whose results differ, so that downstream projects can run them in CI.
+
+use crate::{
+    bls12_381::{BLSPairingEngine, BLSScalar, PointValidation, BLSG1, BLSG2},
+    fmt::Debug,
+    jubjub::{JubjubPoint, JubjubScalar},
+    prelude::*,
+    traits::Pairing,
+};
+use ::bls12_381 as reference;
+use ::jubjub as reference_jubjub;
+use ark_ec::ProjectiveCurve;
+use ark_ff::{BigInteger, PrimeField};
+
+/// The flag of the point at infinity in the encoding of the `bls12_381` crate.
+const INFINITY_FLAG: u8 = 0x40;
+
+/// The number of edge cases at the start of the sampled scalars.
+const NUM_EDGE_SCALARS: usize = 5;
+
+/// Compare the BLS12-381 scalar field, groups and pairing with the `bls12_381` crate,
+/// on the edge cases and `rounds` random inputs.
+pub fn check_bls12_381<R: CryptoRng + RngCore>(prng: &mut R, rounds: usize) -> Result<()> {
+    let samples = sample_scalars::<BLSScalar, R>(prng, rounds);
+    let pairs = scalar_pairs(&samples);
+
+    for (a, b) in pairs.iter() {
+        let (ref_a, ref_b) = (to_reference_scalar(a)?, to_reference_scalar(b)?);
+        check(
+            a.add(b).to_bytes() == ref_a.add(&ref_b).to_bytes(),
+            "BLS12-381 scalar addition",
+            (a, b),
+        )?;
+        check(
+            a.sub(b).to_bytes() == ref_a.sub(&ref_b).to_bytes(),
+            "BLS12-381 scalar subtraction",
+            (a, b),
+        )?;
+        check(
+            a.mul(b).to_bytes() == ref_a.mul(&ref_b).to_bytes(),
+            "BLS12-381 scalar multiplication",
+            (a, b),
+        )?;
+
+        let (g1, ref_g1) = (
+            BLSG1::get_base().mul(a),
+            reference::G1Projective::generator() * ref_a,
+        );
+        let (h1, ref_h1) = (
+            BLSG1::get_base().mul(b),
+            reference::G1Projective::generator() * ref_b,
+        );
+        check(
+            g1_bytes(&g1.add(&h1)) == g1_reference_bytes(&(ref_g1 + ref_h1)),
+            "BLS12-381 G1 addition",
+            (a, b),
+        )?;
+        check(
+            g1_bytes(&g1.mul(b)) == g1_reference_bytes(&(ref_g1 * ref_b)),
+            "BLS12-381 G1 scalar multiplication",
+            (a, b),
+        )?;
+
+        let (g2, ref_g2) = (
+            BLSG2::get_base().mul(a),
+            reference::G2Projective::generator() * ref_a,
+        );
+        let (h2, ref_h2) = (
+            BLSG2::get_base().mul(b),
+            reference::G2Projective::generator() * ref_b,
+        );
+        check(
+            g2_bytes(&g2.add(&h2)) == g2_reference_bytes(&(ref_g2 + ref_h2)),
+            "BLS12-381 G2 addition",
+            (a, b),
+        )?;
+    }
+
+    for a in samples.iter() {
+        let ref_a = to_reference_scalar(a)?;
+        check(
+            a.neg().to_bytes() == ref_a.neg().to_bytes(),
+            "BLS12-381 scalar negation",
+            a,
+        )?;
+        check(
+            a.square().to_bytes() == ref_a.square().to_bytes(),
+            "BLS12-381 scalar squaring",
+            a,
+        )?;
+        let inv = a.inv().ok().map(|inv| inv.to_bytes());
+        let ref_inv: Option<reference::Scalar> = ref_a.invert().into();
+        check(
+            inv == ref_inv.map(|inv| inv.to_bytes().to_vec()),
+            "BLS12-381 scalar inversion",
+            a,
+        )?;
+
+        let (g1, ref_g1) = (
+            BLSG1::get_base().mul(a),
+            reference::G1Projective::generator() * ref_a,
+        );
+        check(
+            g1_bytes(&g1) == g1_reference_bytes(&ref_g1),
+            "BLS12-381 G1 generator multiplication",
+            a,
+        )?;
+        check(
+            g1_bytes(&g1.double()) == g1_reference_bytes(&ref_g1.double()),
+            "BLS12-381 G1 doubling",
+            a,
+        )?;
+        check(
+            g1_bytes(&g1.neg()) == g1_reference_bytes(&ref_g1.neg()),
+            "BLS12-381 G1 negation",
+            a,
+        )?;
+        check(
+            BLSG1::from_compressed_bytes_with_validation(
+                &g1.to_compressed_bytes(),
+                PointValidation::Subgroup,
+            )
+            .ok()
+                == Some(g1),
+            "BLS12-381 G1 compressed encoding",
+            a,
+        )?;
+
+        let (g2, ref_g2) = (
+            BLSG2::get_base().mul(a),
+            reference::G2Projective::generator() * ref_a,
+        );
+        check(
+            g2_bytes(&g2) == g2_reference_bytes(&ref_g2),
+            "BLS12-381 G2 generator multiplication",
+            a,
+        )?;
+        check(
+            g2_bytes(&g2.double()) == g2_reference_bytes(&ref_g2.double()),
+            "BLS12-381 G2 doubling",
+            a,
+        )?;
+    }
+
+    // the multi-exponentiations, with zero scalars and points at infinity
+    let points = samples
+        .iter()
+        .rev()
+        .map(|s| BLSG1::get_base().mul(s))
+        .collect_vec();
+    for len in [1, 2, samples.len()] {
+        let scalars = samples[..len].iter().collect_vec();
+        let bases = points[..len].iter().collect_vec();
+        let mut expected = reference::G1Projective::identity();
+        for (s, p) in samples[..len].iter().zip(samples.iter().rev()) {
+            expected += reference::G1Projective::generator()
+                * to_reference_scalar(p)?
+                * to_reference_scalar(s)?;
+        }
+        check(
+            g1_bytes(&BLSG1::multi_exp(&scalars, &bases)) == g1_reference_bytes(&expected),
+            "BLS12-381 G1 multi-exponentiation",
+            len,
+        )?;
+        check(
+            g1_bytes(&BLSG1::multi_exp_unsafe(&scalars, &bases)) == g1_reference_bytes(&expected),
+            "BLS12-381 G1 unsafe multi-exponentiation",
+            len,
+        )?;
+    }
+
+    // the Gt elements are compared through the equalities that they satisfy
+    for (a, b) in pairs.iter() {
+        let (ref_a, ref_b) = (to_reference_scalar(a)?, to_reference_scalar(b)?);
+        let gt = BLSPairingEngine::pairing(&BLSG1::get_base().mul(a), &BLSG2::get_base().mul(b));
+        let ref_gt = reference::pairing(
+            &(reference::G1Projective::generator() * ref_a).into(),
+            &(reference::G2Projective::generator() * ref_b).into(),
+        );
+        for c in [a.mul(b), a.mul(b).add(&BLSScalar::one())] {
+            let other = BLSPairingEngine::pairing(&BLSG1::get_base().mul(&c), &BLSG2::get_base());
+            let ref_other = reference::pairing(
+                &(reference::G1Projective::generator() * to_reference_scalar(&c)?).into(),
+                &reference::G2Affine::generator(),
+            );
+            check(
+                (gt == other) == (ref_gt == ref_other),
+                "BLS12-381 pairing",
+                (a, b),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Compare the Jubjub scalar field and group with the `jubjub` crate,
+/// on the edge cases and `rounds` random inputs.
+pub fn check_jubjub<R: CryptoRng + RngCore>(prng: &mut R, rounds: usize) -> Result<()> {
+    let samples = sample_scalars::<JubjubScalar, R>(prng, rounds);
+    let base = JubjubPoint::get_base();
+    let ref_base = to_reference_point(&base)?;
+
+    for (a, b) in scalar_pairs(&samples).iter() {
+        let (ref_a, ref_b) = (
+            to_reference_jubjub_scalar(a)?,
+            to_reference_jubjub_scalar(b)?,
+        );
+        check(
+            a.add(b).to_bytes() == ref_a.add(&ref_b).to_bytes(),
+            "Jubjub scalar addition",
+            (a, b),
+        )?;
+        check(
+            a.mul(b).to_bytes() == ref_a.mul(&ref_b).to_bytes(),
+            "Jubjub scalar multiplication",
+            (a, b),
+        )?;
+
+        let (p, ref_p) = (base.mul(a), ref_base * ref_a);
+        let (q, ref_q) = (base.mul(b), ref_base * ref_b);
+        check(
+            point_bytes(&p.add(&q)) == point_reference_bytes(&(ref_p + ref_q)),
+            "Jubjub point addition",
+            (a, b),
+        )?;
+        check(
+            point_bytes(&p.mul(b)) == point_reference_bytes(&(ref_p * ref_b)),
+            "Jubjub point multiplication",
+            (a, b),
+        )?;
+    }
+
+    for a in samples.iter() {
+        let ref_a = to_reference_jubjub_scalar(a)?;
+        let inv = a.inv().ok().map(|inv| inv.to_bytes());
+        let ref_inv: Option<reference_jubjub::Fr> = ref_a.invert().into();
+        check(
+            inv == ref_inv.map(|inv| inv.to_bytes().to_vec()),
+            "Jubjub scalar inversion",
+            a,
+        )?;
+
+        let (p, ref_p) = (base.mul(a), ref_base * ref_a);
+        check(
+            point_bytes(&p) == point_reference_bytes(&ref_p),
+            "Jubjub base multiplication",
+            a,
+        )?;
+        check(
+            point_bytes(&p.double()) == point_reference_bytes(&ref_p.double()),
+            "Jubjub point doubling",
+            a,
+        )?;
+        check(
+            point_bytes(&p.neg()) == point_reference_bytes(&ref_p.neg()),
+            "Jubjub point negation",
+            a,
+        )?;
+    }
+    Ok(())
+}
+
+/// Run all the differential checks on the edge cases and `rounds` random inputs.
+pub fn check_all<R: CryptoRng + RngCore>(prng: &mut R, rounds: usize) -> Result<()> {
+    check_bls12_381(prng, rounds).c(d!())?;
+    check_jubjub(prng, rounds).c(d!())
+}
+
+fn check<T: Debug>(equal: bool, operation: &str, inputs: T) -> Result<()> {
+    if equal {
+        Ok(())
+    } else {
+        Err(eg!(format!(
+            "{} differs from the reference implementation for {:?}",
+            operation, inputs
+        )))
+    }
+}
+
+/// Return the edge cases, followed by `rounds` random scalars.
+fn sample_scalars<S: Scalar, R: CryptoRng + RngCore>(prng: &mut R, rounds: usize) -> Vec<S> {
+    let mut samples = vec![
+        S::zero(),
+        S::one(),
+        S::one().neg(),
+        S::from(2u32),
+        S::from(u64::MAX),
+    ];
+    samples.extend((0..rounds).map(|_| S::random(prng)));
+    samples
+}
+
+/// Return the pairs of an edge case and any sample, and pairs of random samples.
+fn scalar_pairs<S: Scalar>(samples: &[S]) -> Vec<(S, S)> {
+    let mut pairs = samples[..NUM_EDGE_SCALARS]
+        .iter()
+        .cartesian_product(samples)
+        .map(|(a, b)| (*a, *b))
+        .collect_vec();
+    pairs.extend(
+        samples
+            .iter()
+            .zip(samples.iter().rev())
+            .map(|(a, b)| (*a, *b)),
+    );
+    pairs
+}
+
+fn to_reference_scalar(scalar: &BLSScalar) -> Result<reference::Scalar> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&scalar.to_bytes());
+    Option::<reference::Scalar>::from(reference::Scalar::from_bytes(&bytes))
+        .c(d!(format!("Non-canonical BLS12-381 scalar {:?}", scalar)))
+}
+
+fn to_reference_jubjub_scalar(scalar: &JubjubScalar) -> Result<reference_jubjub::Fr> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&scalar.to_bytes());
+    Option::<reference_jubjub::Fr>::from(reference_jubjub::Fr::from_bytes(&bytes))
+        .c(d!(format!("Non-canonical Jubjub scalar {:?}", scalar)))
+}
+
+/// Encode a point as its `v` coordinate, with the sign of its `u` coordinate in the top bit.
+fn to_reference_point(point: &JubjubPoint) -> Result<reference_jubjub::ExtendedPoint> {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&point.get_y().to_bytes());
+    bytes[31] |= (point.get_x().to_bytes()[0] & 1) << 7;
+    let affine: Option<reference_jubjub::AffinePoint> =
+        reference_jubjub::AffinePoint::from_bytes(bytes).into();
+    affine
+        .map(reference_jubjub::ExtendedPoint::from)
+        .c(d!(format!("Jubjub point {:?} is not on the curve", point)))
+}
+
+/// Return the uncompressed encoding of a G1 point used by the `bls12_381` crate.
+fn g1_bytes(point: &BLSG1) -> Vec<u8> {
+    let affine = point.0.into_affine();
+    if affine.infinity {
+        return infinity_bytes(96);
+    }
+    let mut bytes = affine.x.into_repr().to_bytes_be();
+    bytes.extend(affine.y.into_repr().to_bytes_be());
+    bytes
+}
+
+/// Return the uncompressed encoding of a G2 point used by the `bls12_381` crate.
+fn g2_bytes(point: &BLSG2) -> Vec<u8> {
+    let affine = point.0.into_affine();
+    if affine.infinity {
+        return infinity_bytes(192);
+    }
+    [affine.x.c1, affine.x.c0, affine.y.c1, affine.y.c0]
+        .iter()
+        .flat_map(|coordinate| coordinate.into_repr().to_bytes_be())
+        .collect()
+}
+
+fn infinity_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    bytes[0] = INFINITY_FLAG;
+    bytes
+}
+
+fn g1_reference_bytes(point: &reference::G1Projective) -> Vec<u8> {
+    reference::G1Affine::from(point).to_uncompressed().to_vec()
+}
+
+fn g2_reference_bytes(point: &reference::G2Projective) -> Vec<u8> {
+    reference::G2Affine::from(point).to_uncompressed().to_vec()
+}
+
+/// Return the coordinates of a Jubjub point, as little-endian bytes.
+fn point_bytes(point: &JubjubPoint) -> Vec<u8> {
+    let mut bytes = point.get_x().to_bytes();
+    bytes.extend(point.get_y().to_bytes());
+    bytes
+}
+
+fn point_reference_bytes(point: &reference_jubjub::ExtendedPoint) -> Vec<u8> {
+    let affine = reference_jubjub::AffinePoint::from(point);
+    let mut bytes = affine.get_u().to_bytes().to_vec();
+    bytes.extend(affine.get_v().to_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::differential::check_all;
+    use ark_std::test_rng;
+
+    #[test]
+    fn differential_checks() {
+        let mut prng = test_rng();
+        check_all(&mut prng, 8).unwrap();
+    }
+}
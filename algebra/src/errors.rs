@@ -1,4 +1,4 @@
-use ark_std::{error, fmt};
+use ark_std::{boxed::Box, error, fmt};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 #[allow(missing_docs)]
@@ -97,6 +97,9 @@ pub enum ZeiError {
     SolvencyInputError,
     SolvencyProveError,
     SolvencyVerificationError,
+    AuditReportProveError,
+    AuditReportVerificationError,
+    UnsupportedPairingSuite,
     ZKProofVerificationError,
     ZKProofBatchVerificationError,
     GroupSignatureTraceError,
@@ -110,17 +113,139 @@ pub enum ZeiError {
     MissingSRSError,
     MissingVerifierParamsError,
     AbarToBarParamsError,
+    RandomnessReuseError,
+    PrecomputedParamsDigestError,
+    ParamsIdMismatchError,
+    SecretSharingError,
+    DkgError,
+    TimelockSolutionError,
+    NonCanonicalEncodingError,
+    SigningSessionError,
+    SigningSessionExpired,
+    /// The length of an object being deserialized exceeds its limit.
+    DeserializationLimitError {
+        /// The name of the limit.
+        limit: &'static str,
+        /// The maximal length allowed.
+        max: usize,
+        /// The actual length.
+        actual: usize,
+    },
+    /// A list does not have the expected number of elements.
+    LengthMismatch {
+        /// The expected number of elements.
+        expected: usize,
+        /// The actual number of elements.
+        actual: usize,
+    },
+    /// The input with this index of a transfer is invalid.
+    InputError {
+        /// The index of the input.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// The output with this index of a transfer is invalid.
+    OutputError {
+        /// The index of the output.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// The signature of the signer with this index is invalid.
+    SignerError {
+        /// The index of the signer, in the order of the signatures.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// The note with this index of a batch is invalid.
+    NoteError {
+        /// The index of the note in the batch.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// The authorization of the multisig account with this index of a note is invalid.
+    AccountError {
+        /// The index of the account authorization in the note.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// The anonymous transfer note with this index of a batch is invalid.
+    AnonNoteError {
+        /// The index of the note in the batch.
+        index: usize,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+    /// A sub-proof of a transfer is invalid.
+    SubProofError {
+        /// The name of the sub-proof.
+        proof: &'static str,
+        /// The cause of the error.
+        source: Box<ZeiError>,
+    },
+}
+
+impl ZeiError {
+    /// Return the innermost cause of the error, following the contextual variants.
+    pub fn root_cause(&self) -> &ZeiError {
+        match self.inner() {
+            Some(source) => source.root_cause(),
+            None => self,
+        }
+    }
+
+    fn inner(&self) -> Option<&ZeiError> {
+        use ZeiError::*;
+        match self {
+            InputError { source, .. }
+            | OutputError { source, .. }
+            | SignerError { source, .. }
+            | NoteError { source, .. }
+            | AccountError { source, .. }
+            | AnonNoteError { source, .. }
+            | SubProofError { source, .. } => Some(source),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for ZeiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         use ZeiError::*;
         f.write_str(match self {
+            LengthMismatch { expected, actual } => {
+                return write!(f, "Expected {} elements, but got {}", expected, actual)
+            }
+            DeserializationLimitError { limit, max, actual } => {
+                return write!(f, "The {} exceed the limit of {}: got {}", limit, max, actual)
+            }
+            InputError { index, source } => return write!(f, "Input {}: {}", index, source),
+            OutputError { index, source } => return write!(f, "Output {}: {}", index, source),
+            SignerError { index, source } => return write!(f, "Signer {}: {}", index, source),
+            NoteError { index, source } => return write!(f, "Note {}: {}", index, source),
+            AccountError { index, source } => return write!(f, "Account {}: {}", index, source),
+            AnonNoteError { index, source } => {
+                return write!(f, "Anonymous note {}: {}", index, source)
+            }
+            SubProofError { proof, source } => return write!(f, "{}: {}", proof, source),
             AXfrProverParamsError => "Could not preprocess anonymous transfer prover",
             AXfrVerifierParamsError => "Could not preprocess anonymous transfer verifier",
             AXfrVerificationError => "Invalid AXfrBody for merkle root",
             AXfrProofError => "Could not create anonymous transfer proof",
             AbarToBarParamsError => "Could not preprocess Abr2Bar conversion prover",
+            RandomnessReuseError => "The randomness of a prover session was reused",
+            PrecomputedParamsDigestError => "The digest of the precomputed parameters does not match",
+            ParamsIdMismatchError => "The proof was generated with different parameters",
+            SecretSharingError => "Could not recover the secret from the shares",
+            DkgError => "The message is not valid in the state of the key generation",
+            TimelockSolutionError => "The solution of the time-lock puzzle is not valid",
+            NonCanonicalEncodingError => "The object is not in canonical form",
+            SigningSessionError => "The message is not valid in the state of the signing session",
+            SigningSessionExpired => "The signing session has expired",
             AnonFeeProofError => "Could not create anonymous transfer proof",
             ArgumentVerificationError => "Proof not valid for statement",
             CommitmentInputError => "The number of messages to be committed is invalid",
@@ -154,6 +279,9 @@ impl fmt::Display for ZeiError {
             SolvencyVerificationError => "Invalid proof for solvency",
             SolvencyProveError => "Cannot build proof of solvency",
             SolvencyInputError => "Invalid input for solvency",
+            AuditReportProveError => "Cannot build proof for audit report",
+            AuditReportVerificationError => "Invalid proof for audit report",
+            UnsupportedPairingSuite => "Unknown or unsupported pairing suite",
             ZKProofVerificationError => "Invalid proof",
             ZKProofBatchVerificationError => "Batch proof instance contains an error",
             GroupSignatureTraceError => "Trace test did not match",
@@ -170,4 +298,36 @@ impl fmt::Display for ZeiError {
     }
 }
 
-impl error::Error for ZeiError {}
+impl error::Error for ZeiError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.inner()
+            .map(|source| source as &(dyn error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::errors::ZeiError;
+    use ark_std::{boxed::Box, error::Error, string::ToString};
+
+    #[test]
+    fn contextual_errors() {
+        let err = ZeiError::NoteError {
+            index: 2,
+            source: Box::new(ZeiError::SignerError {
+                index: 1,
+                source: Box::new(ZeiError::SignatureError),
+            }),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Note 2: Signer 1: Signature verification failed"
+        );
+        assert_eq!(err.root_cause(), &ZeiError::SignatureError);
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            "Signer 1: Signature verification failed"
+        );
+        assert!(ZeiError::SignatureError.source().is_none());
+    }
+}
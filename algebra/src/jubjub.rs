@@ -4,6 +4,7 @@ use crate::{
     errors::AlgebraError,
     hash::{Hash, Hasher},
     prelude::*,
+    traits::{impl_group_by_value_ops, impl_scalar_by_value_ops},
 };
 use ark_ec::{AffineCurve, ProjectiveCurve};
 use ark_ed_on_bls12_381::{EdwardsAffine as AffinePoint, EdwardsProjective, Fr};
@@ -167,6 +168,8 @@ impl Neg for JubjubScalar {
     }
 }
 
+impl_scalar_by_value_ops!(JubjubScalar);
+
 impl From<u32> for JubjubScalar {
     #[inline]
     fn from(value: u32) -> Self {
@@ -206,8 +209,19 @@ impl Scalar for JubjubScalar {
     where
         D: Digest<OutputSize = U64> + Default,
     {
-        let mut prng = derive_prng_from_hash::<D>(hash);
-        Self::random(&mut prng)
+        // Reducing the 512-bit digest modulo the group order yields a scalar whose
+        // distribution is statistically close to uniform.
+        Self(Fr::from_le_bytes_mod_order(&hash.finalize()))
+    }
+
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(Fr::from(value))
+    }
+
+    #[inline]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(Fr::from_le_bytes_mod_order(bytes))
     }
 
     #[inline]
@@ -426,6 +440,8 @@ impl Neg for JubjubPoint {
     }
 }
 
+impl_group_by_value_ops!(JubjubPoint, JubjubScalar);
+
 impl JubjubPoint {
     /// Get the x-coordinate of the Jubjub affine point.
     #[inline]
@@ -446,7 +462,9 @@ mod jubjub_groups_test {
     use crate::{
         jubjub::{JubjubPoint, JubjubScalar},
         prelude::*,
-        traits::group_tests::{test_scalar_operations, test_scalar_serialization},
+        traits::group_tests::{
+            test_scalar_from_hash, test_scalar_operations, test_scalar_serialization,
+        },
     };
     use rand_chacha::ChaCha20Rng;
 
@@ -460,6 +478,13 @@ mod jubjub_groups_test {
         test_scalar_serialization::<JubjubScalar>();
     }
 
+    #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<JubjubScalar>(
+            "449490f3a0ee9e6eaaa9698530c8518ace0034793c578e2ef7ec831761b1c601",
+        );
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = JubjubScalar::from(165747u32);
@@ -25,6 +25,9 @@
 /// Module for the BLS12-381 curve
 pub mod bls12_381;
 
+/// Module for the BN254 curve
+pub mod bn254;
+
 /// Module for the secq256k1 curve
 pub mod secq256k1;
 
@@ -43,6 +46,10 @@ pub mod ristretto;
 /// Module for the Jubjub curve
 pub mod jubjub;
 
+/// Module for differential testing of the arithmetic against a second implementation
+#[cfg(feature = "differential")]
+pub mod differential;
+
 /// Module for serialization of scalars and group elements
 pub mod serialization;
 
@@ -59,6 +66,30 @@ pub use ark_std::{
     UniformRand, Zero,
 };
 
+#[cfg(feature = "tracing")]
+#[doc(hidden)]
+pub use tracing;
+
+/// Enter a span named after a proving or verification stage, e.g., "fft" or "msm", until the
+/// end of the enclosing block, so that integrators can profile where the time goes.
+/// The spans are only recorded with the `tracing` feature, and cost nothing without it.
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_stage {
+    ($name: literal $(,)?) => {
+        let _span = $crate::tracing::info_span!($name).entered();
+    };
+}
+
+/// Enter a span named after a proving or verification stage, e.g., "fft" or "msm", until the
+/// end of the enclosing block, so that integrators can profile where the time goes.
+/// The spans are only recorded with the `tracing` feature, and cost nothing without it.
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_stage {
+    ($name: literal $(,)?) => {};
+}
+
 /// check if the error messages equal
 #[macro_export]
 macro_rules! msg_eq {
@@ -1,6 +1,6 @@
 pub use crate::borrow::Borrow;
 pub use crate::errors::ZeiError;
-pub use crate::iter::Sum;
+pub use crate::iter::{Product, Sum};
 pub use crate::ops::*;
 pub use crate::rand::{CryptoRng, Rng, RngCore, SeedableRng};
 pub use crate::serialization::*;
@@ -1,5 +1,9 @@
 use crate::fmt::{Debug, Formatter};
-use crate::{errors::AlgebraError, prelude::*};
+use crate::{
+    errors::AlgebraError,
+    prelude::*,
+    traits::{impl_group_by_value_ops, impl_scalar_by_value_ops},
+};
 use byteorder::ByteOrder;
 use curve25519_dalek::{
     constants::{ED25519_BASEPOINT_POINT, RISTRETTO_BASEPOINT_POINT},
@@ -151,6 +155,8 @@ impl Neg for RistrettoScalar {
     }
 }
 
+impl_scalar_by_value_ops!(RistrettoScalar);
+
 impl From<u32> for RistrettoScalar {
     #[inline]
     fn from(value: u32) -> Self {
@@ -177,8 +183,9 @@ impl<'a> From<&'a BigUint> for RistrettoScalar {
     fn from(x: &BigUint) -> Self {
         let biguint = x % RistrettoScalar::get_field_size_biguint();
 
+        let le_bytes = biguint.to_bytes_le();
         let mut bytes = [0u8; 32];
-        bytes.copy_from_slice(&biguint.to_bytes_le()[0..32]);
+        bytes[..le_bytes.len()].copy_from_slice(&le_bytes);
 
         Self(curve25519_dalek::scalar::Scalar::from_bytes_mod_order(
             bytes,
@@ -200,6 +207,11 @@ impl Scalar for RistrettoScalar {
         Self(curve25519_dalek::scalar::Scalar::from_hash(hash))
     }
 
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(curve25519_dalek::scalar::Scalar::from(value))
+    }
+
     #[inline]
     fn capacity() -> usize {
         252
@@ -257,6 +269,15 @@ impl Scalar for RistrettoScalar {
         Ok(Self(curve25519_dalek::scalar::Scalar::from_bits(array)))
     }
 
+    #[inline]
+    fn from_bytes_canonical(bytes: &[u8]) -> Result<Self> {
+        let array = <[u8; RISTRETTO_SCALAR_LEN]>::try_from(bytes)
+            .map_err(|_| eg!(AlgebraError::DeserializationError))?;
+        curve25519_dalek::scalar::Scalar::from_canonical_bytes(array)
+            .map(Self)
+            .ok_or_else(|| eg!(AlgebraError::DeserializationError))
+    }
+
     #[inline]
     fn inv(&self) -> Result<Self> {
         Ok(Self(self.0.invert()))
@@ -279,6 +300,12 @@ impl RistrettoScalar {
         let r_mul_edwards_base = CompressedEdwardsY::scalar_mul_basepoint(&r);
         (r, r_mul_edwards_base)
     }
+
+    /// Check if the scalar is canonical, i.e., reduced modulo the group order.
+    #[inline]
+    pub fn is_canonical(&self) -> bool {
+        curve25519_dalek::scalar::Scalar::from_canonical_bytes(self.0.to_bytes()).is_some()
+    }
 }
 
 impl RistrettoPoint {
@@ -287,6 +314,16 @@ impl RistrettoPoint {
     pub fn compress(&self) -> CompressedRistretto {
         CompressedRistretto(self.0.compress())
     }
+
+    /// Compress the doubles of the points in a batch, which takes a single field inversion
+    /// for the batch instead of an inverse square root for each point.
+    /// The compression of a point `P` is batched by computing `P/2` in its place.
+    pub fn double_and_compress_batch(points: &[Self]) -> Vec<CompressedRistretto> {
+        RPoint::double_and_compress_batch(points.iter().map(|p| &p.0))
+            .into_iter()
+            .map(CompressedRistretto)
+            .collect()
+    }
 }
 
 impl CompressedRistretto {
@@ -322,6 +359,15 @@ impl CompressedEdwardsY {
         self.0.decompress()
     }
 
+    /// Check if the bytes are the canonical encoding of a point, since the same point
+    /// can have several encodings.
+    #[inline]
+    pub fn is_canonical(&self) -> bool {
+        self.decompress()
+            .map(|point| point.compress() == self.0)
+            .unwrap_or(false)
+    }
+
     /// Return compressed edwards point of (`ED25519_BASEPOINT_POINT` ^ s)
     #[inline]
     pub fn scalar_mul_basepoint(s: &RistrettoScalar) -> Self {
@@ -337,6 +383,8 @@ impl Neg for RistrettoPoint {
     }
 }
 
+impl_group_by_value_ops!(RistrettoPoint, RistrettoScalar);
+
 impl Group for RistrettoPoint {
     type ScalarType = RistrettoScalar;
     const COMPRESSED_LEN: usize = 32;
@@ -377,6 +425,9 @@ impl Group for RistrettoPoint {
 
     #[inline]
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::COMPRESSED_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
         Ok(Self(
             CR::from_slice(bytes)
                 .decompress()
@@ -386,6 +437,9 @@ impl Group for RistrettoPoint {
 
     #[inline]
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::COMPRESSED_LEN {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
         Ok(Self(
             CR::from_slice(bytes)
                 .decompress()
@@ -460,7 +514,9 @@ impl<'a> SubAssign<&'a RistrettoPoint> for RistrettoPoint {
 
 #[cfg(test)]
 mod ristretto_group_test {
-    use crate::traits::group_tests::{test_scalar_operations, test_scalar_serialization};
+    use crate::traits::group_tests::{
+        test_scalar_from_hash, test_scalar_operations, test_scalar_serialization,
+    };
 
     #[test]
     fn scalar_ops() {
@@ -471,7 +527,30 @@ mod ristretto_group_test {
         test_scalar_serialization::<super::RistrettoScalar>();
     }
     #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<super::RistrettoScalar>(
+            "e5348e4974e7095287c8b6a6dbf7dc215b8a426c26973f19904ddf1ebdefa009",
+        );
+    }
+    #[test]
     fn scalar_to_radix() {
         crate::traits::group_tests::test_to_radix::<super::RistrettoScalar>();
     }
+
+    #[test]
+    fn double_and_compress_batch() {
+        use super::RistrettoPoint;
+        use crate::traits::Group;
+
+        let mut prng = ark_std::test_rng();
+        let mut points: Vec<RistrettoPoint> =
+            (0..17).map(|_| RistrettoPoint::random(&mut prng)).collect();
+        points.push(RistrettoPoint::get_identity());
+        let compressed = RistrettoPoint::double_and_compress_batch(&points);
+        assert_eq!(compressed.len(), points.len());
+        for (p, c) in points.iter().zip(compressed.iter()) {
+            assert_eq!(p.double().compress(), *c);
+        }
+        assert!(RistrettoPoint::double_and_compress_batch(&[]).is_empty());
+    }
 }
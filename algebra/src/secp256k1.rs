@@ -1,6 +1,7 @@
 use crate::errors::AlgebraError;
 use crate::prelude::*;
 use crate::secq256k1::SECQ256K1Scalar;
+use crate::traits::{impl_group_by_value_ops, impl_scalar_by_value_ops};
 use ark_bulletproofs_secq256k1::curve::secp256k1::{Fr, FrParameters, G1Affine, G1Projective};
 use ark_ec::short_weierstrass_jacobian::GroupProjective;
 use ark_ec::{AffineCurve, ProjectiveCurve};
@@ -58,13 +59,9 @@ impl FromStr for SECP256K1Scalar {
     type Err = AlgebraError;
 
     fn from_str(string: &str) -> StdResult<Self, AlgebraError> {
-        let res = Fr::from_str(string);
-
-        if res.is_ok() {
-            Ok(Self(res.unwrap()))
-        } else {
-            Err(AlgebraError::DeserializationError)
-        }
+        Fr::from_str(string)
+            .map(Self)
+            .map_err(|_| AlgebraError::DeserializationError)
     }
 }
 
@@ -176,6 +173,8 @@ impl Neg for SECP256K1Scalar {
     }
 }
 
+impl_scalar_by_value_ops!(SECP256K1Scalar);
+
 impl From<u32> for SECP256K1Scalar {
     #[inline]
     fn from(value: u32) -> Self {
@@ -216,8 +215,19 @@ impl Scalar for SECP256K1Scalar {
     where
         D: Digest<OutputSize = U64> + Default,
     {
-        let mut prng = derive_prng_from_hash::<D>(hash);
-        Self::random(&mut prng)
+        // Reducing the 512-bit digest modulo the group order yields a scalar whose
+        // distribution is statistically close to uniform.
+        Self(Fr::from_le_bytes_mod_order(&hash.finalize()))
+    }
+
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(Fr::from(value))
+    }
+
+    #[inline]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(Fr::from_le_bytes_mod_order(bytes))
     }
 
     #[inline]
@@ -396,26 +406,18 @@ impl Group for SECP256K1G1 {
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G1Affine::deserialize(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G1Affine::deserialize(&mut reader)
+            .map(|affine| Self(G1Projective::from(affine)))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G1Affine::deserialize_unchecked(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G1Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(G1Projective::from(affine)))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
@@ -456,6 +458,8 @@ impl Neg for SECP256K1G1 {
     }
 }
 
+impl_group_by_value_ops!(SECP256K1G1, SECP256K1Scalar);
+
 impl<'a> Add<&'a SECP256K1G1> for SECP256K1G1 {
     type Output = SECP256K1G1;
 
@@ -509,7 +513,9 @@ mod secp256k1_groups_test {
     use crate::{
         prelude::*,
         secp256k1::{SECP256K1Scalar, SECP256K1G1},
-        traits::group_tests::{test_scalar_operations, test_scalar_serialization},
+        traits::group_tests::{
+            test_scalar_from_hash, test_scalar_operations, test_scalar_serialization,
+        },
     };
     use ark_bulletproofs_secq256k1::curve::secp256k1::G1Affine;
     use ark_ec::ProjectiveCurve;
@@ -525,6 +531,13 @@ mod secp256k1_groups_test {
         test_scalar_serialization::<SECP256K1Scalar>();
     }
 
+    #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<SECP256K1Scalar>(
+            "15ef51b04e8daa46050bc7459094ef33c7e8dc0397c183188fa3edec789a3676",
+        );
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = SECP256K1Scalar::from(165747u32);
@@ -577,4 +590,32 @@ mod secp256k1_groups_test {
         let g1_recovered = SECP256K1G1::from_compressed_bytes(&g1_bytes).unwrap();
         assert_eq!(g1, g1_recovered);
     }
+
+    #[test]
+    fn schnorr_identification_protocol() {
+        let mut prng = test_rng();
+
+        // Private key
+        let alpha = SECP256K1Scalar::random(&mut prng);
+
+        // Public key
+        let base = SECP256K1G1::get_base();
+        let u = base.mul(&alpha);
+
+        // Verifier challenge
+        let c = SECP256K1Scalar::random(&mut prng);
+
+        // Prover commitment
+        let alpha_t = SECP256K1Scalar::random(&mut prng);
+        let u_t = base.mul(&alpha_t);
+
+        // Prover response
+        let alpha_z = alpha_t.add(&c.mul(&alpha));
+
+        // Proof verification
+        let left = base.mul(&alpha_z);
+        let right = u_t.add(&u.mul(&c));
+
+        assert_eq!(left, right);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::errors::AlgebraError;
 use crate::prelude::*;
+use crate::traits::{impl_group_by_value_ops, impl_scalar_by_value_ops};
 use ark_bulletproofs_secq256k1::curve::secq256k1::{Fr, FrParameters, G1Affine, G1Projective};
 use ark_ec::{AffineCurve, ProjectiveCurve};
 use ark_ff::{BigInteger, BigInteger320, FftField, FftParameters, Field, FpParameters, PrimeField};
@@ -49,13 +50,9 @@ impl FromStr for SECQ256K1Scalar {
     type Err = AlgebraError;
 
     fn from_str(string: &str) -> StdResult<Self, AlgebraError> {
-        let res = Fr::from_str(string);
-
-        if res.is_ok() {
-            Ok(Self(res.unwrap()))
-        } else {
-            Err(AlgebraError::DeserializationError)
-        }
+        Fr::from_str(string)
+            .map(Self)
+            .map_err(|_| AlgebraError::DeserializationError)
     }
 }
 
@@ -167,6 +164,8 @@ impl Neg for SECQ256K1Scalar {
     }
 }
 
+impl_scalar_by_value_ops!(SECQ256K1Scalar);
+
 impl From<u32> for SECQ256K1Scalar {
     #[inline]
     fn from(value: u32) -> Self {
@@ -192,8 +191,19 @@ impl Scalar for SECQ256K1Scalar {
     where
         D: Digest<OutputSize = U64> + Default,
     {
-        let mut prng = derive_prng_from_hash::<D>(hash);
-        Self::random(&mut prng)
+        // Reducing the 512-bit digest modulo the group order yields a scalar whose
+        // distribution is statistically close to uniform.
+        Self(Fr::from_le_bytes_mod_order(&hash.finalize()))
+    }
+
+    #[inline]
+    fn from_u128(value: u128) -> Self {
+        Self(Fr::from(value))
+    }
+
+    #[inline]
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self(Fr::from_le_bytes_mod_order(bytes))
     }
 
     #[inline]
@@ -299,6 +309,8 @@ impl Neg for SECQ256K1G1 {
     }
 }
 
+impl_group_by_value_ops!(SECQ256K1G1, SECQ256K1Scalar);
+
 impl Group for SECQ256K1G1 {
     type ScalarType = SECQ256K1Scalar;
     const COMPRESSED_LEN: usize = 33;
@@ -345,26 +357,18 @@ impl Group for SECQ256K1G1 {
     fn from_compressed_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G1Affine::deserialize(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G1Affine::deserialize(&mut reader)
+            .map(|affine| Self(G1Projective::from(affine)))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
     fn from_unchecked_bytes(bytes: &[u8]) -> Result<Self> {
         let mut reader = ark_std::io::BufReader::new(bytes);
 
-        let affine = G1Affine::deserialize_unchecked(&mut reader);
-
-        if affine.is_ok() {
-            Ok(Self(G1Projective::from(affine.unwrap()))) // safe unwrap
-        } else {
-            Err(eg!(AlgebraError::DeserializationError))
-        }
+        G1Affine::deserialize_unchecked(&mut reader)
+            .map(|affine| Self(G1Projective::from(affine)))
+            .map_err(|_| eg!(AlgebraError::DeserializationError))
     }
 
     #[inline]
@@ -476,7 +480,9 @@ mod secq256k1_groups_test {
     use crate::{
         prelude::*,
         secq256k1::{SECQ256K1Scalar, SECQ256K1G1},
-        traits::group_tests::{test_scalar_operations, test_scalar_serialization},
+        traits::group_tests::{
+            test_scalar_from_hash, test_scalar_operations, test_scalar_serialization,
+        },
     };
     use ark_bulletproofs_secq256k1::curve::secq256k1::G1Affine;
     use ark_ec::ProjectiveCurve;
@@ -492,6 +498,13 @@ mod secq256k1_groups_test {
         test_scalar_serialization::<SECQ256K1Scalar>();
     }
 
+    #[test]
+    fn scalar_from_hash() {
+        test_scalar_from_hash::<SECQ256K1Scalar>(
+            "dc9f4a468d84d12474158d7f68ac4a63420d207400ab7319e37e84a313de58e9",
+        );
+    }
+
     #[test]
     fn scalar_from_to_bytes() {
         let small_value = SECQ256K1Scalar::from(165747u32);
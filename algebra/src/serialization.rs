@@ -1,7 +1,8 @@
 use crate::secp256k1::{SECP256K1Scalar, SECP256K1G1};
 use crate::secq256k1::SECQ256K1G1;
 use crate::{
-    bls12_381::{BLSGt, BLSScalar, BLSG1, BLSG2},
+    bls12_381::{BLSGt, BLSScalar, PointValidation, BLSG1, BLSG2},
+    bn254::{BN254Gt, BN254Scalar, BN254G1, BN254G2},
     jubjub::{JubjubPoint, JubjubScalar},
     prelude::*,
     ristretto::{CompressedEdwardsY, CompressedRistretto, RistrettoPoint, RistrettoScalar},
@@ -10,6 +11,7 @@ use crate::{
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::io::Cursor;
 use bulletproofs::RangeProof;
+use num_bigint::BigUint;
 use serde::Serializer;
 
 macro_rules! to_from_bytes_scalar {
@@ -21,15 +23,44 @@ macro_rules! to_from_bytes_scalar {
                 v
             }
             fn zei_from_bytes(bytes: &[u8]) -> Result<$t> {
-                $t::from_bytes(bytes)
+                $t::from_bytes_canonical(bytes)
                     .map_err(|_| eg!(crate::errors::ZeiError::DeserializationError))
             }
         }
+
+        impl serde::Serialize for $t {
+            fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let bytes = scalar_to_serde_bytes(self);
+                if serializer.is_human_readable() {
+                    serializer.serialize_str(&b64enc(&bytes))
+                } else {
+                    serializer.serialize_bytes(&bytes)
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let bytes = if deserializer.is_human_readable() {
+                    deserializer.deserialize_str(zei_obj_serde::BytesVisitor)?
+                } else {
+                    deserializer.deserialize_bytes(zei_obj_serde::BytesVisitor)?
+                };
+                scalar_from_serde_bytes(bytes.as_slice()).map_err(serde::de::Error::custom)
+            }
+        }
     };
 }
 
 to_from_bytes_scalar!(RistrettoScalar);
 to_from_bytes_scalar!(BLSScalar);
+to_from_bytes_scalar!(BN254Scalar);
 to_from_bytes_scalar!(JubjubScalar);
 to_from_bytes_scalar!(SECQ256K1Scalar);
 to_from_bytes_scalar!(SECP256K1Scalar);
@@ -41,6 +72,9 @@ impl ZeiFromToBytes for CompressedRistretto {
     }
     #[inline]
     fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 32 {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
         Ok(Self(
             curve25519_dalek::ristretto::CompressedRistretto::from_slice(bytes),
         ))
@@ -54,6 +88,9 @@ impl ZeiFromToBytes for CompressedEdwardsY {
     }
     #[inline]
     fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 32 {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
         Ok(CompressedEdwardsY(
             curve25519_dalek::edwards::CompressedEdwardsY::from_slice(bytes),
         ))
@@ -62,11 +99,6 @@ impl ZeiFromToBytes for CompressedEdwardsY {
 
 serialize_deserialize!(CompressedRistretto);
 serialize_deserialize!(CompressedEdwardsY);
-serialize_deserialize!(RistrettoScalar);
-serialize_deserialize!(BLSScalar);
-serialize_deserialize!(JubjubScalar);
-serialize_deserialize!(SECQ256K1Scalar);
-serialize_deserialize!(SECP256K1Scalar);
 
 macro_rules! to_from_bytes_group {
     ($g:ident) => {
@@ -80,12 +112,27 @@ macro_rules! to_from_bytes_group {
             }
         }
     };
+    ($g:ident, $validation:expr) => {
+        impl ZeiFromToBytes for $g {
+            fn zei_to_bytes(&self) -> Vec<u8> {
+                self.to_compressed_bytes()
+            }
+            fn zei_from_bytes(bytes: &[u8]) -> Result<$g> {
+                $g::from_compressed_bytes_with_validation(bytes, $validation)
+                    .map_err(|_| eg!(crate::errors::ZeiError::SerializationError))
+            }
+        }
+    };
 }
 
 to_from_bytes_group!(RistrettoPoint);
-to_from_bytes_group!(BLSG1);
-to_from_bytes_group!(BLSG2);
+// The serialized structures may contain the identity, e.g., the commitments of zero polynomials.
+to_from_bytes_group!(BLSG1, PointValidation::Subgroup);
+to_from_bytes_group!(BLSG2, PointValidation::Subgroup);
 to_from_bytes_group!(BLSGt);
+to_from_bytes_group!(BN254G1);
+to_from_bytes_group!(BN254G2);
+to_from_bytes_group!(BN254Gt);
 to_from_bytes_group!(JubjubPoint);
 to_from_bytes_group!(SECQ256K1G1);
 to_from_bytes_group!(SECP256K1G1);
@@ -94,6 +141,9 @@ serialize_deserialize!(RistrettoPoint);
 serialize_deserialize!(BLSG1);
 serialize_deserialize!(BLSG2);
 serialize_deserialize!(BLSGt);
+serialize_deserialize!(BN254G1);
+serialize_deserialize!(BN254G2);
+serialize_deserialize!(BN254Gt);
 serialize_deserialize!(JubjubPoint);
 serialize_deserialize!(SECQ256K1G1);
 serialize_deserialize!(SECP256K1G1);
@@ -171,9 +221,281 @@ impl ZeiFromToBytes for x25519_dalek::StaticSecret {
     }
 }
 
+/// The byte order used by a tagged scalar encoding.
+///
+/// `Scalar::to_bytes` is always little-endian for every scalar field in this crate
+/// (BLS12-381, Jubjub, Ristretto, secp256k1, and secq256k1), but other tools
+/// (e.g., Ethereum contracts) commonly expect big-endian. The tagged encoding makes
+/// the choice explicit so that the reader never has to guess.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScalarByteOrder {
+    /// Least significant byte first, same as `Scalar::to_bytes`.
+    LittleEndian,
+    /// Most significant byte first.
+    BigEndian,
+}
+
+impl ScalarByteOrder {
+    /// Return the tag byte of this byte order.
+    pub fn to_tag(&self) -> u8 {
+        match self {
+            ScalarByteOrder::LittleEndian => SCALAR_TAG_LITTLE_ENDIAN,
+            ScalarByteOrder::BigEndian => SCALAR_TAG_BIG_ENDIAN,
+        }
+    }
+
+    /// Parse the tag byte.
+    pub fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            SCALAR_TAG_LITTLE_ENDIAN => Ok(ScalarByteOrder::LittleEndian),
+            SCALAR_TAG_BIG_ENDIAN => Ok(ScalarByteOrder::BigEndian),
+            _ => Err(eg!(ZeiError::DeserializationError)),
+        }
+    }
+}
+
+/// The tag byte for little-endian scalar encodings.
+pub const SCALAR_TAG_LITTLE_ENDIAN: u8 = 0x4c; // 'L'
+/// The tag byte for big-endian scalar encodings.
+pub const SCALAR_TAG_BIG_ENDIAN: u8 = 0x42; // 'B'
+
+/// Encode a scalar as exactly `S::bytes_len()` bytes in the given byte order.
+pub fn scalar_to_ordered_bytes<S: Scalar>(scalar: &S, order: ScalarByteOrder) -> Vec<u8> {
+    let mut bytes = scalar.to_bytes();
+    bytes.resize(S::bytes_len(), 0u8);
+    if order == ScalarByteOrder::BigEndian {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Decode a scalar from exactly `S::bytes_len()` bytes in the given byte order.
+/// Non-canonical encodings (values not smaller than the field size) are rejected.
+pub fn scalar_from_ordered_bytes<S: Scalar>(bytes: &[u8], order: ScalarByteOrder) -> Result<S> {
+    if bytes.len() != S::bytes_len() {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    let value = match order {
+        ScalarByteOrder::LittleEndian => BigUint::from_bytes_le(bytes),
+        ScalarByteOrder::BigEndian => BigUint::from_bytes_be(bytes),
+    };
+    if value >= S::get_field_size_biguint() {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    Ok(S::from(&value))
+}
+
+/// Encode a scalar as `tag || bytes`, where `tag` identifies the byte order of `bytes`.
+pub fn scalar_to_tagged_bytes<S: Scalar>(scalar: &S, order: ScalarByteOrder) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(1 + S::bytes_len());
+    bytes.push(order.to_tag());
+    bytes.extend_from_slice(&scalar_to_ordered_bytes(scalar, order));
+    bytes
+}
+
+/// Decode a scalar from `tag || bytes`, see [`scalar_to_tagged_bytes`].
+pub fn scalar_from_tagged_bytes<S: Scalar>(bytes: &[u8]) -> Result<S> {
+    if bytes.is_empty() {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    let order = ScalarByteOrder::from_tag(bytes[0]).c(d!())?;
+    scalar_from_ordered_bytes(&bytes[1..], order).c(d!())
+}
+
+/// Encode a scalar for serde.
+///
+/// With the `tagged_scalars` feature, this is the tagged little-endian encoding of
+/// [`scalar_to_tagged_bytes`]; otherwise, it is the untagged `Scalar::to_bytes`.
+pub fn scalar_to_serde_bytes<S: Scalar>(scalar: &S) -> Vec<u8> {
+    if cfg!(feature = "tagged_scalars") {
+        scalar_to_tagged_bytes(scalar, ScalarByteOrder::LittleEndian)
+    } else {
+        scalar.to_bytes()
+    }
+}
+
+/// Decode a scalar encoded for serde, see [`scalar_to_serde_bytes`].
+///
+/// Both encodings are accepted regardless of the `tagged_scalars` feature, distinguished by
+/// their lengths, so that the data serialized before the tags were introduced remain readable.
+pub fn scalar_from_serde_bytes<S: Scalar>(bytes: &[u8]) -> Result<S> {
+    if bytes.len() == S::bytes_len() + 1 {
+        scalar_from_tagged_bytes(bytes).c(d!(ZeiError::DeserializationError))
+    } else {
+        S::from_bytes_canonical(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+/// Convert an untagged scalar encoding from one byte order to the other.
+pub fn convert_scalar_byte_order<S: Scalar>(
+    bytes: &[u8],
+    from: ScalarByteOrder,
+    to: ScalarByteOrder,
+) -> Result<Vec<u8>> {
+    let scalar: S = scalar_from_ordered_bytes(bytes, from).c(d!())?;
+    Ok(scalar_to_ordered_bytes(&scalar, to))
+}
+
+/// The limits on the sizes of the objects read by the deserializers.
+///
+/// A length prefix of the input is checked against the limits before anything is allocated
+/// for the content, so crafted payloads cannot trigger large allocations. The limits are
+/// global, see [`set_deserialization_limits`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DeserializationLimits {
+    /// The maximal number of inputs, outputs, or memos of a transfer.
+    pub max_outputs: usize,
+    /// The maximal number of attributes of a credential.
+    pub max_attributes: usize,
+    /// The maximal length of a memo in bytes.
+    pub max_memo_bytes: usize,
+    /// The maximal length in bytes of a proof, or of any other object with a byte encoding.
+    pub max_proof_bytes: usize,
+}
+
+impl DeserializationLimits {
+    /// The default limits, far above what honest objects need.
+    pub const DEFAULT: DeserializationLimits = DeserializationLimits {
+        max_outputs: 1024,
+        max_attributes: 256,
+        max_memo_bytes: 1 << 16,
+        max_proof_bytes: 1 << 22,
+    };
+
+    /// Return an error if `actual` exceeds `max`, naming the limit.
+    pub fn check(limit: &'static str, max: usize, actual: usize) -> Result<()> {
+        if actual > max {
+            Err(eg!(ZeiError::DeserializationLimitError {
+                limit,
+                max,
+                actual
+            }))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Default for DeserializationLimits {
+    fn default() -> Self {
+        DeserializationLimits::DEFAULT
+    }
+}
+
+static DESERIALIZATION_LIMITS: std::sync::RwLock<DeserializationLimits> =
+    std::sync::RwLock::new(DeserializationLimits::DEFAULT);
+
+/// Return the limits currently enforced by the deserializers.
+pub fn deserialization_limits() -> DeserializationLimits {
+    match DESERIALIZATION_LIMITS.read() {
+        Ok(limits) => *limits,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+/// Replace the limits enforced by the deserializers of every thread.
+pub fn set_deserialization_limits(limits: DeserializationLimits) {
+    match DESERIALIZATION_LIMITS.write() {
+        Ok(mut current) => *current = limits,
+        Err(poisoned) => *poisoned.into_inner() = limits,
+    }
+}
+
+/// Module for the deserialization of lists under the [`DeserializationLimits`],
+/// to be used with `#[serde(deserialize_with = "zei_bounded_serde::outputs")]`, etc.
+pub mod zei_bounded_serde {
+    use crate::serialization::{deserialization_limits, DeserializationLimits};
+    use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+    use std::marker::PhantomData;
+
+    struct BoundedSeqVisitor<T> {
+        limit: &'static str,
+        max: usize,
+        marker: PhantomData<T>,
+    }
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for BoundedSeqVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(formatter, "a sequence of at most {} elements", self.max)
+        }
+
+        fn visit_seq<V>(self, mut seq: V) -> Result<Vec<T>, V::Error>
+        where
+            V: SeqAccess<'de>,
+        {
+            let hint = seq.size_hint().unwrap_or(0);
+            DeserializationLimits::check(self.limit, self.max, hint)
+                .map_err(serde::de::Error::custom)?;
+            let mut vec = Vec::with_capacity(hint.min(4096));
+            while let Some(x) = seq.next_element()? {
+                DeserializationLimits::check(self.limit, self.max, vec.len() + 1)
+                    .map_err(serde::de::Error::custom)?;
+                vec.push(x);
+            }
+            Ok(vec)
+        }
+    }
+
+    fn bounded<'de, D, T>(
+        deserializer: D,
+        limit: &'static str,
+        max: usize,
+    ) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        deserializer.deserialize_seq(BoundedSeqVisitor {
+            limit,
+            max,
+            marker: PhantomData,
+        })
+    }
+
+    /// Deserialize the inputs, outputs, or memos of a transfer.
+    pub fn outputs<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        bounded(
+            deserializer,
+            "outputs",
+            deserialization_limits().max_outputs,
+        )
+    }
+
+    /// Deserialize the attributes of a credential, or anything with one element per attribute.
+    pub fn attributes<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        bounded(
+            deserializer,
+            "attributes",
+            deserialization_limits().max_attributes,
+        )
+    }
+
+    /// Deserialize the bytes of a memo.
+    pub fn memo_bytes<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        bounded(
+            deserializer,
+            "memo bytes",
+            deserialization_limits().max_memo_bytes,
+        )
+    }
+}
+
 /// Module for serialization for Zei objects
 pub mod zei_obj_serde {
-    use crate::serialization::ZeiFromToBytes;
+    use crate::serialization::{deserialization_limits, DeserializationLimits, ZeiFromToBytes};
     use crate::utils::{b64dec, b64enc};
     use serde::de::SeqAccess;
     use serde::de::Visitor;
@@ -194,27 +516,50 @@ pub mod zei_obj_serde {
         where
             V: SeqAccess<'de>,
         {
-            let mut vec: Vec<u8> = vec![];
+            // The size hint comes from the input, so it is capped to not allocate arbitrarily.
+            let max = deserialization_limits().max_proof_bytes;
+            let hint = seq.size_hint().unwrap_or(0);
+            check_proof_bytes(max, hint)?;
+            let mut vec: Vec<u8> = Vec::with_capacity(hint.min(4096));
             while let Some(x) = seq.next_element().map_err(serde::de::Error::custom)? {
+                check_proof_bytes(max, vec.len() + 1)?;
                 vec.push(x);
             }
             Ok(vec)
         }
 
-        fn visit_bytes<E>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Vec<u8>, E>
+        where
+            E: serde::de::Error,
+        {
+            check_proof_bytes(deserialization_limits().max_proof_bytes, v.len())?;
             let mut vec: Vec<u8> = vec![];
             vec.extend_from_slice(v);
             Ok(vec)
         }
 
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E>
+        where
+            E: serde::de::Error,
+        {
+            check_proof_bytes(deserialization_limits().max_proof_bytes, v.len())?;
+            Ok(v)
+        }
+
         fn visit_str<E>(self, v: &str) -> Result<Vec<u8>, E>
         where
             E: serde::de::Error,
         {
+            // the base64 encoding is longer than the bytes, so this is checked before decoding
+            check_proof_bytes(deserialization_limits().max_proof_bytes, v.len() / 4 * 3)?;
             b64dec(v).map_err(serde::de::Error::custom)
         }
     }
 
+    fn check_proof_bytes<E: serde::de::Error>(max: usize, len: usize) -> Result<(), E> {
+        DeserializationLimits::check("proof bytes", max, len).map_err(serde::de::Error::custom)
+    }
+
     /// Serialize the data
     pub fn serialize<S, T>(obj: &T, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -244,3 +589,227 @@ pub mod zei_obj_serde {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bls12_381::{BLSGt, BLSScalar, BLSG1, BLSG2},
+        jubjub::{JubjubPoint, JubjubScalar},
+        prelude::*,
+        ristretto::{CompressedEdwardsY, CompressedRistretto, RistrettoPoint, RistrettoScalar},
+        secp256k1::{SECP256K1Scalar, SECP256K1G1},
+        secq256k1::{SECQ256K1Scalar, SECQ256K1G1},
+    };
+    use ark_std::test_rng;
+    use num_bigint::BigUint;
+    use serde::{Deserialize, Serialize};
+
+    fn check_tagged_encoding<S: Scalar>() {
+        let mut prng = test_rng();
+        let orders = [ScalarByteOrder::LittleEndian, ScalarByteOrder::BigEndian];
+
+        let mut samples = vec![S::zero(), S::one(), S::one().neg()];
+        for _ in 0..16 {
+            samples.push(S::random(&mut prng));
+        }
+
+        for s in samples.iter() {
+            let le = scalar_to_ordered_bytes(s, ScalarByteOrder::LittleEndian);
+            let be = scalar_to_ordered_bytes(s, ScalarByteOrder::BigEndian);
+            assert_eq!(le.len(), S::bytes_len());
+            assert_eq!(le, s.to_bytes());
+
+            // the two byte orders are the reverse of each other
+            let mut reversed = be.clone();
+            reversed.reverse();
+            assert_eq!(le, reversed);
+
+            // cross-check with the integer value
+            let biguint: BigUint = (*s).into();
+            assert_eq!(BigUint::from_bytes_le(&le), biguint);
+            assert_eq!(BigUint::from_bytes_be(&be), biguint);
+
+            for order in orders.iter() {
+                let tagged = scalar_to_tagged_bytes(s, *order);
+                assert_eq!(tagged[0], order.to_tag());
+                assert_eq!(scalar_from_tagged_bytes::<S>(&tagged).unwrap(), *s);
+
+                let untagged = scalar_to_ordered_bytes(s, *order);
+                assert_eq!(
+                    scalar_from_ordered_bytes::<S>(&untagged, *order).unwrap(),
+                    *s
+                );
+            }
+
+            assert_eq!(
+                convert_scalar_byte_order::<S>(
+                    &le,
+                    ScalarByteOrder::LittleEndian,
+                    ScalarByteOrder::BigEndian
+                )
+                .unwrap(),
+                be
+            );
+        }
+
+        // non-canonical encodings are rejected
+        let mut modulus = S::get_field_size_le_bytes();
+        modulus.resize(S::bytes_len(), 0u8);
+        assert!(scalar_from_ordered_bytes::<S>(&modulus, ScalarByteOrder::LittleEndian).is_err());
+        assert!(scalar_from_ordered_bytes::<S>(
+            &vec![0xffu8; S::bytes_len()],
+            ScalarByteOrder::BigEndian
+        )
+        .is_err());
+
+        // wrong lengths and unknown tags are rejected
+        let tagged = scalar_to_tagged_bytes(&S::one(), ScalarByteOrder::LittleEndian);
+        assert!(scalar_from_tagged_bytes::<S>(&[]).is_err());
+        assert!(scalar_from_tagged_bytes::<S>(&tagged[..tagged.len() - 1]).is_err());
+        let mut bad_tag = tagged.clone();
+        bad_tag[0] = 0u8;
+        assert!(scalar_from_tagged_bytes::<S>(&bad_tag).is_err());
+
+        // a big-endian tag on little-endian bytes must not silently decode to the same value
+        let mut swapped = tagged;
+        swapped[0] = ScalarByteOrder::BigEndian.to_tag();
+        assert_ne!(scalar_from_tagged_bytes::<S>(&swapped).ok(), Some(S::one()));
+    }
+
+    fn check_malformed_bytes<T: ZeiFromToBytes>(valid: &[u8]) {
+        let mut prng = test_rng();
+
+        assert!(T::zei_from_bytes(&[]).is_err());
+        for len in 0..valid.len() {
+            let _ = T::zei_from_bytes(&valid[..len]);
+        }
+        let mut too_long = valid.to_vec();
+        too_long.push(0u8);
+        let _ = T::zei_from_bytes(&too_long);
+        let _ = T::zei_from_bytes(&vec![0xffu8; valid.len()]);
+
+        for _ in 0..16 {
+            let mut bytes = valid.to_vec();
+            let i = (prng.next_u32() as usize) % bytes.len();
+            bytes[i] ^= 1u8 << (prng.next_u32() % 8);
+            let _ = T::zei_from_bytes(&bytes);
+
+            let mut garbage = vec![0u8; (prng.next_u32() as usize) % (2 * valid.len())];
+            prng.fill_bytes(&mut garbage);
+            let _ = T::zei_from_bytes(&garbage);
+        }
+    }
+
+    fn check_malformed_group_bytes<G: Group + ZeiFromToBytes>() {
+        let mut prng = test_rng();
+        let point = G::random(&mut prng);
+        check_malformed_bytes::<G>(&point.to_compressed_bytes());
+
+        let unchecked = point.to_unchecked_bytes();
+        assert!(G::from_unchecked_bytes(&[]).is_err());
+        for len in 0..unchecked.len() {
+            let _ = G::from_unchecked_bytes(&unchecked[..len]);
+        }
+    }
+
+    #[test]
+    fn malformed_bytes_do_not_panic() {
+        check_malformed_bytes::<RistrettoScalar>(&RistrettoScalar::one().to_bytes());
+        check_malformed_bytes::<BLSScalar>(&BLSScalar::one().to_bytes());
+        check_malformed_bytes::<JubjubScalar>(&JubjubScalar::one().to_bytes());
+        check_malformed_bytes::<SECP256K1Scalar>(&SECP256K1Scalar::one().to_bytes());
+        check_malformed_bytes::<SECQ256K1Scalar>(&SECQ256K1Scalar::one().to_bytes());
+
+        check_malformed_group_bytes::<RistrettoPoint>();
+        check_malformed_group_bytes::<BLSG1>();
+        check_malformed_group_bytes::<BLSG2>();
+        check_malformed_group_bytes::<BLSGt>();
+        check_malformed_group_bytes::<JubjubPoint>();
+        check_malformed_group_bytes::<SECP256K1G1>();
+        check_malformed_group_bytes::<SECQ256K1G1>();
+
+        let point = RistrettoPoint::get_base();
+        check_malformed_bytes::<CompressedRistretto>(&point.compress().zei_to_bytes());
+        assert!(CompressedRistretto::zei_from_bytes(&[0u8; 31]).is_err());
+        assert!(CompressedEdwardsY::zei_from_bytes(&[0u8; 33]).is_err());
+        assert!(RistrettoPoint::from_compressed_bytes(&[0u8; 31]).is_err());
+
+        // small integers do not fill all the bytes of a scalar
+        assert_eq!(
+            RistrettoScalar::from(&BigUint::from(5u32)),
+            RistrettoScalar::from(5u32)
+        );
+    }
+
+    fn check_non_canonical_scalar<S: Scalar + ZeiFromToBytes>() {
+        let minus_one = S::one().neg().zei_to_bytes();
+        assert_eq!(S::zei_from_bytes(&minus_one).unwrap(), S::one().neg());
+
+        let mut modulus = S::get_field_size_le_bytes();
+        modulus.resize(S::bytes_len(), 0);
+        assert!(S::zei_from_bytes(&modulus).is_err());
+        assert!(S::zei_from_bytes(&minus_one[..minus_one.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn non_canonical_scalars_are_rejected() {
+        check_non_canonical_scalar::<RistrettoScalar>();
+        check_non_canonical_scalar::<BLSScalar>();
+        check_non_canonical_scalar::<JubjubScalar>();
+        check_non_canonical_scalar::<SECP256K1Scalar>();
+        check_non_canonical_scalar::<SECQ256K1Scalar>();
+    }
+
+    fn check_scalar_serde<S: Scalar + Serialize + for<'de> Deserialize<'de>>() {
+        let mut prng = test_rng();
+        let s = S::random(&mut prng);
+
+        let bytes = bincode::serialize(&s).unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(bincode::deserialize::<S>(&bytes).unwrap(), s);
+        assert_eq!(serde_json::from_str::<S>(&json).unwrap(), s);
+
+        // the length prefix of bincode is a u64
+        let expected_len = if cfg!(feature = "tagged_scalars") {
+            S::bytes_len() + 1
+        } else {
+            S::bytes_len()
+        };
+        assert_eq!(bytes.len(), 8 + expected_len);
+
+        // both the untagged and the tagged encodings are accepted,
+        // note that bincode encodes a `Vec<u8>` the same way as a byte string
+        let untagged = s.to_bytes();
+        let big_endian = scalar_to_tagged_bytes(&s, ScalarByteOrder::BigEndian);
+        for encoding in [untagged, big_endian].iter() {
+            let bytes = bincode::serialize(encoding).unwrap();
+            assert_eq!(bincode::deserialize::<S>(&bytes).unwrap(), s);
+            let json = serde_json::to_string(&b64enc(encoding)).unwrap();
+            assert_eq!(serde_json::from_str::<S>(&json).unwrap(), s);
+        }
+
+        // a non-canonical untagged encoding is still rejected
+        let mut modulus = S::get_field_size_le_bytes();
+        modulus.resize(S::bytes_len(), 0u8);
+        let bytes = bincode::serialize(&modulus).unwrap();
+        assert!(bincode::deserialize::<S>(&bytes).is_err());
+    }
+
+    #[test]
+    fn scalar_serde() {
+        check_scalar_serde::<BLSScalar>();
+        check_scalar_serde::<JubjubScalar>();
+        check_scalar_serde::<RistrettoScalar>();
+        check_scalar_serde::<SECP256K1Scalar>();
+        check_scalar_serde::<SECQ256K1Scalar>();
+    }
+
+    #[test]
+    fn tagged_scalar_encoding() {
+        check_tagged_encoding::<BLSScalar>();
+        check_tagged_encoding::<JubjubScalar>();
+        check_tagged_encoding::<RistrettoScalar>();
+        check_tagged_encoding::<SECP256K1Scalar>();
+        check_tagged_encoding::<SECQ256K1Scalar>();
+    }
+}
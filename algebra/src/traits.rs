@@ -8,6 +8,86 @@ use digest::{generic_array::typenum::U64, Digest};
 use num_bigint::BigUint;
 use serde::{Deserialize, Serialize};
 
+/// Implement `Sub` with the right operand by value, and `Product`, for a scalar type,
+/// from the operators with the right operand by reference.
+macro_rules! impl_scalar_by_value_ops {
+    ($t:ident) => {
+        impl Sub for $t {
+            type Output = $t;
+
+            #[inline]
+            fn sub(self, rhs: $t) -> $t {
+                self - &rhs
+            }
+        }
+
+        impl Product<$t> for $t {
+            #[inline]
+            fn product<I: Iterator<Item = $t>>(iter: I) -> $t {
+                iter.fold(<$t as One>::one(), |acc, x| acc * &x)
+            }
+        }
+
+        impl<'a> Product<&'a $t> for $t {
+            #[inline]
+            fn product<I: Iterator<Item = &'a $t>>(iter: I) -> $t {
+                iter.fold(<$t as One>::one(), |acc, x| acc * x)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_scalar_by_value_ops;
+
+/// Implement `Add`, `Sub`, and the scalar multiplication with the right operand by value,
+/// and `Sum`, for a group type, from the operators with the right operand by reference.
+macro_rules! impl_group_by_value_ops {
+    ($g:ident, $s:ident) => {
+        impl Add for $g {
+            type Output = $g;
+
+            #[inline]
+            fn add(self, rhs: $g) -> $g {
+                self + &rhs
+            }
+        }
+
+        impl Sub for $g {
+            type Output = $g;
+
+            #[inline]
+            fn sub(self, rhs: $g) -> $g {
+                self - &rhs
+            }
+        }
+
+        impl Mul<$s> for $g {
+            type Output = $g;
+
+            #[inline]
+            fn mul(self, rhs: $s) -> $g {
+                self * &rhs
+            }
+        }
+
+        impl Sum<$g> for $g {
+            #[inline]
+            fn sum<I: Iterator<Item = $g>>(iter: I) -> $g {
+                iter.fold(<$g as Group>::get_identity(), |acc, x| acc + &x)
+            }
+        }
+
+        impl<'a> Sum<&'a $g> for $g {
+            #[inline]
+            fn sum<I: Iterator<Item = &'a $g>>(iter: I) -> $g {
+                iter.fold(<$g as Group>::get_identity(), |acc, x| acc + x)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_group_by_value_ops;
+
 /// The trait for scalars
 pub trait Scalar:
     Copy
@@ -25,7 +105,9 @@ pub trait Scalar:
     + Sized
     + Add<Self, Output = Self>
     + Mul<Self, Output = Self>
+    + Sub<Self, Output = Self>
     + Sum<Self>
+    + Product<Self>
     + for<'a> Add<&'a Self, Output = Self>
     + for<'a> AddAssign<&'a Self>
     + for<'a> Mul<&'a Self, Output = Self>
@@ -33,6 +115,7 @@ pub trait Scalar:
     + for<'a> Sub<&'a Self, Output = Self>
     + for<'a> SubAssign<&'a Self>
     + for<'a> Sum<&'a Self>
+    + for<'a> Product<&'a Self>
     + From<u32>
     + From<u64>
     + Neg<Output = Self>
@@ -42,11 +125,25 @@ pub trait Scalar:
     /// Return a random scalar
     fn random<R: CryptoRng + RngCore>(rng: &mut R) -> Self;
 
-    /// Sample a scalar based on a hash value
+    /// Sample a scalar based on a hash value, by reducing the 64-byte digest modulo the
+    /// field size, so that the scalar is statistically close to uniform.
     fn from_hash<D>(hash: D) -> Self
     where
         D: Digest<OutputSize = U64> + Default;
 
+    /// Convert from a u128 integer, which is smaller than the field size of every scalar.
+    fn from_u128(value: u128) -> Self {
+        let two_pow_64 = Self::from(u64::MAX).add(&Self::one());
+        Self::from((value >> 64) as u64)
+            .mul(&two_pow_64)
+            .add(&Self::from(value as u64))
+    }
+
+    /// Convert from little-endian bytes of any length, reduced modulo the field size.
+    fn from_le_bytes_mod_order(bytes: &[u8]) -> Self {
+        Self::from(&(BigUint::from_bytes_le(bytes) % Self::get_field_size_biguint()))
+    }
+
     /// Return multiplicative generator of order r,
     /// which is also required to be a quadratic nonresidue
     fn multiplicative_generator() -> Self;
@@ -78,9 +175,25 @@ pub trait Scalar:
     /// Convert to bytes
     fn to_bytes(&self) -> Vec<u8>;
 
-    /// Convert from bytes
+    /// Convert from bytes, which may not be the canonical encoding of the scalar.
+    /// The scalars received from other parties must use [`Scalar::from_bytes_canonical`] instead.
     fn from_bytes(bytes: &[u8]) -> Result<Self>;
 
+    /// Convert from the canonical encoding, which is exactly `bytes_len()` little-endian bytes
+    /// of a value smaller than the field size, so that every scalar has a unique encoding.
+    /// The check takes time independent of the value of the scalar.
+    fn from_bytes_canonical(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::bytes_len() {
+            return Err(eg!(AlgebraError::DeserializationError));
+        }
+        let scalar = Self::from_bytes(bytes).c(d!())?;
+        if constant_time_eq(&scalar.to_bytes(), bytes) {
+            Ok(scalar)
+        } else {
+            Err(eg!(AlgebraError::DeserializationError))
+        }
+    }
+
     /// Return the modular inverse of the scalar if it exists
     fn inv(&self) -> Result<Self>;
 
@@ -115,13 +228,18 @@ pub trait Group:
     + PartialEq
     + Eq
     + Clone
+    + Add<Self, Output = Self>
+    + Mul<Self::ScalarType, Output = Self>
+    + Sub<Self, Output = Self>
     + for<'a> Add<&'a Self, Output = Self>
     + for<'a> Mul<&'a Self::ScalarType, Output = Self>
     + for<'a> Sub<&'a Self, Output = Self>
     + for<'a> AddAssign<&'a Self>
     + for<'a> SubAssign<&'a Self>
+    + Sum<Self>
+    + for<'a> Sum<&'a Self>
     + Serialize
-    + Neg
+    + Neg<Output = Self>
     + for<'de> Deserialize<'de>
 {
     /// The scalar type
@@ -157,7 +275,12 @@ pub trait Group:
     /// Return the size of unchecked bytes.
     fn unchecked_size() -> usize;
 
-    /// Sample a group element based on a hash value
+    /// Sample a group element based on a hash value.
+    ///
+    /// The target groups of pairings hash to a scalar, see [`Scalar::from_hash`], and raise
+    /// their base to it. The curve points are instead sampled by the curve, with a PRNG seeded
+    /// from the first 32 bytes of the digest, so that their discrete logarithms are unknown and
+    /// that the generators derived from hashes, e.g., the Pedersen bases, do not change.
     fn from_hash<D>(hash: D) -> Self
     where
         D: Digest<OutputSize = U64> + Default;
@@ -195,6 +318,13 @@ pub trait Pairing {
 
     /// The pairing operation
     fn pairing(a: &Self::G1, b: &Self::G2) -> Self::Gt;
+
+    /// Compute the product of the pairings of the pairs, written additively in `Gt`.
+    /// The implementations for the pairing-friendly curves share the Miller loop and do
+    /// a single final exponentiation, which is much cheaper than separate pairings.
+    fn multi_pairing(pairs: &[(Self::G1, Self::G2)]) -> Self::Gt {
+        pairs.iter().map(|(a, b)| Self::pairing(a, b)).sum()
+    }
 }
 
 /// Convert the scalar into a vector of small chunks, each of size `w`
@@ -317,7 +447,12 @@ pub fn pippenger<G: Group>(scalars: &[&G::ScalarType], elems: &[&G]) -> Result<G
 
 #[cfg(test)]
 pub(crate) mod group_tests {
-    use crate::traits::{scalar_to_radix_2_power_w, Scalar};
+    use crate::prelude::*;
+    use crate::traits::{scalar_to_radix_2_power_w, Pairing};
+    use ark_std::test_rng;
+    use digest::Digest;
+    use num_bigint::BigUint;
+    use sha2::Sha512;
 
     pub(crate) fn test_scalar_operations<S: Scalar>() {
         let a = S::from(40u32);
@@ -385,6 +520,24 @@ pub(crate) mod group_tests {
 
         let v = S::get_field_size_biguint().to_bytes_le();
         assert_eq!(v, S::get_field_size_le_bytes());
+
+        assert_eq!(S::from_u128(3486784401u128), S::from(3486784401u64));
+        let value = (u128::from(u64::MAX) << 64) + 12345;
+        assert_eq!(S::from_u128(value), S::from(&BigUint::from(value)));
+        assert_eq!(
+            S::from_u128(u128::MAX).add(&S::one()),
+            S::from_u128(1u128 << 127).mul(&S::from(2u32))
+        );
+
+        // reduction of the field size, and of wide inputs
+        let mut bytes = S::get_field_size_le_bytes();
+        assert_eq!(S::from_le_bytes_mod_order(&bytes), S::zero());
+        bytes[0] = bytes[0].wrapping_add(5);
+        assert_eq!(S::from_le_bytes_mod_order(&bytes), S::from(5u32));
+        let wide = [0xffu8; 64];
+        let expected = BigUint::from_bytes_le(&wide) % S::get_field_size_biguint();
+        assert_eq!(S::from_le_bytes_mod_order(&wide), S::from(&expected));
+        assert_eq!(S::from_le_bytes_mod_order(&[]), S::zero());
     }
 
     pub(crate) fn test_scalar_serialization<S: Scalar>() {
@@ -392,6 +545,70 @@ pub(crate) mod group_tests {
         let bytes = a.to_bytes();
         let b = S::from_bytes(bytes.as_slice()).unwrap();
         assert_eq!(a, b);
+
+        let a = S::one().neg();
+        let bytes = a.to_bytes();
+        assert_eq!(bytes.len(), S::bytes_len());
+        assert_eq!(S::from_bytes_canonical(&bytes).unwrap(), a);
+
+        // values not smaller than the field size, and encodings of other lengths, are rejected
+        let mut modulus = S::get_field_size_le_bytes();
+        modulus.resize(S::bytes_len(), 0);
+        assert!(S::from_bytes_canonical(&modulus).is_err());
+        assert!(S::from_bytes_canonical(&vec![0xffu8; S::bytes_len()]).is_err());
+        assert!(S::from_bytes_canonical(&bytes[1..]).is_err());
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(S::from_bytes_canonical(&longer).is_err());
+    }
+
+    /// Check that hashing to a scalar is uniform over the field, and that it
+    /// derives `expected` (in little-endian hex) from a fixed message, so that
+    /// the derivation of the challenges cannot change unnoticed.
+    pub(crate) fn test_scalar_from_hash<S: Scalar>(expected: &str) {
+        let scalar = S::from_hash(Sha512::new().chain_update(b"zei scalar from hash"));
+        let expected = (0..expected.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&expected[i..i + 2], 16).unwrap())
+            .collect::<Vec<u8>>();
+        assert_eq!(scalar.to_bytes(), expected);
+
+        // the samples should fall evenly into the quarters of the field
+        const N: usize = 4000;
+        let modulus = S::get_field_size_biguint();
+        let mut buckets = [0usize; 4];
+        for i in 0..N {
+            let scalar = S::from_hash(Sha512::new().chain_update((i as u64).to_le_bytes()));
+            let value = BigUint::from_bytes_le(&scalar.to_bytes());
+            let bucket = (value * 4u32 / &modulus).to_u64_digits();
+            buckets[bucket.first().copied().unwrap_or(0) as usize] += 1;
+        }
+        for count in buckets {
+            assert!(count > N / 4 - N / 20 && count < N / 4 + N / 20);
+        }
+    }
+
+    pub(crate) fn test_multi_pairing<P: Pairing>() {
+        let mut prng = test_rng();
+        let pairs = (0..4)
+            .map(|_| (P::G1::random(&mut prng), P::G2::random(&mut prng)))
+            .collect::<Vec<_>>();
+        let expected = pairs.iter().fold(P::Gt::get_identity(), |acc, (a, b)| {
+            acc.add(&P::pairing(a, b))
+        });
+        assert_eq!(P::multi_pairing(&pairs), expected);
+        assert_eq!(
+            P::multi_pairing(&pairs[..1]),
+            P::pairing(&pairs[0].0, &pairs[0].1)
+        );
+        assert_eq!(P::multi_pairing(&[]), P::Gt::get_identity());
+
+        // e(a, b) * e(-a, b) = 1, as used to check the equality of two pairings
+        let (a, b) = pairs[0];
+        assert_eq!(
+            P::multi_pairing(&[(a, b), (a.neg(), b)]),
+            P::Gt::get_identity()
+        );
     }
 
     pub(crate) fn test_to_radix<S: Scalar>() {
@@ -419,6 +636,7 @@ pub(crate) mod group_tests {
 mod multi_exp_tests {
     use crate::bls12_381::{BLSGt, BLSG1, BLSG2};
     use crate::ristretto::RistrettoPoint;
+    use crate::secp256k1::SECP256K1G1;
     use crate::traits::Group;
 
     #[test]
@@ -437,6 +655,10 @@ mod multi_exp_tests {
     fn test_multiexp_blsgt() {
         run_multiexp_test::<BLSGt>();
     }
+    #[test]
+    fn test_multiexp_secp256k1() {
+        run_multiexp_test::<SECP256K1G1>();
+    }
 
     fn run_multiexp_test<G: Group>() {
         let g = G::multi_exp(&[], &[]);
@@ -470,3 +692,62 @@ mod multi_exp_tests {
         assert_eq!(g, expected);
     }
 }
+
+#[cfg(test)]
+mod ops_tests {
+    use crate::bls12_381::{BLSGt, BLSScalar, BLSG1, BLSG2};
+    use crate::bn254::{BN254Gt, BN254Scalar, BN254G1, BN254G2};
+    use crate::jubjub::{JubjubPoint, JubjubScalar};
+    use crate::prelude::*;
+    use crate::ristretto::{RistrettoPoint, RistrettoScalar};
+    use crate::secp256k1::{SECP256K1Scalar, SECP256K1G1};
+    use crate::secq256k1::{SECQ256K1Scalar, SECQ256K1G1};
+
+    fn run_scalar_ops_test<S: Scalar>() {
+        let a = S::from(40u32);
+        let b = S::from(60u32);
+        assert_eq!(b - a, S::from(20u32));
+        assert_eq!(a * b + a - b, a.mul(&b).add(&a).sub(&b));
+        assert_eq!(-a + a, S::zero());
+
+        let v = vec![S::from(2u32), S::from(3u32), S::from(7u32)];
+        assert_eq!(v.iter().product::<S>(), S::from(42u32));
+        assert_eq!(v.into_iter().product::<S>(), S::from(42u32));
+        assert_eq!(Vec::<S>::new().into_iter().product::<S>(), S::one());
+    }
+
+    fn run_group_ops_test<G: Group>() {
+        let g = G::get_base();
+        let two = G::ScalarType::from(2u32);
+        let three = G::ScalarType::from(3u32);
+        assert_eq!(g * two + g, g.mul(&three));
+        assert_eq!(g * three - g, g.double());
+        assert_eq!(-g + g, G::get_identity());
+
+        let v = vec![g, g.double(), g * three];
+        assert_eq!(v.iter().sum::<G>(), g * G::ScalarType::from(6u32));
+        assert_eq!(v.into_iter().sum::<G>(), g * G::ScalarType::from(6u32));
+        assert_eq!(Vec::<G>::new().into_iter().sum::<G>(), G::get_identity());
+    }
+
+    #[test]
+    fn test_by_value_ops() {
+        run_scalar_ops_test::<BLSScalar>();
+        run_scalar_ops_test::<BN254Scalar>();
+        run_scalar_ops_test::<JubjubScalar>();
+        run_scalar_ops_test::<RistrettoScalar>();
+        run_scalar_ops_test::<SECP256K1Scalar>();
+        run_scalar_ops_test::<SECQ256K1Scalar>();
+
+        run_group_ops_test::<BLSG1>();
+        run_group_ops_test::<BLSG2>();
+        run_group_ops_test::<BLSGt>();
+        run_group_ops_test::<BN254G1>();
+        run_group_ops_test::<BN254G2>();
+        run_group_ops_test::<BN254Gt>();
+        run_group_ops_test::<JubjubPoint>();
+        run_group_ops_test::<RistrettoPoint>();
+        run_group_ops_test::<SECP256K1G1>();
+        run_group_ops_test::<SECQ256K1G1>();
+    }
+}
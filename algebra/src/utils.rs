@@ -34,6 +34,15 @@ pub fn u8_le_slice_to_u32(slice: &[u8]) -> u32 {
     u32::from_le_bytes(a)
 }
 
+/// Compare two byte slices, in time depending only on their lengths
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
 /// Compute the minimum power of two that is greater or equal to the input
 pub fn min_greater_equal_power_of_two(n: u32) -> u32 {
     2.0f64.powi((n as f64).log2().ceil() as i32) as u32
@@ -1,5 +1,5 @@
 use ark_std::test_rng;
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
 use digest::Digest;
 use mem_db::MemoryDB;
 use parking_lot::RwLock;
@@ -37,6 +37,7 @@ use zei_crypto::basic::{pedersen_comm::PedersenCommitmentRistretto, rescue::Resc
 const AMOUNT: u64 = 10u64;
 const ASSET: AssetType = AssetType([1u8; ASSET_TYPE_LENGTH]);
 const BATCHSIZE: [usize; 7] = [1, 2, 3, 6, 10, 20, 30];
+const N_RECORDS: [usize; 3] = [1, 2, 3];
 
 // Measurement of the verification time and batch verification time of `abar_to_abar`.
 fn bench_abar_to_abar(c: &mut Criterion) {
@@ -68,6 +69,7 @@ fn bench_ar_to_abar(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_abar_to_abar,
+    bench_abar_to_abar_prove,
     bench_abar_to_bar,
     bench_abar_to_ar,
     bench_bar_to_abar,
@@ -90,28 +92,7 @@ fn abar_to_abar(
         .map(|_| AXfrKeyPair::generate(&mut prng))
         .collect();
 
-    let mut oabars: Vec<OpenAnonAssetRecord> = inputs
-        .iter()
-        .map(|input| build_oabar(&mut prng, input.0, input.1, &sender))
-        .collect();
-    let abars: Vec<_> = oabars.iter().map(AnonAssetRecord::from_oabar).collect();
-
-    let fdb = MemoryDB::new();
-    let cs = Arc::new(RwLock::new(ChainState::new(fdb, "my_store".to_string(), 0)));
-    let mut state = State::new(cs, false);
-    let store = PrefixedStore::new("my_store", &mut state);
-    let mut mt = PersistentMerkleTree::new(store).unwrap();
-    let mut uids = vec![];
-    for i in 0..abars.len() {
-        let abar_comm = hash_abar(mt.entry_count(), &abars[i]);
-        uids.push(mt.add_commitment_hash(abar_comm).unwrap());
-    }
-    mt.commit().unwrap();
-    let root = mt.get_root().unwrap();
-    for (i, uid) in uids.iter().enumerate() {
-        let proof = mt.generate_proof(*uid).unwrap();
-        oabars[i].update_mt_leaf_info(build_mt_leaf_info_from_proof(proof, *uid));
-    }
+    let (oabars, root) = build_spendable_oabars(&mut prng, &inputs, &sender);
 
     let oabars_out: Vec<OpenAnonAssetRecord> = outputs
         .iter()
@@ -157,6 +138,40 @@ fn abar_to_abar(
     }
 }
 
+// Measurement of the proving time of `abar_to_abar`, for a number of inputs and outputs.
+fn bench_abar_to_abar_prove(c: &mut Criterion) {
+    let mut prng = test_rng();
+    let mut group = c.benchmark_group("abar_to_abar_prove");
+    group.sample_size(10);
+
+    for n in N_RECORDS {
+        let fee = 13u32;
+        let mut inputs = vec![(20 + fee as u64, FEE_TYPE)];
+        inputs.resize(n, (20, FEE_TYPE));
+        let outputs = vec![(20, FEE_TYPE); n];
+        let params = ProverParams::new(inputs.len(), outputs.len(), None).unwrap();
+
+        let sender = AXfrKeyPair::generate(&mut prng);
+        let (oabars, _) = build_spendable_oabars(&mut prng, &inputs, &sender);
+        let oabars_out: Vec<OpenAnonAssetRecord> = outputs
+            .iter()
+            .map(|output| {
+                let receiver = AXfrKeyPair::generate(&mut prng);
+                build_oabar(&mut prng, output.0, output.1, &receiver)
+            })
+            .collect();
+        let pre_note = init_anon_xfr_note(&oabars, &oabars_out, fee, &sender).unwrap();
+        let hash = random_hasher(&mut prng);
+
+        group.bench_function(BenchmarkId::from_parameter(format!("{}x{}", n, n)), |b| {
+            b.iter(|| {
+                finish_anon_xfr_note(&mut prng, &params, pre_note.clone(), hash.clone()).unwrap()
+            });
+        });
+    }
+    group.finish();
+}
+
 fn abar_to_ar(c: &mut Criterion) {
     let mut prng = test_rng();
     let params = ProverParams::abar_to_ar_params(TREE_DEPTH).unwrap();
@@ -320,6 +335,18 @@ fn ar_to_abar(c: &mut Criterion) {
 
     let mut single_group = c.benchmark_group("ar_to_abar");
     single_group.sample_size(20);
+    single_group.bench_function("prove".to_string(), |b| {
+        b.iter(|| {
+            gen_ar_to_abar_note(
+                &mut prng,
+                &params,
+                &obar,
+                &sender,
+                &receiver.get_public_key(),
+            )
+            .unwrap()
+        });
+    });
     single_group.bench_function("non-batch".to_string(), |b| {
         b.iter(|| assert!(verify_ar_to_abar_note(&verify_params, &note).is_ok()));
     });
@@ -414,6 +441,37 @@ fn build_oabar<R: CryptoRng + RngCore>(
         .unwrap()
 }
 
+/// Build records owned by `keypair`, in a new Merkle tree, with their leaf information.
+fn build_spendable_oabars<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    inputs: &[(u64, AssetType)],
+    keypair: &AXfrKeyPair,
+) -> (Vec<OpenAnonAssetRecord>, BLSScalar) {
+    let mut oabars: Vec<OpenAnonAssetRecord> = inputs
+        .iter()
+        .map(|input| build_oabar(prng, input.0, input.1, keypair))
+        .collect();
+    let abars: Vec<_> = oabars.iter().map(AnonAssetRecord::from_oabar).collect();
+
+    let fdb = MemoryDB::new();
+    let cs = Arc::new(RwLock::new(ChainState::new(fdb, "my_store".to_string(), 0)));
+    let mut state = State::new(cs, false);
+    let store = PrefixedStore::new("my_store", &mut state);
+    let mut mt = PersistentMerkleTree::new(store).unwrap();
+    let mut uids = vec![];
+    for i in 0..abars.len() {
+        let abar_comm = hash_abar(mt.entry_count(), &abars[i]);
+        uids.push(mt.add_commitment_hash(abar_comm).unwrap());
+    }
+    mt.commit().unwrap();
+    let root = mt.get_root().unwrap();
+    for (i, uid) in uids.iter().enumerate() {
+        let proof = mt.generate_proof(*uid).unwrap();
+        oabars[i].update_mt_leaf_info(build_mt_leaf_info_from_proof(proof, *uid));
+    }
+    (oabars, root)
+}
+
 fn hash_abar(uid: u64, abar: &AnonAssetRecord) -> BLSScalar {
     let hash = RescueInstance::new();
     hash.rescue(&[
@@ -0,0 +1,149 @@
+use ark_std::test_rng;
+use bulletproofs::BulletproofGens;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use merlin::Transcript;
+use zei::anon_creds::{
+    ac_keygen_issuer, ac_keygen_user, ac_reveal, ac_sign, ac_verify, ACVerificationPolicy, Attr,
+    Credential,
+};
+use zei_algebra::{
+    bls12_381::{BLSScalar, BLSG1},
+    prelude::*,
+    ristretto::{RistrettoPoint, RistrettoScalar},
+};
+use zei_crypto::{
+    basic::elgamal::{elgamal_encrypt, elgamal_key_gen, elgamal_partial_decrypt},
+    bulletproofs::range::{batch_verify_ranges, prove_ranges},
+};
+
+const N_CANDIDATES: [usize; 3] = [16, 256, 4096];
+const BATCH_SIZES: [usize; 5] = [1, 2, 4, 8, 16];
+const N_ATTRS: [usize; 3] = [1, 4, 16];
+
+// Measurement of the ElGamal decryption time, as a partial decryption followed by
+// a linear scan over a list of candidate messages, as done by asset tracers.
+fn bench_elgamal_decryption(c: &mut Criterion) {
+    let mut prng = test_rng();
+    let mut group = c.benchmark_group("elgamal_decryption");
+    group.sample_size(20);
+
+    let (sk, pk) = elgamal_key_gen::<_, BLSG1>(&mut prng);
+    let ctext = elgamal_encrypt(&BLSScalar::from(100u32), &BLSScalar::random(&mut prng), &pk);
+    group.bench_function("partial decryption BLSG1", |b| {
+        b.iter(|| elgamal_partial_decrypt(&ctext, &sk))
+    });
+
+    let (sk, pk) = elgamal_key_gen::<_, RistrettoPoint>(&mut prng);
+    for n_candidates in N_CANDIDATES {
+        let base = RistrettoPoint::get_base();
+        let candidates = (0..n_candidates as u32)
+            .map(|m| base.mul(&RistrettoScalar::from(m)))
+            .collect_vec();
+        // the worst case, where the message is the last candidate
+        let m = RistrettoScalar::from(n_candidates as u32 - 1);
+        let ctext = elgamal_encrypt(&m, &RistrettoScalar::random(&mut prng), &pk);
+        group.bench_with_input(
+            BenchmarkId::new("ristretto with candidates", n_candidates),
+            &candidates,
+            |b, candidates| {
+                b.iter(|| {
+                    let decrypted = elgamal_partial_decrypt(&ctext, &sk);
+                    assert!(candidates.iter().any(|point| *point == decrypted))
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+// Measurement of the batch verification time of range proofs, for a number of proofs.
+fn bench_range_proof_batch_verification(c: &mut Criterion) {
+    let mut prng = test_rng();
+    let bp_gens = BulletproofGens::new(64, 1);
+    let mut group = c.benchmark_group("range_proof_batch_verification");
+    group.sample_size(20);
+
+    for batch_size in BATCH_SIZES {
+        let mut proofs = vec![];
+        let mut commitments = vec![];
+        for _ in 0..batch_size {
+            let value = prng.gen_range(1u64..1000);
+            let blinding = RistrettoScalar::random(&mut prng);
+            let mut transcript = Transcript::new(b"bench");
+            let (proof, coms) =
+                prove_ranges(&bp_gens, &mut transcript, &[value], &[blinding], 64).unwrap();
+            proofs.push(proof);
+            commitments.push(coms);
+        }
+        let proofs_ref = proofs.iter().collect_vec();
+        let commitments_ref = commitments.iter().map(|x| x.as_slice()).collect_vec();
+
+        group.bench_function(BenchmarkId::from_parameter(batch_size), |b| {
+            b.iter(|| {
+                let mut transcripts = vec![Transcript::new(b"bench"); batch_size];
+                assert!(batch_verify_ranges(
+                    &mut prng,
+                    &bp_gens,
+                    &proofs_ref,
+                    &mut transcripts,
+                    &commitments_ref,
+                    64,
+                )
+                .is_ok())
+            })
+        });
+    }
+    group.finish();
+}
+
+// Measurement of the proving and verification time of anonymous credential reveals,
+// for a number of attributes, half of which are revealed.
+fn bench_anon_creds_reveal(c: &mut Criterion) {
+    let mut prng = test_rng();
+    let mut group = c.benchmark_group("anon_creds_reveal");
+    group.sample_size(20);
+
+    for n_attrs in N_ATTRS {
+        let (issuer_sk, issuer_pk) = ac_keygen_issuer(&mut prng, n_attrs);
+        let (user_sk, user_pk) = ac_keygen_user(&mut prng, &issuer_pk);
+        let attrs = (0..n_attrs as u64).map(Attr::U64).collect_vec();
+        let sig = ac_sign(&mut prng, &issuer_sk, &user_pk, &attrs).unwrap();
+        let credential = Credential {
+            sig,
+            attrs: attrs.clone(),
+            ipk: issuer_pk.clone(),
+        };
+        let reveal_map = (0..n_attrs).map(|i| i % 2 == 0).collect_vec();
+        let revealed = attrs
+            .iter()
+            .zip(reveal_map.iter())
+            .map(|(attr, reveal)| reveal.then(|| attr.clone()))
+            .collect_vec();
+
+        group.bench_function(BenchmarkId::new("prove", n_attrs), |b| {
+            b.iter(|| ac_reveal(&mut prng, &user_sk, &credential, &reveal_map).unwrap())
+        });
+        let reveal_sig = ac_reveal(&mut prng, &user_sk, &credential, &reveal_map).unwrap();
+        group.bench_function(BenchmarkId::new("verify", n_attrs), |b| {
+            b.iter(|| {
+                assert!(ac_verify(
+                    &issuer_pk,
+                    &revealed,
+                    &reveal_sig.cm,
+                    &reveal_sig.proof_open,
+                    &ACVerificationPolicy::default()
+                )
+                .is_ok())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_elgamal_decryption,
+    bench_range_proof_batch_verification,
+    bench_anon_creds_reveal
+);
+criterion_main!(benches);
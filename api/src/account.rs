@@ -0,0 +1,471 @@
+//! Accounts with an encrypted balance.
+//!
+//! The balance of an account in an asset type is public only as a Pedersen commitment, the
+//! [`EncryptedBalance`], which anyone can update homomorphically with the commitments of the
+//! amounts of the records received and spent by the holder. The holder keeps the opening of
+//! the commitment in an [`Account`], updated with the same transactions, and can prove on
+//! demand that the balance is at least an amount, by a range proof on the difference.
+//!
+//! Records with a confidential asset type are accepted with the blinding factor of their
+//! asset type, so that the asset type can be checked against the one of the account.
+
+use crate::setup::{BulletproofParams, BULLET_PROOF_RANGE};
+use crate::xfr::{
+    asset_record::open_blind_asset_record,
+    sig::{XfrKeyPair, XfrPublicKey},
+    structs::{AssetType, BlindAssetRecord, OpenAssetRecord, OwnerMemo, XfrAmount, XfrAssetType},
+};
+use bulletproofs::RangeProof;
+use merlin::Transcript;
+use std::io::{Read, Write};
+use zei_algebra::{
+    prelude::*,
+    ristretto::{CompressedRistretto, RistrettoPoint, RistrettoScalar},
+};
+use zei_crypto::{
+    basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto},
+    bulletproofs::{
+        amount::{link_limb_commitments, AmountCommitment, AmountOpening},
+        range::{batch_verify_ranges, prove_ranges_with_rng},
+    },
+};
+
+const BALANCE_PROOF_TRANSCRIPT: &[u8] = b"Zei Account Balance Proof";
+
+/// The direction of a transaction of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// A record received by the holder.
+    Incoming,
+    /// A record spent by the holder.
+    Outgoing,
+}
+
+/// A transaction of an account: a record received or spent by the holder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Whether the record is received or spent.
+    pub direction: Direction,
+    /// The record.
+    pub record: BlindAssetRecord,
+    /// The blinding factor of the asset type of the record, which is zero for a
+    /// non-confidential asset type.
+    pub asset_type_blind: RistrettoScalar,
+}
+
+impl Transaction {
+    /// Return the transaction of an opened record.
+    pub fn new(direction: Direction, record: &OpenAssetRecord) -> Self {
+        Transaction {
+            direction,
+            record: record.blind_asset_record.clone(),
+            asset_type_blind: record.type_blind,
+        }
+    }
+}
+
+/// The public balance of an account, committed as `balance * G + blind * H`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBalance {
+    /// The holder of the account.
+    pub holder: XfrPublicKey,
+    /// The asset type of the account.
+    pub asset_type: AssetType,
+    /// The commitment to the balance.
+    pub commitment: CompressedRistretto,
+}
+
+impl EncryptedBalance {
+    /// Return the balance of a new account, a commitment to zero with a zero blinding factor.
+    pub fn new(holder: XfrPublicKey, asset_type: AssetType) -> Self {
+        EncryptedBalance {
+            holder,
+            asset_type,
+            commitment: RistrettoPoint::get_identity().compress(),
+        }
+    }
+
+    /// Apply a transaction to the commitment: add the commitment to the amount of an incoming
+    /// record, or subtract the one of an outgoing record.
+    /// Return an error if an incoming record is not owned by the holder, or if the asset type
+    /// of the record is not the one of the account.
+    pub fn apply(&mut self, tx: &Transaction) -> Result<()> {
+        let pc_gens = PedersenCommitmentRistretto::default();
+        if tx.direction == Direction::Incoming && tx.record.public_key != self.holder {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let asset_type_matches = match tx.record.asset_type {
+            XfrAssetType::NonConfidential(asset_type) => asset_type == self.asset_type,
+            XfrAssetType::Confidential(_) => {
+                XfrAssetType::from_blind(&pc_gens, &self.asset_type, &tx.asset_type_blind)
+                    == tx.record.asset_type
+            }
+        };
+        if !asset_type_matches {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let commitment = self
+            .commitment
+            .decompress()
+            .c(d!(ZeiError::DecompressElementError))?;
+        let amount_com = amount_commitment(&pc_gens, &tx.record.amount).c(d!())?;
+        let commitment = match tx.direction {
+            Direction::Incoming => commitment.add(&amount_com),
+            Direction::Outgoing => commitment.sub(&amount_com),
+        };
+        self.commitment = commitment.compress();
+        Ok(())
+    }
+
+    /// Verify a proof that the balance is at least `amount`.
+    pub fn verify_balance_proof<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        params: &BulletproofParams,
+        amount: u64,
+        proof: &BalanceProof,
+    ) -> Result<()> {
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let commitment = self
+            .commitment
+            .decompress()
+            .c(d!(ZeiError::DecompressElementError))?;
+        let derived =
+            commitment.sub(&pc_gens.commit(RistrettoScalar::from(amount), RistrettoScalar::zero()));
+
+        let (com_low, com_high) = proof.difference_commitments;
+        AmountCommitment {
+            low: com_low,
+            high: com_high,
+        }
+        .verify_link(&derived)
+        .c(d!(ZeiError::ZKProofVerificationError))?;
+
+        let mut transcripts = [self.transcript(amount)];
+        batch_verify_ranges(
+            prng,
+            &params.bp_gens,
+            &[&proof.range_proof],
+            &mut transcripts,
+            &[&[com_low, com_high]],
+            BULLET_PROOF_RANGE,
+        )
+        .c(d!(ZeiError::ZKProofVerificationError))
+    }
+
+    // The transcript of a balance proof, bound to the account and the amount.
+    fn transcript(&self, amount: u64) -> Transcript {
+        let mut transcript = Transcript::new(BALANCE_PROOF_TRANSCRIPT);
+        transcript.append_message(b"holder", &self.holder.zei_to_bytes());
+        transcript.append_message(b"asset type", &self.asset_type.0);
+        transcript.append_message(b"balance", self.commitment.0.as_bytes());
+        transcript.append_u64(b"amount", amount);
+        transcript
+    }
+}
+
+/// A proof that the balance of an account is at least an amount.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BalanceProof {
+    /// The commitments to the lower and higher 32 bits of the balance minus the amount.
+    pub difference_commitments: (CompressedRistretto, CompressedRistretto),
+    /// The range proof of the difference.
+    #[serde(with = "zei_obj_serde")]
+    pub range_proof: RangeProof,
+}
+
+/// An account as kept by its holder: the encrypted balance with its opening.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    encrypted_balance: EncryptedBalance,
+    balance: u64,
+    blind: RistrettoScalar,
+}
+
+impl Account {
+    /// Create an account with a zero balance.
+    pub fn new(holder: XfrPublicKey, asset_type: AssetType) -> Self {
+        Account {
+            encrypted_balance: EncryptedBalance::new(holder, asset_type),
+            balance: 0,
+            blind: RistrettoScalar::zero(),
+        }
+    }
+
+    /// Return the balance.
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    /// Return the encrypted balance, as seen by everyone else.
+    pub fn encrypted_balance(&self) -> &EncryptedBalance {
+        &self.encrypted_balance
+    }
+
+    /// Open a record received by the holder and apply it to the account.
+    /// Return the transaction, for the others to apply it to the encrypted balance.
+    pub fn receive(
+        &mut self,
+        keypair: &XfrKeyPair,
+        record: &BlindAssetRecord,
+        owner_memo: &Option<OwnerMemo>,
+    ) -> Result<Transaction> {
+        if keypair.get_pk() != self.encrypted_balance.holder {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let record = open_blind_asset_record(record, owner_memo, keypair).c(d!())?;
+        let tx = Transaction::new(Direction::Incoming, &record);
+        self.apply(&tx, &record).c(d!())?;
+        Ok(tx)
+    }
+
+    /// Apply a record spent by the holder, e.g., the output of a transfer to a payee, to the
+    /// account. Return an error if the balance is less than the amount of the record.
+    /// Return the transaction, for the others to apply it to the encrypted balance.
+    pub fn spend(&mut self, record: &OpenAssetRecord) -> Result<Transaction> {
+        let tx = Transaction::new(Direction::Outgoing, record);
+        self.apply(&tx, record).c(d!())?;
+        Ok(tx)
+    }
+
+    /// Apply a transaction of an opened record to the account.
+    pub fn apply(&mut self, tx: &Transaction, record: &OpenAssetRecord) -> Result<()> {
+        if record.blind_asset_record != tx.record || record.type_blind != tx.asset_type_blind {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let amount_blind = AmountOpening {
+            amount: record.amount,
+            blinds: record.amount_blinds,
+        }
+        .linked_blind();
+        let (balance, blind) = match tx.direction {
+            Direction::Incoming => (
+                self.balance.checked_add(record.amount),
+                self.blind.add(&amount_blind),
+            ),
+            Direction::Outgoing => (
+                self.balance.checked_sub(record.amount),
+                self.blind.sub(&amount_blind),
+            ),
+        };
+        let balance = balance.c(d!(ZeiError::ParameterError))?;
+
+        let mut encrypted_balance = self.encrypted_balance.clone();
+        encrypted_balance.apply(tx).c(d!())?;
+        self.encrypted_balance = encrypted_balance;
+        self.balance = balance;
+        self.blind = blind;
+        Ok(())
+    }
+
+    /// Prove that the balance is at least `amount`, without revealing the balance.
+    pub fn prove_balance<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        params: &BulletproofParams,
+        amount: u64,
+    ) -> Result<BalanceProof> {
+        let difference = self
+            .balance
+            .checked_sub(amount)
+            .c(d!(ZeiError::RangeProofProveError))?;
+        // the limbs of the difference are linked to the blind of the balance
+        let opening = AmountOpening::split(prng, difference, &self.blind);
+        let (low, high) = opening.limbs();
+
+        let mut transcript = self.encrypted_balance.transcript(amount);
+        let (range_proof, coms) = prove_ranges_with_rng(
+            prng,
+            &params.bp_gens,
+            &mut transcript,
+            &[low as u64, high as u64],
+            &[opening.blinds.0, opening.blinds.1],
+            BULLET_PROOF_RANGE,
+        )
+        .c(d!(ZeiError::RangeProofProveError))?;
+        Ok(BalanceProof {
+            difference_commitments: (coms[0], coms[1]),
+            range_proof,
+        })
+    }
+
+    /// Save the account to a writer.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        bincode::serialize_into(writer, self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Load an account from a reader, checking that the balance and the blinding factor open
+    /// the encrypted balance.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<Account> {
+        let account: Account =
+            bincode::deserialize_from(reader).c(d!(ZeiError::DeserializationError))?;
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let commitment = pc_gens.commit(RistrettoScalar::from(account.balance), account.blind);
+        if commitment.compress() != account.encrypted_balance.commitment {
+            return Err(eg!(ZeiError::InconsistentStructureError));
+        }
+        Ok(account)
+    }
+}
+
+/// Return the commitment to an amount, as the commitments to its lower and higher 32 bits
+/// combined into `amount * G + (blind_low + 2^32 * blind_high) * H`.
+fn amount_commitment(
+    pc_gens: &PedersenCommitmentRistretto,
+    amount: &XfrAmount,
+) -> Result<RistrettoPoint> {
+    match amount {
+        XfrAmount::Confidential((low, high)) => AmountCommitment {
+            low: *low,
+            high: *high,
+        }
+        .link()
+        .c(d!()),
+        XfrAmount::NonConfidential(amount) => Ok(link_limb_commitments(
+            &AmountOpening::non_confidential(*amount).commit(pc_gens),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::account::{Account, EncryptedBalance};
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType},
+    };
+    use ark_std::{rand::rngs::StdRng, test_rng};
+
+    #[test]
+    fn account_balance() {
+        let mut prng = test_rng();
+        let params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(1);
+        let holder = XfrKeyPair::generate(&mut prng);
+        let payee = XfrKeyPair::generate(&mut prng);
+
+        let record = |prng: &mut StdRng, amount, record_type, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                keypair.get_pk(),
+            );
+            AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap()
+        };
+
+        let mut account = Account::new(holder.get_pk(), asset_type);
+        let mut public = EncryptedBalance::new(holder.get_pk(), asset_type);
+        for (amount, record_type) in [
+            (
+                100,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            ),
+            (
+                50,
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            ),
+            (
+                u64::MAX - 200,
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+            ),
+        ] {
+            let incoming = record(&mut prng, amount, record_type, &holder);
+            let tx = account
+                .receive(
+                    &holder,
+                    &incoming.open_asset_record.blind_asset_record,
+                    &incoming.owner_memo,
+                )
+                .unwrap();
+            public.apply(&tx).unwrap();
+        }
+        assert_eq!(account.balance(), u64::MAX - 50);
+        assert_eq!(account.encrypted_balance(), &public);
+
+        // the balance cannot overflow
+        let incoming = record(
+            &mut prng,
+            51,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            &holder,
+        );
+        assert!(account
+            .receive(
+                &holder,
+                &incoming.open_asset_record.blind_asset_record,
+                &incoming.owner_memo,
+            )
+            .is_err());
+
+        let outgoing = record(
+            &mut prng,
+            u64::MAX - 60,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            &payee,
+        );
+        let tx = account.spend(&outgoing.open_asset_record).unwrap();
+        public.apply(&tx).unwrap();
+        assert_eq!(account.balance(), 10);
+        assert_eq!(account.encrypted_balance(), &public);
+
+        // the balance is proven against the public commitment only
+        for amount in [0, 10] {
+            let proof = account.prove_balance(&mut prng, &params, amount).unwrap();
+            assert!(public
+                .verify_balance_proof(&mut prng, &params, amount, &proof)
+                .is_ok());
+        }
+        assert!(account.prove_balance(&mut prng, &params, 11).is_err());
+        let proof = account.prove_balance(&mut prng, &params, 5).unwrap();
+        assert!(public
+            .verify_balance_proof(&mut prng, &params, 6, &proof)
+            .is_err());
+
+        // the balance cannot go negative
+        let outgoing = record(
+            &mut prng,
+            11,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            &payee,
+        );
+        assert!(account.spend(&outgoing.open_asset_record).is_err());
+        assert_eq!(account.balance(), 10);
+
+        // records of another asset type are rejected
+        let other = AssetRecordTemplate::with_no_asset_tracing(
+            5,
+            AssetType::from_identical_byte(2),
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            holder.get_pk(),
+        );
+        let other = AssetRecord::from_template_no_identity_tracing(&mut prng, &other).unwrap();
+        assert!(account
+            .receive(
+                &holder,
+                &other.open_asset_record.blind_asset_record,
+                &other.owner_memo,
+            )
+            .is_err());
+
+        // the state is persisted and restored
+        let mut bytes = vec![];
+        account.save_to_writer(&mut bytes).unwrap();
+        let restored = Account::load_from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(restored, account);
+        let proof = restored.prove_balance(&mut prng, &params, 10).unwrap();
+        assert!(public
+            .verify_balance_proof(&mut prng, &params, 10, &proof)
+            .is_ok());
+
+        // a state whose balance does not open the commitment is rejected
+        let mut tampered = account;
+        tampered.balance = 11;
+        let mut bytes = vec![];
+        tampered.save_to_writer(&mut bytes).unwrap();
+        assert!(Account::load_from_reader(bytes.as_slice()).is_err());
+    }
+}
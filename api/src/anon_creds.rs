@@ -1,3 +1,5 @@
+use crate::xfr::sig::{XfrKeyPair, XfrPublicKey, XfrSignature};
+use sha2::{Digest, Sha256};
 use zei_algebra::{
     bls12_381::{BLSPairingEngine, BLSScalar, BLSG1, BLSG2},
     prelude::*,
@@ -5,7 +7,8 @@ use zei_algebra::{
 };
 use zei_crypto::{
     anon_creds::{Attribute, CommOutput},
-    basic::elgamal::elgamal_key_gen,
+    attributes::{decode_attributes, encode_attributes},
+    basic::{elgamal::elgamal_key_gen_precomp, fixed_base::FixedBasePrecomp},
 };
 
 type G1 = BLSG1;
@@ -36,8 +39,51 @@ pub type Credential = zei_crypto::anon_creds::Credential<G1, G2, Attr>;
 pub type ACRevealProof = zei_crypto::anon_creds::CredentialCommOpenProof<G2, S>;
 /// The confidential opening proof.
 pub type ACConfidentialRevealProof = zei_crypto::confidential_anon_creds::CACPoK<G1, G2, S>;
-/// The attribute types.
-pub type Attr = u32;
+/// The confidential opening proof for a designated verifier.
+pub type ACDesignatedRevealProof = zei_crypto::confidential_anon_creds::CACDesignatedPoK<G1, G2, S>;
+/// The attribute types. Each attribute of a credential is encoded into a single scalar, so that
+/// the reveal maps and the verifiers address the attributes one by one, see
+/// [`ac_attrs_to_scalars`]: the strings have at most 29 bytes, and the 32-byte values, which
+/// take two scalars, are rejected.
+pub type Attr = zei_crypto::attributes::Attribute;
+
+/// Encode the attributes of a credential into their scalars, one per attribute.
+/// # Example
+/// ```
+/// use zei::anon_creds::{ac_attrs_from_scalars, ac_attrs_to_scalars, Attr};
+/// let attrs = vec![Attr::U64(7), Attr::Utf8("Alice".to_string())];
+/// let scalars = ac_attrs_to_scalars(&attrs).unwrap();
+/// assert_eq!(ac_attrs_from_scalars(&scalars).unwrap(), attrs);
+/// // a 32-byte value takes two scalars
+/// assert!(ac_attrs_to_scalars(&[Attr::Bytes32([0u8; 32])]).is_err());
+/// ```
+pub fn ac_attrs_to_scalars(attrs: &[Attr]) -> Result<Vec<S>> {
+    let scalars = encode_attributes::<S>(attrs).c(d!())?;
+    if scalars.len() != attrs.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    Ok(scalars)
+}
+
+/// Decode the attributes of a credential from their scalars, e.g., as decrypted by a tracer.
+pub fn ac_attrs_from_scalars(scalars: &[S]) -> Result<Vec<Attr>> {
+    let attrs = decode_attributes(scalars).c(d!())?;
+    if attrs.len() != scalars.len() {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    Ok(attrs)
+}
+
+/// Return the credential with the scalars of its attributes.
+fn credential_scalars(
+    credential: &Credential,
+) -> Result<zei_crypto::anon_creds::Credential<G1, G2, S>> {
+    Ok(zei_crypto::anon_creds::Credential {
+        sig: credential.sig.clone(),
+        attrs: ac_attrs_to_scalars(&credential.attrs).c(d!())?,
+        ipk: credential.ipk.clone(),
+    })
+}
 
 /// Generate e key pair for a credential issuer.
 /// # Example
@@ -77,14 +123,14 @@ pub fn ac_keygen_user<R: CryptoRng + RngCore>(
 /// ```
 /// use rand_core::SeedableRng;
 /// use rand_chacha::ChaChaRng;
-/// use zei::anon_creds::{ac_keygen_issuer,ac_keygen_user, ac_sign};
+/// use zei::anon_creds::{ac_keygen_issuer,ac_keygen_user, ac_sign, Attr};
 /// use zei_algebra::bls12_381::BLSScalar;
 /// use zei_algebra::traits::Scalar;
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let num_attrs = 2;
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, num_attrs);
 /// let (_, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
-/// let attributes = vec![1u32, 2];
+/// let attributes = vec![Attr::U64(1), Attr::Utf8("Alice".to_string())];
 /// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attributes[..]);
 /// ```
 pub fn ac_sign<R: CryptoRng + RngCore>(
@@ -93,7 +139,7 @@ pub fn ac_sign<R: CryptoRng + RngCore>(
     user_pk: &ACUserPublicKey,
     attrs: &[Attr],
 ) -> Result<ACSignature> {
-    let attrs_scalar: Vec<BLSScalar> = attrs.iter().map(|x| BLSScalar::from(*x)).collect();
+    let attrs_scalar = ac_attrs_to_scalars(attrs).c(d!())?;
     zei_crypto::anon_creds::grant_credential::<_, BLSPairingEngine>(
         prng,
         issuer_sk,
@@ -121,15 +167,15 @@ pub fn ac_keygen_commitment<R: CryptoRng + RngCore>(prng: &mut R) -> ACCommitmen
 /// ```
 /// use rand_core::SeedableRng;
 /// use rand_chacha::ChaChaRng;
-/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit, Credential};
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit, Attr, Credential};
 /// use zei_algebra::bls12_381::BLSScalar;
 /// use zei_algebra::traits::Scalar;
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let num_attrs = 2;
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, num_attrs);
 /// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
-/// let attr1 = 10;
-/// let attr2 = 20;
+/// let attr1 = Attr::U64(10);
+/// let attr2 = Attr::Date { year: 2000, month: 1, day: 1 };
 /// let attributes = vec![attr1, attr2];
 /// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, attributes.as_slice()).unwrap();
 /// let credential = Credential {
@@ -151,15 +197,7 @@ pub fn ac_commit<R: CryptoRng + RngCore>(
         <BLSPairingEngine as Pairing>::ScalarField,
     >,
 > {
-    let c = zei_crypto::anon_creds::Credential {
-        sig: credential.sig.clone(),
-        attrs: credential
-            .attrs
-            .iter()
-            .map(|x| BLSScalar::from(*x))
-            .collect_vec(),
-        ipk: credential.ipk.clone(),
-    };
+    let c = credential_scalars(credential).c(d!())?;
     zei_crypto::anon_creds::commit_without_randomizer::<_, BLSPairingEngine>(prng, user_sk, &c, msg)
         .c(d!())
 }
@@ -169,14 +207,14 @@ pub fn ac_commit<R: CryptoRng + RngCore>(
 /// ```
 /// use rand_core::SeedableRng;
 /// use rand_chacha::ChaChaRng;
-/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit, ac_keygen_commitment, ac_commit_with_key, Credential};
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit, ac_keygen_commitment, ac_commit_with_key, Attr, Credential};
 /// use zei_algebra::bls12_381::BLSScalar;
 /// use zei_algebra::traits::Scalar;
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let num_attrs = 2;
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, num_attrs);
 /// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
-/// let attributes = vec![10u32, 20u32];
+/// let attributes = vec![Attr::U64(10), Attr::U64(20)];
 /// let signature = ac_sign::<ChaChaRng, >(&mut prng, &issuer_sk, &user_pk, &attributes[..]).unwrap();
 /// let credential = Credential{
 ///   sig:signature,
@@ -200,15 +238,7 @@ pub fn ac_commit_with_key<R: CryptoRng + RngCore>(
         <BLSPairingEngine as Pairing>::ScalarField,
     >,
 > {
-    let c = zei_crypto::anon_creds::Credential {
-        sig: credential.sig.clone(),
-        attrs: credential
-            .attrs
-            .iter()
-            .map(|x| BLSScalar::from(*x))
-            .collect_vec(),
-        ipk: credential.ipk.clone(),
-    };
+    let c = credential_scalars(credential).c(d!())?;
     zei_crypto::anon_creds::commit::<_, BLSPairingEngine>(prng, user_sk, &c, key, msg).c(d!())
 }
 
@@ -229,12 +259,12 @@ pub fn ac_verify_commitment(
 /// ```
 /// use rand_core::SeedableRng;
 /// use rand_chacha::ChaChaRng;
-/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_open_commitment, ac_commit, Credential};
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_open_commitment, ac_commit, Attr, Credential};
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let num_attrs = 2;
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer(&mut prng, num_attrs);
 /// let (user_sk, user_pk) = ac_keygen_user(&mut prng, &issuer_pk);
-/// let attributes = vec![10, 20];
+/// let attributes = vec![Attr::U64(10), Attr::U64(20)];
 /// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attributes[..]).unwrap();
 /// let credential = Credential {
 ///   sig:signature,
@@ -252,15 +282,7 @@ pub fn ac_open_commitment<R: CryptoRng + RngCore>(
     rand: &ACCommitmentKey,
     reveal_map: &[bool],
 ) -> Result<ACRevealProof> {
-    let c = zei_crypto::anon_creds::Credential {
-        sig: credential.sig.clone(),
-        attrs: credential
-            .attrs
-            .iter()
-            .map(|a| BLSScalar::from(*a))
-            .collect_vec(),
-        ipk: credential.ipk.clone(),
-    };
+    let c = credential_scalars(credential).c(d!())?;
 
     let cm = ACCommitment::new(&credential.sig, &rand);
 
@@ -275,31 +297,24 @@ pub fn ac_reveal<R: CryptoRng + RngCore>(
     credential: &Credential,
     reveal_bitmap: &[bool],
 ) -> Result<ACRevealSig> {
-    let c = zei_crypto::anon_creds::Credential {
-        sig: credential.sig.clone(),
-        attrs: credential
-            .attrs
-            .iter()
-            .map(|a| BLSScalar::from(*a))
-            .collect_vec(),
-        ipk: credential.ipk.clone(),
-    };
+    let c = credential_scalars(credential).c(d!())?;
     zei_crypto::anon_creds::open_credential::<_, BLSPairingEngine>(prng, user_sk, &c, reveal_bitmap)
         .c(d!())
 }
-/// Verifies an anonymous credential reveal proof.
+/// Verifies an anonymous credential reveal proof, and checks the policy of the verifier on the
+/// issuer key and the revealed attributes. The default policy only checks the proof.
 /// # Example
 /// ```
 /// use rand_core::SeedableRng;
 /// use rand_chacha::ChaChaRng;
 /// use zei_algebra::traits::Scalar;
 /// use zei_algebra::bls12_381::BLSScalar;
-/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_open_commitment, ac_verify, ac_reveal, Credential};
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_open_commitment, ac_verify, ac_reveal, ACVerificationPolicy, Attr, Credential};
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let num_attrs = 2;
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, num_attrs);
 /// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
-/// let attributes = vec![10u32, 20];
+/// let attributes = vec![Attr::U64(10), Attr::U64(20)];
 /// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attributes[..]).unwrap();
 /// let credential = Credential{
 ///   sig:signature,
@@ -308,11 +323,12 @@ pub fn ac_reveal<R: CryptoRng + RngCore>(
 /// };
 /// let bitmap = [true,false]; // Reveal first attribute and hide the second one
 /// let reveal_sig = ac_reveal::<ChaChaRng>(&mut prng, &user_sk, &credential, &bitmap).unwrap();
-/// let attr_map = [Some(10u32), None];
-/// let result_verification_ok = ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open);
+/// let attr_map = [Some(Attr::U64(10)), None];
+/// let policy = ACVerificationPolicy::default();
+/// let result_verification_ok = ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open, &policy);
 /// assert!(result_verification_ok.is_ok());
-/// let attr_map = [None, Some(20)];
-/// let result_verification_err = ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open);
+/// let attr_map = [None, Some(Attr::U64(20))];
+/// let result_verification_err = ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open, &policy);
 /// assert!(result_verification_err.is_err());
 /// ```
 pub fn ac_verify(
@@ -320,14 +336,18 @@ pub fn ac_verify(
     attrs: &[Option<Attr>],
     cm: &ACCommitment,
     proof_open: &ACRevealProof,
+    policy: &ACVerificationPolicy,
 ) -> Result<()> {
-    let attrs_scalar: Vec<Attribute<S>> = attrs
+    policy.check(issuer_pub_key, attrs).c(d!())?;
+    let attrs_scalar = attrs
         .iter()
         .map(|attr| match attr {
-            Some(x) => Attribute::Revealed(BLSScalar::from(*x)),
-            None => Attribute::Hidden(None),
+            Some(x) => Ok(Attribute::Revealed(
+                ac_attrs_to_scalars(std::slice::from_ref(x)).c(d!())?[0],
+            )),
+            None => Ok(Attribute::Hidden(None)),
         })
-        .collect();
+        .collect::<Result<Vec<Attribute<S>>>>()?;
 
     zei_crypto::anon_creds::verify_open::<BLSPairingEngine>(
         issuer_pub_key,
@@ -338,6 +358,91 @@ pub fn ac_verify(
     .c(d!())
 }
 
+/// The identifier of an issuer public key, the SHA-256 digest of its serialization.
+pub type ACIssuerKeyId = [u8; 32];
+
+/// Return the identifier of an issuer public key.
+pub fn ac_issuer_key_id(issuer_pub_key: &ACIssuerPublicKey) -> Result<ACIssuerKeyId> {
+    let bytes = bincode::serialize(issuer_pub_key).c(d!(ZeiError::SerializationError))?;
+    let mut id = [0u8; 32];
+    id.copy_from_slice(&Sha256::digest(&bytes));
+    Ok(id)
+}
+
+/// The policy of the verifier of a reveal proof, checked by `ac_verify` along with the proof.
+/// The default policy checks nothing beyond the proof.
+/// # Example
+/// ```
+/// use rand_core::SeedableRng;
+/// use rand_chacha::ChaChaRng;
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_reveal, ac_issuer_key_id, ac_verify, ACVerificationPolicy, Attr, Credential};
+/// let mut prng = ChaChaRng::from_seed([0u8;32]);
+/// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, 3);
+/// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
+/// // the last attribute is the issuance epoch
+/// let attributes = vec![Attr::U64(10), Attr::U64(20), Attr::U64(7)];
+/// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attributes[..]).unwrap();
+/// let credential = Credential {
+///   sig: signature,
+///   attrs: attributes,
+///   ipk: issuer_pk.clone(),
+/// };
+/// let reveal_sig = ac_reveal::<ChaChaRng>(&mut prng, &user_sk, &credential, &[true, false, true]).unwrap();
+/// let attr_map = [Some(Attr::U64(10)), None, Some(Attr::U64(7))];
+///
+/// let mut policy = ACVerificationPolicy {
+///   issuer_key_id: Some(ac_issuer_key_id(&issuer_pk).unwrap()),
+///   min_issuance_epoch: Some((2, 5)),
+///   required_attrs: vec![0],
+/// };
+/// assert!(ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open, &policy).is_ok());
+///
+/// // the credential is too old
+/// policy.min_issuance_epoch = Some((2, 8));
+/// assert!(ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open, &policy).is_err());
+///
+/// // the second attribute is hidden
+/// policy.min_issuance_epoch = None;
+/// policy.required_attrs = vec![0, 1];
+/// assert!(ac_verify(&issuer_pk, &attr_map, &reveal_sig.cm, &reveal_sig.proof_open, &policy).is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ACVerificationPolicy {
+    /// The identifier of the expected issuer key, if any.
+    pub issuer_key_id: Option<ACIssuerKeyId>,
+    /// The position of the attribute that holds the issuance epoch, and the minimum epoch,
+    /// if any. The epoch must be revealed, as an integer attribute.
+    pub min_issuance_epoch: Option<(usize, u64)>,
+    /// The positions of the attributes that must be revealed.
+    pub required_attrs: Vec<usize>,
+}
+
+impl ACVerificationPolicy {
+    /// Check the policy against the issuer key and the revealed attributes.
+    pub fn check(&self, issuer_pub_key: &ACIssuerPublicKey, attrs: &[Option<Attr>]) -> Result<()> {
+        if let Some(id) = self.issuer_key_id {
+            if ac_issuer_key_id(issuer_pub_key).c(d!())? != id {
+                return Err(eg!(ZeiError::IdentityRevealVerifyError));
+            }
+        }
+        for position in self.required_attrs.iter() {
+            match attrs.get(*position) {
+                Some(Some(_)) => {}
+                Some(None) => return Err(eg!(ZeiError::IdentityRevealVerifyError)),
+                None => return Err(eg!(ZeiError::ParameterError)),
+            }
+        }
+        if let Some((position, min_epoch)) = self.min_issuance_epoch {
+            match attrs.get(position) {
+                Some(Some(Attr::U64(epoch))) if *epoch >= min_epoch => {}
+                Some(_) => return Err(eg!(ZeiError::IdentityRevealVerifyError)),
+                None => return Err(eg!(ZeiError::ParameterError)),
+            }
+        }
+        Ok(())
+    }
+}
+
 /// The attribute encryption key.
 pub type AttributeEncKey = zei_crypto::basic::elgamal::ElGamalEncKey<G1>;
 /// The attribute decryption key.
@@ -347,6 +452,9 @@ pub type AttributeCiphertext = zei_crypto::basic::elgamal::ElGamalCiphertext<G1>
 
 /// Confidential anonymous credential
 pub type ConfidentialAC = zei_crypto::confidential_anon_creds::ConfidentialAC<G1, G2, S>;
+/// Confidential anonymous credential for a designated verifier
+pub type DesignatedConfidentialAC =
+    zei_crypto::confidential_anon_creds::DesignatedConfidentialAC<G1, G2, S>;
 
 /// Produce a confidential anonymous credential revealing proof.
 /// # Example
@@ -357,12 +465,12 @@ pub type ConfidentialAC = zei_crypto::confidential_anon_creds::ConfidentialAC<G1
 /// use rand_core::SeedableRng;
 /// use zei_algebra::bls12_381::{BLSScalar, BLSG1};
 /// use zei_algebra::traits::Group;
-/// use zei::anon_creds::Credential;
+/// use zei::anon_creds::{Attr, Credential};
 /// let mut prng = ChaChaRng::from_seed([0u8;32]);
 /// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, 3);
 /// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
 /// let (_, enc_key) = ac_confidential_gen_encryption_keys::<ChaChaRng>(&mut prng);
-/// let attrs = vec![10, 20, 30];
+/// let attrs = vec![Attr::U64(10), Attr::U64(20), Attr::U64(30)];
 /// let bitmap = [false, true, false];
 /// let ac_sig = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attrs[..]).unwrap();
 /// let credential = Credential {
@@ -383,16 +491,7 @@ pub fn ac_confidential_open_commitment<R: CryptoRng + RngCore>(
     reveal_map: &[bool],
     msg: &[u8],
 ) -> Result<ConfidentialAC> {
-    let attrs_scalar = credential
-        .attrs
-        .iter()
-        .map(|x| BLSScalar::from(*x))
-        .collect_vec();
-    let c = zei_crypto::anon_creds::Credential {
-        sig: credential.sig.clone(),
-        attrs: attrs_scalar,
-        ipk: credential.ipk.clone(),
-    };
+    let c = credential_scalars(credential).c(d!())?;
     let cm = ACCommitment::new(&credential.sig, &rand);
     zei_crypto::confidential_anon_creds::confidential_open_comm::<R, BLSPairingEngine>(
         prng, usk, &c, &cm, rand, reveal_map, enc_key, msg,
@@ -422,9 +521,217 @@ pub fn ac_confidential_verify(
     .c(d!())
 }
 
+/// Produce a confidential anonymous credential revealing proof that only convinces the verifier
+/// with the encryption key `verifier_key`, who cannot show it to third parties.
+/// # Example
+/// ```
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_commit, Attr, Credential};
+/// use zei::anon_creds::{ac_designated_confidential_open_commitment, ac_designated_confidential_verify, ac_confidential_gen_encryption_keys};
+/// use rand_chacha::ChaChaRng;
+/// use rand_core::SeedableRng;
+/// let mut prng = ChaChaRng::from_seed([0u8;32]);
+/// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, 3);
+/// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
+/// let (_, enc_key) = ac_confidential_gen_encryption_keys::<ChaChaRng>(&mut prng);
+/// let (_, verifier_key) = ac_confidential_gen_encryption_keys::<ChaChaRng>(&mut prng);
+/// let attrs = vec![Attr::U64(10), Attr::U64(20), Attr::U64(30)];
+/// let bitmap = [false, true, false];
+/// let ac_sig = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attrs[..]).unwrap();
+/// let credential = Credential {
+///   sig: ac_sig,
+///   attrs: attrs,
+///   ipk: issuer_pk.clone(),
+/// };
+/// let (sig_commitment,_,key) = ac_commit::<ChaChaRng>(&mut prng, &user_sk, &credential, b"Address").unwrap();
+/// let reveal = ac_designated_confidential_open_commitment::<ChaChaRng>(&mut prng, &user_sk, &credential, &key.unwrap(), &enc_key, &verifier_key, &bitmap[..], b"Some Message").unwrap();
+/// assert!(ac_designated_confidential_verify(&issuer_pk, &enc_key, &verifier_key, &bitmap[..], &sig_commitment, &reveal.cts, &reveal.pok, b"Some Message").is_ok());
+///
+/// let (_, other_key) = ac_confidential_gen_encryption_keys::<ChaChaRng>(&mut prng);
+/// assert!(ac_designated_confidential_verify(&issuer_pk, &enc_key, &other_key, &bitmap[..], &sig_commitment, &reveal.cts, &reveal.pok, b"Some Message").is_err());
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn ac_designated_confidential_open_commitment<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    usk: &ACUserSecretKey,
+    credential: &Credential,
+    rand: &ACCommitmentKey,
+    enc_key: &AttributeEncKey,
+    verifier_key: &AttributeEncKey,
+    reveal_map: &[bool],
+    msg: &[u8],
+) -> Result<DesignatedConfidentialAC> {
+    let c = credential_scalars(credential).c(d!())?;
+    let cm = ACCommitment::new(&credential.sig, &rand);
+    zei_crypto::confidential_anon_creds::confidential_designated_open_comm::<R, BLSPairingEngine>(
+        prng,
+        usk,
+        &c,
+        &cm,
+        rand,
+        reveal_map,
+        enc_key,
+        verifier_key,
+        msg,
+    )
+    .c(d!())
+}
+
+/// Verify a confidential anonymous credential revealing proof for the designated verifier with
+/// the encryption key `verifier_key`.
+#[allow(clippy::too_many_arguments)]
+pub fn ac_designated_confidential_verify(
+    issuer_pk: &ACIssuerPublicKey,
+    enc_key: &AttributeEncKey,
+    verifier_key: &AttributeEncKey,
+    reveal_map: &[bool],
+    sig_commitment: &ACCommitment,
+    attr_ctext: &[AttributeCiphertext],
+    cac_proof: &ACDesignatedRevealProof,
+    msg: &[u8],
+) -> Result<()> {
+    zei_crypto::confidential_anon_creds::confidential_designated_verify_open::<BLSPairingEngine>(
+        issuer_pk,
+        enc_key,
+        verifier_key,
+        reveal_map,
+        sig_commitment,
+        attr_ctext,
+        cac_proof,
+        msg,
+    )
+    .c(d!())
+}
+
 /// Generate encryptiion key for confidential anonymous credentials.
 pub fn ac_confidential_gen_encryption_keys<R: CryptoRng + RngCore>(
     prng: &mut R,
 ) -> (AttributeDecKey, AttributeEncKey) {
-    elgamal_key_gen::<_, G1>(prng)
+    elgamal_key_gen_precomp(prng, &G1_BASE_PRECOMP).unwrap() // safe unwrap
+}
+
+lazy_static! {
+    /// The table of the base of G1, whose generic multiplication branches on the scalar bits.
+    static ref G1_BASE_PRECOMP: FixedBasePrecomp<G1> = FixedBasePrecomp::new(&G1::get_base());
+}
+
+const XFR_KEY_BINDING_DOMAIN: &[u8] = b"Zei AC XfrPublicKey binding";
+
+/// A commitment to a credential bound to an `XfrPublicKey`, e.g., of the sender of a transfer.
+/// The proof of knowledge of the credential is made over the public key, and the key signs
+/// the commitment, so that neither can be reused with another key or another credential.
+/// The attributes of the credential are then revealed from the commitment with
+/// `ac_open_commitment`, and checked with `ac_verify_bound_attributes`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ACXfrKeyBinding {
+    /// The commitment to the credential.
+    pub commitment: ACCommitment,
+    /// The proof of knowledge of the committed credential, over the public key.
+    pub pok: ACPoK,
+    /// The signature of the public key over the commitment.
+    pub signature: XfrSignature,
+}
+
+/// Return the message of the proof of knowledge of a binding to `pub_key`.
+fn xfr_key_binding_pok_message(pub_key: &XfrPublicKey) -> Vec<u8> {
+    let mut msg = XFR_KEY_BINDING_DOMAIN.to_vec();
+    msg.extend_from_slice(&pub_key.zei_to_bytes());
+    msg
+}
+
+/// Return the message signed by the public key of a binding to `commitment`.
+fn xfr_key_binding_sig_message(commitment: &ACCommitment) -> Result<Vec<u8>> {
+    let mut msg = XFR_KEY_BINDING_DOMAIN.to_vec();
+    msg.extend_from_slice(&bincode::serialize(commitment).c(d!(ZeiError::SerializationError))?);
+    Ok(msg)
+}
+
+/// Commit to a credential and bind the commitment to the public key of `keypair`.
+/// Return the binding and the key of the commitment, for revealing attributes later.
+/// # Example
+/// ```
+/// use rand_core::SeedableRng;
+/// use rand_chacha::ChaChaRng;
+/// use zei::anon_creds::{ac_keygen_issuer, ac_keygen_user, ac_sign, ac_bind_to_xfr_key, ac_verify_xfr_key_binding, ac_open_commitment, ac_verify_bound_attributes, ACVerificationPolicy, Attr, Credential};
+/// use zei::xfr::sig::XfrKeyPair;
+/// let mut prng = ChaChaRng::from_seed([0u8;32]);
+/// let (issuer_sk, issuer_pk) = ac_keygen_issuer::<ChaChaRng>(&mut prng, 2);
+/// let (user_sk, user_pk) = ac_keygen_user::<ChaChaRng>(&mut prng, &issuer_pk);
+/// let attributes = vec![Attr::U64(10), Attr::U64(20)];
+/// let signature = ac_sign::<ChaChaRng>(&mut prng, &issuer_sk, &user_pk, &attributes[..]).unwrap();
+/// let credential = Credential {
+///   sig: signature,
+///   attrs: attributes,
+///   ipk: issuer_pk.clone(),
+/// };
+/// let sender = XfrKeyPair::generate(&mut prng);
+/// let (binding, key) = ac_bind_to_xfr_key(&mut prng, &user_sk, &credential, &sender).unwrap();
+/// assert!(ac_verify_xfr_key_binding(&issuer_pk, &sender.get_pk(), &binding).is_ok());
+/// let other = XfrKeyPair::generate(&mut prng);
+/// assert!(ac_verify_xfr_key_binding(&issuer_pk, &other.get_pk(), &binding).is_err());
+///
+/// // reveal the first attribute only
+/// let proof = ac_open_commitment::<ChaChaRng>(&mut prng, &user_sk, &credential, &key, &[true, false]).unwrap();
+/// let policy = ACVerificationPolicy::default();
+/// assert!(ac_verify_bound_attributes(&issuer_pk, &sender.get_pk(), &binding, &[Some(Attr::U64(10)), None], &proof, &policy).is_ok());
+/// assert!(ac_verify_bound_attributes(&issuer_pk, &sender.get_pk(), &binding, &[Some(Attr::U64(11)), None], &proof, &policy).is_err());
+/// ```
+pub fn ac_bind_to_xfr_key<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    user_sk: &ACUserSecretKey,
+    credential: &Credential,
+    keypair: &XfrKeyPair,
+) -> Result<(ACXfrKeyBinding, ACCommitmentKey)> {
+    let key = ac_keygen_commitment(prng);
+    let msg = xfr_key_binding_pok_message(&keypair.get_pk());
+    let (commitment, pok, _) = ac_commit_with_key(prng, user_sk, credential, &key, &msg).c(d!())?;
+    let signature = keypair
+        .sign(&xfr_key_binding_sig_message(&commitment).c(d!())?)
+        .c(d!())?;
+    Ok((
+        ACXfrKeyBinding {
+            commitment,
+            pok,
+            signature,
+        },
+        key,
+    ))
+}
+
+/// Verify that a commitment to a credential issued by `issuer_pub_key` is bound to `pub_key`.
+pub fn ac_verify_xfr_key_binding(
+    issuer_pub_key: &ACIssuerPublicKey,
+    pub_key: &XfrPublicKey,
+    binding: &ACXfrKeyBinding,
+) -> Result<()> {
+    let msg = xfr_key_binding_sig_message(&binding.commitment).c(d!())?;
+    pub_key.verify(&msg, &binding.signature).c(d!())?;
+    ac_verify_commitment(
+        issuer_pub_key,
+        &binding.commitment,
+        &binding.pok,
+        &xfr_key_binding_pok_message(pub_key),
+    )
+    .c(d!())
+}
+
+/// Verify that the holder of `pub_key` holds a credential with the revealed attributes:
+/// the binding of the commitment to the key, and the reveal proof of the commitment under
+/// the policy of the verifier.
+pub fn ac_verify_bound_attributes(
+    issuer_pub_key: &ACIssuerPublicKey,
+    pub_key: &XfrPublicKey,
+    binding: &ACXfrKeyBinding,
+    attrs: &[Option<Attr>],
+    proof_open: &ACRevealProof,
+    policy: &ACVerificationPolicy,
+) -> Result<()> {
+    ac_verify_xfr_key_binding(issuer_pub_key, pub_key, binding).c(d!())?;
+    ac_verify(
+        issuer_pub_key,
+        attrs,
+        &binding.commitment,
+        proof_open,
+        policy,
+    )
+    .c(d!())
 }
@@ -3,38 +3,49 @@ use crate::anon_xfr::address_folding::{
     verify_address_folding, AXfrAddressFoldingInstance, AXfrAddressFoldingWitness,
 };
 use crate::anon_xfr::{
-    add_merkle_path_variables, check_asset_amount, check_inputs, check_roots, commit_in_cs,
-    compute_merkle_root_variables,
+    add_merkle_path_variables,
+    audit::{
+        encrypt_to_auditor, encrypt_to_auditor_in_cs, AXfrAuditInfo, AXfrAuditWitness,
+        AXfrAuditorPubKey,
+    },
+    check_asset_amount, check_inputs, check_roots, commit_in_cs, compute_merkle_root_variables,
     keys::{AXfrKeyPair, AXfrPubKey, AXfrSecretKey},
     nullify, nullify_in_cs,
     structs::{
         AccElemVars, AnonAssetRecord, AxfrOwnerMemo, Commitment, MTNode, MTPath, Nullifier,
         OpenAnonAssetRecord, PayeeWitness, PayeeWitnessVars, PayerWitness, PayerWitnessVars,
     },
-    AXfrPlonkPf, TurboPlonkCS, AMOUNT_LEN, ANON_XFR_BP_GENS_LEN, FEE_TYPE,
+    AXfrPlonkPf, AmountVar, TurboPlonkCS, ANON_XFR_BP_GENS_LEN, FEE_TYPE,
 };
 use crate::errors::ZeiError;
-use crate::setup::{ProverParams, VerifierParams};
+use crate::setup::{ParamsId, ProverParams, VerifierParams};
 use digest::{consts::U64, Digest};
 use merlin::Transcript;
 #[cfg(feature = "parallel")]
 use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubScalar, prelude::*};
 use zei_crypto::basic::rescue::RescueInstance;
 use zei_plonk::plonk::{
+    accumulation::{PlonkAccumulation, PlonkAccumulator},
     constraint_system::{TurboCS, VarIndex},
     prover::prover_with_lagrange,
     verifier::verifier,
 };
 
 /// The domain separator for anonymous transfer, for the Plonk proof.
-const ANON_XFR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Plonk Proof";
+pub(crate) const ANON_XFR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Plonk Proof";
 /// The domain separator for anonymous transfer, for address folding.
-const ANON_XFR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Folding Proof";
+pub(crate) const ANON_XFR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"Anon Xfr Folding Proof";
 /// The domain separator for the number of inputs.
-const N_INPUTS_TRANSCRIPT: &[u8] = b"Number of input ABARs";
+pub(crate) const N_INPUTS_TRANSCRIPT: &[u8] = b"Number of input ABARs";
 /// The domain separator for the number of outputs.
-const N_OUTPUTS_TRANSCRIPT: &[u8] = b"Number of output ABARs";
+pub(crate) const N_OUTPUTS_TRANSCRIPT: &[u8] = b"Number of output ABARs";
+/// The label of the point of a next owner memo, in the folding transcript.
+pub(crate) const NEXT_OWNER_MEMO_POINT_LABEL: &[u8] = b"next owner memo point";
+/// The label of the ciphertext of a next owner memo, in the folding transcript.
+pub(crate) const NEXT_OWNER_MEMO_LABEL: &[u8] = b"next owner memo";
+/// The label of an output without a next owner memo, in the folding transcript.
+pub(crate) const NO_NEXT_OWNER_MEMO_LABEL: &[u8] = b"no next owner memo";
 
 /// Anonymous transfer note.
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
@@ -45,6 +56,9 @@ pub struct AXfrNote {
     pub proof: AXfrPlonkPf,
     /// The address folding instance.
     pub folding_instance: AXfrAddressFoldingInstance,
+    /// The identifier of the parameters that the proof was generated with, which is bound by
+    /// the address folding proof.
+    pub params_id: ParamsId,
 }
 
 /// Anonymous transfer pre-note without proofs and signatures.
@@ -62,8 +76,10 @@ pub struct AXfrPreNote {
 /// Anonymous transfer body.
 pub struct AXfrBody {
     /// The inputs, in terms of nullifiers.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub inputs: Vec<Nullifier>,
     /// The outputs, in terms of new anonymous asset records.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub outputs: Vec<AnonAssetRecord>,
     /// The Merkle tree root.
     pub merkle_root: BLSScalar,
@@ -72,10 +88,96 @@ pub struct AXfrBody {
     /// The amount of fee.
     pub fee: u32,
     /// The owner memos.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub owner_memos: Vec<AxfrOwnerMemo>,
+    /// The copies of the owner memos for the next receiving keys of the receivers that are
+    /// rotating their keys, one per output, or empty if no receiver is rotating its keys.
+    #[serde(default, deserialize_with = "zei_bounded_serde::outputs")]
+    pub next_owner_memos: Vec<Option<AxfrOwnerMemo>>,
+    /// The encryption of the outputs to the auditor, for auditable transfers.
+    #[serde(default)]
+    pub audit: Option<AXfrAuditInfo>,
+}
+
+impl AXfrBody {
+    /// Return the public inputs of the Plonk proof for the body, given the Merkle root.
+    fn pub_inputs(&self, merkle_root: &BLSScalar) -> core::result::Result<AXfrPubInputs, ZeiError> {
+        if !self.next_owner_memos.is_empty() && self.next_owner_memos.len() != self.outputs.len() {
+            return Err(ZeiError::LengthMismatch {
+                expected: self.outputs.len(),
+                actual: self.next_owner_memos.len(),
+            });
+        }
+        if let Some(audit) = &self.audit {
+            if audit.memos.len() != self.outputs.len() {
+                return Err(ZeiError::LengthMismatch {
+                    expected: self.outputs.len(),
+                    actual: audit.memos.len(),
+                });
+            }
+        }
+        Ok(AXfrPubInputs {
+            payers_inputs: self.inputs.clone(),
+            payees_commitments: self
+                .outputs
+                .iter()
+                .map(|output| output.commitment)
+                .collect(),
+            merkle_root: *merkle_root,
+            fee: self.fee,
+            audit: self.audit.clone(),
+        })
+    }
+
+    /// Start the transcript of the address folding proof. The identifier of the parameters and
+    /// the copies of the owner memos for the next receiving keys are appended to it,
+    /// so that the proof binds them.
+    fn folding_transcript(&self, params_id: &ParamsId) -> Transcript {
+        let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+        params_id.append_to_transcript(&mut transcript);
+        for memo in self.next_owner_memos.iter() {
+            match memo {
+                Some(memo) => {
+                    transcript
+                        .append_message(NEXT_OWNER_MEMO_POINT_LABEL, &memo.point.zei_to_bytes());
+                    transcript.append_message(NEXT_OWNER_MEMO_LABEL, &memo.ctext);
+                }
+                None => transcript.append_message(NO_NEXT_OWNER_MEMO_LABEL, &[]),
+            }
+        }
+        transcript
+    }
+}
+
+/// Compute the amount of the fee-type change output that balances an anonymous transfer,
+/// i.e., the fee-type input amount left after the fee-type outputs and the fee.
+///
+/// The fee is a public input of the circuit, which enforces
+/// `sum(fee-type inputs) = sum(fee-type outputs) + fee`, and the fee type is fixed to
+/// `FEE_TYPE` by the circuit, so that it does not need to be a public input.
+pub fn fee_change_amount(
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    fee: u32,
+) -> Result<u64> {
+    let sum = |records: &[OpenAnonAssetRecord]| -> u128 {
+        records
+            .iter()
+            .filter(|record| record.asset_type == FEE_TYPE)
+            .map(|record| record.amount as u128)
+            .sum()
+    };
+    let change = sum(inputs)
+        .checked_sub(sum(outputs) + fee as u128)
+        .c(d!(ZeiError::XfrCreationAssetAmountError))?;
+    u64::try_from(change).c(d!(ZeiError::XfrCreationAssetAmountError))
 }
 
 /// Build an anonymous transfer note without generating the proof.
+///
+/// The inputs and outputs may have different asset types: for each asset type, the input
+/// amounts must sum to the output amounts, except for the fee type, whose inputs also pay
+/// the fee. At least one input must be of the fee type.
 pub fn init_anon_xfr_note(
     inputs: &[OpenAnonAssetRecord],
     outputs: &[OpenAnonAssetRecord],
@@ -90,9 +192,13 @@ pub fn init_anon_xfr_note(
     check_asset_amount(inputs, outputs, fee).c(d!())?;
     check_roots(inputs).c(d!())?;
 
-    // 2. build input witness information
-    let nullifiers = inputs
-        .iter()
+    // 2. build input witness information, in parallel if the `parallel` feature is on
+    #[cfg(feature = "parallel")]
+    let (inputs_iter, outputs_iter) = (inputs.par_iter(), outputs.par_iter());
+    #[cfg(not(feature = "parallel"))]
+    let (inputs_iter, outputs_iter) = (inputs.iter(), outputs.iter());
+
+    let nullifiers = inputs_iter
         .map(|input| {
             let mt_leaf_info = input.mt_leaf_info.as_ref().unwrap();
             nullify(
@@ -134,16 +240,26 @@ pub fn init_anon_xfr_note(
         payers_witnesses: payers_secrets,
         payees_witnesses: payees_secrets,
         fee,
+        audit: None,
     };
-    let out_abars = outputs
-        .iter()
-        .map(AnonAssetRecord::from_oabar)
-        .collect_vec();
+    let out_abars: Vec<AnonAssetRecord> = outputs_iter.map(AnonAssetRecord::from_oabar).collect();
     let out_memos: Result<Vec<AxfrOwnerMemo>> = outputs
         .iter()
         .map(|output| output.owner_memo.clone().c(d!(ZeiError::ParameterError)))
         .collect();
 
+    let next_owner_memos = if outputs
+        .iter()
+        .any(|output| output.next_owner_memo.is_some())
+    {
+        outputs
+            .iter()
+            .map(|output| output.next_owner_memo.clone())
+            .collect()
+    } else {
+        vec![]
+    };
+
     let mt_info_temp = inputs[0].mt_leaf_info.as_ref().unwrap();
     let body = AXfrBody {
         inputs: nullifiers,
@@ -152,6 +268,8 @@ pub fn init_anon_xfr_note(
         merkle_root_version: mt_info_temp.root_version,
         fee,
         owner_memos: out_memos.c(d!())?,
+        next_owner_memos,
+        audit: None,
     };
 
     Ok(AXfrPreNote {
@@ -161,6 +279,44 @@ pub fn init_anon_xfr_note(
     })
 }
 
+/// Build an auditable anonymous transfer note without generating the proof, in which the amount,
+/// the asset type, and the receiver of each output are encrypted to the auditor,
+/// and the proof shows that the encryption is correct.
+///
+/// The proof must be generated and verified with the parameters of
+/// `ParamsKind::AuditableAnonXfr`.
+pub fn init_auditable_anon_xfr_note<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    fee: u32,
+    input_keypair: &AXfrKeyPair,
+    auditor_pub_key: &AXfrAuditorPubKey,
+) -> Result<AXfrPreNote> {
+    let mut pre_note = init_anon_xfr_note(inputs, outputs, fee, input_keypair).c(d!())?;
+
+    let randomness: Vec<JubjubScalar> =
+        outputs.iter().map(|_| JubjubScalar::random(prng)).collect();
+    let memos = pre_note
+        .witness
+        .payees_witnesses
+        .iter()
+        .zip(randomness.iter())
+        .map(|(payee, r)| encrypt_to_auditor(auditor_pub_key, r, payee))
+        .collect::<Result<Vec<_>>>()
+        .c(d!())?;
+
+    pre_note.body.audit = Some(AXfrAuditInfo {
+        auditor_pub_key: *auditor_pub_key,
+        memos,
+    });
+    pre_note.witness.audit = Some(AXfrAuditWitness {
+        auditor_pub_key: *auditor_pub_key,
+        randomness,
+    });
+    Ok(pre_note)
+}
+
 /// Build an anonymous transfer note without generating the proof.
 pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
     prng: &mut R,
@@ -174,7 +330,8 @@ pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64>
         input_keypair,
     } = pre_note;
 
-    let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+    let params_id = params.id().c(d!())?;
+    let mut transcript = body.folding_transcript(&params_id);
     let (folding_instance, folding_witness) = create_address_folding(
         prng,
         hash,
@@ -188,6 +345,7 @@ pub fn finish_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64>
         body: body,
         proof,
         folding_instance,
+        params_id,
     })
 }
 
@@ -198,29 +356,35 @@ pub fn verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
     merkle_root: &BLSScalar,
     hash: D,
 ) -> Result<()> {
+    check_anon_xfr_note(params, note, merkle_root, hash).map_err(|source| eg!(source))
+}
+
+/// Verify an anonymous transfer note, and return the error of the first check that fails.
+pub(crate) fn check_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    note: &AXfrNote,
+    merkle_root: &BLSScalar,
+    hash: D,
+) -> core::result::Result<(), ZeiError> {
     if *merkle_root != note.body.merkle_root {
-        return Err(eg!(ZeiError::AXfrVerificationError));
+        return Err(ZeiError::AXfrVerificationError);
     }
-    let payees_commitments = note
-        .body
-        .outputs
-        .iter()
-        .map(|output| output.commitment)
-        .collect();
-    let pub_inputs = AXfrPubInputs {
-        payers_inputs: note.body.inputs.clone(),
-        payees_commitments,
-        merkle_root: *merkle_root,
-        fee: note.body.fee,
-    };
+    if note.params_id != params.id().map_err(|_| ZeiError::SerializationError)? {
+        return Err(ZeiError::ParamsIdMismatchError);
+    }
+    let pub_inputs = note.body.pub_inputs(merkle_root)?;
 
-    let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+    let mut transcript = note.body.folding_transcript(&note.params_id);
     let (beta, lambda) = verify_address_folding(
         hash,
         &mut transcript,
         ANON_XFR_BP_GENS_LEN,
         &note.folding_instance,
-    )?;
+    )
+    .map_err(|_| ZeiError::SubProofError {
+        proof: "address folding proof",
+        source: Box::new(ZeiError::ZKProofVerificationError),
+    })?;
 
     let address_folding_public_input =
         prepare_verifier_input(&note.folding_instance, &beta, &lambda);
@@ -231,7 +395,10 @@ pub fn verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
         &note.proof,
         &address_folding_public_input,
     )
-    .c(d!(ZeiError::AXfrVerificationError))
+    .map_err(|_| ZeiError::SubProofError {
+        proof: "Plonk proof",
+        source: Box::new(ZeiError::ZKProofVerificationError),
+    })
 }
 
 /// Batch verify the anonymous transfer notes.
@@ -243,6 +410,13 @@ pub fn batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync +
     merkle_roots: &[&BLSScalar],
     hashes: Vec<D>,
 ) -> Result<()> {
+    if params.len() != notes.len()
+        || merkle_roots.len() != notes.len()
+        || hashes.len() != notes.len()
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
     if merkle_roots
         .par_iter()
         .zip(notes)
@@ -257,20 +431,10 @@ pub fn batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync +
         .zip(merkle_roots)
         .zip(hashes)
         .map(|(((param, note), merkle_root), hash)| {
-            let payees_commitments = note
-                .body
-                .outputs
-                .iter()
-                .map(|output| output.commitment)
-                .collect();
-            let pub_inputs = AXfrPubInputs {
-                payers_inputs: note.body.inputs.clone(),
-                payees_commitments,
-                merkle_root: **merkle_root,
-                fee: note.body.fee,
-            };
+            param.check_id(&note.params_id)?;
+            let pub_inputs = note.body.pub_inputs(merkle_root).map_err(|e| eg!(e))?;
 
-            let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+            let mut transcript = note.body.folding_transcript(&note.params_id);
             let (beta, lambda) = verify_address_folding(
                 hash,
                 &mut transcript,
@@ -297,6 +461,96 @@ pub fn batch_verify_anon_xfr_note<D: Digest<OutputSize = U64> + Default + Sync +
     }
 }
 
+/// Add the Plonk proofs of anonymous transfer notes to the accumulator `previous`, which is
+/// `PlonkAccumulator::new()` for the first notes, so that a block of notes is checked with a
+/// single pairing check, see `zei_plonk::plonk::accumulation`.
+/// The Merkle roots and the address folding proofs are checked here, note by note.
+/// Note: all the verifier parameters must come from the same setup.
+pub fn aggregate_anon_xfr_notes<D: Digest<OutputSize = U64> + Default>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+    previous: &PlonkAccumulator,
+) -> Result<PlonkAccumulator> {
+    let mut accumulation = PlonkAccumulation::new(previous);
+    add_anon_xfr_notes(&mut accumulation, params, notes, merkle_roots, hashes).c(d!())?;
+    Ok(accumulation.finish())
+}
+
+/// Check that `accumulator` adds the Plonk proofs of the anonymous transfer notes to the
+/// accumulator `previous`, as computed by `aggregate_anon_xfr_notes`, and decide it with a
+/// single pairing check.
+pub fn verify_aggregated_anon_xfr_notes<D: Digest<OutputSize = U64> + Default>(
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+    previous: &PlonkAccumulator,
+    accumulator: &PlonkAccumulator,
+) -> Result<()> {
+    let pcs = &params.first().c(d!(ZeiError::ParameterError))?.pcs;
+    if aggregate_anon_xfr_notes(params, notes, merkle_roots, hashes, previous).c(d!())?
+        != *accumulator
+    {
+        return Err(eg!(ZeiError::AXfrVerificationError));
+    }
+    accumulator
+        .decide(pcs)
+        .c(d!(ZeiError::AXfrVerificationError))
+}
+
+fn add_anon_xfr_notes<D: Digest<OutputSize = U64> + Default>(
+    accumulation: &mut PlonkAccumulation,
+    params: &[&VerifierParams],
+    notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+) -> Result<()> {
+    if notes.is_empty()
+        || params.len() != notes.len()
+        || merkle_roots.len() != notes.len()
+        || hashes.len() != notes.len()
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    for (((param, note), merkle_root), hash) in
+        params.iter().zip(notes).zip(merkle_roots).zip(hashes)
+    {
+        if **merkle_root != note.body.merkle_root {
+            return Err(eg!(ZeiError::AXfrVerificationError));
+        }
+        param.check_id(&note.params_id).c(d!())?;
+        let pub_inputs = note.body.pub_inputs(merkle_root).map_err(|e| eg!(e))?;
+
+        let mut transcript = note.body.folding_transcript(&note.params_id);
+        let (beta, lambda) = verify_address_folding(
+            hash,
+            &mut transcript,
+            ANON_XFR_BP_GENS_LEN,
+            &note.folding_instance,
+        )?;
+
+        let address_folding_public_input =
+            prepare_verifier_input(&note.folding_instance, &beta, &lambda);
+
+        let (mut transcript, online_inputs) =
+            prepare_xfr_verification(&pub_inputs, &address_folding_public_input);
+        accumulation
+            .add_proof(
+                &mut transcript,
+                &param.pcs,
+                &param.cs,
+                &param.verifier_params,
+                &online_inputs,
+                &note.proof,
+            )
+            .c(d!(ZeiError::AXfrVerificationError))?;
+    }
+    Ok(())
+}
+
 /// Generate a Plonk proof for anonymous transfer.
 pub(crate) fn prove_xfr<R: CryptoRng + RngCore>(
     rng: &mut R,
@@ -315,7 +569,7 @@ pub(crate) fn prove_xfr<R: CryptoRng + RngCore>(
     );
 
     let fee_type = FEE_TYPE.as_scalar();
-    let (mut cs, _) = build_multi_xfr_cs(secret_inputs, fee_type, &folding_witness);
+    let (mut cs, _) = build_multi_xfr_cs(secret_inputs, fee_type, &folding_witness).c(d!())?;
     let witness = cs.get_and_clear_witness();
 
     prover_with_lagrange(
@@ -337,15 +591,8 @@ pub(crate) fn verify_xfr(
     proof: &AXfrPlonkPf,
     address_folding_public_input: &Vec<BLSScalar>,
 ) -> Result<()> {
-    let mut transcript = Transcript::new(ANON_XFR_PLONK_PROOF_TRANSCRIPT);
-    transcript.append_u64(N_INPUTS_TRANSCRIPT, pub_inputs.payers_inputs.len() as u64);
-    transcript.append_u64(
-        N_OUTPUTS_TRANSCRIPT,
-        pub_inputs.payees_commitments.len() as u64,
-    );
-
-    let mut online_inputs = pub_inputs.to_vec();
-    online_inputs.extend_from_slice(address_folding_public_input);
+    let (mut transcript, online_inputs) =
+        prepare_xfr_verification(pub_inputs, address_folding_public_input);
 
     verifier(
         &mut transcript,
@@ -358,6 +605,23 @@ pub(crate) fn verify_xfr(
     .c(d!(ZeiError::ZKProofVerificationError))
 }
 
+/// Prepare the transcript and the online inputs to verify a Plonk proof for anonymous transfer.
+fn prepare_xfr_verification(
+    pub_inputs: &AXfrPubInputs,
+    address_folding_public_input: &[BLSScalar],
+) -> (Transcript, Vec<BLSScalar>) {
+    let mut transcript = Transcript::new(ANON_XFR_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_u64(N_INPUTS_TRANSCRIPT, pub_inputs.payers_inputs.len() as u64);
+    transcript.append_u64(
+        N_OUTPUTS_TRANSCRIPT,
+        pub_inputs.payees_commitments.len() as u64,
+    );
+
+    let mut online_inputs = pub_inputs.to_vec();
+    online_inputs.extend_from_slice(address_folding_public_input);
+    (transcript, online_inputs)
+}
+
 /// The witness of an anonymous transfer.
 #[derive(Debug, Clone)]
 pub struct AXfrWitness {
@@ -367,6 +631,8 @@ pub struct AXfrWitness {
     pub payees_witnesses: Vec<PayeeWitness>,
     /// The fee.
     pub fee: u32,
+    /// The witness of the encryption to the auditor, for auditable transfers.
+    pub audit: Option<AXfrAuditWitness>,
 }
 
 impl AXfrWitness {
@@ -399,6 +665,7 @@ impl AXfrWitness {
             payers_witnesses: vec![payer_witness; n_payers],
             payees_witnesses: vec![payee_witness; n_payees],
             fee,
+            audit: None,
         }
     }
 }
@@ -414,6 +681,8 @@ pub struct AXfrPubInputs {
     pub merkle_root: BLSScalar,
     /// The fee.
     pub fee: u32,
+    /// The audit information, for auditable transfers.
+    pub audit: Option<AXfrAuditInfo>,
 }
 
 impl AXfrPubInputs {
@@ -428,19 +697,37 @@ impl AXfrPubInputs {
             result.push(*comm);
         }
         result.push(BLSScalar::from(self.fee));
+        if let Some(audit) = &self.audit {
+            result.extend(audit.to_public_inputs());
+        }
         result
     }
 
     /// Convert from the witness.
-    pub fn from_witness(witness: &AXfrWitness) -> Self {
+    pub fn from_witness(witness: &AXfrWitness) -> Result<Self> {
+        let payer = witness
+            .payers_witnesses
+            .first()
+            .c(d!(ZeiError::AXfrProverParamsError))?;
+        if let Some(audit) = &witness.audit {
+            if audit.randomness.len() != witness.payees_witnesses.len() {
+                return Err(eg!(ZeiError::LengthMismatch {
+                    expected: witness.payees_witnesses.len(),
+                    actual: audit.randomness.len(),
+                }));
+            }
+        }
+
         let hash = RescueInstance::new();
         let payers_inputs: Vec<Nullifier> = witness
             .payers_witnesses
             .iter()
             .map(|sec| {
                 let keypair = AXfrKeyPair::from_secret_key(sec.secret_key.clone());
-                let public_key_scalars = keypair.get_public_key().get_public_key_scalars().unwrap();
-                let secret_key_scalars = keypair.get_secret_key().get_secret_key_scalars().unwrap();
+                let public_key_scalars =
+                    keypair.get_public_key().get_public_key_scalars().c(d!())?;
+                let secret_key_scalars =
+                    keypair.get_secret_key().get_secret_key_scalars().c(d!())?;
 
                 let pow_2_64 = BLSScalar::from(u64::MAX).add(&BLSScalar::one());
                 let uid_amount = pow_2_64
@@ -452,14 +739,14 @@ impl AXfrPubInputs {
                     public_key_scalars[0],
                     public_key_scalars[1],
                 ])[0];
-                hash.rescue(&[
+                Ok(hash.rescue(&[
                     cur,
                     public_key_scalars[2],
                     secret_key_scalars[0],
                     secret_key_scalars[1],
-                ])[0]
+                ])[0])
             })
-            .collect();
+            .collect::<Result<_>>()?;
 
         let hash = RescueInstance::new();
         let zero = BLSScalar::zero();
@@ -467,7 +754,7 @@ impl AXfrPubInputs {
             .payees_witnesses
             .iter()
             .map(|sec| {
-                let public_key_scalars = sec.public_key.get_public_key_scalars().unwrap();
+                let public_key_scalars = sec.public_key.get_public_key_scalars().c(d!())?;
 
                 let cur = hash.rescue(&[
                     sec.blind,
@@ -475,23 +762,21 @@ impl AXfrPubInputs {
                     sec.asset_type,
                     public_key_scalars[0],
                 ])[0];
-                hash.rescue(&[
+                Ok(hash.rescue(&[
                     cur,
                     public_key_scalars[1],
                     public_key_scalars[2],
                     BLSScalar::zero(),
-                ])[0]
+                ])[0])
             })
-            .collect();
-
-        let payer = &witness.payers_witnesses[0];
+            .collect::<Result<_>>()?;
 
         let commitment = {
             let payer_keypair = AXfrKeyPair::from_secret_key(payer.secret_key.clone());
             let payer_public_key_scalars = payer_keypair
                 .get_public_key()
                 .get_public_key_scalars()
-                .unwrap();
+                .c(d!())?;
 
             let cur = hash.rescue(&[
                 payer.blind,
@@ -516,12 +801,27 @@ impl AXfrPubInputs {
             node = hash.rescue(&input)[0];
         }
 
-        Self {
+        let audit = match witness.audit.as_ref() {
+            Some(audit) => Some(AXfrAuditInfo {
+                auditor_pub_key: audit.auditor_pub_key,
+                memos: witness
+                    .payees_witnesses
+                    .iter()
+                    .zip(audit.randomness.iter())
+                    .map(|(payee, r)| encrypt_to_auditor(&audit.auditor_pub_key, r, payee))
+                    .collect::<Result<_>>()
+                    .c(d!())?,
+            }),
+            None => None,
+        };
+
+        Ok(Self {
             payers_inputs,
             payees_commitments,
             merkle_root: node,
             fee: witness.fee,
-        }
+            audit,
+        })
     }
 }
 
@@ -530,17 +830,27 @@ pub(crate) fn build_multi_xfr_cs(
     witness: AXfrWitness,
     fee_type: BLSScalar,
     folding_witness: &AXfrAddressFoldingWitness,
-) -> (TurboPlonkCS, usize) {
-    assert_ne!(witness.payers_witnesses.len(), 0);
-    assert_ne!(witness.payees_witnesses.len(), 0);
+) -> Result<(TurboPlonkCS, usize)> {
+    zei_algebra::trace_stage!("anonymous transfer circuit");
+    if witness.payers_witnesses.is_empty() || witness.payees_witnesses.is_empty() {
+        return Err(eg!(ZeiError::AXfrProverParamsError));
+    }
+    if let Some(audit) = &witness.audit {
+        if audit.randomness.len() != witness.payees_witnesses.len() {
+            return Err(eg!(ZeiError::LengthMismatch {
+                expected: witness.payees_witnesses.len(),
+                actual: audit.randomness.len(),
+            }));
+        }
+    }
 
     let mut cs = TurboCS::new();
     let payers_secrets = add_payers_witnesses(&mut cs, &witness.payers_witnesses);
     let payees_secrets = add_payees_witnesses(&mut cs, &witness.payees_witnesses);
 
     let keypair = folding_witness.keypair.clone();
-    let public_key_scalars = keypair.get_public_key().get_public_key_scalars().unwrap();
-    let secret_key_scalars = keypair.get_secret_key().get_secret_key_scalars().unwrap();
+    let public_key_scalars = keypair.get_public_key().get_public_key_scalars().c(d!())?;
+    let secret_key_scalars = keypair.get_secret_key().get_secret_key_scalars().c(d!())?;
 
     let public_key_scalars_vars = [
         cs.new_variable(public_key_scalars[0]),
@@ -593,7 +903,7 @@ pub(crate) fn build_multi_xfr_cs(
         let tmp_root_var = compute_merkle_root_variables(&mut cs, acc_elem, &payer.path);
 
         // additional safegaurd to check the payer's amount, although in theory this is not needed.
-        cs.range_check(payer.amount, AMOUNT_LEN);
+        AmountVar::range_checked(&mut cs, payer.amount);
 
         if let Some(root) = root_var {
             cs.equal(root, tmp_root_var);
@@ -618,7 +928,7 @@ pub(crate) fn build_multi_xfr_cs(
         );
 
         // Range check `amount`.
-        cs.range_check(payee.amount, AMOUNT_LEN);
+        AmountVar::range_checked(&mut cs, payee.amount);
 
         // prepare the public input for the output commitment.
         cs.prepare_pi_variable(com_abar_out_var);
@@ -637,13 +947,30 @@ pub(crate) fn build_multi_xfr_cs(
     let fee_var = cs.new_variable(BLSScalar::from(witness.fee));
     cs.prepare_pi_variable(fee_var);
 
+    // encrypt the outputs to the auditor, for auditable transfers.
+    if let Some(audit) = &witness.audit {
+        let auditor_pub_key_var = cs.new_extended_point_variable(&audit.auditor_pub_key.0);
+        cs.prepare_pi_variable(auditor_pub_key_var.get_var().get_x());
+        cs.prepare_pi_variable(auditor_pub_key_var.get_var().get_y());
+        for (payee, randomness) in payees_secrets.iter().zip(audit.randomness.iter()) {
+            let randomness_var =
+                cs.new_variable(BLSScalar::from_bytes(&randomness.to_bytes()).c(d!())?);
+            let (ephemeral_key_var, ciphertext_vars) =
+                encrypt_to_auditor_in_cs(&mut cs, payee, randomness_var, &auditor_pub_key_var);
+            cs.prepare_pi_point_variable(ephemeral_key_var);
+            for ciphertext_var in ciphertext_vars {
+                cs.prepare_pi_variable(ciphertext_var);
+            }
+        }
+    }
+
     prove_address_folding_in_cs(
         &mut cs,
         &public_key_scalars_vars,
         &secret_key_scalars_vars,
         &folding_witness,
     )
-    .unwrap();
+    .c(d!())?;
 
     asset_mixing(&mut cs, &inputs, &outputs, fee_type, fee_var);
 
@@ -651,7 +978,7 @@ pub(crate) fn build_multi_xfr_cs(
     cs.pad();
 
     let n_constraints = cs.size;
-    (cs, n_constraints)
+    Ok((cs, n_constraints))
 }
 
 /// Enforce asset_mixing_with_fees constraints:
@@ -845,12 +1172,17 @@ mod tests {
     };
     use crate::anon_xfr::address_folding::{
         create_address_folding, prepare_verifier_input, verify_address_folding,
+        AXfrAddressFoldingWitness,
     };
     use crate::anon_xfr::{
         abar_to_abar::{
-            asset_mixing, build_multi_xfr_cs, verify_anon_xfr_note, AXfrPubInputs, AXfrWitness,
+            aggregate_anon_xfr_notes, asset_mixing, build_multi_xfr_cs, fee_change_amount,
+            verify_aggregated_anon_xfr_notes, verify_anon_xfr_note, AXfrPubInputs, AXfrWitness,
         },
-        add_merkle_path_variables, commit, commit_in_cs, compute_merkle_root_variables,
+        add_merkle_path_variables,
+        audit::{decrypt_audit_memo, AXfrAuditWitness, AXfrAuditorKeyPair},
+        commit, commit_in_cs, compute_merkle_root, compute_merkle_root_variables,
+        key_rotation::AXfrKeyRing,
         keys::AXfrKeyPair,
         nullify_in_cs, sort,
         structs::{
@@ -859,15 +1191,18 @@ mod tests {
         },
         ANON_XFR_BP_GENS_LEN, FEE_TYPE,
     };
-    use crate::setup::{ProverParams, VerifierParams};
+    use crate::setup::{ParamsId, ProverParams, VerifierParams};
     use crate::xfr::structs::AssetType;
     use ark_std::test_rng;
     use digest::{consts::U64, Digest};
     use merlin::Transcript;
     use sha2::Sha512;
-    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubScalar, prelude::*};
     use zei_crypto::basic::rescue::RescueInstance;
-    use zei_plonk::plonk::constraint_system::{TurboCS, VarIndex};
+    use zei_plonk::plonk::{
+        accumulation::PlonkAccumulator,
+        constraint_system::{TurboCS, VarIndex},
+    };
 
     fn gen_keys<R: CryptoRng + RngCore>(prng: &mut R, n: usize) -> Vec<AXfrKeyPair> {
         (0..n).map(|_| AXfrKeyPair::generate(prng)).collect()
@@ -890,6 +1225,17 @@ mod tests {
         oabar
     }
 
+    fn gen_input_oabar<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: u64,
+        asset_type: AssetType,
+        keypair: &AXfrKeyPair,
+    ) -> OpenAnonAssetRecord {
+        let mut oabar = gen_oabar_with_key(prng, amount, asset_type, keypair);
+        oabar.update_mt_leaf_info(MTLeafInfo::default());
+        oabar
+    }
+
     /// Helper function that resembles the original `gen_anon_xfr_note`
     fn gen_anon_xfr_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
         prng: &mut R,
@@ -996,6 +1342,7 @@ mod tests {
                 payers_witnesses: payers_secrets,
                 payees_witnesses: payees_secrets,
                 fee,
+                audit: None,
             },
             input_keypair,
         )
@@ -1115,6 +1462,100 @@ mod tests {
                 verify_anon_xfr_note(&verifier_params, &note, &merkle_root, test_hash.clone())
                     .is_ok()
             );
+
+            let previous = PlonkAccumulator::new();
+            let accumulator = aggregate_anon_xfr_notes(
+                &[&verifier_params, &verifier_params],
+                &[&note, &note],
+                &[&merkle_root, &merkle_root],
+                vec![test_hash.clone(), test_hash.clone()],
+                &previous,
+            )
+            .unwrap();
+            assert!(verify_aggregated_anon_xfr_notes(
+                &[&verifier_params, &verifier_params],
+                &[&note, &note],
+                &[&merkle_root, &merkle_root],
+                vec![test_hash.clone(), test_hash.clone()],
+                &previous,
+                &accumulator,
+            )
+            .is_ok());
+
+            // the accumulator of the next block builds on this one
+            let next = aggregate_anon_xfr_notes(
+                &[&verifier_params],
+                &[&note],
+                &[&merkle_root],
+                vec![test_hash.clone()],
+                &accumulator,
+            )
+            .unwrap();
+            assert_eq!(next.num_proofs(), 3);
+            assert!(verify_aggregated_anon_xfr_notes(
+                &[&verifier_params],
+                &[&note],
+                &[&merkle_root],
+                vec![test_hash.clone()],
+                &accumulator,
+                &next,
+            )
+            .is_ok());
+            assert!(verify_aggregated_anon_xfr_notes(
+                &[&verifier_params],
+                &[&note],
+                &[&merkle_root],
+                vec![test_hash.clone()],
+                &previous,
+                &next,
+            )
+            .is_err());
+
+            let mut bad_note = note.clone();
+            bad_note.body.fee += 1;
+            assert!(aggregate_anon_xfr_notes(
+                &[&verifier_params, &verifier_params],
+                &[&note, &bad_note],
+                &[&merkle_root, &merkle_root],
+                vec![test_hash.clone(), test_hash.clone()],
+                &previous,
+            )
+            .and_then(|accumulator| accumulator.decide(&verifier_params.pcs))
+            .is_err());
+
+            let zero_root = BLSScalar::zero();
+            assert!(aggregate_anon_xfr_notes(
+                &[&verifier_params],
+                &[&note],
+                &[&zero_root],
+                vec![test_hash.clone()],
+                &previous,
+            )
+            .is_err());
+
+            // a note tagged with other parameters is rejected
+            let mut bad_note = note.clone();
+            bad_note.params_id = ParamsId([1u8; 32]);
+            msg_eq!(
+                ZeiError::ParamsIdMismatchError,
+                verify_anon_xfr_note(&verifier_params, &bad_note, &merkle_root, test_hash.clone())
+                    .unwrap_err()
+            );
+            // the copies of the owner memos for the next receiving keys are bound by the proof
+            let mut bad_note = note.clone();
+            bad_note.body.next_owner_memos = vec![Some(note.body.owner_memos[0].clone())];
+            assert!(verify_anon_xfr_note(
+                &verifier_params,
+                &bad_note,
+                &merkle_root,
+                test_hash.clone()
+            )
+            .is_err());
+
+            // the tag is mandatory
+            let mut untagged_note = serde_json::to_value(&note).unwrap();
+            untagged_note.as_object_mut().unwrap().remove("params_id");
+            assert!(serde_json::from_value::<AXfrNote>(untagged_note).is_err());
         }
     }
 
@@ -1344,6 +1785,125 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_init_anon_xfr_note_asset_amounts() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let other_type = AssetType::from_identical_byte(1);
+        let fee = 5u32;
+
+        let inputs = [
+            gen_input_oabar(&mut prng, 10 + fee as u64, FEE_TYPE, &keypair),
+            gen_input_oabar(&mut prng, 20, other_type, &keypair),
+        ];
+
+        // one note moves both asset types
+        let outputs = [
+            gen_oabar_with_key(&mut prng, 10, FEE_TYPE, &keypair),
+            gen_oabar_with_key(&mut prng, 15, other_type, &keypair),
+            gen_oabar_with_key(&mut prng, 5, other_type, &keypair),
+        ];
+        assert!(init_anon_xfr_note(&inputs, &outputs, fee, &keypair).is_ok());
+
+        // each asset type is balanced on its own
+        let outputs = [
+            gen_oabar_with_key(&mut prng, 5, FEE_TYPE, &keypair),
+            gen_oabar_with_key(&mut prng, 25, other_type, &keypair),
+        ];
+        msg_eq!(
+            ZeiError::XfrCreationAssetAmountError,
+            init_anon_xfr_note(&inputs, &outputs, fee, &keypair).unwrap_err()
+        );
+
+        // the fee is paid by an input of the fee type
+        let inputs = [gen_input_oabar(&mut prng, 20, other_type, &keypair)];
+        let outputs = [gen_oabar_with_key(&mut prng, 20, other_type, &keypair)];
+        msg_eq!(
+            ZeiError::XfrCreationAssetAmountError,
+            init_anon_xfr_note(&inputs, &outputs, 0, &keypair).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_init_anon_xfr_note_next_owner_memos() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let inputs = [gen_input_oabar(&mut prng, 30, FEE_TYPE, &keypair)];
+
+        // without a rotation, the note does not carry next owner memos
+        let outputs = [gen_oabar_with_key(&mut prng, 30, FEE_TYPE, &keypair)];
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, 0, &keypair).unwrap();
+        assert!(pre_note.body.next_owner_memos.is_empty());
+
+        // one receiver is rotating its keys
+        let mut ring = AXfrKeyRing::new(AXfrKeyPair::generate(&mut prng));
+        ring.prepare_rotation(&mut prng).unwrap();
+        let outputs = [
+            gen_oabar_with_key(&mut prng, 10, FEE_TYPE, &keypair),
+            OpenAnonAssetRecordBuilder::new()
+                .amount(20)
+                .asset_type(FEE_TYPE)
+                .receiving_keys(&ring.receiving_keys())
+                .finalize(&mut prng)
+                .unwrap()
+                .build()
+                .unwrap(),
+        ];
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, 0, &keypair).unwrap();
+        let body = &pre_note.body;
+        assert_eq!(body.next_owner_memos.len(), 2);
+        assert!(body.next_owner_memos[0].is_none());
+        assert_eq!(body.next_owner_memos[1], outputs[1].get_next_owner_memo());
+
+        // the receiver reads the payment with either generation of its keys
+        assert!(ring.scan_output(body, 0).is_err());
+        let (oabar, _) = ring.scan_output(body, 1).unwrap();
+        assert_eq!(oabar.get_amount(), 20);
+        ring.rotate().unwrap();
+        let mut body = body.clone();
+        body.owner_memos[1] = body.owner_memos[0].clone();
+        let (oabar, owner) = ring.scan_output(&body, 1).unwrap();
+        assert_eq!(oabar.get_amount(), 20);
+        assert_eq!(owner.get_public_key(), *outputs[1].pub_key_ref());
+
+        // the copies must match the outputs
+        body.next_owner_memos.pop();
+        assert_eq!(
+            body.pub_inputs(&BLSScalar::zero()).unwrap_err(),
+            ZeiError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_fee_change_amount() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let other_type = AssetType::from_identical_byte(1);
+        let fee = 5u32;
+
+        let inputs = [
+            gen_input_oabar(&mut prng, 30, FEE_TYPE, &keypair),
+            gen_input_oabar(&mut prng, 20, other_type, &keypair),
+        ];
+        let mut outputs = vec![
+            gen_oabar_with_key(&mut prng, 10, FEE_TYPE, &keypair),
+            gen_oabar_with_key(&mut prng, 20, other_type, &keypair),
+        ];
+        let change = fee_change_amount(&inputs, &outputs, fee).unwrap();
+        assert_eq!(change, 15);
+        outputs.push(gen_oabar_with_key(&mut prng, change, FEE_TYPE, &keypair));
+        assert!(init_anon_xfr_note(&inputs, &outputs, fee, &keypair).is_ok());
+
+        // the fee-type inputs cannot pay for the fee
+        msg_eq!(
+            ZeiError::XfrCreationAssetAmountError,
+            fee_change_amount(&inputs, &outputs, fee).unwrap_err()
+        );
+    }
+
     #[test]
     fn test_asset_mixing() {
         // Fee type
@@ -2116,6 +2676,7 @@ mod tests {
 
         // compute the constraints.
         let path = MTPath::new(vec![path_node2, path_node1]);
+        assert_eq!(compute_merkle_root(1, &two, &path).unwrap(), root);
         let path_vars = add_merkle_path_variables(&mut cs, path);
         let root_var = compute_merkle_root_variables(&mut cs, elem, &path_vars);
 
@@ -2177,6 +2738,29 @@ mod tests {
         assert!(cs.verify_witness(&witness, &[]).is_err());
     }
 
+    #[test]
+    fn test_build_multi_xfr_cs_malformed_witness() {
+        let folding_witness = AXfrAddressFoldingWitness::default();
+
+        // no inputs
+        let witness = AXfrWitness::fake(0, 1, 1, 0);
+        assert!(AXfrPubInputs::from_witness(&witness).is_err());
+        assert!(build_multi_xfr_cs(witness, FEE_TYPE.as_scalar(), &folding_witness).is_err());
+
+        // an auditor randomness per output
+        let mut witness = AXfrWitness::fake(1, 2, 1, 0);
+        witness.audit = Some(AXfrAuditWitness::fake(1));
+        let expected = ZeiError::LengthMismatch {
+            expected: 2,
+            actual: 1,
+        };
+        msg_eq!(expected, AXfrPubInputs::from_witness(&witness).unwrap_err());
+        msg_eq!(
+            expected,
+            build_multi_xfr_cs(witness, FEE_TYPE.as_scalar(), &folding_witness).unwrap_err()
+        );
+    }
+
     #[test]
     fn test_build_multi_xfr_cs() {
         // fee type.
@@ -2245,7 +2829,7 @@ mod tests {
         fee: u32,
     ) {
         let (secret_inputs, keypair) = new_multi_xfr_witness_for_test(inputs, outputs, fee);
-        let pub_inputs = AXfrPubInputs::from_witness(&secret_inputs);
+        let pub_inputs = AXfrPubInputs::from_witness(&secret_inputs).unwrap();
 
         let mut prng = test_rng();
 
@@ -2268,7 +2852,7 @@ mod tests {
         .unwrap();
 
         // check the constraints.
-        let (mut cs, _) = build_multi_xfr_cs(secret_inputs, fee_type, &folding_witness);
+        let (mut cs, _) = build_multi_xfr_cs(secret_inputs, fee_type, &folding_witness).unwrap();
         let witness = cs.get_and_clear_witness();
 
         let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
@@ -2292,4 +2876,67 @@ mod tests {
             assert!(verify.is_err());
         }
     }
+
+    #[test]
+    fn test_build_auditable_xfr_cs() {
+        let mut prng = test_rng();
+        let fee_type = BLSScalar::from(1234u32);
+        let zero = BLSScalar::zero();
+        let inputs = vec![(30, zero), (10, fee_type)];
+        let outputs = vec![(12, zero), (18, zero), (7, fee_type)];
+        let (mut secret_inputs, keypair) = new_multi_xfr_witness_for_test(inputs, outputs, 3);
+
+        let auditor = AXfrAuditorKeyPair::generate(&mut prng);
+        secret_inputs.audit = Some(AXfrAuditWitness {
+            auditor_pub_key: auditor.get_public_key(),
+            randomness: (0..3).map(|_| JubjubScalar::random(&mut prng)).collect(),
+        });
+        let payees = secret_inputs.payees_witnesses.clone();
+        let pub_inputs = AXfrPubInputs::from_witness(&secret_inputs).unwrap();
+
+        // the auditor decrypts the outputs.
+        let audit = pub_inputs.audit.clone().unwrap();
+        for (memo, payee) in audit.memos.iter().zip(payees.iter()) {
+            let record = decrypt_audit_memo(&auditor.get_secret_key(), memo).unwrap();
+            assert_eq!(record.amount, payee.amount);
+            assert_eq!(record.asset_type, payee.asset_type);
+            assert_eq!(record.receiver, payee.public_key);
+        }
+
+        let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+        let (folding_instance, folding_witness) = create_address_folding(
+            &mut prng,
+            Sha512::new(),
+            &mut transcript,
+            ANON_XFR_BP_GENS_LEN,
+            &keypair,
+        )
+        .unwrap();
+        let (mut cs, _) = build_multi_xfr_cs(secret_inputs, fee_type, &folding_witness).unwrap();
+        let witness = cs.get_and_clear_witness();
+
+        let mut transcript = Transcript::new(ANON_XFR_FOLDING_PROOF_TRANSCRIPT);
+        let (beta, lambda) = verify_address_folding(
+            Sha512::new(),
+            &mut transcript,
+            ANON_XFR_BP_GENS_LEN,
+            &folding_instance,
+        )
+        .unwrap();
+        let address_folding_public_input =
+            prepare_verifier_input(&folding_instance, &beta, &lambda);
+
+        let mut online_inputs = pub_inputs.to_vec();
+        online_inputs.extend_from_slice(&address_folding_public_input);
+        pnk!(cs.verify_witness(&witness, &online_inputs));
+
+        // a memo that does not encrypt the output is rejected.
+        let mut bad_audit = audit;
+        bad_audit.memos[1].ciphertexts[0].add_assign(&BLSScalar::one());
+        let mut bad_pub_inputs = pub_inputs;
+        bad_pub_inputs.audit = Some(bad_audit);
+        let mut online_inputs = bad_pub_inputs.to_vec();
+        online_inputs.extend_from_slice(&address_folding_public_input);
+        assert!(cs.verify_witness(&witness, &online_inputs).is_err());
+    }
 }
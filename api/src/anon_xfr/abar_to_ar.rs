@@ -11,7 +11,7 @@ use crate::anon_xfr::{
     structs::{AccElemVars, Nullifier, OpenAnonAssetRecord, PayerWitness},
     AXfrPlonkPf, TurboPlonkCS, ANON_XFR_BP_GENS_LEN,
 };
-use crate::setup::{ProverParams, VerifierParams};
+use crate::setup::{ParamsId, ProverParams, VerifierParams};
 use crate::xfr::{
     asset_record::{
         build_open_asset_record, AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
@@ -32,10 +32,10 @@ use zei_plonk::plonk::{
 };
 
 /// The domain separator for anonymous-to-transparent, for the Plonk proof.
-const ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to AR Plonk Proof";
+pub(crate) const ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to AR Plonk Proof";
 
 /// The domain separator for anonymous-to-transparent, for address folding.
-const ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"ABAR to AR Folding Proof";
+pub(crate) const ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"ABAR to AR Folding Proof";
 
 /// The anonymous-to-transparent note.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -46,6 +46,9 @@ pub struct AbarToArNote {
     pub proof: AXfrPlonkPf,
     /// The address folding instance.
     pub folding_instance: AXfrAddressFoldingInstance,
+    /// The identifier of the parameters that the proof was generated with, which is bound by
+    /// the address folding proof.
+    pub params_id: ParamsId,
 }
 
 /// The anonymous-to-transparent note without proof.
@@ -74,6 +77,30 @@ pub struct AbarToArBody {
     pub memo: Option<OwnerMemo>,
 }
 
+impl AbarToArBody {
+    /// Return the public inputs of the Plonk proof, except for the address folding:
+    /// the nullifier, the Merkle root, and the disclosed amount and asset type of the output.
+    fn pub_inputs(&self) -> Result<Vec<BLSScalar>> {
+        let amount = self
+            .output
+            .amount
+            .get_amount()
+            .c(d!(ZeiError::ParameterError))?;
+        let asset_type = self
+            .output
+            .asset_type
+            .get_asset_type()
+            .c(d!(ZeiError::ParameterError))?;
+
+        Ok(vec![
+            self.input,
+            self.merkle_root,
+            BLSScalar::from(amount),
+            asset_type.as_scalar(),
+        ])
+    }
+}
+
 /// Generate an anonymous-to-transparent pre-note.
 pub fn init_abar_to_ar_note<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -131,6 +158,14 @@ pub fn init_abar_to_ar_note<R: CryptoRng + RngCore>(
     })
 }
 
+/// Start the transcript of the address folding proof, which binds the identifier of the
+/// parameters.
+fn folding_transcript(params_id: &ParamsId) -> Transcript {
+    let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+    params_id.append_to_transcript(&mut transcript);
+    transcript
+}
+
 /// Finalize an anonymous-to-transparent note.
 pub fn finish_abar_to_ar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
     prng: &mut R,
@@ -144,7 +179,8 @@ pub fn finish_abar_to_ar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64
         input_keypair,
     } = pre_note;
 
-    let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+    let params_id = params.id().c(d!())?;
+    let mut transcript = folding_transcript(&params_id);
     let (folding_instance, folding_witness) = create_address_folding(
         prng,
         hash,
@@ -159,6 +195,7 @@ pub fn finish_abar_to_ar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64
         body,
         proof,
         folding_instance,
+        params_id,
     })
 }
 
@@ -174,7 +211,8 @@ pub fn verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
         return Err(eg!(ZeiError::ParameterError));
     }
 
-    let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+    params.check_id(&note.params_id).c(d!())?;
+    let mut transcript = folding_transcript(&note.params_id);
     let (beta, lambda) = verify_address_folding(
         hash,
         &mut transcript,
@@ -185,19 +223,12 @@ pub fn verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default>(
     let address_folding_public_input =
         prepare_verifier_input(&note.folding_instance, &beta, &lambda);
 
-    let payer_amount = note.body.output.amount.get_amount().unwrap();
-    let payer_asset_type = note.body.output.asset_type.get_asset_type().unwrap();
-
     if *merkle_root != note.body.merkle_root {
         return Err(eg!(ZeiError::AXfrVerificationError));
     }
 
     let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
-    let mut online_inputs = vec![];
-    online_inputs.push(note.body.input.clone());
-    online_inputs.push(merkle_root.clone());
-    online_inputs.push(BLSScalar::from(payer_amount));
-    online_inputs.push(payer_asset_type.as_scalar());
+    let mut online_inputs = note.body.pub_inputs().c(d!())?;
     online_inputs.extend_from_slice(&address_folding_public_input);
 
     verifier(
@@ -220,6 +251,10 @@ pub fn batch_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default + Sync
     merkle_roots: &[&BLSScalar],
     hashes: Vec<D>,
 ) -> Result<()> {
+    if merkle_roots.len() != notes.len() || hashes.len() != notes.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
     // require the output amount & asset type are non-confidential
     if notes.par_iter().any(|note| {
         note.body.output.amount.is_confidential() || note.body.output.asset_type.is_confidential()
@@ -237,10 +272,10 @@ pub fn batch_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default + Sync
 
     let is_ok = notes
         .par_iter()
-        .zip(merkle_roots)
         .zip(hashes)
-        .map(|((note, merkle_root), hash)| {
-            let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+        .map(|(note, hash)| {
+            params.check_id(&note.params_id)?;
+            let mut transcript = folding_transcript(&note.params_id);
             let (beta, lambda) = verify_address_folding(
                 hash,
                 &mut transcript,
@@ -251,15 +286,8 @@ pub fn batch_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default + Sync
             let address_folding_public_input =
                 prepare_verifier_input(&note.folding_instance, &beta, &lambda);
 
-            let payer_amount = note.body.output.amount.get_amount().unwrap();
-            let payer_asset_type = note.body.output.asset_type.get_asset_type().unwrap();
-
             let mut transcript = Transcript::new(ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT);
-            let mut online_inputs = vec![];
-            online_inputs.push(note.body.input.clone());
-            online_inputs.push(*merkle_root.clone());
-            online_inputs.push(BLSScalar::from(payer_amount));
-            online_inputs.push(payer_asset_type.as_scalar());
+            let mut online_inputs = note.body.pub_inputs()?;
             online_inputs.extend_from_slice(&address_folding_public_input);
 
             verifier(
@@ -279,6 +307,7 @@ pub fn batch_verify_abar_to_ar_note<D: Digest<OutputSize = U64> + Default + Sync
         Err(eg!(ZeiError::AXfrVerificationError))
     }
 }
+
 fn prove_abar_to_ar<R: CryptoRng + RngCore>(
     rng: &mut R,
     params: &ProverParams,
@@ -396,3 +425,101 @@ pub fn build_abar_to_ar_cs(
     let n_constraints = cs.size;
     (cs, n_constraints)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::anon_xfr::{
+        abar_to_ar::{build_abar_to_ar_cs, ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT},
+        address_folding::{create_address_folding, prepare_verifier_input, verify_address_folding},
+        commit,
+        keys::AXfrKeyPair,
+        nullify,
+        structs::{MTNode, MTPath, PayerWitness},
+        ANON_XFR_BP_GENS_LEN,
+    };
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use sha2::Sha512;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use zei_crypto::basic::rescue::RescueInstance;
+
+    #[test]
+    fn test_build_abar_to_ar_cs() {
+        let mut prng = test_rng();
+        let zero = BLSScalar::zero();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let amount = 100u64;
+        let asset_type = AssetType::from_identical_byte(7);
+        let uid = 3u64;
+        let blind = BLSScalar::random(&mut prng);
+
+        // a tree of depth one, with the input as the left child.
+        let node = MTNode {
+            siblings1: BLSScalar::random(&mut prng),
+            siblings2: BLSScalar::random(&mut prng),
+            is_left_child: 1,
+            is_right_child: 0,
+        };
+        let hash = RescueInstance::new();
+        let commitment = commit(&keypair.get_public_key(), &blind, amount, &asset_type).unwrap();
+        let leaf = hash.rescue(&[BLSScalar::from(uid), commitment, zero, zero])[0];
+        let root = hash.rescue(&[leaf, node.siblings1, node.siblings2, zero])[0];
+        let nullifier = nullify(&keypair, amount, &asset_type, uid).unwrap();
+
+        let payer_witness = PayerWitness {
+            secret_key: keypair.get_secret_key(),
+            uid,
+            amount,
+            asset_type: asset_type.as_scalar(),
+            path: MTPath::new(vec![node]),
+            blind,
+        };
+
+        let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+        let (folding_instance, folding_witness) = create_address_folding(
+            &mut prng,
+            Sha512::new(),
+            &mut transcript,
+            ANON_XFR_BP_GENS_LEN,
+            &keypair,
+        )
+        .unwrap();
+        let (mut cs, _) = build_abar_to_ar_cs(payer_witness, &folding_witness);
+        let witness = cs.get_and_clear_witness();
+
+        let mut transcript = Transcript::new(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT);
+        let (beta, lambda) = verify_address_folding(
+            Sha512::new(),
+            &mut transcript,
+            ANON_XFR_BP_GENS_LEN,
+            &folding_instance,
+        )
+        .unwrap();
+        let address_folding_public_input =
+            prepare_verifier_input(&folding_instance, &beta, &lambda);
+
+        let online_inputs = |amount: u64, asset_type: &AssetType| {
+            let mut inputs = vec![
+                nullifier,
+                root,
+                BLSScalar::from(amount),
+                asset_type.as_scalar(),
+            ];
+            inputs.extend_from_slice(&address_folding_public_input);
+            inputs
+        };
+        pnk!(cs.verify_witness(&witness, &online_inputs(amount, &asset_type)));
+
+        // disclosing another amount or asset type is rejected.
+        assert!(cs
+            .verify_witness(&witness, &online_inputs(amount + 1, &asset_type))
+            .is_err());
+        assert!(cs
+            .verify_witness(
+                &witness,
+                &online_inputs(amount, &AssetType::from_identical_byte(8))
+            )
+            .is_err());
+    }
+}
@@ -11,7 +11,7 @@ use crate::anon_xfr::{
     structs::{AccElemVars, Nullifier, OpenAnonAssetRecord, PayerWitness},
     AXfrPlonkPf, TurboPlonkCS, ANON_XFR_BP_GENS_LEN, TWO_POW_32,
 };
-use crate::setup::{ProverParams, VerifierParams};
+use crate::setup::{ParamsId, ProverParams, VerifierParams};
 use crate::xfr::{
     asset_record::{build_open_asset_record, AssetRecordType},
     sig::XfrPublicKey,
@@ -42,9 +42,11 @@ use zei_plonk::plonk::{
 };
 
 /// The domain separator for anonymous-to-confidential, for the Plonk proof.
-const ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to BAR Plonk Proof";
+pub(crate) const ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"ABAR to BAR Plonk Proof";
 /// The domain separator for anonymous-to-confidential, for address folding.
-const ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"ABAR to BAR Folding Proof";
+pub(crate) const ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT: &[u8] = b"ABAR to BAR Folding Proof";
+/// The label of the nullifier of the input in the transcript.
+pub(crate) const ABAR_TO_BAR_NULLIFIER_LABEL: &[u8] = b"nullifier";
 
 /// An anonymous-to-confidential note.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -55,6 +57,9 @@ pub struct AbarToBarNote {
     pub proof: AXfrPlonkPf,
     /// The address folding instance.
     pub folding_instance: AXfrAddressFoldingInstance,
+    /// The identifier of the parameters that the proof was generated with, which is bound by
+    /// the address folding proof.
+    pub params_id: ParamsId,
 }
 
 /// An anonymous-to-confidential note without the proof.
@@ -149,7 +154,7 @@ pub fn init_abar_to_bar_note<R: CryptoRng + RngCore>(
     // 4. Compute the inspector's proof.
     let (delegated_schnorr_proof, delegated_schnorr_inspection, beta, lambda) = {
         let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
-        transcript.append_message(b"nullifier", &this_nullifier.to_bytes());
+        transcript.append_message(ABAR_TO_BAR_NULLIFIER_LABEL, &this_nullifier.to_bytes());
         prove_delegated_schnorr(
             prng,
             &vec![(x, gamma), (y, delta)],
@@ -191,6 +196,14 @@ pub fn init_abar_to_bar_note<R: CryptoRng + RngCore>(
     })
 }
 
+/// Start the transcript of the address folding proof, which binds the identifier of the
+/// parameters.
+fn folding_transcript(params_id: &ParamsId) -> Transcript {
+    let mut transcript = Transcript::new(ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT);
+    params_id.append_to_transcript(&mut transcript);
+    transcript
+}
+
 /// Finalize an anonymous-to-confidential note.
 pub fn finish_abar_to_bar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
     prng: &mut R,
@@ -207,7 +220,8 @@ pub fn finish_abar_to_bar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U6
         lambda,
     } = pre_note;
 
-    let mut transcript = Transcript::new(ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT);
+    let params_id = params.id().c(d!())?;
+    let mut transcript = folding_transcript(&params_id);
     let (folding_instance, folding_witness) = create_address_folding(
         prng,
         hash,
@@ -232,9 +246,91 @@ pub fn finish_abar_to_bar_note<R: CryptoRng + RngCore, D: Digest<OutputSize = U6
         body,
         proof,
         folding_instance,
+        params_id,
     })
 }
 
+impl AbarToBarBody {
+    /// Verify the delegated Schnorr proof that the output commitments open to the amount
+    /// and the asset type of the input, and return the public inputs of the Plonk proof,
+    /// except for the address folding.
+    fn verify_and_pub_inputs(&self) -> Result<Vec<BLSScalar>> {
+        let pc_gens = PedersenCommitmentRistretto::default();
+
+        // 1. Get commitments.
+        // 1.1 Reconstruct total amount commitment from bar.
+        let (com_low, com_high) = match self.output.amount {
+            XfrAmount::Confidential((low, high)) => (
+                low.decompress()
+                    .ok_or(ZeiError::DecompressElementError)
+                    .c(d!())?,
+                high.decompress()
+                    .ok_or(ZeiError::DecompressElementError)
+                    .c(d!())?,
+            ),
+            XfrAmount::NonConfidential(amount) => {
+                // Use a trivial commitment
+                let (l, h) = u64_to_u32_pair(amount);
+                (
+                    pc_gens.commit(RistrettoScalar::from(l), RistrettoScalar::zero()),
+                    pc_gens.commit(RistrettoScalar::from(h), RistrettoScalar::zero()),
+                )
+            }
+        };
+
+        // 1.2 Get asset type commitment.
+        let com_amount = com_low.add(&com_high.mul(&RistrettoScalar::from(TWO_POW_32)));
+        let com_asset_type = match self.output.asset_type {
+            XfrAssetType::Confidential(a) => a
+                .decompress()
+                .ok_or(ZeiError::DecompressElementError)
+                .c(d!())?,
+            XfrAssetType::NonConfidential(a) => {
+                // Use a trivial commitment
+                pc_gens.commit(a.as_scalar(), RistrettoScalar::zero())
+            }
+        };
+
+        let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
+
+        // important: address folding relies significantly on the Fiat-Shamir transform.
+        transcript.append_message(ABAR_TO_BAR_NULLIFIER_LABEL, &self.input.to_bytes());
+
+        // 2. Verify the delegated Schnorr proof.
+        let (beta, lambda) = verify_delegated_schnorr(
+            &pc_gens,
+            &vec![com_amount, com_asset_type],
+            &self.delegated_schnorr_proof,
+            &mut transcript,
+        )
+        .c(d!())?;
+
+        let beta_lambda = beta * &lambda;
+        let s1_plus_lambda_s2 = self.delegated_schnorr_proof.response_scalars[0].0
+            + self.delegated_schnorr_proof.response_scalars[1].0 * &lambda;
+
+        let beta_sim_fr =
+            SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&beta.to_bytes()));
+        let lambda_sim_fr =
+            SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&lambda.to_bytes()));
+        let beta_lambda_sim_fr =
+            SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&beta_lambda.to_bytes()));
+        let s1_plus_lambda_s2_sim_fr = SimFr::<SimFrParamsRistretto>::from(
+            &BigUint::from_bytes_le(&s1_plus_lambda_s2.to_bytes()),
+        );
+
+        let mut online_inputs = vec![];
+        online_inputs.push(self.input);
+        online_inputs.push(self.merkle_root);
+        online_inputs.push(self.delegated_schnorr_proof.inspection_comm);
+        online_inputs.extend_from_slice(&beta_sim_fr.limbs);
+        online_inputs.extend_from_slice(&lambda_sim_fr.limbs);
+        online_inputs.extend_from_slice(&beta_lambda_sim_fr.limbs);
+        online_inputs.extend_from_slice(&s1_plus_lambda_s2_sim_fr.limbs);
+        Ok(online_inputs)
+    }
+}
+
 /// Verify the anonymous-to-confidential note.
 pub fn verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default>(
     params: &VerifierParams,
@@ -246,9 +342,6 @@ pub fn verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default>(
         return Err(eg!(ZeiError::AXfrVerificationError));
     }
 
-    let bar = note.body.output.clone();
-    let pc_gens = PedersenCommitmentRistretto::default();
-
     // Reject anonymous-to-confidential notes whose outputs are transparent.
     if note.body.output.get_record_type()
         == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
@@ -256,57 +349,10 @@ pub fn verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default>(
         return Err(eg!(ZeiError::AXfrVerificationError));
     }
 
-    // 1. Get commitments.
-    // 1.1 Reconstruct total amount commitment from bar.
-    let (com_low, com_high) = match bar.amount {
-        XfrAmount::Confidential((low, high)) => (
-            low.decompress()
-                .ok_or(ZeiError::DecompressElementError)
-                .c(d!())?,
-            high.decompress()
-                .ok_or(ZeiError::DecompressElementError)
-                .c(d!())?,
-        ),
-        XfrAmount::NonConfidential(amount) => {
-            // Use a trivial commitment
-            let (l, h) = u64_to_u32_pair(amount);
-            (
-                pc_gens.commit(RistrettoScalar::from(l), RistrettoScalar::zero()),
-                pc_gens.commit(RistrettoScalar::from(h), RistrettoScalar::zero()),
-            )
-        }
-    };
-
-    // 1.2 Get asset type commitment.
-    let com_amount = com_low.add(&com_high.mul(&RistrettoScalar::from(TWO_POW_32)));
-    let com_asset_type = match bar.asset_type {
-        XfrAssetType::Confidential(a) => a
-            .decompress()
-            .ok_or(ZeiError::DecompressElementError)
-            .c(d!())?,
-        XfrAssetType::NonConfidential(a) => {
-            // Use a trivial commitment
-            pc_gens.commit(a.as_scalar(), RistrettoScalar::zero())
-        }
-    };
-
-    let input = note.body.input;
-
-    let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
+    let mut online_inputs = note.body.verify_and_pub_inputs().c(d!())?;
 
-    // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"nullifier", &note.body.input.to_bytes());
-
-    // 2. Verify the delegated Schnorr proof.
-    let (beta, lambda) = verify_delegated_schnorr(
-        &pc_gens,
-        &vec![com_amount, com_asset_type],
-        &note.body.delegated_schnorr_proof,
-        &mut transcript,
-    )
-    .c(d!())?;
-
-    let mut transcript = Transcript::new(ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT);
+    params.check_id(&note.params_id).c(d!())?;
+    let mut transcript = folding_transcript(&note.params_id);
     let (beta_folding, lambda_folding) = verify_address_folding(
         hash,
         &mut transcript,
@@ -315,34 +361,9 @@ pub fn verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default>(
     )?;
     let address_folding_public_input =
         prepare_verifier_input(&note.folding_instance, &beta_folding, &lambda_folding);
-
-    let delegated_schnorr_proof = note.body.delegated_schnorr_proof.clone();
-
-    let beta_lambda = beta * &lambda;
-    let s1_plus_lambda_s2 = delegated_schnorr_proof.response_scalars[0].0
-        + delegated_schnorr_proof.response_scalars[1].0 * &lambda;
-
-    let beta_sim_fr =
-        SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&beta.to_bytes()));
-    let lambda_sim_fr =
-        SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&lambda.to_bytes()));
-    let beta_lambda_sim_fr =
-        SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&beta_lambda.to_bytes()));
-    let s1_plus_lambda_s2_sim_fr =
-        SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&s1_plus_lambda_s2.to_bytes()));
-
-    let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
-    let mut online_inputs = vec![];
-
-    online_inputs.push(input.clone());
-    online_inputs.push(merkle_root.clone());
-    online_inputs.push(delegated_schnorr_proof.inspection_comm);
-    online_inputs.extend_from_slice(&beta_sim_fr.limbs);
-    online_inputs.extend_from_slice(&lambda_sim_fr.limbs);
-    online_inputs.extend_from_slice(&beta_lambda_sim_fr.limbs);
-    online_inputs.extend_from_slice(&s1_plus_lambda_s2_sim_fr.limbs);
     online_inputs.extend_from_slice(&address_folding_public_input);
 
+    let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
     verifier(
         &mut transcript,
         &params.pcs,
@@ -363,6 +384,10 @@ pub fn batch_verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default + Syn
     merkle_roots: &[&BLSScalar],
     hashes: Vec<D>,
 ) -> Result<()> {
+    if merkle_roots.len() != notes.len() || hashes.len() != notes.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
     if merkle_roots
         .par_iter()
         .zip(notes)
@@ -379,66 +404,14 @@ pub fn batch_verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default + Syn
         return Err(eg!(ZeiError::AXfrVerificationError));
     }
 
-    let pc_gens = PedersenCommitmentRistretto::default();
-
     let is_ok = notes
         .par_iter()
-        .zip(merkle_roots)
         .zip(hashes)
-        .map(|((note, merkle_root), hash)| {
-            let bar = note.body.output.clone();
-
-            // 1. Get commitments.
-            // 1.1 Reconstruct total amount commitment from bar.
-            let (com_low, com_high) = match bar.amount {
-                XfrAmount::Confidential((low, high)) => (
-                    low.decompress()
-                        .ok_or(ZeiError::DecompressElementError)
-                        .c(d!())?,
-                    high.decompress()
-                        .ok_or(ZeiError::DecompressElementError)
-                        .c(d!())?,
-                ),
-                XfrAmount::NonConfidential(amount) => {
-                    // Use a trivial commitment
-                    let (l, h) = u64_to_u32_pair(amount);
-                    (
-                        pc_gens.commit(RistrettoScalar::from(l), RistrettoScalar::zero()),
-                        pc_gens.commit(RistrettoScalar::from(h), RistrettoScalar::zero()),
-                    )
-                }
-            };
-
-            // 1.2 Get asset type commitment.
-            let com_amount = com_low.add(&com_high.mul(&RistrettoScalar::from(TWO_POW_32)));
-            let com_asset_type = match bar.asset_type {
-                XfrAssetType::Confidential(a) => a
-                    .decompress()
-                    .ok_or(ZeiError::DecompressElementError)
-                    .c(d!())?,
-                XfrAssetType::NonConfidential(a) => {
-                    // Use a trivial commitment
-                    pc_gens.commit(a.as_scalar(), RistrettoScalar::zero())
-                }
-            };
-
-            let input = note.body.input;
-
-            let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
+        .map(|(note, hash)| {
+            let mut online_inputs = note.body.verify_and_pub_inputs()?;
 
-            // important: address folding relies significantly on the Fiat-Shamir transform.
-            transcript.append_message(b"nullifier", &note.body.input.to_bytes());
-
-            // 2. Verify the delegated Schnorr proof.
-            let (beta, lambda) = verify_delegated_schnorr(
-                &pc_gens,
-                &vec![com_amount, com_asset_type],
-                &note.body.delegated_schnorr_proof,
-                &mut transcript,
-            )
-            .c(d!())?;
-
-            let mut transcript = Transcript::new(ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT);
+            params.check_id(&note.params_id)?;
+            let mut transcript = folding_transcript(&note.params_id);
             let (beta_folding, lambda_folding) = verify_address_folding(
                 hash,
                 &mut transcript,
@@ -447,36 +420,9 @@ pub fn batch_verify_abar_to_bar_note<D: Digest<OutputSize = U64> + Default + Syn
             )?;
             let address_folding_public_input =
                 prepare_verifier_input(&note.folding_instance, &beta_folding, &lambda_folding);
-
-            let delegated_schnorr_proof = note.body.delegated_schnorr_proof.clone();
-
-            let beta_lambda = beta * &lambda;
-            let s1_plus_lambda_s2 = delegated_schnorr_proof.response_scalars[0].0
-                + delegated_schnorr_proof.response_scalars[1].0 * &lambda;
-
-            let beta_sim_fr =
-                SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&beta.to_bytes()));
-            let lambda_sim_fr =
-                SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(&lambda.to_bytes()));
-            let beta_lambda_sim_fr = SimFr::<SimFrParamsRistretto>::from(&BigUint::from_bytes_le(
-                &beta_lambda.to_bytes(),
-            ));
-            let s1_plus_lambda_s2_sim_fr = SimFr::<SimFrParamsRistretto>::from(
-                &BigUint::from_bytes_le(&s1_plus_lambda_s2.to_bytes()),
-            );
-
-            let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
-            let mut online_inputs = vec![];
-
-            online_inputs.push(input.clone());
-            online_inputs.push(*merkle_root.clone());
-            online_inputs.push(delegated_schnorr_proof.inspection_comm);
-            online_inputs.extend_from_slice(&beta_sim_fr.limbs);
-            online_inputs.extend_from_slice(&lambda_sim_fr.limbs);
-            online_inputs.extend_from_slice(&beta_lambda_sim_fr.limbs);
-            online_inputs.extend_from_slice(&s1_plus_lambda_s2_sim_fr.limbs);
             online_inputs.extend_from_slice(&address_folding_public_input);
 
+            let mut transcript = Transcript::new(ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT);
             verifier(
                 &mut transcript,
                 &params.pcs,
@@ -19,6 +19,9 @@ use zei_plonk::plonk::constraint_system::field_simulation::SimFrVar;
 use zei_plonk::plonk::constraint_system::rescue::StateVar;
 use zei_plonk::plonk::constraint_system::VarIndex;
 
+/// The label of the digest of the note body in the address folding transcript.
+pub(crate) const ADDRESS_FOLDING_HASH_LABEL: &[u8] = b"hash";
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Eq)]
 /// The instance for address folding.
 pub struct AXfrAddressFoldingInstance {
@@ -106,7 +109,7 @@ pub fn create_address_folding<R: CryptoRng + RngCore, D: Digest<OutputSize = U64
     let secret_key = keypair.get_secret_key();
 
     // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"hash", hash.finalize().as_slice());
+    transcript.append_message(ADDRESS_FOLDING_HASH_LABEL, hash.finalize().as_slice());
 
     let (scalar_mul_proof, scalar_mul_commitments, blinding_factors) =
         { ScalarMulProof::prove(prng, &bp_gens, transcript, &public_key.0, &secret_key.0)? };
@@ -157,7 +160,7 @@ pub fn verify_address_folding<D: Digest<OutputSize = U64> + Default>(
     let bp_gens = ark_bulletproofs_secq256k1::BulletproofGens::new(bp_gens_len, 1);
 
     // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"hash", hash.finalize().as_slice());
+    transcript.append_message(ADDRESS_FOLDING_HASH_LABEL, hash.finalize().as_slice());
 
     instance
         .scalar_mul_proof
@@ -8,6 +8,7 @@ use crate::anon_xfr::{
 };
 use crate::setup::{ProverParams, VerifierParams};
 use crate::xfr::{
+    digest::{DigestFields, NoteDigester},
     sig::{XfrKeyPair, XfrSignature},
     structs::{BlindAssetRecord, OpenAssetRecord},
 };
@@ -20,7 +21,10 @@ use zei_plonk::plonk::{
 };
 
 /// The domain separator for transparent-to-anonymous, for the Plonk proof.
-const AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"AR to ABAR Plonk Proof";
+pub(crate) const AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"AR to ABAR Plonk Proof";
+
+/// The domain separator of the digest of the transparent-to-anonymous body, signed by the sender.
+const AR_TO_ABAR_BODY_DIGEST_DOMAIN: &[u8] = b"AR to ABAR Body";
 
 /// The transparent-to-anonymous note.
 #[derive(Debug, Serialize, Deserialize, Eq, Clone, PartialEq)]
@@ -44,6 +48,20 @@ pub struct ArToAbarBody {
     pub memo: AxfrOwnerMemo,
 }
 
+impl DigestFields for ArToAbarBody {
+    fn digest_fields(&self, digester: &mut NoteDigester) -> Result<()> {
+        digester
+            .append_fields(&self.input)
+            .c(d!())?
+            .append_scalar(b"output", &self.output.commitment)
+            .append_serialized(b"proof", &self.proof)
+            .c(d!())?
+            .append_bytes(b"memo point", &self.memo.point.zei_to_bytes())
+            .append_bytes(b"memo ciphertext", &self.memo.ctext);
+        Ok(())
+    }
+}
+
 /// Generate a transparent-to-anonymous note.
 pub fn gen_ar_to_abar_note<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -55,9 +73,7 @@ pub fn gen_ar_to_abar_note<R: CryptoRng + RngCore>(
     // generate body
     let body = gen_ar_to_abar_body(prng, params, record, &abar_pubkey).c(d!())?;
 
-    let msg = bincode::serialize(&body)
-        .map_err(|_| ZeiError::SerializationError)
-        .c(d!())?;
+    let msg = body.note_digest(AR_TO_ABAR_BODY_DIGEST_DOMAIN).c(d!())?;
     let signature = bar_keypair.sign(&msg)?;
 
     let note = ArToAbarNote { body, signature };
@@ -66,7 +82,10 @@ pub fn gen_ar_to_abar_note<R: CryptoRng + RngCore>(
 
 /// Verify a transparent-to-anonymous note.
 pub fn verify_ar_to_abar_note(params: &VerifierParams, note: &ArToAbarNote) -> Result<()> {
-    let msg = bincode::serialize(&note.body).c(d!(ZeiError::SerializationError))?;
+    let msg = note
+        .body
+        .note_digest(AR_TO_ABAR_BODY_DIGEST_DOMAIN)
+        .c(d!())?;
     note.body
         .input
         .public_key
@@ -85,7 +104,10 @@ pub fn batch_verify_ar_to_abar_note(
     let is_ok = notes
         .par_iter()
         .map(|note| {
-            let msg = bincode::serialize(&note.body).c(d!(ZeiError::SerializationError))?;
+            let msg = note
+                .body
+                .note_digest(AR_TO_ABAR_BODY_DIGEST_DOMAIN)
+                .c(d!())?;
             note.body
                 .input
                 .public_key
@@ -0,0 +1,266 @@
+use crate::anon_xfr::{
+    keys::AXfrPubKey,
+    scalar_to_amount,
+    structs::{PayeeWitness, PayeeWitnessVars},
+    TurboPlonkCS,
+};
+use zei_algebra::{
+    bls12_381::BLSScalar,
+    jubjub::{JubjubPoint, JubjubScalar},
+    prelude::*,
+};
+use zei_crypto::basic::rescue::RescueInstance;
+use zei_plonk::plonk::constraint_system::{
+    ecc::{ExtendedPointVar, PointVar},
+    rescue::StateVar,
+    VarIndex,
+};
+
+/// The number of scalars encrypted to the auditor for each output:
+/// the amount, the asset type, and the three scalars of the receiver's public key.
+pub const AUDIT_MEMO_LEN: usize = 5;
+
+/// The decryption key of an auditor of anonymous transfers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrAuditorSecretKey(pub(crate) JubjubScalar);
+
+/// The encryption key `sk * G` over Jubjub of an auditor of anonymous transfers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrAuditorPubKey(pub(crate) JubjubPoint);
+
+/// The key pair of an auditor of anonymous transfers.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrAuditorKeyPair {
+    /// The decryption key.
+    pub(crate) secret_key: AXfrAuditorSecretKey,
+    /// The encryption key.
+    pub(crate) pub_key: AXfrAuditorPubKey,
+}
+
+impl AXfrAuditorKeyPair {
+    /// Generate a key pair from `prng`.
+    pub fn generate<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        let secret_key = JubjubScalar::random(prng);
+        Self {
+            secret_key: AXfrAuditorSecretKey(secret_key),
+            pub_key: AXfrAuditorPubKey(JubjubPoint::get_base().mul(&secret_key)),
+        }
+    }
+
+    /// Return the encryption key.
+    pub fn get_public_key(&self) -> AXfrAuditorPubKey {
+        self.pub_key
+    }
+
+    /// Return the decryption key.
+    pub fn get_secret_key(&self) -> AXfrAuditorSecretKey {
+        self.secret_key.clone()
+    }
+}
+
+/// The encryption of an output of an anonymous transfer to the auditor.
+///
+/// With the ephemeral key `E = r * G` and the shared point `D = r * pk = sk * E`,
+/// the i-th scalar is masked by `Rescue(D.x, D.y, i, 0)[0]`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrAuditMemo {
+    /// The ephemeral key `r * G`.
+    pub ephemeral_key: JubjubPoint,
+    /// The masked amount, asset type, and public key scalars of the receiver.
+    pub ciphertexts: [BLSScalar; AUDIT_MEMO_LEN],
+}
+
+/// The audit information of an anonymous transfer, with one memo for each output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrAuditInfo {
+    /// The encryption key of the auditor.
+    pub auditor_pub_key: AXfrAuditorPubKey,
+    /// The memos of the outputs, in the order of the outputs.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
+    pub memos: Vec<AXfrAuditMemo>,
+}
+
+impl AXfrAuditInfo {
+    /// Return the public inputs of the circuit for the audit information:
+    /// the auditor key, followed by the ephemeral key and the ciphertexts of each memo.
+    pub fn to_public_inputs(&self) -> Vec<BLSScalar> {
+        let mut result = vec![
+            self.auditor_pub_key.0.get_x(),
+            self.auditor_pub_key.0.get_y(),
+        ];
+        for memo in &self.memos {
+            result.push(memo.ephemeral_key.get_x());
+            result.push(memo.ephemeral_key.get_y());
+            result.extend_from_slice(&memo.ciphertexts);
+        }
+        result
+    }
+}
+
+/// The witness of the audit information of an anonymous transfer.
+#[derive(Debug, Clone)]
+pub struct AXfrAuditWitness {
+    /// The encryption key of the auditor.
+    pub auditor_pub_key: AXfrAuditorPubKey,
+    /// The randomness of the memo of each output.
+    pub randomness: Vec<JubjubScalar>,
+}
+
+impl AXfrAuditWitness {
+    /// Create a fake `AXfrAuditWitness` for building the constraint system.
+    pub fn fake(n_payees: usize) -> Self {
+        AXfrAuditWitness {
+            auditor_pub_key: AXfrAuditorPubKey(JubjubPoint::get_base()),
+            randomness: vec![JubjubScalar::zero(); n_payees],
+        }
+    }
+}
+
+/// An output of an anonymous transfer, as decrypted by the auditor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AXfrAuditRecord {
+    /// The amount.
+    pub amount: u64,
+    /// The asset type, as a scalar, which can be matched against known asset types
+    /// with `AssetType::as_scalar`.
+    pub asset_type: BLSScalar,
+    /// The public key of the receiver.
+    pub receiver: AXfrPubKey,
+}
+
+/// Return the masks `Rescue(D.x, D.y, i, 0)[0]` for the shared point D.
+fn audit_masks(shared_point: &JubjubPoint) -> [BLSScalar; AUDIT_MEMO_LEN] {
+    let hash = RescueInstance::new();
+    let (x, y) = (shared_point.get_x(), shared_point.get_y());
+    let zero = BLSScalar::zero();
+    let mut masks = [zero; AUDIT_MEMO_LEN];
+    for (i, mask) in masks.iter_mut().enumerate() {
+        *mask = hash.rescue(&[x, y, BLSScalar::from(i as u32), zero])[0];
+    }
+    masks
+}
+
+/// Encrypt the amount, the asset type, and the receiver of an output to the auditor.
+pub fn encrypt_to_auditor(
+    auditor_pub_key: &AXfrAuditorPubKey,
+    randomness: &JubjubScalar,
+    payee: &PayeeWitness,
+) -> Result<AXfrAuditMemo> {
+    let public_key_scalars = payee.public_key.get_public_key_scalars().c(d!())?;
+    let plaintexts = [
+        BLSScalar::from(payee.amount),
+        payee.asset_type,
+        public_key_scalars[0],
+        public_key_scalars[1],
+        public_key_scalars[2],
+    ];
+    let masks = audit_masks(&auditor_pub_key.0.mul(randomness));
+
+    let mut ciphertexts = [BLSScalar::zero(); AUDIT_MEMO_LEN];
+    for (c, (m, mask)) in ciphertexts
+        .iter_mut()
+        .zip(plaintexts.iter().zip(masks.iter()))
+    {
+        *c = m.add(mask);
+    }
+    Ok(AXfrAuditMemo {
+        ephemeral_key: JubjubPoint::get_base().mul(randomness),
+        ciphertexts,
+    })
+}
+
+/// Decrypt an audit memo with the decryption key of the auditor.
+pub fn decrypt_audit_memo(
+    secret_key: &AXfrAuditorSecretKey,
+    memo: &AXfrAuditMemo,
+) -> Result<AXfrAuditRecord> {
+    let masks = audit_masks(&memo.ephemeral_key.mul(&secret_key.0));
+    let mut plaintexts = [BLSScalar::zero(); AUDIT_MEMO_LEN];
+    for (m, (c, mask)) in plaintexts
+        .iter_mut()
+        .zip(memo.ciphertexts.iter().zip(masks.iter()))
+    {
+        *m = c.sub(mask);
+    }
+
+    let amount = scalar_to_amount(&plaintexts[0]).c(d!(ZeiError::DecryptionError))?;
+    let receiver =
+        AXfrPubKey::from_public_key_scalars(&[plaintexts[2], plaintexts[3], plaintexts[4]])
+            .c(d!(ZeiError::DecryptionError))?;
+    Ok(AXfrAuditRecord {
+        amount,
+        asset_type: plaintexts[1],
+        receiver,
+    })
+}
+
+/// Add the constraints that an audit memo encrypts the amount, the asset type,
+/// and the public key scalars of an output, see `encrypt_to_auditor`.
+/// Return the ephemeral key and the ciphertexts.
+pub(crate) fn encrypt_to_auditor_in_cs(
+    cs: &mut TurboPlonkCS,
+    payee: &PayeeWitnessVars,
+    randomness_var: VarIndex,
+    auditor_pub_key_var: &ExtendedPointVar,
+) -> (PointVar, [VarIndex; AUDIT_MEMO_LEN]) {
+    let (ephemeral_key_var, shared_var) =
+        cs.elgamal_key_agreement(randomness_var, auditor_pub_key_var);
+    let shared_var = shared_var.get_var();
+
+    let plaintexts = [
+        payee.amount,
+        payee.asset_type,
+        payee.public_key_scalars[0],
+        payee.public_key_scalars[1],
+        payee.public_key_scalars[2],
+    ];
+    let zero_var = cs.zero_var();
+    let mut ciphertexts = [zero_var; AUDIT_MEMO_LEN];
+    for (i, (c, m)) in ciphertexts.iter_mut().zip(plaintexts.iter()).enumerate() {
+        let index = BLSScalar::from(i as u32);
+        let index_var = cs.new_variable(index);
+        cs.insert_constant_gate(index_var, index);
+        let input_var =
+            StateVar::new([shared_var.get_x(), shared_var.get_y(), index_var, zero_var]);
+        let mask_var = cs.rescue_hash(&input_var)[0];
+        *c = cs.add(*m, mask_var);
+    }
+    (ephemeral_key_var, ciphertexts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anon_xfr::{
+        audit::{decrypt_audit_memo, encrypt_to_auditor, AXfrAuditorKeyPair},
+        keys::AXfrKeyPair,
+        structs::PayeeWitness,
+    };
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubScalar, prelude::*};
+
+    #[test]
+    fn audit_memo() {
+        let mut prng = test_rng();
+        let auditor = AXfrAuditorKeyPair::generate(&mut prng);
+        let receiver = AXfrKeyPair::generate(&mut prng).get_public_key();
+        let asset_type = AssetType::from_identical_byte(5);
+        let payee = PayeeWitness {
+            amount: u64::MAX,
+            blind: BLSScalar::random(&mut prng),
+            asset_type: asset_type.as_scalar(),
+            public_key: receiver,
+        };
+
+        let randomness = JubjubScalar::random(&mut prng);
+        let memo = encrypt_to_auditor(&auditor.get_public_key(), &randomness, &payee).unwrap();
+        let record = decrypt_audit_memo(&auditor.get_secret_key(), &memo).unwrap();
+        assert_eq!(record.amount, u64::MAX);
+        assert_eq!(record.asset_type, asset_type.as_scalar());
+        assert_eq!(record.receiver, receiver);
+
+        // another auditor cannot decrypt the memo
+        let other = AXfrAuditorKeyPair::generate(&mut prng);
+        assert!(decrypt_audit_memo(&other.get_secret_key(), &memo).is_err());
+    }
+}
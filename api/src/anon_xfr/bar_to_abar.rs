@@ -1,14 +1,16 @@
 use crate::anon_xfr::{
-    commit, commit_in_cs,
+    commit,
+    commit_and_prove::XfrCommitments,
+    commit_in_cs,
     keys::AXfrPubKey,
     structs::{AnonAssetRecord, AxfrOwnerMemo, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
     AXfrPlonkPf, TurboPlonkCS, TWO_POW_32,
 };
-use crate::setup::{ProverParams, VerifierParams};
+use crate::setup::{ParamsId, ProverParams, VerifierParams};
 use crate::xfr::{
     asset_record::AssetRecordType,
     sig::{XfrKeyPair, XfrPublicKey, XfrSignature},
-    structs::{BlindAssetRecord, OpenAssetRecord, XfrAmount, XfrAssetType},
+    structs::{BlindAssetRecord, OpenAssetRecord},
 };
 use merlin::Transcript;
 use num_bigint::BigUint;
@@ -33,7 +35,10 @@ use zei_plonk::plonk::{
     verifier::verifier,
 };
 
-const BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"BAR to ABAR Plonk Proof";
+/// The domain separator for confidential-to-anonymous, for the Plonk proof.
+pub(crate) const BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT: &[u8] = b"BAR to ABAR Plonk Proof";
+/// The label of the commitment of the output in the transcript.
+pub(crate) const BAR_TO_ABAR_COMMITMENT_LABEL: &[u8] = b"commitment";
 
 /// A confidential-to-anonymous note.
 #[derive(Debug, Serialize, Deserialize, Eq, Clone, PartialEq)]
@@ -42,6 +47,9 @@ pub struct BarToAbarNote {
     pub body: BarToAbarBody,
     /// The signature.
     pub signature: XfrSignature,
+    /// The identifier of the parameters that the proof was generated with, which is bound by
+    /// the signature.
+    pub params_id: ParamsId,
 }
 
 /// A confidential-to-anonymous body.
@@ -83,21 +91,31 @@ pub fn gen_bar_to_abar_note<R: CryptoRng + RngCore>(
         memo: open_abar.owner_memo.unwrap(),
     };
 
-    let msg = bincode::serialize(&body)
-        .map_err(|_| ZeiError::SerializationError)
-        .c(d!())?;
+    let params_id = params.id().c(d!())?;
+    let msg = signed_message(&body, &params_id).c(d!())?;
     let signature = bar_keypair.sign(&msg)?;
 
-    let note = BarToAbarNote { body, signature };
+    let note = BarToAbarNote {
+        body,
+        signature,
+        params_id,
+    };
     Ok(note)
 }
 
+/// Return the message that the owner of the input signs, which binds the identifier of the
+/// parameters.
+fn signed_message(body: &BarToAbarBody, params_id: &ParamsId) -> Result<Vec<u8>> {
+    bincode::serialize(&(body, params_id)).c(d!(ZeiError::SerializationError))
+}
+
 /// Verify a confidential-to-anonymous note.
 pub fn verify_bar_to_abar_note(
     params: &VerifierParams,
     note: &BarToAbarNote,
     bar_pub_key: &XfrPublicKey,
 ) -> Result<()> {
+    params.check_id(&note.params_id).c(d!())?;
     verify_bar_to_abar(
         params,
         &note.body.input,
@@ -106,7 +124,7 @@ pub fn verify_bar_to_abar_note(
     )
     .c(d!())?;
 
-    let msg = bincode::serialize(&note.body).c(d!(ZeiError::SerializationError))?;
+    let msg = signed_message(&note.body, &note.params_id).c(d!())?;
     bar_pub_key.verify(&msg, &note.signature).c(d!())
 }
 
@@ -117,10 +135,15 @@ pub fn batch_verify_bar_to_abar_note(
     notes: &[&BarToAbarNote],
     bar_pub_keys: &[&XfrPublicKey],
 ) -> Result<()> {
+    if bar_pub_keys.len() != notes.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
     let is_ok = notes
         .par_iter()
         .zip(bar_pub_keys)
         .map(|(note, bar_pub_key)| {
+            params.check_id(&note.params_id)?;
             verify_bar_to_abar(
                 params,
                 &note.body.input,
@@ -129,7 +152,7 @@ pub fn batch_verify_bar_to_abar_note(
             )
             .c(d!())?;
 
-            let msg = bincode::serialize(&note.body).c(d!(ZeiError::SerializationError))?;
+            let msg = signed_message(&note.body, &note.params_id).c(d!())?;
             bar_pub_key.verify(&msg, &note.signature)
         })
         .all(|x| x.is_ok());
@@ -183,7 +206,7 @@ pub(crate) fn prove_bar_to_abar<R: CryptoRng + RngCore>(
 
     let mut transcript = Transcript::new(BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
     // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"commitment", &comm.to_bytes());
+    transcript.append_message(BAR_TO_ABAR_COMMITMENT_LABEL, &comm.to_bytes());
 
     // 3. Compute the delegated Schnorr proof.
     let (delegated_schnorr_proof, inspection, beta, lambda) = prove_delegated_schnorr(
@@ -229,44 +252,15 @@ pub(crate) fn verify_bar_to_abar(
         return Err(eg!(ZeiError::AXfrVerificationError));
     }
 
-    // 1. Get commitments.
-    // 1.1 Reconstruct the commitments for the amount.
-    let (com_low, com_high) = match bar.amount {
-        XfrAmount::Confidential((low, high)) => (
-            low.decompress()
-                .ok_or(ZeiError::DecompressElementError)
-                .c(d!())?,
-            high.decompress()
-                .ok_or(ZeiError::DecompressElementError)
-                .c(d!())?,
-        ),
-        XfrAmount::NonConfidential(amount) => {
-            // a trivial commitment
-            let (l, h) = u64_to_u32_pair(amount);
-            (
-                pc_gens.commit(RistrettoScalar::from(l), RistrettoScalar::zero()),
-                pc_gens.commit(RistrettoScalar::from(h), RistrettoScalar::zero()),
-            )
-        }
-    };
-
-    // 1.2 Reconstruct the commitments for the asset types.
-    let com_amount = com_low.add(&com_high.mul(&RistrettoScalar::from(TWO_POW_32)));
-    let com_asset_type = match bar.asset_type {
-        XfrAssetType::Confidential(a) => a
-            .decompress()
-            .ok_or(ZeiError::DecompressElementError)
-            .c(d!())?,
-        XfrAssetType::NonConfidential(a) => {
-            // a trivial commitment
-            pc_gens.commit(a.as_scalar(), RistrettoScalar::zero())
-        }
-    };
+    // 1. Reconstruct the commitments for the amount and the asset type.
+    let (com_amount, com_asset_type) = XfrCommitments::from_blind_asset_record(bar)
+        .c(d!())?
+        .combine();
 
     let mut transcript = Transcript::new(BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
 
     // important: address folding relies significantly on the Fiat-Shamir transform.
-    transcript.append_message(b"commitment", &abar.commitment.to_bytes());
+    transcript.append_message(BAR_TO_ABAR_COMMITMENT_LABEL, &abar.commitment.to_bytes());
 
     // 2. Verify the delegated Schnorr proof.
     let (beta, lambda) = verify_delegated_schnorr(
@@ -621,7 +615,9 @@ pub(crate) fn build_bar_to_abar_cs(
 #[cfg(test)]
 mod test {
     use crate::anon_xfr::{
-        bar_to_abar::BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT, commit, keys::AXfrKeyPair,
+        bar_to_abar::{BAR_TO_ABAR_COMMITMENT_LABEL, BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT},
+        commit,
+        keys::AXfrKeyPair,
     };
     use crate::xfr::structs::AssetType;
     use ark_std::test_rng;
@@ -667,7 +663,7 @@ mod test {
         // 2. compute the ZK part of the proof
 
         let mut transcript = Transcript::new(BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT);
-        transcript.append_message(b"commitment", &z.to_bytes());
+        transcript.append_message(BAR_TO_ABAR_COMMITMENT_LABEL, &z.to_bytes());
 
         let (proof, non_zk_state, beta, lambda) = prove_delegated_schnorr(
             &mut prng,
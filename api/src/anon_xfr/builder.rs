@@ -0,0 +1,372 @@
+use crate::anon_xfr::{
+    abar_to_abar::{finish_anon_xfr_note, init_anon_xfr_note, AXfrNote, AXfrPreNote},
+    coin_selection::{select_coins, CoinSelectionParams, CoinSelectionPlan},
+    keys::{AXfrKeyPair, AXfrPubKey},
+    structs::{OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+    FEE_TYPE,
+};
+use crate::errors::ZeiError;
+use crate::setup::{ParamsKind, ProverParams, MAX_ANONYMOUS_RECORD_NUMBER};
+use crate::xfr::structs::AssetType;
+use digest::{consts::U64, Digest};
+use zei_algebra::prelude::*;
+
+/// A payment of an anonymous transfer.
+#[derive(Clone, Debug)]
+pub struct AnonTransferOutput {
+    /// The public key of the receiver.
+    pub receiver: AXfrPubKey,
+    /// The amount.
+    pub amount: u64,
+    /// The asset type.
+    pub asset_type: AssetType,
+}
+
+/// The builder of an anonymous transfer from the records owned by a wallet.
+///
+/// It selects the inputs among the owned records, adds the change outputs back to the wallet,
+/// encrypts the owner memos of the outputs, and generates the proof.
+pub struct AnonTransferBuilder<'a> {
+    keypair: &'a AXfrKeyPair,
+    owned_records: Vec<OpenAnonAssetRecord>,
+    outputs: Vec<AnonTransferOutput>,
+    fee: u32,
+}
+
+impl<'a> AnonTransferBuilder<'a> {
+    /// Create a builder for the wallet with the key pair `keypair`.
+    pub fn new(keypair: &'a AXfrKeyPair) -> Self {
+        AnonTransferBuilder {
+            keypair,
+            owned_records: vec![],
+            outputs: vec![],
+            fee: 0,
+        }
+    }
+
+    /// Add records owned by the wallet, with their Merkle tree leaf information,
+    /// which can be selected as inputs.
+    pub fn owned_records(mut self, records: &[OpenAnonAssetRecord]) -> Self {
+        self.owned_records.extend_from_slice(records);
+        self
+    }
+
+    /// Add a payment of `amount` of `asset_type` to `receiver`.
+    pub fn output(mut self, receiver: &AXfrPubKey, amount: u64, asset_type: AssetType) -> Self {
+        self.outputs.push(AnonTransferOutput {
+            receiver: *receiver,
+            amount,
+            asset_type,
+        });
+        self
+    }
+
+    /// Specify the fee, paid in `FEE_TYPE`.
+    pub fn fee(mut self, fee: u32) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    /// Select the inputs among the owned records, and compute the change for each asset type.
+    ///
+    /// For each asset type, the largest records are selected first, which keeps the number of
+    /// inputs small. At least one input is of the fee type, as required by the circuit.
+    pub fn select_inputs(&self) -> Result<(Vec<OpenAnonAssetRecord>, Vec<(AssetType, u64)>)> {
+        self.check_owned_records().c(d!())?;
+
+        // the fee type comes first, then the other asset types in the order of the payments
+        let mut asset_types = vec![FEE_TYPE];
+        for output in self.outputs.iter() {
+            if !asset_types.contains(&output.asset_type) {
+                asset_types.push(output.asset_type);
+            }
+        }
+
+        let mut inputs = vec![];
+        let mut changes = vec![];
+        for asset_type in asset_types {
+            let mut needed: u128 = self
+                .outputs
+                .iter()
+                .filter(|output| output.asset_type == asset_type)
+                .map(|output| output.amount as u128)
+                .sum();
+            if asset_type == FEE_TYPE {
+                needed += self.fee as u128;
+            }
+
+            let mut candidates = self
+                .owned_records
+                .iter()
+                .filter(|record| record.asset_type == asset_type)
+                .collect_vec();
+            candidates.sort_by(|a, b| b.amount.cmp(&a.amount));
+
+            let mut selected: u128 = 0;
+            for candidate in candidates {
+                if selected >= needed && !(asset_type == FEE_TYPE && selected == 0) {
+                    break;
+                }
+                selected += candidate.amount as u128;
+                inputs.push(candidate.clone());
+            }
+            if selected < needed || (asset_type == FEE_TYPE && selected == 0) {
+                return Err(eg!(ZeiError::XfrCreationAssetAmountError));
+            }
+
+            let change = u64::try_from(selected - needed).c(d!(ZeiError::ParameterError))?;
+            if change > 0 {
+                changes.push((asset_type, change));
+            }
+        }
+
+        if inputs.len() > MAX_ANONYMOUS_RECORD_NUMBER
+            || self.outputs.len() + changes.len() > MAX_ANONYMOUS_RECORD_NUMBER
+        {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok((inputs, changes))
+    }
+
+    /// Plan the inputs and the changes with the coin selection minimizing the cost of the proof,
+    /// see [`select_coins`].
+    pub fn plan(&self, params: &CoinSelectionParams) -> Result<CoinSelectionPlan> {
+        self.check_owned_records().c(d!())?;
+        select_coins(&self.owned_records, &self.outputs, self.fee, params).c(d!())
+    }
+
+    fn check_owned_records(&self) -> Result<()> {
+        if self.owned_records.iter().any(|record| {
+            record.mt_leaf_info.is_none() || record.pub_key != self.keypair.get_public_key()
+        }) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(())
+    }
+
+    /// Build the anonymous transfer note without generating the proof.
+    pub fn build_pre_note<R: CryptoRng + RngCore>(&self, prng: &mut R) -> Result<AXfrPreNote> {
+        let (inputs, changes) = self.select_inputs().c(d!())?;
+        self.build_pre_note_with_selection(prng, &inputs, &changes)
+            .c(d!())
+    }
+
+    /// Build the anonymous transfer note without generating the proof, from a plan
+    /// obtained by [`AnonTransferBuilder::plan`].
+    pub fn build_pre_note_with_plan<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        plan: &CoinSelectionPlan,
+    ) -> Result<AXfrPreNote> {
+        if plan.n_payees != self.outputs.len() + plan.changes.len() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        self.build_pre_note_with_selection(prng, &plan.inputs, &plan.changes)
+            .c(d!())
+    }
+
+    fn build_pre_note_with_selection<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        inputs: &[OpenAnonAssetRecord],
+        changes: &[(AssetType, u64)],
+    ) -> Result<AXfrPreNote> {
+        let change_key = self.keypair.get_public_key();
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| (&output.receiver, output.amount, output.asset_type))
+            .chain(
+                changes
+                    .iter()
+                    .map(|(asset_type, amount)| (&change_key, *amount, *asset_type)),
+            )
+            .map(|(receiver, amount, asset_type)| {
+                OpenAnonAssetRecordBuilder::new()
+                    .amount(amount)
+                    .asset_type(asset_type)
+                    .pub_key(receiver)
+                    .finalize(prng)
+                    .c(d!())?
+                    .build()
+                    .c(d!())
+            })
+            .collect::<Result<Vec<OpenAnonAssetRecord>>>()?;
+
+        init_anon_xfr_note(inputs, &outputs, self.fee, self.keypair).c(d!())
+    }
+
+    /// Build the anonymous transfer note, with the proof.
+    /// The prover parameters for the number of inputs and outputs are obtained from the cache.
+    pub fn build<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
+        &self,
+        prng: &mut R,
+        hash: D,
+    ) -> Result<AXfrNote> {
+        let pre_note = self.build_pre_note(prng).c(d!())?;
+        let tree_depth = pre_note.witness.payers_witnesses[0].path.nodes.len();
+        let params = ProverParams::get_or_init(ParamsKind::AnonXfr {
+            n_payers: pre_note.witness.payers_witnesses.len(),
+            n_payees: pre_note.witness.payees_witnesses.len(),
+            tree_depth,
+        })
+        .c(d!())?;
+        finish_anon_xfr_note(prng, &params, pre_note, hash).c(d!())
+    }
+
+    /// Build the anonymous transfer note, with the proof, from a plan
+    /// obtained by [`AnonTransferBuilder::plan`].
+    pub fn build_with_plan<R: CryptoRng + RngCore, D: Digest<OutputSize = U64> + Default>(
+        &self,
+        prng: &mut R,
+        plan: &CoinSelectionPlan,
+        hash: D,
+    ) -> Result<AXfrNote> {
+        let pre_note = self.build_pre_note_with_plan(prng, plan).c(d!())?;
+        let params = ProverParams::get_or_init(plan.params_kind()).c(d!())?;
+        finish_anon_xfr_note(prng, &params, pre_note, hash).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        builder::AnonTransferBuilder,
+        coin_selection::CoinSelectionParams,
+        keys::AXfrKeyPair,
+        structs::{MTLeafInfo, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+        FEE_TYPE,
+    };
+    use crate::setup::MAX_ANONYMOUS_RECORD_NUMBER;
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    fn owned_record<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        keypair: &AXfrKeyPair,
+        amount: u64,
+        asset_type: AssetType,
+    ) -> OpenAnonAssetRecord {
+        OpenAnonAssetRecordBuilder::new()
+            .amount(amount)
+            .asset_type(asset_type)
+            .pub_key(&keypair.get_public_key())
+            .mt_leaf_info(MTLeafInfo::default())
+            .finalize(prng)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn anon_transfer_builder() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let receiver = AXfrKeyPair::generate(&mut prng).get_public_key();
+        let other_type = AssetType::from_identical_byte(1);
+
+        let records = [
+            owned_record(&mut prng, &keypair, 10, FEE_TYPE),
+            owned_record(&mut prng, &keypair, 25, FEE_TYPE),
+            owned_record(&mut prng, &keypair, 7, FEE_TYPE),
+            owned_record(&mut prng, &keypair, 30, other_type),
+            owned_record(&mut prng, &keypair, 12, other_type),
+            owned_record(&mut prng, &keypair, 3, other_type),
+        ];
+
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&records)
+            .output(&receiver, 20, FEE_TYPE)
+            .output(&receiver, 35, other_type)
+            .fee(3);
+
+        let (inputs, changes) = builder.select_inputs().unwrap();
+        let amounts = inputs.iter().map(|input| input.get_amount()).collect_vec();
+        assert_eq!(amounts, vec![25, 30, 12]);
+        assert_eq!(changes, vec![(FEE_TYPE, 2), (other_type, 7)]);
+
+        let pre_note = builder.build_pre_note(&mut prng).unwrap();
+        assert_eq!(pre_note.body.inputs.len(), 3);
+        assert_eq!(pre_note.body.outputs.len(), 4);
+        assert_eq!(pre_note.body.owner_memos.len(), 4);
+        assert_eq!(pre_note.body.fee, 3);
+        let outputs = pre_note
+            .witness
+            .payees_witnesses
+            .iter()
+            .map(|output| (output.amount, output.asset_type, output.public_key))
+            .collect_vec();
+        assert_eq!(
+            outputs,
+            vec![
+                (20, FEE_TYPE.as_scalar(), receiver),
+                (35, other_type.as_scalar(), receiver),
+                (2, FEE_TYPE.as_scalar(), keypair.get_public_key()),
+                (7, other_type.as_scalar(), keypair.get_public_key()),
+            ]
+        );
+
+        // an exact payment has no change
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&records)
+            .output(&receiver, 22, FEE_TYPE)
+            .fee(3);
+        let (inputs, changes) = builder.select_inputs().unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert!(changes.is_empty());
+
+        // the fee must be paid by an input even without fee-type payments
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&records)
+            .output(&receiver, 3, other_type);
+        let (inputs, changes) = builder.select_inputs().unwrap();
+        assert_eq!(inputs[0].get_asset_type(), FEE_TYPE);
+        assert_eq!(changes, vec![(FEE_TYPE, 25), (other_type, 27)]);
+
+        // insufficient funds
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&records)
+            .output(&receiver, 46, other_type);
+        msg_eq!(
+            ZeiError::XfrCreationAssetAmountError,
+            builder.select_inputs().unwrap_err()
+        );
+
+        // records of another wallet cannot be spent
+        let other_keypair = AXfrKeyPair::generate(&mut prng);
+        let builder = AnonTransferBuilder::new(&other_keypair)
+            .owned_records(&records)
+            .output(&receiver, 1, FEE_TYPE);
+        assert!(builder.select_inputs().is_err());
+
+        // the plan of the coin selection is consumed by the builder
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&records)
+            .output(&receiver, 20, FEE_TYPE)
+            .fee(3);
+        let params = CoinSelectionParams {
+            preferred_tree_depth: None,
+            ..Default::default()
+        };
+        let plan = builder.plan(&params).unwrap();
+        assert_eq!(plan.inputs.len(), 1);
+        assert_eq!(plan.inputs[0].get_amount(), 25);
+        assert_eq!(plan.changes, vec![(FEE_TYPE, 2)]);
+        let pre_note = builder.build_pre_note_with_plan(&mut prng, &plan).unwrap();
+        assert_eq!(pre_note.body.inputs.len(), 1);
+        assert_eq!(pre_note.body.outputs.len(), 2);
+
+        // too many inputs for the circuits
+        let small_records = (0..MAX_ANONYMOUS_RECORD_NUMBER + 1)
+            .map(|_| owned_record(&mut prng, &keypair, 1, FEE_TYPE))
+            .collect_vec();
+        let builder = AnonTransferBuilder::new(&keypair)
+            .owned_records(&small_records)
+            .output(&receiver, MAX_ANONYMOUS_RECORD_NUMBER as u64 + 1, FEE_TYPE);
+        msg_eq!(
+            ZeiError::ParameterError,
+            builder.select_inputs().unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use crate::anon_xfr::{
+    builder::AnonTransferOutput, privacy::TreeState, privacy::DEFAULT_MIN_INPUT_AGE,
+    structs::OpenAnonAssetRecord, FEE_TYPE, TREE_DEPTH,
+};
+use crate::errors::ZeiError;
+use crate::setup::{ParamsKind, MAX_ANONYMOUS_RECORD_NUMBER};
+use crate::xfr::structs::AssetType;
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+/// The maximal number of records of one asset type searched for the best combination.
+/// The candidates are the largest records, so the search always finds the fewest inputs.
+pub const MAX_COIN_SELECTION_CANDIDATES: usize = 16;
+
+/// The parameters of the coin selection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoinSelectionParams {
+    /// Records and changes with an amount below the threshold are near-dust, they are only
+    /// spent if needed, and they are not created as changes if avoidable.
+    pub dust_threshold: u64,
+    /// The state of the Merkle tree, if known, to exclude the recent records.
+    pub tree_state: Option<TreeState>,
+    /// The minimal number of leaves appended after a record for it to be spent.
+    /// It is only enforced if the state of the Merkle tree is known.
+    pub min_input_age: u64,
+    /// The tree depth of the circuit to use, for which the parameters are likely cached.
+    /// Records under a root of another depth are only spent if the payment cannot be made otherwise.
+    pub preferred_tree_depth: Option<usize>,
+}
+
+impl Default for CoinSelectionParams {
+    fn default() -> Self {
+        CoinSelectionParams {
+            dust_threshold: 0,
+            tree_state: None,
+            min_input_age: DEFAULT_MIN_INPUT_AGE,
+            preferred_tree_depth: Some(TREE_DEPTH),
+        }
+    }
+}
+
+/// The inputs and the changes of an anonymous transfer, chosen by [`select_coins`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoinSelectionPlan {
+    /// The selected inputs, the fee type first.
+    pub inputs: Vec<OpenAnonAssetRecord>,
+    /// The changes sent back to the wallet, by asset type.
+    pub changes: Vec<(AssetType, u64)>,
+    /// The number of outputs, with the changes.
+    pub n_payees: usize,
+    /// The depth of the Merkle tree of the inputs.
+    pub tree_depth: usize,
+}
+
+impl CoinSelectionPlan {
+    /// Return the kind of the parameters needed to prove the planned transfer.
+    pub fn params_kind(&self) -> ParamsKind {
+        ParamsKind::AnonXfr {
+            n_payers: self.inputs.len(),
+            n_payees: self.n_payees,
+            tree_depth: self.tree_depth,
+        }
+    }
+}
+
+/// The cost of a combination of inputs of one asset type, compared lexicographically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SelectionCost {
+    dust_inputs: usize,
+    has_change: bool,
+    dust_change: bool,
+    co_created_pairs: usize,
+    newest_uid: u64,
+    change: u128,
+}
+
+fn uid(record: &OpenAnonAssetRecord) -> u64 {
+    // the candidates are filtered to the records with leaf information
+    record
+        .mt_leaf_info
+        .as_ref()
+        .map(|info| info.uid)
+        .unwrap_or(0)
+}
+
+/// Select the combination of the fewest records covering `needed`, with the smallest cost,
+/// among candidates sorted by decreasing amounts. Return `None` if the candidates do not suffice.
+fn select_asset_coins<'a>(
+    candidates: &[&'a OpenAnonAssetRecord],
+    needed: u128,
+    min_inputs: usize,
+    max_inputs: usize,
+    dust_threshold: u64,
+) -> Option<(Vec<&'a OpenAnonAssetRecord>, u64)> {
+    let pool = &candidates[..candidates.len().min(MAX_COIN_SELECTION_CANDIDATES)];
+    for k in min_inputs..=max_inputs.min(pool.len()) {
+        // the largest records give the largest sum, so no combination of `k` records suffices
+        // if they do not
+        let largest: u128 = pool[..k].iter().map(|record| record.amount as u128).sum();
+        if largest < needed {
+            continue;
+        }
+
+        let best = pool
+            .iter()
+            .copied()
+            .combinations(k)
+            .filter_map(|selection| {
+                let selected: u128 = selection.iter().map(|record| record.amount as u128).sum();
+                if selected < needed {
+                    return None;
+                }
+                let change = selected - needed;
+                let uids = selection.iter().map(|record| uid(record)).collect_vec();
+                // a transfer inserts its outputs as consecutive leaves
+                let co_created_pairs = uids
+                    .iter()
+                    .tuple_combinations()
+                    .filter(|(a, b)| a.abs_diff(**b) < MAX_ANONYMOUS_RECORD_NUMBER as u64)
+                    .count();
+                let cost = SelectionCost {
+                    dust_inputs: selection
+                        .iter()
+                        .filter(|record| record.amount < dust_threshold)
+                        .count(),
+                    has_change: change > 0,
+                    dust_change: change > 0 && change < dust_threshold as u128,
+                    co_created_pairs,
+                    newest_uid: uids.iter().copied().max().unwrap_or(0),
+                    change,
+                };
+                Some((cost, selection))
+            })
+            .min_by(|(a, _), (b, _)| a.cmp(b));
+
+        if let Some((cost, selection)) = best {
+            return u64::try_from(cost.change)
+                .ok()
+                .map(|change| (selection, change));
+        }
+    }
+    None
+}
+
+/// Select the inputs and changes of a transfer under the Merkle tree root `root`.
+fn select_coins_under_root(
+    records: &[&OpenAnonAssetRecord],
+    root: &BLSScalar,
+    outputs: &[AnonTransferOutput],
+    fee: u32,
+    params: &CoinSelectionParams,
+) -> Result<CoinSelectionPlan> {
+    // the fee type comes first, then the other asset types in the order of the payments
+    let mut asset_types = vec![FEE_TYPE];
+    for output in outputs.iter() {
+        if !asset_types.contains(&output.asset_type) {
+            asset_types.push(output.asset_type);
+        }
+    }
+
+    let mut inputs = vec![];
+    let mut changes = vec![];
+    let mut tree_depth = 0;
+    for asset_type in asset_types {
+        let mut needed: u128 = outputs
+            .iter()
+            .filter(|output| output.asset_type == asset_type)
+            .map(|output| output.amount as u128)
+            .sum();
+        if asset_type == FEE_TYPE {
+            needed += fee as u128;
+        }
+        // at least one input is of the fee type, as required by the circuit
+        let min_inputs = if asset_type == FEE_TYPE || needed > 0 {
+            1
+        } else {
+            0
+        };
+
+        let mut candidates = records
+            .iter()
+            .copied()
+            .filter(|record| {
+                record.asset_type == asset_type
+                    && record.mt_leaf_info.as_ref().map(|info| &info.root) == Some(root)
+            })
+            .collect_vec();
+        candidates.sort_by(|a, b| b.amount.cmp(&a.amount).then(uid(a).cmp(&uid(b))));
+
+        let max_inputs = MAX_ANONYMOUS_RECORD_NUMBER.saturating_sub(inputs.len());
+        let (selection, change) = select_asset_coins(
+            &candidates,
+            needed,
+            min_inputs,
+            max_inputs,
+            params.dust_threshold,
+        )
+        .c(d!(ZeiError::XfrCreationAssetAmountError))?;
+
+        for record in selection {
+            if let Some(info) = record.mt_leaf_info.as_ref() {
+                tree_depth = info.path.nodes.len();
+            }
+            inputs.push(record.clone());
+        }
+        if change > 0 {
+            changes.push((asset_type, change));
+        }
+    }
+
+    let n_payees = outputs.len() + changes.len();
+    if n_payees > MAX_ANONYMOUS_RECORD_NUMBER {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    Ok(CoinSelectionPlan {
+        inputs,
+        changes,
+        n_payees,
+        tree_depth,
+    })
+}
+
+/// Choose the inputs of an anonymous transfer paying `outputs` and `fee` among `records`,
+/// minimizing the cost of the proof, and compute the changes.
+///
+/// All the inputs of a transfer are under the same Merkle tree root. The roots at the preferred
+/// tree depth are tried first, from the newest one. Under a root, for each asset type, the plan
+/// has the fewest inputs that cover the payments, since each input adds a Merkle path to the
+/// circuit. Among them, it avoids near-dust inputs, then changes (an exact payment saves an
+/// output), then near-dust changes, then inputs that were created together, and then prefers
+/// the oldest records.
+///
+/// Records without leaf information, and records younger than `params.min_input_age`
+/// if the state of the tree is known, are never selected.
+pub fn select_coins(
+    records: &[OpenAnonAssetRecord],
+    outputs: &[AnonTransferOutput],
+    fee: u32,
+    params: &CoinSelectionParams,
+) -> Result<CoinSelectionPlan> {
+    let eligible = records
+        .iter()
+        .filter(|record| match (&record.mt_leaf_info, &params.tree_state) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(info), Some(tree)) => {
+                info.uid < tree.num_leaves && tree.num_leaves - 1 - info.uid >= params.min_input_age
+            }
+        })
+        .collect_vec();
+
+    // the distinct roots, as (root, version, depth)
+    let mut roots: Vec<(BLSScalar, u64, usize)> = vec![];
+    for info in eligible
+        .iter()
+        .filter_map(|record| record.mt_leaf_info.as_ref())
+    {
+        match roots.iter_mut().find(|(root, _, _)| *root == info.root) {
+            Some(entry) => entry.1 = entry.1.max(info.root_version),
+            None => roots.push((info.root, info.root_version, info.path.nodes.len())),
+        }
+    }
+    roots.sort_by_key(|(_, version, depth)| {
+        (
+            Some(*depth) != params.preferred_tree_depth,
+            u64::MAX - version,
+        )
+    });
+
+    let mut result = Err(eg!(ZeiError::XfrCreationAssetAmountError));
+    for (root, _, _) in roots.iter() {
+        result = select_coins_under_root(&eligible, root, outputs, fee, params);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        builder::AnonTransferOutput,
+        coin_selection::{select_coins, CoinSelectionParams},
+        keys::AXfrKeyPair,
+        privacy::TreeState,
+        structs::{MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+        FEE_TYPE,
+    };
+    use crate::setup::ParamsKind;
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+    fn owned_record<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        keypair: &AXfrKeyPair,
+        amount: u64,
+        asset_type: AssetType,
+        uid: u64,
+        tree_depth: usize,
+    ) -> OpenAnonAssetRecord {
+        let node = MTNode {
+            siblings1: BLSScalar::zero(),
+            siblings2: BLSScalar::zero(),
+            is_left_child: 1,
+            is_right_child: 0,
+        };
+        OpenAnonAssetRecordBuilder::new()
+            .amount(amount)
+            .asset_type(asset_type)
+            .pub_key(&keypair.get_public_key())
+            .mt_leaf_info(MTLeafInfo {
+                path: MTPath::new(vec![node; tree_depth]),
+                root: BLSScalar::from(tree_depth as u32),
+                root_version: 1,
+                uid,
+            })
+            .finalize(prng)
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn amounts(records: &[OpenAnonAssetRecord]) -> Vec<u64> {
+        records
+            .iter()
+            .map(|record| record.get_amount())
+            .collect_vec()
+    }
+
+    #[test]
+    fn coin_selection() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let receiver = AXfrKeyPair::generate(&mut prng).get_public_key();
+        let payment = |amount: u64| {
+            vec![AnonTransferOutput {
+                receiver,
+                amount,
+                asset_type: FEE_TYPE,
+            }]
+        };
+        let params = CoinSelectionParams {
+            dust_threshold: 5,
+            preferred_tree_depth: Some(3),
+            ..Default::default()
+        };
+
+        let records = [
+            owned_record(&mut prng, &keypair, 50, FEE_TYPE, 0, 3),
+            owned_record(&mut prng, &keypair, 40, FEE_TYPE, 100, 3),
+            owned_record(&mut prng, &keypair, 30, FEE_TYPE, 200, 3),
+            owned_record(&mut prng, &keypair, 2, FEE_TYPE, 300, 3),
+            owned_record(&mut prng, &keypair, 1000, FEE_TYPE, 400, 5),
+        ];
+
+        // the fewest inputs, an exact payment saving the change output
+        let plan = select_coins(&records, &payment(70), 0, &params).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![40, 30]);
+        assert!(plan.changes.is_empty());
+        assert_eq!(
+            plan.params_kind(),
+            ParamsKind::AnonXfr {
+                n_payers: 2,
+                n_payees: 1,
+                tree_depth: 3
+            }
+        );
+
+        // no near-dust change if avoidable
+        let plan = select_coins(&records, &payment(37), 0, &params).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![50]);
+        assert_eq!(plan.changes, vec![(FEE_TYPE, 13)]);
+
+        // the oldest records are preferred
+        let plan = select_coins(&records, &payment(20), 0, &params).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![50]);
+
+        // near-dust inputs are only spent if needed
+        let plan = select_coins(&records, &payment(121), 0, &params).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![50, 40, 30, 2]);
+
+        // the other tree depth is only used if the payment cannot be made otherwise
+        let plan = select_coins(&records, &payment(200), 0, &params).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![1000]);
+        assert_eq!(plan.tree_depth, 5);
+
+        // the recent records are excluded
+        let aged = CoinSelectionParams {
+            tree_state: Some(TreeState { num_leaves: 250 }),
+            min_input_age: 64,
+            ..params
+        };
+        let plan = select_coins(&records, &payment(60), 0, &aged).unwrap();
+        assert_eq!(amounts(&plan.inputs), vec![50, 40]);
+        msg_eq!(
+            ZeiError::XfrCreationAssetAmountError,
+            select_coins(&records, &payment(91), 0, &aged).unwrap_err()
+        );
+
+        // the fee requires an input even without fee-type payments
+        let other_type = AssetType::from_identical_byte(1);
+        let mut with_other = records.to_vec();
+        with_other.push(owned_record(&mut prng, &keypair, 9, other_type, 500, 3));
+        let outputs = vec![AnonTransferOutput {
+            receiver,
+            amount: 9,
+            asset_type: other_type,
+        }];
+        let plan = select_coins(&with_other, &outputs, 0, &params).unwrap();
+        assert_eq!(plan.inputs[0].get_asset_type(), FEE_TYPE);
+        assert_eq!(plan.inputs[1].get_asset_type(), other_type);
+        assert_eq!(plan.n_payees, 2);
+    }
+
+    #[test]
+    fn coin_selection_avoids_co_created_inputs() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let receiver = AXfrKeyPair::generate(&mut prng).get_public_key();
+        let records = [
+            owned_record(&mut prng, &keypair, 10, FEE_TYPE, 10, 3),
+            owned_record(&mut prng, &keypair, 10, FEE_TYPE, 11, 3),
+            owned_record(&mut prng, &keypair, 10, FEE_TYPE, 50, 3),
+        ];
+        let outputs = vec![AnonTransferOutput {
+            receiver,
+            amount: 20,
+            asset_type: FEE_TYPE,
+        }];
+        let params = CoinSelectionParams {
+            preferred_tree_depth: Some(3),
+            ..Default::default()
+        };
+        let plan = select_coins(&records, &outputs, 0, &params).unwrap();
+        let uids = plan
+            .inputs
+            .iter()
+            .map(|record| record.mt_leaf_info.as_ref().unwrap().uid)
+            .collect_vec();
+        assert_eq!(uids, vec![10, 50]);
+    }
+}
@@ -0,0 +1,732 @@
+use crate::anon_xfr::{
+    bar_to_abar::{prove_inspection, verify_inspection},
+    commit_in_cs,
+    structs::{AnonAssetRecord, OpenAnonAssetRecord, PayeeWitness},
+    AXfrPlonkPf, TurboPlonkCS, AMOUNT_LEN, TWO_POW_32,
+};
+use crate::setup::{ParamsKind, ProverParams, VerifierParams};
+use crate::xfr::structs::{BlindAssetRecord, XfrAmount, XfrAssetType};
+use digest::Digest;
+use merlin::Transcript;
+use num_bigint::BigUint;
+use sha2::Sha512;
+use zei_algebra::{
+    bls12_381::BLSScalar,
+    jubjub::{JubjubPoint, JubjubScalar},
+    prelude::*,
+    ristretto::{RistrettoPoint, RistrettoScalar},
+};
+use zei_crypto::{
+    basic::{
+        pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto},
+        rescue::RescueInstance,
+    },
+    delegated_schnorr::{prove_delegated_schnorr, verify_delegated_schnorr, DelegatedSchnorrProof},
+    field_simulation::SimFrParamsRistretto,
+};
+use zei_plonk::plonk::{
+    constraint_system::{rescue::StateVar, TurboCS},
+    prover::prover_with_lagrange,
+    verifier::verifier,
+};
+
+/// The domain separator for linking an external commitment, for the Plonk proof.
+pub(crate) const EXTERNAL_COMMITMENT_PLONK_PROOF_TRANSCRIPT: &[u8] =
+    b"External Commitment Plonk Proof";
+/// The domain separator for the kind of the external commitment.
+pub(crate) const EXTERNAL_COMMITMENT_KIND_TRANSCRIPT: &[u8] = b"External Commitment Kind";
+/// The domain separator for linking the commitments of a record of the transparent transfers.
+pub(crate) const XFR_COMMITMENTS_TRANSCRIPT: &[u8] = b"Xfr Commitments Link Proof";
+/// The label of the commitment of the anonymous asset record in the transcript.
+pub(crate) const XFR_COMMITMENTS_ABAR_LABEL: &[u8] = b"commitment";
+/// The number of bits of the blinding factors of the Pedersen commitments,
+/// which covers the order of the Jubjub scalar field.
+const PEDERSEN_BLIND_BITS: usize = 252;
+
+/// The kinds of external commitments that can be linked to an anonymous asset record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ExternalCommitmentKind {
+    /// A Rescue commitment to the amount, see `rescue_commit`.
+    RescueAmount,
+    /// A Rescue commitment to the asset type, see `rescue_commit`.
+    RescueAssetType,
+    /// A Pedersen commitment over Jubjub to the amount, see `pedersen_commit`.
+    PedersenAmount,
+}
+
+impl ExternalCommitmentKind {
+    fn to_u64(&self) -> u64 {
+        match self {
+            ExternalCommitmentKind::RescueAmount => 0,
+            ExternalCommitmentKind::RescueAssetType => 1,
+            ExternalCommitmentKind::PedersenAmount => 2,
+        }
+    }
+}
+
+/// A commitment supplied by another system, to the amount or to the asset type
+/// of an anonymous asset record.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExternalCommitment {
+    /// A Rescue commitment to the amount.
+    RescueAmount(BLSScalar),
+    /// A Rescue commitment to the asset type.
+    RescueAssetType(BLSScalar),
+    /// A Pedersen commitment over Jubjub to the amount.
+    PedersenAmount(JubjubPoint),
+}
+
+impl ExternalCommitment {
+    /// Return the kind of the commitment.
+    pub fn kind(&self) -> ExternalCommitmentKind {
+        match self {
+            ExternalCommitment::RescueAmount(_) => ExternalCommitmentKind::RescueAmount,
+            ExternalCommitment::RescueAssetType(_) => ExternalCommitmentKind::RescueAssetType,
+            ExternalCommitment::PedersenAmount(_) => ExternalCommitmentKind::PedersenAmount,
+        }
+    }
+
+    /// Return the public inputs of the circuit for the commitment.
+    fn to_public_inputs(&self) -> Vec<BLSScalar> {
+        match self {
+            ExternalCommitment::RescueAmount(c) | ExternalCommitment::RescueAssetType(c) => {
+                vec![*c]
+            }
+            ExternalCommitment::PedersenAmount(p) => vec![p.get_x(), p.get_y()],
+        }
+    }
+}
+
+/// The blinding factor of an external commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExternalCommitmentBlind {
+    /// The blinding factor of a Rescue commitment.
+    Rescue(BLSScalar),
+    /// The blinding factor of a Pedersen commitment.
+    Pedersen(JubjubScalar),
+}
+
+/// Compute the Rescue commitment `rescue(blind, value, 0, 0)[0]`.
+pub fn rescue_commit(value: &BLSScalar, blind: &BLSScalar) -> BLSScalar {
+    let hash = RescueInstance::new();
+    let zero = BLSScalar::zero();
+    hash.rescue(&[*blind, *value, zero, zero])[0]
+}
+
+/// Return the generators `(G, H)` of the Pedersen commitments over Jubjub, where `H` is
+/// sampled by hashing, so nobody knows its discrete log w.r.t. `G`.
+pub fn pedersen_generators() -> (JubjubPoint, JubjubPoint) {
+    let mut hasher = Sha512::new();
+    hasher.update(b"Zei external Pedersen commitment Jubjub blinding generator");
+    (JubjubPoint::get_base(), JubjubPoint::from_hash(hasher))
+}
+
+/// Compute the Pedersen commitment `amount * G + blind * H` over Jubjub.
+pub fn pedersen_commit(amount: u64, blind: &JubjubScalar) -> JubjubPoint {
+    let (g, h) = pedersen_generators();
+    g.mul(&JubjubScalar::from(amount)).add(&h.mul(blind))
+}
+
+/// Create a fresh external commitment of the given kind to an anonymous asset record,
+/// together with its blinding factor.
+pub fn commit_external<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    oabar: &OpenAnonAssetRecord,
+    kind: ExternalCommitmentKind,
+) -> (ExternalCommitment, ExternalCommitmentBlind) {
+    match kind {
+        ExternalCommitmentKind::RescueAmount => {
+            let blind = BLSScalar::random(prng);
+            let comm = rescue_commit(&BLSScalar::from(oabar.amount), &blind);
+            (
+                ExternalCommitment::RescueAmount(comm),
+                ExternalCommitmentBlind::Rescue(blind),
+            )
+        }
+        ExternalCommitmentKind::RescueAssetType => {
+            let blind = BLSScalar::random(prng);
+            let comm = rescue_commit(&oabar.asset_type.as_scalar(), &blind);
+            (
+                ExternalCommitment::RescueAssetType(comm),
+                ExternalCommitmentBlind::Rescue(blind),
+            )
+        }
+        ExternalCommitmentKind::PedersenAmount => {
+            let blind = JubjubScalar::random(prng);
+            let comm = pedersen_commit(oabar.amount, &blind);
+            (
+                ExternalCommitment::PedersenAmount(comm),
+                ExternalCommitmentBlind::Pedersen(blind),
+            )
+        }
+    }
+}
+
+/// Check that the external commitment opens to the amount or the asset type of the record.
+fn check_external_opening(
+    oabar: &OpenAnonAssetRecord,
+    commitment: &ExternalCommitment,
+    blind: &ExternalCommitmentBlind,
+) -> Result<()> {
+    let is_valid = match (commitment, blind) {
+        (ExternalCommitment::RescueAmount(c), ExternalCommitmentBlind::Rescue(r)) => {
+            rescue_commit(&BLSScalar::from(oabar.amount), r) == *c
+        }
+        (ExternalCommitment::RescueAssetType(c), ExternalCommitmentBlind::Rescue(r)) => {
+            rescue_commit(&oabar.asset_type.as_scalar(), r) == *c
+        }
+        (ExternalCommitment::PedersenAmount(c), ExternalCommitmentBlind::Pedersen(r)) => {
+            pedersen_commit(oabar.amount, r) == *c
+        }
+        _ => false,
+    };
+    if is_valid {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::ParameterError))
+    }
+}
+
+/// Prove that an external commitment opens to the amount or the asset type committed
+/// in an anonymous asset record, without revealing either.
+///
+/// This allows another system to link its own committed data to a record created by a Zei
+/// transfer, whose commitment is public.
+pub fn prove_external_commitment<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &ProverParams,
+    oabar: &OpenAnonAssetRecord,
+    commitment: &ExternalCommitment,
+    blind: &ExternalCommitmentBlind,
+) -> Result<AXfrPlonkPf> {
+    check_external_opening(oabar, commitment, blind).c(d!())?;
+
+    let payee_witness = PayeeWitness {
+        amount: oabar.amount,
+        blind: oabar.blind,
+        asset_type: oabar.asset_type.as_scalar(),
+        public_key: oabar.pub_key.clone(),
+    };
+
+    let kind = commitment.kind();
+    let mut transcript = Transcript::new(EXTERNAL_COMMITMENT_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_u64(EXTERNAL_COMMITMENT_KIND_TRANSCRIPT, kind.to_u64());
+
+    let (mut cs, _) = build_external_commitment_cs(payee_witness, blind, kind).c(d!())?;
+    let witness = cs.get_and_clear_witness();
+
+    prover_with_lagrange(
+        prng,
+        &mut transcript,
+        &params.pcs,
+        params.lagrange_pcs.as_ref(),
+        &params.cs,
+        &params.prover_params,
+        &witness,
+    )
+    .c(d!(ZeiError::AXfrProofError))
+}
+
+/// Verify that an external commitment opens to the amount or the asset type committed
+/// in an anonymous asset record.
+///
+/// The parameters must be those of `ParamsKind::ExternalCommitment` for the kind of
+/// the commitment.
+pub fn verify_external_commitment(
+    params: &VerifierParams,
+    abar: &AnonAssetRecord,
+    commitment: &ExternalCommitment,
+    proof: &AXfrPlonkPf,
+) -> Result<()> {
+    let mut transcript = Transcript::new(EXTERNAL_COMMITMENT_PLONK_PROOF_TRANSCRIPT);
+    transcript.append_u64(
+        EXTERNAL_COMMITMENT_KIND_TRANSCRIPT,
+        commitment.kind().to_u64(),
+    );
+
+    let mut online_inputs = vec![abar.commitment];
+    online_inputs.extend(commitment.to_public_inputs());
+
+    verifier(
+        &mut transcript,
+        &params.pcs,
+        &params.cs,
+        &params.verifier_params,
+        &online_inputs,
+        proof,
+    )
+    .c(d!(ZeiError::AXfrVerificationError))
+}
+
+/// Same as `verify_external_commitment`, with the parameters from the global cache.
+pub fn verify_external_commitment_with_cached_params(
+    abar: &AnonAssetRecord,
+    commitment: &ExternalCommitment,
+    proof: &AXfrPlonkPf,
+) -> Result<()> {
+    let params = VerifierParams::get_or_init(ParamsKind::ExternalCommitment {
+        kind: commitment.kind(),
+    })
+    .c(d!())?;
+    verify_external_commitment(&params, abar, commitment, proof).c(d!())
+}
+
+/// The Pedersen commitments over Ristretto of a record of the transparent transfers, i.e., of a
+/// `BlindAssetRecord`, to the lower and the higher 32 bits of the amount, and to the asset type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfrCommitments {
+    /// The commitments to the lower and the higher 32 bits of the amount.
+    pub amount: (RistrettoPoint, RistrettoPoint),
+    /// The commitment to the asset type.
+    pub asset_type: RistrettoPoint,
+}
+
+/// The blinding factors of `XfrCommitments`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfrCommitmentBlinds {
+    /// The blinding factors of the lower and the higher 32 bits of the amount.
+    pub amount: (RistrettoScalar, RistrettoScalar),
+    /// The blinding factor of the asset type.
+    pub asset_type: RistrettoScalar,
+}
+
+/// A proof that `XfrCommitments` open to the amount and the asset type of an anonymous asset
+/// record: a delegated Schnorr proof over Ristretto, and a Plonk proof of its inspection.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfrCommitmentsProof {
+    /// The delegated Schnorr proof of the opening of the commitments.
+    pub delegated_schnorr_proof:
+        DelegatedSchnorrProof<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto>,
+    /// The Plonk proof that the inspected values are those of the anonymous asset record.
+    pub inspection_proof: AXfrPlonkPf,
+}
+
+impl XfrCommitments {
+    /// Read the commitments of a blind asset record, where a transparent amount or asset type
+    /// is committed with a zero blinding factor.
+    pub fn from_blind_asset_record(bar: &BlindAssetRecord) -> Result<Self> {
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let amount = match bar.amount {
+            XfrAmount::Confidential((low, high)) => (
+                low.decompress()
+                    .ok_or(ZeiError::DecompressElementError)
+                    .c(d!())?,
+                high.decompress()
+                    .ok_or(ZeiError::DecompressElementError)
+                    .c(d!())?,
+            ),
+            XfrAmount::NonConfidential(amount) => {
+                // a trivial commitment
+                let (l, h) = u64_to_u32_pair(amount);
+                (
+                    pc_gens.commit(RistrettoScalar::from(l), RistrettoScalar::zero()),
+                    pc_gens.commit(RistrettoScalar::from(h), RistrettoScalar::zero()),
+                )
+            }
+        };
+        let asset_type = match bar.asset_type {
+            XfrAssetType::Confidential(a) => a
+                .decompress()
+                .ok_or(ZeiError::DecompressElementError)
+                .c(d!())?,
+            XfrAssetType::NonConfidential(a) => {
+                // a trivial commitment
+                pc_gens.commit(a.as_scalar(), RistrettoScalar::zero())
+            }
+        };
+        Ok(XfrCommitments { amount, asset_type })
+    }
+
+    /// Return the commitments to the whole amount and to the asset type.
+    pub(crate) fn combine(&self) -> (RistrettoPoint, RistrettoPoint) {
+        let com_amount = self
+            .amount
+            .0
+            .add(&self.amount.1.mul(&RistrettoScalar::from(TWO_POW_32)));
+        (com_amount, self.asset_type)
+    }
+}
+
+/// Create fresh commitments over Ristretto to the amount and the asset type of an anonymous
+/// asset record, as a record of the transparent transfers would have them.
+pub fn commit_xfr<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    oabar: &OpenAnonAssetRecord,
+) -> (XfrCommitments, XfrCommitmentBlinds) {
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let blinds = XfrCommitmentBlinds {
+        amount: (RistrettoScalar::random(prng), RistrettoScalar::random(prng)),
+        asset_type: RistrettoScalar::random(prng),
+    };
+    let (amount_lo, amount_hi) = u64_to_u32_pair(oabar.amount);
+    let commitments = XfrCommitments {
+        amount: (
+            pc_gens.commit(RistrettoScalar::from(amount_lo), blinds.amount.0),
+            pc_gens.commit(RistrettoScalar::from(amount_hi), blinds.amount.1),
+        ),
+        asset_type: pc_gens.commit(oabar.asset_type.as_scalar(), blinds.asset_type),
+    };
+    (commitments, blinds)
+}
+
+/// Prove that the commitments over Ristretto of a record of the transparent transfers open to
+/// the amount and the asset type of an anonymous asset record, without revealing either.
+///
+/// This lets a transaction that spends or creates both kinds of records share a value between
+/// them. The parameters must be those of `ParamsKind::BarToAbar`, whose circuit is reused.
+pub fn prove_xfr_commitments<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &ProverParams,
+    oabar: &OpenAnonAssetRecord,
+    commitments: &XfrCommitments,
+    blinds: &XfrCommitmentBlinds,
+) -> Result<XfrCommitmentsProof> {
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let (amount_lo, amount_hi) = u64_to_u32_pair(oabar.amount);
+    let is_valid = pc_gens.commit(RistrettoScalar::from(amount_lo), blinds.amount.0)
+        == commitments.amount.0
+        && pc_gens.commit(RistrettoScalar::from(amount_hi), blinds.amount.1)
+            == commitments.amount.1
+        && pc_gens.commit(oabar.asset_type.as_scalar(), blinds.asset_type)
+            == commitments.asset_type;
+    if !is_valid {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let x = RistrettoScalar::from(oabar.amount);
+    let y: RistrettoScalar = oabar.asset_type.as_scalar();
+    let gamma = blinds
+        .amount
+        .0
+        .add(&blinds.amount.1.mul(&RistrettoScalar::from(TWO_POW_32)));
+    let delta = blinds.asset_type;
+    let (com_amount, com_asset_type) = commitments.combine();
+
+    let x_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&x.to_bytes()));
+    let y_in_bls12_381 = BLSScalar::from(&BigUint::from_bytes_le(&y.to_bytes()));
+
+    let abar = AnonAssetRecord::from_oabar(oabar);
+    let mut transcript = Transcript::new(XFR_COMMITMENTS_TRANSCRIPT);
+    transcript.append_message(XFR_COMMITMENTS_ABAR_LABEL, &abar.commitment.to_bytes());
+
+    let (delegated_schnorr_proof, inspection, beta, lambda) = prove_delegated_schnorr(
+        prng,
+        &vec![(x, gamma), (y, delta)],
+        &pc_gens,
+        &vec![com_amount, com_asset_type],
+        &mut transcript,
+    )
+    .c(d!())?;
+
+    let inspection_proof = prove_inspection(
+        prng,
+        params,
+        x_in_bls12_381,
+        y_in_bls12_381,
+        oabar.blind,
+        &oabar.pub_key,
+        &delegated_schnorr_proof,
+        &inspection,
+        &beta,
+        &lambda,
+    )
+    .c(d!())?;
+
+    Ok(XfrCommitmentsProof {
+        delegated_schnorr_proof,
+        inspection_proof,
+    })
+}
+
+/// Verify that the commitments over Ristretto of a record of the transparent transfers open to
+/// the amount and the asset type of an anonymous asset record.
+///
+/// The parameters must be those of `ParamsKind::BarToAbar`.
+pub fn verify_xfr_commitments(
+    params: &VerifierParams,
+    abar: &AnonAssetRecord,
+    commitments: &XfrCommitments,
+    proof: &XfrCommitmentsProof,
+) -> Result<()> {
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let (com_amount, com_asset_type) = commitments.combine();
+
+    let mut transcript = Transcript::new(XFR_COMMITMENTS_TRANSCRIPT);
+    transcript.append_message(XFR_COMMITMENTS_ABAR_LABEL, &abar.commitment.to_bytes());
+
+    let (beta, lambda) = verify_delegated_schnorr(
+        &pc_gens,
+        &vec![com_amount, com_asset_type],
+        &proof.delegated_schnorr_proof,
+        &mut transcript,
+    )
+    .c(d!(ZeiError::AXfrVerificationError))?;
+
+    verify_inspection(
+        params,
+        abar.commitment,
+        &proof.delegated_schnorr_proof,
+        &proof.inspection_proof,
+        &beta,
+        &lambda,
+    )
+    .c(d!(ZeiError::AXfrVerificationError))
+}
+
+/// Same as `verify_xfr_commitments`, with the parameters from the global cache.
+pub fn verify_xfr_commitments_with_cached_params(
+    abar: &AnonAssetRecord,
+    commitments: &XfrCommitments,
+    proof: &XfrCommitmentsProof,
+) -> Result<()> {
+    let params = VerifierParams::get_or_init(ParamsKind::BarToAbar).c(d!())?;
+    verify_xfr_commitments(&params, abar, commitments, proof).c(d!())
+}
+
+/// Return `[4^i * base, 2 * 4^i * base, 3 * 4^i * base]` for `i` in `0..n_windows`,
+/// the bases of the fixed-base scalar multiplication in the constraint system.
+fn window_bases(base: &JubjubPoint, n_windows: usize) -> [Vec<JubjubPoint>; 3] {
+    let mut bases = [
+        Vec::with_capacity(n_windows),
+        Vec::with_capacity(n_windows),
+        Vec::with_capacity(n_windows),
+    ];
+    let mut cur = *base;
+    for _ in 0..n_windows {
+        let double = cur.double();
+        bases[0].push(cur);
+        bases[1].push(double);
+        bases[2].push(double.add(&cur));
+        cur = double.double();
+    }
+    bases
+}
+
+/// Construct the constraint system linking an external commitment to an anonymous asset record.
+///
+/// The public inputs are the commitment of the record, followed by the external commitment,
+/// as a scalar for Rescue, or as the coordinates of the point for Pedersen.
+pub fn build_external_commitment_cs(
+    payee_data: PayeeWitness,
+    blind: &ExternalCommitmentBlind,
+    kind: ExternalCommitmentKind,
+) -> Result<(TurboPlonkCS, usize)> {
+    let mut cs = TurboCS::new();
+
+    let amount_var = cs.new_variable(BLSScalar::from(payee_data.amount));
+    let asset_var = cs.new_variable(payee_data.asset_type);
+    let blind_var = cs.new_variable(payee_data.blind);
+
+    let public_key_scalars = payee_data.public_key.get_public_key_scalars().c(d!())?;
+    let public_key_scalars_vars = [
+        cs.new_variable(public_key_scalars[0]),
+        cs.new_variable(public_key_scalars[1]),
+        cs.new_variable(public_key_scalars[2]),
+    ];
+
+    // the commitment of the record
+    let com_abar_var = commit_in_cs(
+        &mut cs,
+        blind_var,
+        amount_var,
+        asset_var,
+        &public_key_scalars_vars,
+    );
+    cs.prepare_pi_variable(com_abar_var);
+
+    // the external commitment, to the same amount or asset type
+    match (kind, blind) {
+        (ExternalCommitmentKind::RescueAmount, ExternalCommitmentBlind::Rescue(r))
+        | (ExternalCommitmentKind::RescueAssetType, ExternalCommitmentBlind::Rescue(r)) => {
+            let value_var = if kind == ExternalCommitmentKind::RescueAmount {
+                amount_var
+            } else {
+                asset_var
+            };
+            let ext_blind_var = cs.new_variable(*r);
+            let zero_var = cs.zero_var();
+            let input_var = StateVar::new([ext_blind_var, value_var, zero_var, zero_var]);
+            let com_ext_var = cs.rescue_hash(&input_var)[0];
+            cs.prepare_pi_variable(com_ext_var);
+        }
+        (ExternalCommitmentKind::PedersenAmount, ExternalCommitmentBlind::Pedersen(r)) => {
+            // `amount * G + blind * H` is computed as a single fixed-base multiplication
+            // of the bits of the amount followed by the bits of the blinding factor.
+            let ext_blind_var = cs.new_variable(BLSScalar::from_bytes(&r.to_bytes()).c(d!())?);
+            let mut bits = cs.range_check(amount_var, AMOUNT_LEN);
+            bits.extend(cs.range_check(ext_blind_var, PEDERSEN_BLIND_BITS));
+
+            let (g, h) = pedersen_generators();
+            let [mut bases1, mut bases2, mut bases3] = window_bases(&g, AMOUNT_LEN / 2);
+            let [h_bases1, h_bases2, h_bases3] = window_bases(&h, PEDERSEN_BLIND_BITS / 2);
+            bases1.extend(h_bases1);
+            bases2.extend(h_bases2);
+            bases3.extend(h_bases3);
+
+            let com_ext_var = cs.scalar_mul_with_bases(&bases1, &bases2, &bases3, &bits);
+            cs.prepare_pi_point_variable(com_ext_var);
+        }
+        _ => return Err(eg!(ZeiError::ParameterError)),
+    }
+
+    // pad the number of constraints to power of two
+    cs.pad();
+
+    let n_constraints = cs.size;
+    Ok((cs, n_constraints))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_external_commitment_cs, commit_external, commit_xfr, prove_external_commitment,
+        prove_xfr_commitments, verify_external_commitment, verify_xfr_commitments,
+        ExternalCommitmentBlind, ExternalCommitmentKind, XfrCommitments,
+    };
+    use crate::anon_xfr::{
+        keys::AXfrKeyPair,
+        structs::{AnonAssetRecord, OpenAnonAssetRecordBuilder, PayeeWitness},
+    };
+    use crate::setup::{ParamsKind, ProverParams, VerifierParams};
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubScalar, prelude::*};
+
+    const KINDS: [ExternalCommitmentKind; 3] = [
+        ExternalCommitmentKind::RescueAmount,
+        ExternalCommitmentKind::RescueAssetType,
+        ExternalCommitmentKind::PedersenAmount,
+    ];
+
+    #[test]
+    fn external_commitment_cs() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(1234)
+            .asset_type(AssetType::from_identical_byte(7u8))
+            .pub_key(&keypair.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let payee = PayeeWitness {
+            amount: oabar.amount,
+            blind: oabar.blind,
+            asset_type: oabar.asset_type.as_scalar(),
+            public_key: oabar.pub_key.clone(),
+        };
+
+        for kind in KINDS.iter() {
+            let (comm, blind) = commit_external(&mut prng, &oabar, *kind);
+            assert_eq!(comm.kind(), *kind);
+            let (mut cs, _) = build_external_commitment_cs(payee.clone(), &blind, *kind).unwrap();
+            let witness = cs.get_and_clear_witness();
+
+            let mut online_inputs = vec![AnonAssetRecord::from_oabar(&oabar).commitment];
+            online_inputs.extend(comm.to_public_inputs());
+            assert!(cs.verify_witness(&witness, &online_inputs).is_ok());
+
+            // a commitment to another value is rejected
+            let (other_comm, _) = match kind {
+                ExternalCommitmentKind::RescueAssetType => {
+                    let mut other = oabar.clone();
+                    other.asset_type = AssetType::from_identical_byte(8u8);
+                    commit_external(&mut prng, &other, *kind)
+                }
+                _ => {
+                    let mut other = oabar.clone();
+                    other.amount += 1;
+                    commit_external(&mut prng, &other, *kind)
+                }
+            };
+            let mut online_inputs = vec![AnonAssetRecord::from_oabar(&oabar).commitment];
+            online_inputs.extend(other_comm.to_public_inputs());
+            assert!(cs.verify_witness(&witness, &online_inputs).is_err());
+        }
+
+        // the blinding factor must match the kind
+        assert!(build_external_commitment_cs(
+            payee,
+            &ExternalCommitmentBlind::Pedersen(JubjubScalar::one()),
+            ExternalCommitmentKind::RescueAmount
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn external_commitment_proof() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(1000)
+            .asset_type(AssetType::from_identical_byte(1u8))
+            .pub_key(&keypair.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+
+        let kind = ExternalCommitmentKind::PedersenAmount;
+        let params = ProverParams::get_or_init(ParamsKind::ExternalCommitment { kind }).unwrap();
+        let verifier_params =
+            VerifierParams::get_or_init(ParamsKind::ExternalCommitment { kind }).unwrap();
+
+        let (comm, blind) = commit_external(&mut prng, &oabar, kind);
+        let proof = prove_external_commitment(&mut prng, &params, &oabar, &comm, &blind).unwrap();
+        assert!(verify_external_commitment(&verifier_params, &abar, &comm, &proof).is_ok());
+
+        // the proof does not verify for another record or another commitment
+        let other_abar = AnonAssetRecord {
+            commitment: abar.commitment.add(&BLSScalar::one()),
+        };
+        assert!(verify_external_commitment(&verifier_params, &other_abar, &comm, &proof).is_err());
+        let (other_comm, _) = commit_external(&mut prng, &oabar, kind);
+        assert!(verify_external_commitment(&verifier_params, &abar, &other_comm, &proof).is_err());
+
+        // an opening that does not match is rejected before proving
+        assert!(
+            prove_external_commitment(&mut prng, &params, &oabar, &other_comm, &blind).is_err()
+        );
+    }
+
+    #[test]
+    fn xfr_commitments_proof() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount((3u64 << 32) + 1000)
+            .asset_type(AssetType::from_identical_byte(1u8))
+            .pub_key(&keypair.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+
+        let params = ProverParams::get_or_init(ParamsKind::BarToAbar).unwrap();
+        let verifier_params = VerifierParams::get_or_init(ParamsKind::BarToAbar).unwrap();
+
+        let (comms, blinds) = commit_xfr(&mut prng, &oabar);
+        let proof = prove_xfr_commitments(&mut prng, &params, &oabar, &comms, &blinds).unwrap();
+        assert!(verify_xfr_commitments(&verifier_params, &abar, &comms, &proof).is_ok());
+
+        // the proof does not verify for another record or other commitments
+        let other_abar = AnonAssetRecord {
+            commitment: abar.commitment.add(&BLSScalar::one()),
+        };
+        assert!(verify_xfr_commitments(&verifier_params, &other_abar, &comms, &proof).is_err());
+        let (other_comms, _) = commit_xfr(&mut prng, &oabar);
+        let swapped = XfrCommitments {
+            amount: (comms.amount.1, comms.amount.0),
+            asset_type: comms.asset_type,
+        };
+        for comms in [other_comms, swapped] {
+            assert!(verify_xfr_commitments(&verifier_params, &abar, &comms, &proof).is_err());
+        }
+
+        // an opening that does not match is rejected before proving
+        assert!(prove_xfr_commitments(&mut prng, &params, &oabar, &other_comms, &blinds).is_err());
+    }
+}
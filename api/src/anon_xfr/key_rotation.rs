@@ -0,0 +1,250 @@
+//! Two generations of receiving keys, for rotating the key of a wallet without losing payments.
+//!
+//! During a rotation window, a wallet publishes its current key together with the next one.
+//! Payments are still owned by the current key, but their owner memos are encrypted to both
+//! keys, so that the wallet can read in-flight payments with either generation. The copies for
+//! the next keys are carried in the `next_owner_memos` of the note body, which the address
+//! folding proof binds, and [`AXfrKeyRing::scan_output`] reads them from there. Once the
+//! rotation is done and the records of the previous key are spent, the previous key is
+//! explicitly deprecated and senders must stop paying to it.
+
+use crate::anon_xfr::{
+    abar_to_abar::AXfrBody,
+    keys::{AXfrKeyPair, AXfrPubKey},
+    parse_memo,
+    structs::{AnonAssetRecord, AxfrOwnerMemo, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+};
+use zei_algebra::prelude::*;
+
+/// The receiving keys that a wallet publishes: payments are made to `current`,
+/// and their owner memos are also encrypted to `next` during a rotation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrReceivingKeys {
+    /// The key that owns new payments.
+    pub current: AXfrPubKey,
+    /// The key that will replace the current key, if a rotation is in progress.
+    pub next: Option<AXfrPubKey>,
+    /// The keys that have been deprecated, to which senders must not pay anymore.
+    pub deprecated: Vec<AXfrPubKey>,
+}
+
+impl AXfrReceivingKeys {
+    /// Check that a payment can be made to `pub_key`, i.e., that it is one of the published
+    /// keys and it has not been deprecated.
+    pub fn check_payable(&self, pub_key: &AXfrPubKey) -> Result<()> {
+        if self.deprecated.contains(pub_key) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        if self.current != *pub_key && self.next.as_ref() != Some(pub_key) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(())
+    }
+}
+
+/// The key pairs of a wallet across a key rotation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AXfrKeyRing {
+    current: AXfrKeyPair,
+    next: Option<AXfrKeyPair>,
+    previous: Option<AXfrKeyPair>,
+    deprecated: Vec<AXfrPubKey>,
+}
+
+impl AXfrKeyRing {
+    /// Create a key ring with a single key pair.
+    pub fn new(current: AXfrKeyPair) -> Self {
+        AXfrKeyRing {
+            current,
+            next: None,
+            previous: None,
+            deprecated: vec![],
+        }
+    }
+
+    /// Return the current key pair.
+    pub fn current(&self) -> &AXfrKeyPair {
+        &self.current
+    }
+
+    /// Return the receiving keys to publish.
+    pub fn receiving_keys(&self) -> AXfrReceivingKeys {
+        AXfrReceivingKeys {
+            current: self.current.get_public_key(),
+            next: self.next.as_ref().map(|kp| kp.get_public_key()),
+            deprecated: self.deprecated.clone(),
+        }
+    }
+
+    /// Start a rotation by generating the next key pair.
+    /// Return an error if a rotation is already in progress.
+    pub fn prepare_rotation<R: CryptoRng + RngCore>(&mut self, prng: &mut R) -> Result<()> {
+        if self.next.is_some() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        self.next = Some(AXfrKeyPair::generate(prng));
+        Ok(())
+    }
+
+    /// Make the next key pair current, and keep the current one as the previous key pair
+    /// until it is deprecated. Return an error if no rotation has been prepared, or if
+    /// the previous key pair of the last rotation has not been deprecated yet.
+    pub fn rotate(&mut self) -> Result<()> {
+        if self.previous.is_some() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let next = self.next.take().c(d!(ZeiError::ParameterError))?;
+        self.previous = Some(core::mem::replace(&mut self.current, next));
+        Ok(())
+    }
+
+    /// Deprecate the previous key pair, which can no longer open nor spend records,
+    /// and return its public key. It must only be called once all its records are spent.
+    pub fn deprecate_previous(&mut self) -> Result<AXfrPubKey> {
+        let previous = self.previous.take().c(d!(ZeiError::ParameterError))?;
+        let pub_key = previous.get_public_key();
+        self.deprecated.push(pub_key);
+        Ok(pub_key)
+    }
+
+    /// Check if a public key has been deprecated.
+    pub fn is_deprecated(&self, pub_key: &AXfrPubKey) -> bool {
+        self.deprecated.contains(pub_key)
+    }
+
+    /// Return the key pair of the ring for a public key, unless it has been deprecated.
+    pub fn key_pair(&self, pub_key: &AXfrPubKey) -> Option<&AXfrKeyPair> {
+        self.active_key_pairs()
+            .find(|kp| kp.get_public_key() == *pub_key)
+    }
+
+    /// Scan a record with its owner memos, i.e., the memo to the owner key and, if any, the
+    /// memo to the next key of the sender's view of the receiving keys. The memos can
+    /// be decrypted by any key pair of the ring, but the record must be owned by one of them.
+    /// Return the opened record and the key pair that owns it, or an error if the record
+    /// is not for this wallet.
+    pub fn scan(
+        &self,
+        record: &AnonAssetRecord,
+        memos: &[&AxfrOwnerMemo],
+    ) -> Result<(OpenAnonAssetRecord, &AXfrKeyPair)> {
+        for memo in memos.iter() {
+            for decryption_key in self.active_key_pairs() {
+                let plaintext = match memo.decrypt(&decryption_key.get_secret_key()) {
+                    Ok(plaintext) => plaintext,
+                    Err(_) => continue,
+                };
+                for owner in self.active_key_pairs() {
+                    if let Ok((amount, asset_type, blind)) = parse_memo(&plaintext, owner, record) {
+                        let mut builder = OpenAnonAssetRecordBuilder::new()
+                            .pub_key(&owner.get_public_key())
+                            .amount(amount)
+                            .asset_type(asset_type);
+                        builder.oabar.blind = blind;
+                        builder.oabar.owner_memo = Some((*memo).clone());
+                        return Ok((builder.build().c(d!())?, owner));
+                    }
+                }
+            }
+        }
+        Err(eg!(ZeiError::DecryptionError))
+    }
+
+    /// Scan an output of an anonymous transfer note with its owner memo and, if any, the copy of
+    /// the memo for the next receiving key, see [`AXfrKeyRing::scan`].
+    pub fn scan_output(
+        &self,
+        body: &AXfrBody,
+        index: usize,
+    ) -> Result<(OpenAnonAssetRecord, &AXfrKeyPair)> {
+        let record = body.outputs.get(index).c(d!(ZeiError::ParameterError))?;
+        let memo = body
+            .owner_memos
+            .get(index)
+            .c(d!(ZeiError::ParameterError))?;
+        let mut memos = vec![memo];
+        if let Some(Some(next_memo)) = body.next_owner_memos.get(index) {
+            memos.push(next_memo);
+        }
+        self.scan(record, &memos).c(d!())
+    }
+
+    fn active_key_pairs(&self) -> impl Iterator<Item = &AXfrKeyPair> {
+        core::iter::once(&self.current)
+            .chain(self.previous.iter())
+            .chain(self.next.iter())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        key_rotation::AXfrKeyRing,
+        keys::AXfrKeyPair,
+        structs::{AnonAssetRecord, OpenAnonAssetRecordBuilder},
+    };
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn key_rotation() {
+        let mut prng = test_rng();
+        let mut ring = AXfrKeyRing::new(AXfrKeyPair::generate(&mut prng));
+        let old_key = ring.current().get_public_key();
+        ring.prepare_rotation(&mut prng).unwrap();
+        assert!(ring.prepare_rotation(&mut prng).is_err());
+        let keys = ring.receiving_keys();
+        let new_key = keys.next.unwrap();
+        assert!(keys.check_payable(&old_key).is_ok());
+        assert!(keys.check_payable(&new_key).is_ok());
+
+        // a payment sent during the rotation window
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(10)
+            .asset_type(AssetType::from_identical_byte(1))
+            .receiving_keys(&keys)
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(*oabar.pub_key_ref(), old_key);
+        let abar = AnonAssetRecord::from_oabar(&oabar);
+        let memo = oabar.get_owner_memo().unwrap();
+        let next_memo = oabar.get_next_owner_memo().unwrap();
+
+        // after the rotation, the payment is still readable with either memo
+        ring.rotate().unwrap();
+        assert_eq!(ring.current().get_public_key(), new_key);
+        for memos in [vec![&memo], vec![&next_memo], vec![&next_memo, &memo]] {
+            let (opened, owner) = ring.scan(&abar, &memos).unwrap();
+            assert_eq!(owner.get_public_key(), old_key);
+            assert_eq!(opened.get_amount(), 10);
+            assert_eq!(opened.get_blind(), oabar.get_blind());
+        }
+
+        // a payment made to a single key after the rotation
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(20)
+            .asset_type(AssetType::from_identical_byte(1))
+            .receiving_keys(&ring.receiving_keys())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert!(oabar.get_next_owner_memo().is_none());
+        let new_abar = AnonAssetRecord::from_oabar(&oabar);
+        let new_memo = oabar.get_owner_memo().unwrap();
+        assert!(ring.scan(&new_abar, &[&new_memo]).is_ok());
+
+        // the previous key must be deprecated before the next rotation
+        ring.prepare_rotation(&mut prng).unwrap();
+        assert!(ring.rotate().is_err());
+        assert_eq!(ring.deprecate_previous().unwrap(), old_key);
+        assert!(ring.is_deprecated(&old_key));
+        assert!(ring.key_pair(&old_key).is_none());
+        assert!(ring.receiving_keys().check_payable(&old_key).is_err());
+        assert!(ring.scan(&abar, &[&memo, &next_memo]).is_err());
+        assert!(ring.scan(&new_abar, &[&new_memo]).is_ok());
+    }
+}
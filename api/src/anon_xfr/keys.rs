@@ -3,6 +3,7 @@ use digest::{generic_array::GenericArray, Digest};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 use zei_algebra::secp256k1::{SECP256K1Scalar, SECP256K1G1, SECP256K1_SCALAR_LEN};
+use zei_algebra::secq256k1::{SECQ256K1Scalar, SECQ256K1_SCALAR_LEN};
 use zei_algebra::{bls12_381::BLSScalar, prelude::*};
 
 /// The length of the secret key for anonymous transfer.
@@ -139,6 +140,29 @@ impl AXfrPubKey {
         Ok([first, second, third])
     }
 
+    /// Recover the public key from its BLS12-381 scalar representation,
+    /// see `get_public_key_scalars`.
+    pub fn from_public_key_scalars(scalars: &[BLSScalar; 3]) -> Result<Self> {
+        let mut bytes = Vec::with_capacity(2 * SECQ256K1_SCALAR_LEN);
+        bytes.extend_from_slice(&scalars[0].to_bytes()[0..31]);
+        bytes.extend_from_slice(&scalars[1].to_bytes()[0..31]);
+        bytes.extend_from_slice(&scalars[2].to_bytes()[0..2]);
+        if scalars[2].to_bytes()[2..].iter().any(|b| *b != 0) {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+
+        let x = SECQ256K1Scalar::from_bytes(&bytes[0..SECQ256K1_SCALAR_LEN]).c(d!())?;
+        let y = SECQ256K1Scalar::from_bytes(&bytes[SECQ256K1_SCALAR_LEN..]).c(d!())?;
+        let mut point = SECP256K1G1::get_point_from_x(&x).c(d!())?;
+        if point.get_y() != y {
+            point = point.neg();
+        }
+        if point.get_y() != y {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        Ok(AXfrPubKey(point))
+    }
+
     /// Encrypt the message
     pub fn encrypt<R: CryptoRng + RngCore>(
         &self,
@@ -196,7 +220,8 @@ impl ZeiFromToBytes for AXfrKeyPair {
         }
 
         let secret_key = AXfrSecretKey(
-            SECP256K1Scalar::from_bytes(&bytes[0..SECP256K1Scalar::bytes_len()]).c(d!())?,
+            SECP256K1Scalar::from_bytes_canonical(&bytes[0..SECP256K1Scalar::bytes_len()])
+                .c(d!())?,
         );
 
         let offset = SECP256K1Scalar::bytes_len();
@@ -232,4 +257,17 @@ mod tests {
     pub(crate) fn ecdsa_from_to_bytes() {
         check_from_to_bytes::<SECP256K1G1>();
     }
+
+    #[test]
+    fn public_key_scalars() {
+        let mut prng = test_rng();
+        for _ in 0..8 {
+            let public_key = AXfrKeyPair::generate(&mut prng).get_public_key();
+            let scalars = public_key.get_public_key_scalars().unwrap();
+            assert_eq!(
+                AXfrPubKey::from_public_key_scalars(&scalars).unwrap(),
+                public_key
+            );
+        }
+    }
 }
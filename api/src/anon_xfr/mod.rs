@@ -8,11 +8,14 @@ use crate::{
             OpenAnonAssetRecord,
         },
     },
-    xfr::structs::{AssetType, ASSET_TYPE_LENGTH},
+    xfr::{
+        balance::BalanceSheet,
+        memo_padding::unpad,
+        structs::{AssetType, ASSET_TYPE_LENGTH},
+    },
 };
 use zei_algebra::{
     bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN},
-    collections::HashMap,
     prelude::*,
 };
 use zei_crypto::basic::rescue::RescueInstance;
@@ -34,12 +37,28 @@ pub mod abar_to_bar;
 pub mod address_folding;
 /// Module for converting transparent assets to anonymous assets.
 pub mod ar_to_abar;
+/// Module for encrypting the outputs of anonymous transfers to an auditor.
+pub mod audit;
 /// Module for converting confidential assets to anonymous assets.
 pub mod bar_to_abar;
+/// Module for building anonymous transfers from a wallet, with coin selection.
+pub mod builder;
+/// Module for selecting the inputs of anonymous transfers that are cheapest to prove.
+pub mod coin_selection;
+/// Module for linking anonymous asset records to commitments of other systems.
+pub mod commit_and_prove;
+/// Module for rotating the receiving keys of a wallet.
+pub mod key_rotation;
 /// Module for the spending key and the public key.
 pub mod keys;
+/// Module for estimating the privacy of planned anonymous transfers.
+pub mod privacy;
+/// Module for binding anonymous transfers to a newer Merkle tree root.
+pub mod root_binding;
 /// Module for shared structures.
 pub mod structs;
+/// Module for tracking the anonymous asset records owned by a wallet.
+pub mod tracker;
 
 /// The asset type for FRA.
 const ASSET_TYPE_FRA: AssetType = AssetType([0; ASSET_TYPE_LENGTH]);
@@ -65,43 +84,37 @@ fn check_inputs(inputs: &[OpenAnonAssetRecord], keypair: &AXfrKeyPair) -> Result
 
 /// Check that for each asset type total input amount == total output amount
 /// and for FRA, total input amount == total output amount + fees.
+/// As in the circuit, at least one input must be of the fee type.
 fn check_asset_amount(
     inputs: &[OpenAnonAssetRecord],
     outputs: &[OpenAnonAssetRecord],
     fee: u32,
 ) -> Result<()> {
     let fee_asset_type = FEE_TYPE;
-    let mut balances = HashMap::new();
+    if !inputs
+        .iter()
+        .any(|record| record.asset_type == fee_asset_type)
+    {
+        return Err(eg!(ZeiError::XfrCreationAssetAmountError));
+    }
 
+    let mut balances = BalanceSheet::new();
     for record in inputs.iter() {
-        if let Some(x) = balances.get_mut(&record.asset_type) {
-            *x += record.amount as i128;
-        } else {
-            balances.insert(record.asset_type, record.amount as i128);
-        }
+        balances
+            .add_input(record.asset_type, record.amount)
+            .c(d!())?;
     }
-
     for record in outputs.iter() {
-        if let Some(x) = balances.get_mut(&record.asset_type) {
-            *x -= record.amount as i128;
-        } else {
-            balances.insert(record.asset_type, -(record.amount as i128));
-        }
+        balances
+            .add_output(record.asset_type, record.amount)
+            .c(d!())?;
     }
-
-    for (&asset_type, &sum) in balances.iter() {
-        if asset_type != fee_asset_type {
-            if sum != 0i128 {
-                return Err(eg!(ZeiError::XfrCreationAssetAmountError));
-            }
-        } else {
-            if sum != fee.into() {
-                return Err(eg!(ZeiError::XfrCreationAssetAmountError));
-            }
-        }
-    }
-
-    Ok(())
+    balances
+        .add_output(fee_asset_type, u64::from(fee))
+        .c(d!())?;
+    balances
+        .check()
+        .c(d!(ZeiError::XfrCreationAssetAmountError))
 }
 
 /// Check that the Merkle roots in input asset records are the same
@@ -127,7 +140,7 @@ fn check_roots(inputs: &[OpenAnonAssetRecord]) -> Result<()> {
 }
 
 /// Parse the owner memo from bytes.
-/// * `bytes` - the memo bytes.
+/// * `bytes` - the memo bytes, which may be padded.
 /// * `key_pair` - the memo bytes.
 /// * `abar` - Associated anonymous blind asset record to check memo info against.
 /// Return Error if memo info does not match the commitment.
@@ -137,16 +150,15 @@ pub fn parse_memo(
     key_pair: &AXfrKeyPair,
     abar: &AnonAssetRecord,
 ) -> Result<(u64, AssetType, BLSScalar)> {
-    if bytes.len() != 8 + ASSET_TYPE_LENGTH + BLS12_381_SCALAR_LEN {
-        return Err(eg!(ZeiError::ParameterError));
-    }
+    let bytes = unpad(bytes, &[8 + ASSET_TYPE_LENGTH + BLS12_381_SCALAR_LEN])
+        .c(d!(ZeiError::ParameterError))?;
     let amount = u8_le_slice_to_u64(&bytes[0..8]);
     let mut i = 8;
     let mut asset_type_array = [0u8; ASSET_TYPE_LENGTH];
     asset_type_array.copy_from_slice(&bytes[i..i + ASSET_TYPE_LENGTH]);
     let asset_type = AssetType(asset_type_array);
     i += ASSET_TYPE_LENGTH;
-    let blind = BLSScalar::from_bytes(&bytes[i..i + BLS12_381_SCALAR_LEN])
+    let blind = BLSScalar::from_bytes_canonical(&bytes[i..i + BLS12_381_SCALAR_LEN])
         .c(d!(ZeiError::ParameterError))?;
 
     let public_key_scalars = key_pair.get_public_key().get_public_key_scalars()?;
@@ -225,6 +237,49 @@ pub(crate) const AMOUNT_LEN: usize = 64;
 /// Depth of the Merkle Tree circuit.
 pub const TREE_DEPTH: usize = 20;
 
+/// Convert a scalar to an amount, and fail if it is not below 2^{`AMOUNT_LEN`},
+/// instead of silently truncating it to its lowest 64 bits.
+pub fn scalar_to_amount(scalar: &BLSScalar) -> Result<u64> {
+    let bytes = scalar.to_bytes();
+    if bytes[AMOUNT_LEN / 8..].iter().any(|byte| *byte != 0) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let mut amount_bytes = [0u8; AMOUNT_LEN / 8];
+    amount_bytes.copy_from_slice(&bytes[..AMOUNT_LEN / 8]);
+    Ok(u64::from_le_bytes(amount_bytes))
+}
+
+/// A variable of the constraint system that is range-checked to `AMOUNT_LEN` bits,
+/// so that its value is always the scalar of a u64 amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AmountVar(VarIndex);
+
+impl AmountVar {
+    /// Add a variable for the amount to the constraint system, with the range check.
+    pub fn new(cs: &mut TurboPlonkCS, amount: u64) -> Self {
+        let var = cs.new_variable(BLSScalar::from(amount));
+        Self::range_checked(cs, var)
+    }
+
+    /// Range-check an existing variable of the constraint system as an amount.
+    pub fn range_checked(cs: &mut TurboPlonkCS, var: VarIndex) -> Self {
+        cs.range_check(var, AMOUNT_LEN);
+        AmountVar(var)
+    }
+
+    /// Return the index of the variable.
+    pub fn var(&self) -> VarIndex {
+        self.0
+    }
+
+    /// Return the amount of the variable in the witness of the constraint system, which is the
+    /// amount that the circuit checks, and fail if the witness does not satisfy the range check.
+    pub fn amount(&self, cs: &TurboPlonkCS) -> Result<u64> {
+        let scalar = cs.witness.get(self.0).c(d!(ZeiError::IndexError))?;
+        scalar_to_amount(scalar).c(d!())
+    }
+}
+
 /// Add the commitment constraints to the constraint system:
 /// comm = hash(hash(blinding, amount, asset_type, 0), pubkey_x, 0, 0).
 pub fn commit_in_cs(
@@ -368,5 +423,59 @@ pub fn compute_merkle_root_variables(
     node_var
 }
 
+/// Compute the Merkle tree root of the record at position `uid` with the commitment
+/// `commitment`, given its Merkle path, natively.
+pub fn compute_merkle_root(uid: u64, commitment: &Commitment, path: &MTPath) -> Result<BLSScalar> {
+    let hash = RescueInstance::new();
+    let zero = BLSScalar::zero();
+
+    let mut node = hash.rescue(&[BLSScalar::from(uid), *commitment, zero, zero])[0];
+    for path_node in path.nodes.iter() {
+        let input = match (path_node.is_left_child, path_node.is_right_child) {
+            (1, 0) => [node, path_node.siblings1, path_node.siblings2, zero],
+            (0, 1) => [path_node.siblings1, path_node.siblings2, node, zero],
+            (0, 0) => [path_node.siblings1, node, path_node.siblings2, zero],
+            _ => return Err(eg!(ZeiError::ParameterError)),
+        };
+        node = hash.rescue(&input)[0];
+    }
+    Ok(node)
+}
+
 /// The number of the Bulletproofs generators needed for anonymous transfer.
 pub const ANON_XFR_BP_GENS_LEN: usize = 2048;
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{scalar_to_amount, AmountVar, TurboPlonkCS};
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+    #[test]
+    fn checked_amounts() {
+        assert_eq!(scalar_to_amount(&BLSScalar::zero()).unwrap(), 0);
+        assert_eq!(
+            scalar_to_amount(&BLSScalar::from(u64::MAX)).unwrap(),
+            u64::MAX
+        );
+        let pow_2_64 = BLSScalar::from(u64::MAX).add(&BLSScalar::one());
+        msg_eq!(
+            ZeiError::ParameterError,
+            scalar_to_amount(&pow_2_64).unwrap_err()
+        );
+        assert!(scalar_to_amount(&BLSScalar::one().neg()).is_err());
+
+        let mut cs = TurboPlonkCS::new();
+        let amount_var = AmountVar::new(&mut cs, 1 << 40);
+        assert_eq!(amount_var.amount(&cs).unwrap(), 1 << 40);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness, &[]).is_ok());
+
+        // a scalar beyond 64 bits cannot be claimed as an amount, natively nor in the circuit
+        let mut cs = TurboPlonkCS::new();
+        let var = cs.new_variable(pow_2_64);
+        let amount_var = AmountVar::range_checked(&mut cs, var);
+        assert!(amount_var.amount(&cs).is_err());
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness, &[]).is_err());
+    }
+}
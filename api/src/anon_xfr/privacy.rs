@@ -0,0 +1,265 @@
+use crate::anon_xfr::structs::OpenAnonAssetRecord;
+use crate::errors::ZeiError;
+use crate::setup::MAX_ANONYMOUS_RECORD_NUMBER;
+use zei_algebra::{collections::HashSet, prelude::*};
+
+/// The default minimal number of leaves in the Merkle tree for a healthy anonymity set.
+pub const DEFAULT_MIN_ANONYMITY_SET_SIZE: u64 = 1024;
+/// The default minimal number of leaves appended after an input for it to be considered aged.
+pub const DEFAULT_MIN_INPUT_AGE: u64 = 64;
+
+/// The state of the Merkle tree that the transfer is proven against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeState {
+    /// The number of leaves in the tree under the root used by the transfer.
+    pub num_leaves: u64,
+}
+
+/// The thresholds below which a planned transfer is reported as weak.
+///
+/// Every leaf of the Merkle tree acts as a decoy of every input, so the anonymity set is the
+/// tree under the root, and the thresholds restrict how much of it is plausibly spent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivacyParams {
+    /// The minimal number of leaves in the Merkle tree.
+    pub min_anonymity_set_size: u64,
+    /// The minimal number of leaves appended after each input.
+    pub min_input_age: u64,
+}
+
+impl Default for PrivacyParams {
+    fn default() -> Self {
+        PrivacyParams {
+            min_anonymity_set_size: DEFAULT_MIN_ANONYMITY_SET_SIZE,
+            min_input_age: DEFAULT_MIN_INPUT_AGE,
+        }
+    }
+}
+
+/// A reason for which a planned transfer may leak information.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrivacyWarning {
+    /// The Merkle tree has fewer leaves than the minimum.
+    SmallAnonymitySet {
+        /// The number of leaves.
+        size: u64,
+    },
+    /// The input was inserted recently, so spending it soon after narrows the likely candidates.
+    RecentInput {
+        /// The ID of the input in the Merkle tree.
+        uid: u64,
+        /// The number of leaves appended after the input.
+        age: u64,
+    },
+    /// The input is used more than once.
+    DuplicateInput {
+        /// The ID of the input in the Merkle tree.
+        uid: u64,
+    },
+    /// Two inputs are close enough in the tree to have been created by the same transfer,
+    /// which links them if that transfer is known.
+    CoCreatedInputs {
+        /// The ID of the first input.
+        first_uid: u64,
+        /// The ID of the second input.
+        second_uid: u64,
+    },
+    /// An output is sent to the public key of an input, which links it to the payer.
+    OutputToInputKey {
+        /// The position of the output.
+        output: usize,
+    },
+    /// Two outputs are sent to the same public key, which links them together.
+    ReusedOutputKey {
+        /// The position of the first output.
+        first_output: usize,
+        /// The position of the second output.
+        second_output: usize,
+    },
+}
+
+/// The privacy indicators of a planned anonymous transfer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivacyReport {
+    /// The number of leaves that any input may be, as seen by an observer.
+    pub anonymity_set_size: u64,
+    /// The smallest number of leaves appended after one of the inputs.
+    pub min_input_age: u64,
+    /// The warnings found in the planned transfer.
+    pub warnings: Vec<PrivacyWarning>,
+}
+
+impl PrivacyReport {
+    /// Return true if no warning is raised.
+    pub fn is_healthy(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// Estimate the privacy of an anonymous transfer before proving it.
+///
+/// The report is advisory: it relies on heuristics on the positions of the inputs and on the
+/// public keys, and a transfer without warnings can still be linked by information off the ledger.
+/// Every input must carry its Merkle tree leaf information.
+pub fn estimate_anon_xfr_privacy(
+    tree: &TreeState,
+    inputs: &[OpenAnonAssetRecord],
+    outputs: &[OpenAnonAssetRecord],
+    params: &PrivacyParams,
+) -> Result<PrivacyReport> {
+    let mut uids = Vec::with_capacity(inputs.len());
+    for input in inputs.iter() {
+        let uid = input
+            .mt_leaf_info
+            .as_ref()
+            .c(d!(ZeiError::ParameterError))?
+            .uid;
+        if uid >= tree.num_leaves {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        uids.push(uid);
+    }
+
+    let mut warnings = vec![];
+    if tree.num_leaves < params.min_anonymity_set_size {
+        warnings.push(PrivacyWarning::SmallAnonymitySet {
+            size: tree.num_leaves,
+        });
+    }
+
+    let mut min_input_age = tree.num_leaves;
+    let mut seen = HashSet::new();
+    for (i, uid) in uids.iter().enumerate() {
+        let age = tree.num_leaves - 1 - uid;
+        min_input_age = min_input_age.min(age);
+        if age < params.min_input_age {
+            warnings.push(PrivacyWarning::RecentInput { uid: *uid, age });
+        }
+        if !seen.insert(*uid) {
+            warnings.push(PrivacyWarning::DuplicateInput { uid: *uid });
+            continue;
+        }
+        // a transfer inserts its outputs as consecutive leaves
+        for other in uids[..i].iter() {
+            let (first_uid, second_uid) = (*other.min(uid), *other.max(uid));
+            if first_uid != second_uid
+                && second_uid - first_uid < MAX_ANONYMOUS_RECORD_NUMBER as u64
+            {
+                warnings.push(PrivacyWarning::CoCreatedInputs {
+                    first_uid,
+                    second_uid,
+                });
+            }
+        }
+    }
+
+    for (i, output) in outputs.iter().enumerate() {
+        if inputs.iter().any(|input| input.pub_key == output.pub_key) {
+            warnings.push(PrivacyWarning::OutputToInputKey { output: i });
+        }
+        for (j, other) in outputs[..i].iter().enumerate() {
+            if other.pub_key == output.pub_key {
+                warnings.push(PrivacyWarning::ReusedOutputKey {
+                    first_output: j,
+                    second_output: i,
+                });
+            }
+        }
+    }
+
+    Ok(PrivacyReport {
+        anonymity_set_size: tree.num_leaves,
+        min_input_age,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        keys::AXfrKeyPair,
+        privacy::{estimate_anon_xfr_privacy, PrivacyParams, PrivacyWarning, TreeState},
+        structs::{MTLeafInfo, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+    };
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    fn gen_oabar<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        keypair: &AXfrKeyPair,
+        uid: Option<u64>,
+    ) -> OpenAnonAssetRecord {
+        let mut builder = OpenAnonAssetRecordBuilder::new()
+            .amount(10)
+            .asset_type(AssetType::from_identical_byte(0u8))
+            .pub_key(&keypair.get_public_key());
+        if let Some(uid) = uid {
+            builder = builder.mt_leaf_info(MTLeafInfo {
+                uid,
+                ..Default::default()
+            });
+        }
+        builder.finalize(prng).unwrap().build().unwrap()
+    }
+
+    #[test]
+    fn anon_xfr_privacy() {
+        let mut prng = test_rng();
+        let keypairs = (0..4)
+            .map(|_| AXfrKeyPair::generate(&mut prng))
+            .collect::<Vec<_>>();
+        let params = PrivacyParams::default();
+        let tree = TreeState { num_leaves: 10000 };
+
+        let inputs = vec![
+            gen_oabar(&mut prng, &keypairs[0], Some(100)),
+            gen_oabar(&mut prng, &keypairs[1], Some(5000)),
+        ];
+        let outputs = vec![
+            gen_oabar(&mut prng, &keypairs[2], None),
+            gen_oabar(&mut prng, &keypairs[3], None),
+        ];
+        let report = estimate_anon_xfr_privacy(&tree, &inputs, &outputs, &params).unwrap();
+        assert!(report.is_healthy());
+        assert_eq!(report.anonymity_set_size, 10000);
+        assert_eq!(report.min_input_age, 4999);
+
+        // a small tree, a recent input created together with another one, and key reuse
+        let tree = TreeState { num_leaves: 103 };
+        let inputs = vec![
+            gen_oabar(&mut prng, &keypairs[0], Some(100)),
+            gen_oabar(&mut prng, &keypairs[1], Some(102)),
+        ];
+        let outputs = vec![
+            gen_oabar(&mut prng, &keypairs[1], None),
+            gen_oabar(&mut prng, &keypairs[2], None),
+            gen_oabar(&mut prng, &keypairs[2], None),
+        ];
+        let report = estimate_anon_xfr_privacy(&tree, &inputs, &outputs, &params).unwrap();
+        assert_eq!(report.min_input_age, 0);
+        assert_eq!(
+            report.warnings,
+            vec![
+                PrivacyWarning::SmallAnonymitySet { size: 103 },
+                PrivacyWarning::RecentInput { uid: 100, age: 2 },
+                PrivacyWarning::RecentInput { uid: 102, age: 0 },
+                PrivacyWarning::CoCreatedInputs {
+                    first_uid: 100,
+                    second_uid: 102
+                },
+                PrivacyWarning::OutputToInputKey { output: 0 },
+                PrivacyWarning::ReusedOutputKey {
+                    first_output: 1,
+                    second_output: 2
+                },
+            ]
+        );
+
+        // inputs must be in the tree
+        let outside = vec![gen_oabar(&mut prng, &keypairs[0], Some(103))];
+        assert!(estimate_anon_xfr_privacy(&tree, &outside, &[], &params).is_err());
+        let no_leaf = vec![gen_oabar(&mut prng, &keypairs[0], None)];
+        assert!(estimate_anon_xfr_privacy(&tree, &no_leaf, &[], &params).is_err());
+    }
+}
@@ -0,0 +1,346 @@
+use crate::anon_xfr::{
+    abar_to_abar::{verify_anon_xfr_note, AXfrNote},
+    structs::MTPath,
+};
+use crate::errors::ZeiError;
+use crate::setup::VerifierParams;
+use digest::{consts::U64, Digest};
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+use zei_crypto::basic::rescue::RescueInstance;
+
+/// A proof that a Merkle tree root is an append-only extension of an older root.
+///
+/// The ternary Merkle tree of the ledger is filled from left to right and empty subtrees
+/// are represented by zero. Given the last leaf `uid` of the old tree, every node on the left
+/// of its path is shared by the old and the new tree, and every node on the right of its path
+/// is zero in the old tree. Therefore, the path of this leaf in the new tree determines both
+/// roots, and it does not involve any secret of the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MTRootUpdateProof {
+    /// The ID of the last leaf of the old tree.
+    pub uid: u64,
+    /// The hash of the last leaf of the old tree.
+    pub leaf: BLSScalar,
+    /// The authentication path of this leaf in the new tree.
+    pub path: MTPath,
+}
+
+/// An anonymous transfer note whose Merkle root has been updated after the proof was generated.
+///
+/// This allows a note to be generated on an air-gapped device against the root that the device
+/// last synchronized, carried to an online device, and bound to the latest root before broadcast.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AXfrRootBoundNote {
+    /// The anonymous transfer note, proven against an older Merkle root.
+    pub note: AXfrNote,
+    /// The Merkle root that the note is bound to.
+    pub merkle_root: BLSScalar,
+    /// An index of the bound Merkle root in the ledger.
+    pub merkle_root_version: u64,
+    /// The proof that the bound root extends the root of the note.
+    pub root_update: MTRootUpdateProof,
+}
+
+/// Compute the roots of the old tree and the new tree from a root update proof.
+fn compute_updated_roots(proof: &MTRootUpdateProof) -> Result<(BLSScalar, BLSScalar)> {
+    let depth = proof.path.nodes.len();
+    if depth == 0
+        || 3u64
+            .checked_pow(depth as u32)
+            .map_or(false, |max_leaves| proof.uid >= max_leaves)
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let hash = RescueInstance::new();
+    let zero = BLSScalar::zero();
+
+    let mut uid = proof.uid;
+    let mut old_node = proof.leaf;
+    let mut new_node = proof.leaf;
+    for node in proof.path.nodes.iter() {
+        let position = uid % 3;
+        uid /= 3;
+
+        // the position of the node must agree with the leaf ID
+        let expected = match position {
+            0 => (1u8, 0u8),
+            1 => (0u8, 0u8),
+            _ => (0u8, 1u8),
+        };
+        if (node.is_left_child, node.is_right_child) != expected {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let (old_input, new_input) = match position {
+            0 => (
+                [old_node, zero, zero, zero],
+                [new_node, node.siblings1, node.siblings2, zero],
+            ),
+            1 => (
+                [node.siblings1, old_node, zero, zero],
+                [node.siblings1, new_node, node.siblings2, zero],
+            ),
+            _ => (
+                [node.siblings1, node.siblings2, old_node, zero],
+                [node.siblings1, node.siblings2, new_node, zero],
+            ),
+        };
+        old_node = hash.rescue(&old_input)[0];
+        new_node = hash.rescue(&new_input)[0];
+    }
+
+    Ok((old_node, new_node))
+}
+
+/// Verify that `new_root` is an append-only extension of `old_root`.
+pub fn verify_root_update(
+    old_root: &BLSScalar,
+    new_root: &BLSScalar,
+    proof: &MTRootUpdateProof,
+) -> Result<()> {
+    let (computed_old_root, computed_new_root) = compute_updated_roots(proof).c(d!())?;
+    if computed_old_root != *old_root || computed_new_root != *new_root {
+        return Err(eg!(ZeiError::AXfrVerificationError));
+    }
+    Ok(())
+}
+
+/// Bind an anonymous transfer note to a newer Merkle root.
+/// This step is cheap and does not require the spending key.
+pub fn bind_merkle_root(
+    note: AXfrNote,
+    merkle_root: &BLSScalar,
+    merkle_root_version: u64,
+    root_update: MTRootUpdateProof,
+) -> Result<AXfrRootBoundNote> {
+    if merkle_root_version < note.body.merkle_root_version {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    verify_root_update(&note.body.merkle_root, merkle_root, &root_update).c(d!())?;
+
+    Ok(AXfrRootBoundNote {
+        note,
+        merkle_root: *merkle_root,
+        merkle_root_version,
+        root_update,
+    })
+}
+
+/// Verify an anonymous transfer note that has been bound to a newer Merkle root.
+pub fn verify_root_bound_anon_xfr_note<D: Digest<OutputSize = U64> + Default>(
+    params: &VerifierParams,
+    bound_note: &AXfrRootBoundNote,
+    merkle_root: &BLSScalar,
+    hash: D,
+) -> Result<()> {
+    if *merkle_root != bound_note.merkle_root
+        || bound_note.merkle_root_version < bound_note.note.body.merkle_root_version
+    {
+        return Err(eg!(ZeiError::AXfrVerificationError));
+    }
+    verify_root_update(
+        &bound_note.note.body.merkle_root,
+        merkle_root,
+        &bound_note.root_update,
+    )
+    .c(d!(ZeiError::AXfrVerificationError))?;
+
+    verify_anon_xfr_note(
+        params,
+        &bound_note.note,
+        &bound_note.note.body.merkle_root,
+        hash,
+    )
+    .c(d!(ZeiError::AXfrVerificationError))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::anon_xfr::{
+        abar_to_abar::{finish_anon_xfr_note, init_anon_xfr_note},
+        commit,
+        keys::AXfrKeyPair,
+        root_binding::{
+            bind_merkle_root, verify_root_bound_anon_xfr_note, verify_root_update,
+            MTRootUpdateProof,
+        },
+        structs::{MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecordBuilder},
+        FEE_TYPE,
+    };
+    use crate::setup::{ProverParams, VerifierParams};
+    use ark_std::test_rng;
+    use sha2::Sha512;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use zei_crypto::basic::rescue::RescueInstance;
+
+    fn node(siblings1: BLSScalar, siblings2: BLSScalar, position: u64) -> MTNode {
+        let (is_left_child, is_right_child) = match position {
+            0 => (1u8, 0u8),
+            1 => (0u8, 0u8),
+            _ => (0u8, 1u8),
+        };
+        MTNode {
+            siblings1,
+            siblings2,
+            is_left_child,
+            is_right_child,
+        }
+    }
+
+    #[test]
+    fn test_root_update() {
+        let mut prng = test_rng();
+        let hash = RescueInstance::new();
+        let zero = BLSScalar::zero();
+
+        // a tree of depth 2 with nine leaves
+        let leaves: Vec<BLSScalar> = (0..9).map(|_| BLSScalar::random(&mut prng)).collect();
+        let padded = |n: usize| {
+            let mut padded = leaves[..n].to_vec();
+            padded.resize(9, zero);
+            padded
+        };
+        // the middle level of the tree with the first `n` leaves, where empty subtrees are zero
+        let children = |n: usize| {
+            padded(n)
+                .chunks(3)
+                .map(|c| {
+                    if c.iter().all(|x| x.is_zero()) {
+                        zero
+                    } else {
+                        hash.rescue(&[c[0], c[1], c[2], zero])[0]
+                    }
+                })
+                .collect::<Vec<BLSScalar>>()
+        };
+        let root_of = |n: usize| {
+            let c = children(n);
+            hash.rescue(&[c[0], c[1], c[2], zero])[0]
+        };
+
+        // the old tree has four leaves, the new tree has eight leaves
+        let (old_n, new_n) = (4usize, 8usize);
+        let new_leaves = padded(new_n);
+        let new_children = children(new_n);
+        let proof = MTRootUpdateProof {
+            uid: 3,
+            leaf: leaves[3],
+            path: MTPath::new(vec![
+                node(new_leaves[4], new_leaves[5], 0),
+                node(new_children[0], new_children[2], 1),
+            ]),
+        };
+        assert!(verify_root_update(&root_of(old_n), &root_of(new_n), &proof).is_ok());
+        assert!(verify_root_update(&root_of(old_n), &root_of(old_n), &proof).is_err());
+        assert!(verify_root_update(&root_of(old_n - 1), &root_of(new_n), &proof).is_err());
+
+        // a tree that rewrites an old leaf is not an extension
+        let mut rewritten = proof.clone();
+        rewritten.path.nodes[1].siblings1 = BLSScalar::random(&mut prng);
+        let rewritten_root = {
+            let middle = hash.rescue(&[leaves[3], new_leaves[4], new_leaves[5], zero])[0];
+            hash.rescue(&[
+                rewritten.path.nodes[1].siblings1,
+                middle,
+                new_children[2],
+                zero,
+            ])[0]
+        };
+        assert!(verify_root_update(&root_of(old_n), &rewritten_root, &rewritten).is_err());
+
+        // the positions must agree with the leaf ID
+        let mut wrong_position = proof.clone();
+        wrong_position.path.nodes[0] = node(new_leaves[4], new_leaves[5], 2);
+        assert!(verify_root_update(&root_of(old_n), &root_of(new_n), &wrong_position).is_err());
+    }
+
+    #[test]
+    fn test_root_bound_anon_xfr() {
+        let mut prng = test_rng();
+        let params = ProverParams::new(1, 1, Some(1)).unwrap();
+        let hash = RescueInstance::new();
+        let zero = BLSScalar::zero();
+
+        let fee_amount = 25u32;
+        let output_amount = 10u64;
+        let input_amount = output_amount + fee_amount as u64;
+
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let keypair_out = AXfrKeyPair::generate(&mut prng);
+
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(input_amount)
+            .asset_type(FEE_TYPE)
+            .pub_key(&keypair.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let commitment = commit(
+            oabar.pub_key_ref(),
+            &oabar.get_blind(),
+            oabar.get_amount(),
+            &oabar.get_asset_type(),
+        )
+        .unwrap();
+
+        // the air-gapped device knows a tree with only its own record
+        let leaf = hash.rescue(&[zero, commitment, zero, zero])[0];
+        let old_root = hash.rescue(&[leaf, zero, zero, zero])[0];
+        let mut oabar_in = oabar;
+        oabar_in.update_mt_leaf_info(MTLeafInfo {
+            path: MTPath::new(vec![node(zero, zero, 0)]),
+            root: old_root,
+            root_version: 1,
+            uid: 0,
+        });
+
+        let oabar_out = OpenAnonAssetRecordBuilder::new()
+            .amount(output_amount)
+            .asset_type(FEE_TYPE)
+            .pub_key(&keypair_out.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let pre_note = init_anon_xfr_note(&[oabar_in], &[oabar_out], fee_amount, &keypair).unwrap();
+        let note = finish_anon_xfr_note(&mut prng, &params, pre_note, Sha512::new()).unwrap();
+
+        // meanwhile, another record has been added to the ledger
+        let other_leaf = BLSScalar::random(&mut prng);
+        let new_root = hash.rescue(&[leaf, other_leaf, zero, zero])[0];
+        let root_update = MTRootUpdateProof {
+            uid: 0,
+            leaf,
+            path: MTPath::new(vec![node(other_leaf, zero, 0)]),
+        };
+
+        assert!(bind_merkle_root(note.clone(), &new_root, 0, root_update.clone()).is_err());
+        assert!(bind_merkle_root(
+            note.clone(),
+            &BLSScalar::random(&mut prng),
+            2,
+            root_update.clone()
+        )
+        .is_err());
+        let bound_note = bind_merkle_root(note, &new_root, 2, root_update).unwrap();
+
+        let verifier_params = VerifierParams::from(params);
+        assert!(verify_root_bound_anon_xfr_note(
+            &verifier_params,
+            &bound_note,
+            &new_root,
+            Sha512::new()
+        )
+        .is_ok());
+        assert!(verify_root_bound_anon_xfr_note(
+            &verifier_params,
+            &bound_note,
+            &old_root,
+            Sha512::new()
+        )
+        .is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use crate::anon_xfr::keys::AXfrSecretKey;
 use crate::anon_xfr::{
     commit, decrypt_memo,
+    key_rotation::AXfrReceivingKeys,
     keys::{AXfrKeyPair, AXfrPubKey},
 };
-use crate::xfr::structs::AssetType;
+use crate::xfr::{memo_padding::MemoPaddingPolicy, structs::AssetType};
 use serde::Serialize;
 use wasm_bindgen::prelude::*;
 use zei_algebra::{bls12_381::BLSScalar, prelude::*};
@@ -86,6 +87,10 @@ pub struct OpenAnonAssetRecord {
     pub(crate) pub_key: AXfrPubKey,
     pub(crate) owner_memo: Option<AxfrOwnerMemo>,
     pub(crate) mt_leaf_info: Option<MTLeafInfo>,
+    #[serde(default)]
+    pub(crate) next_pub_key: Option<AXfrPubKey>,
+    #[serde(default)]
+    pub(crate) next_owner_memo: Option<AxfrOwnerMemo>,
 }
 
 impl OpenAnonAssetRecord {
@@ -120,12 +125,18 @@ impl OpenAnonAssetRecord {
     pub fn get_owner_memo(&self) -> Option<AxfrOwnerMemo> {
         self.owner_memo.clone()
     }
+
+    /// Get the copy of the owner memo for the next receiving key, if any
+    pub fn get_next_owner_memo(&self) -> Option<AxfrOwnerMemo> {
+        self.next_owner_memo.clone()
+    }
 }
 
 #[derive(Default)]
 /// The builder for an opened anonymous asset record.
 pub struct OpenAnonAssetRecordBuilder {
     pub(crate) oabar: OpenAnonAssetRecord,
+    memo_padding: MemoPaddingPolicy,
 }
 
 impl OpenAnonAssetRecordBuilder {
@@ -154,6 +165,20 @@ impl OpenAnonAssetRecordBuilder {
         self
     }
 
+    /// Specify the receiving keys of a wallet in a key rotation: the record is owned by the
+    /// current key, and the owner memo is also encrypted to the next key, if any
+    pub fn receiving_keys(mut self, keys: &AXfrReceivingKeys) -> Self {
+        self.oabar.pub_key = keys.current;
+        self.oabar.next_pub_key = keys.next;
+        self
+    }
+
+    /// Specify the padding policy of the owner memos
+    pub fn memo_padding(mut self, padding: MemoPaddingPolicy) -> Self {
+        self.memo_padding = padding;
+        self
+    }
+
     /// Update mt_leaf_info
     pub fn mt_leaf_info(mut self, mt_leaf_info: MTLeafInfo) -> Self {
         self.oabar.update_mt_leaf_info(mt_leaf_info);
@@ -174,8 +199,12 @@ impl OpenAnonAssetRecordBuilder {
         msg.extend_from_slice(&self.oabar.amount.to_le_bytes());
         msg.extend_from_slice(&self.oabar.asset_type.0);
         msg.extend_from_slice(&self.oabar.blind.to_bytes());
+        let msg = self.memo_padding.pad(&msg).c(d!())?;
 
         self.oabar.owner_memo = Some(AxfrOwnerMemo::new(prng, &self.oabar.pub_key, &msg)?);
+        if let Some(next_pub_key) = self.oabar.next_pub_key {
+            self.oabar.next_owner_memo = Some(AxfrOwnerMemo::new(prng, &next_pub_key, &msg)?);
+        }
         Ok(self)
     }
 
@@ -310,6 +339,7 @@ pub struct AxfrOwnerMemo {
     /// The random point used to generate the shared point.
     pub point: AXfrPubKey,
     /// The ciphertext.
+    #[serde(deserialize_with = "zei_bounded_serde::memo_bytes")]
     pub ctext: Vec<u8>,
 }
 
@@ -333,7 +363,8 @@ impl AxfrOwnerMemo {
 #[cfg(test)]
 mod test {
     use crate::anon_xfr::keys::AXfrKeyPair;
-    use crate::anon_xfr::structs::AXfrPubKey;
+    use crate::anon_xfr::structs::{AXfrPubKey, AnonAssetRecord, OpenAnonAssetRecordBuilder};
+    use crate::xfr::{memo_padding::MemoPaddingPolicy, structs::AssetType};
     use ark_std::test_rng;
     use zei_algebra::prelude::*;
 
@@ -362,4 +393,34 @@ mod test {
         let reformed_key_pair = AXfrKeyPair::zei_from_bytes(bytes.as_slice()).unwrap();
         assert_eq!(keypair, reformed_key_pair);
     }
+
+    #[test]
+    fn test_padded_owner_memo() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+
+        let mut lengths = vec![];
+        for padding in [MemoPaddingPolicy::default(), MemoPaddingPolicy::standard()] {
+            let oabar = OpenAnonAssetRecordBuilder::new()
+                .amount(10)
+                .asset_type(AssetType::from_identical_byte(1))
+                .pub_key(&keypair.get_public_key())
+                .memo_padding(padding)
+                .finalize(&mut prng)
+                .unwrap()
+                .build()
+                .unwrap();
+            let abar = AnonAssetRecord::from_oabar(&oabar);
+            let memo = oabar.get_owner_memo().unwrap();
+            lengths.push(memo.ctext.len());
+
+            let opened = OpenAnonAssetRecordBuilder::from_abar(&abar, memo, &keypair)
+                .unwrap()
+                .build()
+                .unwrap();
+            assert_eq!(opened.get_amount(), 10);
+            assert_eq!(opened.get_blind(), oabar.get_blind());
+        }
+        assert!(lengths[1] > lengths[0]);
+    }
 }
@@ -0,0 +1,271 @@
+//! Tracking of the anonymous asset records owned by a wallet.
+//!
+//! The ledger appends the anonymous asset records of each block to its Merkle tree, in order,
+//! so that the position of a record, its `uid`, is the number of records before it. The tree
+//! itself is kept by the ledger: an [`OwnedAbarTracker`] is a sparse index of its leaves, which
+//! keeps only the records that the wallet can open with its key pair, by their positions, along
+//! with their nullifiers. It ingests the blocks in the same order with the owner memos of their
+//! records, and marks the owned records as spent when their nullifiers appear in a block.
+//! The authentication paths of the owned records are then fetched from the ledger by their
+//! positions, and accepted once they lead from the records to the root of the tree, so that
+//! the unspent records can be the inputs of the next transfers of the wallet.
+
+use crate::anon_xfr::{
+    compute_merkle_root,
+    keys::{AXfrKeyPair, AXfrPubKey},
+    nullify,
+    structs::{
+        AnonAssetRecord, AxfrOwnerMemo, MTLeafInfo, Nullifier, OpenAnonAssetRecord,
+        OpenAnonAssetRecordBuilder,
+    },
+};
+use crate::xfr::structs::AssetType;
+use std::io::{Read, Write};
+use zei_algebra::{collections::BTreeMap, prelude::*};
+
+/// An anonymous asset record owned by the wallet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OwnedAbar {
+    /// The position of the record in the Merkle tree.
+    pub uid: u64,
+    /// The opened record, with its Merkle tree leaf information once it is known.
+    pub record: OpenAnonAssetRecord,
+    /// The nullifier that is revealed when the record is spent.
+    pub nullifier: Nullifier,
+    /// Whether the nullifier has appeared in a block.
+    pub spent: bool,
+}
+
+/// The state of a wallet: the anonymous asset records that it owns, by their positions in the
+/// Merkle tree, and the number of records of the blocks ingested so far.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OwnedAbarTracker {
+    pub_key: AXfrPubKey,
+    next_uid: u64,
+    owned: BTreeMap<u64, OwnedAbar>,
+}
+
+impl OwnedAbarTracker {
+    /// Create a tracker for the wallet with the public key `pub_key`, which starts to ingest
+    /// blocks from the record at position `start_uid`, e.g., 0 for a new wallet.
+    pub fn new(pub_key: AXfrPubKey, start_uid: u64) -> Self {
+        OwnedAbarTracker {
+            pub_key,
+            next_uid: start_uid,
+            owned: BTreeMap::new(),
+        }
+    }
+
+    /// Return the position of the first record of the next block.
+    pub fn next_uid(&self) -> u64 {
+        self.next_uid
+    }
+
+    /// Ingest a block: its records with their owner memos, in the order of the Merkle tree,
+    /// and the nullifiers of the records that it spends.
+    /// Return the positions of the records of the block owned by the wallet.
+    pub fn ingest_block(
+        &mut self,
+        keypair: &AXfrKeyPair,
+        records: &[(AnonAssetRecord, AxfrOwnerMemo)],
+        nullifiers: &[Nullifier],
+    ) -> Result<Vec<u64>> {
+        if keypair.get_public_key() != self.pub_key {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        // the tracker is only updated once the whole block is ingested
+        let mut block = vec![];
+        for (uid, (record, memo)) in (self.next_uid..).zip(records.iter()) {
+            // the records of other wallets cannot be opened
+            let builder = match OpenAnonAssetRecordBuilder::from_abar(record, memo.clone(), keypair)
+            {
+                Ok(builder) => builder,
+                Err(_) => continue,
+            };
+            let record = builder.build().c(d!())?;
+            let nullifier =
+                nullify(keypair, record.get_amount(), &record.get_asset_type(), uid).c(d!())?;
+            block.push(OwnedAbar {
+                uid,
+                record,
+                nullifier,
+                spent: false,
+            });
+        }
+
+        self.next_uid += records.len() as u64;
+        let uids = block.iter().map(|owned| owned.uid).collect();
+        self.owned
+            .extend(block.into_iter().map(|owned| (owned.uid, owned)));
+
+        // a record may be spent in the block that creates it
+        for owned in self.owned.values_mut() {
+            if nullifiers.contains(&owned.nullifier) {
+                owned.spent = true;
+            }
+        }
+        Ok(uids)
+    }
+
+    /// Set the Merkle tree leaf information of an owned record, e.g., the authentication path
+    /// of its position in the tree of the ledger, once the path leads from the record to the
+    /// root of the tree.
+    pub fn update_mt_leaf_info(&mut self, mt_leaf_info: MTLeafInfo) -> Result<()> {
+        let owned = self
+            .owned
+            .get_mut(&mt_leaf_info.uid)
+            .c(d!(ZeiError::ParameterError))?;
+        let commitment = AnonAssetRecord::from_oabar(&owned.record).commitment;
+        let root = compute_merkle_root(mt_leaf_info.uid, &commitment, &mt_leaf_info.path)
+            .c(d!(ZeiError::MerkleTreeVerificationError))?;
+        if root != mt_leaf_info.root {
+            return Err(eg!(ZeiError::MerkleTreeVerificationError));
+        }
+        owned.record.update_mt_leaf_info(mt_leaf_info);
+        Ok(())
+    }
+
+    /// Return the owned record at a position of the Merkle tree, if any.
+    pub fn get(&self, uid: u64) -> Option<&OwnedAbar> {
+        self.owned.get(&uid)
+    }
+
+    /// Return the owned records, spent or not, in the order of their positions.
+    pub fn owned(&self) -> impl Iterator<Item = &OwnedAbar> {
+        self.owned.values()
+    }
+
+    /// Return the unspent records, which can be the inputs of a transfer once their Merkle
+    /// tree leaf information is set.
+    pub fn unspent(&self) -> Vec<OpenAnonAssetRecord> {
+        self.owned()
+            .filter(|owned| !owned.spent)
+            .map(|owned| owned.record.clone())
+            .collect()
+    }
+
+    /// Return the total amount of the unspent records of an asset type.
+    pub fn balance(&self, asset_type: &AssetType) -> u128 {
+        self.owned()
+            .filter(|owned| !owned.spent && owned.record.get_asset_type() == *asset_type)
+            .map(|owned| owned.record.get_amount() as u128)
+            .sum()
+    }
+
+    /// Save the state of the wallet to a writer.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        bincode::serialize_into(writer, self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Load the state of a wallet from a reader.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<OwnedAbarTracker> {
+        bincode::deserialize_from(reader).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        compute_merkle_root,
+        keys::AXfrKeyPair,
+        structs::{
+            AnonAssetRecord, AxfrOwnerMemo, MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecordBuilder,
+        },
+        tracker::OwnedAbarTracker,
+    };
+    use crate::xfr::structs::AssetType;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+    fn abar<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        keypair: &AXfrKeyPair,
+        amount: u64,
+        asset_type: AssetType,
+    ) -> (AnonAssetRecord, AxfrOwnerMemo) {
+        let oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(amount)
+            .asset_type(asset_type)
+            .pub_key(&keypair.get_public_key())
+            .finalize(prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        (
+            AnonAssetRecord::from_oabar(&oabar),
+            oabar.get_owner_memo().unwrap(),
+        )
+    }
+
+    #[test]
+    fn track_owned_abars() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let other = AXfrKeyPair::generate(&mut prng);
+        let asset_type = AssetType::from_identical_byte(1);
+        let mut tracker = OwnedAbarTracker::new(keypair.get_public_key(), 5);
+
+        let block = vec![
+            abar(&mut prng, &keypair, 10, asset_type),
+            abar(&mut prng, &other, 20, asset_type),
+            abar(&mut prng, &keypair, 30, asset_type),
+        ];
+        assert_eq!(
+            tracker.ingest_block(&keypair, &block, &[]).unwrap(),
+            vec![5, 7]
+        );
+        assert_eq!(tracker.next_uid(), 8);
+        assert_eq!(tracker.balance(&asset_type), 40);
+        assert!(tracker.ingest_block(&other, &block, &[]).is_err());
+
+        // the first record is spent, along with a record of another wallet
+        let spent = tracker.get(5).unwrap().nullifier;
+        let block = vec![abar(&mut prng, &keypair, 5, asset_type)];
+        let uids = tracker
+            .ingest_block(&keypair, &block, &[BLSScalar::one(), spent])
+            .unwrap();
+        assert_eq!(uids, vec![8]);
+        assert_eq!(tracker.balance(&asset_type), 35);
+        assert_eq!(
+            tracker
+                .unspent()
+                .iter()
+                .map(|record| record.get_amount())
+                .collect_vec(),
+            vec![30, 5]
+        );
+
+        // the leaf information is set by position, once its path leads to the root
+        let commitment = AnonAssetRecord::from_oabar(&tracker.get(7).unwrap().record).commitment;
+        let path = MTPath::new(vec![MTNode {
+            siblings1: BLSScalar::one(),
+            siblings2: BLSScalar::zero(),
+            is_left_child: 0,
+            is_right_child: 1,
+        }]);
+        let mut mt_leaf_info = MTLeafInfo {
+            root: BLSScalar::one(),
+            path: path.clone(),
+            root_version: 1,
+            uid: 7,
+        };
+        assert!(tracker.update_mt_leaf_info(mt_leaf_info.clone()).is_err());
+        mt_leaf_info.root = compute_merkle_root(7, &commitment, &path).unwrap();
+        tracker.update_mt_leaf_info(mt_leaf_info.clone()).unwrap();
+        assert_eq!(tracker.unspent()[0].mt_leaf_info, Some(mt_leaf_info));
+        assert!(tracker
+            .update_mt_leaf_info(MTLeafInfo {
+                uid: 6,
+                ..Default::default()
+            })
+            .is_err());
+
+        let mut bytes = vec![];
+        tracker.save_to_writer(&mut bytes).unwrap();
+        assert_eq!(
+            OwnedAbarTracker::load_from_reader(bytes.as_slice()).unwrap(),
+            tracker
+        );
+    }
+}
@@ -0,0 +1,416 @@
+//! Selective-disclosure audit reports over the records of an account holder.
+//!
+//! An account holder can prove statements about the total amount of a set of their records,
+//! e.g., that their total outflow in a period is below a bound, to an auditor who only sees
+//! the commitments of the records. The total is committed by adding up the Pedersen
+//! commitments of the amounts, and each statement is proven by a range proof on the
+//! difference between the bound and the total, so that the amounts are not revealed.
+//!
+//! The report is signed by the holder, and binds the records to the holder, the asset type,
+//! and the period. A report only proves statements about the records that it lists, so the
+//! auditor must check against the ledger that they are the records of the period, e.g., all
+//! the records spent by the holder in the period.
+
+use crate::setup::{BulletproofParams, MAX_CONFIDENTIAL_RECORD_NUMBER};
+use crate::xfr::{
+    sig::{XfrKeyPair, XfrPublicKey, XfrSignature},
+    structs::{AssetType, BlindAssetRecord, OpenAssetRecord, XfrAmount, XfrAssetType},
+};
+use merlin::Transcript;
+use zei_algebra::{
+    prelude::*,
+    ristretto::{CompressedRistretto, RistrettoPoint, RistrettoScalar},
+    utils::min_greater_equal_power_of_two,
+};
+use zei_crypto::{
+    basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto},
+    bulletproofs::{
+        amount::{
+            batch_verify_amounts, link_limb_commitments, prove_amounts, AmountCommitment,
+            AmountOpening,
+        },
+        range::{RangeProofBackend, VersionedRangeProof},
+    },
+};
+
+const AUDIT_REPORT_TRANSCRIPT: &[u8] = b"Zei Audit Report";
+
+/// A statement about the total amount of the records of a report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditStatement {
+    /// The total amount is less than the bound.
+    TotalBelow(u64),
+    /// The total amount is at least the bound, and less than the bound plus 2^64.
+    TotalAtLeast(u64),
+}
+
+impl AuditStatement {
+    /// Return the difference between the bound and the total that is proven to be in
+    /// `[0, 2^64)`, or `None` if the statement does not hold for the total.
+    fn difference(&self, total: u128) -> Option<u64> {
+        let difference = match self {
+            AuditStatement::TotalBelow(bound) => u128::from(*bound).checked_sub(total + 1)?,
+            AuditStatement::TotalAtLeast(bound) => total.checked_sub(u128::from(*bound))?,
+        };
+        u64::try_from(difference).ok()
+    }
+
+    /// Return the blinding factor of the difference, from the blinding factor of the total.
+    fn difference_blind(&self, total_blind: RistrettoScalar) -> RistrettoScalar {
+        match self {
+            AuditStatement::TotalBelow(_) => -total_blind,
+            AuditStatement::TotalAtLeast(_) => total_blind,
+        }
+    }
+
+    /// Derive the commitment to the difference from the commitment to the total.
+    fn difference_commitment(
+        &self,
+        pc_gens: &PedersenCommitmentRistretto,
+        total_com: &RistrettoPoint,
+    ) -> RistrettoPoint {
+        match self {
+            AuditStatement::TotalBelow(bound) => {
+                let bound = RistrettoScalar::from(*bound).sub(&RistrettoScalar::one());
+                pc_gens
+                    .commit(bound, RistrettoScalar::zero())
+                    .sub(total_com)
+            }
+            AuditStatement::TotalAtLeast(bound) => {
+                let bound = RistrettoScalar::from(*bound);
+                total_com.sub(&pc_gens.commit(bound, RistrettoScalar::zero()))
+            }
+        }
+    }
+}
+
+/// The signed part of an audit report.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditReportBody {
+    /// The account holder, who owns the records.
+    pub holder: XfrPublicKey,
+    /// The period of the records, e.g., as block heights, which is checked by the auditor.
+    pub period: (u64, u64),
+    /// The asset type of the records.
+    pub asset_type: AssetType,
+    /// The records of the report.
+    pub records: Vec<BlindAssetRecord>,
+    /// The blinding factors of the asset types of the records, which are zero for the
+    /// non-confidential asset types.
+    pub asset_type_blinds: Vec<RistrettoScalar>,
+    /// The statements about the total amount of the records.
+    pub statements: Vec<AuditStatement>,
+    /// The commitments to the lower and higher 32 bits of the difference of each statement.
+    pub difference_commitments: Vec<(CompressedRistretto, CompressedRistretto)>,
+    /// The range proof of the differences.
+    #[serde(with = "zei_obj_serde")]
+    pub range_proof: VersionedRangeProof,
+}
+
+/// An audit report, signed by the account holder.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditReport {
+    /// The body of the report.
+    pub body: AuditReportBody,
+    /// The signature of the holder over the body.
+    pub signature: XfrSignature,
+}
+
+/// Generate an audit report proving the statements about the total amount of the records,
+/// which must be owned by the key pair and have the given asset type.
+/// Return an error if a statement does not hold.
+pub fn generate_audit_report<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    keypair: &XfrKeyPair,
+    period: (u64, u64),
+    asset_type: AssetType,
+    records: &[OpenAssetRecord],
+    statements: Vec<AuditStatement>,
+) -> Result<AuditReport> {
+    let upper_power2 = min_greater_equal_power_of_two((2 * statements.len()) as u32) as usize;
+    if statements.is_empty() || upper_power2 > MAX_CONFIDENTIAL_RECORD_NUMBER {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let holder = keypair.get_pk();
+
+    let mut total = 0u128;
+    let mut total_blind = RistrettoScalar::zero();
+    for record in records {
+        if record.blind_asset_record.public_key != holder || record.asset_type != asset_type {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        total += u128::from(record.amount);
+        let opening = AmountOpening {
+            amount: record.amount,
+            blinds: record.amount_blinds,
+        };
+        total_blind = total_blind.add(&opening.linked_blind());
+    }
+
+    // the limbs of each difference are linked to the blind of the difference
+    let mut openings = Vec::with_capacity(statements.len());
+    for statement in statements.iter() {
+        let difference = statement
+            .difference(total)
+            .c(d!(ZeiError::AuditReportProveError))?;
+        openings.push(AmountOpening::split(
+            prng,
+            difference,
+            &statement.difference_blind(total_blind),
+        ));
+    }
+
+    let params = BulletproofParams::default();
+    let mut transcript = Transcript::new(AUDIT_REPORT_TRANSCRIPT);
+    let (range_proof, coms) = prove_amounts(
+        prng,
+        RangeProofBackend::Bulletproofs,
+        &params.bp_gens,
+        &mut transcript,
+        &openings,
+    )
+    .c(d!(ZeiError::AuditReportProveError))?;
+    let difference_commitments = coms.iter().map(|com| (com.low, com.high)).collect_vec();
+
+    let body = AuditReportBody {
+        holder,
+        period,
+        asset_type,
+        records: records
+            .iter()
+            .map(|record| record.blind_asset_record.clone())
+            .collect(),
+        asset_type_blinds: records.iter().map(|record| record.type_blind).collect(),
+        statements,
+        difference_commitments,
+        range_proof,
+    };
+    let msg = bincode::serialize(&body).c(d!(ZeiError::SerializationError))?;
+    let signature = keypair.sign(&msg).c(d!())?;
+    Ok(AuditReport { body, signature })
+}
+
+/// Verify an audit report: the signature of the holder, the ownership and the asset type of
+/// the records, and the statements about their total amount, from the commitments only.
+pub fn verify_audit_report<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    report: &AuditReport,
+) -> Result<()> {
+    let body = &report.body;
+    let msg = bincode::serialize(body).c(d!(ZeiError::SerializationError))?;
+    body.holder.verify(&msg, &report.signature).c(d!())?;
+
+    let num_statements = body.statements.len();
+    let upper_power2 = min_greater_equal_power_of_two((2 * num_statements) as u32) as usize;
+    if num_statements == 0
+        || upper_power2 > MAX_CONFIDENTIAL_RECORD_NUMBER
+        || body.difference_commitments.len() != num_statements
+        || body.asset_type_blinds.len() != body.records.len()
+    {
+        return Err(eg!(ZeiError::InconsistentStructureError));
+    }
+
+    // 1. Add up the commitments to the amounts of the records.
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let mut total_com = RistrettoPoint::get_identity();
+    for (record, type_blind) in body.records.iter().zip(&body.asset_type_blinds) {
+        if record.public_key != body.holder {
+            return Err(eg!(ZeiError::AuditReportVerificationError));
+        }
+        let asset_type_matches = match record.asset_type {
+            XfrAssetType::NonConfidential(asset_type) => asset_type == body.asset_type,
+            XfrAssetType::Confidential(_) => {
+                XfrAssetType::from_blind(&pc_gens, &body.asset_type, type_blind)
+                    == record.asset_type
+            }
+        };
+        if !asset_type_matches {
+            return Err(eg!(ZeiError::AuditReportVerificationError));
+        }
+        let com = match record.amount {
+            XfrAmount::Confidential((low, high)) => {
+                AmountCommitment { low, high }.link().c(d!())?
+            }
+            XfrAmount::NonConfidential(amount) => {
+                link_limb_commitments(&AmountOpening::non_confidential(amount).commit(&pc_gens))
+            }
+        };
+        total_com = total_com.add(&com);
+    }
+
+    // 2. Compare the commitments to the differences with the ones derived from the total.
+    let mut commitments = Vec::with_capacity(num_statements);
+    for (statement, (low, high)) in body.statements.iter().zip(&body.difference_commitments) {
+        let derived = statement.difference_commitment(&pc_gens, &total_com);
+        let com = AmountCommitment {
+            low: *low,
+            high: *high,
+        };
+        com.verify_link(&derived)
+            .c(d!(ZeiError::AuditReportVerificationError))?;
+        commitments.push(com);
+    }
+
+    // 3. Verify that the differences are in range.
+    let mut transcripts = [Transcript::new(AUDIT_REPORT_TRANSCRIPT)];
+    batch_verify_amounts(
+        prng,
+        &params.bp_gens,
+        &[&body.range_proof],
+        &mut transcripts,
+        &[commitments.as_slice()],
+    )
+    .c(d!(ZeiError::AuditReportVerificationError))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::audit_report::{generate_audit_report, verify_audit_report, AuditStatement};
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::{open_blind_asset_record, AssetRecordType},
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType, OpenAssetRecord},
+    };
+    use ark_std::{rand::rngs::StdRng, test_rng};
+
+    #[test]
+    fn audit_report() {
+        let mut prng = test_rng();
+        let params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(1);
+        let holder = XfrKeyPair::generate(&mut prng);
+        let other = XfrKeyPair::generate(&mut prng);
+
+        let record = |prng: &mut StdRng, amount, record_type, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                keypair.get_pk(),
+            );
+            let record = AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap();
+            let blind_asset_record = &record.open_asset_record.blind_asset_record;
+            open_blind_asset_record(blind_asset_record, &record.owner_memo, keypair).unwrap()
+        };
+        let records: Vec<OpenAssetRecord> = vec![
+            record(
+                &mut prng,
+                u64::MAX - 10,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                &holder,
+            ),
+            record(
+                &mut prng,
+                20,
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                &holder,
+            ),
+            record(
+                &mut prng,
+                30,
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                &holder,
+            ),
+        ];
+
+        let outflow = vec![records[1].clone(), records[2].clone()];
+        let report = generate_audit_report(
+            &mut prng,
+            &holder,
+            (100, 200),
+            asset_type,
+            &outflow,
+            vec![
+                AuditStatement::TotalBelow(u64::MAX),
+                AuditStatement::TotalAtLeast(50),
+            ],
+        )
+        .unwrap();
+        assert!(verify_audit_report(&mut prng, &params, &report).is_ok());
+
+        // a total of 2^64 + 39 is at least 40, but the difference must be less than 2^64
+        for (bound, provable) in [(40, true), (39, false)] {
+            let report = generate_audit_report(
+                &mut prng,
+                &holder,
+                (100, 200),
+                asset_type,
+                &records,
+                vec![AuditStatement::TotalAtLeast(bound)],
+            );
+            assert_eq!(report.is_ok(), provable);
+            if let Ok(report) = report {
+                assert!(verify_audit_report(&mut prng, &params, &report).is_ok());
+            }
+        }
+
+        // false statements cannot be proven
+        for statement in [
+            AuditStatement::TotalBelow(50),
+            AuditStatement::TotalAtLeast(51),
+        ] {
+            assert!(generate_audit_report(
+                &mut prng,
+                &holder,
+                (100, 200),
+                asset_type,
+                &outflow,
+                vec![statement],
+            )
+            .is_err());
+        }
+
+        // records of another holder cannot be reported
+        let foreign = record(
+            &mut prng,
+            10,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            &other,
+        );
+        assert!(generate_audit_report(
+            &mut prng,
+            &holder,
+            (100, 200),
+            asset_type,
+            &[foreign],
+            vec![AuditStatement::TotalBelow(100)],
+        )
+        .is_err());
+
+        // a report whose statements, records, or period are changed is rejected
+        let report = generate_audit_report(
+            &mut prng,
+            &holder,
+            (100, 200),
+            asset_type,
+            &outflow,
+            vec![AuditStatement::TotalBelow(51)],
+        )
+        .unwrap();
+        assert!(verify_audit_report(&mut prng, &params, &report).is_ok());
+        let mut tampered = report.clone();
+        tampered.body.statements[0] = AuditStatement::TotalBelow(50);
+        assert!(verify_audit_report(&mut prng, &params, &tampered).is_err());
+        let mut tampered = report.clone();
+        tampered.body.records.pop();
+        assert!(verify_audit_report(&mut prng, &params, &tampered).is_err());
+        let mut tampered = report.clone();
+        tampered.body.period = (0, 200);
+        assert!(verify_audit_report(&mut prng, &params, &tampered).is_err());
+
+        // a false statement signed by the holder is rejected by the proof
+        let mut resigned = report.clone();
+        resigned.body.statements[0] = AuditStatement::TotalBelow(50);
+        let msg = bincode::serialize(&resigned.body).unwrap();
+        resigned.signature = holder.sign(&msg).unwrap();
+        assert!(verify_audit_report(&mut prng, &params, &resigned).is_err());
+
+        // a report signed by another key is rejected
+        let mut resigned = report;
+        let msg = bincode::serialize(&resigned.body).unwrap();
+        resigned.signature = other.sign(&msg).unwrap();
+        assert!(verify_audit_report(&mut prng, &params, &resigned).is_err());
+    }
+}
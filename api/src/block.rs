@@ -0,0 +1,338 @@
+//! Verification of all the notes of a block with a single call.
+//!
+//! [`verify_block`] checks the confidential transfer notes and the anonymous transfer notes of
+//! a block in stages, from the cheapest to the most expensive: the signatures of the notes,
+//! the Plonk proofs of the anonymous transfers, and the batched range proofs and tracing
+//! proofs of the confidential transfers. With the `parallel` feature on, the independent
+//! checks of each stage run on the work-stealing thread pool of `rayon`. A failure is reported
+//! with the index of the first failing note of the first stage that fails.
+
+use crate::anon_xfr::abar_to_abar::{check_anon_xfr_note, AXfrNote};
+use crate::setup::{BulletproofParams, VerifierParams};
+use crate::xfr::{
+    batch_verify_xfr_bodies, check_xfr_body, structs::XfrNote, verify_transfer_multisig,
+    XfrNotePoliciesRef,
+};
+use digest::{consts::U64, Digest};
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+/// Verify the notes of a block: the confidential transfer notes with their policies, and the
+/// anonymous transfer notes with their verifier parameters, the Merkle roots that they are
+/// checked against, and the hashes of the transactions that contain them.
+///
+/// On failure, the error is a `ZeiError::NoteError` or a `ZeiError::AnonNoteError` with the
+/// index of the first failing note, chained to the error of the verification of the note.
+/// The proofs of the confidential transfer notes are verified in a batch, and the notes are only
+/// verified one by one when the batch fails. If each of them then passes on its own, which a
+/// sound batch rules out but for a negligible probability, no note can be blamed, and the error
+/// is a `ZeiError::ZKProofBatchVerificationError` without an index.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_block<R, D>(
+    prng: &mut R,
+    params: &mut BulletproofParams,
+    notes: &[&XfrNote],
+    policies: &[&XfrNotePoliciesRef<'_>],
+    anon_params: &[&VerifierParams],
+    anon_notes: &[&AXfrNote],
+    merkle_roots: &[&BLSScalar],
+    hashes: Vec<D>,
+) -> Result<()>
+where
+    R: CryptoRng + RngCore,
+    D: Digest<OutputSize = U64> + Default + Sync + Send,
+{
+    if notes.len() != policies.len() {
+        return Err(eg!(ZeiError::LengthMismatch {
+            expected: notes.len(),
+            actual: policies.len(),
+        }));
+    }
+    for len in [anon_params.len(), merkle_roots.len(), hashes.len()] {
+        if len != anon_notes.len() {
+            return Err(eg!(ZeiError::LengthMismatch {
+                expected: anon_notes.len(),
+                actual: len,
+            }));
+        }
+    }
+
+    // 1. Check the signatures of the confidential transfer notes.
+    #[cfg(feature = "parallel")]
+    let iter = notes.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let iter = notes.iter();
+
+    let signatures: Vec<core::result::Result<(), ZeiError>> =
+        iter.map(|note| verify_transfer_multisig(note)).collect();
+    for (index, result) in signatures.into_iter().enumerate() {
+        result.map_err(|source| {
+            eg!(ZeiError::NoteError {
+                index,
+                source: Box::new(source),
+            })
+        })?;
+    }
+
+    // 2. Verify the anonymous transfer notes, one Plonk proof each.
+    #[cfg(feature = "parallel")]
+    let iter = anon_params
+        .par_iter()
+        .zip(anon_notes)
+        .zip(merkle_roots)
+        .zip(hashes);
+    #[cfg(not(feature = "parallel"))]
+    let iter = anon_params
+        .iter()
+        .zip(anon_notes)
+        .zip(merkle_roots)
+        .zip(hashes);
+
+    let anon_notes: Vec<core::result::Result<(), ZeiError>> = iter
+        .map(|(((params, note), merkle_root), hash)| {
+            check_anon_xfr_note(params, note, merkle_root, hash)
+        })
+        .collect();
+    for (index, result) in anon_notes.into_iter().enumerate() {
+        result.map_err(|source| {
+            eg!(ZeiError::AnonNoteError {
+                index,
+                source: Box::new(source),
+            })
+        })?;
+    }
+
+    // 3. Batch-verify the proofs of the confidential transfer notes, and only if the batch
+    // fails, verify the notes one by one to find the first failing one.
+    let bodies = notes.iter().map(|note| &note.body).collect_vec();
+    if batch_verify_xfr_bodies(prng, params, &bodies, policies).is_err() {
+        for (index, (body, policies)) in bodies.iter().zip(policies.iter()).enumerate() {
+            check_xfr_body(prng, params, body, policies).map_err(|source| {
+                eg!(ZeiError::NoteError {
+                    index,
+                    source: Box::new(source),
+                })
+            })?;
+        }
+        return Err(eg!(ZeiError::ZKProofBatchVerificationError));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::{
+        abar_to_abar::{finish_anon_xfr_note, init_anon_xfr_note, AXfrNote},
+        commit,
+        keys::AXfrKeyPair,
+        structs::{MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecordBuilder},
+        FEE_TYPE,
+    };
+    use crate::block::verify_block;
+    use crate::setup::{BulletproofParams, ParamsId, ParamsKind, ProverParams, VerifierParams};
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        compute_transfer_multisig, gen_xfr_note,
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType, XfrAmount, XfrNote},
+        XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use sha2::{Digest, Sha512};
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use zei_crypto::basic::rescue::RescueInstance;
+
+    const ANON_XFR_KIND: ParamsKind = ParamsKind::AnonXfr {
+        n_payers: 1,
+        n_payees: 1,
+        tree_depth: 1,
+    };
+
+    fn create_note<R: CryptoRng + RngCore>(prng: &mut R) -> (XfrNote, XfrKeyPair) {
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let record_type = AssetRecordType::ConfidentialAmount_NonConfidentialAssetType;
+        let sender = XfrKeyPair::generate(prng);
+        let mut record = |amount: u64| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                sender.pub_key,
+            );
+            AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap()
+        };
+        let inputs = vec![record(10)];
+        let outputs = vec![record(3), record(7)];
+        let note = gen_xfr_note(prng, &inputs, &outputs, &[&sender]).unwrap();
+        (note, sender)
+    }
+
+    // An anonymous transfer of a record that is the only leaf of a Merkle tree of depth one.
+    fn create_anon_note<R: CryptoRng + RngCore>(prng: &mut R) -> AXfrNote {
+        let params = ProverParams::get_or_init(ANON_XFR_KIND).unwrap();
+        let sender = AXfrKeyPair::generate(prng);
+        let receiver = AXfrKeyPair::generate(prng);
+        let mut record = |amount: u64, keypair: &AXfrKeyPair| {
+            OpenAnonAssetRecordBuilder::new()
+                .amount(amount)
+                .asset_type(FEE_TYPE)
+                .pub_key(&keypair.get_public_key())
+                .finalize(prng)
+                .unwrap()
+                .build()
+                .unwrap()
+        };
+        let mut input = record(15, &sender);
+        let output = record(10, &receiver);
+
+        let commitment = commit(
+            input.pub_key_ref(),
+            &input.get_blind(),
+            input.get_amount(),
+            &input.get_asset_type(),
+        )
+        .unwrap();
+        let hash = RescueInstance::new();
+        let zero = BLSScalar::zero();
+        let leaf = hash.rescue(&[zero, commitment, zero, zero])[0];
+        input.update_mt_leaf_info(MTLeafInfo {
+            path: MTPath::new(vec![MTNode {
+                siblings1: zero,
+                siblings2: zero,
+                is_left_child: 1,
+                is_right_child: 0,
+            }]),
+            root: hash.rescue(&[leaf, zero, zero, zero])[0],
+            root_version: 1,
+            uid: 0,
+        });
+
+        let pre_note = init_anon_xfr_note(&[input], &[output], 5, &sender).unwrap();
+        finish_anon_xfr_note(prng, &params, pre_note, Sha512::new()).unwrap()
+    }
+
+    #[test]
+    fn block_verification() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let (notes, senders): (Vec<XfrNote>, Vec<XfrKeyPair>) =
+            (0..4).map(|_| create_note(&mut prng)).unzip();
+        let policies = notes
+            .iter()
+            .map(|note| {
+                XfrNotePolicies::empty_policies(note.body.inputs.len(), note.body.outputs.len())
+            })
+            .collect_vec();
+        let policies_refs = policies.iter().map(|p| p.to_ref()).collect_vec();
+        let policies_refs = policies_refs.iter().collect_vec();
+
+        let verify = |params: &mut BulletproofParams, notes: &[XfrNote]| {
+            verify_block::<_, Sha512>(
+                &mut test_rng(),
+                params,
+                &notes.iter().collect_vec(),
+                &policies_refs,
+                &[],
+                &[],
+                &[],
+                vec![],
+            )
+        };
+        assert!(verify(&mut params, &notes).is_ok());
+
+        // a broken signature is reported with the index of the note and of the signer
+        let mut bad_notes = notes.clone();
+        bad_notes[2].body.outputs[0].amount = XfrAmount::NonConfidential(3);
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 2,
+                source: Box::new(ZeiError::SignerError {
+                    index: 0,
+                    source: Box::new(ZeiError::SignatureError)
+                })
+            },
+            verify(&mut params, &bad_notes).unwrap_err()
+        );
+
+        // a note signed with the proofs of another note fails the proof verification
+        let mut bad_notes = notes.clone();
+        bad_notes[1].body.proofs = notes[3].body.proofs.clone();
+        bad_notes[1].multisig =
+            compute_transfer_multisig(&bad_notes[1].body, &[&senders[1]]).unwrap();
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 1,
+                source: Box::new(ZeiError::SubProofError {
+                    proof: "range proof",
+                    source: Box::new(ZeiError::XfrVerifyConfidentialAmountError)
+                })
+            },
+            verify(&mut params, &bad_notes).unwrap_err()
+        );
+
+        assert!(verify_block::<_, Sha512>(
+            &mut prng,
+            &mut params,
+            &notes.iter().collect_vec(),
+            &policies_refs[1..],
+            &[],
+            &[],
+            &[],
+            vec![],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn anon_block_verification() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let verifier_params = VerifierParams::get_or_init(ANON_XFR_KIND).unwrap();
+        let note = create_anon_note(&mut prng);
+
+        let verify = |params: &mut BulletproofParams, anon_notes: &[AXfrNote]| {
+            let merkle_roots = anon_notes
+                .iter()
+                .map(|note| note.body.merkle_root)
+                .collect_vec();
+            verify_block::<_, Sha512>(
+                &mut test_rng(),
+                params,
+                &[],
+                &[],
+                &vec![verifier_params.as_ref(); anon_notes.len()],
+                &anon_notes.iter().collect_vec(),
+                &merkle_roots.iter().collect_vec(),
+                vec![Sha512::new(); anon_notes.len()],
+            )
+        };
+        assert!(verify(&mut params, &[note.clone(), note.clone()]).is_ok());
+
+        // a note with the parameters of another circuit is reported with its index, and the
+        // cause of the failure is chained to the error
+        let mut bad_note = note.clone();
+        bad_note.params_id = ParamsId::default();
+        msg_eq!(
+            ZeiError::AnonNoteError {
+                index: 1,
+                source: Box::new(ZeiError::ParamsIdMismatchError)
+            },
+            verify(&mut params, &[note.clone(), bad_note]).unwrap_err()
+        );
+
+        // so is a note whose proof does not verify
+        let mut bad_note = note.clone();
+        bad_note.proof = create_anon_note(&mut prng).proof;
+        msg_eq!(
+            ZeiError::AnonNoteError {
+                index: 0,
+                source: Box::new(ZeiError::SubProofError {
+                    proof: "Plonk proof",
+                    source: Box::new(ZeiError::ZKProofVerificationError)
+                })
+            },
+            verify(&mut params, &[bad_note, note]).unwrap_err()
+        );
+    }
+}
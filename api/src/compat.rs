@@ -0,0 +1,53 @@
+/// The algebra, now the `zei_algebra` crate.
+#[deprecated(since = "0.2.0", note = "use the `zei_algebra` crate instead")]
+pub mod algebra {
+    pub use zei_algebra::*;
+}
+
+/// The basic cryptographic primitives, now `zei_crypto::basic`.
+#[deprecated(since = "0.2.0", note = "use `zei_crypto::basic` instead")]
+pub mod basic_crypto {
+    pub use zei_crypto::basic::*;
+}
+
+/// The cryptographic protocols, now the `zei_crypto` crate.
+#[deprecated(since = "0.2.0", note = "use the `zei_crypto` crate instead")]
+pub mod crypto {
+    pub use zei_crypto::basic::{chaum_pedersen, pedersen_elgamal};
+    pub use zei_crypto::*;
+}
+
+/// The utilities, now `zei_algebra::utils`.
+#[deprecated(since = "0.2.0", note = "use `zei_algebra::utils` instead")]
+pub mod utils {
+    pub use zei_algebra::utils::*;
+}
+
+/// The platform interfaces, now at the root of the `zei` crate.
+#[deprecated(
+    since = "0.2.0",
+    note = "use the modules at the root of the `zei` crate instead"
+)]
+pub mod api {
+    pub use crate::{anon_creds, anon_xfr, setup, xfr};
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod test {
+    use crate::compat::{algebra, basic_crypto, crypto, utils};
+    use zei_algebra::ristretto::RistrettoScalar;
+
+    #[test]
+    fn historical_paths() {
+        // the historical paths name the same items as the new ones
+        let scalar: algebra::ristretto::RistrettoScalar = RistrettoScalar::from(7u32);
+        let _: zei_crypto::basic::pedersen_comm::PedersenCommitmentRistretto =
+            basic_crypto::pedersen_comm::PedersenCommitmentRistretto::default();
+        let _: Option<&zei_crypto::basic::chaum_pedersen::ChaumPedersenProof> =
+            Option::<&crypto::chaum_pedersen::ChaumPedersenProof>::None;
+        assert_eq!(utils::u64_to_u32_pair(1u64 << 32), (0, 1));
+        assert_eq!(scalar, RistrettoScalar::from(7u32));
+        let _ = crate::compat::api::xfr::structs::AssetType::from_identical_byte(0);
+    }
+}
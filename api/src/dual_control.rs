@@ -0,0 +1,419 @@
+//! Dual-control decryption of the asset tracing memos, with a hash-chained audit log.
+//!
+//! The decryption keys of a tracer are split into two shares, held by two officers, so that
+//! no officer can decrypt the memos alone. Every decryption needs both shares and the
+//! signatures of both officers on an audit record, which names the officers, the note and the
+//! time of the decryption. The record is appended to the audit log before the decrypted
+//! records are returned, and each record includes the hash of the previous one, so that
+//! records cannot be removed or reordered without being detected by [`verify_audit_log`].
+
+use crate::tracing::{decrypt_tracing_memos, TracedRecord};
+use crate::xfr::{
+    sampling::{note_digest, NoteDigest},
+    sig::{Signer, XfrPublicKey, XfrSignature},
+    structs::{AssetTracerDecKeys, AssetTracerEncKeys, AssetTracerKeyPair, XfrNote},
+};
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+/// The domain separator of the digests of the tracer keys.
+const TRACER_KEY_DIGEST_DOMAIN: &[u8] = b"Zei Dual Control Tracer Key";
+/// The domain separator of the signed audit record bodies.
+const AUDIT_RECORD_SIGNATURE_DOMAIN: &[u8] = b"Zei Dual Control Audit Record";
+/// The domain separator of the hashes of the audit records.
+const AUDIT_RECORD_HASH_DOMAIN: &[u8] = b"Zei Dual Control Audit Record Hash";
+
+/// The hash that the first record of an audit log links to.
+pub const AUDIT_LOG_GENESIS_HASH: [u8; 32] = [0u8; 32];
+
+/// A share of the decryption keys of a tracer, held by an officer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracerKeyShare {
+    /// The index of the share, 0 or 1.
+    pub index: u8,
+    /// The officer holding the share, who must sign the audit records.
+    pub holder: XfrPublicKey,
+    /// The encryption keys of the tracer.
+    pub enc_key: AssetTracerEncKeys,
+    /// The digest of the decryption keys, to detect shares of different keys.
+    pub key_digest: [u8; 32],
+    share: Vec<u8>,
+}
+
+fn tracer_key_digest(dec_key_bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(TRACER_KEY_DIGEST_DOMAIN);
+    hasher.update(dec_key_bytes);
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&hasher.finalize());
+    digest
+}
+
+/// Split the decryption keys of a tracer into two shares, for the officers `holders`.
+/// Each share alone reveals nothing about the keys.
+pub fn split_tracer_keys<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    keypair: &AssetTracerKeyPair,
+    holders: [XfrPublicKey; 2],
+) -> Result<[TracerKeyShare; 2]> {
+    if holders[0] == holders[1] {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let dec_key_bytes = bincode::serialize(&keypair.dec_key).c(d!(ZeiError::SerializationError))?;
+    let key_digest = tracer_key_digest(&dec_key_bytes);
+
+    let mut mask = vec![0u8; dec_key_bytes.len()];
+    prng.fill_bytes(&mut mask);
+    let masked = dec_key_bytes
+        .iter()
+        .zip(mask.iter())
+        .map(|(b, m)| b ^ m)
+        .collect_vec();
+
+    let share = |index: u8, share: Vec<u8>| TracerKeyShare {
+        index,
+        holder: holders[index as usize],
+        enc_key: keypair.enc_key.clone(),
+        key_digest,
+        share,
+    };
+    Ok([share(0, mask), share(1, masked)])
+}
+
+/// Recombine the decryption keys of a tracer from its two shares.
+fn combine_tracer_keys(shares: [&TracerKeyShare; 2]) -> Result<AssetTracerKeyPair> {
+    let [a, b] = shares;
+    if a.index == b.index
+        || a.key_digest != b.key_digest
+        || a.enc_key != b.enc_key
+        || a.share.len() != b.share.len()
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let dec_key_bytes = a
+        .share
+        .iter()
+        .zip(b.share.iter())
+        .map(|(x, y)| x ^ y)
+        .collect_vec();
+    if tracer_key_digest(&dec_key_bytes) != a.key_digest {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let dec_key: AssetTracerDecKeys =
+        bincode::deserialize(&dec_key_bytes).c(d!(ZeiError::DeserializationError))?;
+    Ok(AssetTracerKeyPair {
+        enc_key: a.enc_key.clone(),
+        dec_key,
+    })
+}
+
+/// The part of an audit record signed by the officers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecordBody {
+    /// The position of the record in the log, starting at 0.
+    pub sequence: u64,
+    /// The hash of the previous record, or [`AUDIT_LOG_GENESIS_HASH`] for the first one.
+    pub prev_hash: [u8; 32],
+    /// The digest of the decryption keys of the tracer.
+    pub key_digest: [u8; 32],
+    /// The officers who performed the decryption, in the order of their shares.
+    pub officers: [XfrPublicKey; 2],
+    /// The digest of the decrypted note.
+    pub note: NoteDigest,
+    /// The time of the decryption, as provided by the caller, e.g., in seconds since the epoch.
+    pub timestamp: u64,
+}
+
+impl AuditRecordBody {
+    fn signed_message(&self) -> Result<Vec<u8>> {
+        let mut msg = AUDIT_RECORD_SIGNATURE_DOMAIN.to_vec();
+        msg.extend(bincode::serialize(self).c(d!(ZeiError::SerializationError))?);
+        Ok(msg)
+    }
+}
+
+/// A record of the audit log, signed by both officers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// The signed body.
+    pub body: AuditRecordBody,
+    /// The signatures of the officers, in the order of `body.officers`.
+    pub signatures: [XfrSignature; 2],
+}
+
+impl AuditRecord {
+    /// Compute the hash of the record, which the next record links to.
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        let bytes = bincode::serialize(self).c(d!(ZeiError::SerializationError))?;
+        let mut hasher = Sha256::new();
+        hasher.update(AUDIT_RECORD_HASH_DOMAIN);
+        hasher.update(&bytes);
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hasher.finalize());
+        Ok(hash)
+    }
+
+    fn verify_signatures(&self) -> Result<()> {
+        if self.body.officers[0] == self.body.officers[1] {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let msg = self.body.signed_message().c(d!())?;
+        for (index, (officer, signature)) in self
+            .body
+            .officers
+            .iter()
+            .zip(self.signatures.iter())
+            .enumerate()
+        {
+            officer
+                .verify(&msg, signature)
+                .c(d!(ZeiError::SignerError {
+                    index,
+                    source: Box::new(ZeiError::SignatureError),
+                }))?;
+        }
+        Ok(())
+    }
+}
+
+/// The storage of an audit log. The records must be persisted by `append` before it returns,
+/// since the decryption result is only released afterwards.
+pub trait AuditLogSink {
+    /// Append a record to the log.
+    fn append(&mut self, record: &AuditRecord) -> Result<()>;
+}
+
+/// An in-memory audit log.
+impl AuditLogSink for Vec<AuditRecord> {
+    fn append(&mut self, record: &AuditRecord) -> Result<()> {
+        self.push(record.clone());
+        Ok(())
+    }
+}
+
+/// An officer taking part in a decryption: the share of the officer, and a signer for
+/// the public key that the share is bound to.
+pub struct Officer<'a> {
+    /// The share of the decryption keys.
+    pub share: &'a TracerKeyShare,
+    /// The signer of the audit record.
+    pub signer: &'a dyn Signer,
+}
+
+/// A tracer whose decryption keys are under dual control, and whose decryptions are logged.
+pub struct DualControlTracer<L: AuditLogSink> {
+    enc_key: AssetTracerEncKeys,
+    key_digest: [u8; 32],
+    log: L,
+    sequence: u64,
+    last_hash: [u8; 32],
+}
+
+impl<L: AuditLogSink> DualControlTracer<L> {
+    /// Create a tracer with an empty audit log, for the keys shared in `share`.
+    pub fn new(share: &TracerKeyShare, log: L) -> Self {
+        DualControlTracer {
+            enc_key: share.enc_key.clone(),
+            key_digest: share.key_digest,
+            log,
+            sequence: 0,
+            last_hash: AUDIT_LOG_GENESIS_HASH,
+        }
+    }
+
+    /// Resume a tracer whose audit log already has some records, which are verified.
+    pub fn resume(share: &TracerKeyShare, log: L, records: &[AuditRecord]) -> Result<Self> {
+        verify_audit_log(records, &share.key_digest).c(d!())?;
+        let last_hash = match records.last() {
+            Some(record) => record.hash().c(d!())?,
+            None => AUDIT_LOG_GENESIS_HASH,
+        };
+        Ok(DualControlTracer {
+            enc_key: share.enc_key.clone(),
+            key_digest: share.key_digest,
+            log,
+            sequence: records.len() as u64,
+            last_hash,
+        })
+    }
+
+    /// Return the encryption keys of the tracer.
+    pub fn enc_key(&self) -> &AssetTracerEncKeys {
+        &self.enc_key
+    }
+
+    /// Return the audit log.
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+
+    /// Decrypt the asset tracing memos of a note, as [`decrypt_tracing_memos`] does, with the
+    /// shares of both officers, after appending a record of the decryption to the audit log.
+    pub fn decrypt<R: CryptoRng + RngCore>(
+        &mut self,
+        prng: &mut R,
+        officers: [Officer<'_>; 2],
+        note: &XfrNote,
+        timestamp: u64,
+    ) -> Result<Vec<TracedRecord>> {
+        for (index, officer) in officers.iter().enumerate() {
+            if officer.share.key_digest != self.key_digest
+                || officer.share.holder != officer.signer.public_key()
+            {
+                return Err(eg!(ZeiError::SignerError {
+                    index,
+                    source: Box::new(ZeiError::ParameterError),
+                }));
+            }
+        }
+        let keypair = combine_tracer_keys([officers[0].share, officers[1].share]).c(d!())?;
+
+        let body = AuditRecordBody {
+            sequence: self.sequence,
+            prev_hash: self.last_hash,
+            key_digest: self.key_digest,
+            officers: [officers[0].share.holder, officers[1].share.holder],
+            note: note_digest(note).c(d!())?,
+            timestamp,
+        };
+        let msg = body.signed_message().c(d!())?;
+        let signatures = [
+            officers[0].signer.sign(&msg).c(d!())?,
+            officers[1].signer.sign(&msg).c(d!())?,
+        ];
+        let record = AuditRecord { body, signatures };
+        record.verify_signatures().c(d!())?;
+
+        // The decryption is only released once it is logged.
+        self.log.append(&record).c(d!())?;
+        self.last_hash = record.hash().c(d!())?;
+        self.sequence += 1;
+
+        decrypt_tracing_memos(prng, &keypair, note).c(d!())
+    }
+}
+
+/// Verify an audit log for the tracer keys with this digest: the records must be numbered
+/// from 0, each one must link to the hash of the previous one, and be signed by its officers.
+pub fn verify_audit_log(records: &[AuditRecord], key_digest: &[u8; 32]) -> Result<()> {
+    let mut prev_hash = AUDIT_LOG_GENESIS_HASH;
+    for (sequence, record) in records.iter().enumerate() {
+        if record.body.sequence != sequence as u64
+            || record.body.prev_hash != prev_hash
+            || record.body.key_digest != *key_digest
+        {
+            return Err(eg!(ZeiError::InconsistentStructureError));
+        }
+        record.verify_signatures().c(d!(ZeiError::SignatureError))?;
+        prev_hash = record.hash().c(d!())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_tracer_keys, verify_audit_log, AuditRecord, DualControlTracer, Officer};
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        gen_xfr_note,
+        sig::XfrKeyPair,
+        structs::{
+            AssetRecord, AssetRecordTemplate, AssetTracerKeyPair, AssetType, TracingPolicies,
+            TracingPolicy,
+        },
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn dual_control_decryption() {
+        let mut prng = test_rng();
+        let tracer_keypair = AssetTracerKeyPair::generate(&mut prng);
+        let alice = XfrKeyPair::generate(&mut prng);
+        let bob = XfrKeyPair::generate(&mut prng);
+        let mallory = XfrKeyPair::generate(&mut prng);
+        let shares =
+            split_tracer_keys(&mut prng, &tracer_keypair, [alice.pub_key, bob.pub_key]).unwrap();
+
+        let policies = TracingPolicies::from_policy(TracingPolicy {
+            enc_keys: tracer_keypair.enc_key.clone(),
+            asset_tracing: true,
+            identity_tracing: None,
+        });
+        let sender = XfrKeyPair::generate(&mut prng);
+        let template = AssetRecordTemplate::with_asset_tracing(
+            10,
+            AssetType::from_identical_byte(1),
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            sender.pub_key,
+            policies,
+        );
+        let input = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+        let output = AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+        let note = gen_xfr_note(&mut prng, &[input], &[output], &[&sender]).unwrap();
+
+        let mut tracer = DualControlTracer::new(&shares[0], Vec::<AuditRecord>::new());
+        let officers = || {
+            [
+                Officer {
+                    share: &shares[0],
+                    signer: &alice,
+                },
+                Officer {
+                    share: &shares[1],
+                    signer: &bob,
+                },
+            ]
+        };
+        for timestamp in 0..3 {
+            let traced = tracer
+                .decrypt(&mut prng, officers(), &note, timestamp)
+                .unwrap();
+            assert_eq!(traced.len(), 2);
+            assert_eq!(traced[0].amount, 10);
+        }
+        assert_eq!(tracer.log().len(), 3);
+        verify_audit_log(tracer.log(), &shares[0].key_digest).unwrap();
+
+        // a single share, or a signer that does not hold its share, is not enough
+        let one_share = [
+            Officer {
+                share: &shares[0],
+                signer: &alice,
+            },
+            Officer {
+                share: &shares[0],
+                signer: &alice,
+            },
+        ];
+        assert!(tracer.decrypt(&mut prng, one_share, &note, 3).is_err());
+        let wrong_signer = [
+            Officer {
+                share: &shares[0],
+                signer: &alice,
+            },
+            Officer {
+                share: &shares[1],
+                signer: &mallory,
+            },
+        ];
+        assert!(tracer.decrypt(&mut prng, wrong_signer, &note, 3).is_err());
+        assert_eq!(tracer.log().len(), 3);
+
+        // removing or altering a record breaks the chain
+        let mut log = tracer.log().clone();
+        log.remove(1);
+        assert!(verify_audit_log(&log, &shares[0].key_digest).is_err());
+        let mut log = tracer.log().clone();
+        log[2].body.timestamp = 100;
+        assert!(verify_audit_log(&log, &shares[0].key_digest).is_err());
+
+        // the tracer resumes from a verified log
+        let log = tracer.log().clone();
+        let mut resumed = DualControlTracer::resume(&shares[1], log.clone(), &log).unwrap();
+        resumed.decrypt(&mut prng, officers(), &note, 3).unwrap();
+        verify_audit_log(resumed.log(), &shares[0].key_digest).unwrap();
+    }
+}
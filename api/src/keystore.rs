@@ -0,0 +1,392 @@
+//! Password-encrypted keystores for secret keys.
+//!
+//! A [`Keystore`] holds a secret key encrypted under a key derived from a password with
+//! Argon2id, and it is stored as JSON. The parameters of the key derivation, its salt, and the
+//! AEAD algorithm are stored along with the ciphertext and authenticated by the AEAD, whose
+//! tag is the MAC of the keystore, so that wallets share a single format, and a keystore
+//! written with stronger parameters or another algorithm remains readable. The parameters are
+//! capped, so that a crafted keystore cannot make its reader spend unbounded memory or time,
+//! and the derived key and the encoded secret key are erased from memory after use.
+//!
+//! ```
+//! use ark_std::test_rng;
+//! use zei::keystore::{KdfParams, Keystore};
+//! use zei::xfr::sig::{XfrKeyPair, XfrSecretKey};
+//!
+//! let mut prng = test_rng();
+//! let keypair = XfrKeyPair::generate(&mut prng);
+//! // low-cost parameters for the example, wallets should use the default ones
+//! let params = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+//!
+//! let keystore =
+//!     Keystore::encrypt(&mut prng, keypair.get_sk_ref(), b"password", params).unwrap();
+//! let json = keystore.to_json().unwrap();
+//!
+//! let keystore = Keystore::from_json(&json).unwrap();
+//! assert_eq!(keystore.decrypt::<XfrSecretKey>(b"password").unwrap(), keypair.get_sk());
+//! assert!(keystore.decrypt::<XfrSecretKey>(b"wrong password").is_err());
+//! ```
+
+use crate::anon_creds::{ACIssuerSecretKey, ACUserSecretKey};
+use crate::anon_xfr::keys::AXfrKeyPair;
+use crate::xfr::sig::XfrSecretKey;
+use argon2::{Algorithm, Argon2, Params, Version};
+use std::io::{Read, Write};
+use zei_algebra::prelude::*;
+use zei_crypto::basic::aead::{Aead, AeadAlgorithm, ChaCha20Poly1305Aead, AEAD_KEY_LENGTH};
+use zeroize::Zeroizing;
+
+/// The version of the keystore format.
+pub const KEYSTORE_VERSION: u32 = 1;
+/// The maximal memory size of the key derivation in KiB, i.e., 1 GiB.
+pub const MAX_KDF_M_COST: u32 = 1024 * 1024;
+/// The maximal number of passes of the key derivation.
+pub const MAX_KDF_T_COST: u32 = 32;
+/// The maximal degree of parallelism of the key derivation.
+pub const MAX_KDF_P_COST: u32 = 16;
+const KEYSTORE_DOMAIN: &[u8] = b"Zei Keystore";
+const SALT_LENGTH: usize = 32;
+
+/// A secret key that can be stored in a keystore.
+pub trait KeystoreSecret: Sized {
+    /// The kind of the secret key, recorded in the keystore.
+    const KIND: &'static str;
+
+    /// Encode the secret key into bytes.
+    fn to_secret_bytes(&self) -> Result<Vec<u8>>;
+
+    /// Decode the secret key from bytes.
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self>;
+}
+
+impl KeystoreSecret for XfrSecretKey {
+    const KIND: &'static str = "xfr_secret_key";
+
+    fn to_secret_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.zei_to_bytes())
+    }
+
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        XfrSecretKey::zei_from_bytes(bytes).c(d!())
+    }
+}
+
+impl KeystoreSecret for AXfrKeyPair {
+    const KIND: &'static str = "axfr_key_pair";
+
+    fn to_secret_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.zei_to_bytes())
+    }
+
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        AXfrKeyPair::zei_from_bytes(bytes).c(d!())
+    }
+}
+
+impl KeystoreSecret for ACUserSecretKey {
+    const KIND: &'static str = "ac_user_secret_key";
+
+    fn to_secret_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).c(d!(ZeiError::SerializationError))
+    }
+
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+impl KeystoreSecret for ACIssuerSecretKey {
+    const KIND: &'static str = "ac_issuer_secret_key";
+
+    fn to_secret_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).c(d!(ZeiError::SerializationError))
+    }
+
+    fn from_secret_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+/// The cost parameters of Argon2id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// The memory size in KiB.
+    pub m_cost: u32,
+    /// The number of passes.
+    pub t_cost: u32,
+    /// The degree of parallelism.
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    /// The parameters recommended by OWASP for Argon2id.
+    fn default() -> Self {
+        KdfParams {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Check that the costs do not exceed [`MAX_KDF_M_COST`], [`MAX_KDF_T_COST`], and
+    /// [`MAX_KDF_P_COST`].
+    pub fn check(&self) -> Result<()> {
+        if self.m_cost > MAX_KDF_M_COST
+            || self.t_cost > MAX_KDF_T_COST
+            || self.p_cost > MAX_KDF_P_COST
+        {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(())
+    }
+
+    /// Derive the encryption key of a keystore from a password and a salt.
+    fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; AEAD_KEY_LENGTH]>> {
+        self.check().c(d!())?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(AEAD_KEY_LENGTH))
+            .map_err(|_| eg!(ZeiError::ParameterError))?;
+        let mut key = Zeroizing::new([0u8; AEAD_KEY_LENGTH]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password, salt, &mut *key)
+            .map_err(|_| eg!(ZeiError::ParameterError))?;
+        Ok(key)
+    }
+}
+
+/// A secret key encrypted under a password.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    /// The version of the format.
+    pub version: u32,
+    /// The kind of the secret key.
+    pub kind: String,
+    /// The parameters of the key derivation.
+    pub kdf: KdfParams,
+    /// The salt of the key derivation, in base64.
+    pub salt: String,
+    /// The AEAD algorithm.
+    pub cipher: AeadAlgorithm,
+    /// The encrypted secret key, along with the tag of the AEAD, in base64.
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypt a secret key under a password, with a fresh salt.
+    pub fn encrypt<S: KeystoreSecret, R: CryptoRng + RngCore>(
+        prng: &mut R,
+        secret: &S,
+        password: &[u8],
+        kdf: KdfParams,
+    ) -> Result<Keystore> {
+        let bytes = Zeroizing::new(secret.to_secret_bytes().c(d!())?);
+        Self::encrypt_bytes(prng, S::KIND, &bytes, password, kdf).c(d!())
+    }
+
+    /// Decrypt the secret key, or return an error if the password is wrong, the keystore was
+    /// modified, or it holds another kind of secret key.
+    pub fn decrypt<S: KeystoreSecret>(&self, password: &[u8]) -> Result<S> {
+        if self.kind != S::KIND {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let bytes = self.decrypt_bytes(password).c(d!())?;
+        S::from_secret_bytes(&bytes).c(d!())
+    }
+
+    /// Re-encrypt the secret key under a new password, with a fresh salt and the same
+    /// parameters.
+    pub fn rotate_password<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        old_password: &[u8],
+        new_password: &[u8],
+    ) -> Result<Keystore> {
+        let bytes = self.decrypt_bytes(old_password).c(d!())?;
+        Self::encrypt_bytes(prng, &self.kind, &bytes, new_password, self.kdf).c(d!())
+    }
+
+    /// Encode the keystore into JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Decode a keystore from JSON.
+    pub fn from_json(json: &str) -> Result<Keystore> {
+        let keystore: Keystore =
+            serde_json::from_str(json).c(d!(ZeiError::DeserializationError))?;
+        keystore.check_version().c(d!())?;
+        Ok(keystore)
+    }
+
+    /// Save the keystore to a writer, in JSON.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Load a keystore from a reader, in JSON.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<Keystore> {
+        let keystore: Keystore =
+            serde_json::from_reader(reader).c(d!(ZeiError::DeserializationError))?;
+        keystore.check_version().c(d!())?;
+        Ok(keystore)
+    }
+
+    fn encrypt_bytes<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        kind: &str,
+        bytes: &[u8],
+        password: &[u8],
+        kdf: KdfParams,
+    ) -> Result<Keystore> {
+        let mut salt = [0u8; SALT_LENGTH];
+        prng.fill_bytes(&mut salt);
+        let mut keystore = Keystore {
+            version: KEYSTORE_VERSION,
+            kind: kind.to_string(),
+            kdf,
+            salt: b64enc(&salt),
+            cipher: ChaCha20Poly1305Aead::ALGORITHM,
+            ciphertext: String::new(),
+        };
+
+        // the key is derived from a fresh salt, so that it only encrypts this secret key
+        let key = kdf.derive_key(password, &salt).c(d!())?;
+        let ciphertext = ChaCha20Poly1305Aead::encrypt(&key, &keystore.header(), bytes).c(d!())?;
+        keystore.ciphertext = b64enc(&ciphertext);
+        Ok(keystore)
+    }
+
+    fn decrypt_bytes(&self, password: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        self.check_version().c(d!())?;
+        let salt = b64dec(&self.salt).c(d!(ZeiError::DeserializationError))?;
+        let ciphertext = b64dec(&self.ciphertext).c(d!(ZeiError::DeserializationError))?;
+        let key = self.kdf.derive_key(password, &salt).c(d!())?;
+        self.cipher
+            .decrypt(&key, &self.header(), &ciphertext)
+            .map(Zeroizing::new)
+            .c(d!(ZeiError::DecryptionError))
+    }
+
+    fn check_version(&self) -> Result<()> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        Ok(())
+    }
+
+    // The fields other than the ciphertext, authenticated as the associated data.
+    fn header(&self) -> Vec<u8> {
+        let mut header = KEYSTORE_DOMAIN.to_vec();
+        header.extend_from_slice(&self.version.to_le_bytes());
+        header.extend_from_slice(&(self.kind.len() as u64).to_le_bytes());
+        header.extend_from_slice(self.kind.as_bytes());
+        header.extend_from_slice(&self.kdf.m_cost.to_le_bytes());
+        header.extend_from_slice(&self.kdf.t_cost.to_le_bytes());
+        header.extend_from_slice(&self.kdf.p_cost.to_le_bytes());
+        header.extend_from_slice(&(self.salt.len() as u64).to_le_bytes());
+        header.extend_from_slice(self.salt.as_bytes());
+        header.push(self.cipher.id());
+        header
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_creds::{ac_keygen_issuer, ac_keygen_user, ACUserSecretKey};
+    use crate::anon_xfr::keys::AXfrKeyPair;
+    use crate::keystore::{KdfParams, Keystore, MAX_KDF_M_COST, MAX_KDF_T_COST};
+    use crate::xfr::sig::{XfrKeyPair, XfrSecretKey};
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+    use zei_crypto::basic::aead::AeadAlgorithm;
+
+    const PARAMS: KdfParams = KdfParams {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[test]
+    fn keystore() {
+        let mut prng = test_rng();
+        let keypair = XfrKeyPair::generate(&mut prng);
+        let keystore = Keystore::encrypt(&mut prng, &keypair.sec_key, b"password", PARAMS).unwrap();
+        assert_eq!(
+            keystore.decrypt::<XfrSecretKey>(b"password").unwrap(),
+            keypair.sec_key
+        );
+        msg_eq!(
+            ZeiError::DecryptionError,
+            keystore.decrypt::<XfrSecretKey>(b"passw0rd").unwrap_err()
+        );
+        assert!(keystore.decrypt::<AXfrKeyPair>(b"password").is_err());
+
+        let mut bytes = vec![];
+        keystore.save_to_writer(&mut bytes).unwrap();
+        assert_eq!(
+            Keystore::load_from_reader(bytes.as_slice()).unwrap(),
+            keystore
+        );
+        assert_eq!(
+            Keystore::from_json(&keystore.to_json().unwrap()).unwrap(),
+            keystore
+        );
+
+        // the parameters and the algorithm are authenticated
+        let mut modified = keystore.clone();
+        modified.kdf.t_cost = 2;
+        assert!(modified.decrypt::<XfrSecretKey>(b"password").is_err());
+        let mut modified = keystore.clone();
+        modified.cipher = AeadAlgorithm::Aes256GcmSiv;
+        assert!(modified.decrypt::<XfrSecretKey>(b"password").is_err());
+        let mut modified = keystore.clone();
+        modified.version += 1;
+        assert!(Keystore::from_json(&modified.to_json().unwrap()).is_err());
+
+        // the costs are capped, before any memory is allocated for the key derivation
+        let mut modified = keystore.clone();
+        modified.kdf.m_cost = MAX_KDF_M_COST + 1;
+        msg_eq!(
+            ZeiError::ParameterError,
+            modified.decrypt::<XfrSecretKey>(b"password").unwrap_err()
+        );
+        let params = KdfParams {
+            t_cost: MAX_KDF_T_COST + 1,
+            ..PARAMS
+        };
+        assert!(Keystore::encrypt(&mut prng, &keypair.sec_key, b"password", params).is_err());
+
+        let rotated = keystore
+            .rotate_password(&mut prng, b"password", b"new password")
+            .unwrap();
+        assert_eq!(
+            rotated.decrypt::<XfrSecretKey>(b"new password").unwrap(),
+            keypair.sec_key
+        );
+        assert!(rotated.decrypt::<XfrSecretKey>(b"password").is_err());
+        assert_ne!(rotated.salt, keystore.salt);
+        assert!(keystore
+            .rotate_password(&mut prng, b"wrong password", b"new password")
+            .is_err());
+    }
+
+    #[test]
+    fn keystore_kinds() {
+        let mut prng = test_rng();
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let keystore = Keystore::encrypt(&mut prng, &keypair, b"password", PARAMS).unwrap();
+        assert_eq!(
+            keystore.decrypt::<AXfrKeyPair>(b"password").unwrap(),
+            keypair
+        );
+
+        let (_, issuer_pk) = ac_keygen_issuer(&mut prng, 3);
+        let (user_sk, _) = ac_keygen_user(&mut prng, &issuer_pk);
+        let keystore = Keystore::encrypt(&mut prng, &user_sk, b"password", PARAMS).unwrap();
+        assert_eq!(
+            keystore.decrypt::<ACUserSecretKey>(b"password").unwrap(),
+            user_sk
+        );
+    }
+}
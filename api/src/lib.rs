@@ -29,18 +29,52 @@ extern crate serde_derive;
 #[macro_use]
 extern crate lazy_static;
 
+/// Module for accounts with an encrypted balance, updated homomorphically by their transactions.
+pub mod account;
 /// The wrapper for anonymous credentials.
 pub mod anon_creds;
 /// Module for anonymous transfer.
 pub mod anon_xfr;
+/// Module for the audit reports of account holders, proving statements about their records.
+pub mod audit_report;
+/// Module for verifying all the notes of a block with a single call.
+pub mod block;
+/// Module for the deprecated module paths from before the split into the `zei_algebra`,
+/// `zei_crypto` and `zei` crates.
+#[cfg(feature = "compat")]
+#[allow(deprecated)]
+pub mod compat;
+/// Module for dual-control decryption of the asset tracing memos, with an audit log.
+pub mod dual_control;
+/// Module for password-encrypted keystores of secret keys.
+#[cfg(feature = "keystore")]
+pub mod keystore;
+/// Module for tagging credentials with their pairing suite, and verifying them with the right backend.
+pub mod pairing_suite;
 /// The wrapper of the parameters.
 pub mod parameters;
 /// Module for serialization.
 pub mod serialization;
+/// Module for thread-safe prover and verifier services with byte-in/byte-out interfaces.
+pub mod service;
 /// Module for generating parameters.
 pub mod setup;
+/// Module for extracting machine-readable specifications of the protocols from the code.
+#[cfg(feature = "spec")]
+pub mod spec;
+/// Module for confidential delegation of stake to validators.
+pub mod staking;
+/// Module for generating and checking JSON test vectors for the language bindings.
+#[cfg(feature = "testing")]
+pub mod testing;
+/// Module for decrypting the asset tracing memos of notes, for auditors.
+pub mod tracing;
 /// Module for confidential transfer.
 pub mod xfr;
 
 pub use zei_algebra::errors;
 pub use zei_algebra::ristretto;
+
+#[cfg(feature = "compat")]
+#[allow(deprecated)]
+pub use compat::{algebra, api, basic_crypto, crypto, utils};
@@ -0,0 +1,193 @@
+//! Credentials tagged with the pairing suite they are built over.
+//!
+//! A deployment can run several pairing engines side by side, e.g., BLS12-381 for the
+//! existing credentials and BN254 for the ones checked by EVM contracts. A
+//! [`SuiteTaggedReveal`] carries the identifier of its suite together with the encodings of
+//! the issuer public key and of the reveal proof, and [`verify_tagged_reveal`] selects the
+//! backend from that identifier at runtime, rejecting the suites it does not know.
+//!
+//! The transparent and confidential transfer notes do not use pairings, so only the
+//! anonymous credentials are tagged.
+
+use zei_algebra::{
+    bls12_381::BLSPairingEngine, bn254::BN254PairingEngine, prelude::*, traits::Pairing,
+};
+use zei_crypto::anon_creds::{verify_open, Attribute, CredentialIssuerPK, CredentialSigOpenProof};
+
+use crate::anon_creds::Attr;
+
+/// The pairing suites that credentials can be built over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum PairingSuite {
+    /// The BLS12-381 curve.
+    Bls12_381 = 1,
+    /// The BN254 curve.
+    Bn254 = 2,
+}
+
+impl PairingSuite {
+    /// Return the identifier of the suite, as written in the tagged credentials.
+    pub fn id(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Return the suite of an identifier, or an error if the suite is unknown.
+    pub fn from_id(id: u16) -> Result<Self> {
+        match id {
+            1 => Ok(PairingSuite::Bls12_381),
+            2 => Ok(PairingSuite::Bn254),
+            _ => Err(eg!(ZeiError::UnsupportedPairingSuite)),
+        }
+    }
+}
+
+/// A pairing engine that credentials can be tagged with.
+pub trait SuitePairing: Pairing {
+    /// The suite of the pairing engine.
+    const SUITE: PairingSuite;
+}
+
+impl SuitePairing for BLSPairingEngine {
+    const SUITE: PairingSuite = PairingSuite::Bls12_381;
+}
+
+impl SuitePairing for BN254PairingEngine {
+    const SUITE: PairingSuite = PairingSuite::Bn254;
+}
+
+/// The reveal proof of a credential, tagged with the pairing suite of the credential.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuiteTaggedReveal {
+    /// The identifier of the pairing suite.
+    pub suite: u16,
+    /// The encoding of the issuer public key.
+    pub issuer_pk: Vec<u8>,
+    /// The encoding of the reveal proof.
+    pub reveal_sig: Vec<u8>,
+}
+
+impl SuiteTaggedReveal {
+    /// Tag the reveal proof of a credential with the suite of its pairing engine.
+    pub fn new<P: SuitePairing>(
+        issuer_pk: &CredentialIssuerPK<P::G1, P::G2>,
+        reveal_sig: &CredentialSigOpenProof<P::G1, P::G2, P::ScalarField>,
+    ) -> Result<Self> {
+        Ok(SuiteTaggedReveal {
+            suite: P::SUITE.id(),
+            issuer_pk: bincode::serialize(issuer_pk).c(d!(ZeiError::SerializationError))?,
+            reveal_sig: bincode::serialize(reveal_sig).c(d!(ZeiError::SerializationError))?,
+        })
+    }
+
+    /// Return the pairing suite of the reveal proof, or an error if the suite is unknown.
+    pub fn suite(&self) -> Result<PairingSuite> {
+        PairingSuite::from_id(self.suite)
+    }
+}
+
+/// Verify a tagged reveal proof against the revealed attributes, with the backend of its
+/// pairing suite. Return an error if the suite is unknown.
+pub fn verify_tagged_reveal(tagged: &SuiteTaggedReveal, attrs: &[Option<Attr>]) -> Result<()> {
+    match tagged.suite().c(d!())? {
+        PairingSuite::Bls12_381 => verify_reveal::<BLSPairingEngine>(tagged, attrs).c(d!()),
+        PairingSuite::Bn254 => verify_reveal::<BN254PairingEngine>(tagged, attrs).c(d!()),
+    }
+}
+
+fn verify_reveal<P: SuitePairing>(
+    tagged: &SuiteTaggedReveal,
+    attrs: &[Option<Attr>],
+) -> Result<()> {
+    let issuer_pk: CredentialIssuerPK<P::G1, P::G2> =
+        bincode::deserialize(&tagged.issuer_pk).c(d!(ZeiError::DeserializationError))?;
+    let reveal_sig: CredentialSigOpenProof<P::G1, P::G2, P::ScalarField> =
+        bincode::deserialize(&tagged.reveal_sig).c(d!(ZeiError::DeserializationError))?;
+    let attrs_scalar = attrs
+        .iter()
+        .map(|attr| match attr {
+            Some(x) => match x.to_scalars::<P::ScalarField>().c(d!())?.as_slice() {
+                [scalar] => Ok(Attribute::Revealed(*scalar)),
+                _ => Err(eg!(ZeiError::ParameterError)),
+            },
+            None => Ok(Attribute::Hidden(None)),
+        })
+        .collect::<Result<Vec<Attribute<P::ScalarField>>>>()?;
+    verify_open::<P>(
+        &issuer_pk,
+        &reveal_sig.cm,
+        &reveal_sig.proof_open,
+        attrs_scalar.as_slice(),
+    )
+    .c(d!())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_creds::{
+        ac_keygen_issuer, ac_keygen_user, ac_reveal, ac_sign, Attr, Credential,
+    };
+    use crate::pairing_suite::{verify_tagged_reveal, PairingSuite, SuiteTaggedReveal};
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::BLSPairingEngine,
+        bn254::{BN254PairingEngine, BN254Scalar},
+        prelude::*,
+    };
+    use zei_crypto::anon_creds::{
+        grant_credential, issuer_keygen, open_credential, user_keygen, Credential as Cred,
+    };
+    use zei_crypto::attributes::encode_attributes;
+
+    #[test]
+    fn tagged_reveal() {
+        let mut prng = test_rng();
+        let attrs = vec![Attr::U64(10), Attr::U64(20)];
+        let bitmap = [true, false];
+
+        // a BLS12-381 credential
+        let (issuer_sk, issuer_pk) = ac_keygen_issuer(&mut prng, 2);
+        let (user_sk, user_pk) = ac_keygen_user(&mut prng, &issuer_pk);
+        let sig = ac_sign(&mut prng, &issuer_sk, &user_pk, &attrs).unwrap();
+        let credential = Credential {
+            sig,
+            attrs: attrs.clone(),
+            ipk: issuer_pk.clone(),
+        };
+        let reveal_sig = ac_reveal(&mut prng, &user_sk, &credential, &bitmap).unwrap();
+        let bls_tagged =
+            SuiteTaggedReveal::new::<BLSPairingEngine>(&issuer_pk, &reveal_sig).unwrap();
+        assert_eq!(bls_tagged.suite().unwrap(), PairingSuite::Bls12_381);
+        assert!(verify_tagged_reveal(&bls_tagged, &[Some(Attr::U64(10)), None]).is_ok());
+        assert!(verify_tagged_reveal(&bls_tagged, &[Some(Attr::U64(11)), None]).is_err());
+
+        // a BN254 credential
+        let scalars = encode_attributes::<BN254Scalar>(&attrs).unwrap();
+        let (issuer_sk, issuer_pk) = issuer_keygen::<_, BN254PairingEngine>(&mut prng, 2);
+        let (user_sk, user_pk) = user_keygen::<_, BN254PairingEngine>(&mut prng, &issuer_pk);
+        let sig =
+            grant_credential::<_, BN254PairingEngine>(&mut prng, &issuer_sk, &user_pk, &scalars)
+                .unwrap();
+        let credential = Cred {
+            sig,
+            attrs: scalars,
+            ipk: issuer_pk.clone(),
+        };
+        let reveal_sig =
+            open_credential::<_, BN254PairingEngine>(&mut prng, &user_sk, &credential, &bitmap)
+                .unwrap();
+        let bn_tagged =
+            SuiteTaggedReveal::new::<BN254PairingEngine>(&issuer_pk, &reveal_sig).unwrap();
+        assert_eq!(bn_tagged.suite().unwrap(), PairingSuite::Bn254);
+        assert!(verify_tagged_reveal(&bn_tagged, &[Some(Attr::U64(10)), None]).is_ok());
+        assert!(verify_tagged_reveal(&bn_tagged, &[Some(Attr::U64(11)), None]).is_err());
+
+        // a proof tagged with the wrong suite, or an unknown one, is rejected
+        let mut mistagged = bn_tagged.clone();
+        mistagged.suite = PairingSuite::Bls12_381.id();
+        assert!(verify_tagged_reveal(&mistagged, &[Some(Attr::U64(10)), None]).is_err());
+        let mut unknown = bn_tagged;
+        unknown.suite = 3;
+        assert!(unknown.suite().is_err());
+        assert!(verify_tagged_reveal(&unknown, &[Some(Attr::U64(10)), None]).is_err());
+    }
+}
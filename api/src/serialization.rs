@@ -1,6 +1,7 @@
 use crate::xfr::{
-    sig::{XfrPublicKey, XfrSecretKey, XfrSignature},
+    sig::{KeyType, XfrPublicKey, XfrSecretKey, XfrSignature},
     structs::{AssetType, ASSET_TYPE_LENGTH},
+    view_key::{ViewKey, VIEW_KEY_LENGTH},
 };
 use serde::Serializer;
 use zei_algebra::prelude::*;
@@ -56,6 +57,35 @@ impl ZeiFromToBytes for XfrSignature {
 
 serialize_deserialize!(XfrSignature);
 
+impl ZeiFromToBytes for ViewKey {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.key_type.to_byte()];
+        bytes.extend_from_slice(&self.scalar);
+        bytes.extend_from_slice(&self.pub_key.to_bytes());
+        bytes
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != VIEW_KEY_LENGTH {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let key_type = match bytes[0] {
+            0u8 => KeyType::Ed25519,
+            1u8 => KeyType::Secp256k1,
+            _ => return Err(eg!(ZeiError::DeserializationError)),
+        };
+        // the public key must be of the same key type
+        if bytes[33] != bytes[0] {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let pub_key = XfrPublicKey::from_bytes(&bytes[33..]).c(d!())?;
+        ViewKey::from_view_secret(key_type, bytes[1..33].to_vec(), pub_key)
+            .c(d!(ZeiError::DeserializationError))
+    }
+}
+
+serialize_deserialize!(ViewKey);
+
 #[cfg(test)]
 mod test {
     use crate::anon_xfr::keys::{AXfrKeyPair, AXfrPubKey};
@@ -281,4 +311,37 @@ mod test {
             pnk!(Err(eg!("Failed to deserialize XfrPublicKey from JSON")));
         }
     }
+
+    #[test]
+    fn deserialization_limits_are_enforced() {
+        use zei_algebra::serialization::{
+            deserialization_limits, zei_bounded_serde, DeserializationLimits,
+        };
+        use zei_crypto::basic::hybrid_encryption::Ctext;
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Outputs(#[serde(deserialize_with = "zei_bounded_serde::outputs")] Vec<u32>);
+
+        let limits = deserialization_limits();
+        assert_eq!(limits, DeserializationLimits::default());
+
+        let json = serde_json::to_string(&vec![0u32; limits.max_outputs]).unwrap();
+        assert!(serde_json::from_str::<Outputs>(&json).is_ok());
+        let json = serde_json::to_string(&vec![0u32; limits.max_outputs + 1]).unwrap();
+        assert!(serde_json::from_str::<Outputs>(&json).is_err());
+
+        // a huge length prefix is rejected before anything is allocated
+        let huge = [0xddu8, 0xff, 0xff, 0xff, 0xff];
+        let err = Outputs::deserialize(&mut Deserializer::new(&huge[..])).unwrap_err();
+        assert!(err.to_string().contains("limit"));
+        assert!(Ctext::deserialize(&mut Deserializer::new(&huge[..])).is_err());
+
+        let memo = crate::anon_xfr::structs::AxfrOwnerMemo {
+            point: AXfrKeyPair::generate(&mut test_rng()).get_public_key(),
+            ctext: vec![0u8; limits.max_memo_bytes + 1],
+        };
+        let json = serde_json::to_string(&memo).unwrap();
+        assert!(serde_json::from_str::<crate::anon_xfr::structs::AxfrOwnerMemo>(&json).is_err());
+        assert!(Ctext::zei_from_bytes(&memo.ctext).is_err());
+    }
 }
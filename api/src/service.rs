@@ -0,0 +1,631 @@
+use crate::anon_xfr::{
+    abar_to_abar::{finish_anon_xfr_note, init_anon_xfr_note, verify_anon_xfr_note, AXfrNote},
+    abar_to_ar::{
+        finish_abar_to_ar_note, init_abar_to_ar_note, verify_abar_to_ar_note, AbarToArNote,
+    },
+    abar_to_bar::{
+        finish_abar_to_bar_note, init_abar_to_bar_note, verify_abar_to_bar_note, AbarToBarNote,
+    },
+    ar_to_abar::{gen_ar_to_abar_note, verify_ar_to_abar_note, ArToAbarNote},
+    bar_to_abar::{gen_bar_to_abar_note, verify_bar_to_abar_note, BarToAbarNote},
+    keys::{AXfrKeyPair, AXfrPubKey},
+    structs::OpenAnonAssetRecord,
+};
+use crate::setup::{BulletproofParams, ParamsKind, ProverParams, VerifierParams};
+use crate::xfr::{
+    asset_record::AssetRecordType,
+    batch_verify_xfr_notes, gen_xfr_note,
+    sig::{XfrKeyPair, XfrPublicKey},
+    structs::{AssetRecord, OpenAssetRecord, XfrNote},
+    XfrNotePolicies,
+};
+use rand_chacha::ChaChaRng;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha512};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+#[cfg(feature = "parallel")]
+use crate::anon_xfr::abar_to_abar::batch_verify_anon_xfr_note;
+
+/// The limits that a service enforces on the requests, before doing any expensive work.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceLimits {
+    /// The maximal size, in bytes, of a serialized argument, e.g., a note.
+    pub max_input_size: usize,
+    /// The maximal number of notes in a batch.
+    pub max_batch_size: usize,
+    /// The depth of the Merkle tree of the anonymous records.
+    pub tree_depth: usize,
+}
+
+impl Default for ServiceLimits {
+    fn default() -> Self {
+        ServiceLimits {
+            max_input_size: 1 << 20,
+            max_batch_size: 64,
+            tree_depth: crate::anon_xfr::TREE_DEPTH,
+        }
+    }
+}
+
+impl ServiceLimits {
+    fn check_batch(&self, len: usize) -> Result<()> {
+        if len == 0 || len > self.max_batch_size {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(())
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T> {
+        if bytes.len() > self.max_input_size {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    bincode::serialize(value).c(d!(ZeiError::SerializationError))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Seed a ChaCha generator from `prng`.
+fn seed_prng<R: RngCore>(prng: &mut R) -> Result<ChaChaRng> {
+    ChaChaRng::from_rng(prng).c(d!(ZeiError::ParameterError))
+}
+
+/// The SHA-512 hash of a message, to which the address folding of an anonymous note is bound.
+fn message_hash(msg: &[u8]) -> Sha512 {
+    let mut hash = Sha512::new();
+    hash.update(msg);
+    hash
+}
+
+/// A pool of Bulletproofs parameters, which are mutable during the verification, since
+/// the generators grow on demand, and thus cannot be shared between concurrent requests.
+struct BulletproofParamsPool {
+    params: Mutex<Vec<BulletproofParams>>,
+}
+
+impl BulletproofParamsPool {
+    fn new() -> Self {
+        BulletproofParamsPool {
+            params: Mutex::new(vec![]),
+        }
+    }
+
+    fn with<T, F: FnOnce(&mut BulletproofParams) -> T>(&self, f: F) -> T {
+        let mut params = lock(&self.params).pop().unwrap_or_default();
+        let res = f(&mut params);
+        lock(&self.params).push(params);
+        res
+    }
+}
+
+/// A thread-safe verifier of notes given in their bincode serialization.
+///
+/// The Plonk verifier parameters come from the global cache, see [`VerifierParams::get_or_init`],
+/// and can be loaded ahead of the first request with [`ZeiVerifierService::preload`].
+/// The Bulletproofs parameters are pooled across requests.
+pub struct ZeiVerifierService {
+    limits: ServiceLimits,
+    prng: Mutex<ChaChaRng>,
+    bp_params: BulletproofParamsPool,
+}
+
+impl ZeiVerifierService {
+    /// Create a verifier service, whose randomness for batch verification is seeded from `prng`.
+    /// Return an error if `prng` fails to provide the seed.
+    pub fn new<R: CryptoRng + RngCore>(prng: &mut R, limits: ServiceLimits) -> Result<Self> {
+        Ok(ZeiVerifierService {
+            limits,
+            prng: Mutex::new(seed_prng(prng).c(d!())?),
+            bp_params: BulletproofParamsPool::new(),
+        })
+    }
+
+    /// Return the limits of the service.
+    pub fn limits(&self) -> &ServiceLimits {
+        &self.limits
+    }
+
+    /// Load the verifier parameters of these kinds into the global cache.
+    pub fn preload(&self, kinds: &[ParamsKind]) -> Result<()> {
+        for kind in kinds {
+            VerifierParams::get_or_init(*kind).c(d!())?;
+        }
+        Ok(())
+    }
+
+    /// Verify a confidential transfer note, with the serialized [`XfrNotePolicies`],
+    /// or without policies if `policies` is `None`.
+    pub fn verify_xfr_note(&self, note: &[u8], policies: Option<&[u8]>) -> Result<()> {
+        self.batch_verify_xfr_notes(&[note], &[policies]).c(d!())
+    }
+
+    /// Batch-verify confidential transfer notes, see [`ZeiVerifierService::verify_xfr_note`].
+    pub fn batch_verify_xfr_notes(
+        &self,
+        notes: &[&[u8]],
+        policies: &[Option<&[u8]>],
+    ) -> Result<()> {
+        self.limits.check_batch(notes.len()).c(d!())?;
+        if notes.len() != policies.len() {
+            return Err(eg!(ZeiError::LengthMismatch {
+                expected: notes.len(),
+                actual: policies.len(),
+            }));
+        }
+
+        let notes: Vec<XfrNote> = notes
+            .iter()
+            .map(|note| self.limits.decode(note))
+            .collect::<Result<_>>()
+            .c(d!())?;
+        let policies: Vec<XfrNotePolicies> = notes
+            .iter()
+            .zip(policies.iter())
+            .map(|(note, policies)| match policies {
+                Some(bytes) => self.limits.decode(bytes),
+                None => Ok(XfrNotePolicies::empty_policies(
+                    note.body.inputs.len(),
+                    note.body.outputs.len(),
+                )),
+            })
+            .collect::<Result<_>>()
+            .c(d!())?;
+
+        let note_refs = notes.iter().collect_vec();
+        let policies_refs = policies.iter().map(|p| p.to_ref()).collect_vec();
+        let policies_refs = policies_refs.iter().collect_vec();
+
+        let mut prng = seed_prng(&mut *lock(&self.prng)).c(d!())?;
+        self.bp_params
+            .with(|params| batch_verify_xfr_notes(&mut prng, params, &note_refs, &policies_refs))
+            .c(d!())
+    }
+
+    /// Verify a transparent-to-anonymous note.
+    pub fn verify_ar_to_abar_note(&self, note: &[u8]) -> Result<()> {
+        let note: ArToAbarNote = self.limits.decode(note).c(d!())?;
+        let params = VerifierParams::get_or_init(ParamsKind::ArToAbar).c(d!())?;
+        verify_ar_to_abar_note(&params, &note).c(d!())
+    }
+
+    /// Verify a confidential-to-anonymous note, signed by the owner of the input.
+    pub fn verify_bar_to_abar_note(&self, note: &[u8], signer: &[u8]) -> Result<()> {
+        let note: BarToAbarNote = self.limits.decode(note).c(d!())?;
+        let signer = XfrPublicKey::zei_from_bytes(signer).c(d!())?;
+        let params = VerifierParams::get_or_init(ParamsKind::BarToAbar).c(d!())?;
+        verify_bar_to_abar_note(&params, &note, &signer).c(d!())
+    }
+
+    /// Verify an anonymous transfer note against a Merkle root, where `msg` is the message
+    /// that the address folding of the note is bound to.
+    pub fn verify_anon_xfr_note(&self, note: &[u8], merkle_root: &[u8], msg: &[u8]) -> Result<()> {
+        let note: AXfrNote = self.limits.decode(note).c(d!())?;
+        let merkle_root = BLSScalar::zei_from_bytes(merkle_root).c(d!())?;
+        let params = VerifierParams::get_or_init(self.anon_xfr_kind(&note)).c(d!())?;
+        verify_anon_xfr_note(&params, &note, &merkle_root, message_hash(msg)).c(d!())
+    }
+
+    /// Batch-verify anonymous transfer notes, see [`ZeiVerifierService::verify_anon_xfr_note`].
+    /// The notes are verified in parallel.
+    #[cfg(feature = "parallel")]
+    pub fn batch_verify_anon_xfr_notes(
+        &self,
+        notes: &[&[u8]],
+        merkle_roots: &[&[u8]],
+        msgs: &[&[u8]],
+    ) -> Result<()> {
+        self.limits.check_batch(notes.len()).c(d!())?;
+        for len in [merkle_roots.len(), msgs.len()] {
+            if len != notes.len() {
+                return Err(eg!(ZeiError::LengthMismatch {
+                    expected: notes.len(),
+                    actual: len,
+                }));
+            }
+        }
+
+        let notes: Vec<AXfrNote> = notes
+            .iter()
+            .map(|note| self.limits.decode(note))
+            .collect::<Result<_>>()
+            .c(d!())?;
+        let merkle_roots: Vec<BLSScalar> = merkle_roots
+            .iter()
+            .map(|root| BLSScalar::zei_from_bytes(root))
+            .collect::<Result<_>>()
+            .c(d!())?;
+        let params = notes
+            .iter()
+            .map(|note| VerifierParams::get_or_init(self.anon_xfr_kind(note)))
+            .collect::<Result<Vec<_>>>()
+            .c(d!())?;
+
+        batch_verify_anon_xfr_note(
+            &params.iter().map(|p| p.as_ref()).collect_vec(),
+            &notes.iter().collect_vec(),
+            &merkle_roots.iter().collect_vec(),
+            msgs.iter().map(|msg| message_hash(msg)).collect(),
+        )
+        .c(d!())
+    }
+
+    /// Verify an anonymous-to-transparent note against a Merkle root, where `msg` is the
+    /// message that the address folding of the note is bound to.
+    pub fn verify_abar_to_ar_note(
+        &self,
+        note: &[u8],
+        merkle_root: &[u8],
+        msg: &[u8],
+    ) -> Result<()> {
+        let note: AbarToArNote = self.limits.decode(note).c(d!())?;
+        let merkle_root = BLSScalar::zei_from_bytes(merkle_root).c(d!())?;
+        let params = VerifierParams::get_or_init(ParamsKind::AbarToAr {
+            tree_depth: self.limits.tree_depth,
+        })
+        .c(d!())?;
+        verify_abar_to_ar_note(&params, &note, &merkle_root, message_hash(msg)).c(d!())
+    }
+
+    /// Verify an anonymous-to-confidential note against a Merkle root, where `msg` is the
+    /// message that the address folding of the note is bound to.
+    pub fn verify_abar_to_bar_note(
+        &self,
+        note: &[u8],
+        merkle_root: &[u8],
+        msg: &[u8],
+    ) -> Result<()> {
+        let note: AbarToBarNote = self.limits.decode(note).c(d!())?;
+        let merkle_root = BLSScalar::zei_from_bytes(merkle_root).c(d!())?;
+        let params = VerifierParams::get_or_init(ParamsKind::AbarToBar {
+            tree_depth: self.limits.tree_depth,
+        })
+        .c(d!())?;
+        verify_abar_to_bar_note(&params, &note, &merkle_root, message_hash(msg)).c(d!())
+    }
+
+    fn anon_xfr_kind(&self, note: &AXfrNote) -> ParamsKind {
+        ParamsKind::AnonXfr {
+            n_payers: note.body.inputs.len(),
+            n_payees: note.body.outputs.len(),
+            tree_depth: self.limits.tree_depth,
+        }
+    }
+}
+
+/// A thread-safe prover of notes, whose arguments and results are given in their bincode
+/// serialization, or in their canonical byte representation for keys.
+///
+/// The Plonk prover parameters come from the global cache, see [`ProverParams::get_or_init`],
+/// and can be loaded ahead of the first request with [`ZeiProverService::preload`].
+pub struct ZeiProverService {
+    limits: ServiceLimits,
+    prng: Mutex<ChaChaRng>,
+}
+
+impl ZeiProverService {
+    /// Create a prover service, whose randomness is seeded from `prng`.
+    /// Return an error if `prng` fails to provide the seed.
+    pub fn new<R: CryptoRng + RngCore>(prng: &mut R, limits: ServiceLimits) -> Result<Self> {
+        Ok(ZeiProverService {
+            limits,
+            prng: Mutex::new(seed_prng(prng).c(d!())?),
+        })
+    }
+
+    /// Return the limits of the service.
+    pub fn limits(&self) -> &ServiceLimits {
+        &self.limits
+    }
+
+    /// Load the prover parameters of these kinds into the global cache.
+    pub fn preload(&self, kinds: &[ParamsKind]) -> Result<()> {
+        for kind in kinds {
+            ProverParams::get_or_init(*kind).c(d!())?;
+        }
+        Ok(())
+    }
+
+    /// Derive the randomness of a request, so that the requests do not hold the lock
+    /// while proving.
+    fn request_prng(&self) -> Result<ChaChaRng> {
+        seed_prng(&mut *lock(&self.prng)).c(d!())
+    }
+
+    /// Generate a confidential transfer note from the serialized lists of input and output
+    /// [`AssetRecord`]s and the serialized list of the [`XfrKeyPair`]s of the inputs.
+    pub fn gen_xfr_note(&self, inputs: &[u8], outputs: &[u8], key_pairs: &[u8]) -> Result<Vec<u8>> {
+        let inputs: Vec<AssetRecord> = self.limits.decode(inputs).c(d!())?;
+        let outputs: Vec<AssetRecord> = self.limits.decode(outputs).c(d!())?;
+        let key_pairs: Vec<XfrKeyPair> = self.limits.decode(key_pairs).c(d!())?;
+
+        let note = gen_xfr_note(
+            &mut self.request_prng().c(d!())?,
+            &inputs,
+            &outputs,
+            &key_pairs.iter().collect_vec(),
+        )
+        .c(d!())?;
+        encode(&note).c(d!())
+    }
+
+    /// Generate a transparent-to-anonymous note, spending the [`OpenAssetRecord`] owned by
+    /// the key pair, for the anonymous public key.
+    pub fn gen_ar_to_abar_note(
+        &self,
+        record: &[u8],
+        key_pair: &[u8],
+        receiver: &[u8],
+    ) -> Result<Vec<u8>> {
+        let record: OpenAssetRecord = self.limits.decode(record).c(d!())?;
+        let key_pair = XfrKeyPair::zei_from_bytes(key_pair).c(d!())?;
+        let receiver = AXfrPubKey::zei_from_bytes(receiver).c(d!())?;
+        let params = ProverParams::get_or_init(ParamsKind::ArToAbar).c(d!())?;
+
+        let note = gen_ar_to_abar_note(
+            &mut self.request_prng().c(d!())?,
+            &params,
+            &record,
+            &key_pair,
+            &receiver,
+        )
+        .c(d!())?;
+        encode(&note).c(d!())
+    }
+
+    /// Generate a confidential-to-anonymous note, spending the [`OpenAssetRecord`] owned by
+    /// the key pair, for the anonymous public key.
+    pub fn gen_bar_to_abar_note(
+        &self,
+        record: &[u8],
+        key_pair: &[u8],
+        receiver: &[u8],
+    ) -> Result<Vec<u8>> {
+        let record: OpenAssetRecord = self.limits.decode(record).c(d!())?;
+        let key_pair = XfrKeyPair::zei_from_bytes(key_pair).c(d!())?;
+        let receiver = AXfrPubKey::zei_from_bytes(receiver).c(d!())?;
+        let params = ProverParams::get_or_init(ParamsKind::BarToAbar).c(d!())?;
+
+        let note = gen_bar_to_abar_note(
+            &mut self.request_prng().c(d!())?,
+            &params,
+            &record,
+            &key_pair,
+            &receiver,
+        )
+        .c(d!())?;
+        encode(&note).c(d!())
+    }
+
+    /// Generate an anonymous transfer note from the serialized lists of input and output
+    /// [`OpenAnonAssetRecord`]s, where the inputs carry their Merkle paths, and `msg` is the
+    /// message that the address folding of the note is bound to.
+    pub fn gen_anon_xfr_note(
+        &self,
+        inputs: &[u8],
+        outputs: &[u8],
+        fee: u32,
+        key_pair: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        let inputs: Vec<OpenAnonAssetRecord> = self.limits.decode(inputs).c(d!())?;
+        let outputs: Vec<OpenAnonAssetRecord> = self.limits.decode(outputs).c(d!())?;
+        let key_pair = AXfrKeyPair::zei_from_bytes(key_pair).c(d!())?;
+        let params = ProverParams::get_or_init(ParamsKind::AnonXfr {
+            n_payers: inputs.len(),
+            n_payees: outputs.len(),
+            tree_depth: self.limits.tree_depth,
+        })
+        .c(d!())?;
+
+        let pre_note = init_anon_xfr_note(&inputs, &outputs, fee, &key_pair).c(d!())?;
+        let note = finish_anon_xfr_note(
+            &mut self.request_prng().c(d!())?,
+            &params,
+            pre_note,
+            message_hash(msg),
+        )
+        .c(d!())?;
+        encode(&note).c(d!())
+    }
+
+    /// Generate an anonymous-to-transparent note, spending the serialized
+    /// [`OpenAnonAssetRecord`] with its Merkle path, for the public key `receiver`, where
+    /// `msg` is the message that the address folding of the note is bound to.
+    pub fn gen_abar_to_ar_note(
+        &self,
+        record: &[u8],
+        key_pair: &[u8],
+        receiver: &[u8],
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        let record: OpenAnonAssetRecord = self.limits.decode(record).c(d!())?;
+        let key_pair = AXfrKeyPair::zei_from_bytes(key_pair).c(d!())?;
+        let receiver = XfrPublicKey::zei_from_bytes(receiver).c(d!())?;
+        let params = ProverParams::get_or_init(ParamsKind::AbarToAr {
+            tree_depth: self.limits.tree_depth,
+        })
+        .c(d!())?;
+
+        let mut prng = self.request_prng().c(d!())?;
+        let pre_note = init_abar_to_ar_note(&mut prng, &record, &key_pair, &receiver).c(d!())?;
+        let note =
+            finish_abar_to_ar_note(&mut prng, &params, pre_note, message_hash(msg)).c(d!())?;
+        encode(&note).c(d!())
+    }
+
+    /// Generate an anonymous-to-confidential note, spending the serialized
+    /// [`OpenAnonAssetRecord`] with its Merkle path, for the public key `receiver` and with
+    /// the confidentiality of `asset_record_type`, where `msg` is the message that the address
+    /// folding of the note is bound to.
+    pub fn gen_abar_to_bar_note(
+        &self,
+        record: &[u8],
+        key_pair: &[u8],
+        receiver: &[u8],
+        asset_record_type: AssetRecordType,
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        let record: OpenAnonAssetRecord = self.limits.decode(record).c(d!())?;
+        let key_pair = AXfrKeyPair::zei_from_bytes(key_pair).c(d!())?;
+        let receiver = XfrPublicKey::zei_from_bytes(receiver).c(d!())?;
+        let params = ProverParams::get_or_init(ParamsKind::AbarToBar {
+            tree_depth: self.limits.tree_depth,
+        })
+        .c(d!())?;
+
+        let mut prng = self.request_prng().c(d!())?;
+        let pre_note =
+            init_abar_to_bar_note(&mut prng, &record, &key_pair, &receiver, asset_record_type)
+                .c(d!())?;
+        let note =
+            finish_abar_to_bar_note(&mut prng, &params, pre_note, message_hash(msg)).c(d!())?;
+        encode(&note).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ServiceLimits, ZeiProverService, ZeiVerifierService};
+    use crate::anon_xfr::{
+        abar_to_bar::AbarToBarNote,
+        commit,
+        keys::AXfrKeyPair,
+        structs::{MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecordBuilder},
+    };
+    use crate::xfr::{
+        asset_record::{open_blind_asset_record, AssetRecordType},
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType},
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+    use zei_crypto::basic::rescue::RescueInstance;
+
+    #[test]
+    fn xfr_note_roundtrip() {
+        let mut prng = test_rng();
+        let prover = ZeiProverService::new(&mut prng, ServiceLimits::default()).unwrap();
+        let verifier = ZeiVerifierService::new(&mut prng, ServiceLimits::default()).unwrap();
+
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let record_type = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let sender = XfrKeyPair::generate(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+        let mut record = |amount: u64, owner: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                owner.pub_key,
+            );
+            AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+        };
+        let inputs = vec![record(10, &sender)];
+        let outputs = vec![record(4, &receiver), record(6, &sender)];
+
+        let note = prover
+            .gen_xfr_note(
+                &bincode::serialize(&inputs).unwrap(),
+                &bincode::serialize(&outputs).unwrap(),
+                &bincode::serialize(&vec![sender]).unwrap(),
+            )
+            .unwrap();
+        verifier.verify_xfr_note(&note, None).unwrap();
+        verifier
+            .batch_verify_xfr_notes(&[&note, &note], &[None, None])
+            .unwrap();
+
+        let mut tampered = note.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(verifier.verify_xfr_note(&tampered, None).is_err());
+
+        let small = ZeiVerifierService::new(
+            &mut prng,
+            ServiceLimits {
+                max_input_size: note.len() - 1,
+                ..ServiceLimits::default()
+            },
+        )
+        .unwrap();
+        assert!(small.verify_xfr_note(&note, None).is_err());
+        assert!(verifier.batch_verify_xfr_notes(&[], &[]).is_err());
+    }
+
+    #[test]
+    fn abar_to_bar_note_roundtrip() {
+        let mut prng = test_rng();
+        let limits = ServiceLimits {
+            tree_depth: 1,
+            ..ServiceLimits::default()
+        };
+        let prover = ZeiProverService::new(&mut prng, limits).unwrap();
+        let verifier = ZeiVerifierService::new(&mut prng, limits).unwrap();
+
+        // a record that is the only leaf of a Merkle tree of depth one
+        let sender = AXfrKeyPair::generate(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+        let asset_type = AssetType::from_identical_byte(1u8);
+        let mut oabar = OpenAnonAssetRecordBuilder::new()
+            .amount(10)
+            .asset_type(asset_type)
+            .pub_key(&sender.get_public_key())
+            .finalize(&mut prng)
+            .unwrap()
+            .build()
+            .unwrap();
+        let commitment = commit(
+            oabar.pub_key_ref(),
+            &oabar.get_blind(),
+            oabar.get_amount(),
+            &oabar.get_asset_type(),
+        )
+        .unwrap();
+        let hash = RescueInstance::new();
+        let zero = BLSScalar::zero();
+        let leaf = hash.rescue(&[zero, commitment, zero, zero])[0];
+        let merkle_root = hash.rescue(&[leaf, zero, zero, zero])[0];
+        oabar.update_mt_leaf_info(MTLeafInfo {
+            path: MTPath::new(vec![MTNode {
+                siblings1: zero,
+                siblings2: zero,
+                is_left_child: 1,
+                is_right_child: 0,
+            }]),
+            root: merkle_root,
+            root_version: 1,
+            uid: 0,
+        });
+
+        let note = prover
+            .gen_abar_to_bar_note(
+                &bincode::serialize(&oabar).unwrap(),
+                &sender.zei_to_bytes(),
+                &receiver.pub_key.zei_to_bytes(),
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                b"abar to bar",
+            )
+            .unwrap();
+        let root = merkle_root.zei_to_bytes();
+        verifier
+            .verify_abar_to_bar_note(&note, &root, b"abar to bar")
+            .unwrap();
+        assert!(verifier
+            .verify_abar_to_bar_note(&note, &root, b"another message")
+            .is_err());
+
+        let note: AbarToBarNote = bincode::deserialize(&note).unwrap();
+        let record =
+            open_blind_asset_record(&note.body.output, &note.body.memo, &receiver).unwrap();
+        assert_eq!(record.amount, 10);
+        assert_eq!(record.asset_type, asset_type);
+    }
+}
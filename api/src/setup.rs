@@ -1,6 +1,7 @@
 // The Public Setup needed for Proofs
 use crate::anon_xfr::abar_to_abar::{build_multi_xfr_cs, AXfrWitness};
 use crate::anon_xfr::address_folding::AXfrAddressFoldingWitness;
+use crate::anon_xfr::audit::AXfrAuditWitness;
 use crate::anon_xfr::keys::AXfrKeyPair;
 use crate::anon_xfr::structs::{PayeeWitness, PayerWitness};
 use crate::anon_xfr::{
@@ -8,6 +9,9 @@ use crate::anon_xfr::{
     abar_to_bar::build_abar_to_bar_cs,
     ar_to_abar::build_ar_to_abar_cs,
     bar_to_abar::build_bar_to_abar_cs,
+    commit_and_prove::{
+        build_external_commitment_cs, ExternalCommitmentBlind, ExternalCommitmentKind,
+    },
     structs::{MTNode, MTPath},
     TurboPlonkCS, FEE_TYPE, TREE_DEPTH,
 };
@@ -17,14 +21,23 @@ use crate::parameters::{
     VERIFIER_SPECIFIC_PARAMS,
 };
 use bulletproofs::BulletproofGens;
+use merlin::Transcript;
 use rand_chacha::ChaChaRng;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
 use zei_algebra::ristretto::RistrettoPoint;
 use zei_algebra::{
     bls12_381::{BLSScalar, BLSG1},
+    collections::BTreeMap,
+    jubjub::JubjubScalar,
     prelude::*,
     ristretto::RistrettoScalar,
 };
+use zei_crypto::bulletproofs::range::RangeProofBackend;
 use zei_crypto::delegated_schnorr::{DelegatedSchnorrInspection, DelegatedSchnorrProof};
 use zei_crypto::field_simulation::SimFrParamsRistretto;
 use zei_plonk::{
@@ -35,6 +48,12 @@ use zei_plonk::{
     poly_commit::{kzg_poly_com::KZGCommitmentSchemeBLS, pcs::PolyComScheme},
 };
 
+/// Module for the multi-party ceremony of the structured reference string.
+pub mod srs;
+
+/// Module for the constraint counts, proof sizes and proving time estimates of the circuits.
+pub mod circuit_stats;
+
 /// The Bulletproofs URS.
 #[derive(Serialize, Deserialize)]
 pub struct BulletproofParams {
@@ -44,6 +63,10 @@ pub struct BulletproofParams {
     pub bp_circuit_gens: BulletproofGens,
     /// The number of bits in the range proof.
     pub range_proof_bits: usize,
+    /// The backend of the range proofs created with these parameters. Proofs of either
+    /// backend are verified.
+    #[serde(skip)]
+    pub range_proof_backend: RangeProofBackend,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -63,11 +86,15 @@ pub struct ProverParams {
 /// The verifier parameters.
 pub struct VerifierParams {
     /// The shrunk version of the polynomial commitment scheme.
-    pub pcs: KZGCommitmentSchemeBLS,
+    pub(crate) pcs: KZGCommitmentSchemeBLS,
     /// The shrunk version of the constraint system.
-    pub cs: TurboPlonkCS,
+    pub(crate) cs: TurboPlonkCS,
     /// The TurboPlonk verifying key.
-    pub verifier_params: PlonkVK<KZGCommitmentSchemeBLS>,
+    pub(crate) verifier_params: PlonkVK<KZGCommitmentSchemeBLS>,
+    /// The identifier of the parameters, computed on the first use. The parameters that it
+    /// hashes are not modified once they are built.
+    #[serde(skip)]
+    id: OnceLock<ParamsId>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -86,6 +113,26 @@ pub struct VerifierParamsSplitSpecific {
     pub verifier_params: PlonkVK<KZGCommitmentSchemeBLS>,
 }
 
+/// The identifier of the parameters of a circuit, which is the SHA-256 digest of its Plonk
+/// verifying key and of the elements of the SRS used by the verifier. It is the same for the
+/// prover parameters and the verifier parameters of a circuit, whether the latter are shrunk,
+/// split, or embedded, and it changes with the circuit or the SRS.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct ParamsId(pub [u8; 32]);
+
+const PARAMS_ID_DOMAIN: &[u8] = b"Zei Params Id";
+/// The label of the identifier of the parameters, in the transcripts of the proofs.
+pub(crate) const PARAMS_ID_LABEL: &[u8] = b"Params Id";
+
+impl ParamsId {
+    /// Append the identifier to the transcript of a proof, which binds the proof to it.
+    pub(crate) fn append_to_transcript(&self, transcript: &mut Transcript) {
+        transcript.append_message(PARAMS_ID_LABEL, &self.0);
+    }
+}
+
 /// The range in the Bulletproofs range check.
 pub const BULLET_PROOF_RANGE: usize = 32;
 /// The maximal number
@@ -95,6 +142,103 @@ pub const MAX_ANONYMOUS_RECORD_NUMBER: usize = 6;
 /// The default number of Bulletproofs generators
 pub const DEFAULT_BP_NUM_GENS: usize = 256;
 
+/// The kinds of parameters, used as the keys of the parameter cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ParamsKind {
+    /// Anonymous transfer with a given number of inputs, outputs, and a given tree depth.
+    AnonXfr {
+        /// The number of inputs.
+        n_payers: usize,
+        /// The number of outputs.
+        n_payees: usize,
+        /// The depth of the Merkle tree.
+        tree_depth: usize,
+    },
+    /// Auditable anonymous transfer, whose outputs are encrypted to an auditor,
+    /// with a given number of inputs, outputs, and a given tree depth.
+    AuditableAnonXfr {
+        /// The number of inputs.
+        n_payers: usize,
+        /// The number of outputs.
+        n_payees: usize,
+        /// The depth of the Merkle tree.
+        tree_depth: usize,
+    },
+    /// Confidential to anonymous.
+    BarToAbar,
+    /// Anonymous to confidential, with a given tree depth.
+    AbarToBar {
+        /// The depth of the Merkle tree.
+        tree_depth: usize,
+    },
+    /// Transparent to anonymous.
+    ArToAbar,
+    /// Anonymous to transparent, with a given tree depth.
+    AbarToAr {
+        /// The depth of the Merkle tree.
+        tree_depth: usize,
+    },
+    /// Linking an external commitment of a given kind to an anonymous asset record.
+    ExternalCommitment {
+        /// The kind of the external commitment.
+        kind: ExternalCommitmentKind,
+    },
+}
+
+/// A thread-safe cache of parameters.
+///
+/// Each kind of parameters has its own slot, so a thread generating some parameters
+/// only blocks the threads requesting the same kind.
+struct ParamsCache<T> {
+    slots: Mutex<BTreeMap<ParamsKind, Arc<Mutex<Option<Arc<T>>>>>>,
+}
+
+impl<T> ParamsCache<T> {
+    fn new() -> Self {
+        ParamsCache {
+            slots: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Return the cached parameters, or initialize them with `init` if they are not cached.
+    fn get_or_init<F: FnOnce() -> Result<T>>(&self, kind: ParamsKind, init: F) -> Result<Arc<T>> {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(kind)
+            .or_default()
+            .clone();
+
+        let mut slot = slot.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(params) = slot.as_ref() {
+            return Ok(params.clone());
+        }
+        let params = Arc::new(init().c(d!())?);
+        *slot = Some(params.clone());
+        Ok(params)
+    }
+
+    fn clear(&self) {
+        self.slots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+lazy_static! {
+    static ref PROVER_PARAMS_CACHE: ParamsCache<ProverParams> = ParamsCache::new();
+    static ref VERIFIER_PARAMS_CACHE: ParamsCache<VerifierParams> = ParamsCache::new();
+}
+
+/// Remove all the prover and verifier parameters from the cache.
+/// Parameters that are still in use are freed when they are dropped.
+pub fn clear_params_cache() {
+    PROVER_PARAMS_CACHE.clear();
+    VERIFIER_PARAMS_CACHE.clear();
+}
+
 impl BulletproofParams {
     /// Load the URS for Bulletproofs.
     pub fn new() -> Result<BulletproofParams> {
@@ -123,52 +267,58 @@ impl Default for BulletproofParams {
             bp_gens: range_generators,
             bp_circuit_gens: circuit_generators,
             range_proof_bits: BULLET_PROOF_RANGE,
+            range_proof_backend: RangeProofBackend::default(),
         }
     }
 }
 
-impl ProverParams {
-    /// Obtain the parameters for anonymous transfer for a given number of inputs and a given number of outputs.
-    pub fn new(
-        n_payers: usize,
-        n_payees: usize,
-        tree_depth: Option<usize>,
-    ) -> Result<ProverParams> {
-        let srs = SRS.c(d!(ZeiError::MissingSRSError))?;
+impl ParamsKind {
+    /// Build the constraint system of the circuit of this kind, with dummy witnesses.
+    pub fn build_cs(&self) -> Result<TurboPlonkCS> {
+        Ok(match *self {
+            ParamsKind::AnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth,
+            } => Self::anon_xfr_cs(n_payers, n_payees, tree_depth).c(d!())?,
+            ParamsKind::AuditableAnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth,
+            } => Self::auditable_anon_xfr_cs(n_payers, n_payees, tree_depth).c(d!())?,
+            ParamsKind::BarToAbar => Self::bar_to_abar_cs(),
+            ParamsKind::AbarToBar { tree_depth } => Self::abar_to_bar_cs(tree_depth),
+            ParamsKind::ArToAbar => Self::ar_to_abar_cs(),
+            ParamsKind::AbarToAr { tree_depth } => Self::abar_to_ar_cs(tree_depth),
+            ParamsKind::ExternalCommitment { kind } => Self::external_commitment_cs(kind),
+        })
+    }
 
+    fn anon_xfr_cs(n_payers: usize, n_payees: usize, tree_depth: usize) -> Result<TurboPlonkCS> {
         let folding_witness = AXfrAddressFoldingWitness::default();
 
-        let (cs, _) = match tree_depth {
-            Some(depth) => build_multi_xfr_cs(
-                AXfrWitness::fake(n_payers, n_payees, depth, 0),
-                FEE_TYPE.as_scalar(),
-                &folding_witness,
-            ),
-            None => build_multi_xfr_cs(
-                AXfrWitness::fake(n_payers, n_payees, TREE_DEPTH, 0),
-                FEE_TYPE.as_scalar(),
-                &folding_witness,
-            ),
-        };
-
-        let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&srs)
-            .c(d!(ZeiError::DeserializationError))?;
-
-        let lagrange_pcs = load_lagrange_params(cs.size());
+        let (cs, _) = build_multi_xfr_cs(
+            AXfrWitness::fake(n_payers, n_payees, tree_depth, 0),
+            FEE_TYPE.as_scalar(),
+            &folding_witness,
+        )?;
+        Ok(cs)
+    }
 
-        let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref()).unwrap();
+    fn auditable_anon_xfr_cs(
+        n_payers: usize,
+        n_payees: usize,
+        tree_depth: usize,
+    ) -> Result<TurboPlonkCS> {
+        let folding_witness = AXfrAddressFoldingWitness::default();
 
-        Ok(ProverParams {
-            pcs,
-            lagrange_pcs,
-            cs,
-            prover_params,
-        })
+        let mut witness = AXfrWitness::fake(n_payers, n_payees, tree_depth, 0);
+        witness.audit = Some(AXfrAuditWitness::fake(n_payees));
+        let (cs, _) = build_multi_xfr_cs(witness, FEE_TYPE.as_scalar(), &folding_witness)?;
+        Ok(cs)
     }
 
-    /// Obtain the parameters for confidential to anonymous.
-    pub fn bar_to_abar_params() -> Result<ProverParams> {
-        let srs = SRS.c(d!(ZeiError::MissingSRSError))?;
+    fn bar_to_abar_cs() -> TurboPlonkCS {
         let zero = BLSScalar::zero();
 
         let proof = DelegatedSchnorrProof::<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto> {
@@ -209,24 +359,10 @@ impl ProverParams {
             &beta,
             &lambda,
         );
-
-        let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&srs)
-            .c(d!(ZeiError::DeserializationError))?;
-
-        let lagrange_pcs = load_lagrange_params(cs.size());
-
-        let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref()).unwrap();
-
-        Ok(ProverParams {
-            pcs,
-            lagrange_pcs,
-            cs,
-            prover_params,
-        })
+        cs
     }
 
-    /// Obtain the parameters for anonymous to confidential.
-    pub fn abar_to_bar_params(tree_depth: usize) -> Result<ProverParams> {
+    fn abar_to_bar_cs(tree_depth: usize) -> TurboPlonkCS {
         let bls_zero = BLSScalar::zero();
 
         let proof = DelegatedSchnorrProof::<RistrettoScalar, RistrettoPoint, SimFrParamsRistretto> {
@@ -282,24 +418,10 @@ impl ProverParams {
             &lambda,
             &folding_witness,
         );
-        let srs = SRS.c(d!(ZeiError::MissingSRSError))?;
-        let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&srs)
-            .c(d!(ZeiError::DeserializationError))?;
-
-        let lagrange_pcs = load_lagrange_params(cs.size());
-
-        let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref()).unwrap();
-
-        Ok(ProverParams {
-            pcs,
-            lagrange_pcs,
-            cs,
-            prover_params,
-        })
+        cs
     }
 
-    /// Obtain the parameters for transparent to anonymous.
-    pub fn ar_to_abar_params() -> Result<ProverParams> {
+    fn ar_to_abar_cs() -> TurboPlonkCS {
         let bls_zero = BLSScalar::zero();
 
         // It's okay to choose a fixed seed to build CS.
@@ -313,25 +435,10 @@ impl ProverParams {
         };
 
         let (cs, _) = build_ar_to_abar_cs(dummy_payee);
-
-        let srs = SRS.c(d!(ZeiError::MissingSRSError))?;
-        let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&srs)
-            .c(d!(ZeiError::DeserializationError))?;
-
-        let lagrange_pcs = load_lagrange_params(cs.size());
-
-        let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref()).unwrap();
-
-        Ok(ProverParams {
-            pcs,
-            lagrange_pcs,
-            cs,
-            prover_params,
-        })
+        cs
     }
 
-    /// Obtain the parameters for anonymous to transparent.
-    pub fn abar_to_ar_params(tree_depth: usize) -> Result<ProverParams> {
+    fn abar_to_ar_cs(tree_depth: usize) -> TurboPlonkCS {
         let bls_zero = BLSScalar::zero();
 
         // It's okay to choose a fixed seed to build CS.
@@ -356,13 +463,153 @@ impl ProverParams {
         let folding_witness = AXfrAddressFoldingWitness::default();
 
         let (cs, _) = build_abar_to_ar_cs(payer_secret, &folding_witness);
+        cs
+    }
+
+    fn external_commitment_cs(kind: ExternalCommitmentKind) -> TurboPlonkCS {
+        let bls_zero = BLSScalar::zero();
 
+        // It's okay to choose a fixed seed to build CS.
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let keypair = AXfrKeyPair::generate(&mut prng);
+        let dummy_payee = PayeeWitness {
+            amount: 0,
+            blind: bls_zero,
+            asset_type: bls_zero,
+            public_key: keypair.get_public_key(),
+        };
+        let dummy_blind = match kind {
+            ExternalCommitmentKind::PedersenAmount => {
+                ExternalCommitmentBlind::Pedersen(JubjubScalar::zero())
+            }
+            _ => ExternalCommitmentBlind::Rescue(bls_zero),
+        };
+
+        let (cs, _) = build_external_commitment_cs(dummy_payee, &dummy_blind, kind).unwrap();
+        cs
+    }
+}
+
+impl ProverParams {
+    /// Obtain the parameters for anonymous transfer for a given number of inputs and a given number of outputs.
+    pub fn new(
+        n_payers: usize,
+        n_payees: usize,
+        tree_depth: Option<usize>,
+    ) -> Result<ProverParams> {
+        Self::from_cs(
+            ParamsKind::AnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth: tree_depth.unwrap_or(TREE_DEPTH),
+            }
+            .build_cs()?,
+        )
+    }
+
+    /// Obtain the parameters of a given kind from the global cache.
+    /// They are generated or loaded on the first request, and shared afterwards.
+    pub fn get_or_init(kind: ParamsKind) -> Result<Arc<ProverParams>> {
+        PROVER_PARAMS_CACHE.get_or_init(kind, || match kind {
+            ParamsKind::AnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth,
+            } => Self::new(n_payers, n_payees, Some(tree_depth)),
+            ParamsKind::AuditableAnonXfr { .. } => Self::from_cs(kind.build_cs()?),
+            ParamsKind::BarToAbar => Self::bar_to_abar_params(),
+            ParamsKind::AbarToBar { tree_depth } => Self::abar_to_bar_params(tree_depth),
+            ParamsKind::ArToAbar => Self::ar_to_abar_params(),
+            ParamsKind::AbarToAr { tree_depth } => Self::abar_to_ar_params(tree_depth),
+            ParamsKind::ExternalCommitment { kind } => Self::external_commitment_params(kind),
+        })
+    }
+
+    /// Load the prover parameters, including the SRS and its Lagrange basis format, from a reader.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<ProverParams> {
+        bincode::deserialize_from(reader).c(d!(ZeiError::DeserializationError))
+    }
+
+    /// Save the prover parameters, including the SRS and its Lagrange basis format, to a writer.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        bincode::serialize_into(writer, self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Obtain the parameters for confidential to anonymous.
+    pub fn bar_to_abar_params() -> Result<ProverParams> {
+        Self::from_cs(ParamsKind::BarToAbar.build_cs()?)
+    }
+
+    /// Obtain the parameters for anonymous to confidential.
+    pub fn abar_to_bar_params(tree_depth: usize) -> Result<ProverParams> {
+        Self::from_cs(ParamsKind::AbarToBar { tree_depth }.build_cs()?)
+    }
+
+    /// Obtain the parameters for transparent to anonymous.
+    pub fn ar_to_abar_params() -> Result<ProverParams> {
+        Self::from_cs(ParamsKind::ArToAbar.build_cs()?)
+    }
+
+    /// Obtain the parameters for anonymous to transparent.
+    pub fn abar_to_ar_params(tree_depth: usize) -> Result<ProverParams> {
+        Self::from_cs(ParamsKind::AbarToAr { tree_depth }.build_cs()?)
+    }
+
+    /// Obtain the parameters for linking an external commitment of a given kind.
+    pub fn external_commitment_params(kind: ExternalCommitmentKind) -> Result<ProverParams> {
+        Self::from_cs(ParamsKind::ExternalCommitment { kind }.build_cs()?)
+    }
+
+    /// Obtain the parameters of a given kind with the Lagrange basis format of the SRS supplied
+    /// by the caller, e.g., from a memory-mapped file, instead of the embedded one.
+    ///
+    /// The bytes are in the format of the embedded Lagrange bases, and they are only used after
+    /// their SHA-256 digest is checked against `expected_digest`, see `precomputed_params_digest`.
+    pub fn with_lagrange_bases(
+        kind: ParamsKind,
+        lagrange_bytes: &[u8],
+        expected_digest: &[u8; 32],
+    ) -> Result<ProverParams> {
+        check_digest(lagrange_bytes, expected_digest).c(d!())?;
+        let cs = kind.build_cs().c(d!())?;
+        let lagrange_pcs = parse_lagrange_bases(lagrange_bytes).c(d!())?;
+        if lagrange_pcs.public_parameter_group_1.len() != cs.size() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Self::from_cs_with_lagrange(cs, Some(lagrange_pcs))
+    }
+
+    /// Load the fully preprocessed prover parameters, including the FFT domains and the
+    /// evaluations over them, from bytes supplied by the caller, e.g., from a memory-mapped file
+    /// written by `save_to_writer`.
+    ///
+    /// The bytes are only deserialized after their SHA-256 digest is checked against
+    /// `expected_digest`, see `precomputed_params_digest`.
+    pub fn load_precomputed(bytes: &[u8], expected_digest: &[u8; 32]) -> Result<ProverParams> {
+        check_digest(bytes, expected_digest).c(d!())?;
+        Self::load_from_reader(bytes).c(d!())
+    }
+
+    /// Return the identifier of the parameters, which tags the proofs generated with them.
+    pub fn id(&self) -> Result<ParamsId> {
+        params_id(&self.pcs, self.prover_params.get_verifier_params_ref()).c(d!())
+    }
+
+    /// Preprocess the constraint system with the SRS.
+    fn from_cs(cs: TurboPlonkCS) -> Result<ProverParams> {
+        let lagrange_pcs = load_lagrange_params(cs.size());
+        Self::from_cs_with_lagrange(cs, lagrange_pcs)
+    }
+
+    /// Preprocess the constraint system with the SRS and, if any, its Lagrange basis format.
+    fn from_cs_with_lagrange(
+        cs: TurboPlonkCS,
+        lagrange_pcs: Option<KZGCommitmentSchemeBLS>,
+    ) -> Result<ProverParams> {
         let srs = SRS.c(d!(ZeiError::MissingSRSError))?;
         let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&srs)
             .c(d!(ZeiError::DeserializationError))?;
 
-        let lagrange_pcs = load_lagrange_params(cs.size());
-
         let prover_params = indexer_with_lagrange(&cs, &pcs, lagrange_pcs.as_ref()).unwrap();
 
         Ok(ProverParams {
@@ -377,24 +624,69 @@ impl ProverParams {
 fn load_lagrange_params(size: usize) -> Option<KZGCommitmentSchemeBLS> {
     match LAGRANGE_BASES.get(&size) {
         None => None,
-        Some(bytes) => {
-            let mut len_bytes = [0u8; 4];
-            len_bytes.copy_from_slice(&bytes[0..4]);
-            let len = u32::from_le_bytes(len_bytes) as usize;
+        Some(bytes) => parse_lagrange_bases(bytes).ok(),
+    }
+}
 
-            let n = BLSG1::unchecked_size();
+fn parse_lagrange_bases(bytes: &[u8]) -> Result<KZGCommitmentSchemeBLS> {
+    if bytes.len() < 4 {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    let mut len_bytes = [0u8; 4];
+    len_bytes.copy_from_slice(&bytes[0..4]);
+    let len = u32::from_le_bytes(len_bytes) as usize;
 
-            let mut v = vec![];
-            for i in 0..len {
-                v.push(BLSG1::from_unchecked_bytes(&bytes[(4 + n * i)..(4 + n * (i + 1))]).ok()?);
-            }
+    let n = BLSG1::unchecked_size();
+    if bytes.len() != 4 + n * len {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
 
-            Some(KZGCommitmentSchemeBLS {
-                public_parameter_group_1: v,
-                public_parameter_group_2: vec![],
-            })
-        }
+    let mut v = vec![];
+    for i in 0..len {
+        v.push(
+            BLSG1::from_unchecked_bytes(&bytes[(4 + n * i)..(4 + n * (i + 1))])
+                .c(d!(ZeiError::DeserializationError))?,
+        );
     }
+
+    Ok(KZGCommitmentSchemeBLS {
+        public_parameter_group_1: v,
+        public_parameter_group_2: vec![],
+    })
+}
+
+/// Compute the SHA-256 digest of precomputed parameters, to be published along with them
+/// and checked by `ProverParams::with_lagrange_bases` and `ProverParams::load_precomputed`.
+pub fn precomputed_params_digest(bytes: &[u8]) -> [u8; 32] {
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&Sha256::digest(bytes));
+    digest
+}
+
+fn params_id(
+    pcs: &KZGCommitmentSchemeBLS,
+    vk: &PlonkVK<KZGCommitmentSchemeBLS>,
+) -> Result<ParamsId> {
+    let mut hasher = Sha256::new();
+    hasher.update(PARAMS_ID_DOMAIN);
+    for g1 in pcs.public_parameter_group_1.iter().take(1) {
+        hasher.update(g1.to_compressed_bytes());
+    }
+    for g2 in pcs.public_parameter_group_2.iter().take(2) {
+        hasher.update(g2.to_compressed_bytes());
+    }
+    hasher.update(bincode::serialize(vk).c(d!(ZeiError::SerializationError))?);
+
+    let mut id = [0u8; 32];
+    id.copy_from_slice(&hasher.finalize());
+    Ok(ParamsId(id))
+}
+
+fn check_digest(bytes: &[u8], expected_digest: &[u8; 32]) -> Result<()> {
+    if precomputed_params_digest(bytes) != *expected_digest {
+        return Err(eg!(ZeiError::PrecomputedParamsDigestError));
+    }
+    Ok(())
 }
 
 impl VerifierParams {
@@ -409,36 +701,99 @@ impl VerifierParams {
     }
 
     /// Load the verifier parameters for a given number of inputs and a given number of outputs.
+    ///
+    /// The shrunk parameters of all the sizes from 1x1 to 6x6 are embedded into the binary,
+    /// unless the `no_vk` feature is enabled, in which case they are generated and shrunk here.
     pub fn load(n_payers: usize, n_payees: usize) -> Result<VerifierParams> {
-        if n_payees > MAX_ANONYMOUS_RECORD_NUMBER || n_payers > MAX_ANONYMOUS_RECORD_NUMBER {
+        if n_payers == 0
+            || n_payees == 0
+            || n_payees > MAX_ANONYMOUS_RECORD_NUMBER
+            || n_payers > MAX_ANONYMOUS_RECORD_NUMBER
+        {
             Err(SimpleError::new(d!(ZeiError::MissingVerifierParamsError), None).into())
         } else {
             match (VERIFIER_COMMON_PARAMS, VERIFIER_SPECIFIC_PARAMS) {
                 (Some(c_bytes), Some(s_bytes)) => {
                     let common: VerifierParamsSplitCommon =
                         bincode::deserialize(c_bytes).c(d!(ZeiError::DeserializationError))?;
-                    let specials: Vec<Vec<Vec<u8>>> = bincode::deserialize(s_bytes).unwrap();
-                    let special: VerifierParamsSplitSpecific =
-                        bincode::deserialize(&specials[n_payers - 1][n_payees - 1])
-                            .c(d!(ZeiError::DeserializationError))?;
+                    let specials: Vec<Vec<Vec<u8>>> =
+                        bincode::deserialize(s_bytes).c(d!(ZeiError::DeserializationError))?;
+                    let special_bytes = specials
+                        .get(n_payers - 1)
+                        .and_then(|v| v.get(n_payees - 1))
+                        .ok_or(eg!(ZeiError::MissingVerifierParamsError))?;
+                    let special: VerifierParamsSplitSpecific = bincode::deserialize(special_bytes)
+                        .c(d!(ZeiError::DeserializationError))?;
                     Ok(VerifierParams {
                         pcs: common.pcs,
                         cs: special.cs,
                         verifier_params: special.verifier_params,
+                        id: OnceLock::new(),
                     })
                 }
-                _ => Self::create(n_payers, n_payees, None),
+                _ => Self::create(n_payers, n_payees, None)?.shrink(),
             }
         }
     }
 
+    /// Obtain the parameters of a given kind from the global cache.
+    /// They are loaded or generated on the first request, and shared afterwards.
+    /// The embedded parameters are used when they are available for this kind.
+    pub fn get_or_init(kind: ParamsKind) -> Result<Arc<VerifierParams>> {
+        VERIFIER_PARAMS_CACHE.get_or_init(kind, || match kind {
+            ParamsKind::AnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth,
+            } => {
+                if tree_depth == TREE_DEPTH {
+                    Self::load(n_payers, n_payees)
+                } else {
+                    Self::create(n_payers, n_payees, Some(tree_depth))
+                }
+            }
+            ParamsKind::AuditableAnonXfr { .. } => {
+                VerifierParams::from(ProverParams::from_cs(kind.build_cs()?)?).shrink()
+            }
+            ParamsKind::BarToAbar => Self::bar_to_abar_params(),
+            ParamsKind::AbarToBar { tree_depth } => {
+                if tree_depth == TREE_DEPTH {
+                    Self::abar_to_bar_params()
+                } else {
+                    Ok(Self::from(ProverParams::abar_to_bar_params(tree_depth)?))
+                }
+            }
+            ParamsKind::ArToAbar => Self::ar_to_abar_params(),
+            ParamsKind::AbarToAr { tree_depth } => {
+                if tree_depth == TREE_DEPTH {
+                    Self::abar_to_ar_params()
+                } else {
+                    Ok(Self::from(ProverParams::abar_to_ar_params(tree_depth)?))
+                }
+            }
+            ParamsKind::ExternalCommitment { kind } => {
+                VerifierParams::from(ProverParams::external_commitment_params(kind)?).shrink()
+            }
+        })
+    }
+
+    /// Load the verifier parameters from a reader.
+    pub fn load_from_reader<R: Read>(reader: R) -> Result<VerifierParams> {
+        bincode::deserialize_from(reader).c(d!(ZeiError::DeserializationError))
+    }
+
+    /// Save the verifier parameters to a writer.
+    pub fn save_to_writer<W: Write>(&self, writer: W) -> Result<()> {
+        bincode::serialize_into(writer, self).c(d!(ZeiError::SerializationError))
+    }
+
     /// Obtain the parameters for anonymous to confidential.
     pub fn abar_to_bar_params() -> Result<VerifierParams> {
         if let Some(bytes) = ABAR_TO_BAR_VERIFIER_PARAMS {
             bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
         } else {
             let prover_params = ProverParams::abar_to_bar_params(TREE_DEPTH)?;
-            Ok(VerifierParams::from(prover_params))
+            VerifierParams::from(prover_params).shrink()
         }
     }
 
@@ -448,7 +803,7 @@ impl VerifierParams {
             bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
         } else {
             let prover_params = ProverParams::bar_to_abar_params()?;
-            Ok(VerifierParams::from(prover_params))
+            VerifierParams::from(prover_params).shrink()
         }
     }
 
@@ -458,7 +813,7 @@ impl VerifierParams {
             bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
         } else {
             let prover_params = ProverParams::ar_to_abar_params()?;
-            Ok(VerifierParams::from(prover_params))
+            VerifierParams::from(prover_params).shrink()
         }
     }
 
@@ -468,16 +823,51 @@ impl VerifierParams {
             bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))
         } else {
             let prover_params = ProverParams::abar_to_ar_params(TREE_DEPTH)?;
-            Ok(VerifierParams::from(prover_params))
+            VerifierParams::from(prover_params).shrink()
+        }
+    }
+
+    /// Return the identifier of the parameters, which is the same as that of the prover
+    /// parameters they come from. It is computed once, and cached.
+    pub fn id(&self) -> Result<ParamsId> {
+        if let Some(id) = self.id.get() {
+            return Ok(*id);
+        }
+        let id = params_id(&self.pcs, &self.verifier_params).c(d!())?;
+        Ok(*self.id.get_or_init(|| id))
+    }
+
+    /// Return the shrunk version of the polynomial commitment scheme.
+    pub fn pcs(&self) -> &KZGCommitmentSchemeBLS {
+        &self.pcs
+    }
+
+    /// Return the shrunk version of the constraint system.
+    pub fn cs(&self) -> &TurboPlonkCS {
+        &self.cs
+    }
+
+    /// Return the TurboPlonk verifying key.
+    pub fn verifier_params(&self) -> &PlonkVK<KZGCommitmentSchemeBLS> {
+        &self.verifier_params
+    }
+
+    /// Check that a proof tagged with `params_id` was generated with these parameters.
+    pub fn check_id(&self, params_id: &ParamsId) -> Result<()> {
+        if *params_id != self.id().c(d!())? {
+            return Err(eg!(ZeiError::ParamsIdMismatchError));
         }
+        Ok(())
     }
 
-    /// Shrink the verifier parameters.
+    /// Shrink the verifier parameters to the minimal verifying key,
+    /// which drops the SRS powers and the constraint system data that verification does not use.
     pub fn shrink(self) -> Result<VerifierParams> {
         Ok(VerifierParams {
             pcs: self.pcs.shrink_to_verifier_only()?,
             cs: self.cs.shrink_to_verifier_only()?,
             verifier_params: self.verifier_params,
+            id: self.id,
         })
     }
 
@@ -501,6 +891,7 @@ impl From<ProverParams> for VerifierParams {
             pcs: params.pcs,
             cs: params.cs,
             verifier_params: params.prover_params.get_verifier_params(),
+            id: OnceLock::new(),
         }
     }
 }
@@ -508,14 +899,22 @@ impl From<ProverParams> for VerifierParams {
 #[cfg(test)]
 mod test {
     use crate::anon_xfr::TREE_DEPTH;
-    use crate::parameters::SRS;
-    use crate::setup::{ProverParams, VerifierParams};
+    use crate::parameters::{LAGRANGE_BASES, SRS};
+    use crate::setup::{
+        clear_params_cache, precomputed_params_digest, ParamsId, ParamsKind, ProverParams,
+        VerifierParams, MAX_ANONYMOUS_RECORD_NUMBER,
+    };
+    use std::sync::Arc;
     use zei_algebra::{
         bls12_381::{BLSScalar, BLSG1},
         prelude::*,
     };
-    use zei_plonk::poly_commit::{
-        field_polynomial::FpPolynomial, kzg_poly_com::KZGCommitmentSchemeBLS, pcs::PolyComScheme,
+    use zei_plonk::{
+        plonk::constraint_system::ConstraintSystem,
+        poly_commit::{
+            field_polynomial::FpPolynomial, kzg_poly_com::KZGCommitmentSchemeBLS,
+            pcs::PolyComScheme,
+        },
     };
 
     #[test]
@@ -528,6 +927,124 @@ mod test {
         assert_eq!(v, v2);
     }
 
+    #[test]
+    fn test_params_cache() {
+        let kind = ParamsKind::AnonXfr {
+            n_payers: 1,
+            n_payees: 1,
+            tree_depth: 1,
+        };
+        let params = ProverParams::get_or_init(kind).unwrap();
+        assert!(Arc::ptr_eq(
+            &params,
+            &ProverParams::get_or_init(kind).unwrap()
+        ));
+
+        let handles = (0..4)
+            .map(|_| std::thread::spawn(move || ProverParams::get_or_init(kind).unwrap()))
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert!(Arc::ptr_eq(&params, &handle.join().unwrap()));
+        }
+
+        let verifier_params = VerifierParams::get_or_init(kind).unwrap();
+        assert!(Arc::ptr_eq(
+            &verifier_params,
+            &VerifierParams::get_or_init(kind).unwrap()
+        ));
+
+        clear_params_cache();
+        assert!(!Arc::ptr_eq(
+            &params,
+            &ProverParams::get_or_init(kind).unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_params_reader_writer() {
+        let params = ProverParams::new(1, 1, Some(1)).unwrap();
+        let mut bytes = vec![];
+        params.save_to_writer(&mut bytes).unwrap();
+        let params_de = ProverParams::load_from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(bincode::serialize(&params_de).unwrap(), bytes);
+
+        let verifier_params = VerifierParams::from(params_de);
+        let mut bytes = vec![];
+        verifier_params.save_to_writer(&mut bytes).unwrap();
+        let verifier_params_de = VerifierParams::load_from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(bincode::serialize(&verifier_params_de).unwrap(), bytes);
+        assert!(ProverParams::load_from_reader(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn test_precomputed_params() {
+        let params = ProverParams::new(1, 1, Some(1)).unwrap();
+        let mut bytes = vec![];
+        params.save_to_writer(&mut bytes).unwrap();
+        let digest = precomputed_params_digest(&bytes);
+
+        let params_de = ProverParams::load_precomputed(&bytes, &digest).unwrap();
+        assert_eq!(bincode::serialize(&params_de).unwrap(), bytes);
+        bytes[0] ^= 1;
+        msg_eq!(
+            ZeiError::PrecomputedParamsDigestError,
+            ProverParams::load_precomputed(&bytes, &digest).unwrap_err()
+        );
+
+        let kind = ParamsKind::AnonXfr {
+            n_payers: 1,
+            n_payees: 1,
+            tree_depth: TREE_DEPTH,
+        };
+        let size = kind.build_cs().unwrap().size();
+        if let Some(lagrange_bytes) = LAGRANGE_BASES.get(&size) {
+            let digest = precomputed_params_digest(lagrange_bytes);
+            let params = ProverParams::with_lagrange_bases(kind, lagrange_bytes, &digest).unwrap();
+            assert_eq!(
+                bincode::serialize(&params).unwrap(),
+                bincode::serialize(&ProverParams::new(1, 1, None).unwrap()).unwrap()
+            );
+            assert!(ProverParams::with_lagrange_bases(kind, lagrange_bytes, &[0u8; 32]).is_err());
+        }
+
+        // the Lagrange bases must match the size of the constraint system
+        let lagrange_bytes = 0u32.to_le_bytes();
+        let digest = precomputed_params_digest(&lagrange_bytes);
+        msg_eq!(
+            ZeiError::ParameterError,
+            ProverParams::with_lagrange_bases(kind, &lagrange_bytes, &digest).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_params_id() {
+        let params = ProverParams::new(1, 1, Some(1)).unwrap();
+        let id = params.id().unwrap();
+        let verifier_params = VerifierParams::from(params);
+        assert_eq!(verifier_params.id().unwrap(), id);
+        let verifier_params = verifier_params.shrink().unwrap();
+        assert_eq!(verifier_params.id().unwrap(), id);
+
+        // the identifier changes with the circuit
+        let other = ProverParams::new(1, 2, Some(1)).unwrap();
+        assert_ne!(other.id().unwrap(), id);
+
+        assert!(verifier_params.check_id(&id).is_ok());
+        msg_eq!(
+            ZeiError::ParamsIdMismatchError,
+            verifier_params.check_id(&other.id().unwrap()).unwrap_err()
+        );
+        msg_eq!(
+            ZeiError::ParamsIdMismatchError,
+            verifier_params.check_id(&ParamsId::default()).unwrap_err()
+        );
+
+        // the identifier is not serialized, and it is recomputed after deserialization
+        let bytes = bincode::serialize(&verifier_params).unwrap();
+        let verifier_params: VerifierParams = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(verifier_params.id().unwrap(), id);
+    }
+
     #[test]
     fn test_vk_params_serialization() {
         let params = VerifierParams::create(3, 3, Some(TREE_DEPTH))
@@ -540,6 +1057,20 @@ mod test {
         assert_eq!(v, v2);
     }
 
+    #[test]
+    fn test_vk_params_shrink() {
+        let params = VerifierParams::create(1, 1, Some(1)).unwrap();
+        let size = bincode::serialize(&params).unwrap().len();
+        let shrunk = params.shrink().unwrap();
+        let v = bincode::serialize(&shrunk).unwrap();
+        assert!(v.len() < size);
+        assert_eq!(bincode::serialize(&shrunk.shrink().unwrap()).unwrap(), v);
+
+        assert!(VerifierParams::load(0, 1).is_err());
+        assert!(VerifierParams::load(1, 0).is_err());
+        assert!(VerifierParams::load(MAX_ANONYMOUS_RECORD_NUMBER + 1, 1).is_err());
+    }
+
     #[test]
     fn test_crs_commit() {
         let pcs = KZGCommitmentSchemeBLS::from_unchecked_bytes(&SRS.unwrap()).unwrap();
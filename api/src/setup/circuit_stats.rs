@@ -0,0 +1,169 @@
+use crate::anon_xfr::TREE_DEPTH;
+use crate::setup::{ParamsKind, MAX_ANONYMOUS_RECORD_NUMBER};
+use std::time::{Duration, Instant};
+use zei_algebra::{
+    bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN, BLSG1},
+    prelude::*,
+};
+use zei_plonk::plonk::constraint_system::{ConstraintSystem, TurboCS};
+
+/// The number of points in the multi-scalar multiplication timed by the calibration.
+pub const CALIBRATION_MSM_SIZE: usize = 256;
+
+/// The timing of the current machine, used to estimate the proving time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProvingCalibration {
+    /// The time of a multi-scalar multiplication in G1, per point.
+    pub msm_time_per_point: Duration,
+}
+
+impl ProvingCalibration {
+    /// Measure the time of a small multi-scalar multiplication in G1 on the current machine.
+    pub fn measure<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        let scalars = (0..CALIBRATION_MSM_SIZE)
+            .map(|_| BLSScalar::random(prng))
+            .collect_vec();
+        let points = (0..CALIBRATION_MSM_SIZE)
+            .map(|_| BLSG1::random(prng))
+            .collect_vec();
+        let scalars_ref = scalars.iter().collect_vec();
+        let points_ref = points.iter().collect_vec();
+
+        let start = Instant::now();
+        let _ = BLSG1::multi_exp(&scalars_ref, &points_ref);
+        ProvingCalibration {
+            msm_time_per_point: start.elapsed() / CALIBRATION_MSM_SIZE as u32,
+        }
+    }
+}
+
+/// The size and the cost of the circuit of a kind of parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The kind of the circuit.
+    pub kind: ParamsKind,
+    /// The number of constraints, padded to a power of two.
+    pub num_constraints: usize,
+    /// The minimal degree of the SRS to prove the circuit.
+    pub srs_degree: usize,
+    /// The size of a proof in bytes.
+    pub proof_size: usize,
+}
+
+impl CircuitStats {
+    /// Build the circuit of the given kind and compute its statistics.
+    pub fn new(kind: ParamsKind) -> Result<Self> {
+        let cs = kind.build_cs().c(d!())?;
+        let num_constraints = cs.size();
+        let n_wires_per_gate = TurboCS::<BLSScalar>::n_wires_per_gate();
+
+        // the witness and quotient polynomials, z(X), and the two opening proofs
+        let num_commitments = 2 * n_wires_per_gate + 3;
+        // the witness and permutation polynomials at \zeta, and z(X) at \zeta\omega
+        let num_evaluations = 2 * n_wires_per_gate;
+        let commitment_len = BLSG1::get_base().to_compressed_bytes().len();
+
+        Ok(CircuitStats {
+            kind,
+            num_constraints,
+            srs_degree: num_constraints + 2,
+            proof_size: num_commitments * commitment_len + num_evaluations * BLS12_381_SCALAR_LEN,
+        })
+    }
+
+    /// Return the total number of points in the multi-scalar multiplications of the prover,
+    /// which dominate the proving time.
+    pub fn num_msm_points(&self) -> usize {
+        let n_wires_per_gate = TurboCS::<BLSScalar>::n_wires_per_gate();
+        (2 * n_wires_per_gate + 3) * (self.srs_degree + 1)
+    }
+
+    /// Roughly estimate the proving time from the timing of the current machine.
+    /// This ignores the FFTs and the witness generation, so it is a lower bound in practice.
+    pub fn estimated_proving_time(&self, calibration: &ProvingCalibration) -> Duration {
+        calibration.msm_time_per_point * self.num_msm_points() as u32
+    }
+}
+
+/// Return the kinds of the circuits with built-in parameters: the conversions, and the
+/// anonymous transfers with up to `MAX_ANONYMOUS_RECORD_NUMBER` inputs and outputs.
+pub fn supported_circuits() -> Vec<ParamsKind> {
+    let mut kinds = vec![
+        ParamsKind::ArToAbar,
+        ParamsKind::AbarToAr {
+            tree_depth: TREE_DEPTH,
+        },
+        ParamsKind::BarToAbar,
+        ParamsKind::AbarToBar {
+            tree_depth: TREE_DEPTH,
+        },
+    ];
+    for n_payers in 1..=MAX_ANONYMOUS_RECORD_NUMBER {
+        for n_payees in 1..=MAX_ANONYMOUS_RECORD_NUMBER {
+            kinds.push(ParamsKind::AnonXfr {
+                n_payers,
+                n_payees,
+                tree_depth: TREE_DEPTH,
+            });
+        }
+    }
+    kinds
+}
+
+/// Compute the statistics of all the circuits with built-in parameters.
+pub fn all_circuit_stats() -> Result<Vec<CircuitStats>> {
+    supported_circuits()
+        .into_iter()
+        .map(CircuitStats::new)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::anon_xfr::TREE_DEPTH;
+    use crate::setup::{
+        circuit_stats::{supported_circuits, CircuitStats, ProvingCalibration},
+        ParamsKind, MAX_ANONYMOUS_RECORD_NUMBER,
+    };
+    use ark_std::test_rng;
+    use std::time::Duration;
+
+    #[test]
+    fn circuit_stats() {
+        assert_eq!(
+            supported_circuits().len(),
+            4 + MAX_ANONYMOUS_RECORD_NUMBER * MAX_ANONYMOUS_RECORD_NUMBER
+        );
+
+        let small = CircuitStats::new(ParamsKind::AnonXfr {
+            n_payers: 1,
+            n_payees: 1,
+            tree_depth: TREE_DEPTH,
+        })
+        .unwrap();
+        let large = CircuitStats::new(ParamsKind::AnonXfr {
+            n_payers: 2,
+            n_payees: 1,
+            tree_depth: TREE_DEPTH,
+        })
+        .unwrap();
+        assert!(small.num_constraints.is_power_of_two());
+        assert!(small.num_constraints <= large.num_constraints);
+        assert_eq!(small.srs_degree, small.num_constraints + 2);
+        // the proof size does not depend on the circuit
+        assert_eq!(small.proof_size, large.proof_size);
+
+        // 13 compressed points in G1 and 10 scalars with 5 wires per gate
+        assert_eq!(small.proof_size, 13 * 48 + 10 * 32);
+
+        let calibration = ProvingCalibration {
+            msm_time_per_point: Duration::from_micros(10),
+        };
+        assert!(
+            small.estimated_proving_time(&calibration)
+                <= large.estimated_proving_time(&calibration)
+        );
+        let measured = ProvingCalibration::measure(&mut test_rng());
+        assert!(measured.msm_time_per_point > Duration::ZERO);
+    }
+}
@@ -0,0 +1,322 @@
+use crate::errors::ZeiError;
+use sha2::{Digest, Sha512};
+use zei_algebra::{
+    bls12_381::{BLSGt, BLSPairingEngine, BLSScalar, BLSG1, BLSG2},
+    prelude::*,
+    traits::Pairing,
+};
+use zei_plonk::poly_commit::kzg_poly_com::KZGCommitmentSchemeBLS;
+
+/// The domain separator of the SRS ceremony.
+const SRS_CEREMONY_DOMAIN: &[u8] = b"Zei SRS Ceremony";
+
+/// A contribution to the SRS ceremony.
+///
+/// A contributor samples a secret `s` and multiplies the `i`-th power of the SRS by `s^i`,
+/// so the trapdoor of the SRS becomes `tau * s`. The contribution records `s` in both groups
+/// together with a proof of knowledge of `s`, and the new first powers of the SRS.
+/// The trapdoor remains unknown as long as one contributor forgets its secret.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SRSContribution {
+    /// The hash of the previous contribution, or of the ceremony parameters for the first one.
+    pub prev_hash: Vec<u8>,
+    /// The secret of the contributor times the generator of G1.
+    pub s_g1: BLSG1,
+    /// The secret of the contributor times the generator of G2.
+    pub s_g2: BLSG2,
+    /// The first power of the SRS in G1 after this contribution.
+    pub tau_g1: BLSG1,
+    /// The first power of the SRS in G2 after this contribution.
+    pub tau_g2: BLSG2,
+    /// The commitment of the Schnorr proof of knowledge of the secret.
+    pub pok_commitment: BLSG1,
+    /// The response of the Schnorr proof of knowledge of the secret.
+    pub pok_response: BLSScalar,
+}
+
+impl SRSContribution {
+    /// Compute the hash of this contribution, which the next contribution is chained to.
+    pub fn hash(&self) -> Vec<u8> {
+        let mut hasher = Sha512::new();
+        hasher.update(SRS_CEREMONY_DOMAIN);
+        hasher.update(&self.prev_hash);
+        hasher.update(&self.s_g1.to_compressed_bytes());
+        hasher.update(&self.s_g2.to_compressed_bytes());
+        hasher.update(&self.tau_g1.to_compressed_bytes());
+        hasher.update(&self.tau_g2.to_compressed_bytes());
+        hasher.update(&self.pok_commitment.to_compressed_bytes());
+        hasher.update(&self.pok_response.to_bytes());
+        hasher.finalize().to_vec()
+    }
+
+    /// Compute the challenge of the proof of knowledge, bound to the previous contribution.
+    fn pok_challenge(
+        prev_hash: &[u8],
+        s_g1: &BLSG1,
+        s_g2: &BLSG2,
+        pok_commitment: &BLSG1,
+    ) -> BLSScalar {
+        let mut hasher = Sha512::new();
+        hasher.update(SRS_CEREMONY_DOMAIN);
+        hasher.update(b"proof of knowledge");
+        hasher.update(prev_hash);
+        hasher.update(&s_g1.to_compressed_bytes());
+        hasher.update(&s_g2.to_compressed_bytes());
+        hasher.update(&pok_commitment.to_compressed_bytes());
+        BLSScalar::from_hash(hasher)
+    }
+}
+
+/// A multi-party powers-of-tau ceremony for the SRS of the KZG polynomial commitment scheme.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SRSCeremony {
+    /// The maximal degree of the polynomials supported by the SRS.
+    pub max_degree: usize,
+    /// The current SRS.
+    pub srs: KZGCommitmentSchemeBLS,
+    /// The chain of contributions to the SRS.
+    pub contributions: Vec<SRSContribution>,
+}
+
+impl SRSCeremony {
+    /// Start a ceremony with the trapdoor `tau = 1`, which is publicly known.
+    /// At least one contribution is needed before the SRS can be used.
+    pub fn new(max_degree: usize) -> Result<Self> {
+        if max_degree == 0 {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let srs = KZGCommitmentSchemeBLS {
+            public_parameter_group_1: vec![BLSG1::get_base(); max_degree + 1],
+            public_parameter_group_2: vec![BLSG2::get_base(); 2],
+        };
+        Ok(SRSCeremony {
+            max_degree,
+            srs,
+            contributions: vec![],
+        })
+    }
+
+    /// The hash that the next contribution is chained to.
+    pub fn last_hash(&self) -> Vec<u8> {
+        match self.contributions.last() {
+            Some(contribution) => contribution.hash(),
+            None => {
+                let mut hasher = Sha512::new();
+                hasher.update(SRS_CEREMONY_DOMAIN);
+                hasher.update(&(self.max_degree as u64).to_le_bytes());
+                hasher.finalize().to_vec()
+            }
+        }
+    }
+
+    /// Add a contribution with fresh randomness to the SRS.
+    /// The contributor should verify the ceremony first, and erase `prng` afterwards.
+    pub fn contribute<R: CryptoRng + RngCore>(&mut self, prng: &mut R) -> Result<&SRSContribution> {
+        let s = BLSScalar::random(prng);
+        if s.is_zero() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let mut power = BLSScalar::one();
+        for elem in self.srs.public_parameter_group_1.iter_mut() {
+            *elem = elem.mul(&power);
+            power.mul_assign(&s);
+        }
+        self.srs.public_parameter_group_2[1] = self.srs.public_parameter_group_2[1].mul(&s);
+
+        let prev_hash = self.last_hash();
+        let s_g1 = BLSG1::get_base().mul(&s);
+        let s_g2 = BLSG2::get_base().mul(&s);
+        let k = BLSScalar::random(prng);
+        let pok_commitment = BLSG1::get_base().mul(&k);
+        let challenge = SRSContribution::pok_challenge(&prev_hash, &s_g1, &s_g2, &pok_commitment);
+
+        self.contributions.push(SRSContribution {
+            prev_hash,
+            s_g1,
+            s_g2,
+            tau_g1: self.srs.public_parameter_group_1[1],
+            tau_g2: self.srs.public_parameter_group_2[1],
+            pok_commitment,
+            pok_response: k.add(&challenge.mul(&s)),
+        });
+        Ok(self.contributions.last().unwrap())
+    }
+
+    /// Verify the chain of contributions and the structure of the resulting SRS.
+    pub fn verify<R: CryptoRng + RngCore>(&self, prng: &mut R) -> Result<()> {
+        if self.contributions.is_empty() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let g1 = BLSG1::get_base();
+        let g2 = BLSG2::get_base();
+        // check that e(a1, a2) = e(b1, b2), with a single final exponentiation
+        let pairings_equal = |a1: &BLSG1, a2: &BLSG2, b1: &BLSG1, b2: &BLSG2| {
+            BLSPairingEngine::multi_pairing(&[(*a1, *a2), (b1.neg(), *b2)]) == BLSGt::get_identity()
+        };
+
+        // 1. verify the chain of contributions
+        let mut prev_hash = SRSCeremony::new(self.max_degree).c(d!())?.last_hash();
+        let mut prev_tau_g1 = g1;
+        for contribution in self.contributions.iter() {
+            if contribution.prev_hash != prev_hash
+                || contribution.s_g1 == BLSG1::get_identity()
+                || contribution.tau_g1 == BLSG1::get_identity()
+            {
+                return Err(eg!(ZeiError::InconsistentStructureError));
+            }
+
+            // the proof of knowledge of `s`
+            let challenge = SRSContribution::pok_challenge(
+                &prev_hash,
+                &contribution.s_g1,
+                &contribution.s_g2,
+                &contribution.pok_commitment,
+            );
+            if g1.mul(&contribution.pok_response)
+                != contribution
+                    .pok_commitment
+                    .add(&contribution.s_g1.mul(&challenge))
+            {
+                return Err(eg!(ZeiError::ArgumentVerificationError));
+            }
+
+            // `s` is the same in both groups, the new `tau` is the old `tau` times `s`,
+            // and the new `tau` is the same in both groups
+            if !pairings_equal(&contribution.s_g1, &g2, &g1, &contribution.s_g2)
+                || !pairings_equal(&contribution.tau_g1, &g2, &prev_tau_g1, &contribution.s_g2)
+                || !pairings_equal(&contribution.tau_g1, &g2, &g1, &contribution.tau_g2)
+            {
+                return Err(eg!(ZeiError::ArgumentVerificationError));
+            }
+
+            prev_hash = contribution.hash();
+            prev_tau_g1 = contribution.tau_g1;
+        }
+
+        // 2. verify that the SRS consists of the powers of the last `tau`
+        let powers_g1 = &self.srs.public_parameter_group_1;
+        let powers_g2 = &self.srs.public_parameter_group_2;
+        let last = self.contributions.last().unwrap();
+        if powers_g1.len() != self.max_degree + 1
+            || powers_g2.len() != 2
+            || powers_g1[0] != g1
+            || powers_g2[0] != g2
+            || powers_g1[1] != last.tau_g1
+            || powers_g2[1] != last.tau_g2
+        {
+            return Err(eg!(ZeiError::InconsistentStructureError));
+        }
+
+        // e(sum r_i * g1[i + 1], g2) == e(sum r_i * g1[i], tau * g2) for random r_i
+        let randomizers = (0..self.max_degree)
+            .map(|_| BLSScalar::random(prng))
+            .collect::<Vec<BLSScalar>>();
+        let randomizers_ref = randomizers.iter().collect::<Vec<&BLSScalar>>();
+        let lhs = BLSG1::multi_exp(
+            &randomizers_ref,
+            &powers_g1[1..].iter().collect::<Vec<&BLSG1>>(),
+        );
+        let rhs = BLSG1::multi_exp(
+            &randomizers_ref,
+            &powers_g1[..self.max_degree].iter().collect::<Vec<&BLSG1>>(),
+        );
+        if !pairings_equal(&lhs, &g2, &rhs, &powers_g2[1]) {
+            return Err(eg!(ZeiError::ArgumentVerificationError));
+        }
+
+        Ok(())
+    }
+
+    /// Finish the ceremony and return the SRS, after verifying the ceremony.
+    pub fn into_srs<R: CryptoRng + RngCore>(self, prng: &mut R) -> Result<KZGCommitmentSchemeBLS> {
+        self.verify(prng).c(d!())?;
+        Ok(self.srs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::srs::SRSCeremony;
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use zei_algebra::{
+        bls12_381::{BLSScalar, BLSG1},
+        prelude::*,
+    };
+    use zei_plonk::poly_commit::{field_polynomial::FpPolynomial, pcs::PolyComScheme};
+
+    #[test]
+    fn srs_ceremony() {
+        let mut prng = test_rng();
+        let mut ceremony = SRSCeremony::new(16).unwrap();
+        assert!(ceremony.verify(&mut prng).is_err());
+
+        for _ in 0..3 {
+            ceremony.contribute(&mut prng).unwrap();
+            assert!(ceremony.verify(&mut prng).is_ok());
+        }
+
+        let bytes = bincode::serialize(&ceremony).unwrap();
+        let ceremony_de: SRSCeremony = bincode::deserialize(&bytes).unwrap();
+        assert!(ceremony_de.verify(&mut prng).is_ok());
+
+        // the SRS can be used for commitments and openings
+        let srs = ceremony.into_srs(&mut prng).unwrap();
+        let poly = FpPolynomial::from_coefs(
+            (0..10)
+                .map(|_| BLSScalar::random(&mut prng))
+                .collect::<Vec<BLSScalar>>(),
+        );
+        let commitment = srs.commit(&poly).unwrap();
+        let point = BLSScalar::random(&mut prng);
+        let eval = poly.eval(&point);
+        let proof = srs
+            .prove(&mut Transcript::new(b"srs"), &poly, &point, 16)
+            .unwrap();
+        assert!(srs
+            .verify(
+                &mut Transcript::new(b"srs"),
+                &commitment,
+                16,
+                &point,
+                &eval,
+                &proof
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn srs_ceremony_tampering() {
+        let mut prng = test_rng();
+        let mut ceremony = SRSCeremony::new(8).unwrap();
+        ceremony.contribute(&mut prng).unwrap();
+        ceremony.contribute(&mut prng).unwrap();
+
+        // a power that is not consistent with the others
+        let original = ceremony.srs.public_parameter_group_1[5];
+        ceremony.srs.public_parameter_group_1[5] = BLSG1::random(&mut prng);
+        assert!(ceremony.verify(&mut prng).is_err());
+        ceremony.srs.public_parameter_group_1[5] = original;
+        assert!(ceremony.verify(&mut prng).is_ok());
+
+        // a broken hash chain
+        let mut broken = ceremony.contributions.clone();
+        broken[1].prev_hash[0] ^= 1;
+        let original = std::mem::replace(&mut ceremony.contributions, broken);
+        assert!(ceremony.verify(&mut prng).is_err());
+        ceremony.contributions = original;
+
+        // a contribution that replaces the SRS instead of building on it
+        let mut other = SRSCeremony::new(8).unwrap();
+        other.contribute(&mut prng).unwrap();
+        other.contribute(&mut prng).unwrap();
+        let mut mixed = ceremony.contributions.clone();
+        mixed[1] = other.contributions[1].clone();
+        mixed[1].prev_hash = ceremony.contributions[0].hash();
+        ceremony.contributions = mixed;
+        ceremony.srs = other.srs;
+        assert!(ceremony.verify(&mut prng).is_err());
+    }
+}
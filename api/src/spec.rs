@@ -0,0 +1,796 @@
+//! Machine-readable specifications of the protocols, extracted from the code.
+//!
+//! The transcript labels are the constants used by the provers and the verifiers, the structure
+//! of the Plonk proofs is read from the verifier parameters, and the fields of the notes are
+//! read from their `Deserialize` implementations, which list them in the order of the
+//! serialization. The extraction fails if the descriptions of the fields do not match them.
+
+use crate::anon_xfr::{
+    abar_to_abar::{
+        AXfrBody, AXfrNote, ANON_XFR_FOLDING_PROOF_TRANSCRIPT, ANON_XFR_PLONK_PROOF_TRANSCRIPT,
+        NEXT_OWNER_MEMO_LABEL, NEXT_OWNER_MEMO_POINT_LABEL, NO_NEXT_OWNER_MEMO_LABEL,
+        N_INPUTS_TRANSCRIPT, N_OUTPUTS_TRANSCRIPT,
+    },
+    abar_to_ar::{
+        AbarToArBody, AbarToArNote, ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT,
+        ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT,
+    },
+    abar_to_bar::{
+        AbarToBarBody, AbarToBarNote, ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT,
+        ABAR_TO_BAR_NULLIFIER_LABEL, ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT,
+    },
+    address_folding::ADDRESS_FOLDING_HASH_LABEL,
+    ar_to_abar::{ArToAbarBody, ArToAbarNote, AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT},
+    bar_to_abar::{
+        BarToAbarBody, BarToAbarNote, BAR_TO_ABAR_COMMITMENT_LABEL,
+        BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT,
+    },
+    ANON_XFR_BP_GENS_LEN, TREE_DEPTH,
+};
+use crate::setup::{ParamsKind, VerifierParams, BULLET_PROOF_RANGE, PARAMS_ID_LABEL};
+use crate::xfr::{
+    asset_mixer::ASSET_MIXING_PROOF_TRANSCRIPT,
+    proofs::{ASSET_EQUALITY_TRANSCRIPT, ASSET_TRACING_PROOFS_TRANSCRIPT, RANGE_PROOF_TRANSCRIPT},
+    structs::{TracerMemo, XfrBody, XfrNote},
+};
+use serde::de::{DeserializeOwned, Deserializer, Visitor};
+use std::fmt;
+use zei_algebra::prelude::*;
+use zei_plonk::plonk::constraint_system::ConstraintSystem;
+
+/// The version of the format of the protocol specifications.
+pub const SPEC_VERSION: u32 = 2;
+
+/// An operation on a transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranscriptOp {
+    /// A message appended under a label.
+    Message {
+        /// The label.
+        label: String,
+        /// What the message is.
+        content: String,
+    },
+    /// An integer appended under a label.
+    U64 {
+        /// The label.
+        label: String,
+        /// What the integer is.
+        content: String,
+    },
+    /// A sub-protocol that continues on the same transcript.
+    SubProtocol {
+        /// The name of the sub-protocol.
+        name: String,
+    },
+    /// Operations repeated for each element of a list, in order.
+    ForEach {
+        /// The list.
+        list: String,
+        /// The operations for each element.
+        ops: Vec<TranscriptOp>,
+    },
+}
+
+/// A Fiat-Shamir transcript, from its domain separator.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptSpec {
+    /// The domain separator that the transcript is created with.
+    pub domain: String,
+    /// The operations on the transcript before the proof is verified, in order.
+    pub ops: Vec<TranscriptOp>,
+}
+
+/// A proof system, with the structure of its verification equation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofSystem {
+    /// A TurboPlonk proof, whose structure is read from the verifier parameters.
+    Plonk {
+        /// The kind of the parameters.
+        params: ParamsKind,
+        /// The number of constraints.
+        n_constraints: usize,
+        /// The number of variables.
+        n_vars: usize,
+        /// The number of public inputs.
+        n_public_inputs: usize,
+    },
+    /// An address folding proof, i.e., a scalar multiplication proof over secq256k1
+    /// followed by a delegated Schnorr proof.
+    AddressFolding {
+        /// The number of Bulletproofs generators.
+        n_generators: usize,
+    },
+    /// A delegated Schnorr proof of the openings of Pedersen commitments over Ristretto.
+    DelegatedSchnorr {
+        /// The number of commitments.
+        n_commitments: usize,
+    },
+    /// A Bulletproofs or Bulletproofs+ range proof of the amounts, told apart by its encoding.
+    RangeProof {
+        /// The number of bits of each range, as the amounts are split in two halves.
+        n_bits: usize,
+    },
+    /// A Bulletproofs proof of asset mixing.
+    AssetMixing,
+    /// A Chaum-Pedersen proof that committed asset types are equal.
+    AssetEquality,
+    /// Pedersen-ElGamal equality proofs that the tracing ciphertexts match the commitments.
+    AssetTracing,
+}
+
+/// A step of the verification of a note.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VerificationStep {
+    /// A check on the public data of the note.
+    Check {
+        /// What is checked.
+        description: String,
+    },
+    /// A signature verification.
+    Signature {
+        /// The signer.
+        signer: String,
+        /// The signed message.
+        message: String,
+    },
+    /// A proof verification, with the transcript that the proof is verified on.
+    Proof {
+        /// The proof system.
+        system: ProofSystem,
+        /// The transcript.
+        transcript: TranscriptSpec,
+    },
+}
+
+/// A field of a serialized structure. Every field is mandatory, unless its description says
+/// otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    /// The name of the field.
+    pub name: String,
+    /// What the field is, and how it is encoded.
+    pub description: String,
+    /// The fields of the structure that the field holds, or holds a list of, if described.
+    pub fields: Vec<FieldSpec>,
+}
+
+/// The specification of a protocol: the fields of its notes, and the steps of their
+/// verification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolSpec {
+    /// The name of the protocol.
+    pub name: String,
+    /// The fields of a note, in the order of the serialization.
+    pub fields: Vec<FieldSpec>,
+    /// The verification steps, in order.
+    pub steps: Vec<VerificationStep>,
+}
+
+/// The specifications of all the protocols.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolSpecs {
+    /// The version of the format.
+    pub version: u32,
+    /// The protocols.
+    pub protocols: Vec<ProtocolSpec>,
+}
+
+impl ProtocolSpecs {
+    /// Extract the specifications from the code: the transcript labels are the ones used by
+    /// the provers and verifiers, the structure of the Plonk proofs is read from the verifier
+    /// parameters, and the fields of the notes from their deserializers.
+    pub fn extract() -> Result<Self> {
+        Ok(ProtocolSpecs {
+            version: SPEC_VERSION,
+            protocols: vec![
+                xfr_spec().c(d!())?,
+                ar_to_abar_spec().c(d!())?,
+                bar_to_abar_spec().c(d!())?,
+                abar_to_abar_spec().c(d!())?,
+                abar_to_bar_spec().c(d!())?,
+                abar_to_ar_spec().c(d!())?,
+            ],
+        })
+    }
+
+    /// Serialize the specifications to JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).c(d!(ZeiError::SerializationError))
+    }
+}
+
+// The error of the `FieldsProbe`, which carries the names of the fields of the structure.
+#[derive(Debug)]
+struct ProbedFields(Vec<&'static str>);
+
+impl fmt::Display for ProbedFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fields {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ProbedFields {}
+
+impl serde::de::Error for ProbedFields {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        ProbedFields(vec![])
+    }
+}
+
+// A deserializer that stops at the first structure, and returns the names of its fields
+// as given by its `Deserialize` implementation.
+struct FieldsProbe;
+
+impl<'de> Deserializer<'de> for FieldsProbe {
+    type Error = ProbedFields;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        _visitor: V,
+    ) -> core::result::Result<V::Value, ProbedFields> {
+        Err(ProbedFields(vec![]))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> core::result::Result<V::Value, ProbedFields> {
+        Err(ProbedFields(fields.to_vec()))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+// Return the names of the serialized fields of a structure, in order.
+fn serialized_fields<T: DeserializeOwned>() -> Vec<&'static str> {
+    match T::deserialize(FieldsProbe) {
+        Ok(_) => vec![],
+        Err(ProbedFields(fields)) => fields,
+    }
+}
+
+// Check that the descriptions are the ones of the serialized fields of `T`, in order.
+fn fields<T: DeserializeOwned>(descriptions: Vec<FieldSpec>) -> Result<Vec<FieldSpec>> {
+    let names = serialized_fields::<T>();
+    if names != descriptions.iter().map(|f| f.name.as_str()).collect_vec() {
+        return Err(eg!(format!(
+            "{} has the fields {:?}",
+            core::any::type_name::<T>(),
+            names
+        )));
+    }
+    Ok(descriptions)
+}
+
+fn field(name: &str, description: &str) -> FieldSpec {
+    FieldSpec {
+        name: name.to_string(),
+        description: description.to_string(),
+        fields: vec![],
+    }
+}
+
+fn structure(name: &str, description: &str, fields: Vec<FieldSpec>) -> FieldSpec {
+    FieldSpec {
+        fields,
+        ..field(name, description)
+    }
+}
+
+fn params_id_field() -> FieldSpec {
+    field(
+        "params_id",
+        "the identifier of the verifier parameters of the circuit, see `ParamsId`; \
+         mandatory, so that a note made with other parameters is rejected",
+    )
+}
+
+fn anon_note_fields<T: DeserializeOwned>(body: FieldSpec) -> Result<Vec<FieldSpec>> {
+    fields::<T>(vec![
+        body,
+        field("proof", "the Plonk proof"),
+        field(
+            "folding_instance",
+            "the address folding proof of the ownership of the input",
+        ),
+        params_id_field(),
+    ])
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn message(label_bytes: &[u8], content: &str) -> TranscriptOp {
+    TranscriptOp::Message {
+        label: label(label_bytes),
+        content: content.to_string(),
+    }
+}
+
+fn check(description: &str) -> VerificationStep {
+    VerificationStep::Check {
+        description: description.to_string(),
+    }
+}
+
+fn signature(signer: &str, message: &str) -> VerificationStep {
+    VerificationStep::Signature {
+        signer: signer.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn proof(system: ProofSystem, domain: &[u8], ops: Vec<TranscriptOp>) -> VerificationStep {
+    VerificationStep::Proof {
+        system,
+        transcript: TranscriptSpec {
+            domain: label(domain),
+            ops,
+        },
+    }
+}
+
+fn plonk(params: ParamsKind) -> Result<ProofSystem> {
+    let verifier_params = VerifierParams::get_or_init(params).c(d!())?;
+    Ok(ProofSystem::Plonk {
+        params,
+        n_constraints: verifier_params.cs.size(),
+        n_vars: verifier_params.cs.num_vars(),
+        n_public_inputs: verifier_params.cs.public_vars_witness_indices().len(),
+    })
+}
+
+// The transcript of an address folding proof starts with the identifier of the parameters,
+// followed by the operations of the note, if any.
+fn address_folding(domain: &[u8], note_ops: Vec<TranscriptOp>) -> VerificationStep {
+    let mut ops = vec![message(
+        PARAMS_ID_LABEL,
+        "the 32 bytes of the params_id of the note",
+    )];
+    ops.extend(note_ops);
+    ops.extend(vec![
+        message(ADDRESS_FOLDING_HASH_LABEL, "the digest of the note body"),
+        TranscriptOp::SubProtocol {
+            name: "scalar multiplication proof".to_string(),
+        },
+        TranscriptOp::SubProtocol {
+            name: "delegated Schnorr proof".to_string(),
+        },
+    ]);
+    proof(
+        ProofSystem::AddressFolding {
+            n_generators: ANON_XFR_BP_GENS_LEN,
+        },
+        domain,
+        ops,
+    )
+}
+
+fn params_id_check() -> VerificationStep {
+    check("the params_id of the note is the identifier of the verifier parameters")
+}
+
+fn xfr_spec() -> Result<ProtocolSpec> {
+    let tracer_memo = fields::<TracerMemo>(vec![
+        field("enc_key", "the encryption keys of the tracer"),
+        field(
+            "lock_amount",
+            "the ElGamal encryptions of the low and the high 32 bits of the amount, if traced",
+        ),
+        field(
+            "lock_asset_type",
+            "the ElGamal encryption of the asset type, if traced",
+        ),
+        field(
+            "lock_attributes",
+            "the encryptions of the traced identity attributes",
+        ),
+        field(
+            "lock_info",
+            "a hybrid encryption of the big-endian low and high 32 bits of the amount, if \
+             traced, the 32 bytes of the asset type, if traced, and each traced attribute as \
+             the 32-byte encoding of its BLS12-381 scalar",
+        ),
+    ])
+    .c(d!())?;
+    let body = fields::<XfrBody>(vec![
+        field("inputs", "the input records"),
+        field("outputs", "the output records"),
+        field("proofs", "the range, asset and tracing proofs"),
+        structure(
+            "asset_tracing_memos",
+            "for each input and then each output, the list of its tracer memos",
+            tracer_memo,
+        ),
+        field(
+            "owners_memos",
+            "for each output, the memo that opens it to its owner, if confidential",
+        ),
+    ])
+    .c(d!())?;
+    let note = fields::<XfrNote>(vec![
+        structure("body", "the body", body),
+        field(
+            "multisig",
+            "the signatures of the owners of the inputs, in the order of their public keys",
+        ),
+        field(
+            "account_authorizations",
+            "the authorizations of the multisig accounts that approve the transfer, in the \
+             order of their addresses",
+        ),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "xfr".to_string(),
+        fields: note,
+        steps: vec![
+            signature(
+                "the owners of the inputs",
+                "the root of the body tree, as a multisignature",
+            ),
+            signature(
+                "a threshold of the signers of each authorizing multisig account",
+                "the root of the body tree",
+            ),
+            check("the amounts and asset types of the non-confidential records balance"),
+            proof(
+                ProofSystem::RangeProof {
+                    n_bits: BULLET_PROOF_RANGE,
+                },
+                RANGE_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+            proof(
+                ProofSystem::AssetEquality,
+                ASSET_EQUALITY_TRANSCRIPT,
+                vec![],
+            ),
+            proof(
+                ProofSystem::AssetMixing,
+                ASSET_MIXING_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+            proof(
+                ProofSystem::AssetTracing,
+                ASSET_TRACING_PROOFS_TRANSCRIPT,
+                vec![],
+            ),
+        ],
+    })
+}
+
+fn ar_to_abar_spec() -> Result<ProtocolSpec> {
+    let body = fields::<ArToAbarBody>(vec![
+        field("input", "the transparent input record"),
+        field("output", "the anonymous output record"),
+        field("proof", "the Plonk proof of the commitment of the output"),
+        field("memo", "the memo that opens the output to its owner"),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "ar_to_abar".to_string(),
+        fields: fields::<ArToAbarNote>(vec![
+            structure("body", "the body", body),
+            field("signature", "the signature of the owner of the input"),
+        ])
+        .c(d!())?,
+        steps: vec![
+            signature(
+                "the owner of the input",
+                "the field-by-field digest of the body, see `NoteDigester`",
+            ),
+            proof(
+                plonk(ParamsKind::ArToAbar).c(d!())?,
+                AR_TO_ABAR_PLONK_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+        ],
+    })
+}
+
+fn bar_to_abar_spec() -> Result<ProtocolSpec> {
+    let body = fields::<BarToAbarBody>(vec![
+        field("input", "the confidential input record"),
+        field("output", "the anonymous output record"),
+        field(
+            "proof",
+            "the delegated Schnorr proof and the Plonk proof that the output commits to the \
+             amount and asset type of the input",
+        ),
+        field("memo", "the memo that opens the output to its owner"),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "bar_to_abar".to_string(),
+        fields: fields::<BarToAbarNote>(vec![
+            structure("body", "the body", body),
+            field("signature", "the signature of the owner of the input"),
+            params_id_field(),
+        ])
+        .c(d!())?,
+        steps: vec![
+            params_id_check(),
+            proof(
+                ProofSystem::DelegatedSchnorr { n_commitments: 2 },
+                BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT,
+                vec![message(
+                    BAR_TO_ABAR_COMMITMENT_LABEL,
+                    "the commitment of the output",
+                )],
+            ),
+            proof(
+                plonk(ParamsKind::BarToAbar).c(d!())?,
+                BAR_TO_ABAR_PLONK_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+            signature(
+                "the owner of the input",
+                "the bincode serialization of the pair `(body, params_id)`",
+            ),
+        ],
+    })
+}
+
+fn abar_to_abar_spec() -> Result<ProtocolSpec> {
+    let params = ParamsKind::AnonXfr {
+        n_payers: 1,
+        n_payees: 1,
+        tree_depth: TREE_DEPTH,
+    };
+    let body = fields::<AXfrBody>(vec![
+        field("inputs", "the nullifiers of the inputs"),
+        field("outputs", "the anonymous output records"),
+        field(
+            "merkle_root",
+            "the Merkle root that the inputs are proven against",
+        ),
+        field("merkle_root_version", "the version of the Merkle root"),
+        field("fee", "the fee"),
+        field(
+            "owner_memos",
+            "for each output, the memo that opens it to its owner",
+        ),
+        field(
+            "next_owner_memos",
+            "for each output, the copy of its owner memo for the next receiving key of its \
+             owner, if any, or empty if no owner is rotating its keys; optional",
+        ),
+        field(
+            "audit",
+            "the encryption of the outputs to the auditor, for auditable transfers; optional",
+        ),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "abar_to_abar".to_string(),
+        fields: anon_note_fields::<AXfrNote>(structure("body", "the body", body)).c(d!())?,
+        steps: vec![
+            check("the Merkle root of the body is the given root"),
+            params_id_check(),
+            address_folding(
+                ANON_XFR_FOLDING_PROOF_TRANSCRIPT,
+                vec![TranscriptOp::ForEach {
+                    list: "next_owner_memos".to_string(),
+                    ops: vec![
+                        message(
+                            NEXT_OWNER_MEMO_POINT_LABEL,
+                            "the point of the memo, if the output has one",
+                        ),
+                        message(
+                            NEXT_OWNER_MEMO_LABEL,
+                            "the ciphertext of the memo, if the output has one",
+                        ),
+                        message(
+                            NO_NEXT_OWNER_MEMO_LABEL,
+                            "an empty message, if the output has none",
+                        ),
+                    ],
+                }],
+            ),
+            proof(
+                plonk(params).c(d!())?,
+                ANON_XFR_PLONK_PROOF_TRANSCRIPT,
+                vec![
+                    TranscriptOp::U64 {
+                        label: label(N_INPUTS_TRANSCRIPT),
+                        content: "the number of inputs".to_string(),
+                    },
+                    TranscriptOp::U64 {
+                        label: label(N_OUTPUTS_TRANSCRIPT),
+                        content: "the number of outputs".to_string(),
+                    },
+                ],
+            ),
+        ],
+    })
+}
+
+fn abar_to_bar_spec() -> Result<ProtocolSpec> {
+    let body = fields::<AbarToBarBody>(vec![
+        field("input", "the nullifier of the input"),
+        field("output", "the confidential output record"),
+        field(
+            "delegated_schnorr_proof",
+            "the delegated Schnorr proof that the output commits to the amount and asset \
+             type of the input",
+        ),
+        field(
+            "merkle_root",
+            "the Merkle root that the input is proven against",
+        ),
+        field("merkle_root_version", "the version of the Merkle root"),
+        field(
+            "memo",
+            "the memo that opens the output to its owner, if any",
+        ),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "abar_to_bar".to_string(),
+        fields: anon_note_fields::<AbarToBarNote>(structure("body", "the body", body)).c(d!())?,
+        steps: vec![
+            check("the Merkle root of the body is the given root"),
+            check("the output is not transparent"),
+            proof(
+                ProofSystem::DelegatedSchnorr { n_commitments: 2 },
+                ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT,
+                vec![message(
+                    ABAR_TO_BAR_NULLIFIER_LABEL,
+                    "the nullifier of the input",
+                )],
+            ),
+            params_id_check(),
+            address_folding(ABAR_TO_BAR_FOLDING_PROOF_TRANSCRIPT, vec![]),
+            proof(
+                plonk(ParamsKind::AbarToBar {
+                    tree_depth: TREE_DEPTH,
+                })
+                .c(d!())?,
+                ABAR_TO_BAR_PLONK_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+        ],
+    })
+}
+
+fn abar_to_ar_spec() -> Result<ProtocolSpec> {
+    let body = fields::<AbarToArBody>(vec![
+        field("input", "the nullifier of the input"),
+        field("output", "the transparent output record"),
+        field(
+            "merkle_root",
+            "the Merkle root that the input is proven against",
+        ),
+        field("merkle_root_version", "the version of the Merkle root"),
+        field("memo", "the memo of the output, if any"),
+    ])
+    .c(d!())?;
+    Ok(ProtocolSpec {
+        name: "abar_to_ar".to_string(),
+        fields: anon_note_fields::<AbarToArNote>(structure("body", "the body", body)).c(d!())?,
+        steps: vec![
+            check("the output is transparent"),
+            params_id_check(),
+            address_folding(ABAR_TO_AR_FOLDING_PROOF_TRANSCRIPT, vec![]),
+            check("the Merkle root of the body is the given root"),
+            proof(
+                plonk(ParamsKind::AbarToAr {
+                    tree_depth: TREE_DEPTH,
+                })
+                .c(d!())?,
+                ABAR_TO_AR_PLONK_PROOF_TRANSCRIPT,
+                vec![],
+            ),
+        ],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::spec::{field, fields, FieldSpec, ProofSystem, ProtocolSpecs, VerificationStep};
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        sig::XfrKeyPair,
+        structs::{AssetRecordTemplate, AssetType, XfrNote},
+        tests::create_xfr,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn protocol_specs() {
+        let specs = ProtocolSpecs::extract().unwrap();
+        let json = specs.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<ProtocolSpecs>(&json).unwrap(), specs);
+        assert_eq!(
+            specs
+                .protocols
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect_vec(),
+            [
+                "xfr",
+                "ar_to_abar",
+                "bar_to_abar",
+                "abar_to_abar",
+                "abar_to_bar",
+                "abar_to_ar"
+            ]
+        );
+
+        for protocol in specs.protocols.iter().skip(1) {
+            let has_plonk = protocol.steps.iter().any(|step| {
+                matches!(
+                    step,
+                    VerificationStep::Proof {
+                        system: ProofSystem::Plonk {
+                            n_constraints,
+                            n_public_inputs,
+                            ..
+                        },
+                        ..
+                    } if *n_constraints > 0 && *n_public_inputs > 0
+                )
+            });
+            assert!(has_plonk, "{}", protocol.name);
+        }
+        assert!(json.contains("\"Anon Xfr Plonk Proof\""));
+    }
+
+    #[test]
+    fn note_fields() {
+        let specs = ProtocolSpecs::extract().unwrap();
+        let names = |fields: &[FieldSpec]| {
+            fields
+                .iter()
+                .map(|field| field.name.clone())
+                .sorted()
+                .collect_vec()
+        };
+
+        // the fields of the spec are the ones of a serialized note
+        let mut prng = test_rng();
+        let keypair = XfrKeyPair::generate(&mut prng);
+        let template = AssetRecordTemplate::with_no_asset_tracing(
+            10,
+            AssetType::from_identical_byte(0),
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            keypair.pub_key,
+        );
+        let (note, _, _) = create_xfr(&mut prng, &[template.clone()], &[template], &[&keypair]);
+        let json = serde_json::to_value(&note).unwrap();
+        let keys = |value: &serde_json::Value| {
+            value
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .sorted()
+                .collect_vec()
+        };
+        let xfr = &specs.protocols[0];
+        assert_eq!(keys(&json), names(&xfr.fields));
+        assert_eq!(keys(&json["body"]), names(&xfr.fields[0].fields));
+
+        // the notes of the protocols with Plonk parameters carry their identifier
+        for protocol in specs.protocols.iter().skip(2) {
+            assert!(
+                names(&protocol.fields).contains(&"params_id".to_string()),
+                "{}",
+                protocol.name
+            );
+        }
+
+        // a description that does not match the serialization is rejected
+        assert!(fields::<XfrNote>(vec![field("body", "the body")]).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Confidential delegation of stake to validators.
+//!
+//! A staker delegates a committed amount to a validator with a [`Delegation`]: the commitments
+//! to the lower and higher 32 bits of the amount, as in the records of confidential transfers,
+//! a range proof that the amount is less than 2^64, and a signature of the staker binding the
+//! amount to the validator. The consensus layer verifies delegations with [`verify_delegation`]
+//! or [`batch_verify_delegations`], and only ever sees the commitments, which it can add up to
+//! obtain a commitment to the total stake of a validator.
+
+use crate::setup::{BulletproofParams, BULLET_PROOF_RANGE};
+use crate::xfr::{
+    digest::{DigestFields, NoteDigester},
+    sig::{XfrKeyPair, XfrPublicKey, XfrSignature},
+    structs::XfrAmount,
+};
+use bulletproofs::RangeProof;
+use merlin::Transcript;
+use zei_algebra::{
+    prelude::*,
+    ristretto::{CompressedRistretto, RistrettoScalar},
+    utils::u64_to_u32_pair,
+};
+use zei_crypto::bulletproofs::range::{batch_verify_ranges, prove_ranges_with_rng};
+
+const DELEGATION_TRANSCRIPT: &[u8] = b"Zei Delegation";
+const DELEGATION_BODY_DIGEST_DOMAIN: &[u8] = b"Delegation Body";
+
+/// The signed part of a delegation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DelegationBody {
+    /// The staker, who signs the delegation.
+    pub staker: XfrPublicKey,
+    /// The name of the validator, e.g., its address.
+    pub validator: Vec<u8>,
+    /// The commitments to the lower and higher 32 bits of the delegated amount.
+    pub amount_commitments: (CompressedRistretto, CompressedRistretto),
+    /// The range proof of the amount.
+    #[serde(with = "zei_obj_serde")]
+    pub range_proof: RangeProof,
+}
+
+impl DelegationBody {
+    /// Return the delegated amount as it appears in a record.
+    pub fn amount(&self) -> XfrAmount {
+        XfrAmount::Confidential(self.amount_commitments)
+    }
+
+    // The transcript of the range proof, bound to the staker and the validator.
+    fn transcript(staker: &XfrPublicKey, validator: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(DELEGATION_TRANSCRIPT);
+        transcript.append_message(b"staker", &staker.zei_to_bytes());
+        transcript.append_message(b"validator", validator);
+        transcript
+    }
+}
+
+impl DigestFields for DelegationBody {
+    fn digest_fields(&self, digester: &mut NoteDigester) -> Result<()> {
+        let (com_low, com_high) = &self.amount_commitments;
+        digester
+            .append_bytes(b"staker", &self.staker.zei_to_bytes())
+            .append_bytes(b"validator", &self.validator)
+            .append_bytes(b"amount low", com_low.0.as_bytes())
+            .append_bytes(b"amount high", com_high.0.as_bytes())
+            .append_bytes(b"range proof", &self.range_proof.to_bytes());
+        Ok(())
+    }
+}
+
+/// A delegation of a committed amount to a validator, signed by the staker.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Delegation {
+    /// The body of the delegation.
+    pub body: DelegationBody,
+    /// The signature of the staker over the body.
+    pub signature: XfrSignature,
+}
+
+/// Delegate `amount` to `validator`, committed with the blinding factors of its lower and higher
+/// 32 bits, e.g., those of the record that holds the stake.
+pub fn delegate<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    keypair: &XfrKeyPair,
+    validator: &[u8],
+    amount: u64,
+    amount_blinds: &(RistrettoScalar, RistrettoScalar),
+) -> Result<Delegation> {
+    if validator.is_empty() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let staker = keypair.get_pk();
+    let (low, high) = u64_to_u32_pair(amount);
+
+    let params = BulletproofParams::default();
+    let mut transcript = DelegationBody::transcript(&staker, validator);
+    let (range_proof, coms) = prove_ranges_with_rng(
+        prng,
+        &params.bp_gens,
+        &mut transcript,
+        &[low as u64, high as u64],
+        &[amount_blinds.0, amount_blinds.1],
+        BULLET_PROOF_RANGE,
+    )
+    .c(d!(ZeiError::RangeProofProveError))?;
+
+    let body = DelegationBody {
+        staker,
+        validator: validator.to_vec(),
+        amount_commitments: (coms[0], coms[1]),
+        range_proof,
+    };
+    let msg = body.note_digest(DELEGATION_BODY_DIGEST_DOMAIN).c(d!())?;
+    let signature = keypair.sign(&msg).c(d!())?;
+    Ok(Delegation { body, signature })
+}
+
+/// Verify a delegation: the signature of the staker and the range proof of the amount.
+pub fn verify_delegation<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    delegation: &Delegation,
+) -> Result<()> {
+    batch_verify_delegations(prng, params, &[delegation]).c(d!())
+}
+
+/// Batch-verify delegations, with a single verification of all the range proofs.
+pub fn batch_verify_delegations<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    delegations: &[&Delegation],
+) -> Result<()> {
+    let mut transcripts = Vec::with_capacity(delegations.len());
+    let mut commitments = Vec::with_capacity(delegations.len());
+    for delegation in delegations {
+        let body = &delegation.body;
+        if body.validator.is_empty() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let msg = body.note_digest(DELEGATION_BODY_DIGEST_DOMAIN).c(d!())?;
+        body.staker
+            .verify(&msg, &delegation.signature)
+            .c(d!(ZeiError::SignatureError))?;
+        transcripts.push(DelegationBody::transcript(&body.staker, &body.validator));
+        commitments.push([body.amount_commitments.0, body.amount_commitments.1]);
+    }
+    if delegations.is_empty() {
+        return Ok(());
+    }
+
+    let proofs = delegations
+        .iter()
+        .map(|delegation| &delegation.body.range_proof)
+        .collect_vec();
+    let commitments = commitments.iter().map(|coms| &coms[..]).collect_vec();
+    batch_verify_ranges(
+        prng,
+        &params.bp_gens,
+        &proofs,
+        &mut transcripts,
+        &commitments,
+        BULLET_PROOF_RANGE,
+    )
+    .c(d!(ZeiError::RangeProofVerifyError))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::staking::{batch_verify_delegations, delegate, verify_delegation};
+    use crate::xfr::{sig::XfrKeyPair, structs::XfrAmount};
+    use ark_std::test_rng;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+    use zei_crypto::basic::pedersen_comm::PedersenCommitmentRistretto;
+
+    #[test]
+    fn delegation() {
+        let mut prng = test_rng();
+        let params = BulletproofParams::default();
+        let staker = XfrKeyPair::generate(&mut prng);
+        let blinds = (
+            RistrettoScalar::random(&mut prng),
+            RistrettoScalar::random(&mut prng),
+        );
+
+        let delegation = delegate(&mut prng, &staker, b"validator 1", u64::MAX, &blinds).unwrap();
+        assert!(verify_delegation(&mut prng, &params, &delegation).is_ok());
+
+        // the amount is committed as in a record with the same blinding factors
+        let pc_gens = PedersenCommitmentRistretto::default();
+        assert_eq!(
+            delegation.body.amount(),
+            XfrAmount::from_blinds(&pc_gens, u64::MAX, &blinds.0, &blinds.1)
+        );
+
+        let other = delegate(&mut prng, &staker, b"validator 2", 10, &blinds).unwrap();
+        assert!(batch_verify_delegations(&mut prng, &params, &[&delegation, &other]).is_ok());
+
+        // the delegation is bound to the validator and the staker
+        let mut tampered = delegation.clone();
+        tampered.body.validator = b"validator 2".to_vec();
+        assert!(verify_delegation(&mut prng, &params, &tampered).is_err());
+        assert!(batch_verify_delegations(&mut prng, &params, &[&other, &tampered]).is_err());
+
+        let mut tampered = delegation.clone();
+        let other_staker = XfrKeyPair::generate(&mut prng);
+        tampered.signature = other_staker.sign(b"message").unwrap();
+        tampered.body.staker = other_staker.get_pk();
+        assert!(verify_delegation(&mut prng, &params, &tampered).is_err());
+
+        // the amount cannot be swapped for another one
+        let mut tampered = delegation;
+        if let XfrAmount::Confidential(coms) = other.body.amount() {
+            tampered.body.amount_commitments = coms;
+        }
+        assert!(verify_delegation(&mut prng, &params, &tampered).is_err());
+
+        assert!(delegate(&mut prng, &staker, b"", 10, &blinds).is_err());
+    }
+}
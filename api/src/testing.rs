@@ -0,0 +1,487 @@
+use crate::anon_xfr::{
+    abar_to_abar::{finish_anon_xfr_note, init_anon_xfr_note, verify_anon_xfr_note, AXfrNote},
+    abar_to_ar::{
+        finish_abar_to_ar_note, init_abar_to_ar_note, verify_abar_to_ar_note, AbarToArNote,
+    },
+    ar_to_abar::{gen_ar_to_abar_note, verify_ar_to_abar_note, ArToAbarNote},
+    bar_to_abar::{gen_bar_to_abar_note, verify_bar_to_abar_note, BarToAbarNote},
+    commit,
+    keys::AXfrKeyPair,
+    structs::{MTLeafInfo, MTNode, MTPath, OpenAnonAssetRecord, OpenAnonAssetRecordBuilder},
+    FEE_TYPE,
+};
+use crate::setup::{BulletproofParams, ParamsKind, ProverParams, VerifierParams};
+use crate::xfr::{
+    asset_record::AssetRecordType,
+    compute_transfer_multisig, gen_xfr_note,
+    sig::{XfrKeyPair, XfrPublicKey},
+    structs::{AssetRecord, AssetRecordTemplate, AssetType, XfrNote},
+    verify_xfr_note, XfrNotePolicies,
+};
+use sha2::Sha512;
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+use zei_crypto::{basic::rescue::RescueInstance, deterministic::DeterministicProver};
+
+/// The version of the format of the test vectors.
+pub const TEST_VECTORS_VERSION: u32 = 1;
+
+/// The depth of the Merkle tree of the anonymous test vectors, which hold a single record.
+pub const TEST_VECTORS_TREE_DEPTH: usize = 1;
+
+/// A note of a test vector, with the public data that its verification needs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestVectorNote {
+    /// A confidential transfer note.
+    Xfr(XfrNote),
+    /// A transparent-to-anonymous note.
+    ArToAbar(ArToAbarNote),
+    /// A confidential-to-anonymous note.
+    BarToAbar {
+        /// The note.
+        note: BarToAbarNote,
+        /// The public key of the signer of the note.
+        signer: XfrPublicKey,
+    },
+    /// An anonymous transfer note.
+    AbarToAbar {
+        /// The note.
+        note: AXfrNote,
+        /// The Merkle root that the note is verified against.
+        merkle_root: BLSScalar,
+    },
+    /// An anonymous-to-transparent note.
+    AbarToAr {
+        /// The note.
+        note: AbarToArNote,
+        /// The Merkle root that the note is verified against.
+        merkle_root: BLSScalar,
+    },
+}
+
+/// A test vector: a note, the parameters to verify it, and the expected verification result.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// The name of the test vector.
+    pub name: String,
+    /// The kind of the Plonk parameters, or none for the Bulletproofs of confidential transfers.
+    pub params: Option<ParamsKind>,
+    /// The note.
+    pub note: TestVectorNote,
+    /// Whether the note is valid.
+    pub valid: bool,
+}
+
+/// A set of test vectors, generated from a seed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// The version of the format.
+    pub version: u32,
+    /// The seed of the deterministic prover used to generate the notes.
+    pub seed: [u8; 32],
+    /// The test vectors.
+    pub vectors: Vec<TestVector>,
+}
+
+impl TestVectors {
+    /// Generate a valid and an invalid test vector for every kind of note, from a seed.
+    pub fn generate(seed: [u8; 32]) -> Result<Self> {
+        let mut prng = DeterministicProver::from_seed(seed);
+        let mut vectors = vec![];
+        vectors.extend(xfr_vectors(&mut prng.fork(b"xfr")).c(d!())?);
+        vectors.extend(ar_to_abar_vectors(&mut prng.fork(b"ar to abar")).c(d!())?);
+        vectors.extend(bar_to_abar_vectors(&mut prng.fork(b"bar to abar")).c(d!())?);
+        vectors.extend(abar_to_abar_vectors(&mut prng.fork(b"abar to abar")).c(d!())?);
+        vectors.extend(abar_to_ar_vectors(&mut prng.fork(b"abar to ar")).c(d!())?);
+        Ok(TestVectors {
+            version: TEST_VECTORS_VERSION,
+            seed,
+            vectors,
+        })
+    }
+
+    /// Serialize the test vectors to JSON.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Deserialize test vectors from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let vectors: TestVectors =
+            serde_json::from_str(json).c(d!(ZeiError::DeserializationError))?;
+        if vectors.version != TEST_VECTORS_VERSION {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        Ok(vectors)
+    }
+
+    /// Check that every test vector verifies exactly when it is expected to be valid.
+    pub fn check(&self) -> Result<()> {
+        for (index, vector) in self.vectors.iter().enumerate() {
+            if vector.verify().is_ok() != vector.valid {
+                return Err(eg!(ZeiError::NoteError {
+                    index,
+                    source: Box::new(ZeiError::InconsistentStructureError),
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TestVector {
+    /// Verify the note of the test vector.
+    pub fn verify(&self) -> Result<()> {
+        match (&self.note, self.params) {
+            (TestVectorNote::Xfr(note), None) => {
+                let mut prng = DeterministicProver::from_seed([0u8; 32]);
+                let policies = XfrNotePolicies::empty_policies(
+                    note.body.inputs.len(),
+                    note.body.outputs.len(),
+                );
+                verify_xfr_note(
+                    &mut prng,
+                    &mut BulletproofParams::default(),
+                    note,
+                    &policies.to_ref(),
+                )
+                .c(d!())
+            }
+            (TestVectorNote::ArToAbar(note), Some(kind)) => {
+                let params = VerifierParams::get_or_init(kind).c(d!())?;
+                verify_ar_to_abar_note(&params, note).c(d!())
+            }
+            (TestVectorNote::BarToAbar { note, signer }, Some(kind)) => {
+                let params = VerifierParams::get_or_init(kind).c(d!())?;
+                verify_bar_to_abar_note(&params, note, signer).c(d!())
+            }
+            (TestVectorNote::AbarToAbar { note, merkle_root }, Some(kind)) => {
+                let params = VerifierParams::get_or_init(kind).c(d!())?;
+                verify_anon_xfr_note(&params, note, merkle_root, Sha512::new()).c(d!())
+            }
+            (TestVectorNote::AbarToAr { note, merkle_root }, Some(kind)) => {
+                let params = VerifierParams::get_or_init(kind).c(d!())?;
+                verify_abar_to_ar_note(&params, note, merkle_root, Sha512::new()).c(d!())
+            }
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+}
+
+fn vector(name: &str, params: Option<ParamsKind>, note: TestVectorNote, valid: bool) -> TestVector {
+    TestVector {
+        name: name.to_string(),
+        params,
+        note,
+        valid,
+    }
+}
+
+fn asset_record<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    amount: u64,
+    asset_type: AssetType,
+    record_type: AssetRecordType,
+    owner: &XfrKeyPair,
+) -> Result<AssetRecord> {
+    let template =
+        AssetRecordTemplate::with_no_asset_tracing(amount, asset_type, record_type, owner.pub_key);
+    AssetRecord::from_template_no_identity_tracing(prng, &template).c(d!())
+}
+
+/// Create an anonymous record that is the only leaf of a Merkle tree of depth one.
+fn single_leaf_record<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    keypair: &AXfrKeyPair,
+    amount: u64,
+    asset_type: AssetType,
+) -> Result<OpenAnonAssetRecord> {
+    let mut oabar = OpenAnonAssetRecordBuilder::new()
+        .amount(amount)
+        .asset_type(asset_type)
+        .pub_key(&keypair.get_public_key())
+        .finalize(prng)
+        .c(d!())?
+        .build()
+        .c(d!())?;
+    let commitment = commit(
+        oabar.pub_key_ref(),
+        &oabar.get_blind(),
+        oabar.get_amount(),
+        &oabar.get_asset_type(),
+    )
+    .c(d!())?;
+
+    let hash = RescueInstance::new();
+    let zero = BLSScalar::zero();
+    let leaf = hash.rescue(&[zero, commitment, zero, zero])[0];
+    let root = hash.rescue(&[leaf, zero, zero, zero])[0];
+    oabar.update_mt_leaf_info(MTLeafInfo {
+        path: MTPath::new(vec![MTNode {
+            siblings1: zero,
+            siblings2: zero,
+            is_left_child: 1,
+            is_right_child: 0,
+        }]),
+        root,
+        root_version: 1,
+        uid: 0,
+    });
+    Ok(oabar)
+}
+
+fn xfr_vectors<R: CryptoRng + RngCore>(prng: &mut R) -> Result<Vec<TestVector>> {
+    let mut vectors = vec![];
+    let record_types = [
+        (
+            "xfr non-confidential",
+            AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        ),
+        (
+            "xfr confidential amount",
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+        ),
+        (
+            "xfr confidential asset type",
+            AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+        ),
+        (
+            "xfr confidential",
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        ),
+    ];
+    for (name, record_type) in record_types {
+        let senders = [XfrKeyPair::generate(prng), XfrKeyPair::generate(prng)];
+        let receivers = [XfrKeyPair::generate(prng), XfrKeyPair::generate(prng)];
+        let (type_0, type_1) = (
+            AssetType::from_identical_byte(0),
+            AssetType::from_identical_byte(1),
+        );
+        let inputs = [
+            asset_record(prng, 10, type_0, record_type, &senders[0]).c(d!())?,
+            asset_record(prng, 20, type_1, record_type, &senders[1]).c(d!())?,
+        ];
+        let outputs = [
+            asset_record(prng, 10, type_0, record_type, &receivers[0]).c(d!())?,
+            asset_record(prng, 20, type_1, record_type, &receivers[1]).c(d!())?,
+        ];
+        let note = gen_xfr_note(prng, &inputs, &outputs, &[&senders[0], &senders[1]]).c(d!())?;
+
+        // signed by the receivers instead of the senders
+        let mut bad_note = note.clone();
+        bad_note.multisig =
+            compute_transfer_multisig(&bad_note.body, &[&receivers[0], &receivers[1]]).c(d!())?;
+
+        vectors.push(vector(name, None, TestVectorNote::Xfr(note), true));
+        vectors.push(vector(
+            &format!("{} with wrong signers", name),
+            None,
+            TestVectorNote::Xfr(bad_note),
+            false,
+        ));
+    }
+    Ok(vectors)
+}
+
+fn ar_to_abar_vectors<R: CryptoRng + RngCore>(prng: &mut R) -> Result<Vec<TestVector>> {
+    let kind = ParamsKind::ArToAbar;
+    let params = ProverParams::get_or_init(kind).c(d!())?;
+    let bar_keypair = XfrKeyPair::generate(prng);
+    let abar_keypair = AXfrKeyPair::generate(prng);
+    let record = asset_record(
+        prng,
+        10,
+        FEE_TYPE,
+        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+        &bar_keypair,
+    )
+    .c(d!())?;
+    let note = gen_ar_to_abar_note(
+        prng,
+        &params,
+        &record.open_asset_record,
+        &bar_keypair,
+        &abar_keypair.get_public_key(),
+    )
+    .c(d!())?;
+
+    // signed by another key than the owner of the input
+    let mut bad_note = note.clone();
+    let msg = bincode::serialize(&bad_note.body).c(d!(ZeiError::SerializationError))?;
+    bad_note.signature = XfrKeyPair::generate(prng).sign(&msg).c(d!())?;
+
+    Ok(vec![
+        vector(
+            "ar to abar",
+            Some(kind),
+            TestVectorNote::ArToAbar(note),
+            true,
+        ),
+        vector(
+            "ar to abar with wrong signer",
+            Some(kind),
+            TestVectorNote::ArToAbar(bad_note),
+            false,
+        ),
+    ])
+}
+
+fn bar_to_abar_vectors<R: CryptoRng + RngCore>(prng: &mut R) -> Result<Vec<TestVector>> {
+    let kind = ParamsKind::BarToAbar;
+    let params = ProverParams::get_or_init(kind).c(d!())?;
+    let bar_keypair = XfrKeyPair::generate(prng);
+    let abar_keypair = AXfrKeyPair::generate(prng);
+    let record = asset_record(
+        prng,
+        10,
+        FEE_TYPE,
+        AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+        &bar_keypair,
+    )
+    .c(d!())?;
+    let note = gen_bar_to_abar_note(
+        prng,
+        &params,
+        &record.open_asset_record,
+        &bar_keypair,
+        &abar_keypair.get_public_key(),
+    )
+    .c(d!())?;
+
+    Ok(vec![
+        vector(
+            "bar to abar",
+            Some(kind),
+            TestVectorNote::BarToAbar {
+                note: note.clone(),
+                signer: bar_keypair.pub_key,
+            },
+            true,
+        ),
+        vector(
+            "bar to abar with wrong signer",
+            Some(kind),
+            TestVectorNote::BarToAbar {
+                note,
+                signer: XfrKeyPair::generate(prng).pub_key,
+            },
+            false,
+        ),
+    ])
+}
+
+fn abar_to_abar_vectors<R: CryptoRng + RngCore>(prng: &mut R) -> Result<Vec<TestVector>> {
+    let kind = ParamsKind::AnonXfr {
+        n_payers: 1,
+        n_payees: 1,
+        tree_depth: TEST_VECTORS_TREE_DEPTH,
+    };
+    let params = ProverParams::get_or_init(kind).c(d!())?;
+    let keypair = AXfrKeyPair::generate(prng);
+    let receiver = AXfrKeyPair::generate(prng);
+    let (fee, amount) = (5u32, 10u64);
+
+    let input = single_leaf_record(prng, &keypair, amount + fee as u64, FEE_TYPE).c(d!())?;
+    let output = OpenAnonAssetRecordBuilder::new()
+        .amount(amount)
+        .asset_type(FEE_TYPE)
+        .pub_key(&receiver.get_public_key())
+        .finalize(prng)
+        .c(d!())?
+        .build()
+        .c(d!())?;
+    let pre_note = init_anon_xfr_note(&[input], &[output], fee, &keypair).c(d!())?;
+    let note = finish_anon_xfr_note(prng, &params, pre_note, Sha512::new()).c(d!())?;
+    let merkle_root = note.body.merkle_root;
+
+    // the fee does not match the proof
+    let mut bad_note = note.clone();
+    bad_note.body.fee += 1;
+
+    Ok(vec![
+        vector(
+            "abar to abar",
+            Some(kind),
+            TestVectorNote::AbarToAbar { note, merkle_root },
+            true,
+        ),
+        vector(
+            "abar to abar with wrong fee",
+            Some(kind),
+            TestVectorNote::AbarToAbar {
+                note: bad_note,
+                merkle_root,
+            },
+            false,
+        ),
+    ])
+}
+
+fn abar_to_ar_vectors<R: CryptoRng + RngCore>(prng: &mut R) -> Result<Vec<TestVector>> {
+    let kind = ParamsKind::AbarToAr {
+        tree_depth: TEST_VECTORS_TREE_DEPTH,
+    };
+    let params = ProverParams::get_or_init(kind).c(d!())?;
+    let keypair = AXfrKeyPair::generate(prng);
+    let receiver = XfrKeyPair::generate(prng);
+
+    let input = single_leaf_record(prng, &keypair, 10, FEE_TYPE).c(d!())?;
+    let merkle_root = input.mt_leaf_info.as_ref().c(d!())?.root;
+    let pre_note = init_abar_to_ar_note(prng, &input, &keypair, &receiver.pub_key).c(d!())?;
+    let note = finish_abar_to_ar_note(prng, &params, pre_note, Sha512::new()).c(d!())?;
+
+    Ok(vec![
+        vector(
+            "abar to ar",
+            Some(kind),
+            TestVectorNote::AbarToAr {
+                note: note.clone(),
+                merkle_root,
+            },
+            true,
+        ),
+        vector(
+            "abar to ar with wrong merkle root",
+            Some(kind),
+            TestVectorNote::AbarToAr {
+                note,
+                merkle_root: BLSScalar::random(prng),
+            },
+            false,
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::testing::TestVectors;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn test_vectors() {
+        let vectors = TestVectors::generate([3u8; 32]).unwrap();
+        assert!(vectors.vectors.iter().any(|vector| vector.valid));
+        assert!(vectors.vectors.iter().any(|vector| !vector.valid));
+        assert!(vectors.check().is_ok());
+
+        // the vectors are reproducible from the seed, and survive the JSON encoding
+        let json = vectors.to_json().unwrap();
+        assert_eq!(
+            TestVectors::generate([3u8; 32]).unwrap().to_json().unwrap(),
+            json
+        );
+        let decoded = TestVectors::from_json(&json).unwrap();
+        assert_eq!(decoded, vectors);
+        assert!(decoded.check().is_ok());
+
+        // a vector whose expected result is flipped is detected
+        let mut flipped = decoded;
+        flipped.vectors[0].valid = !flipped.vectors[0].valid;
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 0,
+                source: Box::new(ZeiError::InconsistentStructureError)
+            },
+            flipped.check().unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,212 @@
+//! Decryption of the asset tracing memos of confidential transfer notes, for auditors.
+//!
+//! An auditor holding the keys of a tracing policy can recover the amounts, the asset types,
+//! and the identity attributes of the records of a note that are traced under this policy.
+//! The amounts and asset types are checked against the commitments of the records by
+//! verifying the tracing proof of the note for the auditor's key, so that a sender cannot
+//! give the auditor a memo for different values than the ones transferred.
+
+use crate::anon_creds::Attr;
+use crate::xfr::{
+    proofs::verify_tracing_proof_for_key,
+    sig::XfrPublicKey,
+    structs::{AssetTracerKeyPair, AssetType, XfrNote},
+};
+use zei_algebra::prelude::*;
+
+/// The position of a record in a note.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordPosition {
+    /// The index of an input record.
+    Input(usize),
+    /// The index of an output record.
+    Output(usize),
+}
+
+impl RecordPosition {
+    fn error(self, source: ZeiError) -> ZeiError {
+        match self {
+            RecordPosition::Input(index) => ZeiError::InputError {
+                index,
+                source: Box::new(source),
+            },
+            RecordPosition::Output(index) => ZeiError::OutputError {
+                index,
+                source: Box::new(source),
+            },
+        }
+    }
+}
+
+/// A record of a note, as decrypted by an auditor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracedRecord {
+    /// The position of the record in the note.
+    pub position: RecordPosition,
+    /// The amount of the record.
+    pub amount: u64,
+    /// The asset type of the record.
+    pub asset_type: AssetType,
+    /// The identity attributes of the owner revealed to the auditor.
+    pub identity_attributes: Vec<Attr>,
+    /// The owner of the record.
+    pub owner: XfrPublicKey,
+}
+
+/// Decrypt the asset tracing memos of a note for the tracer key pair, and return the records
+/// traced by this tracer, in the order of the inputs and then the outputs.
+///
+/// The confidential amounts and asset types are checked to be the ones committed in the
+/// records, while the non-confidential ones are read from the records. The identity
+/// attributes are only checked against their ElGamal ciphertexts in the memos, whose
+/// consistency with the credentials of the owners is checked by the note verification.
+pub fn decrypt_tracing_memos<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    tracer_keypair: &AssetTracerKeyPair,
+    note: &XfrNote,
+) -> Result<Vec<TracedRecord>> {
+    let body = &note.body;
+    if body.inputs.len() + body.outputs.len() != body.asset_tracing_memos.len() {
+        return Err(eg!(ZeiError::InconsistentStructureError));
+    }
+    let positions = (0..body.inputs.len())
+        .map(RecordPosition::Input)
+        .chain((0..body.outputs.len()).map(RecordPosition::Output));
+    let records = body.inputs.iter().chain(&body.outputs);
+
+    let mut traced = vec![];
+    let mut has_locks = false;
+    for ((position, record), memos) in positions.zip(records).zip(&body.asset_tracing_memos) {
+        for memo in memos
+            .iter()
+            .filter(|memo| memo.enc_key == tracer_keypair.enc_key)
+        {
+            has_locks |= memo.lock_amount.is_some() || memo.lock_asset_type.is_some();
+            let (amount, asset_type, identity_attributes) =
+                memo.decrypt(&tracer_keypair.dec_key)
+                    .c(d!(position.error(ZeiError::BogusAssetTracerMemo)))?;
+            // the non-confidential amounts and asset types are not locked in the memos
+            let amount = match amount {
+                Some(amount) => amount,
+                None => record
+                    .amount
+                    .get_amount()
+                    .c(d!(position.error(ZeiError::InconsistentStructureError)))?,
+            };
+            let asset_type = match asset_type {
+                Some(asset_type) => asset_type,
+                None => record
+                    .asset_type
+                    .get_asset_type()
+                    .c(d!(position.error(ZeiError::InconsistentStructureError)))?,
+            };
+            traced.push(TracedRecord {
+                position,
+                amount,
+                asset_type,
+                identity_attributes,
+                owner: record.public_key,
+            });
+        }
+    }
+
+    if has_locks {
+        verify_tracing_proof_for_key(prng, body, &tracer_keypair.enc_key.record_data_enc_key)
+            .c(d!())?;
+    }
+    Ok(traced)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::tracing::{decrypt_tracing_memos, RecordPosition};
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        gen_xfr_note,
+        sig::XfrKeyPair,
+        structs::{
+            AssetRecord, AssetRecordTemplate, AssetTracerKeyPair, AssetType, TracerMemo,
+            TracingPolicies, TracingPolicy,
+        },
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::{rand::rngs::StdRng, test_rng};
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn decrypt_tracing_memos_of_note() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let tracer_keypair = AssetTracerKeyPair::generate(&mut prng);
+        let policies = TracingPolicies::from_policy(TracingPolicy {
+            enc_keys: tracer_keypair.enc_key.clone(),
+            asset_tracing: true,
+            identity_tracing: None,
+        });
+        let asset_type = AssetType::from_identical_byte(1);
+        let sender = XfrKeyPair::generate(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+
+        let record = |prng: &mut StdRng, record_type, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_asset_tracing(
+                10,
+                asset_type,
+                record_type,
+                keypair.pub_key,
+                policies.clone(),
+            );
+            AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap()
+        };
+        let input = record(
+            &mut prng,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            &sender,
+        );
+        let output = record(
+            &mut prng,
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            &receiver,
+        );
+        let mut note = gen_xfr_note(&mut prng, &[input], &[output], &[&sender]).unwrap();
+        let note_policies = XfrNotePolicies::new(
+            vec![policies.clone()],
+            vec![None],
+            vec![policies.clone()],
+            vec![None],
+        );
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &note_policies.to_ref()).is_ok());
+
+        let traced = decrypt_tracing_memos(&mut prng, &tracer_keypair, &note).unwrap();
+        assert_eq!(traced.len(), 2);
+        assert_eq!(traced[0].position, RecordPosition::Input(0));
+        assert_eq!(traced[0].owner, sender.pub_key);
+        assert_eq!(traced[1].position, RecordPosition::Output(0));
+        assert_eq!(traced[1].owner, receiver.pub_key);
+        for record in traced.iter() {
+            assert_eq!(record.amount, 10);
+            assert_eq!(record.asset_type, asset_type);
+            assert!(record.identity_attributes.is_empty());
+        }
+
+        // another tracer sees nothing
+        let other_keypair = AssetTracerKeyPair::generate(&mut prng);
+        assert!(decrypt_tracing_memos(&mut prng, &other_keypair, &note)
+            .unwrap()
+            .is_empty());
+
+        // a well-formed memo for another amount than the committed one is rejected
+        let (blind_low, blind_high) = (
+            RistrettoScalar::random(&mut prng),
+            RistrettoScalar::random(&mut prng),
+        );
+        note.body.asset_tracing_memos[1][0] = TracerMemo::new(
+            &mut prng,
+            &tracer_keypair.enc_key,
+            Some((11, 0, &blind_low, &blind_high)),
+            None,
+            &[],
+        );
+        assert!(decrypt_tracing_memos(&mut prng, &tracer_keypair, &note).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::setup::BulletproofParams;
 use bulletproofs::{
-    r1cs::{batch_verify, Prover, R1CSProof, Verifier},
+    r1cs::{batch_verify, Prover, R1CSError, R1CSProof, Verifier},
     BulletproofGens, PedersenGens,
 };
 use merlin::Transcript;
@@ -11,6 +11,9 @@ use zei_algebra::{
 };
 use zei_crypto::bulletproofs::mix::{mix, MixCommitment, MixValue};
 
+/// The domain separator for the asset mixing proof.
+pub(crate) const ASSET_MIXING_PROOF_TRANSCRIPT: &[u8] = b"AssetMixingProof";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 /// The asset mixing proof.
 pub struct AssetMixProof(#[serde(with = "zei_obj_serde")] pub R1CSProof);
@@ -51,9 +54,31 @@ impl Eq for AssetMixProof {}
 pub fn prove_asset_mixing(
     inputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
     outputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
+) -> Result<AssetMixProof> {
+    prove_asset_mixing_inner(inputs, outputs, |prover, bp_gens| prover.prove(bp_gens))
+}
+
+/// Create a proof of asset mixing as in `prove_asset_mixing`, with the randomness of `prng`
+/// instead of the thread RNG, e.g., to reproduce a proof from a seed.
+pub fn prove_asset_mixing_with_rng<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    inputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
+    outputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
+) -> Result<AssetMixProof> {
+    prove_asset_mixing_inner(inputs, outputs, |prover, bp_gens| {
+        prover.prove_with_rng(bp_gens, prng)
+    })
+}
+
+fn prove_asset_mixing_inner<
+    F: FnOnce(Prover, &BulletproofGens) -> core::result::Result<R1CSProof, R1CSError>,
+>(
+    inputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
+    outputs: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
+    prove: F,
 ) -> Result<AssetMixProof> {
     let pc_gens = PedersenGens::default();
-    let mut prover_transcript = Transcript::new(b"AssetMixingProof");
+    let mut prover_transcript = Transcript::new(ASSET_MIXING_PROOF_TRANSCRIPT);
     let mut prover = Prover::new(&pc_gens, &mut prover_transcript);
     fn extract_values_and_blinds(
         list: &[(u64, RistrettoScalar, RistrettoScalar, RistrettoScalar)],
@@ -114,9 +139,7 @@ pub fn prove_asset_mixing(
 
     let num_gates = asset_mix_num_generators(n, m);
     let bp_gens = BulletproofGens::new(num_gates.next_power_of_two(), 1);
-    let proof = prover
-        .prove(&bp_gens)
-        .c(d!(ZeiError::AssetMixerVerificationError))?;
+    let proof = prove(prover, &bp_gens).c(d!(ZeiError::AssetMixerVerificationError))?;
     Ok(AssetMixProof(proof))
 }
 
@@ -191,7 +214,7 @@ pub fn batch_verify_asset_mixing<R: CryptoRng + RngCore>(
     let mut transcripts = Vec::with_capacity(instances.len());
     let mut verifiers = Vec::with_capacity(instances.len());
     for _ in 0..instances.len() {
-        transcripts.push(Transcript::new(b"AssetMixingProof"));
+        transcripts.push(Transcript::new(ASSET_MIXING_PROOF_TRANSCRIPT));
     }
     for (instance, transcript) in instances.iter().zip(transcripts.iter_mut()) {
         let mut verifier = Verifier::new(transcript);
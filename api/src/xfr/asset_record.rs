@@ -3,10 +3,10 @@ use crate::anon_creds::{
     ConfidentialAC, Credential,
 };
 use crate::xfr::{
-    sig::{XfrKeyPair, XfrPublicKey},
+    sig::{KeyType, XfrKeyPair, XfrPublicKey},
     structs::{
         AssetRecord, AssetRecordTemplate, AssetType, BlindAssetRecord, OpenAssetRecord, OwnerMemo,
-        TracerMemo, TracingPolicies, XfrAmount, XfrAssetType,
+        OwnerMemoOptions, TracerMemo, TracingPolicies, XfrAmount, XfrAssetType,
     },
 };
 use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
@@ -239,6 +239,17 @@ impl AssetRecord {
     pub fn from_template_no_identity_tracing<R: CryptoRng + RngCore>(
         prng: &mut R,
         template: &AssetRecordTemplate,
+    ) -> Result<AssetRecord> {
+        AssetRecord::from_template_with_memo_options(prng, template, &OwnerMemoOptions::default())
+            .c(d!())
+    }
+
+    /// Create the asset record using a template, without identity tracing, and with the
+    /// options of its owner memo.
+    pub fn from_template_with_memo_options<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        template: &AssetRecordTemplate,
+        memo_options: &OwnerMemoOptions,
     ) -> Result<AssetRecord> {
         let empty_id_proofs_and_ctext = vec![(None, vec![]); template.asset_tracing_policies.len()];
         for policy in template.asset_tracing_policies.get_policies().iter() {
@@ -246,8 +257,13 @@ impl AssetRecord {
                 return Err(eg!(ZeiError::ParameterError));
             }
         }
-        build_record_input_from_template(prng, &template, empty_id_proofs_and_ctext.as_slice())
-            .c(d!())
+        build_record_input_from_template(
+            prng,
+            &template,
+            empty_id_proofs_and_ctext.as_slice(),
+            memo_options,
+        )
+        .c(d!())
     }
 
     /// Create the asset record using a template, with identity tracing.
@@ -283,7 +299,13 @@ impl AssetRecord {
             };
             id_proofs_and_attrs.push((conf_id, attrs));
         }
-        build_record_input_from_template(prng, &template, id_proofs_and_attrs.as_slice()).c(d!())
+        build_record_input_from_template(
+            prng,
+            &template,
+            id_proofs_and_attrs.as_slice(),
+            &OwnerMemoOptions::default(),
+        )
+        .c(d!())
     }
 }
 
@@ -328,6 +350,7 @@ fn sample_blind_asset_record<R: CryptoRng + RngCore>(
     pc_gens: &PedersenCommitmentRistretto,
     asset_record: &AssetRecordTemplate,
     attrs_and_ctexts: Vec<Vec<(Attr, AttributeCiphertext)>>,
+    memo_options: &OwnerMemoOptions,
 ) -> (
     BlindAssetRecord,
     (RistrettoScalar, RistrettoScalar),
@@ -346,9 +369,13 @@ fn sample_blind_asset_record<R: CryptoRng + RngCore>(
             ),
 
             AssetRecordType::ConfidentialAmount_NonConfidentialAssetType => {
-                let (owner_memo, amount_blinds) =
-                    OwnerMemo::from_amount(prng, asset_record.amount, &asset_record.public_key)
-                        .unwrap(); // safe unwrap
+                let (owner_memo, amount_blinds) = OwnerMemo::from_amount_with_options(
+                    prng,
+                    asset_record.amount,
+                    &asset_record.public_key,
+                    memo_options,
+                )
+                .unwrap(); // safe unwrap
 
                 (
                     XfrAmount::from_blinds(
@@ -365,10 +392,11 @@ fn sample_blind_asset_record<R: CryptoRng + RngCore>(
             }
 
             AssetRecordType::NonConfidentialAmount_ConfidentialAssetType => {
-                let (owner_memo, asset_type_blind) = OwnerMemo::from_asset_type(
+                let (owner_memo, asset_type_blind) = OwnerMemo::from_asset_type_with_options(
                     prng,
                     &asset_record.asset_type,
                     &asset_record.public_key,
+                    memo_options,
                 )
                 .unwrap(); //safe unwrap
 
@@ -383,11 +411,12 @@ fn sample_blind_asset_record<R: CryptoRng + RngCore>(
 
             AssetRecordType::ConfidentialAmount_ConfidentialAssetType => {
                 let (owner_memo, amount_blinds, asset_type_blind) =
-                    OwnerMemo::from_amount_and_asset_type(
+                    OwnerMemo::from_amount_and_asset_type_with_options(
                         prng,
                         asset_record.amount,
                         &asset_record.asset_type,
                         &asset_record.public_key,
+                        memo_options,
                     )
                     .unwrap(); //safe unwrap
                 (
@@ -448,9 +477,27 @@ pub fn build_open_asset_record<R: CryptoRng + RngCore>(
     pc_gens: &PedersenCommitmentRistretto,
     asset_record: &AssetRecordTemplate,
     attrs_and_ctexts: Vec<Vec<(Attr, AttributeCiphertext)>>,
+) -> (OpenAssetRecord, Vec<TracerMemo>, Option<OwnerMemo>) {
+    build_open_asset_record_with_memo_options(
+        prng,
+        pc_gens,
+        asset_record,
+        attrs_and_ctexts,
+        &OwnerMemoOptions::default(),
+    )
+}
+
+/// Build open asset record from the template and identity attributes, with the options of its
+/// owner memo.
+pub fn build_open_asset_record_with_memo_options<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pc_gens: &PedersenCommitmentRistretto,
+    asset_record: &AssetRecordTemplate,
+    attrs_and_ctexts: Vec<Vec<(Attr, AttributeCiphertext)>>,
+    memo_options: &OwnerMemoOptions,
 ) -> (OpenAssetRecord, Vec<TracerMemo>, Option<OwnerMemo>) {
     let (blind_asset_record, amount_blinds, type_blind, asset_tracing_memos, owner_memo) =
-        sample_blind_asset_record(prng, pc_gens, asset_record, attrs_and_ctexts);
+        sample_blind_asset_record(prng, pc_gens, asset_record, attrs_and_ctexts, memo_options);
 
     let open_asset_record = OpenAssetRecord {
         blind_asset_record,
@@ -470,19 +517,44 @@ pub fn build_blind_asset_record<R: CryptoRng + RngCore>(
     asset_record: &AssetRecordTemplate,
     attrs_and_ctexts: Vec<Vec<(Attr, AttributeCiphertext)>>,
 ) -> (BlindAssetRecord, Vec<TracerMemo>, Option<OwnerMemo>) {
-    let (blind_asset_record, _, _, asset_tracing_memos, owner_memo) =
-        sample_blind_asset_record(prng, pc_gens, asset_record, attrs_and_ctexts);
+    let (blind_asset_record, _, _, asset_tracing_memos, owner_memo) = sample_blind_asset_record(
+        prng,
+        pc_gens,
+        asset_record,
+        attrs_and_ctexts,
+        &OwnerMemoOptions::default(),
+    );
 
     (blind_asset_record, asset_tracing_memos, owner_memo)
 }
 
 /// Open a blind asset record to obtain the open asset record.
 /// The caller needs to have the key to decrypt the owner memo if some fields are confidential.
+/// The owner memo may be addressed to the key pair or to its view key.
 pub fn open_blind_asset_record(
     input: &BlindAssetRecord,
     owner_memo: &Option<OwnerMemo>,
     keypair: &XfrKeyPair,
 ) -> Result<OpenAssetRecord> {
+    let (key_type, s) = match owner_memo {
+        Some(memo) => memo.receiver_scalar(keypair).c(d!())?,
+        None => keypair.get_sk_ref().as_scalar_bytes(),
+    };
+    open_blind_asset_record_with_scalar(input, owner_memo, &key_type, &s)
+}
+
+/// Open a blind asset record given the scalar of the receiver.
+pub(crate) fn open_blind_asset_record_with_scalar(
+    input: &BlindAssetRecord,
+    owner_memo: &Option<OwnerMemo>,
+    key_type: &KeyType,
+    s: &[u8],
+) -> Result<OpenAssetRecord> {
+    if let Some(owner_memo) = owner_memo {
+        if !owner_memo.check_view_tag_with_scalar(key_type, s).c(d!())? {
+            return Err(eg!(ZeiError::DecryptionError));
+        }
+    }
     let (amount, asset_type, amount_blinds, type_blind) = match input.get_record_type() {
         AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType => (
             input.amount.get_amount().c(d!(ZeiError::ParameterError))?,
@@ -496,8 +568,12 @@ pub fn open_blind_asset_record(
 
         AssetRecordType::ConfidentialAmount_NonConfidentialAssetType => {
             let owner_memo = owner_memo.as_ref().c(d!(ZeiError::ParameterError))?;
-            let amount = owner_memo.decrypt_amount(&keypair).c(d!())?;
-            let amount_blinds = owner_memo.derive_amount_blinds(&keypair).c(d!())?;
+            let amount =
+                OwnerMemo::parse_amount(&owner_memo.decrypt_with_scalar(key_type, s).c(d!())?)
+                    .c(d!())?;
+            let amount_blinds = owner_memo
+                .derive_amount_blinds_with_scalar(key_type, s)
+                .c(d!())?;
             (
                 amount,
                 input
@@ -511,8 +587,12 @@ pub fn open_blind_asset_record(
 
         AssetRecordType::NonConfidentialAmount_ConfidentialAssetType => {
             let owner_memo = owner_memo.as_ref().c(d!(ZeiError::ParameterError))?;
-            let asset_type = owner_memo.decrypt_asset_type(&keypair).c(d!())?;
-            let asset_type_blind = owner_memo.derive_asset_type_blind(&keypair).c(d!())?;
+            let asset_type =
+                OwnerMemo::parse_asset_type(&owner_memo.decrypt_with_scalar(key_type, s).c(d!())?)
+                    .c(d!())?;
+            let asset_type_blind = owner_memo
+                .derive_asset_type_blind_with_scalar(key_type, s)
+                .c(d!())?;
             (
                 input.amount.get_amount().c(d!(ZeiError::ParameterError))?,
                 asset_type,
@@ -523,10 +603,16 @@ pub fn open_blind_asset_record(
 
         AssetRecordType::ConfidentialAmount_ConfidentialAssetType => {
             let owner_memo = owner_memo.as_ref().c(d!(ZeiError::ParameterError))?;
-            let (amount, asset_type) =
-                owner_memo.decrypt_amount_and_asset_type(&keypair).c(d!())?;
-            let amount_blinds = owner_memo.derive_amount_blinds(&keypair).c(d!())?;
-            let asset_type_blind = owner_memo.derive_asset_type_blind(&keypair).c(d!())?;
+            let (amount, asset_type) = OwnerMemo::parse_amount_and_asset_type(
+                &owner_memo.decrypt_with_scalar(key_type, s).c(d!())?,
+            )
+            .c(d!())?;
+            let amount_blinds = owner_memo
+                .derive_amount_blinds_with_scalar(key_type, s)
+                .c(d!())?;
+            let asset_type_blind = owner_memo
+                .derive_asset_type_blind_with_scalar(key_type, s)
+                .c(d!())?;
 
             (amount, asset_type, amount_blinds, asset_type_blind)
         }
@@ -546,6 +632,7 @@ fn build_record_input_from_template<R: CryptoRng + RngCore>(
     prng: &mut R,
     asset_record: &AssetRecordTemplate,
     identity_proofs_and_attrs: &[(Option<ConfidentialAC>, Vec<Attr>)],
+    memo_options: &OwnerMemoOptions,
 ) -> Result<AssetRecord> {
     if asset_record.asset_tracing_policies.len() != identity_proofs_and_attrs.len() {
         return Err(eg!(ZeiError::ParameterError));
@@ -565,7 +652,7 @@ fn build_record_input_from_template<R: CryptoRng + RngCore>(
             (Some(conf_ac), attrs) => {
                 let c = conf_ac.cts.clone();
                 let p = conf_ac.pok.clone();
-                let attrs_and_ctexts = attrs.iter().zip(c).map(|(a, c)| (*a, c)).collect();
+                let attrs_and_ctexts = attrs.iter().cloned().zip(c).collect();
                 (attrs_and_ctexts, Some(p))
             }
         };
@@ -573,7 +660,13 @@ fn build_record_input_from_template<R: CryptoRng + RngCore>(
         reveal_proofs.push(reveal_proof);
     }
     let (open_asset_record, asset_tracing_memos, owner_memo) =
-        build_open_asset_record(prng, &pc_gens, asset_record, attrs_ctexts);
+        build_open_asset_record_with_memo_options(
+            prng,
+            &pc_gens,
+            asset_record,
+            attrs_ctexts,
+            memo_options,
+        );
 
     Ok(AssetRecord {
         open_asset_record,
@@ -1,15 +1,16 @@
-use crate::anon_creds::{Attr, AttributeCiphertext};
+use crate::anon_creds::{ac_attrs_from_scalars, ac_attrs_to_scalars, Attr, AttributeCiphertext};
 use crate::xfr::structs::{
     AssetTracerDecKeys, AssetTracerEncKeys, AssetType, TracerMemo, ASSET_TYPE_LENGTH,
 };
 use zei_algebra::{
-    bls12_381::{BLSScalar, BLSG1},
+    bls12_381::{BLSScalar, BLS12_381_SCALAR_LEN, BLSG1},
     prelude::*,
     ristretto::{RistrettoPoint, RistrettoScalar},
 };
 use zei_crypto::basic::{
     elgamal::{
-        elgamal_encrypt, elgamal_partial_decrypt, ElGamalCiphertext, ElGamalDecKey, ElGamalEncKey,
+        elgamal_encrypt, elgamal_encrypt_precomp, elgamal_partial_decrypt, ElGamalCiphertext,
+        ElGamalDecKey, ElGamalEncKey, ElGamalEncKeyPrecomp,
     },
     hybrid_encryption::{hybrid_decrypt_with_x25519_secret_key, hybrid_encrypt_x25519},
 };
@@ -24,6 +25,16 @@ type DecryptedAssetMemo = (Option<u64>, Option<AssetType>, Vec<Attr>);
 
 const U32_BYTES: usize = 4;
 
+/// Return the bytes of the scalar of an attribute in the lock of a memo, from which the tracer
+/// decodes the attribute. An attribute that does not encode into a single scalar has no
+/// ciphertext in a valid memo, and is written as the zero scalar, which does not decode.
+fn attr_lock_bytes(attr: &Attr) -> Vec<u8> {
+    ac_attrs_to_scalars(std::slice::from_ref(attr))
+        .map(|scalars| scalars[0])
+        .unwrap_or_else(|_| BLSScalar::zero())
+        .to_bytes()
+}
+
 impl TracerMemo {
     /// Sample a new TracerMemo.
     /// amount_info is (amount_low, amount_high, amount_blind_low, amount_blind_high) tuple
@@ -35,34 +46,67 @@ impl TracerMemo {
         asset_type_info: Option<(&AssetType, &RistrettoScalar)>,
         attrs_info: &[(Attr, AttributeCiphertext)],
     ) -> Self {
+        Self::new_with_encryption(
+            prng,
+            tracer_enc_key,
+            |m, r| elgamal_encrypt(m, r, &tracer_enc_key.record_data_enc_key),
+            amount_info,
+            asset_type_info,
+            attrs_info,
+        )
+    }
+
+    /// Same as [`TracerMemo::new`], with the precomputed tables of the record data encryption
+    /// key of the tracer, for building many memos for the same tracer.
+    pub fn new_with_precomp<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        tracer_enc_key: &AssetTracerEncKeys,
+        precomp: &ElGamalEncKeyPrecomp<RistrettoPoint>,
+        amount_info: Option<(u32, u32, &RistrettoScalar, &RistrettoScalar)>,
+        asset_type_info: Option<(&AssetType, &RistrettoScalar)>,
+        attrs_info: &[(Attr, AttributeCiphertext)],
+    ) -> Result<Self> {
+        if precomp.pub_key() != tracer_enc_key.record_data_enc_key {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(Self::new_with_encryption(
+            prng,
+            tracer_enc_key,
+            |m, r| elgamal_encrypt_precomp(m, r, precomp),
+            amount_info,
+            asset_type_info,
+            attrs_info,
+        ))
+    }
+
+    fn new_with_encryption<R, F>(
+        prng: &mut R,
+        tracer_enc_key: &AssetTracerEncKeys,
+        encrypt: F,
+        amount_info: Option<(u32, u32, &RistrettoScalar, &RistrettoScalar)>,
+        asset_type_info: Option<(&AssetType, &RistrettoScalar)>,
+        attrs_info: &[(Attr, AttributeCiphertext)],
+    ) -> Self
+    where
+        R: CryptoRng + RngCore,
+        F: Fn(&RistrettoScalar, &RistrettoScalar) -> RecordDataCiphertext,
+    {
         let mut plaintext = vec![];
         let lock_amount = amount_info.map(|(amount_low, amount_high, blind_low, blind_high)| {
             plaintext.extend_from_slice(&amount_low.to_be_bytes());
             plaintext.extend_from_slice(&amount_high.to_be_bytes());
-            let ctext_amount_low = elgamal_encrypt(
-                &RistrettoScalar::from(amount_low),
-                blind_low,
-                &tracer_enc_key.record_data_enc_key,
-            );
-            let ctext_amount_high = elgamal_encrypt(
-                &RistrettoScalar::from(amount_high),
-                blind_high,
-                &tracer_enc_key.record_data_enc_key,
-            );
+            let ctext_amount_low = encrypt(&RistrettoScalar::from(amount_low), blind_low);
+            let ctext_amount_high = encrypt(&RistrettoScalar::from(amount_high), blind_high);
             (ctext_amount_low, ctext_amount_high)
         });
 
         let lock_asset_type = asset_type_info.map(|(asset_type, blind)| {
             plaintext.extend_from_slice(&asset_type.0);
-            elgamal_encrypt(
-                &asset_type.as_scalar(),
-                blind,
-                &tracer_enc_key.record_data_enc_key,
-            )
+            encrypt(&asset_type.as_scalar(), blind)
         });
 
         for (attr, _) in attrs_info.iter() {
-            plaintext.extend_from_slice(&attr.to_be_bytes())
+            plaintext.extend_from_slice(&attr_lock_bytes(attr))
         }
         let lock_info = hybrid_encrypt_x25519(prng, &tracer_enc_key.lock_info_enc_key, &plaintext);
 
@@ -114,13 +158,16 @@ impl TracerMemo {
             None
         };
 
-        if plaintext.len() < self.lock_attributes.len() * U32_BYTES {
+        // decode the attributes from their scalars
+        if plaintext.len() != self.lock_attributes.len() * BLS12_381_SCALAR_LEN {
             return Err(eg!(ZeiError::BogusAssetTracerMemo));
         }
-        let mut attrs = vec![];
-        for attr_byte in plaintext.chunks(U32_BYTES) {
-            attrs.push(u8_be_slice_to_u32(attr_byte));
-        }
+        let scalars = plaintext
+            .chunks(BLS12_381_SCALAR_LEN)
+            .map(BLSScalar::from_bytes)
+            .collect::<Result<Vec<BLSScalar>>>()
+            .c(d!(ZeiError::BogusAssetTracerMemo))?;
+        let attrs = ac_attrs_from_scalars(&scalars).c(d!(ZeiError::BogusAssetTracerMemo))?;
 
         if !self
             .verify_identity_attributes(&dec_key.attrs_dec_key, &attrs)
@@ -180,6 +227,8 @@ impl TracerMemo {
     /// If self.lock_asset_type is None, return Err(ZeiError::ParameterError),
     /// Otherwise, if decrypted asset_type is not in the candidate list return Err(ZeiError::AssetTracingExtractionError),
     /// else return the decrypted asset_type.
+    /// Only available with the `brute_force_decryption` feature.
+    #[cfg(feature = "brute_force_decryption")]
     pub fn extract_asset_type(
         &self,
         dec_key: &ElGamalDecKey<RistrettoScalar>,
@@ -202,16 +251,16 @@ impl TracerMemo {
     pub fn verify_identity_attributes(
         &self,
         dec_key: &ElGamalDecKey<BLSScalar>,
-        expected_attributes: &[u32],
+        expected_attributes: &[Attr],
     ) -> Result<Vec<bool>> {
         if self.lock_attributes.len() != expected_attributes.len() {
             return Err(eg!(ZeiError::ParameterError));
         }
+        let expected_scalars = ac_attrs_to_scalars(expected_attributes).c(d!())?;
         let mut result = vec![];
-        for (ctext, expected) in self.lock_attributes.iter().zip(expected_attributes.iter()) {
-            let scalar_attr = BLSScalar::from(*expected);
+        for (ctext, scalar_attr) in self.lock_attributes.iter().zip(expected_scalars.iter()) {
             let elem = elgamal_partial_decrypt(ctext, dec_key);
-            if elem != BLSG1::get_base().mul(&scalar_attr) {
+            if elem != BLSG1::get_base().mul(scalar_attr) {
                 result.push(false);
             } else {
                 result.push(true);
@@ -223,10 +272,11 @@ impl TracerMemo {
 
 #[cfg(test)]
 mod tests {
+    use crate::anon_creds::{ac_attrs_to_scalars, Attr};
     use crate::xfr::structs::{AssetTracerKeyPair, AssetType, TracerMemo};
     use ark_std::test_rng;
     use zei_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::RistrettoScalar};
-    use zei_crypto::basic::elgamal::elgamal_encrypt;
+    use zei_crypto::basic::elgamal::{elgamal_encrypt, ElGamalEncKeyPrecomp};
 
     #[test]
     fn extract_amount_from_tracer_memo() {
@@ -257,6 +307,43 @@ mod tests {
     }
 
     #[test]
+    fn tracer_memo_with_precomp() {
+        let mut prng = test_rng();
+        let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
+        let precomp = ElGamalEncKeyPrecomp::new(&tracer_keys.enc_key.record_data_enc_key);
+        let asset_type = AssetType::from_identical_byte(2u8);
+        let memo = TracerMemo::new_with_precomp(
+            &mut prng,
+            &tracer_keys.enc_key,
+            &precomp,
+            Some((
+                7,
+                1,
+                &RistrettoScalar::from(191919u32),
+                &RistrettoScalar::from(2222u32),
+            )),
+            Some((&asset_type, &RistrettoScalar::from(3333u32))),
+            &[],
+        )
+        .unwrap();
+        let (amount, decrypted_type, _) = memo.decrypt(&tracer_keys.dec_key).unwrap();
+        assert_eq!(amount, Some(7 + (1 << 32)));
+        assert_eq!(decrypted_type, Some(asset_type));
+
+        let other_keys = AssetTracerKeyPair::generate(&mut prng);
+        assert!(TracerMemo::new_with_precomp(
+            &mut prng,
+            &other_keys.enc_key,
+            &precomp,
+            None,
+            None,
+            &[]
+        )
+        .is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "brute_force_decryption")]
     fn extract_asset_type_from_tracer_memo() {
         let mut prng = test_rng();
         let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
@@ -334,14 +421,15 @@ mod tests {
     fn extract_identity_attributed_from_tracer_memo() {
         let mut prng = test_rng();
         let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
-        let attrs = [1u32, 2, 3];
+        let attr = |i: u64| Attr::U64(i);
+        let attrs = [attr(1), Attr::Utf8("Alice".to_string()), attr(3)];
 
         let attrs_and_ctexts = attrs
             .iter()
             .map(|x| {
-                let scalar = BLSScalar::from(*x);
+                let scalar = ac_attrs_to_scalars(std::slice::from_ref(x)).unwrap()[0];
                 (
-                    *x,
+                    x.clone(),
                     elgamal_encrypt(
                         &scalar,
                         &BLSScalar::from(1000u32),
@@ -359,35 +447,62 @@ mod tests {
             &attrs_and_ctexts,
         );
 
+        // the tracer decodes the attributes
+        let (_, _, decrypted) = memo.decrypt(&tracer_keys.dec_key).unwrap();
+        assert_eq!(decrypted, attrs);
+
         msg_eq!(
             ZeiError::ParameterError,
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[1u32])
+            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[attr(1)])
                 .unwrap_err(),
         );
         msg_eq!(
             ZeiError::ParameterError,
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[1u32, 2, 3, 4])
-                .unwrap_err(),
+            memo.verify_identity_attributes(
+                &tracer_keys.dec_key.attrs_dec_key,
+                &[attrs.to_vec(), vec![attr(4)]].concat()
+            )
+            .unwrap_err(),
         );
         assert_eq!(
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[1u32, 2, 4])
-                .unwrap(),
+            memo.verify_identity_attributes(
+                &tracer_keys.dec_key.attrs_dec_key,
+                &[attrs[0].clone(), attrs[1].clone(), attr(4)]
+            )
+            .unwrap(),
             vec![true, true, false]
         );
         assert_eq!(
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[4u32, 2, 3])
-                .unwrap(),
-            vec![false, true, true]
-        );
-        assert_eq!(
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[1u32, 2, 3])
+            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &attrs)
                 .unwrap(),
             vec![true, true, true]
         );
         assert_eq!(
-            memo.verify_identity_attributes(&tracer_keys.dec_key.attrs_dec_key, &[3u32, 1, 2])
-                .unwrap(),
+            memo.verify_identity_attributes(
+                &tracer_keys.dec_key.attrs_dec_key,
+                &[attr(3), attr(1), Attr::Utf8("Bob".to_string())]
+            )
+            .unwrap(),
             vec![false, false, false]
         );
+
+        // the attributes that take several scalars are rejected
+        assert!(memo
+            .verify_identity_attributes(
+                &tracer_keys.dec_key.attrs_dec_key,
+                &[attr(1), Attr::Bytes32([1u8; 32]), attr(3)]
+            )
+            .is_err());
+        let memo = TracerMemo::new(
+            &mut prng,
+            &tracer_keys.enc_key,
+            None,
+            None,
+            &[(Attr::Bytes32([1u8; 32]), attrs_and_ctexts[0].1.clone())],
+        );
+        msg_eq!(
+            ZeiError::BogusAssetTracerMemo,
+            memo.decrypt(&tracer_keys.dec_key).unwrap_err(),
+        );
     }
 }
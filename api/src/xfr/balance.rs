@@ -0,0 +1,143 @@
+//! Per-asset accounting of the amounts of a transfer.
+//!
+//! The builders and the verifiers of transfers add up the input and the output amounts of each
+//! asset type in a [`BalanceSheet`], with checked `u128` arithmetic, so that no sum of `u64`
+//! amounts wraps around. A sheet whose totals differ for an asset type is reported as an
+//! [`AssetImbalance`], which names the asset type and both totals.
+
+use crate::xfr::structs::AssetType;
+use std::fmt;
+use zei_algebra::{collections::BTreeMap, prelude::*};
+
+/// The inputs and the outputs of an asset type do not have the same total amount.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AssetImbalance {
+    /// The asset type.
+    pub asset: AssetType,
+    /// The total amount of the inputs.
+    pub input: u128,
+    /// The total amount of the outputs, including the fees.
+    pub output: u128,
+}
+
+impl fmt::Display for AssetImbalance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The inputs of asset type {:?} total {}, but the outputs total {}",
+            self.asset.0, self.input, self.output
+        )
+    }
+}
+
+/// The total input and output amounts of each asset type of a transfer.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BalanceSheet {
+    totals: BTreeMap<AssetType, (u128, u128)>,
+}
+
+impl BalanceSheet {
+    /// Create an empty balance sheet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an input amount of an asset type.
+    pub fn add_input(&mut self, asset: AssetType, amount: u64) -> Result<()> {
+        let (input, _) = self.totals.entry(asset).or_insert((0, 0));
+        *input = input
+            .checked_add(u128::from(amount))
+            .c(d!(ZeiError::ParameterError))?;
+        Ok(())
+    }
+
+    /// Add an output amount of an asset type, e.g., an output record or a fee.
+    pub fn add_output(&mut self, asset: AssetType, amount: u64) -> Result<()> {
+        let (_, output) = self.totals.entry(asset).or_insert((0, 0));
+        *output = output
+            .checked_add(u128::from(amount))
+            .c(d!(ZeiError::ParameterError))?;
+        Ok(())
+    }
+
+    /// Return true if an amount of the asset type was added.
+    pub fn contains(&self, asset: &AssetType) -> bool {
+        self.totals.contains_key(asset)
+    }
+
+    /// Return the total input and output amounts of an asset type.
+    pub fn totals(&self, asset: &AssetType) -> (u128, u128) {
+        self.totals.get(asset).copied().unwrap_or((0, 0))
+    }
+
+    /// Return the imbalance of the first asset type, in their order, whose inputs and outputs
+    /// do not have the same total amount.
+    pub fn imbalance(&self) -> Option<AssetImbalance> {
+        self.totals
+            .iter()
+            .find(|(_, (input, output))| input != output)
+            .map(|(asset, (input, output))| AssetImbalance {
+                asset: *asset,
+                input: *input,
+                output: *output,
+            })
+    }
+
+    /// Check that the inputs and the outputs of each asset type have the same total amount.
+    pub fn check(&self) -> Result<()> {
+        match self.imbalance() {
+            Some(imbalance) => Err(eg!(imbalance)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AssetImbalance, BalanceSheet};
+    use crate::xfr::structs::AssetType;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn balance_sheet() {
+        let asset = AssetType::from_identical_byte(1);
+        let other = AssetType::from_identical_byte(2);
+
+        let mut sheet = BalanceSheet::new();
+        sheet.add_input(asset, u64::MAX).unwrap();
+        sheet.add_input(asset, u64::MAX).unwrap();
+        sheet.add_input(other, 5).unwrap();
+        sheet.add_output(other, 5).unwrap();
+        sheet.add_output(asset, u64::MAX).unwrap();
+        assert!(sheet.contains(&other));
+        assert_eq!(
+            sheet.totals(&asset),
+            (2 * u128::from(u64::MAX), u128::from(u64::MAX))
+        );
+
+        // the sums do not wrap around
+        let imbalance = AssetImbalance {
+            asset,
+            input: 2 * u128::from(u64::MAX),
+            output: u128::from(u64::MAX),
+        };
+        assert_eq!(sheet.imbalance(), Some(imbalance));
+        msg_eq!(imbalance, sheet.check().unwrap_err());
+
+        sheet.add_output(asset, u64::MAX).unwrap();
+        assert!(sheet.check().is_ok());
+
+        // an output of an asset type without inputs
+        sheet
+            .add_output(AssetType::from_identical_byte(3), 1)
+            .unwrap();
+        assert_eq!(
+            sheet.imbalance(),
+            Some(AssetImbalance {
+                asset: AssetType::from_identical_byte(3),
+                input: 0,
+                output: 1,
+            })
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use crate::errors::ZeiError;
+use crate::xfr::{
+    sampling::{note_digest, NoteDigest, NoteSample, NoteSampler, SamplingCommitment},
+    sig::{XfrMultiSig, XfrPublicKey},
+    structs::XfrBody,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+/// The domain separator of the message signed by the senders of a transfer.
+const XFR_BODY_ROOT_DOMAIN: &[u8] = b"Zei Transfer Body Root";
+
+/// A field of the transfer body, which is a leaf of the body tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum XfrBodyField {
+    /// The input at the given position.
+    Input(u64),
+    /// The output at the given position.
+    Output(u64),
+    /// The proofs.
+    Proofs,
+    /// The tracing memos at the given position.
+    AssetTracingMemos(u64),
+    /// The owner memo at the given position.
+    OwnerMemo(u64),
+}
+
+/// The root of the body tree, together with the number of leaves of each kind.
+///
+/// The senders of a transfer sign this root, so that a light client can check a single field of
+/// the body against the signature with a short inclusion proof, instead of the whole body.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfrBodyRoot {
+    /// The root of the binary Merkle tree of the fields.
+    pub root: [u8; 32],
+    /// The number of inputs.
+    pub num_inputs: u64,
+    /// The number of outputs.
+    pub num_outputs: u64,
+    /// The number of lists of tracing memos.
+    pub num_asset_tracing_memos: u64,
+    /// The number of owner memos.
+    pub num_owners_memos: u64,
+}
+
+impl XfrBodyRoot {
+    /// Return the message signed by the senders.
+    pub fn signed_message(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(XFR_BODY_ROOT_DOMAIN);
+        hasher.update(&self.root);
+        hasher.update(&self.num_inputs.to_le_bytes());
+        hasher.update(&self.num_outputs.to_le_bytes());
+        hasher.update(&self.num_asset_tracing_memos.to_le_bytes());
+        hasher.update(&self.num_owners_memos.to_le_bytes());
+        hasher.finalize().to_vec()
+    }
+
+    /// Verify the multisignature of the senders over the root.
+    pub fn verify_multisig(&self, pubkeys: &[&XfrPublicKey], multisig: &XfrMultiSig) -> Result<()> {
+        multisig.verify(pubkeys, &self.signed_message()).c(d!())
+    }
+
+    /// Return the number of leaves, which are ordered as inputs, outputs, proofs,
+    /// tracing memos, and owner memos.
+    fn num_leaves(&self) -> u64 {
+        self.num_inputs
+            + self.num_outputs
+            + 1
+            + self.num_asset_tracing_memos
+            + self.num_owners_memos
+    }
+
+    /// Return the position of the leaf of a field.
+    fn leaf_index(&self, field: &XfrBodyField) -> Result<u64> {
+        let (offset, index, len) = match *field {
+            XfrBodyField::Input(i) => (0, i, self.num_inputs),
+            XfrBodyField::Output(i) => (self.num_inputs, i, self.num_outputs),
+            XfrBodyField::Proofs => (self.num_inputs + self.num_outputs, 0, 1),
+            XfrBodyField::AssetTracingMemos(i) => (
+                self.num_inputs + self.num_outputs + 1,
+                i,
+                self.num_asset_tracing_memos,
+            ),
+            XfrBodyField::OwnerMemo(i) => (
+                self.num_inputs + self.num_outputs + 1 + self.num_asset_tracing_memos,
+                i,
+                self.num_owners_memos,
+            ),
+        };
+        if index >= len {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(offset + index)
+    }
+
+    fn commitment(&self) -> SamplingCommitment {
+        SamplingCommitment {
+            root: self.root,
+            num_notes: self.num_leaves(),
+        }
+    }
+}
+
+/// The inclusion proof of a field of the transfer body under the body root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XfrBodyFieldProof {
+    /// The field.
+    pub field: XfrBodyField,
+    /// The sibling nodes from the leaf to the root.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl XfrBodyFieldProof {
+    /// Verify that `value` is the field of the body under the root.
+    pub fn verify<T: Serialize>(&self, root: &XfrBodyRoot, value: &T) -> Result<()> {
+        let sample = NoteSample {
+            index: root.leaf_index(&self.field).c(d!())?,
+            digest: field_digest(&self.field, value).c(d!())?,
+            siblings: self.siblings.clone(),
+        };
+        sample
+            .verify_inclusion(&root.commitment())
+            .c(d!(ZeiError::ArgumentVerificationError))
+    }
+}
+
+/// The binary Merkle tree of the fields of a transfer body.
+///
+/// Each input, output, list of tracing memos and owner memo is a leaf, and so are the proofs.
+/// A leaf is the digest of the field, together with its kind and position.
+#[derive(Clone, Debug)]
+pub struct XfrBodyTree {
+    root: XfrBodyRoot,
+    digests: Vec<NoteDigest>,
+    tree: NoteSampler,
+}
+
+impl XfrBodyTree {
+    /// Build the tree of a transfer body.
+    pub fn new(body: &XfrBody) -> Result<Self> {
+        let mut digests = vec![];
+        for (i, input) in body.inputs.iter().enumerate() {
+            digests.push(field_digest(&XfrBodyField::Input(i as u64), input).c(d!())?);
+        }
+        for (i, output) in body.outputs.iter().enumerate() {
+            digests.push(field_digest(&XfrBodyField::Output(i as u64), output).c(d!())?);
+        }
+        digests.push(field_digest(&XfrBodyField::Proofs, &body.proofs).c(d!())?);
+        for (i, memos) in body.asset_tracing_memos.iter().enumerate() {
+            let field = XfrBodyField::AssetTracingMemos(i as u64);
+            digests.push(field_digest(&field, memos).c(d!())?);
+        }
+        for (i, memo) in body.owners_memos.iter().enumerate() {
+            digests.push(field_digest(&XfrBodyField::OwnerMemo(i as u64), memo).c(d!())?);
+        }
+
+        let tree = NoteSampler::new(&digests).c(d!())?;
+        let root = XfrBodyRoot {
+            root: tree.commitment().root,
+            num_inputs: body.inputs.len() as u64,
+            num_outputs: body.outputs.len() as u64,
+            num_asset_tracing_memos: body.asset_tracing_memos.len() as u64,
+            num_owners_memos: body.owners_memos.len() as u64,
+        };
+        Ok(XfrBodyTree {
+            root,
+            digests,
+            tree,
+        })
+    }
+
+    /// Return the root of the tree.
+    pub fn root(&self) -> XfrBodyRoot {
+        self.root
+    }
+
+    /// Return the inclusion proof of a field.
+    pub fn prove_field(&self, field: XfrBodyField) -> Result<XfrBodyFieldProof> {
+        let index = self.root.leaf_index(&field).c(d!())?;
+        let sample = self
+            .tree
+            .prove_inclusion(index, &self.digests[index as usize])
+            .c(d!())?;
+        Ok(XfrBodyFieldProof {
+            field,
+            siblings: sample.siblings,
+        })
+    }
+}
+
+/// Compute the digest of a field of the body, bound to its kind and position.
+fn field_digest<T: Serialize>(field: &XfrBodyField, value: &T) -> Result<NoteDigest> {
+    note_digest(&(field, value)).c(d!())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        body_tree::{XfrBodyField, XfrBodyTree},
+        sig::XfrKeyPair,
+        structs::{AssetRecordTemplate, AssetType},
+        tests::create_xfr,
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn xfr_body_tree() {
+        let mut prng = test_rng();
+        let sender = XfrKeyPair::generate(&mut prng);
+        let receivers = (0..3)
+            .map(|_| XfrKeyPair::generate(&mut prng))
+            .collect::<Vec<_>>();
+        let template = |amount, pk| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                AssetType::from_identical_byte(1u8),
+                AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                pk,
+            )
+        };
+        let (note, _, _) = create_xfr(
+            &mut prng,
+            &[template(30, sender.get_pk())],
+            &receivers
+                .iter()
+                .map(|receiver| template(10, receiver.get_pk()))
+                .collect::<Vec<_>>(),
+            &[&sender],
+        );
+
+        // the multisignature is over the root
+        let policies = XfrNotePolicies::empty_policies(1, 3);
+        let mut params = BulletproofParams::default();
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &policies.to_ref()).is_ok());
+
+        let tree = XfrBodyTree::new(&note.body).unwrap();
+        let root = tree.root();
+        assert_eq!(root.num_inputs, 1);
+        assert_eq!(root.num_outputs, 3);
+        assert!(root
+            .verify_multisig(&[&sender.pub_key], &note.multisig)
+            .is_ok());
+
+        // a light client checks one output and its memo
+        let proof = tree.prove_field(XfrBodyField::Output(1)).unwrap();
+        assert!(proof.verify(&root, &note.body.outputs[1]).is_ok());
+        assert!(proof.verify(&root, &note.body.outputs[2]).is_err());
+        let proof = tree.prove_field(XfrBodyField::OwnerMemo(1)).unwrap();
+        assert!(proof.verify(&root, &note.body.owners_memos[1]).is_ok());
+        assert!(proof.verify(&root, &note.body.owners_memos[0]).is_err());
+
+        let proof = tree.prove_field(XfrBodyField::Proofs).unwrap();
+        assert!(proof.verify(&root, &note.body.proofs).is_ok());
+
+        // the same value under another field is rejected
+        let mut proof = tree.prove_field(XfrBodyField::Input(0)).unwrap();
+        assert!(proof.verify(&root, &note.body.inputs[0]).is_ok());
+        proof.field = XfrBodyField::Output(0);
+        assert!(proof.verify(&root, &note.body.inputs[0]).is_err());
+
+        assert!(tree.prove_field(XfrBodyField::Output(3)).is_err());
+        assert!(tree.prove_field(XfrBodyField::Input(1)).is_err());
+
+        // a modified body does not match the signature
+        let mut body = note.body.clone();
+        body.outputs.swap(0, 2);
+        let other_root = XfrBodyTree::new(&body).unwrap().root();
+        assert!(other_root
+            .verify_multisig(&[&sender.pub_key], &note.multisig)
+            .is_err());
+    }
+}
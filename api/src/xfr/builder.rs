@@ -0,0 +1,436 @@
+use crate::anon_creds::{ACCommitmentKey, ACUserSecretKey, Credential};
+use crate::xfr::{
+    balance::BalanceSheet,
+    gen_xfr_note_with_signers,
+    sig::Signer,
+    structs::{
+        AssetRecord, AssetRecordTemplate, AssetType, OpenAssetRecord, TracingPolicies, XfrNote,
+    },
+};
+use std::fmt;
+use zei_algebra::prelude::*;
+
+/// The errors of the transfer note builder, pointing at the offending input or output.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum XfrNoteBuilderError {
+    /// The transfer has no input.
+    NoInputs,
+    /// The signer of the input with this index does not own the input.
+    InputSignerMismatch(usize),
+    /// The input with this index has an identity tracing policy, but no credential.
+    InputMissingCredential(usize),
+    /// The output with this index has an identity tracing policy, but no credential.
+    OutputMissingCredential(usize),
+    /// The output with this index has an asset type that is not in the inputs.
+    OutputAssetTypeNotInInputs(usize),
+    /// The inputs and outputs of this asset type do not have the same total amount.
+    UnbalancedAssetType(AssetType),
+    /// The input with this index cannot be built.
+    InvalidInput(usize),
+    /// The output with this index cannot be built.
+    InvalidOutput(usize),
+}
+
+impl fmt::Display for XfrNoteBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use XfrNoteBuilderError::*;
+        match self {
+            NoInputs => f.write_str("The transfer has no input"),
+            InputSignerMismatch(i) => write!(f, "The signer of input {} does not own it", i),
+            InputMissingCredential(i) => write!(
+                f,
+                "Input {} has an identity tracing policy but no credential",
+                i
+            ),
+            OutputMissingCredential(i) => write!(
+                f,
+                "Output {} has an identity tracing policy but no credential",
+                i
+            ),
+            OutputAssetTypeNotInInputs(i) => {
+                write!(f, "The asset type of output {} is not in the inputs", i)
+            }
+            UnbalancedAssetType(asset_type) => write!(
+                f,
+                "The amounts of asset type {:?} are not balanced",
+                asset_type.0
+            ),
+            InvalidInput(i) => write!(f, "Input {} cannot be built", i),
+            InvalidOutput(i) => write!(f, "Output {} cannot be built", i),
+        }
+    }
+}
+
+/// The credential used to prove the identity of the owner of a record to asset tracers.
+#[derive(Clone, Copy)]
+pub struct IdentityCredential<'a> {
+    /// The secret key of the credential owner.
+    pub user_sec_key: &'a ACUserSecretKey,
+    /// The credential.
+    pub credential: &'a Credential,
+    /// The commitment key of the credential.
+    pub commitment_key: &'a ACCommitmentKey,
+}
+
+enum InputSource<'a> {
+    Record(AssetRecord),
+    Open {
+        oar: OpenAssetRecord,
+        policies: TracingPolicies,
+        identity: Option<IdentityCredential<'a>>,
+    },
+}
+
+enum OutputSource<'a> {
+    Record(AssetRecord),
+    Template {
+        template: AssetRecordTemplate,
+        identity: Option<IdentityCredential<'a>>,
+    },
+}
+
+/// A builder of confidential transfer notes.
+///
+/// Each input is added together with the signer of its owner, who co-signs the note, e.g.,
+/// a key pair or a hardware wallet, and each output is added as a template. All of them are
+/// checked by `validate` before any proof is generated.
+#[derive(Default)]
+pub struct XfrNoteBuilder<'a> {
+    inputs: Vec<(InputSource<'a>, &'a dyn Signer)>,
+    outputs: Vec<OutputSource<'a>>,
+}
+
+impl<'a> XfrNoteBuilder<'a> {
+    /// Create an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an input without tracing policies, to be co-signed by `signer`.
+    pub fn input(self, oar: OpenAssetRecord, signer: &'a dyn Signer) -> Self {
+        self.input_with_tracing(oar, TracingPolicies::new(), signer)
+    }
+
+    /// Add an input with tracing policies, which must not trace the identity,
+    /// to be co-signed by `signer`.
+    pub fn input_with_tracing(
+        mut self,
+        oar: OpenAssetRecord,
+        policies: TracingPolicies,
+        signer: &'a dyn Signer,
+    ) -> Self {
+        self.inputs.push((
+            InputSource::Open {
+                oar,
+                policies,
+                identity: None,
+            },
+            signer,
+        ));
+        self
+    }
+
+    /// Add an input with tracing policies, whose identity proofs are generated
+    /// from `identity`, to be co-signed by `signer`.
+    pub fn input_with_identity(
+        mut self,
+        oar: OpenAssetRecord,
+        policies: TracingPolicies,
+        identity: IdentityCredential<'a>,
+        signer: &'a dyn Signer,
+    ) -> Self {
+        self.inputs.push((
+            InputSource::Open {
+                oar,
+                policies,
+                identity: Some(identity),
+            },
+            signer,
+        ));
+        self
+    }
+
+    /// Add an input that is already built, to be co-signed by `signer`.
+    pub fn input_record(mut self, record: AssetRecord, signer: &'a dyn Signer) -> Self {
+        self.inputs.push((InputSource::Record(record), signer));
+        self
+    }
+
+    /// Add an output from a template, whose tracing policies must not trace the identity.
+    pub fn output(mut self, template: AssetRecordTemplate) -> Self {
+        self.outputs.push(OutputSource::Template {
+            template,
+            identity: None,
+        });
+        self
+    }
+
+    /// Add an output from a template, whose identity proofs are generated from `identity`.
+    pub fn output_with_identity(
+        mut self,
+        template: AssetRecordTemplate,
+        identity: IdentityCredential<'a>,
+    ) -> Self {
+        self.outputs.push(OutputSource::Template {
+            template,
+            identity: Some(identity),
+        });
+        self
+    }
+
+    /// Add an output that is already built.
+    pub fn output_record(mut self, record: AssetRecord) -> Self {
+        self.outputs.push(OutputSource::Record(record));
+        self
+    }
+
+    /// Check the signers, the credentials, and the balance of each asset type.
+    pub fn validate(&self) -> Result<()> {
+        if self.inputs.is_empty() {
+            return Err(eg!(XfrNoteBuilderError::NoInputs));
+        }
+
+        let mut balances = BalanceSheet::new();
+        for (i, (input, signer)) in self.inputs.iter().enumerate() {
+            let (oar, policies, has_identity) = match input {
+                InputSource::Record(record) => (&record.open_asset_record, None, true),
+                InputSource::Open {
+                    oar,
+                    policies,
+                    identity,
+                } => (oar, Some(policies), identity.is_some()),
+            };
+            if oar.blind_asset_record.public_key != signer.public_key() {
+                return Err(eg!(XfrNoteBuilderError::InputSignerMismatch(i)));
+            }
+            if !has_identity && policies.map_or(false, traces_identity) {
+                return Err(eg!(XfrNoteBuilderError::InputMissingCredential(i)));
+            }
+            balances.add_input(oar.asset_type, oar.amount).c(d!())?;
+        }
+
+        for (i, output) in self.outputs.iter().enumerate() {
+            let (amount, asset_type) = match output {
+                OutputSource::Record(record) => (
+                    record.open_asset_record.amount,
+                    record.open_asset_record.asset_type,
+                ),
+                OutputSource::Template { template, identity } => {
+                    if identity.is_none() && traces_identity(&template.asset_tracing_policies) {
+                        return Err(eg!(XfrNoteBuilderError::OutputMissingCredential(i)));
+                    }
+                    (template.amount, template.asset_type)
+                }
+            };
+            if !balances.contains(&asset_type) {
+                return Err(eg!(XfrNoteBuilderError::OutputAssetTypeNotInInputs(i)));
+            }
+            balances.add_output(asset_type, amount).c(d!())?;
+        }
+
+        if let Some(imbalance) = balances.imbalance() {
+            return Err(eg!(imbalance)).c(d!(XfrNoteBuilderError::UnbalancedAssetType(
+                imbalance.asset
+            )));
+        }
+        Ok(())
+    }
+
+    /// Validate the transfer, then build the records and generate the note.
+    pub fn build<R: CryptoRng + RngCore>(self, prng: &mut R) -> Result<XfrNote> {
+        self.validate().c(d!())?;
+
+        let mut inputs = Vec::with_capacity(self.inputs.len());
+        let mut signers = Vec::with_capacity(self.inputs.len());
+        for (i, (input, signer)) in self.inputs.into_iter().enumerate() {
+            let record = match input {
+                InputSource::Record(record) => Ok(record),
+                InputSource::Open {
+                    oar,
+                    policies,
+                    identity: None,
+                } => AssetRecord::from_open_asset_record_with_asset_tracing_but_no_identity(
+                    prng, oar, policies,
+                ),
+                InputSource::Open {
+                    oar,
+                    policies,
+                    identity: Some(identity),
+                } => AssetRecord::from_open_asset_record_with_tracing(
+                    prng,
+                    oar,
+                    policies,
+                    identity.user_sec_key,
+                    identity.credential,
+                    identity.commitment_key,
+                ),
+            };
+            inputs.push(record.c(d!(XfrNoteBuilderError::InvalidInput(i)))?);
+            signers.push(signer);
+        }
+
+        let mut outputs = Vec::with_capacity(self.outputs.len());
+        for (i, output) in self.outputs.into_iter().enumerate() {
+            let record = match output {
+                OutputSource::Record(record) => Ok(record),
+                OutputSource::Template {
+                    template,
+                    identity: None,
+                } => AssetRecord::from_template_no_identity_tracing(prng, &template),
+                OutputSource::Template {
+                    template,
+                    identity: Some(identity),
+                } => AssetRecord::from_template_with_identity_tracing(
+                    prng,
+                    &template,
+                    identity.user_sec_key,
+                    identity.credential,
+                    identity.commitment_key,
+                ),
+            };
+            outputs.push(record.c(d!(XfrNoteBuilderError::InvalidOutput(i)))?);
+        }
+
+        gen_xfr_note_with_signers(prng, &inputs, &outputs, &signers).c(d!())
+    }
+}
+
+fn traces_identity(policies: &TracingPolicies) -> bool {
+    policies
+        .get_policies()
+        .iter()
+        .any(|policy| policy.identity_tracing.is_some())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::{open_blind_asset_record, AssetRecordType},
+        balance::AssetImbalance,
+        builder::{XfrNoteBuilder, XfrNoteBuilderError},
+        sig::{Signer, XfrKeyPair, XfrPublicKey, XfrSignature},
+        structs::{AssetRecord, AssetRecordTemplate, AssetType, OpenAssetRecord},
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    fn open_record<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        keypair: &XfrKeyPair,
+        amount: u64,
+        asset_type: AssetType,
+    ) -> OpenAssetRecord {
+        let template = AssetRecordTemplate::with_no_asset_tracing(
+            amount,
+            asset_type,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            keypair.pub_key,
+        );
+        let record = AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap();
+        open_blind_asset_record(
+            &record.open_asset_record.blind_asset_record,
+            &record.owner_memo,
+            keypair,
+        )
+        .unwrap()
+    }
+
+    fn template(keypair: &XfrKeyPair, amount: u64, asset_type: AssetType) -> AssetRecordTemplate {
+        AssetRecordTemplate::with_no_asset_tracing(
+            amount,
+            asset_type,
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            keypair.pub_key,
+        )
+    }
+
+    // A signer that keeps its key pair out of the builder, as a hardware wallet would.
+    struct Wallet(XfrKeyPair);
+
+    impl Signer for Wallet {
+        fn public_key(&self) -> XfrPublicKey {
+            self.0.pub_key
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<XfrSignature> {
+            self.0.sign(message)
+        }
+    }
+
+    #[test]
+    fn xfr_note_builder() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let alice = XfrKeyPair::generate(&mut prng);
+        let bob = XfrKeyPair::generate(&mut prng);
+        let asset_type = AssetType::from_identical_byte(0);
+        let other_type = AssetType::from_identical_byte(1);
+
+        let note = XfrNoteBuilder::new()
+            .input(open_record(&mut prng, &alice, 10, asset_type), &alice)
+            .input(open_record(&mut prng, &bob, 5, other_type), &bob)
+            .output(template(&bob, 7, asset_type))
+            .output(template(&alice, 3, asset_type))
+            .output(template(&alice, 5, other_type))
+            .build(&mut prng)
+            .unwrap();
+        let policies = XfrNotePolicies::empty_policies(2, 3);
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &policies.to_ref()).is_ok());
+
+        // an input signed by a wallet
+        let wallet = Wallet(XfrKeyPair::generate(&mut prng));
+        let note = XfrNoteBuilder::new()
+            .input(open_record(&mut prng, &alice, 10, asset_type), &alice)
+            .input(open_record(&mut prng, &wallet.0, 5, asset_type), &wallet)
+            .output(template(&bob, 15, asset_type))
+            .build(&mut prng)
+            .unwrap();
+        let policies = XfrNotePolicies::empty_policies(2, 1);
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &policies.to_ref()).is_ok());
+
+        msg_eq!(
+            XfrNoteBuilderError::NoInputs,
+            XfrNoteBuilder::new()
+                .output(template(&bob, 7, asset_type))
+                .validate()
+                .unwrap_err()
+        );
+
+        msg_eq!(
+            XfrNoteBuilderError::InputSignerMismatch(1),
+            XfrNoteBuilder::new()
+                .input(open_record(&mut prng, &alice, 10, asset_type), &alice)
+                .input(open_record(&mut prng, &alice, 5, asset_type), &bob)
+                .output(template(&bob, 15, asset_type))
+                .validate()
+                .unwrap_err()
+        );
+
+        msg_eq!(
+            XfrNoteBuilderError::OutputAssetTypeNotInInputs(1),
+            XfrNoteBuilder::new()
+                .input(open_record(&mut prng, &alice, 10, asset_type), &alice)
+                .output(template(&bob, 10, asset_type))
+                .output(template(&bob, 0, other_type))
+                .validate()
+                .unwrap_err()
+        );
+
+        let err = XfrNoteBuilder::new()
+            .input(open_record(&mut prng, &alice, 10, asset_type), &alice)
+            .output(template(&bob, 11, asset_type))
+            .build(&mut prng)
+            .unwrap_err();
+        msg_eq!(XfrNoteBuilderError::UnbalancedAssetType(asset_type), err);
+        msg_eq!(
+            AssetImbalance {
+                asset: asset_type,
+                input: 10,
+                output: 11,
+            },
+            err
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! The canonical form of confidential transfer notes.
+//!
+//! A note can have several encodings that verify, e.g., an Ed25519 point or a Ristretto scalar
+//! can be encoded in more than one way, a secp256k1 signature `(r, s)` is also valid as
+//! `(r, -s)`, and the signatures of a same signer can be swapped. Downstream indices that
+//! hash notes would see such notes as distinct. The canonical form of a note is the single
+//! encoding that they should use.
+//!
+//! The body of a note is signed by its senders, so only the senders can fix a non-canonical
+//! body, while anyone can put the multisig and the account authorizations of a valid note in
+//! canonical form.
+
+use crate::xfr::{
+    body_tree::XfrBodyTree,
+    multisig::MultiSigAuthorization,
+    sig::{KeyType, XfrPublicKey},
+    structs::{AssetTypeAndAmountProof, OwnerMemo, XfrBody, XfrNote},
+};
+use zei_algebra::{prelude::*, ristretto::CompressedEdwardsY};
+
+impl XfrBody {
+    /// Check if the body has its canonical encoding, i.e., the Ed25519 public keys and
+    /// owner memo shares have their canonical encodings, and the scalars of the proofs are
+    /// reduced.
+    pub fn is_canonical(&self) -> bool {
+        let records_canonical = self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .all(|record| record.public_key.is_canonical());
+        let memos_canonical = self
+            .owners_memos
+            .iter()
+            .flatten()
+            .all(owner_memo_is_canonical);
+        let asset_proof_canonical = match &self.proofs.asset_type_and_amount_proof {
+            AssetTypeAndAmountProof::ConfAsset(proof) => proof.is_canonical(),
+            AssetTypeAndAmountProof::ConfAll(proofs) => proofs.1.is_canonical(),
+            _ => true,
+        };
+        let tracing_proofs_canonical = self
+            .proofs
+            .asset_tracing_proof
+            .asset_type_and_amount_proofs
+            .iter()
+            .all(|proof| proof.is_canonical());
+        records_canonical && memos_canonical && asset_proof_canonical && tracing_proofs_canonical
+    }
+}
+
+impl XfrNote {
+    /// Check if the note has its canonical encoding: the body must be canonical, and the
+    /// multisig and the account authorizations must be in the form returned by
+    /// [`XfrNote::normalize`].
+    pub fn is_canonical(&self) -> bool {
+        if !self.body.is_canonical() {
+            return false;
+        }
+        match XfrBodyTree::new(&self.body) {
+            Ok(tree) => {
+                let message = tree.root().signed_message();
+                let pubkeys = self.input_public_keys();
+                self.multisig.is_canonical(&pubkeys, &message)
+                    && self
+                        .account_authorizations
+                        .iter()
+                        .all(|authorization| authorization_is_canonical(authorization, &message))
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Put the multisig and the account authorizations of the note in canonical form, which
+    /// keeps a valid note valid.
+    /// Return an error if the body is not canonical, since it cannot be changed without
+    /// the senders, or if a signature is invalid.
+    pub fn normalize(&mut self) -> Result<()> {
+        if !self.body.is_canonical() {
+            return Err(eg!(ZeiError::NonCanonicalEncodingError));
+        }
+        let message = XfrBodyTree::new(&self.body)
+            .c(d!())?
+            .root()
+            .signed_message();
+        let pubkeys = self.input_public_keys();
+        self.multisig = self.multisig.normalize(&pubkeys, &message).c(d!())?;
+        for authorization in self.account_authorizations.iter_mut() {
+            for (index, signature) in authorization.bundle.signatures.iter_mut() {
+                let key = authorization
+                    .policy
+                    .keys()
+                    .get(*index as usize)
+                    .c(d!(ZeiError::SignatureError))?;
+                *signature = signature.normalize(key, &message).c(d!())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn input_public_keys(&self) -> Vec<&XfrPublicKey> {
+        self.body
+            .inputs
+            .iter()
+            .map(|input| &input.public_key)
+            .collect_vec()
+    }
+}
+
+// The signatures of an account authorization must be canonical, e.g., a secp256k1 `(r, s)`
+// rather than `(r, -s)`.
+fn authorization_is_canonical(authorization: &MultiSigAuthorization, message: &[u8]) -> bool {
+    authorization
+        .bundle
+        .signatures
+        .iter()
+        .all(
+            |(index, signature)| match authorization.policy.keys().get(*index as usize) {
+                Some(key) => signature.is_canonical(key, message),
+                None => false,
+            },
+        )
+}
+
+// The share of an Ed25519 memo is an Edwards point, while a secp256k1 share is parsed.
+fn owner_memo_is_canonical(memo: &OwnerMemo) -> bool {
+    match memo.key_type {
+        KeyType::Ed25519 => CompressedEdwardsY::from_slice(&memo.blind_share_bytes).is_canonical(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        sig::{XfrKeyPair, XfrSignature},
+        structs::{AssetRecordTemplate, AssetType},
+        tests::create_xfr,
+        verify_transfer_multisig, verify_xfr_note_strict, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use libsecp256k1::RecoveryId;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn canonical_note() {
+        let mut prng = test_rng();
+        let asset_type = AssetType::from_identical_byte(1);
+        let sender = XfrKeyPair::generate_secp256k1(&mut prng);
+        let receiver = XfrKeyPair::generate_ed25519(&mut prng);
+        let template = |amount, keypair: &XfrKeyPair| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                keypair.get_pk(),
+            )
+        };
+        let inputs = [template(10, &sender), template(20, &sender)];
+        let outputs = [template(30, &receiver)];
+        let (note, _, _) = create_xfr(&mut prng, &inputs, &outputs, &[&sender, &sender]);
+        assert!(note.is_canonical());
+        let mut params = BulletproofParams::default();
+        let policies = XfrNotePolicies::empty_policies(2, 1);
+
+        // a high s and a swapped recovery id verify, but are not canonical
+        let mut malleated = note.clone();
+        for sig in malleated.multisig.signatures.iter_mut() {
+            if let XfrSignature::Secp256k1(sign, rec) = sig {
+                sign.s = -sign.s;
+                *rec = RecoveryId::parse(rec.serialize() ^ 1).unwrap();
+            }
+        }
+        assert_ne!(malleated, note);
+        assert!(verify_transfer_multisig(&malleated).is_ok());
+        assert!(!malleated.is_canonical());
+        assert!(
+            verify_xfr_note_strict(&mut prng, &mut params, &malleated, &policies.to_ref()).is_err()
+        );
+
+        malleated.normalize().unwrap();
+        assert_eq!(malleated, note);
+        assert!(
+            verify_xfr_note_strict(&mut prng, &mut params, &malleated, &policies.to_ref()).is_ok()
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Incremental digests of notes.
+//!
+//! A note is signed over a digest computed field by field, in a fixed order and with each
+//! field prefixed by a label and its length, instead of over its full serialization. Signing
+//! then does not need a serialized copy of a large note in memory, and the signatures do not
+//! depend on how serde encodes the note.
+
+use crate::errors::ZeiError;
+use crate::xfr::{
+    sampling::NoteDigest,
+    structs::{BlindAssetRecord, XfrAmount, XfrAssetType},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io;
+use zei_algebra::prelude::*;
+
+/// The tag of a confidential field.
+const CONFIDENTIAL_TAG: u8 = 0u8;
+/// The tag of a non-confidential field.
+const NON_CONFIDENTIAL_TAG: u8 = 1u8;
+
+/// The incremental hasher of the fields of a note, whose output is a [`NoteDigest`].
+#[derive(Clone, Debug)]
+pub struct NoteDigester {
+    hasher: Sha256,
+}
+
+impl NoteDigester {
+    /// Start the digest of a kind of note, given by its domain separator.
+    pub fn new(domain: &'static [u8]) -> Self {
+        let mut digester = NoteDigester {
+            hasher: Sha256::new(),
+        };
+        digester.append_bytes(b"domain", domain);
+        digester
+    }
+
+    /// Append a field given by its bytes.
+    pub fn append_bytes(&mut self, label: &'static [u8], bytes: &[u8]) -> &mut Self {
+        self.append_len_prefixed(label);
+        self.append_len_prefixed(bytes);
+        self
+    }
+
+    /// Append an integer field.
+    pub fn append_u64(&mut self, label: &'static [u8], value: u64) -> &mut Self {
+        self.append_bytes(label, &value.to_le_bytes())
+    }
+
+    /// Append a scalar field, by its canonical encoding.
+    pub fn append_scalar<S: Scalar>(&mut self, label: &'static [u8], scalar: &S) -> &mut Self {
+        self.append_bytes(label, &scalar.to_bytes())
+    }
+
+    /// Append a field that has no canonical encoding of its own, such as a proof, by streaming
+    /// its bincode serialization into the hasher.
+    pub fn append_serialized<T: Serialize>(
+        &mut self,
+        label: &'static [u8],
+        value: &T,
+    ) -> Result<&mut Self> {
+        let len = bincode::serialized_size(value).c(d!(ZeiError::SerializationError))?;
+        self.append_len_prefixed(label);
+        self.hasher.update(&len.to_le_bytes());
+        bincode::serialize_into(HasherWriter(&mut self.hasher), value)
+            .c(d!(ZeiError::SerializationError))?;
+        Ok(self)
+    }
+
+    /// Append the fields of a value.
+    pub fn append_fields<T: DigestFields + ?Sized>(&mut self, value: &T) -> Result<&mut Self> {
+        value.digest_fields(self).c(d!())?;
+        Ok(self)
+    }
+
+    /// Return the digest.
+    pub fn finalize(self) -> NoteDigest {
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&self.hasher.finalize());
+        digest
+    }
+
+    fn append_len_prefixed(&mut self, bytes: &[u8]) {
+        self.hasher.update(&(bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+}
+
+/// The writer that feeds the bytes written to it into a hasher.
+struct HasherWriter<'a>(&'a mut Sha256);
+
+impl<'a> io::Write for HasherWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The trait for the parts of a note that are appended to a [`NoteDigester`] field by field.
+pub trait DigestFields {
+    /// Append the fields, in a fixed order.
+    fn digest_fields(&self, digester: &mut NoteDigester) -> Result<()>;
+
+    /// Return the digest of the fields alone, under the given domain separator.
+    fn note_digest(&self, domain: &'static [u8]) -> Result<NoteDigest> {
+        let mut digester = NoteDigester::new(domain);
+        self.digest_fields(&mut digester).c(d!())?;
+        Ok(digester.finalize())
+    }
+}
+
+impl DigestFields for BlindAssetRecord {
+    fn digest_fields(&self, digester: &mut NoteDigester) -> Result<()> {
+        match &self.amount {
+            XfrAmount::Confidential((low, high)) => digester
+                .append_bytes(b"amount", &[CONFIDENTIAL_TAG])
+                .append_bytes(b"amount low", low.0.as_bytes())
+                .append_bytes(b"amount high", high.0.as_bytes()),
+            XfrAmount::NonConfidential(amount) => digester
+                .append_bytes(b"amount", &[NON_CONFIDENTIAL_TAG])
+                .append_u64(b"amount value", *amount),
+        };
+        match &self.asset_type {
+            XfrAssetType::Confidential(com) => digester
+                .append_bytes(b"asset type", &[CONFIDENTIAL_TAG])
+                .append_bytes(b"asset type commitment", com.0.as_bytes()),
+            XfrAssetType::NonConfidential(asset_type) => digester
+                .append_bytes(b"asset type", &[NON_CONFIDENTIAL_TAG])
+                .append_bytes(b"asset type value", &asset_type.0),
+        };
+        digester.append_bytes(b"public key", &self.public_key.zei_to_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DigestFields, HasherWriter, NoteDigester};
+    use crate::xfr::{
+        sig::XfrKeyPair,
+        structs::{AssetType, BlindAssetRecord, XfrAmount, XfrAssetType},
+    };
+    use ark_std::test_rng;
+    use sha2::{Digest, Sha256};
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn record_digest() {
+        let mut prng = test_rng();
+        let record = BlindAssetRecord {
+            amount: XfrAmount::NonConfidential(10),
+            asset_type: XfrAssetType::NonConfidential(AssetType::from_identical_byte(1)),
+            public_key: XfrKeyPair::generate(&mut prng).get_pk(),
+        };
+        let digest = record.note_digest(b"test").unwrap();
+        assert_eq!(digest, record.clone().note_digest(b"test").unwrap());
+        assert_ne!(digest, record.note_digest(b"other test").unwrap());
+
+        let mut other = record.clone();
+        other.amount = XfrAmount::NonConfidential(11);
+        assert_ne!(digest, other.note_digest(b"test").unwrap());
+
+        let mut other = record;
+        other.public_key = XfrKeyPair::generate(&mut prng).get_pk();
+        assert_ne!(digest, other.note_digest(b"test").unwrap());
+    }
+
+    #[test]
+    fn serialized_field_is_streamed() {
+        let value = vec![7u64; 100];
+        let bytes = bincode::serialize(&value).unwrap();
+
+        let mut hasher = Sha256::new();
+        bincode::serialize_into(HasherWriter(&mut hasher), &value).unwrap();
+        let mut expected = Sha256::new();
+        expected.update(&bytes);
+        assert_eq!(hasher.finalize(), expected.finalize());
+
+        // a serialized field is digested as its bytes, without keeping them in memory.
+        let mut d1 = NoteDigester::new(b"test");
+        d1.append_serialized(b"value", &value).unwrap();
+        let mut d2 = NoteDigester::new(b"test");
+        d2.append_bytes(b"value", &bytes);
+        assert_eq!(d1.finalize(), d2.finalize());
+    }
+}
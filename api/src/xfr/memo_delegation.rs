@@ -0,0 +1,139 @@
+//! Owner memos whose decryption their owner can delegate, e.g., to an auditor.
+//!
+//! Next to the `OwnerMemo` of an output, the sender can attach a [`DelegableMemoLock`], which
+//! encrypts the same amount and asset type under the proxy re-encryption key of the owner and
+//! a scope, e.g., a period. To let an auditor read the memos of a scope, the owner hands a share
+//! of a re-encryption key for it to each of several proxies, which turn the locks of the scope
+//! into shares of [`DelegatedMemoLock`]s for the auditor, and the auditor decrypts from a
+//! threshold of them. The shares of the re-encryption key are useless for other scopes and
+//! other parties. The auditor, alone or with fewer than the threshold of proxies, learns
+//! nothing about the secret key of the owner, but the auditor colluding with the threshold of
+//! proxies recovers it, so the proxies must be chosen independently of the auditor.
+
+use crate::xfr::{
+    memo_padding::MemoPaddingPolicy,
+    structs::{AssetType, OwnerMemo},
+};
+use zei_algebra::prelude::*;
+use zei_crypto::basic::proxy_reencryption::{
+    pre_decrypt, pre_decrypt_reencrypted, pre_encrypt, pre_reencrypt, PreCiphertext, PrePublicKey,
+    PreReCiphertextShare, PreReKeyShare, PreSecretKey,
+};
+
+/// The lock of the amount and the asset type of an output for its owner, which the owner can
+/// delegate within its scope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegableMemoLock(pub PreCiphertext);
+
+/// A lock re-encrypted for the delegatee of the owner, from the shares of a threshold of
+/// proxies.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegatedMemoLock(pub Vec<PreReCiphertextShare>);
+
+impl DelegableMemoLock {
+    /// Lock the confidential amount, the confidential asset type, or both, of an output for
+    /// its owner within a scope.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: Option<u64>,
+        asset_type: Option<&AssetType>,
+        owner_key: &PrePublicKey,
+        scope: &[u8],
+        padding: &MemoPaddingPolicy,
+    ) -> Result<Self> {
+        let plaintext = OwnerMemo::encode_plaintext(amount, asset_type).c(d!())?;
+        let ctext =
+            pre_encrypt(prng, owner_key, scope, &padding.pad(&plaintext).c(d!())?).c(d!())?;
+        Ok(DelegableMemoLock(ctext))
+    }
+
+    /// Return the scope of the lock.
+    pub fn scope(&self) -> &[u8] {
+        &self.0.scope
+    }
+
+    /// Decrypt the lock with the secret key of the owner, and return the amount and the
+    /// asset type that it encrypts.
+    pub fn decrypt(&self, owner_key: &PreSecretKey) -> Result<(Option<u64>, Option<AssetType>)> {
+        let padded = pre_decrypt(owner_key, &self.0).c(d!(ZeiError::DecryptionError))?;
+        OwnerMemo::parse_plaintext(&padded).c(d!())
+    }
+
+    /// Re-encrypt the lock into a share for the delegatee of a share of a re-encryption key of
+    /// its scope, as a proxy.
+    pub fn reencrypt(&self, rekey: &PreReKeyShare) -> Result<PreReCiphertextShare> {
+        pre_reencrypt(rekey, &self.0).c(d!())
+    }
+}
+
+impl DelegatedMemoLock {
+    /// Decrypt the lock with the secret key of the delegatee, and return the amount and the
+    /// asset type that it encrypts. It requires the shares of a threshold of proxies.
+    pub fn decrypt(
+        &self,
+        delegatee_key: &PreSecretKey,
+    ) -> Result<(Option<u64>, Option<AssetType>)> {
+        let padded =
+            pre_decrypt_reencrypted(delegatee_key, &self.0).c(d!(ZeiError::DecryptionError))?;
+        OwnerMemo::parse_plaintext(&padded).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DelegableMemoLock, DelegatedMemoLock};
+    use crate::xfr::{memo_padding::MemoPaddingPolicy, structs::AssetType};
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+    use zei_crypto::basic::proxy_reencryption::{pre_key_gen, pre_rekey};
+
+    #[test]
+    fn delegated_owner_memo() {
+        let mut prng = test_rng();
+        let padding = MemoPaddingPolicy::default();
+        let asset_type = AssetType::from_identical_byte(5);
+        let (owner_sk, owner_pk) = pre_key_gen(&mut prng);
+        let (auditor_sk, auditor_pk) = pre_key_gen(&mut prng);
+
+        let lock = DelegableMemoLock::new(
+            &mut prng,
+            Some(100),
+            Some(&asset_type),
+            &owner_pk,
+            b"2022-Q1",
+            &padding,
+        )
+        .unwrap();
+        assert_eq!(lock.scope(), b"2022-Q1");
+        assert_eq!(
+            lock.decrypt(&owner_sk).unwrap(),
+            (Some(100), Some(asset_type))
+        );
+        msg_eq!(
+            ZeiError::DecryptionError,
+            lock.decrypt(&auditor_sk).unwrap_err()
+        );
+
+        let rekeys = pre_rekey(&mut prng, &owner_sk, &auditor_pk, b"2022-Q1", 2, 3).unwrap();
+        let shares = rekeys
+            .iter()
+            .map(|rekey| lock.reencrypt(rekey).unwrap())
+            .collect_vec();
+        let delegated = DelegatedMemoLock(shares[1..].to_vec());
+        assert_eq!(
+            delegated.decrypt(&auditor_sk).unwrap(),
+            (Some(100), Some(asset_type))
+        );
+        assert!(DelegatedMemoLock(shares[..1].to_vec())
+            .decrypt(&auditor_sk)
+            .is_err());
+
+        // the amount alone, and a lock of another scope
+        let lock =
+            DelegableMemoLock::new(&mut prng, Some(7), None, &owner_pk, b"2022-Q2", &padding)
+                .unwrap();
+        assert_eq!(lock.decrypt(&owner_sk).unwrap(), (Some(7), None));
+        assert!(lock.reencrypt(&rekeys[0]).is_err());
+        assert!(DelegableMemoLock::new(&mut prng, None, None, &owner_pk, b"", &padding).is_err());
+    }
+}
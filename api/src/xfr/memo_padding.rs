@@ -0,0 +1,132 @@
+//! Padding of the owner memos to a small set of fixed sizes.
+//!
+//! The length of an encrypted owner memo is the length of its plaintext plus a constant
+//! overhead, which lets an observer tell apart memos carrying different information. A padding
+//! policy pads every plaintext to the smallest of its bucket sizes that fits it, with a `0x80`
+//! byte followed by zero bytes, so that memos of the same bucket cannot be told apart.
+//!
+//! The buckets are at least [`MIN_MEMO_PADDING_BUCKET`] bytes long, which is more than any
+//! unpadded plaintext, so that padded and unpadded memos are parsed unambiguously and the
+//! scanning of the existing memos is unchanged.
+
+use zei_algebra::prelude::*;
+
+/// The padding byte that marks the end of the plaintext.
+const PADDING_MARKER: u8 = 0x80;
+
+/// The smallest bucket size, larger than the plaintext of any unpadded memo.
+pub const MIN_MEMO_PADDING_BUCKET: usize = 96;
+
+/// The bucket sizes of the standard padding policy.
+pub const STANDARD_MEMO_PADDING_BUCKETS: [usize; 2] = [128, 256];
+
+/// The bucket sizes to which the owner memos are padded.
+/// The default policy does not pad the memos.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoPaddingPolicy {
+    buckets: Vec<usize>,
+}
+
+impl MemoPaddingPolicy {
+    /// Create a padding policy with the given bucket sizes.
+    /// Return an error if there is no bucket, or if a bucket is smaller than
+    /// [`MIN_MEMO_PADDING_BUCKET`].
+    pub fn new(mut buckets: Vec<usize>) -> Result<Self> {
+        if buckets.is_empty() || buckets.iter().any(|size| *size < MIN_MEMO_PADDING_BUCKET) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        buckets.sort_unstable();
+        buckets.dedup();
+        Ok(MemoPaddingPolicy { buckets })
+    }
+
+    /// Return the standard padding policy, with the [`STANDARD_MEMO_PADDING_BUCKETS`] sizes.
+    pub fn standard() -> Self {
+        MemoPaddingPolicy {
+            buckets: STANDARD_MEMO_PADDING_BUCKETS.to_vec(),
+        }
+    }
+
+    /// Return the bucket sizes, in increasing order.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets
+    }
+
+    /// Pad a plaintext to the smallest bucket that fits it and the padding marker.
+    /// The plaintext is returned as is if the policy does not pad the memos, and an error
+    /// is returned if it does not fit in any bucket.
+    pub fn pad(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        if self.buckets.is_empty() {
+            return Ok(plaintext.to_vec());
+        }
+        let size = self
+            .buckets
+            .iter()
+            .find(|size| **size >= MIN_MEMO_PADDING_BUCKET && **size > plaintext.len())
+            .c(d!(ZeiError::ParameterError))?;
+        let mut padded = Vec::with_capacity(*size);
+        padded.extend_from_slice(plaintext);
+        padded.push(PADDING_MARKER);
+        padded.resize(*size, 0u8);
+        Ok(padded)
+    }
+}
+
+/// Check that a decrypted memo plaintext may be padded, from its length.
+pub(crate) fn may_be_padded(len: usize) -> bool {
+    len >= MIN_MEMO_PADDING_BUCKET
+}
+
+/// Remove the padding of a decrypted memo plaintext, if any. A plaintext of one of the
+/// `unpadded_lens` lengths is returned as is.
+pub(crate) fn unpad<'a>(bytes: &'a [u8], unpadded_lens: &[usize]) -> Result<&'a [u8]> {
+    if unpadded_lens.contains(&bytes.len()) {
+        return Ok(bytes);
+    }
+    if !may_be_padded(bytes.len()) {
+        return Err(eg!(ZeiError::InconsistentStructureError));
+    }
+    let end = bytes
+        .iter()
+        .rposition(|byte| *byte != 0u8)
+        .c(d!(ZeiError::InconsistentStructureError))?;
+    if bytes[end] != PADDING_MARKER || !unpadded_lens.contains(&end) {
+        return Err(eg!(ZeiError::InconsistentStructureError));
+    }
+    Ok(&bytes[..end])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xfr::memo_padding::{unpad, MemoPaddingPolicy, MIN_MEMO_PADDING_BUCKET};
+
+    #[test]
+    fn pad_and_unpad() {
+        assert!(MemoPaddingPolicy::new(vec![]).is_err());
+        assert!(MemoPaddingPolicy::new(vec![MIN_MEMO_PADDING_BUCKET - 1]).is_err());
+        let policy = MemoPaddingPolicy::new(vec![256, 128, 128]).unwrap();
+        assert_eq!(policy.buckets(), &[128, 256]);
+
+        // plaintexts ending with the padding marker or zeros are recovered
+        for plaintext in [
+            vec![1u8; 8],
+            vec![0u8; 40],
+            vec![0x80u8; 127],
+            vec![7u8; 200],
+        ] {
+            let padded = policy.pad(&plaintext).unwrap();
+            let expected = if plaintext.len() < 128 { 128 } else { 256 };
+            assert_eq!(padded.len(), expected);
+            let lens = [plaintext.len()];
+            assert_eq!(unpad(&padded, &lens).unwrap(), plaintext.as_slice());
+            assert_eq!(unpad(&plaintext, &lens).unwrap(), plaintext.as_slice());
+            assert!(unpad(&padded, &[plaintext.len() - 1]).is_err());
+        }
+        assert!(policy.pad(&[0u8; 256]).is_err());
+
+        // the default policy does not pad
+        let plaintext = [3u8; 40];
+        let unpadded = MemoPaddingPolicy::default().pad(&plaintext).unwrap();
+        assert_eq!(unpadded, plaintext.to_vec());
+    }
+}
@@ -1,10 +1,11 @@
-use serde::ser::Serialize;
 use zei_algebra::{
-    collections::HashMap,
     prelude::*,
     ristretto::{CompressedRistretto, RistrettoScalar},
 };
-use zei_crypto::basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto};
+use zei_crypto::{
+    basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto},
+    bulletproofs::amount::{link_limb_commitments, AmountCommitment, AmountOpening},
+};
 
 /// Module for asset mixing.
 pub mod asset_mixer;
@@ -12,12 +13,43 @@ pub mod asset_mixer;
 pub mod asset_record;
 /// Module for asset tracing.
 pub mod asset_tracer;
+/// Module for the per-asset accounting of the amounts of transfers.
+pub mod balance;
+/// Module for the Merkle tree of the fields of transfer bodies.
+pub mod body_tree;
+/// Module for building transfer notes from templates.
+pub mod builder;
+/// Module for the canonical form of transfer notes.
+pub mod canonical;
+/// Module for incremental digests of notes, which are signed instead of their serialization.
+pub mod digest;
+/// Module for owner memos whose decryption can be delegated with proxy re-encryption.
+pub mod memo_delegation;
+/// Module for padding the owner memos to fixed sizes.
+pub mod memo_padding;
+/// Module for multisig account addresses and their authorizations.
+pub mod multisig;
+/// Module for owner memos whose keys are derived from the context of their outputs.
+pub mod owner_memo_kdf;
+/// Module for partially signed transfers, merged from the signatures of several co-signers.
+pub mod partially_signed;
 /// Module for zero-knowledge proofs.
 pub mod proofs;
+/// Module for auditable sampling of notes.
+pub mod sampling;
 /// Module for signatures.
 pub mod sig;
+/// Module for signing sessions of transfers and threshold signatures with remote co-signing
+/// services.
+pub mod signing_session;
+/// Module for spot-checking notes, for light clients.
+pub mod spot_check;
+/// Module for stealth addresses.
+pub mod stealth;
 /// Module for shared structures.
 pub mod structs;
+/// Module for view keys.
+pub mod view_key;
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -27,18 +59,20 @@ use crate::setup::BulletproofParams;
 
 use self::{
     asset_mixer::{
-        batch_verify_asset_mixing, prove_asset_mixing, AssetMixProof, AssetMixingInstance,
+        batch_verify_asset_mixing, prove_asset_mixing_with_rng, AssetMixProof, AssetMixingInstance,
     },
+    balance::BalanceSheet,
+    body_tree::XfrBodyTree,
+    multisig::verify_account_authorizations,
     proofs::{
-        asset_amount_tracing_proofs, asset_proof, batch_verify_confidential_amount,
-        batch_verify_confidential_asset, batch_verify_tracer_tracing_proof, gen_range_proof,
+        asset_amount_tracing_proofs, asset_proof, batch_verify_asset_tracing,
+        batch_verify_confidential_amount, batch_verify_confidential_asset,
+        batch_verify_tracer_tracing_proof, gen_range_proof, verify_identity_tracing_proofs,
     },
-    sig::{XfrKeyPair, XfrMultiSig, XfrPublicKey},
+    sig::{Signer, XfrKeyPair, XfrMultiSig, XfrPublicKey},
     structs::*,
 };
 
-const POW_2_32: u64 = 0xFFFF_FFFFu64 + 1;
-
 #[derive(Clone, Copy, Debug)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::enum_variant_names)]
@@ -179,17 +213,37 @@ pub fn gen_xfr_note<R: CryptoRng + RngCore>(
     inputs: &[AssetRecord],
     outputs: &[AssetRecord],
     input_key_pairs: &[&XfrKeyPair],
+) -> Result<XfrNote> {
+    let signers = input_key_pairs
+        .iter()
+        .map(|kp| -> &dyn Signer { *kp })
+        .collect_vec();
+    gen_xfr_note_with_signers(prng, inputs, outputs, &signers).c(d!())
+}
+
+/// Generate a confidential transfer note, whose inputs are signed by the signers of their
+/// owners, e.g., hardware wallets, instead of key pairs.
+pub fn gen_xfr_note_with_signers<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    inputs: &[AssetRecord],
+    outputs: &[AssetRecord],
+    input_signers: &[&dyn Signer],
 ) -> Result<XfrNote> {
     if inputs.is_empty() {
         return Err(eg!(ZeiError::ParameterError));
     }
 
-    check_keys(inputs, input_key_pairs).c(d!())?;
+    check_signers(inputs, input_signers).c(d!())?;
 
     let body = gen_xfr_body(prng, inputs, outputs).c(d!())?;
-    let multisig = compute_transfer_multisig(&body, input_key_pairs).c(d!())?;
+    let root = XfrBodyTree::new(&body).c(d!())?.root();
+    let multisig = XfrMultiSig::sign_with_signers(input_signers, &root.signed_message()).c(d!())?;
 
-    Ok(XfrNote { body, multisig })
+    Ok(XfrNote {
+        body,
+        multisig,
+        account_authorizations: vec![],
+    })
 }
 
 /// Generate the confidential transfer body.
@@ -244,6 +298,26 @@ pub fn gen_xfr_body<R: CryptoRng + RngCore>(
     prng: &mut R,
     inputs: &[AssetRecord],
     outputs: &[AssetRecord],
+) -> Result<XfrBody> {
+    gen_xfr_body_with_optional_params(prng, None, inputs, outputs).c(d!())
+}
+
+/// Generate the confidential transfer body as in [`gen_xfr_body`], with the range proofs of
+/// the backend of `params`.
+pub fn gen_xfr_body_with_params<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &BulletproofParams,
+    inputs: &[AssetRecord],
+    outputs: &[AssetRecord],
+) -> Result<XfrBody> {
+    gen_xfr_body_with_optional_params(prng, Some(params), inputs, outputs).c(d!())
+}
+
+fn gen_xfr_body_with_optional_params<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: Option<&BulletproofParams>,
+    inputs: &[AssetRecord],
+    outputs: &[AssetRecord],
 ) -> Result<XfrBody> {
     if inputs.is_empty() {
         return Err(eg!(ZeiError::ParameterError));
@@ -267,14 +341,20 @@ pub fn gen_xfr_body<R: CryptoRng + RngCore>(
     let asset_amount_proof = if single_asset {
         gen_xfr_proofs_single_asset(
             prng,
+            params,
             open_inputs.as_slice(),
             open_outputs.as_slice(),
             xfr_type,
         )
         .c(d!())?
     } else {
-        gen_xfr_proofs_multi_asset(open_inputs.as_slice(), open_outputs.as_slice(), xfr_type)
-            .c(d!())?
+        gen_xfr_proofs_multi_asset(
+            prng,
+            open_inputs.as_slice(),
+            open_outputs.as_slice(),
+            xfr_type,
+        )
+        .c(d!())?
     };
 
     let asset_type_amount_tracing_proof =
@@ -324,50 +404,50 @@ pub fn gen_xfr_body<R: CryptoRng + RngCore>(
     })
 }
 
-fn check_keys(inputs: &[AssetRecord], input_key_pairs: &[&XfrKeyPair]) -> Result<()> {
-    if inputs.len() != input_key_pairs.len() {
-        return Err(eg!(ZeiError::ParameterError));
+fn check_signers(inputs: &[AssetRecord], input_signers: &[&dyn Signer]) -> Result<()> {
+    if inputs.len() != input_signers.len() {
+        return Err(eg!(ZeiError::LengthMismatch {
+            expected: inputs.len(),
+            actual: input_signers.len(),
+        }));
     }
-    for (input, key) in inputs.iter().zip(input_key_pairs.iter()) {
+    for (index, (input, signer)) in inputs.iter().zip(input_signers.iter()).enumerate() {
         let inkey = &input.open_asset_record.blind_asset_record.public_key;
-        if inkey != &key.pub_key {
-            return Err(eg!(ZeiError::ParameterError));
+        if *inkey != signer.public_key() {
+            return Err(eg!(ZeiError::InputError {
+                index,
+                source: Box::new(ZeiError::ParameterError),
+            }));
         }
     }
     Ok(())
 }
 
-fn gen_xfr_proofs_multi_asset(
+fn gen_xfr_proofs_multi_asset<R: CryptoRng + RngCore>(
+    prng: &mut R,
     inputs: &[&OpenAssetRecord],
     outputs: &[&OpenAssetRecord],
     xfr_type: XfrType,
 ) -> Result<AssetTypeAndAmountProof> {
-    let pow2_32 = RistrettoScalar::from(POW_2_32);
-
-    let mut ins = vec![];
-
-    for x in inputs.iter() {
-        ins.push((
-            x.amount,
-            x.asset_type.as_scalar(),
-            x.amount_blinds.0.add(&pow2_32.mul(&x.amount_blinds.1)),
-            x.type_blind,
-        ));
-    }
-
-    let mut out = vec![];
-    for x in outputs.iter() {
-        out.push((
+    let linked = |x: &&OpenAssetRecord| {
+        let opening = AmountOpening {
+            amount: x.amount,
+            blinds: x.amount_blinds,
+        };
+        (
             x.amount,
             x.asset_type.as_scalar(),
-            x.amount_blinds.0.add(&pow2_32.mul(&x.amount_blinds.1)),
+            opening.linked_blind(),
             x.type_blind,
-        ));
-    }
+        )
+    };
+    let ins: Vec<_> = inputs.iter().map(linked).collect();
+    let out: Vec<_> = outputs.iter().map(linked).collect();
 
     match xfr_type {
         XfrType::Confidential_MultiAsset => {
-            let mix_proof = prove_asset_mixing(ins.as_slice(), out.as_slice()).c(d!())?;
+            let mix_proof =
+                prove_asset_mixing_with_rng(prng, ins.as_slice(), out.as_slice()).c(d!())?;
             Ok(AssetTypeAndAmountProof::AssetMix(mix_proof))
         }
         XfrType::NonConfidential_MultiAsset => Ok(AssetTypeAndAmountProof::NoProof),
@@ -377,6 +457,7 @@ fn gen_xfr_proofs_multi_asset(
 
 fn gen_xfr_proofs_single_asset<R: CryptoRng + RngCore>(
     prng: &mut R,
+    params: Option<&BulletproofParams>,
     inputs: &[&OpenAssetRecord],
     outputs: &[&OpenAssetRecord],
     xfr_type: XfrType,
@@ -385,16 +466,18 @@ fn gen_xfr_proofs_single_asset<R: CryptoRng + RngCore>(
 
     match xfr_type {
         XfrType::NonConfidential_SingleAsset => Ok(AssetTypeAndAmountProof::NoProof),
-        XfrType::ConfidentialAmount_NonConfidentialAssetType_SingleAsset => Ok(
-            AssetTypeAndAmountProof::ConfAmount(gen_range_proof(inputs, outputs).c(d!())?),
-        ),
+        XfrType::ConfidentialAmount_NonConfidentialAssetType_SingleAsset => {
+            Ok(AssetTypeAndAmountProof::ConfAmount(
+                gen_range_proof(prng, params, inputs, outputs).c(d!())?,
+            ))
+        }
         XfrType::NonConfidentialAmount_ConfidentialAssetType_SingleAsset => {
             Ok(AssetTypeAndAmountProof::ConfAsset(Box::new(
                 asset_proof(prng, &pc_gens, inputs, outputs).c(d!())?,
             )))
         }
         XfrType::Confidential_SingleAsset => Ok(AssetTypeAndAmountProof::ConfAll(Box::new((
-            gen_range_proof(inputs, outputs).c(d!())?,
+            gen_range_proof(prng, params, inputs, outputs).c(d!())?,
             asset_proof(prng, &pc_gens, inputs, outputs).c(d!())?,
         )))),
         _ => Err(eg!(ZeiError::XfrCreationAssetAmountError)), // Type cannot be multi asset
@@ -403,71 +486,44 @@ fn gen_xfr_proofs_single_asset<R: CryptoRng + RngCore>(
 
 /// Check that for each asset type total input amount == total output amount.
 fn check_asset_amount(inputs: &[AssetRecord], outputs: &[AssetRecord]) -> Result<()> {
-    let mut amounts = HashMap::new();
-
+    let mut balances = BalanceSheet::new();
     for record in inputs.iter() {
-        match amounts.get_mut(&(record.open_asset_record.asset_type)) {
-            None => {
-                amounts.insert(
-                    record.open_asset_record.asset_type,
-                    vec![i128::from(record.open_asset_record.amount)],
-                );
-            }
-            Some(vec) => {
-                vec.push(i128::from(record.open_asset_record.amount));
-            }
-        };
+        let oar = &record.open_asset_record;
+        balances.add_input(oar.asset_type, oar.amount).c(d!())?;
     }
-
     for record in outputs.iter() {
-        match amounts.get_mut(&record.open_asset_record.asset_type) {
-            None => {
-                amounts.insert(
-                    record.open_asset_record.asset_type,
-                    vec![-i128::from(record.open_asset_record.amount)],
-                );
-            }
-            Some(vec) => {
-                vec.push(-i128::from(record.open_asset_record.amount));
-            }
-        };
-    }
-
-    for (_, a) in amounts.iter() {
-        let sum = a.iter().sum::<i128>();
-        if sum != 0i128 {
-            return Err(eg!(ZeiError::XfrCreationAssetAmountError));
-        }
+        let oar = &record.open_asset_record;
+        balances.add_output(oar.asset_type, oar.amount).c(d!())?;
     }
-
-    Ok(())
+    balances
+        .check()
+        .c(d!(ZeiError::XfrCreationAssetAmountError))
 }
 
-/// Compute a multisignature over the body.
+/// Compute a multisignature over the root of the body tree.
 pub(crate) fn compute_transfer_multisig(
     body: &XfrBody,
     keys: &[&XfrKeyPair],
 ) -> Result<XfrMultiSig> {
-    let mut bytes = vec![];
-    body.serialize(&mut rmp_serde::Serializer::new(&mut bytes))
-        .c(d!(ZeiError::SerializationError))?;
-    Ok(XfrMultiSig::sign(&keys, &bytes)?)
+    let root = XfrBodyTree::new(body).c(d!())?.root();
+    Ok(XfrMultiSig::sign(&keys, &root.signed_message())?)
 }
 
-/// Verify the multisignature over the body.
-pub(crate) fn verify_transfer_multisig(xfr_note: &XfrNote) -> Result<()> {
-    let mut bytes = vec![];
-    xfr_note
-        .body
-        .serialize(&mut rmp_serde::Serializer::new(&mut bytes))
-        .c(d!(ZeiError::SerializationError))?;
+/// Verify the multisignature over the root of the body tree, and the authorizations of the
+/// multisig accounts, which sign the same message.
+pub(crate) fn verify_transfer_multisig(xfr_note: &XfrNote) -> core::result::Result<(), ZeiError> {
+    let root = XfrBodyTree::new(&xfr_note.body)
+        .map_err(|_| ZeiError::SerializationError)?
+        .root();
     let pubkeys = xfr_note
         .body
         .inputs
         .iter()
         .map(|input| &input.public_key)
         .collect_vec();
-    xfr_note.multisig.verify(&pubkeys, &bytes)
+    let message = root.signed_message();
+    xfr_note.multisig.check(&pubkeys, &message)?;
+    verify_account_authorizations(xfr_note, &message)
 }
 
 /// Verify a confidential transfer note.
@@ -489,14 +545,48 @@ pub fn batch_verify_xfr_notes<R: CryptoRng + RngCore>(
     policies: &[&XfrNotePoliciesRef<'_>],
 ) -> Result<()> {
     // Verify each note's multisignature, one by one.
-    for xfr_note in notes {
-        verify_transfer_multisig(xfr_note).c(d!())?;
+    for (index, xfr_note) in notes.iter().enumerate() {
+        verify_transfer_multisig(xfr_note).map_err(|source| {
+            eg!(ZeiError::NoteError {
+                index,
+                source: Box::new(source),
+            })
+        })?;
     }
 
     let bodies = notes.iter().map(|note| &note.body).collect_vec();
     batch_verify_xfr_bodies(prng, params, &bodies, policies).c(d!())
 }
 
+/// Verify a confidential transfer note in strict mode, which also rejects a note that is not
+/// in canonical form, see [`XfrNote::is_canonical`].
+pub fn verify_xfr_note_strict<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &mut BulletproofParams,
+    xfr_note: &XfrNote,
+    policies: &XfrNotePoliciesRef<'_>,
+) -> Result<()> {
+    batch_verify_xfr_notes_strict(prng, params, &[&xfr_note], &[&policies]).c(d!())
+}
+
+/// Batch-verify confidential transfer notes in strict mode, see [`verify_xfr_note_strict`].
+pub fn batch_verify_xfr_notes_strict<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &mut BulletproofParams,
+    notes: &[&XfrNote],
+    policies: &[&XfrNotePoliciesRef<'_>],
+) -> Result<()> {
+    for (index, xfr_note) in notes.iter().enumerate() {
+        if !xfr_note.is_canonical() {
+            return Err(eg!(ZeiError::NoteError {
+                index,
+                source: Box::new(ZeiError::NonCanonicalEncodingError),
+            }));
+        }
+    }
+    batch_verify_xfr_notes(prng, params, notes, policies).c(d!())
+}
+
 pub(crate) fn batch_verify_xfr_body_asset_records<R: CryptoRng + RngCore>(
     prng: &mut R,
     params: &mut BulletproofParams,
@@ -541,13 +631,28 @@ pub(crate) fn batch_verify_xfr_body_asset_records<R: CryptoRng + RngCore>(
     }
 
     // 1. Batch-verify confidential amounts.
-    batch_verify_confidential_amount(prng, params, conf_amount_records.as_slice()).c(d!())?;
+    batch_verify_confidential_amount(prng, params, conf_amount_records.as_slice()).c(d!(
+        ZeiError::SubProofError {
+            proof: "range proof",
+            source: Box::new(ZeiError::XfrVerifyConfidentialAmountError),
+        }
+    ))?;
 
     // 2. Batch-verify confidential asset_types.
-    batch_verify_confidential_asset(prng, &conf_asset_type_records).c(d!())?;
+    batch_verify_confidential_asset(prng, &conf_asset_type_records).c(d!(
+        ZeiError::SubProofError {
+            proof: "asset type equality proof",
+            source: Box::new(ZeiError::XfrVerifyConfidentialAssetError),
+        }
+    ))?;
 
     // 3. Batch-verify confidential asset mix proofs.
-    batch_verify_asset_mix(prng, params, conf_asset_mix_bodies.as_slice()).c(d!())
+    batch_verify_asset_mix(prng, params, conf_asset_mix_bodies.as_slice()).c(d!(
+        ZeiError::SubProofError {
+            proof: "asset mixing proof",
+            source: Box::new(ZeiError::AssetMixerVerificationError),
+        }
+    ))
 }
 
 #[derive(Clone, Default)]
@@ -660,6 +765,74 @@ pub fn verify_xfr_body<R: CryptoRng + RngCore>(
     batch_verify_xfr_bodies(prng, params, &[body], &[policies]).c(d!())
 }
 
+/// Verify the confidential transfer body with policies, as in `verify_xfr_body`, and return
+/// the error of the first check that fails.
+pub(crate) fn check_xfr_body<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: &mut BulletproofParams,
+    body: &XfrBody,
+    policies: &XfrNotePoliciesRef<'_>,
+) -> core::result::Result<(), ZeiError> {
+    let range_proof_error = |_| ZeiError::SubProofError {
+        proof: "range proof",
+        source: Box::new(ZeiError::XfrVerifyConfidentialAmountError),
+    };
+    let asset_proof_error = |_| ZeiError::SubProofError {
+        proof: "asset type equality proof",
+        source: Box::new(ZeiError::XfrVerifyConfidentialAssetError),
+    };
+    let (inputs, outputs) = (body.inputs.as_slice(), body.outputs.as_slice());
+
+    // 1. Verify amounts and asset types.
+    match &body.proofs.asset_type_and_amount_proof {
+        AssetTypeAndAmountProof::ConfAll(x) => {
+            batch_verify_confidential_amount(prng, params, &[(&body.inputs, &body.outputs, &x.0)])
+                .map_err(range_proof_error)?;
+            batch_verify_confidential_asset(prng, &[(&body.inputs, &body.outputs, &x.1)])
+                .map_err(asset_proof_error)?;
+        }
+        AssetTypeAndAmountProof::ConfAmount(range_proof) => {
+            verify_plain_asset(inputs, outputs).map_err(|_| ZeiError::XfrVerifyAssetAmountError)?;
+            batch_verify_confidential_amount(
+                prng,
+                params,
+                &[(&body.inputs, &body.outputs, range_proof)],
+            )
+            .map_err(range_proof_error)?;
+        }
+        AssetTypeAndAmountProof::ConfAsset(asset_proof) => {
+            verify_plain_amounts(inputs, outputs)
+                .map_err(|_| ZeiError::XfrVerifyAssetAmountError)?;
+            batch_verify_confidential_asset(prng, &[(&body.inputs, &body.outputs, asset_proof)])
+                .map_err(asset_proof_error)?;
+        }
+        AssetTypeAndAmountProof::NoProof => {
+            verify_plain_asset_mix(inputs, outputs)
+                .map_err(|_| ZeiError::XfrVerifyAssetAmountError)?;
+        }
+        AssetTypeAndAmountProof::AssetMix(asset_mix_proof) => {
+            batch_verify_asset_mix(prng, params, &[(inputs, outputs, asset_mix_proof)]).map_err(
+                |_| ZeiError::SubProofError {
+                    proof: "asset mixing proof",
+                    source: Box::new(ZeiError::AssetMixerVerificationError),
+                },
+            )?;
+        }
+    }
+
+    // 2. Verify tracing proofs.
+    batch_verify_asset_tracing(prng, &[body], &[policies]).map_err(|_| {
+        ZeiError::SubProofError {
+            proof: "asset tracing proof",
+            source: Box::new(ZeiError::XfrVerifyAssetTracingAssetAmountError),
+        }
+    })?;
+    verify_identity_tracing_proofs(body, policies).map_err(|_| ZeiError::SubProofError {
+        proof: "identity tracing proof",
+        source: Box::new(ZeiError::XfrVerifyAssetTracingIdentityError),
+    })
+}
+
 /// Batch-verify confidential transfer bodies with policies.
 pub fn batch_verify_xfr_bodies<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -724,67 +897,24 @@ fn verify_plain_asset(inputs: &[BlindAssetRecord], outputs: &[BlindAssetRecord])
 }
 
 fn verify_plain_asset_mix(inputs: &[BlindAssetRecord], outputs: &[BlindAssetRecord]) -> Result<()> {
-    let mut amounts = HashMap::new();
-
+    let mut balances = BalanceSheet::new();
     for record in inputs.iter() {
-        match amounts.get_mut(
-            &record
-                .asset_type
-                .get_asset_type()
-                .c(d!(ZeiError::ParameterError))?,
-        ) {
-            None => {
-                amounts.insert(
-                    record
-                        .asset_type
-                        .get_asset_type()
-                        .c(d!(ZeiError::ParameterError))?,
-                    vec![i128::from(
-                        record.amount.get_amount().c(d!(ZeiError::ParameterError))?,
-                    )],
-                );
-            }
-            Some(vec) => {
-                vec.push(i128::from(
-                    record.amount.get_amount().c(d!(ZeiError::ParameterError))?,
-                ));
-            }
-        };
+        let asset_type = record
+            .asset_type
+            .get_asset_type()
+            .c(d!(ZeiError::ParameterError))?;
+        let amount = record.amount.get_amount().c(d!(ZeiError::ParameterError))?;
+        balances.add_input(asset_type, amount).c(d!())?;
     }
-
     for record in outputs.iter() {
-        match amounts.get_mut(
-            &record
-                .asset_type
-                .get_asset_type()
-                .c(d!(ZeiError::ParameterError))?,
-        ) {
-            None => {
-                amounts.insert(
-                    record
-                        .asset_type
-                        .get_asset_type()
-                        .c(d!(ZeiError::ParameterError))?,
-                    vec![-i128::from(
-                        record.amount.get_amount().c(d!(ZeiError::ParameterError))?,
-                    )],
-                );
-            }
-            Some(vec) => {
-                vec.push(-i128::from(
-                    record.amount.get_amount().c(d!(ZeiError::ParameterError))?,
-                ));
-            }
-        };
+        let asset_type = record
+            .asset_type
+            .get_asset_type()
+            .c(d!(ZeiError::ParameterError))?;
+        let amount = record.amount.get_amount().c(d!(ZeiError::ParameterError))?;
+        balances.add_output(asset_type, amount).c(d!())?;
     }
-
-    for (_, a) in amounts.iter() {
-        let sum = a.iter().sum::<i128>();
-        if sum != 0i128 {
-            return Err(eg!(ZeiError::XfrVerifyAssetAmountError));
-        }
-    }
-    Ok(())
+    balances.check().c(d!(ZeiError::XfrVerifyAssetAmountError))
 }
 
 fn batch_verify_asset_mix<R: CryptoRng + RngCore>(
@@ -795,42 +925,24 @@ fn batch_verify_asset_mix<R: CryptoRng + RngCore>(
     fn process_bars(
         bars: &[BlindAssetRecord],
     ) -> Result<Vec<(CompressedRistretto, CompressedRistretto)>> {
-        let pow2_32 = RistrettoScalar::from(POW_2_32);
+        let pc_gens = PedersenCommitmentRistretto::default();
         bars.iter()
             .map(|x| {
-                let (com_amount_low, com_amount_high) = match x.amount {
-                    XfrAmount::Confidential((c1, c2)) => (
-                        c1.decompress().c(d!(ZeiError::DecompressElementError)),
-                        c2.decompress().c(d!(ZeiError::DecompressElementError)),
+                let com_amount = match x.amount {
+                    XfrAmount::Confidential((low, high)) => AmountCommitment { low, high }
+                        .link()
+                        .c(d!(ZeiError::ParameterError))?,
+                    XfrAmount::NonConfidential(amount) => link_limb_commitments(
+                        &AmountOpening::non_confidential(amount).commit(&pc_gens),
                     ),
-                    XfrAmount::NonConfidential(amount) => {
-                        let pc_gens = PedersenCommitmentRistretto::default();
-                        let (low, high) = u64_to_u32_pair(amount);
-                        (
-                            Ok(pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero())),
-                            Ok(pc_gens
-                                .commit(RistrettoScalar::from(high), RistrettoScalar::zero())),
-                        )
-                    }
                 };
-                match (com_amount_low, com_amount_high) {
-                    (Ok(com_amount_low), Ok(com_amount_high)) => {
-                        let com_amount =
-                            (com_amount_low.add(&com_amount_high.mul(&pow2_32))).compress();
-
-                        let com_type = match x.asset_type {
-                            XfrAssetType::Confidential(c) => c,
-                            XfrAssetType::NonConfidential(asset_type) => {
-                                let pc_gens = PedersenCommitmentRistretto::default();
-                                pc_gens
-                                    .commit(asset_type.as_scalar(), RistrettoScalar::zero())
-                                    .compress()
-                            }
-                        };
-                        Ok((com_amount, com_type))
-                    }
-                    _ => Err(eg!(ZeiError::ParameterError)),
-                }
+                let com_type = match x.asset_type {
+                    XfrAssetType::Confidential(c) => c,
+                    XfrAssetType::NonConfidential(asset_type) => pc_gens
+                        .commit(asset_type.as_scalar(), RistrettoScalar::zero())
+                        .compress(),
+                };
+                Ok((com_amount.compress(), com_type))
             })
             .collect()
     }
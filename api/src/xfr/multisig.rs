@@ -0,0 +1,408 @@
+//! Multisig accounts, whose addresses commit to a key set and a threshold.
+//!
+//! A [`MultiSigPolicy`] lets any `threshold` of its keys authorize a message, and its
+//! [`MultiSigAddress`] is the hash of the threshold and the sorted key set, so that every chain
+//! derives the same address for the same account. A transfer note carries the
+//! [`MultiSigAuthorization`]s of the accounts that approve it, which sign the same message as
+//! the multisignature of the note, and are checked by [`crate::xfr::verify_xfr_note`].
+
+use crate::xfr::{
+    body_tree::XfrBodyTree,
+    sig::{Signer, XfrKeyPair, XfrPublicKey, XfrSignature},
+    structs::XfrNote,
+};
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+const MULTISIG_ADDRESS_DOMAIN: &[u8] = b"Zei MultiSig Address";
+
+/// The length of a multisig account address.
+pub const MULTISIG_ADDRESS_LENGTH: usize = 32;
+
+/// The address of a multisig account, which commits to its key set and its threshold.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct MultiSigAddress(pub [u8; MULTISIG_ADDRESS_LENGTH]);
+
+/// The policy of a multisig account: any `threshold` of the keys can authorize a message.
+///
+/// The keys are kept sorted by their byte representation and without duplicates, so that the
+/// same key set and threshold always give the same address.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigPolicy {
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
+    keys: Vec<XfrPublicKey>,
+    threshold: u32,
+}
+
+/// The signatures authorizing a message for a multisig account, each with the index of
+/// its signer in the sorted key set of the policy.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigBundle {
+    /// The list of signatures, ordered by the indices of the signers.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
+    pub signatures: Vec<(u32, XfrSignature)>,
+}
+
+/// The authorization of a transfer by a multisig account: the policy of the account, which
+/// gives its address, and the signatures of enough of its signers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiSigAuthorization {
+    /// The policy of the account.
+    pub policy: MultiSigPolicy,
+    /// The signatures of the signers of the account.
+    pub bundle: MultiSigBundle,
+}
+
+impl MultiSigAuthorization {
+    /// Return the address of the account.
+    pub fn address(&self) -> MultiSigAddress {
+        self.policy.address()
+    }
+}
+
+impl MultiSigPolicy {
+    /// Create a policy from a key set and a threshold, which must be between one and
+    /// the number of distinct keys.
+    pub fn new(keys: &[XfrPublicKey], threshold: u32) -> Result<Self> {
+        let mut keys = keys.to_vec();
+        keys.sort_unstable_by_key(|key| key.to_bytes());
+        keys.dedup();
+        if threshold == 0 || threshold as usize > keys.len() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(MultiSigPolicy { keys, threshold })
+    }
+
+    /// Return the sorted key set.
+    pub fn keys(&self) -> &[XfrPublicKey] {
+        &self.keys
+    }
+
+    /// Return the threshold.
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Derive the address of the account, as the hash of the threshold and the sorted key set.
+    pub fn address(&self) -> MultiSigAddress {
+        let mut hasher = Sha256::new();
+        hasher.update(MULTISIG_ADDRESS_DOMAIN);
+        hasher.update(&self.threshold.to_le_bytes());
+        hasher.update(&(self.keys.len() as u32).to_le_bytes());
+        for key in self.keys.iter() {
+            hasher.update(&key.to_bytes());
+        }
+        let mut address = [0u8; MULTISIG_ADDRESS_LENGTH];
+        address.copy_from_slice(&hasher.finalize());
+        MultiSigAddress(address)
+    }
+
+    /// Sign a message with the key pairs of some of the signers of the account.
+    pub fn sign(&self, keypairs: &[&XfrKeyPair], message: &[u8]) -> Result<MultiSigBundle> {
+        let signers = keypairs
+            .iter()
+            .map(|kp| -> &dyn Signer { *kp })
+            .collect_vec();
+        self.sign_with_signers(&signers, message)
+    }
+
+    /// Sign a message with some of the signers of the account, e.g., hardware wallets.
+    pub fn sign_with_signers(
+        &self,
+        signers: &[&dyn Signer],
+        message: &[u8],
+    ) -> Result<MultiSigBundle> {
+        let mut signatures = vec![];
+        for signer in signers.iter() {
+            let pub_key = signer.public_key();
+            let index = self
+                .keys
+                .iter()
+                .position(|key| *key == pub_key)
+                .c(d!(ZeiError::ParameterError))?;
+            signatures.push((index as u32, signer.sign(message).c(d!())?));
+        }
+        signatures.sort_unstable_by_key(|(index, _)| *index);
+        Ok(MultiSigBundle { signatures })
+    }
+
+    /// Verify that the policy is the one of `address` and that the bundle satisfies it for
+    /// the message, i.e., it has at least `threshold` valid signatures of distinct signers.
+    pub fn verify(
+        &self,
+        address: &MultiSigAddress,
+        message: &[u8],
+        bundle: &MultiSigBundle,
+    ) -> Result<()> {
+        if self.address() != *address {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        self.check(message, bundle).map_err(|err| eg!(err))
+    }
+
+    /// Verify that the bundle satisfies the policy for the message, and return the cause of a
+    /// failure as a `ZeiError`, so that it can be the source of the error of the note.
+    pub(crate) fn check(
+        &self,
+        message: &[u8],
+        bundle: &MultiSigBundle,
+    ) -> core::result::Result<(), ZeiError> {
+        // a deserialized policy has not been checked by `new`
+        if self.threshold == 0 || self.threshold as usize > self.keys.len() {
+            return Err(ZeiError::ParameterError);
+        }
+        if bundle.signatures.len() < self.threshold as usize {
+            return Err(ZeiError::SignatureError);
+        }
+        let mut last_index = None;
+        for (position, (index, signature)) in bundle.signatures.iter().enumerate() {
+            // the indices must be strictly increasing, to count each signer once
+            if last_index.map_or(false, |last| last >= *index) {
+                return Err(ZeiError::SignatureError);
+            }
+            last_index = Some(*index);
+            let key = self
+                .keys
+                .get(*index as usize)
+                .ok_or(ZeiError::SignatureError)?;
+            if key.verify(message, signature).is_err() {
+                return Err(ZeiError::SignerError {
+                    index: position,
+                    source: Box::new(ZeiError::SignatureError),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl XfrNote {
+    /// Authorize the transfer on behalf of a multisig account, with the key pairs of some of
+    /// its signers. The authorizations stay ordered by the addresses of their accounts, and a
+    /// previous authorization of the same account is replaced.
+    pub fn authorize_for_account(
+        &mut self,
+        policy: &MultiSigPolicy,
+        keypairs: &[&XfrKeyPair],
+    ) -> Result<()> {
+        let root = XfrBodyTree::new(&self.body).c(d!())?.root();
+        let bundle = policy.sign(keypairs, &root.signed_message()).c(d!())?;
+        let authorization = MultiSigAuthorization {
+            policy: policy.clone(),
+            bundle,
+        };
+        let address = authorization.address();
+        match self
+            .account_authorizations
+            .binary_search_by_key(&address, MultiSigAuthorization::address)
+        {
+            Ok(index) => self.account_authorizations[index] = authorization,
+            Err(index) => self.account_authorizations.insert(index, authorization),
+        }
+        Ok(())
+    }
+
+    /// Return the addresses of the multisig accounts that authorize the transfer, which the
+    /// ledger can trust once the note is verified.
+    pub fn authorizing_accounts(&self) -> Vec<MultiSigAddress> {
+        self.account_authorizations
+            .iter()
+            .map(MultiSigAuthorization::address)
+            .collect()
+    }
+}
+
+/// Verify the account authorizations of a note over the message signed by its senders: the
+/// addresses must be strictly increasing, so that each account appears once, and each bundle
+/// must satisfy the policy of its account.
+pub(crate) fn verify_account_authorizations(
+    xfr_note: &XfrNote,
+    message: &[u8],
+) -> core::result::Result<(), ZeiError> {
+    let mut last_address = None;
+    for (index, authorization) in xfr_note.account_authorizations.iter().enumerate() {
+        let address = authorization.address();
+        if last_address.map_or(false, |last| last >= address) {
+            return Err(ZeiError::AccountError {
+                index,
+                source: Box::new(ZeiError::InconsistentStructureError),
+            });
+        }
+        last_address = Some(address);
+        authorization
+            .policy
+            .check(message, &authorization.bundle)
+            .map_err(|source| ZeiError::AccountError {
+                index,
+                source: Box::new(source),
+            })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        multisig::MultiSigPolicy,
+        sig::XfrKeyPair,
+        structs::{AssetRecordTemplate, AssetType},
+        tests::create_xfr,
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn multisig_policy() {
+        let mut prng = test_rng();
+        let keypairs = (0..3)
+            .map(|_| XfrKeyPair::generate(&mut prng))
+            .collect_vec();
+        let keys = keypairs.iter().map(|kp| kp.pub_key).collect_vec();
+
+        // the address does not depend on the order of the keys, nor on duplicates
+        let policy = MultiSigPolicy::new(&keys, 2).unwrap();
+        let address = policy.address();
+        let reordered = [keys[2], keys[0], keys[1], keys[0]];
+        assert_eq!(
+            MultiSigPolicy::new(&reordered, 2).unwrap().address(),
+            address
+        );
+        assert_ne!(MultiSigPolicy::new(&keys, 3).unwrap().address(), address);
+        assert_ne!(
+            MultiSigPolicy::new(&keys[..2], 2).unwrap().address(),
+            address
+        );
+        assert!(MultiSigPolicy::new(&keys, 0).is_err());
+        assert!(MultiSigPolicy::new(&keys, 4).is_err());
+
+        let message = b"message";
+        let bundle = policy.sign(&[&keypairs[2], &keypairs[0]], message).unwrap();
+        assert!(policy.verify(&address, message, &bundle).is_ok());
+        assert!(policy.verify(&address, b"other message", &bundle).is_err());
+
+        // the policy must be the one of the address
+        let other_policy = MultiSigPolicy::new(&keys, 1).unwrap();
+        let other_bundle = other_policy.sign(&[&keypairs[0]], message).unwrap();
+        assert!(other_policy
+            .verify(&address, message, &other_bundle)
+            .is_err());
+
+        // not enough signers
+        let short_bundle = policy.sign(&[&keypairs[1]], message).unwrap();
+        msg_eq!(
+            ZeiError::SignatureError,
+            policy.verify(&address, message, &short_bundle).unwrap_err()
+        );
+
+        // the same signer cannot be counted twice
+        let mut duplicate_bundle = short_bundle.clone();
+        duplicate_bundle
+            .signatures
+            .push(short_bundle.signatures[0].clone());
+        msg_eq!(
+            ZeiError::SignatureError,
+            policy
+                .verify(&address, message, &duplicate_bundle)
+                .unwrap_err()
+        );
+
+        // a key outside the policy cannot sign
+        let outsider = XfrKeyPair::generate(&mut prng);
+        assert!(policy.sign(&[&outsider], message).is_err());
+    }
+
+    #[test]
+    fn multisig_account_authorization() {
+        let mut prng = test_rng();
+        let keypairs = (0..3)
+            .map(|_| XfrKeyPair::generate(&mut prng))
+            .collect_vec();
+        let keys = keypairs.iter().map(|kp| kp.pub_key).collect_vec();
+        let policy = MultiSigPolicy::new(&keys, 2).unwrap();
+        let address = policy.address();
+
+        let template = |amount: u64, keypair: &XfrKeyPair| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                AssetType::from_identical_byte(0),
+                AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                keypair.pub_key,
+            )
+        };
+        let owner = XfrKeyPair::generate(&mut prng);
+        let (mut note, _, _) = create_xfr(
+            &mut prng,
+            &[template(10, &owner)],
+            &[template(10, &keypairs[0])],
+            &[&owner],
+        );
+        let (mut other_note, _, _) = create_xfr(
+            &mut prng,
+            &[template(10, &owner)],
+            &[template(10, &keypairs[1])],
+            &[&owner],
+        );
+        let policies = XfrNotePolicies::empty_policies(1, 1);
+        let mut params = BulletproofParams::default();
+        let mut verify = |note| verify_xfr_note(&mut prng, &mut params, note, &policies.to_ref());
+
+        note.authorize_for_account(&policy, &[&keypairs[0], &keypairs[1]])
+            .unwrap();
+        assert_eq!(note.authorizing_accounts(), vec![address]);
+        assert!(verify(&note).is_ok());
+
+        // not enough signers
+        let mut short_note = note.clone();
+        short_note
+            .authorize_for_account(&policy, &[&keypairs[2]])
+            .unwrap();
+        assert_eq!(short_note.authorizing_accounts(), vec![address]);
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 0,
+                source: Box::new(ZeiError::AccountError {
+                    index: 0,
+                    source: Box::new(ZeiError::SignatureError),
+                }),
+            },
+            verify(&short_note).unwrap_err()
+        );
+
+        // the authorization of another note
+        other_note.account_authorizations = note.account_authorizations.clone();
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 0,
+                source: Box::new(ZeiError::AccountError {
+                    index: 0,
+                    source: Box::new(ZeiError::SignerError {
+                        index: 0,
+                        source: Box::new(ZeiError::SignatureError),
+                    }),
+                }),
+            },
+            verify(&other_note).unwrap_err()
+        );
+
+        // an account cannot be listed twice
+        let mut duplicate_note = note.clone();
+        duplicate_note
+            .account_authorizations
+            .push(note.account_authorizations[0].clone());
+        msg_eq!(
+            ZeiError::NoteError {
+                index: 0,
+                source: Box::new(ZeiError::AccountError {
+                    index: 1,
+                    source: Box::new(ZeiError::InconsistentStructureError),
+                }),
+            },
+            verify(&duplicate_note).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Owner memos whose keys are bound to their context.
+//!
+//! An owner memo from [`OwnerMemo::with_context`] has a fresh ephemeral key of the sender, and
+//! all its keys, i.e., the blinds of the commitments and the key of the lock, are derived with
+//! HKDF from the shared point and the context of the output: the note id and the output index.
+//! The keys of a memo therefore reveal nothing about the keys of the other outputs, even those
+//! sent to the same receiver. The derivation is public, so that a wallet that recovers from its
+//! secret key can recompute the keys of each of its outputs.
+//!
+//! As the other owner memos with a view tag, their locks are key-committing, so that they are
+//! never decrypted to a wrong plaintext under the key of another receiver.
+
+use crate::xfr::{
+    memo_padding::MemoPaddingPolicy,
+    sig::{XfrKeyPair, XfrPublicKey},
+    structs::{AssetType, OwnerMemo},
+};
+use hkdf::Hkdf;
+use sha2::Sha512;
+use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+use zei_crypto::basic::hybrid_encryption::{
+    committing_symmetric_decrypt, committing_symmetric_encrypt, Ctext, COMMITTING_TAG_LENGTH,
+};
+
+/// The salt of the key derivation of owner memos.
+const OWNER_MEMO_KDF_SALT: &[u8] = b"Zei Owner Memo KDF";
+
+/// The context of an output, which the keys of its owner memo are bound to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerMemoContext {
+    /// The identifier of the note, e.g., the digest of its inputs.
+    pub note_id: [u8; 32],
+    /// The position of the output in the note.
+    pub output_index: u64,
+}
+
+impl OwnerMemoContext {
+    // The HKDF info of a key: the context, followed by the label of the key.
+    fn info(&self, label: &[u8]) -> Vec<u8> {
+        let mut info = self.note_id.to_vec();
+        info.extend_from_slice(&self.output_index.to_le_bytes());
+        info.extend_from_slice(label);
+        info
+    }
+}
+
+/// The keys of an owner memo bound to a context.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnerMemoKeys {
+    /// The blinds of the amount, for the lower and the higher 32 bits.
+    pub amount_blinds: (RistrettoScalar, RistrettoScalar),
+    /// The blind of the asset type.
+    pub asset_type_blind: RistrettoScalar,
+    /// The key of the lock.
+    pub lock_key: [u8; 32],
+}
+
+/// Derive the keys of an owner memo from the shared point, in the encoding of
+/// `OwnerMemo`, and the context of the output.
+pub fn derive_owner_memo_keys(
+    shared_point: &[u8],
+    context: &OwnerMemoContext,
+) -> Result<OwnerMemoKeys> {
+    let hkdf = Hkdf::<Sha512>::new(Some(OWNER_MEMO_KDF_SALT), shared_point);
+    let expand_scalar = |label: &[u8]| -> Result<RistrettoScalar> {
+        let mut okm = [0u8; 64];
+        hkdf.expand(&context.info(label), &mut okm)
+            .map_err(|_| eg!(ZeiError::ParameterError))?;
+        Ok(RistrettoScalar::from_le_bytes_mod_order(&okm))
+    };
+
+    let amount_blinds = (
+        expand_scalar(b"amount_low").c(d!())?,
+        expand_scalar(b"amount_high").c(d!())?,
+    );
+    let asset_type_blind = expand_scalar(b"asset_type").c(d!())?;
+    let mut lock_key = [0u8; 32];
+    hkdf.expand(&context.info(b"lock"), &mut lock_key)
+        .map_err(|_| eg!(ZeiError::ParameterError))?;
+
+    Ok(OwnerMemoKeys {
+        amount_blinds,
+        asset_type_blind,
+        lock_key,
+    })
+}
+
+impl OwnerMemo {
+    /// Construct an `OwnerMemo` whose keys are bound to the context of the output, for a record
+    /// with a confidential amount, a confidential asset type, or both.
+    /// Return the memo and its keys, whose blinds are those of the commitments of the record.
+    pub fn with_context<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: Option<u64>,
+        asset_type: Option<&AssetType>,
+        pub_key: &XfrPublicKey,
+        padding: &MemoPaddingPolicy,
+        context: &OwnerMemoContext,
+    ) -> Result<(Self, OwnerMemoKeys)> {
+        let plaintext = OwnerMemo::encode_plaintext(amount, asset_type).c(d!())?;
+
+        let (key_type, r, blind_share_bytes) = pub_key.random_scalar_with_compressed_point(prng);
+        let shared_point =
+            OwnerMemo::derive_shared_point(&key_type, &r, &pub_key.as_compressed_point())
+                .c(d!())?;
+        let keys = derive_owner_memo_keys(&shared_point, context).c(d!())?;
+
+        let (ctext, tag) = committing_symmetric_encrypt(
+            &keys.lock_key,
+            &blind_share_bytes,
+            &padding.pad(&plaintext).c(d!())?,
+        );
+        let mut lock_bytes = tag.to_vec();
+        lock_bytes.extend_from_slice(&ctext.0);
+
+        Ok((
+            OwnerMemo {
+                key_type,
+                blind_share_bytes,
+                lock_bytes,
+                view_tag: Some(OwnerMemo::calc_view_tag(&shared_point)),
+            },
+            keys,
+        ))
+    }
+
+    /// Derive the keys of an `OwnerMemo` from [`OwnerMemo::with_context`].
+    pub fn derive_keys_with_context(
+        &self,
+        keypair: &XfrKeyPair,
+        context: &OwnerMemoContext,
+    ) -> Result<OwnerMemoKeys> {
+        let (key_type, s) = keypair.sec_key.as_scalar_bytes();
+        let shared_point =
+            OwnerMemo::derive_shared_point(&key_type, &s, &self.blind_share_bytes).c(d!())?;
+        derive_owner_memo_keys(&shared_point, context).c(d!())
+    }
+
+    /// Decrypt the lock of an `OwnerMemo` from [`OwnerMemo::with_context`], and return the
+    /// amount and the asset type that it encrypts.
+    pub fn decrypt_with_context(
+        &self,
+        keypair: &XfrKeyPair,
+        context: &OwnerMemoContext,
+    ) -> Result<(Option<u64>, Option<AssetType>)> {
+        if self.lock_bytes.len() < COMMITTING_TAG_LENGTH {
+            return Err(eg!(ZeiError::DecryptionError));
+        }
+        let keys = self.derive_keys_with_context(keypair, context).c(d!())?;
+        let mut tag = [0u8; COMMITTING_TAG_LENGTH];
+        tag.copy_from_slice(&self.lock_bytes[..COMMITTING_TAG_LENGTH]);
+        let ctext = Ctext(self.lock_bytes[COMMITTING_TAG_LENGTH..].to_vec());
+        let padded =
+            committing_symmetric_decrypt(&keys.lock_key, &self.blind_share_bytes, &ctext, &tag)
+                .c(d!(ZeiError::DecryptionError))?;
+        OwnerMemo::parse_plaintext(&padded).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{derive_owner_memo_keys, OwnerMemoContext};
+    use crate::xfr::{
+        memo_padding::MemoPaddingPolicy,
+        sig::XfrKeyPair,
+        structs::{AssetType, OwnerMemo},
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn owner_memo_with_context() {
+        let mut prng = test_rng();
+        let asset_type = AssetType::from_identical_byte(3);
+        let padding = MemoPaddingPolicy::default();
+        let context = OwnerMemoContext {
+            note_id: [7u8; 32],
+            output_index: 1,
+        };
+
+        for keypair in [
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        ] {
+            let (memo, keys) = OwnerMemo::with_context(
+                &mut prng,
+                Some(100),
+                Some(&asset_type),
+                &keypair.get_pk(),
+                &padding,
+                &context,
+            )
+            .unwrap();
+            assert_eq!(
+                memo.decrypt_with_context(&keypair, &context).unwrap(),
+                (Some(100), Some(asset_type))
+            );
+            assert_eq!(
+                memo.derive_keys_with_context(&keypair, &context).unwrap(),
+                keys
+            );
+
+            // the keys are bound to the context.
+            let other_context = OwnerMemoContext {
+                output_index: 2,
+                ..context
+            };
+            assert!(memo.decrypt_with_context(&keypair, &other_context).is_err());
+            assert_ne!(
+                memo.derive_keys_with_context(&keypair, &other_context)
+                    .unwrap(),
+                keys
+            );
+
+            // another receiver cannot decrypt.
+            let other = XfrKeyPair::generate_ed25519(&mut prng);
+            assert!(memo.decrypt_with_context(&other, &context).is_err());
+
+            let (memo, _) = OwnerMemo::with_context(
+                &mut prng,
+                Some(5),
+                None,
+                &keypair.get_pk(),
+                &padding,
+                &context,
+            )
+            .unwrap();
+            assert_eq!(
+                memo.decrypt_with_context(&keypair, &context).unwrap(),
+                (Some(5), None)
+            );
+        }
+
+        assert!(OwnerMemo::with_context(
+            &mut prng,
+            None,
+            None,
+            &XfrKeyPair::generate(&mut prng).get_pk(),
+            &padding,
+            &context,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn keys_of_different_outputs_are_independent() {
+        let shared_point = [9u8; 32];
+        let context = OwnerMemoContext::default();
+        let keys0 = derive_owner_memo_keys(&shared_point, &context).unwrap();
+        let keys1 = derive_owner_memo_keys(
+            &shared_point,
+            &OwnerMemoContext {
+                output_index: 1,
+                ..context
+            },
+        )
+        .unwrap();
+        assert_ne!(keys0.lock_key, keys1.lock_key);
+        assert_ne!(keys0.amount_blinds, keys1.amount_blinds);
+        assert_ne!(keys0.asset_type_blind, keys1.asset_type_blind);
+    }
+}
@@ -0,0 +1,326 @@
+//! Partially signed transfer notes, passed around between the co-signers of a transfer.
+//!
+//! The body of a transfer, with its proofs, is built once and then signed by the owners of
+//! its inputs, which may each sign on a different device, e.g., a hardware wallet. A
+//! [`PartiallySignedXfr`] holds the body, one signature slot per input, and free-form
+//! metadata for the signers. The contributions of the co-signers are merged into a single
+//! partially signed transfer, which is finalized into an [`XfrNote`] once every slot is filled.
+
+use crate::xfr::{
+    body_tree::XfrBodyTree,
+    gen_xfr_body,
+    sig::{AsyncSigner, Signer, XfrMultiSig, XfrPublicKey, XfrSignature},
+    structs::{AssetRecord, XfrBody, XfrNote},
+};
+use zei_algebra::{collections::BTreeMap, prelude::*};
+
+/// The version of the serialization format of partially signed transfers.
+pub const PARTIALLY_SIGNED_XFR_VERSION: u32 = 1;
+
+/// A transfer note whose inputs are not all signed yet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartiallySignedXfr {
+    /// The version of the serialization format.
+    pub version: u32,
+    /// The body of the transfer, with its proofs.
+    pub body: XfrBody,
+    /// The signature of the owner of each input, if any yet.
+    pub signatures: Vec<Option<XfrSignature>>,
+    /// The metadata for the signers, e.g., a description of the transfer.
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl PartiallySignedXfr {
+    /// Create a partially signed transfer with no signature, from a transfer body.
+    pub fn new(body: XfrBody) -> Self {
+        let signatures = vec![None; body.inputs.len()];
+        PartiallySignedXfr {
+            version: PARTIALLY_SIGNED_XFR_VERSION,
+            body,
+            signatures,
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Build the body of a transfer from its inputs and outputs, and create a partially
+    /// signed transfer with no signature.
+    pub fn create<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        inputs: &[AssetRecord],
+        outputs: &[AssetRecord],
+    ) -> Result<Self> {
+        let body = gen_xfr_body(prng, inputs, outputs).c(d!())?;
+        Ok(PartiallySignedXfr::new(body))
+    }
+
+    /// Serialize the partially signed transfer.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Deserialize a partially signed transfer, and check its version and its number
+    /// of signature slots.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let psxfr: PartiallySignedXfr =
+            bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))?;
+        psxfr.check_structure().c(d!())?;
+        Ok(psxfr)
+    }
+
+    /// Return the message signed by the owners of the inputs.
+    pub fn signed_message(&self) -> Result<Vec<u8>> {
+        let root = XfrBodyTree::new(&self.body).c(d!())?.root();
+        Ok(root.signed_message())
+    }
+
+    /// Sign every unsigned input owned by the signer, e.g., a key pair, and return the number
+    /// of inputs signed. Return an error if the signer owns no input.
+    pub fn sign(&mut self, signer: &dyn Signer) -> Result<usize> {
+        let indices = self.input_indices(&signer.public_key()).c(d!())?;
+        let message = self.signed_message().c(d!())?;
+        let signature = signer.sign(&message).c(d!())?;
+        Ok(self.fill_signatures(indices, signature))
+    }
+
+    /// Sign every unsigned input owned by an asynchronous signer, e.g., a device that waits
+    /// for the confirmation of its user, and return the number of inputs signed.
+    pub async fn sign_async(&mut self, signer: &dyn AsyncSigner) -> Result<usize> {
+        let indices = self.input_indices(&signer.public_key()).c(d!())?;
+        let message = self.signed_message().c(d!())?;
+        let signature = signer.sign_async(&message).await.c(d!())?;
+        self.verify_signature(indices[0], &message, &signature)
+            .c(d!())?;
+        Ok(self.fill_signatures(indices, signature))
+    }
+
+    /// Add the signature of the owner of an input, e.g., computed by a hardware wallet
+    /// over [`Self::signed_message`]. The signature is verified before being added.
+    pub fn add_signature(&mut self, index: usize, signature: XfrSignature) -> Result<()> {
+        let message = self.signed_message().c(d!())?;
+        self.verify_signature(index, &message, &signature).c(d!())?;
+        self.signatures[index] = Some(signature);
+        Ok(())
+    }
+
+    /// Add the metadata entry, which must not conflict with an existing one.
+    pub fn add_metadata(&mut self, key: String, value: String) -> Result<()> {
+        match self.metadata.get(&key) {
+            Some(existing) if *existing != value => Err(eg!(ZeiError::InconsistentStructureError)),
+            _ => {
+                self.metadata.insert(key, value);
+                Ok(())
+            }
+        }
+    }
+
+    /// Merge the signatures and the metadata of another partially signed version of the same
+    /// transfer. The other signatures are verified, and the ones of the inputs that are
+    /// already signed are ignored.
+    pub fn merge(&mut self, other: &PartiallySignedXfr) -> Result<()> {
+        self.check_structure().c(d!())?;
+        other.check_structure().c(d!())?;
+        if self.body != other.body {
+            return Err(eg!(ZeiError::InconsistentStructureError));
+        }
+        let message = self.signed_message().c(d!())?;
+        for (index, signature) in other.signatures.iter().enumerate() {
+            if let (None, Some(signature)) = (&self.signatures[index], signature) {
+                self.verify_signature(index, &message, signature).c(d!())?;
+                self.signatures[index] = Some(signature.clone());
+            }
+        }
+        for (key, value) in other.metadata.iter() {
+            self.add_metadata(key.clone(), value.clone()).c(d!())?;
+        }
+        Ok(())
+    }
+
+    /// Return the indices of the inputs that are not signed yet.
+    pub fn missing_signatures(&self) -> Vec<usize> {
+        self.signatures
+            .iter()
+            .enumerate()
+            .filter(|(_, signature)| signature.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Check if every input is signed.
+    pub fn is_complete(&self) -> bool {
+        self.signatures.iter().all(Option::is_some)
+    }
+
+    /// Finalize the transfer into a note, whose multisignature is made of the signatures
+    /// of the inputs. Return an error if an input is not signed.
+    pub fn finalize(self) -> Result<XfrNote> {
+        self.check_structure().c(d!())?;
+        let mut signers = Vec::with_capacity(self.signatures.len());
+        for (index, (input, signature)) in self.body.inputs.iter().zip(self.signatures).enumerate()
+        {
+            let signature = signature.c(d!(ZeiError::SignerError {
+                index,
+                source: Box::new(ZeiError::SignatureError),
+            }))?;
+            signers.push((input.public_key.zei_to_bytes(), signature));
+        }
+        // the multisignature lists the signatures in the order of the public keys
+        signers.sort_by(|a, b| a.0.cmp(&b.0));
+        let multisig = XfrMultiSig {
+            signatures: signers
+                .into_iter()
+                .map(|(_, signature)| signature)
+                .collect(),
+        };
+        let note = XfrNote {
+            body: self.body,
+            multisig,
+            account_authorizations: vec![],
+        };
+        let pubkeys = note
+            .body
+            .inputs
+            .iter()
+            .map(|input| &input.public_key)
+            .collect_vec();
+        let message = XfrBodyTree::new(&note.body)
+            .c(d!())?
+            .root()
+            .signed_message();
+        note.multisig.verify(&pubkeys, &message).c(d!())?;
+        Ok(note)
+    }
+
+    pub(crate) fn check_structure(&self) -> Result<()> {
+        if self.version != PARTIALLY_SIGNED_XFR_VERSION {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        if self.signatures.len() != self.body.inputs.len() {
+            return Err(eg!(ZeiError::LengthMismatch {
+                expected: self.body.inputs.len(),
+                actual: self.signatures.len(),
+            }));
+        }
+        Ok(())
+    }
+
+    // Return an error if the public key owns no input.
+    fn input_indices(&self, pub_key: &XfrPublicKey) -> Result<Vec<usize>> {
+        let indices = self
+            .body
+            .inputs
+            .iter()
+            .enumerate()
+            .filter(|(_, input)| input.public_key == *pub_key)
+            .map(|(index, _)| index)
+            .collect_vec();
+        if indices.is_empty() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(indices)
+    }
+
+    fn fill_signatures(&mut self, indices: Vec<usize>, signature: XfrSignature) -> usize {
+        let mut signed = 0;
+        for index in indices {
+            if self.signatures[index].is_none() {
+                self.signatures[index] = Some(signature.clone());
+                signed += 1;
+            }
+        }
+        signed
+    }
+
+    fn verify_signature(
+        &self,
+        index: usize,
+        message: &[u8],
+        signature: &XfrSignature,
+    ) -> Result<()> {
+        let input = self.body.inputs.get(index).c(d!(ZeiError::IndexError))?;
+        input
+            .public_key
+            .verify(message, signature)
+            .c(d!(ZeiError::SignerError {
+                index,
+                source: Box::new(ZeiError::SignatureError),
+            }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        partially_signed::PartiallySignedXfr,
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType},
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn partially_signed_xfr() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(1);
+        let alice = XfrKeyPair::generate_secp256k1(&mut prng);
+        let bob = XfrKeyPair::generate_ed25519(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+
+        let mut record = |amount, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                keypair.get_pk(),
+            );
+            AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+        };
+        let inputs = [record(10, &alice), record(20, &bob), record(30, &alice)];
+        let outputs = [record(60, &receiver)];
+        let mut psxfr = PartiallySignedXfr::create(&mut prng, &inputs, &outputs).unwrap();
+        psxfr
+            .add_metadata("memo".to_string(), "rent".to_string())
+            .unwrap();
+        assert_eq!(psxfr.missing_signatures(), vec![0, 1, 2]);
+
+        // the co-signers sign their own copies
+        let bytes = psxfr.to_bytes().unwrap();
+        let mut alice_copy = PartiallySignedXfr::from_bytes(&bytes).unwrap();
+        assert_eq!(alice_copy.sign(&alice).unwrap(), 2);
+        assert!(alice_copy.sign(&receiver).is_err());
+        let mut bob_copy = PartiallySignedXfr::from_bytes(&bytes).unwrap();
+        let message = bob_copy.signed_message().unwrap();
+        assert!(bob_copy
+            .add_signature(0, bob.sign(&message).unwrap())
+            .is_err());
+        bob_copy
+            .add_signature(1, bob.sign(&message).unwrap())
+            .unwrap();
+        bob_copy
+            .add_metadata("device".to_string(), "hardware wallet".to_string())
+            .unwrap();
+
+        // an incomplete transfer cannot be finalized
+        assert!(alice_copy.clone().finalize().is_err());
+
+        psxfr.merge(&alice_copy).unwrap();
+        psxfr.merge(&bob_copy).unwrap();
+        assert!(psxfr.is_complete());
+        assert_eq!(psxfr.metadata.len(), 2);
+
+        // conflicting metadata and other transfers are not merged
+        let mut conflicting = bob_copy.clone();
+        conflicting
+            .metadata
+            .insert("memo".to_string(), "gift".to_string());
+        assert!(psxfr.clone().merge(&conflicting).is_err());
+        let other = PartiallySignedXfr::create(&mut prng, &inputs, &outputs).unwrap();
+        assert!(psxfr.clone().merge(&other).is_err());
+
+        let note = psxfr.finalize().unwrap();
+        let policies = XfrNotePolicies::empty_policies(inputs.len(), outputs.len());
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &policies.to_ref()).is_ok());
+    }
+}
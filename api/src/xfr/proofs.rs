@@ -1,5 +1,5 @@
 use crate::anon_creds::{ac_confidential_verify, ACCommitment, ACConfidentialRevealProof};
-use crate::setup::{BulletproofParams, BULLET_PROOF_RANGE, MAX_CONFIDENTIAL_RECORD_NUMBER};
+use crate::setup::{BulletproofParams, MAX_CONFIDENTIAL_RECORD_NUMBER};
 use crate::xfr::{
     asset_record::AssetRecordType,
     asset_tracer::RecordDataEncKey,
@@ -9,12 +9,11 @@ use crate::xfr::{
     },
     XfrNotePoliciesRef,
 };
-use bulletproofs::RangeProof;
 use linear_map::LinearMap;
 use merlin::Transcript;
 use zei_algebra::{
     prelude::*,
-    ristretto::{CompressedRistretto, RistrettoPoint, RistrettoScalar},
+    ristretto::{RistrettoPoint, RistrettoScalar},
     utils::{min_greater_equal_power_of_two, u64_to_u32_pair},
 };
 use zei_crypto::basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto};
@@ -30,10 +29,21 @@ use zei_crypto::{
             PedersenElGamalEqProof, PedersenElGamalProofInstance,
         },
     },
-    bulletproofs::range::{batch_verify_ranges, prove_ranges},
+    bulletproofs::{
+        amount::{
+            batch_verify_amounts, link_limb_commitments, prove_amounts, AmountCommitment,
+            AmountOpening,
+        },
+        range::VersionedRangeProof,
+    },
 };
 
-const POW_2_32: u64 = 0xFFFF_FFFFu64 + 1;
+/// The domain separator for the asset tracing proofs.
+pub(crate) const ASSET_TRACING_PROOFS_TRANSCRIPT: &[u8] = b"AssetTracingProofs";
+/// The domain separator for the range proof of confidential amounts.
+pub(crate) const RANGE_PROOF_TRANSCRIPT: &[u8] = b"Zei Range Proof";
+/// The domain separator for the asset equality proof of confidential asset types.
+pub(crate) const ASSET_EQUALITY_TRANSCRIPT: &[u8] = b"AssetEquality";
 
 pub(crate) fn asset_amount_tracing_proofs<R: CryptoRng + RngCore>(
     prng: &mut R,
@@ -50,7 +60,7 @@ pub(crate) fn asset_amount_tracing_proofs<R: CryptoRng + RngCore>(
     // 2. Do asset tracing for each tracer_key.
     let mut proofs = vec![];
     for (tracer_pub_key, records_memos) in pks_map.iter() {
-        let mut transcript = Transcript::new(b"AssetTracingProofs");
+        let mut transcript = Transcript::new(ASSET_TRACING_PROOFS_TRANSCRIPT);
         let proof = build_same_key_asset_type_amount_tracing_proof(
             prng,
             &mut transcript,
@@ -232,6 +242,22 @@ pub(crate) fn batch_verify_tracer_tracing_proof<R: CryptoRng + RngCore>(
     }
 
     // 1. Batch asset_type and amount tracing.
+    batch_verify_asset_tracing(prng, xfr_bodies, instances_policies).c(d!())?;
+
+    // 2. Check the identity proof individually for now.
+    for (xfr_body, policies) in xfr_bodies.iter().zip(instances_policies.iter()) {
+        verify_identity_tracing_proofs(xfr_body, policies).c(d!())?;
+    }
+
+    Ok(())
+}
+
+/// Batch-verify the amount and asset type tracing proofs of the bodies.
+pub(crate) fn batch_verify_asset_tracing<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    xfr_bodies: &[&XfrBody],
+    instances_policies: &[&XfrNotePoliciesRef<'_>],
+) -> Result<()> {
     let input_reveal_policies: Result<Vec<&[&TracingPolicies]>> = instances_policies
         .iter()
         .map(|policies| {
@@ -258,29 +284,29 @@ pub(crate) fn batch_verify_tracer_tracing_proof<R: CryptoRng + RngCore>(
         &input_reveal_policies.c(d!())?,
         &output_reveal_policies.c(d!())?,
     )
-    .c(d!(ZeiError::XfrVerifyAssetTracingAssetAmountError))?;
-
-    // 2. Check the identity proof individually for now.
-    for (xfr_body, policies) in xfr_bodies.iter().zip(instances_policies.iter()) {
-        // 2. do identity tracing proof
-        let inputs_len = xfr_body.inputs.len();
-        verify_identity_proofs(
-            &policies.inputs_tracing_policies,
-            &xfr_body.asset_tracing_memos[..inputs_len],
-            &xfr_body.proofs.asset_tracing_proof.inputs_identity_proofs,
-            &policies.inputs_sig_commitments,
-        )
-        .c(d!())?;
-        verify_identity_proofs(
-            &policies.outputs_tracing_policies,
-            &xfr_body.asset_tracing_memos[inputs_len..],
-            &xfr_body.proofs.asset_tracing_proof.outputs_identity_proofs,
-            &policies.outputs_sig_commitments,
-        )
-        .c(d!())?;
-    }
+    .c(d!(ZeiError::XfrVerifyAssetTracingAssetAmountError))
+}
 
-    Ok(())
+/// Verify the identity tracing proofs of the inputs and the outputs of a body.
+pub(crate) fn verify_identity_tracing_proofs(
+    xfr_body: &XfrBody,
+    policies: &XfrNotePoliciesRef<'_>,
+) -> Result<()> {
+    let inputs_len = xfr_body.inputs.len();
+    verify_identity_proofs(
+        &policies.inputs_tracing_policies,
+        &xfr_body.asset_tracing_memos[..inputs_len],
+        &xfr_body.proofs.asset_tracing_proof.inputs_identity_proofs,
+        &policies.inputs_sig_commitments,
+    )
+    .c(d!())?;
+    verify_identity_proofs(
+        &policies.outputs_tracing_policies,
+        &xfr_body.asset_tracing_memos[inputs_len..],
+        &xfr_body.proofs.asset_tracing_proof.outputs_identity_proofs,
+        &policies.outputs_sig_commitments,
+    )
+    .c(d!())
 }
 
 fn batch_verify_asset_tracing_proofs<R: CryptoRng + RngCore>(
@@ -341,10 +367,67 @@ fn batch_verify_asset_tracing_proofs<R: CryptoRng + RngCore>(
             instances.push(peg_eq_instance);
         }
     }
-    let mut transcript = Transcript::new(b"AssetTracingProofs");
+    let mut transcript = Transcript::new(ASSET_TRACING_PROOFS_TRANSCRIPT);
     pedersen_elgamal_batch_verify(&mut transcript, prng, &instances).c(d!())
 }
 
+/// Verify the amount and asset type tracing proof of a body for a single tracer, i.e., that the
+/// ciphertexts encrypted to `key` match the commitments of the records. The records of each
+/// tracer are grouped from the memos, in the order in which the prover groups them.
+pub(crate) fn verify_tracing_proof_for_key<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    xfr_body: &XfrBody,
+    key: &RecordDataEncKey,
+) -> Result<()> {
+    if xfr_body.inputs.len() + xfr_body.outputs.len() != xfr_body.asset_tracing_memos.len() {
+        return Err(eg!(ZeiError::InconsistentStructureError));
+    }
+    let mut map: LinearMap<RecordDataEncKey, BarMemoVec<'_>> = LinearMap::new();
+    for (bar, memos) in xfr_body
+        .inputs
+        .iter()
+        .chain(&xfr_body.outputs)
+        .zip(&xfr_body.asset_tracing_memos)
+    {
+        if bar.get_record_type() == AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType
+        {
+            continue;
+        }
+        for memo in memos {
+            // a memo locks the amount or the asset type if and only if its policy traces assets
+            if memo.lock_amount.is_some() || memo.lock_asset_type.is_some() {
+                map.entry(memo.enc_key.record_data_enc_key.clone())
+                    .or_insert(Default::default())
+                    .push(bar, memo);
+            }
+        }
+    }
+
+    let proofs = &xfr_body
+        .proofs
+        .asset_tracing_proof
+        .asset_type_and_amount_proofs;
+    if map.len() != proofs.len() {
+        return Err(eg!(ZeiError::XfrVerifyAssetTracingAssetAmountError));
+    }
+    let (proof, records_and_memos) = proofs
+        .iter()
+        .zip(map.iter())
+        .find(|(_, (k, _))| *k == key)
+        .map(|(proof, (_, records_and_memos))| (proof, records_and_memos))
+        .c(d!(ZeiError::ParameterError))?;
+    let (cts, commitments) = extract_ciphertext_and_commitments(&records_and_memos.0).c(d!())?;
+    let instance = PedersenElGamalProofInstance {
+        public_key: key,
+        cts,
+        commitments,
+        proof,
+    };
+    let mut transcript = Transcript::new(ASSET_TRACING_PROOFS_TRANSCRIPT);
+    pedersen_elgamal_batch_verify(&mut transcript, prng, &[instance])
+        .c(d!(ZeiError::XfrVerifyAssetTracingAssetAmountError))
+}
+
 #[derive(Default)]
 struct BarMemoVec<'a>(Vec<(&'a BlindAssetRecord, &'a TracerMemo)>);
 
@@ -491,7 +574,10 @@ fn extract_ciphertext_and_commitments(
 /// Compute a range proof for confidential amount non-confidential asset type transfers.
 /// The proof guarantees that output amounts and difference between total input,
 /// and total output are in the range [0,2^{64} - 1].
-pub(crate) fn gen_range_proof(
+/// The proof uses the backend of the parameters, or Bulletproofs without parameters.
+pub(crate) fn gen_range_proof<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    params: Option<&BulletproofParams>,
     inputs: &[&OpenAssetRecord],
     outputs: &[&OpenAssetRecord],
 ) -> Result<XfrRangeProof> {
@@ -501,62 +587,55 @@ pub(crate) fn gen_range_proof(
         return Err(eg!(ZeiError::RangeProofProveError));
     }
 
-    let params = BulletproofParams::default();
-
-    // Build values vector (out amounts + amount difference).
-    let in_total = inputs.iter().fold(0u64, |accum, x| accum + x.amount);
-    let out_amounts: Vec<u64> = outputs.iter().map(|x| x.amount).collect();
-    let out_total = out_amounts.iter().sum::<u64>();
-    let xfr_diff = if in_total >= out_total {
-        in_total - out_total
-    } else {
-        return Err(eg!(ZeiError::RangeProofProveError));
+    let default_params;
+    let params = match params {
+        Some(params) => params,
+        None => {
+            default_params = BulletproofParams::default();
+            &default_params
+        }
     };
-    let mut values = Vec::with_capacity(upper_power2);
-    for x in out_amounts {
-        let (lower, higher) = u64_to_u32_pair(x);
-        values.push(lower as u64);
-        values.push(higher as u64);
-    }
-    let (diff_low, diff_high) = u64_to_u32_pair(xfr_diff);
-    values.push(diff_low as u64);
-    values.push(diff_high as u64);
-    values.resize(upper_power2, 0u64);
 
-    // Build blinding vectors (out blindings + blindings difference).
+    // Open the out amounts and the amount difference, whose blinds are the blinds difference.
+    let total = |records: &[&OpenAssetRecord]| -> u128 {
+        records.iter().map(|x| u128::from(x.amount)).sum()
+    };
+    let xfr_diff = total(inputs)
+        .checked_sub(total(outputs))
+        .and_then(|diff| u64::try_from(diff).ok())
+        .c(d!(ZeiError::RangeProofProveError))?;
     let (total_blind_input_low, total_blind_input_high) = add_blindings(inputs);
     let (total_blind_output_low, total_blind_output_high) = add_blindings(outputs);
-
-    let xfr_blind_diff_low = total_blind_input_low.sub(&total_blind_output_low);
-    let xfr_blind_diff_high = total_blind_input_high.sub(&total_blind_output_high);
-
-    let mut range_proof_blinds = Vec::with_capacity(upper_power2);
-    for output in outputs.iter() {
-        range_proof_blinds.push(output.amount_blinds.0); // low
-        range_proof_blinds.push(output.amount_blinds.1); // high
-    }
-    range_proof_blinds.push(xfr_blind_diff_low);
-    range_proof_blinds.push(xfr_blind_diff_high);
-    for _ in range_proof_blinds.len()..upper_power2 {
-        range_proof_blinds.push(RistrettoScalar::default());
-    }
-
-    let mut transcript = Transcript::new(b"Zei Range Proof");
-    let (range_proof, coms) = prove_ranges(
+    let mut openings = outputs
+        .iter()
+        .map(|x| AmountOpening {
+            amount: x.amount,
+            blinds: x.amount_blinds,
+        })
+        .collect_vec();
+    openings.push(AmountOpening {
+        amount: xfr_diff,
+        blinds: (
+            total_blind_input_low.sub(&total_blind_output_low),
+            total_blind_input_high.sub(&total_blind_output_high),
+        ),
+    });
+
+    let mut transcript = Transcript::new(RANGE_PROOF_TRANSCRIPT);
+    let (range_proof, coms) = prove_amounts(
+        prng,
+        params.range_proof_backend,
         &params.bp_gens,
         &mut transcript,
-        values.as_slice(),
-        range_proof_blinds.as_slice(),
-        BULLET_PROOF_RANGE,
+        &openings,
     )
     .c(d!(ZeiError::RangeProofProveError))?;
 
-    let diff_com_low = coms[2 * num_output];
-    let diff_com_high = coms[2 * num_output + 1];
+    let diff_com = coms[num_output];
     Ok(XfrRangeProof {
         range_proof,
-        xfr_diff_commitment_low: diff_com_low,
-        xfr_diff_commitment_high: diff_com_high,
+        xfr_diff_commitment_low: diff_com.low,
+        xfr_diff_commitment_high: diff_com.high,
     })
 }
 
@@ -576,111 +655,123 @@ pub(crate) fn batch_verify_confidential_amount<R: CryptoRng + RngCore>(
         &XfrRangeProof,
     )],
 ) -> Result<()> {
-    let mut transcripts = vec![Transcript::new(b"Zei Range Proof"); instances.len()];
-    let proofs: Vec<&RangeProof> = instances.iter().map(|(_, _, pf)| &pf.range_proof).collect();
+    let mut transcripts = vec![Transcript::new(RANGE_PROOF_TRANSCRIPT); instances.len()];
+    let proofs: Vec<&VersionedRangeProof> =
+        instances.iter().map(|(_, _, pf)| &pf.range_proof).collect();
     let mut commitments = vec![];
     for (input, output, proof) in instances {
         commitments
             .push(extract_value_commitments(input.as_slice(), output.as_slice(), proof).c(d!())?);
     }
     let value_commitments = commitments.iter().map(|c| c.as_slice()).collect_vec();
-    batch_verify_ranges(
+    batch_verify_amounts(
         prng,
         &params.bp_gens,
         proofs.as_slice(),
         &mut transcripts,
         &value_commitments,
-        BULLET_PROOF_RANGE,
     )
     .c(d!(ZeiError::XfrVerifyConfidentialAmountError))
 }
 
+/// Decompress the low and high amount commitments of the records, committing to the
+/// non-confidential amounts with a zero blinding factor.
+fn decompress_amount_commitments(
+    bars: &[BlindAssetRecord],
+) -> Result<Vec<(RistrettoPoint, RistrettoPoint)>> {
+    let pc_gens = PedersenCommitmentRistretto::default();
+    bars.iter()
+        .map(|x| match x.amount {
+            XfrAmount::Confidential((com_low, com_high)) => Ok((
+                com_low
+                    .decompress()
+                    .c(d!(ZeiError::DecompressElementError))?,
+                com_high
+                    .decompress()
+                    .c(d!(ZeiError::DecompressElementError))?,
+            )),
+            XfrAmount::NonConfidential(amount) => {
+                Ok(AmountOpening::non_confidential(amount).commit(&pc_gens))
+            }
+        })
+        .collect()
+}
+
 fn extract_value_commitments(
     inputs: &[BlindAssetRecord],
     outputs: &[BlindAssetRecord],
     proof: &XfrRangeProof,
-) -> Result<Vec<CompressedRistretto>> {
+) -> Result<Vec<AmountCommitment>> {
     let num_output = outputs.len();
-    let upper_power2 = min_greater_equal_power_of_two((2 * num_output + 2) as u32) as usize;
-    let pow2_32 = RistrettoScalar::from(POW_2_32);
-
-    let mut commitments = Vec::with_capacity(upper_power2);
+    let mut commitments = Vec::with_capacity(num_output + 1);
 
     // 1. Verify proof commitment to transfer's input - output amounts match proof commitments.
     let mut total_input_com_low = RistrettoPoint::get_identity();
     let mut total_input_com_high = RistrettoPoint::get_identity();
-    for input in inputs.iter() {
-        let (com_low, com_high) = match input.amount {
-            XfrAmount::Confidential((com_low, com_high)) => (
-                com_low
-                    .decompress()
-                    .c(d!(ZeiError::XfrVerifyConfidentialAmountError))?,
-                com_high
-                    .decompress()
-                    .c(d!(ZeiError::XfrVerifyConfidentialAmountError))?,
-            ),
-            XfrAmount::NonConfidential(amount) => {
-                let (low, high) = u64_to_u32_pair(amount);
-                let pc_gens = PedersenCommitmentRistretto::default();
-                let com_low = pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero());
-                let com_high = pc_gens.commit(RistrettoScalar::from(high), RistrettoScalar::zero());
-                (com_low, com_high)
-            }
-        };
+    for (com_low, com_high) in
+        decompress_amount_commitments(inputs).c(d!(ZeiError::XfrVerifyConfidentialAmountError))?
+    {
         total_input_com_low = total_input_com_low.add(&com_low);
         total_input_com_high = total_input_com_high.add(&com_high);
     }
+    // The commitments of the confidential amounts are already compressed. Those of the
+    // non-confidential amounts are compressed in a batch, from their halves, which are
+    // committed with halved amounts.
+    let pc_gens = PedersenCommitmentRistretto::default();
+    let half = RistrettoScalar::from(2u32).inv().c(d!())?;
     let mut total_output_com_low = RistrettoPoint::get_identity();
     let mut total_output_com_high = RistrettoPoint::get_identity();
+    let mut halves = vec![];
     for output in outputs.iter() {
         let (com_low, com_high) = match output.amount {
-            XfrAmount::Confidential((com_low, com_high)) => (
-                com_low.decompress().c(d!(ZeiError::ParameterError))?,
-                com_high.decompress().c(d!(ZeiError::ParameterError))?,
-            ),
+            XfrAmount::Confidential((low, high)) => {
+                let commitment = AmountCommitment { low, high };
+                commitments.push(commitment);
+                commitment.decompress().c(d!(ZeiError::ParameterError))?
+            }
             XfrAmount::NonConfidential(amount) => {
-                let (low, high) = u64_to_u32_pair(amount);
-                let pc_gens = PedersenCommitmentRistretto::default();
-                let com_low = pc_gens.commit(RistrettoScalar::from(low), RistrettoScalar::zero());
-                let com_high = pc_gens.commit(RistrettoScalar::from(high), RistrettoScalar::zero());
-                (com_low, com_high)
+                let (amount_low, amount_high) = u64_to_u32_pair(amount);
+                let half_low = pc_gens.commit(
+                    RistrettoScalar::from(amount_low).mul(&half),
+                    RistrettoScalar::zero(),
+                );
+                let half_high = pc_gens.commit(
+                    RistrettoScalar::from(amount_high).mul(&half),
+                    RistrettoScalar::zero(),
+                );
+                halves.push(half_low);
+                halves.push(half_high);
+                // filled in from the batch below
+                commitments.push(AmountCommitment::default());
+                (half_low.double(), half_high.double())
             }
         };
         total_output_com_low = total_output_com_low.add(&com_low);
         total_output_com_high = total_output_com_high.add(&com_high);
-
-        commitments.push(com_low.compress());
-        commitments.push(com_high.compress());
+    }
+    let mut compressed = RistrettoPoint::double_and_compress_batch(&halves).into_iter();
+    for (commitment, output) in commitments.iter_mut().zip(outputs.iter()) {
+        if let XfrAmount::NonConfidential(_) = output.amount {
+            commitment.low = compressed.next().c(d!(ZeiError::ParameterError))?;
+            commitment.high = compressed.next().c(d!(ZeiError::ParameterError))?;
+        }
     }
 
     // 2. Derive input - output commitment, compare with proof struct low and high commitments
-    let derived_xfr_diff_com = total_input_com_low.sub(&total_output_com_low).add(
-        &total_input_com_high
-            .sub(&total_output_com_high)
-            .mul(&pow2_32),
-    );
-    let proof_xfr_com_low = proof
-        .xfr_diff_commitment_low
-        .decompress()
-        .c(d!(ZeiError::DecompressElementError))?;
-    let proof_xfr_com_high = proof
-        .xfr_diff_commitment_high
-        .decompress()
-        .c(d!(ZeiError::DecompressElementError))?;
-    let proof_xfr_com_diff = proof_xfr_com_low.add(&proof_xfr_com_high.mul(&pow2_32));
-
-    if derived_xfr_diff_com.compress() != proof_xfr_com_diff.compress() {
-        return Err(eg!(ZeiError::XfrVerifyConfidentialAmountError));
-    }
+    let derived_xfr_diff_com = link_limb_commitments(&(
+        total_input_com_low.sub(&total_output_com_low),
+        total_input_com_high.sub(&total_output_com_high),
+    ));
+    let diff_com = AmountCommitment {
+        low: proof.xfr_diff_commitment_low,
+        high: proof.xfr_diff_commitment_high,
+    };
+    diff_com
+        .verify_link(&derived_xfr_diff_com)
+        .c(d!(ZeiError::XfrVerifyConfidentialAmountError))?;
 
     // 3. Push diff commitments.
-    commitments.push(proof.xfr_diff_commitment_low);
-    commitments.push(proof.xfr_diff_commitment_high);
-
-    // 4. Pad with commitments to 0.
-    for _ in commitments.len()..upper_power2 {
-        commitments.push(CompressedRistretto::identity());
-    }
+    commitments.push(diff_com);
 
     Ok(commitments)
 }
@@ -705,7 +796,7 @@ pub(crate) fn asset_proof<R: CryptoRng + RngCore>(
         asset_coms.push(commitment);
         asset_blinds.push(x.type_blind);
     }
-    let mut transcript = Transcript::new(b"AssetEquality");
+    let mut transcript = Transcript::new(ASSET_EQUALITY_TRANSCRIPT);
 
     chaum_pedersen_prove_multiple_eq(
         &mut transcript,
@@ -726,7 +817,7 @@ pub(crate) fn batch_verify_confidential_asset<R: CryptoRng + RngCore>(
     )],
 ) -> Result<()> {
     let pc_gens = PedersenCommitmentRistretto::default();
-    let mut transcript = Transcript::new(b"AssetEquality");
+    let mut transcript = Transcript::new(ASSET_EQUALITY_TRANSCRIPT);
     let mut proof_instances = Vec::with_capacity(instances.len());
     for (inputs, outputs, proof) in instances {
         let instance_commitments: Result<Vec<RistrettoPoint>> = inputs
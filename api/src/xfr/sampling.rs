@@ -0,0 +1,404 @@
+use crate::errors::ZeiError;
+use rand_chacha::ChaChaRng;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use zei_algebra::{collections::HashMap, prelude::*};
+
+/// The domain separator of note digests.
+const NOTE_DIGEST_DOMAIN: &[u8] = b"Zei Sampling Note Digest";
+/// The domain separator of the sampling seed.
+const SAMPLING_SEED_DOMAIN: &[u8] = b"Zei Sampling Seed";
+/// The prefix of a leaf in the Merkle tree of note digests.
+const LEAF_PREFIX: u8 = 0u8;
+/// The prefix of an internal node in the Merkle tree of note digests.
+const NODE_PREFIX: u8 = 1u8;
+
+/// The digest of a note.
+pub type NoteDigest = [u8; 32];
+
+/// Compute the digest of a note, or of any other structure of a block.
+pub fn note_digest<T: Serialize>(note: &T) -> Result<NoteDigest> {
+    let bytes = bincode::serialize(note).c(d!(ZeiError::SerializationError))?;
+    let mut hasher = Sha256::new();
+    hasher.update(NOTE_DIGEST_DOMAIN);
+    hasher.update(&bytes);
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&hasher.finalize());
+    Ok(digest)
+}
+
+fn hash_leaf(digest: &NoteDigest) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(digest);
+
+    let mut res = [0u8; 32];
+    res.copy_from_slice(&hasher.finalize());
+    res
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+
+    let mut res = [0u8; 32];
+    res.copy_from_slice(&hasher.finalize());
+    res
+}
+
+/// The depth of the binary Merkle tree with `num_notes` leaves.
+fn tree_depth(num_notes: u64) -> usize {
+    num_notes.next_power_of_two().trailing_zeros() as usize
+}
+
+/// A commitment to the ordered list of notes of a block.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SamplingCommitment {
+    /// The root of the binary Merkle tree of note digests.
+    pub root: [u8; 32],
+    /// The number of notes in the block.
+    pub num_notes: u64,
+}
+
+/// A sampled note, together with its inclusion proof.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteSample {
+    /// The index of the note in the block.
+    pub index: u64,
+    /// The digest of the note.
+    pub digest: NoteDigest,
+    /// The sibling nodes from the leaf to the root.
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl NoteSample {
+    /// Verify the inclusion of the sampled note under the commitment.
+    pub fn verify_inclusion(&self, commitment: &SamplingCommitment) -> Result<()> {
+        if self.index >= commitment.num_notes
+            || self.siblings.len() != tree_depth(commitment.num_notes)
+        {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let mut index = self.index;
+        let mut node = hash_leaf(&self.digest);
+        for sibling in self.siblings.iter() {
+            node = if index & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+            index >>= 1;
+        }
+
+        if node != commitment.root {
+            return Err(eg!(ZeiError::ArgumentVerificationError));
+        }
+        Ok(())
+    }
+}
+
+/// The binary Merkle tree of the note digests of a block, used to sample notes for audits.
+///
+/// The tree is padded to a power of two with zero nodes. The sample is derived from the
+/// commitment to the tree and a beacon value published after the block, such as the hash of a
+/// later block. Since the notes are fixed before the beacon is known, neither the block producer
+/// nor the auditor can choose which notes are inspected, and anyone can check the selection.
+#[derive(Clone, Debug)]
+pub struct NoteSampler {
+    num_notes: u64,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl NoteSampler {
+    /// Build the tree from the ordered note digests of a block.
+    pub fn new(digests: &[NoteDigest]) -> Result<Self> {
+        if digests.is_empty() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let num_notes = digests.len() as u64;
+
+        let mut level = digests.iter().map(hash_leaf).collect::<Vec<[u8; 32]>>();
+        level.resize(num_notes.next_power_of_two() as usize, [0u8; 32]);
+
+        let mut levels = vec![level];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_node(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next);
+        }
+
+        Ok(NoteSampler { num_notes, levels })
+    }
+
+    /// Build the tree from the ordered notes of a block.
+    pub fn from_notes<T: Serialize>(notes: &[T]) -> Result<Self> {
+        let digests = notes
+            .iter()
+            .map(note_digest)
+            .collect::<Result<Vec<NoteDigest>>>()
+            .c(d!())?;
+        Self::new(&digests).c(d!())
+    }
+
+    /// Return the commitment to the notes.
+    pub fn commitment(&self) -> SamplingCommitment {
+        SamplingCommitment {
+            root: self.levels.last().unwrap()[0],
+            num_notes: self.num_notes,
+        }
+    }
+
+    /// Return the note digest at `index`, together with its inclusion proof.
+    pub fn prove_inclusion(&self, index: u64, digest: &NoteDigest) -> Result<NoteSample> {
+        if index >= self.num_notes || self.levels[0][index as usize] != hash_leaf(digest) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let mut position = index as usize;
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .map(|level| {
+                let sibling = level[position ^ 1];
+                position >>= 1;
+                sibling
+            })
+            .collect();
+
+        Ok(NoteSample {
+            index,
+            digest: *digest,
+            siblings,
+        })
+    }
+
+    /// Sample `num_samples` distinct notes seeded by `beacon`, with their inclusion proofs.
+    pub fn sample(
+        &self,
+        digests: &[NoteDigest],
+        beacon: &[u8],
+        num_samples: usize,
+    ) -> Result<Vec<NoteSample>> {
+        if digests.len() as u64 != self.num_notes {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        sample_indices(&self.commitment(), beacon, num_samples)
+            .c(d!())?
+            .into_iter()
+            .map(|index| self.prove_inclusion(index, &digests[index as usize]))
+            .collect()
+    }
+}
+
+/// Draw a uniformly random integer in `[0, bound)` with rejection sampling.
+fn uniform_below(prng: &mut ChaChaRng, bound: u64) -> u64 {
+    let zone = u64::MAX - (u64::MAX - bound + 1) % bound;
+    loop {
+        let x = prng.next_u64();
+        if x <= zone {
+            return x % bound;
+        }
+    }
+}
+
+/// Compute the indices of the `num_samples` notes selected by `beacon` under the commitment.
+///
+/// The indices are derived deterministically from the commitment and the beacon, with a partial
+/// Fisher-Yates shuffle seeded by their hash, so that they are distinct and unbiased.
+pub fn sample_indices(
+    commitment: &SamplingCommitment,
+    beacon: &[u8],
+    num_samples: usize,
+) -> Result<Vec<u64>> {
+    if num_samples as u64 > commitment.num_notes {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(SAMPLING_SEED_DOMAIN);
+    hasher.update(&commitment.root);
+    hasher.update(&commitment.num_notes.to_le_bytes());
+    hasher.update(&(num_samples as u64).to_le_bytes());
+    hasher.update(&(beacon.len() as u64).to_le_bytes());
+    hasher.update(beacon);
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&hasher.finalize());
+    let mut prng = ChaChaRng::from_seed(seed);
+
+    // a sparse representation of the permutation of the indices
+    let mut swapped = HashMap::new();
+    let mut indices = Vec::with_capacity(num_samples);
+    for i in 0..num_samples as u64 {
+        let j = i + uniform_below(&mut prng, commitment.num_notes - i);
+        let value_j = *swapped.get(&j).unwrap_or(&j);
+        let value_i = *swapped.get(&i).unwrap_or(&i);
+        swapped.insert(j, value_i);
+        indices.push(value_j);
+    }
+    Ok(indices)
+}
+
+/// Verify that `samples` are exactly the notes selected by `beacon` under the commitment,
+/// and that each of them is included in the block.
+/// At least `min_samples` notes, and at least one, must be sampled, so that an empty or
+/// truncated sample does not pass for an audit of the block.
+pub fn verify_samples(
+    commitment: &SamplingCommitment,
+    beacon: &[u8],
+    samples: &[NoteSample],
+    min_samples: usize,
+) -> Result<()> {
+    if samples.is_empty() || samples.len() < min_samples {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let indices = sample_indices(commitment, beacon, samples.len()).c(d!())?;
+    for (index, sample) in indices.iter().zip(samples.iter()) {
+        if *index != sample.index {
+            return Err(eg!(ZeiError::ArgumentVerificationError));
+        }
+        sample
+            .verify_inclusion(commitment)
+            .c(d!(ZeiError::ArgumentVerificationError))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        sampling::{note_digest, sample_indices, verify_samples, NoteDigest, NoteSampler},
+        sig::XfrKeyPair,
+        structs::{AssetRecordTemplate, AssetType},
+        tests::create_xfr,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn note_sampling() {
+        let mut prng = test_rng();
+        for num_notes in [1usize, 2, 5, 8, 13] {
+            let digests = (0..num_notes)
+                .map(|_| {
+                    let mut digest = [0u8; 32];
+                    prng.fill_bytes(&mut digest);
+                    digest
+                })
+                .collect::<Vec<NoteDigest>>();
+            let sampler = NoteSampler::new(&digests).unwrap();
+            let commitment = sampler.commitment();
+
+            for (i, digest) in digests.iter().enumerate() {
+                let sample = sampler.prove_inclusion(i as u64, digest).unwrap();
+                assert!(sample.verify_inclusion(&commitment).is_ok());
+            }
+
+            let num_samples = (num_notes + 1) / 2;
+            let samples = sampler.sample(&digests, b"beacon", num_samples).unwrap();
+            assert!(verify_samples(&commitment, b"beacon", &samples, num_samples).is_ok());
+
+            // an empty or a truncated sample is rejected
+            assert!(verify_samples(&commitment, b"beacon", &[], 0).is_err());
+            assert!(verify_samples(&commitment, b"beacon", &samples, num_samples + 1).is_err());
+
+            let mut indices = samples.iter().map(|s| s.index).collect::<Vec<u64>>();
+            indices.sort_unstable();
+            indices.dedup();
+            assert_eq!(indices.len(), num_samples);
+
+            // all the notes can be sampled, but no more
+            assert_eq!(
+                sample_indices(&commitment, b"beacon", num_notes)
+                    .unwrap()
+                    .into_iter()
+                    .sorted()
+                    .collect::<Vec<u64>>(),
+                (0..num_notes as u64).collect::<Vec<u64>>()
+            );
+            assert!(sample_indices(&commitment, b"beacon", num_notes + 1).is_err());
+
+            // a tampered digest is not included
+            let mut tampered = samples[0].clone();
+            tampered.digest[0] ^= 1;
+            assert!(tampered.verify_inclusion(&commitment).is_err());
+        }
+    }
+
+    #[test]
+    fn note_sampling_cannot_be_chosen() {
+        let mut prng = test_rng();
+        let digests = (0..64u8).map(|i| [i; 32]).collect::<Vec<NoteDigest>>();
+        let sampler = NoteSampler::new(&digests).unwrap();
+        let commitment = sampler.commitment();
+
+        let samples = sampler.sample(&digests, b"beacon", 4).unwrap();
+        assert!(verify_samples(&commitment, b"another beacon", &samples, 4).is_err());
+
+        // selecting other notes is rejected, even with valid inclusion proofs
+        let mut chosen = samples.clone();
+        let index = (0..64u64)
+            .find(|i| samples.iter().all(|s| s.index != *i))
+            .unwrap();
+        chosen[0] = sampler
+            .prove_inclusion(index, &digests[index as usize])
+            .unwrap();
+        assert!(chosen[0].verify_inclusion(&commitment).is_ok());
+        assert!(verify_samples(&commitment, b"beacon", &chosen, 4).is_err());
+
+        // the randomness changes with the beacon
+        let mut beacon = [0u8; 32];
+        prng.fill_bytes(&mut beacon);
+        assert_ne!(
+            sample_indices(&commitment, &beacon, 8).unwrap(),
+            sample_indices(&commitment, b"beacon", 8).unwrap()
+        );
+    }
+
+    #[test]
+    fn xfr_note_sampling() {
+        let mut prng = test_rng();
+        let notes = (0..3u64)
+            .map(|i| {
+                let sender = XfrKeyPair::generate(&mut prng);
+                let receiver = XfrKeyPair::generate(&mut prng);
+                let template = |pk| {
+                    AssetRecordTemplate::with_no_asset_tracing(
+                        10 + i,
+                        AssetType::from_identical_byte(1u8),
+                        AssetRecordType::NonConfidentialAmount_NonConfidentialAssetType,
+                        pk,
+                    )
+                };
+                create_xfr(
+                    &mut prng,
+                    &[template(sender.get_pk())],
+                    &[template(receiver.get_pk())],
+                    &[&sender],
+                )
+                .0
+            })
+            .collect::<Vec<_>>();
+
+        let sampler = NoteSampler::from_notes(&notes).unwrap();
+        let digests = notes
+            .iter()
+            .map(|note| note_digest(note).unwrap())
+            .collect::<Vec<NoteDigest>>();
+        let samples = sampler.sample(&digests, b"beacon", 2).unwrap();
+        assert!(verify_samples(&sampler.commitment(), b"beacon", &samples, 2).is_ok());
+        for sample in samples.iter() {
+            assert_eq!(
+                note_digest(&notes[sample.index as usize]).unwrap(),
+                sample.digest
+            );
+        }
+    }
+}
@@ -1,5 +1,9 @@
 use crate::anon_xfr::keys::{AXfrPubKey, AXfrSecretKey};
+use crate::xfr::view_key::hybrid_decrypt_aead_with_scalar;
 use ark_serialize::{Flags, SWFlags};
+use curve25519_dalek::{
+    edwards::CompressedEdwardsY, montgomery::MontgomeryPoint, scalar::Scalar as DalekScalar,
+};
 use digest::consts::U64;
 use ed25519_dalek::{
     ExpandedSecretKey, PublicKey as Ed25519PublicKey, SecretKey as Ed25519SecretKey,
@@ -12,16 +16,25 @@ use libsecp256k1::{
     Signature as Secp256k1Signature,
 };
 use sha3::{Digest, Keccak256};
+use std::{future::Future, pin::Pin};
 use wasm_bindgen::prelude::*;
 use zei_algebra::{
     cmp::Ordering,
     hash::{Hash, Hasher},
     prelude::*,
-    ristretto::RistrettoScalar,
+    ristretto::{self, RistrettoScalar},
     secp256k1::{SECP256K1Scalar, SECP256K1G1},
 };
-use zei_crypto::basic::hybrid_encryption::{
-    hybrid_decrypt_with_ed25519_secret_key, hybrid_encrypt_ed25519, ZeiHybridCiphertext,
+use zei_crypto::basic::{
+    aead::Aead,
+    hybrid_encryption::{
+        committing_symmetric_decrypt, committing_symmetric_encrypt,
+        hybrid_decrypt_committing_with_ed25519_secret_key, hybrid_decrypt_with_ed25519_secret_key,
+        hybrid_encrypt_aead, hybrid_encrypt_aead_ed25519, hybrid_encrypt_committing_ed25519,
+        hybrid_encrypt_ed25519, Ctext, XPublicKey, XSecretKey, ZeiCommittingCiphertext,
+        ZeiHybridCiphertext, COMMITTING_TAG_LENGTH,
+    },
+    kem::Secp256k1Kem,
 };
 
 /// The length of the secret key for confidential transfer.
@@ -197,7 +210,61 @@ impl XfrPublicKey {
                 bytes.append(&mut ctext);
                 Ok(bytes)
             }
-            XfrPublicKeyInner::Address(_) => panic!("Address not supported"),
+            XfrPublicKeyInner::Address(_) => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+
+    /// Hybrid encryption with a key-committing scheme, whose lock cannot be decrypted under
+    /// another key than this one, see [`XfrSecretKey::hybrid_decrypt_committing`].
+    pub fn hybrid_encrypt_committing<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self.0 {
+            XfrPublicKeyInner::Ed25519(pk) => {
+                Ok(hybrid_encrypt_committing_ed25519(prng, &pk, msg).zei_to_bytes())
+            }
+            XfrPublicKeyInner::Secp256k1(pk) => {
+                let bytes = convert_point_libsecp256k1_to_algebra(&pk);
+                let point = SECP256K1G1::from_compressed_bytes(&bytes).c(d!())?;
+                let share_scalar = SECP256K1Scalar::random(prng);
+                let share = SECP256K1G1::get_base()
+                    .mul(&share_scalar)
+                    .to_compressed_bytes();
+                let key = secp256k1_symmetric_key(&point.mul(&share_scalar));
+                let (ctext, tag) = committing_symmetric_encrypt(&key, &share, msg);
+                let mut bytes = share;
+                bytes.extend_from_slice(&tag);
+                bytes.extend_from_slice(&ctext.0);
+                Ok(bytes)
+            }
+            XfrPublicKeyInner::Address(_) => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+
+    /// Hybrid encryption with an AEAD algorithm and a key-committing scheme, whose lock names the
+    /// algorithm and cannot be decrypted under another key than this one,
+    /// see [`XfrSecretKey::hybrid_decrypt_aead`].
+    pub fn hybrid_encrypt_aead<A: Aead, R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self.0 {
+            XfrPublicKeyInner::Ed25519(pk) => {
+                Ok(hybrid_encrypt_aead_ed25519::<A, R>(prng, &pk, msg)
+                    .c(d!())?
+                    .zei_to_bytes())
+            }
+            XfrPublicKeyInner::Secp256k1(pk) => {
+                let bytes = convert_point_libsecp256k1_to_algebra(&pk);
+                let point = SECP256K1G1::from_compressed_bytes(&bytes).c(d!())?;
+                Ok(hybrid_encrypt_aead::<Secp256k1Kem, A, R>(prng, &point, msg)
+                    .c(d!())?
+                    .zei_to_bytes())
+            }
+            XfrPublicKeyInner::Address(_) => Err(eg!(ZeiError::ParameterError)),
         }
     }
 
@@ -290,6 +357,40 @@ impl XfrPublicKey {
         }
     }
 
+    /// Check if the public key has its canonical encoding. An Ed25519 public key may have
+    /// several encodings of the same point, while the other keys are encoded canonically.
+    pub fn is_canonical(&self) -> bool {
+        match self.0 {
+            XfrPublicKeyInner::Ed25519(pk) => {
+                ristretto::CompressedEdwardsY::from_slice(pk.as_bytes()).is_canonical()
+            }
+            _ => true,
+        }
+    }
+
+    /// Convert an Ed25519 public key into the Montgomery (X25519) form.
+    /// Return an error for non-Ed25519 keys, invalid points, and small-order points.
+    pub fn to_x25519(&self) -> Result<XPublicKey> {
+        let montgomery = self.to_montgomery().c(d!())?;
+        XPublicKey::zei_from_bytes(montgomery.as_bytes())
+    }
+
+    // Decompress the Ed25519 public key and map it to the Montgomery curve.
+    fn to_montgomery(&self) -> Result<MontgomeryPoint> {
+        match self.0 {
+            XfrPublicKeyInner::Ed25519(pk) => {
+                let point = CompressedEdwardsY::from_slice(pk.as_bytes())
+                    .decompress()
+                    .c(d!(ZeiError::DecompressElementError))?;
+                if point.is_small_order() {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                Ok(point.to_montgomery())
+            }
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+
     /// Create a (fake) public key through hashing-to-curve from arbitrary bytes
     pub fn hash_from_bytes<D>(bytes: &[u8]) -> Self
     where
@@ -300,6 +401,90 @@ impl XfrPublicKey {
     }
 }
 
+impl From<Ed25519PublicKey> for XfrPublicKey {
+    fn from(pk: Ed25519PublicKey) -> Self {
+        XfrPublicKey(XfrPublicKeyInner::Ed25519(pk))
+    }
+}
+
+impl From<Secp256k1PublicKey> for XfrPublicKey {
+    fn from(pk: Secp256k1PublicKey) -> Self {
+        XfrPublicKey(XfrPublicKeyInner::Secp256k1(pk))
+    }
+}
+
+impl TryFrom<&XfrPublicKey> for Ed25519PublicKey {
+    type Error = Box<dyn RucError>;
+
+    /// Extract the Ed25519 public key, failing for other key types.
+    fn try_from(pk: &XfrPublicKey) -> Result<Self> {
+        match pk.0 {
+            XfrPublicKeyInner::Ed25519(pk) => Ok(pk),
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+}
+
+impl TryFrom<&XfrPublicKey> for Secp256k1PublicKey {
+    type Error = Box<dyn RucError>;
+
+    /// Extract the secp256k1 public key, failing for other key types.
+    /// An address only keeps the hash of the key, so it cannot be converted back.
+    fn try_from(pk: &XfrPublicKey) -> Result<Self> {
+        match pk.0 {
+            XfrPublicKeyInner::Secp256k1(pk) => Ok(pk),
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+}
+
+impl TryFrom<&XfrPublicKey> for SECP256K1G1 {
+    type Error = Box<dyn RucError>;
+
+    /// Convert a secp256k1 public key into the algebra point.
+    fn try_from(pk: &XfrPublicKey) -> Result<Self> {
+        let pk = Secp256k1PublicKey::try_from(pk).c(d!())?;
+        SECP256K1G1::from_compressed_bytes(&convert_point_libsecp256k1_to_algebra(&pk))
+            .c(d!(ZeiError::DeserializationError))
+    }
+}
+
+impl TryFrom<&SECP256K1G1> for XfrPublicKey {
+    type Error = Box<dyn RucError>;
+
+    /// Convert an algebra point into a secp256k1 public key, rejecting the identity.
+    fn try_from(point: &SECP256K1G1) -> Result<Self> {
+        if *point == SECP256K1G1::get_identity() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        let mut bytes = [0u8; 65];
+        bytes[0] = 0x04;
+        let mut x = point.get_x().to_bytes();
+        let mut y = point.get_y().to_bytes();
+        x.reverse();
+        y.reverse();
+        bytes[1..33].copy_from_slice(&x);
+        bytes[33..65].copy_from_slice(&y);
+        let pk = Secp256k1PublicKey::parse(&bytes).c(d!(ZeiError::DeserializationError))?;
+        Ok(XfrPublicKey(XfrPublicKeyInner::Secp256k1(pk)))
+    }
+}
+
+impl TryFrom<&[u8]> for XfrPublicKey {
+    type Error = Box<dyn RucError>;
+
+    /// Parse the public key with [`XfrPublicKey::from_bytes`].
+    fn try_from(bytes: &[u8]) -> Result<Self> {
+        XfrPublicKey::from_bytes(bytes).c(d!())
+    }
+}
+
+impl From<&XfrPublicKey> for [u8; XFR_PUBLIC_KEY_LENGTH] {
+    fn from(pk: &XfrPublicKey) -> Self {
+        pk.to_bytes()
+    }
+}
+
 impl Clone for XfrSecretKey {
     fn clone(&self) -> Self {
         Self::from_bytes(&self.to_bytes()).unwrap()
@@ -362,6 +547,9 @@ impl XfrSecretKey {
                 Ok(hybrid_decrypt_with_ed25519_secret_key(&ctext, sk))
             }
             XfrSecretKey::Secp256k1(sk) => {
+                if lock.len() < 33 {
+                    return Err(eg!(ZeiError::DecryptionError));
+                }
                 let s: LibSecp256k1Scalar = (*sk).into();
                 let bytes = convert_scalar_libsecp256k1_to_algebra(&s.0);
                 let sk = AXfrSecretKey(SECP256K1Scalar::from_bytes(&bytes)?);
@@ -369,10 +557,47 @@ impl XfrSecretKey {
 
                 sk.decrypt(&share, &lock[33..])
             }
-            XfrSecretKey::Address(_) => panic!("Address not supported"),
+            XfrSecretKey::Address(_) => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+
+    /// Hybrid decryption of a lock of [`XfrPublicKey::hybrid_encrypt_committing`].
+    /// Return an error if the lock is not encrypted under this key or has been modified,
+    /// so that trying many keys never decrypts the lock to a wrong plaintext.
+    pub fn hybrid_decrypt_committing(&self, lock: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            XfrSecretKey::Ed25519(sk) => {
+                let ctext = ZeiCommittingCiphertext::zei_from_bytes(lock).c(d!())?;
+                hybrid_decrypt_committing_with_ed25519_secret_key(&ctext, sk).c(d!())
+            }
+            XfrSecretKey::Secp256k1(sk) => {
+                if lock.len() < 33 + COMMITTING_TAG_LENGTH {
+                    return Err(eg!(ZeiError::DecryptionError));
+                }
+                let s: LibSecp256k1Scalar = (*sk).into();
+                let bytes = convert_scalar_libsecp256k1_to_algebra(&s.0);
+                let scalar = SECP256K1Scalar::from_bytes(&bytes).c(d!())?;
+                let share = SECP256K1G1::from_compressed_bytes(&lock[0..33])
+                    .c(d!(ZeiError::DecryptionError))?;
+                let key = secp256k1_symmetric_key(&share.mul(&scalar));
+                let mut tag = [0u8; COMMITTING_TAG_LENGTH];
+                tag.copy_from_slice(&lock[33..33 + COMMITTING_TAG_LENGTH]);
+                let ctext = Ctext(lock[33 + COMMITTING_TAG_LENGTH..].to_vec());
+                committing_symmetric_decrypt(&key, &lock[0..33], &ctext, &tag).c(d!())
+            }
+            XfrSecretKey::Address(_) => Err(eg!(ZeiError::ParameterError)),
         }
     }
 
+    /// Hybrid decryption of a lock of [`XfrPublicKey::hybrid_encrypt_aead`], with the AEAD
+    /// algorithm named in the lock. Return an error if the lock is not encrypted under this key
+    /// or has been modified, so that trying many keys never decrypts the lock to a wrong
+    /// plaintext.
+    pub fn hybrid_decrypt_aead(&self, lock: &[u8]) -> Result<Vec<u8>> {
+        let (key_type, s) = self.as_scalar_bytes();
+        hybrid_decrypt_aead_with_scalar(&key_type, &s, lock).c(d!())
+    }
+
     /// Sign a message.
     pub fn sign(&self, message: &[u8]) -> Result<XfrSignature> {
         match self {
@@ -489,6 +714,49 @@ impl XfrSecretKey {
         let sk = Secp256k1SecretKey::parse_slice(bytes).c(d!(ZeiError::DeserializationError))?;
         Ok(XfrSecretKey::Address(sk))
     }
+
+    /// Convert an Ed25519 secret key into the X25519 secret key with the same (clamped) scalar,
+    /// so that `XPublicKey::from(&sk.to_x25519()?) == pk.to_x25519()?`.
+    pub fn to_x25519(&self) -> Result<XSecretKey> {
+        match self {
+            XfrSecretKey::Ed25519(_) => {
+                let (_, key_bytes) = self.as_scalar_bytes();
+                XSecretKey::zei_from_bytes(&key_bytes)
+            }
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+
+    /// Compute the Diffie-Hellman shared secret with another public key of the same key type.
+    /// For Ed25519 keys, the exchange is done over X25519 and returns the Montgomery u-coordinate;
+    /// for secp256k1 keys, it returns the compressed shared point.
+    pub fn diffie_hellman(&self, pk: &XfrPublicKey) -> Result<Vec<u8>> {
+        match (self, pk.0) {
+            (XfrSecretKey::Ed25519(_), XfrPublicKeyInner::Ed25519(_)) => {
+                let (_, key_bytes) = self.as_scalar_bytes();
+                let mut bits = [0u8; 32];
+                bits.copy_from_slice(&key_bytes);
+                let shared = pk.to_montgomery().c(d!())? * DalekScalar::from_bits(bits);
+                if shared.as_bytes() == &[0u8; 32] {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                Ok(shared.to_bytes().to_vec())
+            }
+            (XfrSecretKey::Secp256k1(_), XfrPublicKeyInner::Secp256k1(other)) => {
+                let (_, key_bytes) = self.as_scalar_bytes();
+                let scalar = SECP256K1Scalar::from_bytes(&key_bytes)?;
+                let point = SECP256K1G1::from_compressed_bytes(
+                    &convert_point_libsecp256k1_to_algebra(&other),
+                )?;
+                let shared = point.mul(&scalar);
+                if shared == SECP256K1G1::get_identity() {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                Ok(shared.to_compressed_bytes())
+            }
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
 }
 
 impl XfrKeyPair {
@@ -543,6 +811,21 @@ impl XfrKeyPair {
         self.sec_key.hybrid_decrypt(lock)
     }
 
+    /// Hybrid decryption with the key-committing scheme.
+    pub fn hybrid_decrypt_committing(&self, lock: &[u8]) -> Result<Vec<u8>> {
+        self.sec_key.hybrid_decrypt_committing(lock)
+    }
+
+    /// Hybrid decryption with an AEAD algorithm.
+    pub fn hybrid_decrypt_aead(&self, lock: &[u8]) -> Result<Vec<u8>> {
+        self.sec_key.hybrid_decrypt_aead(lock)
+    }
+
+    /// Compute the Diffie-Hellman shared secret with another public key.
+    pub fn diffie_hellman(&self, pk: &XfrPublicKey) -> Result<Vec<u8>> {
+        self.sec_key.diffie_hellman(pk)
+    }
+
     /// Sign a message.
     pub fn sign(&self, msg: &[u8]) -> Result<XfrSignature> {
         self.sec_key.sign(msg)
@@ -573,6 +856,57 @@ impl XfrKeyPair {
     }
 }
 
+// Derive the symmetric key of the committing scheme from a secp256k1 Diffie-Hellman point.
+fn secp256k1_symmetric_key(dh: &SECP256K1G1) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&sha2::Sha256::digest(&dh.to_compressed_bytes()));
+    key
+}
+
+/// A signer of messages under a public key, which does not have to hold the secret key in
+/// memory, e.g., a hardware wallet or an HSM.
+pub trait Signer {
+    /// Return the public key of the signer.
+    fn public_key(&self) -> XfrPublicKey;
+
+    /// Sign a message.
+    fn sign(&self, message: &[u8]) -> Result<XfrSignature>;
+}
+
+/// The software signer, which holds the secret key in memory.
+impl Signer for XfrKeyPair {
+    fn public_key(&self) -> XfrPublicKey {
+        self.pub_key
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<XfrSignature> {
+        self.sec_key.sign(message)
+    }
+}
+
+/// The signature returned by an asynchronous signer.
+pub type SignatureFuture<'a> = Pin<Box<dyn Future<Output = Result<XfrSignature>> + 'a>>;
+
+/// A signer that returns its signatures asynchronously, e.g., a device that waits for the
+/// confirmation of its user. Every [`Signer`] is an asynchronous signer that is always ready.
+pub trait AsyncSigner {
+    /// Return the public key of the signer.
+    fn public_key(&self) -> XfrPublicKey;
+
+    /// Request the signature of a message.
+    fn sign_async<'a>(&'a self, message: &'a [u8]) -> SignatureFuture<'a>;
+}
+
+impl<S: Signer> AsyncSigner for S {
+    fn public_key(&self) -> XfrPublicKey {
+        Signer::public_key(self)
+    }
+
+    fn sign_async<'a>(&'a self, message: &'a [u8]) -> SignatureFuture<'a> {
+        Box::pin(std::future::ready(Signer::sign(self, message)))
+    }
+}
+
 impl ZeiFromToBytes for XfrKeyPair {
     fn zei_to_bytes(&self) -> Vec<u8> {
         let mut vec = vec![];
@@ -582,6 +916,9 @@ impl ZeiFromToBytes for XfrKeyPair {
     }
 
     fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < XFR_SECRET_KEY_LENGTH {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
         Ok(XfrKeyPair {
             sec_key: XfrSecretKey::zei_from_bytes(&bytes[0..XFR_SECRET_KEY_LENGTH]).c(d!())?,
             pub_key: XfrPublicKey::zei_from_bytes(&bytes[XFR_SECRET_KEY_LENGTH..]).c(d!())?,
@@ -590,6 +927,64 @@ impl ZeiFromToBytes for XfrKeyPair {
 }
 
 impl XfrSignature {
+    /// Check if a signature of `pub_key` over `message` has its canonical form: an Ed25519
+    /// signature must have a reduced scalar, and a secp256k1 signature must have a low `s`
+    /// and the recovery id of the public key.
+    pub fn is_canonical(&self, pub_key: &XfrPublicKey, message: &[u8]) -> bool {
+        match self.normalize(pub_key, message) {
+            Ok(normalized) => normalized.to_bytes() == self.to_bytes(),
+            Err(_) => false,
+        }
+    }
+
+    /// Return the canonical form of a signature of `pub_key` over `message`, which is valid if
+    /// and only if the signature is. Return an error if the recovery id of a secp256k1
+    /// signature cannot be found.
+    pub fn normalize(&self, pub_key: &XfrPublicKey, message: &[u8]) -> Result<XfrSignature> {
+        match self {
+            XfrSignature::Ed25519(sign) => {
+                // `s` and `s + l` are the same scalar for the base point, of order `l`
+                let mut bytes = sign.to_bytes();
+                let mut s_bytes = [0u8; 32];
+                s_bytes.copy_from_slice(&bytes[32..]);
+                bytes[32..].copy_from_slice(&DalekScalar::from_bytes_mod_order(s_bytes).to_bytes());
+                let sign =
+                    Ed25519Signature::from_bytes(&bytes).c(d!(ZeiError::DeserializationError))?;
+                Ok(XfrSignature::Ed25519(sign))
+            }
+            XfrSignature::Secp256k1(sign, _) => {
+                let mut sign = *sign;
+                sign.normalize_s();
+                let mut hasher = Keccak256::new();
+                hasher.update(message);
+                let msg =
+                    Message::parse_slice(&hasher.finalize()[..]).c(d!(ZeiError::SignatureError))?;
+                for id in 0..2u8 {
+                    let rec = RecoveryId::parse(id).c(d!(ZeiError::SignatureError))?;
+                    let recovered = match recover(&msg, &sign, &rec) {
+                        Ok(pk) => XfrPublicKey(XfrPublicKeyInner::Secp256k1(pk)),
+                        Err(_) => continue,
+                    };
+                    if recovered == *pub_key {
+                        return Ok(XfrSignature::Secp256k1(sign, rec));
+                    }
+                }
+                Err(eg!(ZeiError::SignatureError))
+            }
+            XfrSignature::Address(sign, rec) => {
+                // negating `s` flips the parity of the recovered point
+                let mut sign = *sign;
+                let mut rec = rec.serialize();
+                if sign.s.is_high() {
+                    sign.normalize_s();
+                    rec ^= 1;
+                }
+                let rec = RecoveryId::parse(rec).c(d!(ZeiError::SignatureError))?;
+                Ok(XfrSignature::Address(sign, rec))
+            }
+        }
+    }
+
     /// Convert into bytes.
     pub fn to_bytes(&self) -> [u8; XFR_SIGNATURE_LENGTH] {
         let mut bytes = [0u8; XFR_SIGNATURE_LENGTH];
@@ -663,29 +1058,104 @@ pub struct XfrMultiSig {
 impl XfrMultiSig {
     /// Sign a multisig under a list of key pairs.
     pub fn sign(keypairs: &[&XfrKeyPair], message: &[u8]) -> Result<Self> {
-        // sort the key pairs based on alphabetical order of their public keys
-        let mut sorted = keypairs.to_owned();
-        sorted.sort_unstable_by_key(|kp| kp.pub_key.zei_to_bytes());
+        let signers = keypairs
+            .iter()
+            .map(|kp| -> &dyn Signer { *kp })
+            .collect_vec();
+        Self::sign_with_signers(&signers, message)
+    }
+
+    /// Sign a multisig with a list of signers.
+    pub fn sign_with_signers(signers: &[&dyn Signer], message: &[u8]) -> Result<Self> {
+        // sort the signers based on alphabetical order of their public keys
+        let mut sorted = signers.to_owned();
+        sorted.sort_unstable_by_key(|signer| signer.public_key().zei_to_bytes());
         let mut signatures = vec![];
-        for kp in sorted {
-            signatures.push(kp.sign(message)?);
+        for signer in sorted {
+            signatures.push(signer.sign(message)?);
+        }
+        Ok(XfrMultiSig { signatures })
+    }
+
+    /// Sign a multisig with a list of asynchronous signers, requested one after the other.
+    pub async fn sign_async(signers: &[&dyn AsyncSigner], message: &[u8]) -> Result<Self> {
+        // sort the signers based on alphabetical order of their public keys
+        let mut sorted = signers.to_owned();
+        sorted.sort_unstable_by_key(|signer| signer.public_key().zei_to_bytes());
+        let mut signatures = vec![];
+        for signer in sorted {
+            signatures.push(signer.sign_async(message).await.c(d!())?);
         }
         Ok(XfrMultiSig { signatures })
     }
 
     /// Verify a multisig.
     pub fn verify(&self, pubkeys: &[&XfrPublicKey], message: &[u8]) -> Result<()> {
+        self.check(pubkeys, message).map_err(|err| eg!(err))
+    }
+
+    /// Verify a multisig, and return the cause of a failure as a `ZeiError`, so that it can be
+    /// the source of the error of the note that carries the multisig.
+    pub(crate) fn check(
+        &self,
+        pubkeys: &[&XfrPublicKey],
+        message: &[u8],
+    ) -> core::result::Result<(), ZeiError> {
         if pubkeys.len() != self.signatures.len() {
-            return Err(eg!(ZeiError::SignatureError));
+            return Err(ZeiError::LengthMismatch {
+                expected: pubkeys.len(),
+                actual: self.signatures.len(),
+            });
         }
         // sort the key pairs based on alphabetical order of their public keys
         let mut sorted = pubkeys.to_owned();
         sorted.sort_unstable_by_key(|k| k.zei_to_bytes());
-        for (pk, sig) in sorted.iter().zip(self.signatures.iter()) {
-            pk.verify(&message, &sig).c(d!())?;
+        for (index, (pk, sig)) in sorted.iter().zip(self.signatures.iter()).enumerate() {
+            if pk.verify(message, sig).is_err() {
+                return Err(ZeiError::SignerError {
+                    index,
+                    source: Box::new(ZeiError::SignatureError),
+                });
+            }
         }
         Ok(())
     }
+
+    /// Check if a multisig of `pubkeys` over `message` has its canonical form, see [`Self::normalize`].
+    pub fn is_canonical(&self, pubkeys: &[&XfrPublicKey], message: &[u8]) -> bool {
+        match self.normalize(pubkeys, message) {
+            Ok(normalized) => normalized == *self,
+            Err(_) => false,
+        }
+    }
+
+    /// Return the canonical form of a multisig of `pubkeys` over `message`, where each signature
+    /// has its canonical form, and the signatures of a same signer, which can be swapped,
+    /// are sorted by their bytes.
+    pub fn normalize(&self, pubkeys: &[&XfrPublicKey], message: &[u8]) -> Result<XfrMultiSig> {
+        if pubkeys.len() != self.signatures.len() {
+            return Err(eg!(ZeiError::LengthMismatch {
+                expected: pubkeys.len(),
+                actual: self.signatures.len(),
+            }));
+        }
+        // sort the key pairs based on alphabetical order of their public keys
+        let mut sorted = pubkeys.to_owned();
+        sorted.sort_unstable_by_key(|k| k.zei_to_bytes());
+        let mut signed = vec![];
+        for (index, (pk, sig)) in sorted.iter().zip(self.signatures.iter()).enumerate() {
+            let sig = sig.normalize(pk, message).c(d!(ZeiError::SignerError {
+                index,
+                source: Box::new(ZeiError::SignatureError),
+            }))?;
+            signed.push((pk.zei_to_bytes(), sig.to_bytes(), sig));
+        }
+        // the keys are already sorted, so this only sorts the signatures of a same key
+        signed.sort_unstable_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        Ok(XfrMultiSig {
+            signatures: signed.into_iter().map(|(_, _, sig)| sig).collect(),
+        })
+    }
 }
 
 /// Function helper for get recovery id from u64.
@@ -743,10 +1213,22 @@ fn convert_scalar_libsecp256k1_to_algebra(b: &[u32; 8]) -> Vec<u8> {
 
 #[cfg(test)]
 mod test {
-    use crate::xfr::sig::{XfrKeyPair, XfrMultiSig, XfrPublicKeyInner, XfrSecretKey};
+    use crate::xfr::sig::{
+        AsyncSigner, SignatureFuture, Signer, XfrKeyPair, XfrMultiSig, XfrPublicKey,
+        XfrPublicKeyInner, XfrSecretKey, XfrSignature, XFR_PUBLIC_KEY_LENGTH,
+    };
     use ark_std::{env, test_rng};
+    use ed25519_dalek::PublicKey as Ed25519PublicKey;
+    use libsecp256k1::PublicKey as Secp256k1PublicKey;
     use ruc::err::*;
-    use zei_algebra::prelude::*;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+    use zei_algebra::{prelude::*, secp256k1::SECP256K1G1};
+    use zei_crypto::basic::hybrid_encryption::XPublicKey;
 
     #[test]
     fn signatures() {
@@ -862,4 +1344,241 @@ mod test {
             "Multisignature should have verify correctly even when keylist is unordered"
         );
     }
+
+    #[test]
+    fn committing_hybrid_encryption() {
+        let mut prng = test_rng();
+        let msg = b"owner memo";
+        let keypairs = [
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        ];
+        for keypair in keypairs.iter() {
+            let lock = keypair
+                .pub_key
+                .hybrid_encrypt_committing(&mut prng, msg)
+                .unwrap();
+            assert_eq!(keypair.hybrid_decrypt_committing(&lock).unwrap(), msg);
+
+            // the lock does not decrypt under other keys, even of the same type
+            for other in [
+                XfrKeyPair::generate_ed25519(&mut prng),
+                XfrKeyPair::generate_secp256k1(&mut prng),
+            ] {
+                assert!(other.hybrid_decrypt_committing(&lock).is_err());
+            }
+
+            // nor once modified
+            let mut modified = lock.clone();
+            *modified.last_mut().unwrap() ^= 1;
+            assert!(keypair.hybrid_decrypt_committing(&modified).is_err());
+            assert!(keypair
+                .hybrid_decrypt_committing(&lock[..lock.len() - msg.len() - 1])
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn aead_hybrid_encryption() {
+        use zei_crypto::basic::aead::{Aes256GcmSivAead, ChaCha20Poly1305Aead};
+        let mut prng = test_rng();
+        let msg = b"owner memo";
+        for keypair in [
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        ] {
+            let lock = keypair
+                .pub_key
+                .hybrid_encrypt_aead::<Aes256GcmSivAead, _>(&mut prng, msg)
+                .unwrap();
+            assert_eq!(keypair.hybrid_decrypt_aead(&lock).unwrap(), msg);
+            let lock = keypair
+                .pub_key
+                .hybrid_encrypt_aead::<ChaCha20Poly1305Aead, _>(&mut prng, msg)
+                .unwrap();
+            assert_eq!(keypair.hybrid_decrypt_aead(&lock).unwrap(), msg);
+
+            let other = XfrKeyPair::generate_ed25519(&mut prng);
+            assert!(other.hybrid_decrypt_aead(&lock).is_err());
+            let mut modified = lock.clone();
+            *modified.last_mut().unwrap() ^= 1;
+            assert!(keypair.hybrid_decrypt_aead(&modified).is_err());
+        }
+        assert!(XfrKeyPair::generate_address(&mut prng)
+            .pub_key
+            .hybrid_encrypt_aead::<Aes256GcmSivAead, _>(&mut prng, msg)
+            .is_err());
+    }
+
+    #[test]
+    fn hybrid_encryption_errors() {
+        let mut prng = test_rng();
+        let msg = b"message";
+
+        let keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let lock = keypair.pub_key.hybrid_encrypt(&mut prng, msg).unwrap();
+        assert_eq!(keypair.hybrid_decrypt(&lock).unwrap(), msg);
+        assert!(keypair.hybrid_decrypt(&lock[..20]).is_err());
+
+        let address = XfrKeyPair::generate_address(&mut prng);
+        assert!(address.pub_key.hybrid_encrypt(&mut prng, msg).is_err());
+        assert!(address.hybrid_decrypt(&lock).is_err());
+    }
+
+    #[test]
+    fn x25519_conversion_and_diffie_hellman() {
+        let mut prng = test_rng();
+        let alice = XfrKeyPair::generate_ed25519(&mut prng);
+        let bob = XfrKeyPair::generate_ed25519(&mut prng);
+
+        let x_sk = alice.sec_key.to_x25519().unwrap();
+        let x_pk = alice.pub_key.to_x25519().unwrap();
+        assert_eq!(XPublicKey::from(&x_sk), x_pk);
+
+        let shared_a = alice.diffie_hellman(&bob.pub_key).unwrap();
+        let shared_b = bob.diffie_hellman(&alice.pub_key).unwrap();
+        assert_eq!(shared_a, shared_b);
+
+        let alice = XfrKeyPair::generate_secp256k1(&mut prng);
+        let bob = XfrKeyPair::generate_secp256k1(&mut prng);
+        assert_eq!(
+            alice.diffie_hellman(&bob.pub_key).unwrap(),
+            bob.diffie_hellman(&alice.pub_key).unwrap()
+        );
+        assert!(alice.pub_key.to_x25519().is_err());
+        assert!(alice.sec_key.to_x25519().is_err());
+
+        // mixed key types are rejected
+        let carol = XfrKeyPair::generate_ed25519(&mut prng);
+        assert!(alice.diffie_hellman(&carol.pub_key).is_err());
+
+        // small-order points are rejected
+        let identity = XfrPublicKey::from_bytes(&{
+            let mut bytes = [0u8; 32];
+            bytes[0] = 1;
+            bytes
+        })
+        .unwrap();
+        assert!(identity.to_x25519().is_err());
+        assert!(carol.diffie_hellman(&identity).is_err());
+    }
+
+    #[test]
+    fn public_key_conversions() {
+        let mut prng = test_rng();
+
+        let keypair = XfrKeyPair::generate_ed25519(&mut prng);
+        let ed_pk = Ed25519PublicKey::try_from(&keypair.pub_key).unwrap();
+        assert_eq!(XfrPublicKey::from(ed_pk), keypair.pub_key);
+        assert!(Secp256k1PublicKey::try_from(&keypair.pub_key).is_err());
+        assert!(SECP256K1G1::try_from(&keypair.pub_key).is_err());
+
+        let keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let secp_pk = Secp256k1PublicKey::try_from(&keypair.pub_key).unwrap();
+        assert_eq!(XfrPublicKey::from(secp_pk), keypair.pub_key);
+        assert!(Ed25519PublicKey::try_from(&keypair.pub_key).is_err());
+
+        // round trip through the algebra point
+        let point = SECP256K1G1::try_from(&keypair.pub_key).unwrap();
+        assert_eq!(XfrPublicKey::try_from(&point).unwrap(), keypair.pub_key);
+        let neg = point.neg();
+        let neg_pk = XfrPublicKey::try_from(&neg).unwrap();
+        assert_eq!(SECP256K1G1::try_from(&neg_pk).unwrap(), neg);
+        assert!(XfrPublicKey::try_from(&SECP256K1G1::get_identity()).is_err());
+
+        let keypair = XfrKeyPair::generate_address(&mut prng);
+        assert!(Secp256k1PublicKey::try_from(&keypair.pub_key).is_err());
+        assert!(SECP256K1G1::try_from(&keypair.pub_key).is_err());
+
+        // bytes
+        let bytes: [u8; XFR_PUBLIC_KEY_LENGTH] = (&keypair.pub_key).into();
+        assert_eq!(XfrPublicKey::try_from(&bytes[..]).unwrap(), keypair.pub_key);
+        assert!(XfrPublicKey::try_from(&bytes[1..]).is_err());
+    }
+
+    // A device that asks its user to confirm each signature, and answers on the next poll.
+    struct Device(XfrKeyPair);
+
+    struct Confirmation<'a> {
+        device: &'a Device,
+        message: &'a [u8],
+        confirmed: bool,
+    }
+
+    impl<'a> Future for Confirmation<'a> {
+        type Output = Result<XfrSignature>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.confirmed {
+                Poll::Ready(self.device.0.sign(self.message))
+            } else {
+                self.confirmed = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    impl AsyncSigner for Device {
+        fn public_key(&self) -> XfrPublicKey {
+            self.0.pub_key
+        }
+
+        fn sign_async<'a>(&'a self, message: &'a [u8]) -> SignatureFuture<'a> {
+            Box::pin(Confirmation {
+                device: self,
+                message,
+                confirmed: false,
+            })
+        }
+    }
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    #[test]
+    fn signers() {
+        let mut prng = test_rng();
+        let msg = b"message";
+        let keypairs = (0..3)
+            .map(|_| XfrKeyPair::generate(&mut prng))
+            .collect_vec();
+        let pubkeys = keypairs.iter().map(|kp| &kp.pub_key).collect_vec();
+        let expected = XfrMultiSig::sign(&keypairs.iter().collect_vec(), msg).unwrap();
+
+        // the key pairs are software signers
+        let signer: &dyn Signer = &keypairs[0];
+        assert_eq!(signer.public_key(), keypairs[0].pub_key);
+        assert!(keypairs[0]
+            .pub_key
+            .verify(msg, &signer.sign(msg).unwrap())
+            .is_ok());
+        let signers = keypairs
+            .iter()
+            .map(|kp| -> &dyn Signer { kp })
+            .collect_vec();
+        let multisig = XfrMultiSig::sign_with_signers(&signers, msg).unwrap();
+        assert_eq!(multisig, expected);
+        assert!(multisig.verify(&pubkeys, msg).is_ok());
+
+        // a device signs along with the software signers
+        let device = Device(keypairs[1].clone());
+        let async_signers: Vec<&dyn AsyncSigner> = vec![&keypairs[0], &device, &keypairs[2]];
+        let multisig = block_on(XfrMultiSig::sign_async(&async_signers, msg)).unwrap();
+        assert!(multisig.verify(&pubkeys, msg).is_ok());
+    }
 }
@@ -0,0 +1,1392 @@
+//! Signing sessions between a coordinator and remote co-signing services.
+//!
+//! There are two kinds of sessions:
+//! - the co-signing of [`XfrNote`]s, the only notes whose inputs may belong to several signers:
+//!   the coordinator holds a [`PartiallySignedXfr`] in a [`CoordinatorSession`], and sends a sign
+//!   request to the co-signer of each input, which answers through its [`CosignerSessions`]
+//!   registry;
+//! - the threshold signatures of a message by the group of a key generation, see
+//!   `zei_crypto::dkg` and `zei_crypto::threshold_sig`: the coordinator broadcasts each round of
+//!   a [`ThresholdCoordinatorSession`] to the signers, which answer through their
+//!   [`ThresholdSignerSessions`] registry. A threshold BLS signature takes a single round, and a
+//!   threshold Schnorr signature three: the signers commit to their nonces, reveal them once
+//!   every commitment is known, and answer the challenge of the common nonce.
+//!
+//! The other notes are signed by the single owner of their input, or authorized by the proof of
+//! the anonymous key that spends it, and do not need sessions. Both sides are serializable, so
+//! they can be persisted between messages, and the [`SigningMessage`]s are versioned, so that
+//! the network layers only transport opaque bytes.
+//!
+//! Every message carries the session identifier, the expiry time of the session, and a sequence
+//! number, and each side refuses the messages that do not fit the state of its session:
+//! a co-signer never signs two different bodies in the same session, a threshold signer never
+//! answers two challenges with the same nonce, and the coordinator only accepts one response per
+//! request. The times are provided by the caller, in seconds.
+
+use crate::xfr::{
+    partially_signed::PartiallySignedXfr,
+    sig::{Signer, XfrPublicKey, XfrSignature},
+    structs::XfrNote,
+};
+use zei_algebra::{
+    bls12_381::{BLSG1, BLSG2},
+    collections::BTreeMap,
+    prelude::*,
+    ristretto::{RistrettoPoint, RistrettoScalar},
+};
+use zei_crypto::{
+    dkg::{BLSThresholdKeyShare, DkgPublicOutput, RistrettoThresholdKeyShare},
+    threshold_sig::{
+        bls_combine, bls_sign_share, bls_verify, bls_verify_partial, check_signers,
+        schnorr_combine, schnorr_group_nonce, schnorr_nonce_commitment, schnorr_sign_share,
+        schnorr_verify, schnorr_verify_partial, BLSPartialSignature, NonceCommitment, SchnorrNonce,
+        ThresholdSchnorrSignature,
+    },
+};
+
+/// The version of the messages of signing sessions.
+pub const SIGNING_PROTOCOL_VERSION: u32 = 1;
+
+/// The length of the identifier of a signing session.
+pub const SIGNING_SESSION_ID_LENGTH: usize = 32;
+
+/// The identifier of a signing session, chosen at random by the coordinator.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SigningSessionId(pub [u8; SIGNING_SESSION_ID_LENGTH]);
+
+/// The content of a message of a signing session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningPayload {
+    /// The coordinator asks a co-signer to sign the inputs that it owns.
+    SignRequest {
+        /// The transfer to sign.
+        psxfr: PartiallySignedXfr,
+        /// The public key of the co-signer.
+        signer: XfrPublicKey,
+    },
+    /// A co-signer returns its signature of the transfer.
+    SignResponse {
+        /// The public key of the co-signer.
+        signer: XfrPublicKey,
+        /// The signature of the message of the transfer.
+        signature: XfrSignature,
+    },
+    /// Either side aborts the session.
+    Abort {
+        /// The reason, for the logs.
+        reason: String,
+    },
+    /// The coordinator asks the signers of a group to sign a message.
+    ThresholdSignRequest {
+        /// The scheme of the signature.
+        scheme: ThresholdScheme,
+        /// The message to sign.
+        message: Vec<u8>,
+        /// The indices of the signers, in increasing order.
+        signers: Vec<u32>,
+    },
+    /// A signer commits to its nonce of a threshold Schnorr signature.
+    NonceCommitment {
+        /// The index of the signer.
+        signer: u32,
+        /// The commitment to the nonce.
+        commitment: NonceCommitment,
+    },
+    /// The coordinator forwards the commitments of every signer, in the order of the signers.
+    NonceCommitments {
+        /// The commitments, with the indices of their signers.
+        commitments: Vec<(u32, NonceCommitment)>,
+    },
+    /// A signer reveals its nonce, once it knows every commitment.
+    NonceReveal {
+        /// The index of the signer.
+        signer: u32,
+        /// The public nonce.
+        nonce: RistrettoPoint,
+    },
+    /// The coordinator forwards the nonces of every signer, in the order of the signers.
+    NonceReveals {
+        /// The nonces, with the indices of their signers.
+        nonces: Vec<(u32, RistrettoPoint)>,
+    },
+    /// A signer returns its response to the challenge of a threshold Schnorr signature.
+    PartialSchnorr {
+        /// The index of the signer.
+        signer: u32,
+        /// The response of the signer.
+        response: RistrettoScalar,
+    },
+    /// A signer returns its partial threshold BLS signature.
+    PartialBLS {
+        /// The partial signature, with the index of the signer.
+        partial: BLSPartialSignature,
+    },
+}
+
+/// A message of a signing session.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningMessage {
+    /// The version of the message format.
+    pub version: u32,
+    /// The identifier of the session.
+    pub session_id: SigningSessionId,
+    /// The sequence number of the request, which the response to the request repeats.
+    pub sequence: u64,
+    /// The time after which the session is expired.
+    pub expires_at: u64,
+    /// The content of the message.
+    pub payload: SigningPayload,
+}
+
+impl SigningMessage {
+    /// Serialize the message.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self).c(d!(ZeiError::SerializationError))
+    }
+
+    /// Deserialize a message, and check its version.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let message: SigningMessage =
+            bincode::deserialize(bytes).c(d!(ZeiError::DeserializationError))?;
+        if message.version != SIGNING_PROTOCOL_VERSION {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        Ok(message)
+    }
+
+    fn check(&self, now: u64) -> Result<()> {
+        if self.version != SIGNING_PROTOCOL_VERSION {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        if now >= self.expires_at {
+            return Err(eg!(ZeiError::SigningSessionExpired));
+        }
+        Ok(())
+    }
+}
+
+/// The state of a signing session.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SigningSessionState {
+    /// Some signers of a threshold Schnorr signature did not commit to their nonces yet.
+    AwaitingNonceCommitments,
+    /// Some signers of a threshold Schnorr signature did not reveal their nonces yet.
+    AwaitingNonces,
+    /// Some inputs, or some signers of a threshold signature, are not signed yet.
+    AwaitingSignatures,
+    /// Every input is signed, or the threshold signature is combined.
+    Complete,
+    /// The session was aborted by either side.
+    Aborted,
+    /// The session expired before every input was signed.
+    Expired,
+}
+
+/// The session of the coordinator of a transfer, which collects the signatures of the co-signers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoordinatorSession {
+    id: SigningSessionId,
+    expires_at: u64,
+    psxfr: PartiallySignedXfr,
+    state: SigningSessionState,
+    next_sequence: u64,
+    /// The co-signers whose response is awaited, with the sequence numbers of their requests.
+    pending: Vec<(XfrPublicKey, u64)>,
+}
+
+impl CoordinatorSession {
+    /// Start a session collecting the signatures of a transfer until `expires_at`.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        psxfr: PartiallySignedXfr,
+        expires_at: u64,
+    ) -> Result<Self> {
+        psxfr.check_structure().c(d!())?;
+        let mut id = [0u8; SIGNING_SESSION_ID_LENGTH];
+        prng.fill_bytes(&mut id);
+        let state = if psxfr.is_complete() {
+            SigningSessionState::Complete
+        } else {
+            SigningSessionState::AwaitingSignatures
+        };
+        Ok(CoordinatorSession {
+            id: SigningSessionId(id),
+            expires_at,
+            psxfr,
+            state,
+            next_sequence: 0,
+            pending: vec![],
+        })
+    }
+
+    /// Return the identifier of the session.
+    pub fn id(&self) -> &SigningSessionId {
+        &self.id
+    }
+
+    /// Return the state of the session at the time `now`.
+    pub fn state(&self, now: u64) -> SigningSessionState {
+        match self.state {
+            SigningSessionState::AwaitingSignatures if now >= self.expires_at => {
+                SigningSessionState::Expired
+            }
+            state => state,
+        }
+    }
+
+    /// Return the transfer with the signatures collected so far.
+    pub fn psxfr(&self) -> &PartiallySignedXfr {
+        &self.psxfr
+    }
+
+    /// Create the sign request for the co-signer with the public key `signer`,
+    /// which must own an input that is not signed yet.
+    pub fn request(&mut self, signer: &XfrPublicKey, now: u64) -> Result<SigningMessage> {
+        self.check_awaiting(now).c(d!())?;
+        let owns_unsigned_input = self
+            .psxfr
+            .missing_signatures()
+            .into_iter()
+            .any(|index| self.psxfr.body.inputs[index].public_key == *signer);
+        if !owns_unsigned_input {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.pending.retain(|(pending, _)| pending != signer);
+        self.pending.push((*signer, sequence));
+        Ok(self.message(
+            sequence,
+            SigningPayload::SignRequest {
+                psxfr: self.psxfr.clone(),
+                signer: *signer,
+            },
+        ))
+    }
+
+    /// Process a message from a co-signer, and return the new state of the session.
+    pub fn receive(&mut self, message: &SigningMessage, now: u64) -> Result<SigningSessionState> {
+        self.check_awaiting(now).c(d!())?;
+        if message.session_id != self.id {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        message.check(now).c(d!())?;
+        match &message.payload {
+            SigningPayload::SignResponse { signer, signature } => {
+                // only the response to the latest request of the co-signer is accepted, once
+                let position = self
+                    .pending
+                    .iter()
+                    .position(|pending| *pending == (*signer, message.sequence))
+                    .c(d!(ZeiError::SigningSessionError))?;
+                let indices = self
+                    .psxfr
+                    .missing_signatures()
+                    .into_iter()
+                    .filter(|index| self.psxfr.body.inputs[*index].public_key == *signer)
+                    .collect_vec();
+                for index in indices {
+                    self.psxfr.add_signature(index, signature.clone()).c(d!())?;
+                }
+                self.pending.remove(position);
+                if self.psxfr.is_complete() {
+                    self.state = SigningSessionState::Complete;
+                }
+            }
+            SigningPayload::Abort { .. } => {
+                self.state = SigningSessionState::Aborted;
+            }
+            _ => {
+                return Err(eg!(ZeiError::SigningSessionError));
+            }
+        }
+        Ok(self.state)
+    }
+
+    /// Abort the session, and return the message notifying the co-signers.
+    pub fn abort(&mut self, reason: &str) -> SigningMessage {
+        self.state = SigningSessionState::Aborted;
+        self.pending.clear();
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.message(
+            sequence,
+            SigningPayload::Abort {
+                reason: reason.to_string(),
+            },
+        )
+    }
+
+    /// Finalize the transfer of a complete session into a note.
+    pub fn finalize(self) -> Result<XfrNote> {
+        if self.state != SigningSessionState::Complete {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        self.psxfr.finalize().c(d!())
+    }
+
+    fn check_awaiting(&self, now: u64) -> Result<()> {
+        match self.state(now) {
+            SigningSessionState::AwaitingSignatures => Ok(()),
+            SigningSessionState::Expired => Err(eg!(ZeiError::SigningSessionExpired)),
+            _ => Err(eg!(ZeiError::SigningSessionError)),
+        }
+    }
+
+    fn message(&self, sequence: u64, payload: SigningPayload) -> SigningMessage {
+        SigningMessage {
+            version: SIGNING_PROTOCOL_VERSION,
+            session_id: self.id,
+            sequence,
+            expires_at: self.expires_at,
+            payload,
+        }
+    }
+}
+
+/// The registry of the signing sessions of a co-signing service.
+///
+/// It signs at most one message per session: a repeated request for the same transfer is
+/// answered again, e.g., after a network failure, but a request for another transfer in the
+/// same session is refused, as well as any request in an aborted session.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosignerSessions {
+    /// The message signed in each session, with the expiry time of the session.
+    signed: BTreeMap<SigningSessionId, (Vec<u8>, u64)>,
+    aborted: BTreeMap<SigningSessionId, u64>,
+}
+
+impl CosignerSessions {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a message from a coordinator with the signer of the service, and return the
+    /// response, if any.
+    pub fn handle(
+        &mut self,
+        message: &SigningMessage,
+        signer: &dyn Signer,
+        now: u64,
+    ) -> Result<Option<SigningMessage>> {
+        message.check(now).c(d!())?;
+        let session_id = message.session_id;
+        if self.aborted.contains_key(&session_id) {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+
+        match &message.payload {
+            SigningPayload::SignRequest {
+                psxfr,
+                signer: requested,
+            } => {
+                if *requested != signer.public_key() {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                let mut psxfr = psxfr.clone();
+                psxfr.check_structure().c(d!())?;
+                let signed_message = psxfr.signed_message().c(d!())?;
+                if let Some((previous, _)) = self.signed.get(&session_id) {
+                    if *previous != signed_message {
+                        return Err(eg!(ZeiError::SigningSessionError));
+                    }
+                }
+
+                psxfr.sign(signer).c(d!())?;
+                let signature = psxfr
+                    .signatures
+                    .iter()
+                    .zip(psxfr.body.inputs.iter())
+                    .find(|(_, input)| input.public_key == *requested)
+                    .and_then(|(signature, _)| signature.clone())
+                    .c(d!(ZeiError::SignatureError))?;
+                self.signed
+                    .insert(session_id, (signed_message, message.expires_at));
+
+                Ok(Some(SigningMessage {
+                    version: SIGNING_PROTOCOL_VERSION,
+                    session_id,
+                    sequence: message.sequence,
+                    expires_at: message.expires_at,
+                    payload: SigningPayload::SignResponse {
+                        signer: *requested,
+                        signature,
+                    },
+                }))
+            }
+            SigningPayload::Abort { .. } => {
+                self.aborted.insert(session_id, message.expires_at);
+                Ok(None)
+            }
+            _ => Err(eg!(ZeiError::SigningSessionError)),
+        }
+    }
+
+    /// Forget the sessions expired at the time `now`, which cannot receive any message anymore.
+    pub fn prune(&mut self, now: u64) {
+        self.signed.retain(|_, (_, expires_at)| now < *expires_at);
+        self.aborted.retain(|_, expires_at| now < *expires_at);
+    }
+
+    /// Return the number of sessions remembered.
+    pub fn len(&self) -> usize {
+        self.signed.len() + self.aborted.len()
+    }
+
+    /// Return true if no session is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.signed.is_empty() && self.aborted.is_empty()
+    }
+}
+
+/// The sequence number of the sign request of a threshold signing session.
+const THRESHOLD_REQUEST_SEQUENCE: u64 = 0;
+/// The sequence number of the broadcast of the nonce commitments.
+const THRESHOLD_COMMITMENTS_SEQUENCE: u64 = 1;
+/// The sequence number of the broadcast of the nonces.
+const THRESHOLD_NONCES_SEQUENCE: u64 = 2;
+
+/// The scheme of a threshold signature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThresholdScheme {
+    /// Threshold Schnorr over Ristretto, whose signers exchange nonces.
+    Schnorr,
+    /// Threshold BLS over BLS12-381, whose signers sign without interaction.
+    BLS,
+}
+
+/// The public outcome of the key generation of the group that signs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThresholdGroupKey {
+    /// A threshold Schnorr key.
+    Schnorr(DkgPublicOutput<RistrettoPoint>),
+    /// A threshold BLS key.
+    BLS(DkgPublicOutput<BLSG2>),
+}
+
+impl ThresholdGroupKey {
+    /// Return the scheme of the key.
+    pub fn scheme(&self) -> ThresholdScheme {
+        match self {
+            ThresholdGroupKey::Schnorr(_) => ThresholdScheme::Schnorr,
+            ThresholdGroupKey::BLS(_) => ThresholdScheme::BLS,
+        }
+    }
+
+    fn check_signers(&self, signers: &[u32]) -> Result<()> {
+        match self {
+            ThresholdGroupKey::Schnorr(output) => check_signers(output, signers).c(d!()),
+            ThresholdGroupKey::BLS(output) => check_signers(output, signers).c(d!()),
+        }
+    }
+}
+
+/// The key share of a signer of a group.
+#[derive(Copy, Clone, Debug)]
+pub enum ThresholdSigner<'a> {
+    /// A threshold Schnorr key share.
+    Schnorr(&'a RistrettoThresholdKeyShare),
+    /// A threshold BLS key share.
+    BLS(&'a BLSThresholdKeyShare),
+}
+
+impl<'a> ThresholdSigner<'a> {
+    /// Return the index of the signer.
+    pub fn index(&self) -> u32 {
+        match self {
+            ThresholdSigner::Schnorr(key_share) => key_share.index,
+            ThresholdSigner::BLS(key_share) => key_share.index,
+        }
+    }
+
+    /// Return the scheme of the key share.
+    pub fn scheme(&self) -> ThresholdScheme {
+        match self {
+            ThresholdSigner::Schnorr(_) => ThresholdScheme::Schnorr,
+            ThresholdSigner::BLS(_) => ThresholdScheme::BLS,
+        }
+    }
+
+    fn check_signers(&self, signers: &[u32]) -> Result<()> {
+        let checked = match self {
+            ThresholdSigner::Schnorr(key_share) => check_signers(&key_share.output, signers),
+            ThresholdSigner::BLS(key_share) => check_signers(&key_share.output, signers),
+        };
+        checked.c(d!())?;
+        if !signers.contains(&self.index()) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(())
+    }
+}
+
+/// A signature of a message by the group of a threshold key.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThresholdSignature {
+    /// A threshold Schnorr signature.
+    Schnorr(ThresholdSchnorrSignature),
+    /// A threshold BLS signature.
+    BLS(BLSG1),
+}
+
+impl ThresholdSignature {
+    /// Verify the signature of a message under the key of the group.
+    pub fn verify(&self, key: &ThresholdGroupKey, message: &[u8]) -> Result<()> {
+        match (self, key) {
+            (ThresholdSignature::Schnorr(signature), ThresholdGroupKey::Schnorr(output)) => {
+                schnorr_verify(&output.public_key, message, signature).c(d!())
+            }
+            (ThresholdSignature::BLS(signature), ThresholdGroupKey::BLS(output)) => {
+                bls_verify(&output.public_key, message, signature).c(d!())
+            }
+            _ => Err(eg!(ZeiError::ParameterError)),
+        }
+    }
+}
+
+/// The session of the coordinator of a threshold signature, which runs the rounds of the
+/// signers and combines their partial signatures.
+///
+/// Each round is a single message, [`ThresholdCoordinatorSession::round`], broadcast to every
+/// signer, and ends when every signer answered it, once.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdCoordinatorSession {
+    id: SigningSessionId,
+    expires_at: u64,
+    key: ThresholdGroupKey,
+    message: Vec<u8>,
+    signers: Vec<u32>,
+    state: SigningSessionState,
+    commitments: BTreeMap<u32, NonceCommitment>,
+    nonces: BTreeMap<u32, RistrettoPoint>,
+    responses: BTreeMap<u32, RistrettoScalar>,
+    partials: BTreeMap<u32, BLSPartialSignature>,
+    signature: Option<ThresholdSignature>,
+}
+
+impl ThresholdCoordinatorSession {
+    /// Start a session for the `signers` of the group of `key` to sign `message` until
+    /// `expires_at`. The signers are given in increasing order, and at least `threshold` of them
+    /// are needed for the signature to combine.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        key: ThresholdGroupKey,
+        signers: Vec<u32>,
+        message: Vec<u8>,
+        expires_at: u64,
+    ) -> Result<Self> {
+        key.check_signers(&signers).c(d!())?;
+        let mut id = [0u8; SIGNING_SESSION_ID_LENGTH];
+        prng.fill_bytes(&mut id);
+        let state = match key.scheme() {
+            ThresholdScheme::Schnorr => SigningSessionState::AwaitingNonceCommitments,
+            ThresholdScheme::BLS => SigningSessionState::AwaitingSignatures,
+        };
+        Ok(ThresholdCoordinatorSession {
+            id: SigningSessionId(id),
+            expires_at,
+            key,
+            message,
+            signers,
+            state,
+            commitments: BTreeMap::new(),
+            nonces: BTreeMap::new(),
+            responses: BTreeMap::new(),
+            partials: BTreeMap::new(),
+            signature: None,
+        })
+    }
+
+    /// Return the identifier of the session.
+    pub fn id(&self) -> &SigningSessionId {
+        &self.id
+    }
+
+    /// Return the state of the session at the time `now`.
+    pub fn state(&self, now: u64) -> SigningSessionState {
+        match self.state {
+            SigningSessionState::AwaitingNonceCommitments
+            | SigningSessionState::AwaitingNonces
+            | SigningSessionState::AwaitingSignatures
+                if now >= self.expires_at =>
+            {
+                SigningSessionState::Expired
+            }
+            state => state,
+        }
+    }
+
+    /// Return the message of the current round, to broadcast to every signer. The same message
+    /// is returned until the round ends, so that it can be sent again after a network failure.
+    pub fn round(&self, now: u64) -> Result<SigningMessage> {
+        self.check_awaiting(now).c(d!())?;
+        let payload = match self.round_sequence() {
+            THRESHOLD_REQUEST_SEQUENCE => SigningPayload::ThresholdSignRequest {
+                scheme: self.key.scheme(),
+                message: self.message.clone(),
+                signers: self.signers.clone(),
+            },
+            THRESHOLD_COMMITMENTS_SEQUENCE => SigningPayload::NonceCommitments {
+                commitments: self.commitments.clone().into_iter().collect(),
+            },
+            _ => SigningPayload::NonceReveals {
+                nonces: self.nonces.clone().into_iter().collect(),
+            },
+        };
+        Ok(self.message(payload))
+    }
+
+    /// Process a message from a signer, and return the new state of the session.
+    /// A partial signature that does not verify is refused, and the session waits for another
+    /// answer of its signer.
+    pub fn receive(&mut self, message: &SigningMessage, now: u64) -> Result<SigningSessionState> {
+        self.check_awaiting(now).c(d!())?;
+        if message.session_id != self.id {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        message.check(now).c(d!())?;
+        if let SigningPayload::Abort { .. } = message.payload {
+            self.state = SigningSessionState::Aborted;
+            return Ok(self.state);
+        }
+        if message.sequence != self.round_sequence() {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+
+        match (&message.payload, &self.key, self.state) {
+            (
+                SigningPayload::NonceCommitment { signer, commitment },
+                ThresholdGroupKey::Schnorr(_),
+                SigningSessionState::AwaitingNonceCommitments,
+            ) => {
+                self.check_new_answer(*signer, self.commitments.contains_key(signer))
+                    .c(d!())?;
+                self.commitments.insert(*signer, *commitment);
+                if self.commitments.len() == self.signers.len() {
+                    self.state = SigningSessionState::AwaitingNonces;
+                }
+            }
+            (
+                SigningPayload::NonceReveal { signer, nonce },
+                ThresholdGroupKey::Schnorr(_),
+                SigningSessionState::AwaitingNonces,
+            ) => {
+                self.check_new_answer(*signer, self.nonces.contains_key(signer))
+                    .c(d!())?;
+                if schnorr_nonce_commitment(*signer, nonce) != self.commitments[signer] {
+                    return Err(eg!(ZeiError::SignatureError));
+                }
+                self.nonces.insert(*signer, *nonce);
+                if self.nonces.len() == self.signers.len() {
+                    self.state = SigningSessionState::AwaitingSignatures;
+                }
+            }
+            (
+                SigningPayload::PartialSchnorr { signer, response },
+                ThresholdGroupKey::Schnorr(output),
+                SigningSessionState::AwaitingSignatures,
+            ) => {
+                self.check_new_answer(*signer, self.responses.contains_key(signer))
+                    .c(d!())?;
+                let group_nonce: RistrettoPoint = self.nonces.values().sum();
+                schnorr_verify_partial(
+                    output,
+                    &self.signers,
+                    *signer,
+                    &self.nonces[signer],
+                    &group_nonce,
+                    &self.message,
+                    response,
+                )
+                .c(d!())?;
+                self.responses.insert(*signer, *response);
+                if self.responses.len() == self.signers.len() {
+                    let nonces = self.nonces.clone().into_iter().collect_vec();
+                    let responses = self.responses.clone().into_iter().collect_vec();
+                    let signature = schnorr_combine(output, &nonces, &self.message, &responses);
+                    self.complete(signature.map(ThresholdSignature::Schnorr))
+                        .c(d!())?;
+                }
+            }
+            (
+                SigningPayload::PartialBLS { partial },
+                ThresholdGroupKey::BLS(output),
+                SigningSessionState::AwaitingSignatures,
+            ) => {
+                self.check_new_answer(partial.signer, self.partials.contains_key(&partial.signer))
+                    .c(d!())?;
+                bls_verify_partial(output, &self.message, partial).c(d!())?;
+                self.partials.insert(partial.signer, *partial);
+                if self.partials.len() == self.signers.len() {
+                    let partials = self.partials.values().copied().collect_vec();
+                    let signature = bls_combine(output, &self.message, &partials);
+                    self.complete(signature.map(ThresholdSignature::BLS))
+                        .c(d!())?;
+                }
+            }
+            _ => {
+                return Err(eg!(ZeiError::SigningSessionError));
+            }
+        }
+        Ok(self.state)
+    }
+
+    /// Abort the session, and return the message notifying the signers.
+    pub fn abort(&mut self, reason: &str) -> SigningMessage {
+        self.state = SigningSessionState::Aborted;
+        self.message(SigningPayload::Abort {
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Return the signature of a complete session.
+    pub fn finalize(self) -> Result<ThresholdSignature> {
+        if self.state != SigningSessionState::Complete {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        self.signature.c(d!(ZeiError::SigningSessionError))
+    }
+
+    /// Return the sequence number of the current round.
+    fn round_sequence(&self) -> u64 {
+        match (self.key.scheme(), self.state) {
+            (ThresholdScheme::Schnorr, SigningSessionState::AwaitingNonces) => {
+                THRESHOLD_COMMITMENTS_SEQUENCE
+            }
+            (ThresholdScheme::Schnorr, SigningSessionState::AwaitingSignatures) => {
+                THRESHOLD_NONCES_SEQUENCE
+            }
+            _ => THRESHOLD_REQUEST_SEQUENCE,
+        }
+    }
+
+    /// Check that the answer comes from a signer that did not answer the round yet.
+    fn check_new_answer(&self, signer: u32, answered: bool) -> Result<()> {
+        if !self.signers.contains(&signer) || answered {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        Ok(())
+    }
+
+    /// End the session with the combined signature, or abort it if the partial signatures,
+    /// each of which is valid, do not combine, i.e., if there are fewer than `threshold` signers.
+    fn complete(&mut self, signature: Result<ThresholdSignature>) -> Result<()> {
+        match signature {
+            Ok(signature) => {
+                self.signature = Some(signature);
+                self.state = SigningSessionState::Complete;
+                Ok(())
+            }
+            Err(e) => {
+                self.state = SigningSessionState::Aborted;
+                Err(e)
+            }
+        }
+    }
+
+    fn check_awaiting(&self, now: u64) -> Result<()> {
+        match self.state(now) {
+            SigningSessionState::AwaitingNonceCommitments
+            | SigningSessionState::AwaitingNonces
+            | SigningSessionState::AwaitingSignatures => Ok(()),
+            SigningSessionState::Expired => Err(eg!(ZeiError::SigningSessionExpired)),
+            _ => Err(eg!(ZeiError::SigningSessionError)),
+        }
+    }
+
+    fn message(&self, payload: SigningPayload) -> SigningMessage {
+        SigningMessage {
+            version: SIGNING_PROTOCOL_VERSION,
+            session_id: self.id,
+            sequence: self.round_sequence(),
+            expires_at: self.expires_at,
+            payload,
+        }
+    }
+}
+
+/// The state of a threshold signer in a session.
+#[derive(Serialize, Deserialize)]
+struct ThresholdSignerSession {
+    index: u32,
+    expires_at: u64,
+    message: Vec<u8>,
+    signers: Vec<u32>,
+    /// The nonce of a threshold Schnorr signature, erased once it answered a challenge.
+    nonce: Option<SchnorrNonce>,
+    /// The last round processed, and the answer to it, which a retry of the round gets again.
+    last_round: SigningPayload,
+    last_answer: SigningPayload,
+}
+
+/// The registry of the threshold signing sessions of a signer.
+///
+/// A signer answers each round of a session once: a retried round gets the same answer, e.g.,
+/// after a network failure, but another request, other commitments, or other nonces in the same
+/// session are refused, so that the nonce of the signer never answers two challenges. The
+/// registry holds the secret nonces of the sessions in progress, so it is not cloneable, and a
+/// persisted copy must not be restored once the registry has moved on.
+#[derive(Default, Serialize, Deserialize)]
+pub struct ThresholdSignerSessions {
+    sessions: BTreeMap<SigningSessionId, ThresholdSignerSession>,
+    aborted: BTreeMap<SigningSessionId, u64>,
+}
+
+impl ThresholdSignerSessions {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a message from a coordinator with the key share of the signer, and return the
+    /// response, if any.
+    pub fn handle<R: CryptoRng + RngCore>(
+        &mut self,
+        prng: &mut R,
+        message: &SigningMessage,
+        signer: ThresholdSigner<'_>,
+        now: u64,
+    ) -> Result<Option<SigningMessage>> {
+        message.check(now).c(d!())?;
+        let session_id = message.session_id;
+        if self.aborted.contains_key(&session_id) {
+            return Err(eg!(ZeiError::SigningSessionError));
+        }
+        if let SigningPayload::Abort { .. } = message.payload {
+            self.sessions.remove(&session_id);
+            self.aborted.insert(session_id, message.expires_at);
+            return Ok(None);
+        }
+
+        let answer = match self.sessions.get_mut(&session_id) {
+            Some(session) => {
+                if session.index != signer.index() || session.expires_at != message.expires_at {
+                    return Err(eg!(ZeiError::SigningSessionError));
+                }
+                if session.last_round == message.payload {
+                    session.last_answer.clone()
+                } else {
+                    let answer = session.next_round(message, signer).c(d!())?;
+                    session.last_round = message.payload.clone();
+                    session.last_answer = answer.clone();
+                    answer
+                }
+            }
+            None => {
+                let session = ThresholdSignerSession::start(prng, message, signer).c(d!())?;
+                let answer = session.last_answer.clone();
+                self.sessions.insert(session_id, session);
+                answer
+            }
+        };
+
+        Ok(Some(SigningMessage {
+            version: SIGNING_PROTOCOL_VERSION,
+            session_id,
+            sequence: message.sequence,
+            expires_at: message.expires_at,
+            payload: answer,
+        }))
+    }
+
+    /// Forget the sessions expired at the time `now`, which cannot receive any message anymore.
+    pub fn prune(&mut self, now: u64) {
+        self.sessions.retain(|_, session| now < session.expires_at);
+        self.aborted.retain(|_, expires_at| now < *expires_at);
+    }
+
+    /// Return the number of sessions remembered.
+    pub fn len(&self) -> usize {
+        self.sessions.len() + self.aborted.len()
+    }
+
+    /// Return true if no session is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty() && self.aborted.is_empty()
+    }
+}
+
+impl ThresholdSignerSession {
+    /// Answer the sign request that starts a session.
+    fn start<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        request: &SigningMessage,
+        signer: ThresholdSigner<'_>,
+    ) -> Result<Self> {
+        let (scheme, message, signers) = match &request.payload {
+            SigningPayload::ThresholdSignRequest {
+                scheme,
+                message,
+                signers,
+            } if request.sequence == THRESHOLD_REQUEST_SEQUENCE => (scheme, message, signers),
+            _ => return Err(eg!(ZeiError::SigningSessionError)),
+        };
+        if *scheme != signer.scheme() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        signer.check_signers(signers).c(d!())?;
+
+        let index = signer.index();
+        let (nonce, answer) = match signer {
+            ThresholdSigner::Schnorr(_) => {
+                let nonce = SchnorrNonce::random(prng);
+                let answer = SigningPayload::NonceCommitment {
+                    signer: index,
+                    commitment: nonce.commitment(index),
+                };
+                (Some(nonce), answer)
+            }
+            ThresholdSigner::BLS(key_share) => {
+                let answer = SigningPayload::PartialBLS {
+                    partial: bls_sign_share(key_share, message),
+                };
+                (None, answer)
+            }
+        };
+        Ok(ThresholdSignerSession {
+            index,
+            expires_at: request.expires_at,
+            message: message.clone(),
+            signers: signers.clone(),
+            nonce,
+            last_round: request.payload.clone(),
+            last_answer: answer,
+        })
+    }
+
+    /// Answer the next round of a threshold Schnorr signature.
+    fn next_round(
+        &mut self,
+        round: &SigningMessage,
+        signer: ThresholdSigner<'_>,
+    ) -> Result<SigningPayload> {
+        let key_share = match signer {
+            ThresholdSigner::Schnorr(key_share) => key_share,
+            ThresholdSigner::BLS(_) => return Err(eg!(ZeiError::SigningSessionError)),
+        };
+        match (&self.last_round, &round.payload, round.sequence) {
+            (
+                SigningPayload::ThresholdSignRequest { .. },
+                SigningPayload::NonceCommitments { commitments },
+                THRESHOLD_COMMITMENTS_SEQUENCE,
+            ) => {
+                // the nonce is only revealed once the commitments of every signer are known,
+                // including the unchanged commitment of this signer
+                let nonce = self.nonce.as_ref().c(d!(ZeiError::SigningSessionError))?;
+                let committed = commitments.iter().map(|(index, _)| *index).collect_vec();
+                if committed != self.signers
+                    || !commitments.contains(&(self.index, nonce.commitment(self.index)))
+                {
+                    return Err(eg!(ZeiError::SigningSessionError));
+                }
+                Ok(SigningPayload::NonceReveal {
+                    signer: self.index,
+                    nonce: *nonce.public(),
+                })
+            }
+            (
+                SigningPayload::NonceCommitments { commitments },
+                SigningPayload::NonceReveals { nonces },
+                THRESHOLD_NONCES_SEQUENCE,
+            ) => {
+                let group_nonce = schnorr_group_nonce(commitments, nonces).c(d!())?;
+                let nonce = self.nonce.take().c(d!(ZeiError::SigningSessionError))?;
+                let response = schnorr_sign_share(
+                    key_share,
+                    &self.signers,
+                    nonce,
+                    &group_nonce,
+                    &self.message,
+                )
+                .c(d!())?;
+                Ok(SigningPayload::PartialSchnorr {
+                    signer: self.index,
+                    response,
+                })
+            }
+            _ => Err(eg!(ZeiError::SigningSessionError)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        partially_signed::PartiallySignedXfr,
+        sig::XfrKeyPair,
+        signing_session::{
+            CoordinatorSession, CosignerSessions, SigningMessage, SigningPayload,
+            SigningSessionState, ThresholdCoordinatorSession, ThresholdGroupKey, ThresholdSigner,
+            ThresholdSignerSessions,
+        },
+        structs::{AssetRecord, AssetRecordTemplate, AssetType},
+        verify_xfr_note, XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::BLSG2,
+        prelude::*,
+        ristretto::{RistrettoPoint, RistrettoScalar},
+    };
+    use zei_crypto::dkg::{DkgParticipant, ThresholdKeyShare};
+
+    #[test]
+    fn signing_session() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(1);
+        let alice = XfrKeyPair::generate_secp256k1(&mut prng);
+        let bob = XfrKeyPair::generate_ed25519(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+
+        let mut record = |amount, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                keypair.get_pk(),
+            );
+            AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+        };
+        let inputs = [record(10, &alice), record(20, &bob), record(30, &alice)];
+        let outputs = [record(60, &receiver)];
+        let psxfr = PartiallySignedXfr::create(&mut prng, &inputs, &outputs).unwrap();
+        let other_psxfr = PartiallySignedXfr::create(&mut prng, &inputs, &outputs).unwrap();
+
+        let mut coordinator = CoordinatorSession::new(&mut prng, psxfr, 100).unwrap();
+        let mut alice_service = CosignerSessions::new();
+        let mut bob_service = CosignerSessions::new();
+        assert!(coordinator.request(&receiver.pub_key, 0).is_err());
+
+        // the messages are transported as bytes
+        let request = coordinator.request(&alice.pub_key, 0).unwrap();
+        let request = SigningMessage::from_bytes(&request.to_bytes().unwrap()).unwrap();
+        assert!(alice_service.handle(&request, &bob, 1).is_err());
+        let response = alice_service.handle(&request, &alice, 1).unwrap().unwrap();
+
+        // a retried request is answered again, but not for another transfer
+        assert!(alice_service.handle(&request, &alice, 2).unwrap().is_some());
+        let mut forged = request.clone();
+        forged.payload = SigningPayload::SignRequest {
+            psxfr: other_psxfr,
+            signer: alice.pub_key,
+        };
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            alice_service.handle(&forged, &alice, 2).unwrap_err()
+        );
+
+        assert_eq!(
+            coordinator.receive(&response, 3).unwrap(),
+            SigningSessionState::AwaitingSignatures
+        );
+        assert_eq!(coordinator.psxfr().missing_signatures(), vec![1]);
+        // a response is only accepted once
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            coordinator.receive(&response, 3).unwrap_err()
+        );
+
+        // a response to an outdated request is refused
+        let outdated = coordinator.request(&bob.pub_key, 4).unwrap();
+        let request = coordinator.request(&bob.pub_key, 4).unwrap();
+        let outdated_response = bob_service.handle(&outdated, &bob, 5).unwrap().unwrap();
+        assert!(coordinator.receive(&outdated_response, 5).is_err());
+        let response = bob_service.handle(&request, &bob, 5).unwrap().unwrap();
+        assert_eq!(
+            coordinator.receive(&response, 6).unwrap(),
+            SigningSessionState::Complete
+        );
+
+        let note = coordinator.finalize().unwrap();
+        let policies = XfrNotePolicies::empty_policies(inputs.len(), outputs.len());
+        assert!(verify_xfr_note(&mut prng, &mut params, &note, &policies.to_ref()).is_ok());
+
+        // expired and aborted sessions
+        let psxfr = PartiallySignedXfr::create(&mut prng, &inputs, &outputs).unwrap();
+        let mut coordinator = CoordinatorSession::new(&mut prng, psxfr, 100).unwrap();
+        let request = coordinator.request(&alice.pub_key, 0).unwrap();
+        msg_eq!(
+            ZeiError::SigningSessionExpired,
+            alice_service.handle(&request, &alice, 100).unwrap_err()
+        );
+        assert_eq!(coordinator.state(100), SigningSessionState::Expired);
+
+        let abort = coordinator.abort("cancelled by the user");
+        assert_eq!(coordinator.state(0), SigningSessionState::Aborted);
+        assert!(alice_service.handle(&abort, &alice, 0).unwrap().is_none());
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            alice_service.handle(&request, &alice, 0).unwrap_err()
+        );
+        assert!(coordinator.finalize().is_err());
+
+        assert_eq!(alice_service.len(), 2);
+        alice_service.prune(100);
+        assert!(alice_service.is_empty());
+    }
+
+    fn honest_dkg<G: Group>(threshold: u32, n: u32) -> Vec<ThresholdKeyShare<G>> {
+        let mut prng = test_rng();
+        let mut participants = (1..=n)
+            .map(|index| DkgParticipant::<G>::new(&mut prng, index, threshold, n).unwrap())
+            .collect_vec();
+        let dealings = participants
+            .iter_mut()
+            .map(|p| p.deal().unwrap())
+            .collect_vec();
+        for (commitment, shares) in dealings.iter() {
+            for share in shares.iter() {
+                let recipient = &mut participants[share.recipient as usize - 1];
+                recipient.receive_commitment(commitment).unwrap();
+                recipient.receive_share(share).unwrap();
+            }
+        }
+        for p in participants.iter_mut() {
+            p.complain().unwrap();
+            p.respond().unwrap();
+        }
+        participants
+            .iter_mut()
+            .map(|p| p.finish().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn threshold_bls_session() {
+        let mut prng = test_rng();
+        let shares = honest_dkg::<BLSG2>(2, 3);
+        let key = ThresholdGroupKey::BLS(shares[0].output.clone());
+        let message = b"threshold BLS session".to_vec();
+        let mut services = (0..3).map(|_| ThresholdSignerSessions::new()).collect_vec();
+
+        assert!(ThresholdCoordinatorSession::new(
+            &mut prng,
+            key.clone(),
+            vec![3, 1],
+            message.clone(),
+            100
+        )
+        .is_err());
+        let mut coordinator = ThresholdCoordinatorSession::new(
+            &mut prng,
+            key.clone(),
+            vec![1, 3],
+            message.clone(),
+            100,
+        )
+        .unwrap();
+        let request = coordinator.round(0).unwrap();
+        let request = SigningMessage::from_bytes(&request.to_bytes().unwrap()).unwrap();
+
+        // the participant 2 is not a signer, and a Schnorr key share does not sign BLS
+        assert!(services[1]
+            .handle(&mut prng, &request, ThresholdSigner::BLS(&shares[1]), 1)
+            .is_err());
+        let schnorr_shares = honest_dkg::<RistrettoPoint>(2, 3);
+        assert!(services[0]
+            .handle(
+                &mut prng,
+                &request,
+                ThresholdSigner::Schnorr(&schnorr_shares[0]),
+                1
+            )
+            .is_err());
+
+        let answer = services[0]
+            .handle(&mut prng, &request, ThresholdSigner::BLS(&shares[0]), 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            coordinator.receive(&answer, 2).unwrap(),
+            SigningSessionState::AwaitingSignatures
+        );
+        // an answer is only accepted once
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            coordinator.receive(&answer, 2).unwrap_err()
+        );
+        let answer = services[2]
+            .handle(&mut prng, &request, ThresholdSigner::BLS(&shares[2]), 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            coordinator.receive(&answer, 2).unwrap(),
+            SigningSessionState::Complete
+        );
+        let signature = coordinator.finalize().unwrap();
+        assert!(signature.verify(&key, &message).is_ok());
+        assert!(signature.verify(&key, b"another message").is_err());
+    }
+
+    #[test]
+    fn threshold_schnorr_session() {
+        let mut prng = test_rng();
+        let shares = honest_dkg::<RistrettoPoint>(2, 3);
+        let key = ThresholdGroupKey::Schnorr(shares[0].output.clone());
+        let message = b"threshold Schnorr session".to_vec();
+        let signers = [1usize, 2];
+        let mut services = (0..3).map(|_| ThresholdSignerSessions::new()).collect_vec();
+
+        let mut coordinator = ThresholdCoordinatorSession::new(
+            &mut prng,
+            key.clone(),
+            vec![1, 2],
+            message.clone(),
+            100,
+        )
+        .unwrap();
+
+        // the signers commit to their nonces
+        let request = coordinator.round(0).unwrap();
+        let mut commitments = vec![];
+        for i in signers.iter() {
+            let signer = ThresholdSigner::Schnorr(&shares[i - 1]);
+            let answer = services[i - 1]
+                .handle(&mut prng, &request, signer, 1)
+                .unwrap()
+                .unwrap();
+            // a retried request gets the same commitment
+            let retried = services[i - 1]
+                .handle(&mut prng, &request, signer, 1)
+                .unwrap()
+                .unwrap();
+            assert_eq!(answer, retried);
+            commitments.push(answer);
+        }
+        // the nonces are not revealed before the commitments are known
+        let early = SigningMessage {
+            sequence: 2,
+            payload: SigningPayload::NonceReveals { nonces: vec![] },
+            ..request.clone()
+        };
+        assert!(services[0]
+            .handle(&mut prng, &early, ThresholdSigner::Schnorr(&shares[0]), 1)
+            .is_err());
+        assert_eq!(
+            coordinator.receive(&commitments[0], 2).unwrap(),
+            SigningSessionState::AwaitingNonceCommitments
+        );
+        assert_eq!(
+            coordinator.receive(&commitments[1], 2).unwrap(),
+            SigningSessionState::AwaitingNonces
+        );
+
+        // the signers reveal their nonces once they see every commitment, including their own
+        let round = coordinator.round(3).unwrap();
+        let mut forged = round.clone();
+        if let SigningPayload::NonceCommitments { commitments } = &mut forged.payload {
+            commitments.pop();
+        }
+        assert!(services[0]
+            .handle(&mut prng, &forged, ThresholdSigner::Schnorr(&shares[0]), 4)
+            .is_err());
+        let mut reveals = vec![];
+        for i in signers.iter() {
+            let signer = ThresholdSigner::Schnorr(&shares[i - 1]);
+            reveals.push(
+                services[i - 1]
+                    .handle(&mut prng, &round, signer, 4)
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+        // an answer to the previous round is refused
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            coordinator.receive(&commitments[0], 5).unwrap_err()
+        );
+        coordinator.receive(&reveals[0], 5).unwrap();
+        assert_eq!(
+            coordinator.receive(&reveals[1], 5).unwrap(),
+            SigningSessionState::AwaitingSignatures
+        );
+
+        // the signers answer the challenge of the common nonce, once
+        let round = coordinator.round(6).unwrap();
+        let mut responses = vec![];
+        for i in signers.iter() {
+            let signer = ThresholdSigner::Schnorr(&shares[i - 1]);
+            responses.push(
+                services[i - 1]
+                    .handle(&mut prng, &round, signer, 7)
+                    .unwrap()
+                    .unwrap(),
+            );
+        }
+        let retried = services[0]
+            .handle(&mut prng, &round, ThresholdSigner::Schnorr(&shares[0]), 7)
+            .unwrap()
+            .unwrap();
+        assert_eq!(retried, responses[0]);
+        let mut other_nonces = round.clone();
+        if let SigningPayload::NonceReveals { nonces } = &mut other_nonces.payload {
+            nonces[1].1 = RistrettoPoint::get_base();
+        }
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            services[0]
+                .handle(
+                    &mut prng,
+                    &other_nonces,
+                    ThresholdSigner::Schnorr(&shares[0]),
+                    7
+                )
+                .unwrap_err()
+        );
+
+        // an invalid response is refused, and the valid one is still accepted
+        let mut invalid = responses[0].clone();
+        if let SigningPayload::PartialSchnorr { response, .. } = &mut invalid.payload {
+            *response = response.add(&RistrettoScalar::one());
+        }
+        assert!(coordinator.receive(&invalid, 8).is_err());
+        coordinator.receive(&responses[0], 8).unwrap();
+        assert_eq!(
+            coordinator.receive(&responses[1], 8).unwrap(),
+            SigningSessionState::Complete
+        );
+        let signature = coordinator.finalize().unwrap();
+        assert!(signature.verify(&key, &message).is_ok());
+
+        // expired and aborted sessions
+        let mut coordinator =
+            ThresholdCoordinatorSession::new(&mut prng, key, vec![1, 3], message, 100).unwrap();
+        let request = coordinator.round(0).unwrap();
+        msg_eq!(
+            ZeiError::SigningSessionExpired,
+            services[0]
+                .handle(
+                    &mut prng,
+                    &request,
+                    ThresholdSigner::Schnorr(&shares[0]),
+                    100
+                )
+                .unwrap_err()
+        );
+        assert_eq!(coordinator.state(100), SigningSessionState::Expired);
+        assert!(coordinator.round(100).is_err());
+
+        let abort = coordinator.abort("cancelled by the user");
+        assert_eq!(coordinator.state(0), SigningSessionState::Aborted);
+        assert!(services[0]
+            .handle(&mut prng, &abort, ThresholdSigner::Schnorr(&shares[0]), 0)
+            .unwrap()
+            .is_none());
+        msg_eq!(
+            ZeiError::SigningSessionError,
+            services[0]
+                .handle(&mut prng, &request, ThresholdSigner::Schnorr(&shares[0]), 0)
+                .unwrap_err()
+        );
+        assert!(coordinator.finalize().is_err());
+
+        assert_eq!(services[0].len(), 2);
+        services[0].prune(100);
+        assert!(services[0].is_empty());
+    }
+}
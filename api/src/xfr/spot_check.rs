@@ -0,0 +1,231 @@
+use crate::setup::BulletproofParams;
+use crate::xfr::{
+    batch_verify_xfr_bodies,
+    structs::{AssetTypeAndAmountProof, XfrBody, XfrNote},
+    verify_plain_amounts, verify_plain_asset, verify_plain_asset_mix, verify_transfer_multisig,
+    XfrNotePoliciesRef,
+};
+use rand_chacha::ChaChaRng;
+use zei_algebra::prelude::*;
+
+/// The denominator of the sampling rates.
+pub const SPOT_CHECK_RATE_DENOMINATOR: u32 = 1000;
+
+/// The configuration of a spot check, chosen by the light client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpotCheckConfig {
+    /// The probability that the proofs of a note are verified,
+    /// in units of `1 / SPOT_CHECK_RATE_DENOMINATOR`.
+    pub rate: u32,
+    /// The seed of the sampling, and of the randomness of the batch verification.
+    /// It must be sampled by the light client, and kept secret from the senders of the notes,
+    /// who could otherwise put the invalid proofs where they are not checked.
+    pub seed: [u8; 32],
+}
+
+impl SpotCheckConfig {
+    /// Create a configuration with a rate in units of `1 / SPOT_CHECK_RATE_DENOMINATOR`.
+    pub fn new(rate: u32, seed: [u8; 32]) -> Result<Self> {
+        if rate > SPOT_CHECK_RATE_DENOMINATOR {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(SpotCheckConfig { rate, seed })
+    }
+}
+
+/// The result of a successful spot check.
+///
+/// Unlike a full verification, a spot check does not prove that the notes are valid:
+/// the signatures and the structure of every note are checked, but the zero-knowledge
+/// proofs are only checked for the sampled notes. A note with an invalid proof
+/// passes the check with probability `1 - rate`.
+#[must_use]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpotCheckReport {
+    /// The number of notes.
+    pub num_notes: usize,
+    /// The indices of the notes whose proofs were verified, in increasing order.
+    pub sampled: Vec<usize>,
+}
+
+impl SpotCheckReport {
+    /// Check if the proofs of every note were verified, in which case the spot check was
+    /// as strong as a full verification.
+    pub fn is_complete(&self) -> bool {
+        self.sampled.len() == self.num_notes
+    }
+}
+
+/// Check the parts of a body that do not need the zero-knowledge proofs: the number of memos,
+/// and the balance of the transparent amounts and asset types.
+fn verify_body_structure(body: &XfrBody, policies: &XfrNotePoliciesRef<'_>) -> Result<()> {
+    if body.inputs.is_empty() || body.outputs.is_empty() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    if body.asset_tracing_memos.len() != body.inputs.len() + body.outputs.len() {
+        return Err(eg!(ZeiError::LengthMismatch {
+            expected: body.inputs.len() + body.outputs.len(),
+            actual: body.asset_tracing_memos.len(),
+        }));
+    }
+    if body.owners_memos.len() != body.outputs.len() {
+        return Err(eg!(ZeiError::LengthMismatch {
+            expected: body.outputs.len(),
+            actual: body.owners_memos.len(),
+        }));
+    }
+    if policies.valid
+        && (policies.inputs_tracing_policies.len() != body.inputs.len()
+            || policies.outputs_tracing_policies.len() != body.outputs.len())
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    match &body.proofs.asset_type_and_amount_proof {
+        AssetTypeAndAmountProof::ConfAmount(_) => {
+            verify_plain_asset(&body.inputs, &body.outputs).c(d!())
+        }
+        AssetTypeAndAmountProof::ConfAsset(_) => {
+            verify_plain_amounts(&body.inputs, &body.outputs).c(d!())
+        }
+        AssetTypeAndAmountProof::NoProof => {
+            verify_plain_asset_mix(&body.inputs, &body.outputs).c(d!())
+        }
+        AssetTypeAndAmountProof::ConfAll(_) | AssetTypeAndAmountProof::AssetMix(_) => Ok(()),
+    }
+}
+
+/// Spot-check confidential transfer notes, for light clients that cannot afford a full
+/// verification, see [`SpotCheckReport`] for what is guaranteed.
+///
+/// The signatures and the structure of every note are checked, and each note is sampled
+/// independently with the configured rate, after which the proofs of the sampled notes
+/// are batch-verified.
+pub fn spot_check_xfr_notes(
+    params: &mut BulletproofParams,
+    notes: &[&XfrNote],
+    policies: &[&XfrNotePoliciesRef<'_>],
+    config: &SpotCheckConfig,
+) -> Result<SpotCheckReport> {
+    if notes.len() != policies.len() {
+        return Err(eg!(ZeiError::LengthMismatch {
+            expected: notes.len(),
+            actual: policies.len(),
+        }));
+    }
+
+    // 1. Check the signatures and the structure of every note.
+    for (index, (note, policies)) in notes.iter().zip(policies.iter()).enumerate() {
+        verify_transfer_multisig(note).map_err(|source| {
+            eg!(ZeiError::NoteError {
+                index,
+                source: Box::new(source),
+            })
+        })?;
+        verify_body_structure(&note.body, policies).c(d!(ZeiError::NoteError {
+            index,
+            source: Box::new(ZeiError::InconsistentStructureError),
+        }))?;
+    }
+
+    // 2. Sample the notes whose proofs are verified.
+    let mut prng = ChaChaRng::from_seed(config.seed);
+    let sampled = (0..notes.len())
+        .filter(|_| prng.next_u32() % SPOT_CHECK_RATE_DENOMINATOR < config.rate)
+        .collect_vec();
+
+    // 3. Batch-verify the proofs of the sampled notes.
+    if !sampled.is_empty() {
+        let bodies = sampled.iter().map(|i| &notes[*i].body).collect_vec();
+        let sampled_policies = sampled.iter().map(|i| policies[*i]).collect_vec();
+        batch_verify_xfr_bodies(&mut prng, params, &bodies, &sampled_policies).c(d!())?;
+    }
+
+    Ok(SpotCheckReport {
+        num_notes: notes.len(),
+        sampled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{spot_check_xfr_notes, SpotCheckConfig, SPOT_CHECK_RATE_DENOMINATOR};
+    use crate::setup::BulletproofParams;
+    use crate::xfr::{
+        asset_record::AssetRecordType,
+        gen_xfr_note,
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType, XfrAmount, XfrNote},
+        XfrNotePolicies,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    fn create_note<R: CryptoRng + RngCore>(prng: &mut R) -> XfrNote {
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let record_type = AssetRecordType::ConfidentialAmount_NonConfidentialAssetType;
+        let sender = XfrKeyPair::generate(prng);
+        let mut record = |amount: u64| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                sender.pub_key,
+            );
+            AssetRecord::from_template_no_identity_tracing(prng, &template).unwrap()
+        };
+        let inputs = vec![record(10)];
+        let outputs = vec![record(3), record(7)];
+        gen_xfr_note(prng, &inputs, &outputs, &[&sender]).unwrap()
+    }
+
+    #[test]
+    fn spot_check() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let notes = (0..4).map(|_| create_note(&mut prng)).collect_vec();
+        let policies = notes
+            .iter()
+            .map(|note| {
+                XfrNotePolicies::empty_policies(note.body.inputs.len(), note.body.outputs.len())
+            })
+            .collect_vec();
+        let policies_refs = policies.iter().map(|p| p.to_ref()).collect_vec();
+        let policies_refs = policies_refs.iter().collect_vec();
+
+        // A full rate samples every note.
+        let config = SpotCheckConfig::new(SPOT_CHECK_RATE_DENOMINATOR, [1u8; 32]).unwrap();
+        let report = spot_check_xfr_notes(
+            &mut params,
+            &notes.iter().collect_vec(),
+            &policies_refs,
+            &config,
+        )
+        .unwrap();
+        assert!(report.is_complete());
+
+        // A zero rate only checks the signatures and the structure.
+        let config = SpotCheckConfig::new(0, [1u8; 32]).unwrap();
+        let report = spot_check_xfr_notes(
+            &mut params,
+            &notes.iter().collect_vec(),
+            &policies_refs,
+            &config,
+        )
+        .unwrap();
+        assert!(report.sampled.is_empty());
+
+        // A broken signature is always detected.
+        let mut bad_notes = notes.clone();
+        bad_notes[2].body.outputs[0].amount = XfrAmount::NonConfidential(3);
+        assert!(spot_check_xfr_notes(
+            &mut params,
+            &bad_notes.iter().collect_vec(),
+            &policies_refs,
+            &config
+        )
+        .is_err());
+
+        assert!(SpotCheckConfig::new(SPOT_CHECK_RATE_DENOMINATOR + 1, [0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use crate::xfr::{
+    sig::{XfrKeyPair, XfrPublicKey, XfrPublicKeyInner, XfrSecretKey},
+    structs::BlindAssetRecord,
+};
+use libsecp256k1::{PublicKey as Secp256k1PublicKey, SecretKey as Secp256k1SecretKey};
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+/// The domain separator for the one-time key derivation.
+const STEALTH_TWEAK_DOMAIN: &[u8] = b"Zei Stealth Address One-Time Key";
+
+/// The published address of a receiver, from which senders derive one-time output keys.
+///
+/// A one-time key is `P = H(r * V) * G + S`, where `V` is the view key, `S` is the spend key,
+/// and `R = r * G` is the ephemeral key sent along with the output. Only secp256k1 keys are
+/// supported, since an Ed25519 secret key cannot be built from a derived scalar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthAddress {
+    /// The public key used to detect the outputs.
+    pub view_key: XfrPublicKey,
+    /// The public key used to spend the outputs.
+    pub spend_key: XfrPublicKey,
+}
+
+/// The key that detects the one-time output keys of a stealth address.
+/// It consists of the secret view key and the public spend key, and it cannot spend the outputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StealthViewKey {
+    view_secret: Secp256k1SecretKey,
+    view_key: Secp256k1PublicKey,
+    spend_key: Secp256k1PublicKey,
+}
+
+impl StealthAddress {
+    /// Create a stealth address from the view key and the spend key.
+    pub fn new(view_key: &XfrPublicKey, spend_key: &XfrPublicKey) -> Result<Self> {
+        as_secp256k1_public_key(view_key).c(d!())?;
+        as_secp256k1_public_key(spend_key).c(d!())?;
+        Ok(StealthAddress {
+            view_key: *view_key,
+            spend_key: *spend_key,
+        })
+    }
+
+    /// Derive a fresh one-time public key for an output.
+    /// Return `(one_time_key, ephemeral_key)`, where the ephemeral key must be published with the output.
+    pub fn derive_one_time_key<R: CryptoRng + RngCore>(
+        &self,
+        prng: &mut R,
+    ) -> Result<(XfrPublicKey, XfrPublicKey)> {
+        let view_key = as_secp256k1_public_key(&self.view_key).c(d!())?;
+        let spend_key = as_secp256k1_public_key(&self.spend_key).c(d!())?;
+
+        let r = Secp256k1SecretKey::random(prng);
+        let ephemeral_key = Secp256k1PublicKey::from_secret_key(&r);
+
+        let tweak = one_time_tweak(&view_key, &r).c(d!())?;
+        let one_time_key = tweak_public_key(&spend_key, &tweak).c(d!())?;
+
+        Ok((
+            XfrPublicKey(XfrPublicKeyInner::Secp256k1(one_time_key)),
+            XfrPublicKey(XfrPublicKeyInner::Secp256k1(ephemeral_key)),
+        ))
+    }
+}
+
+impl StealthViewKey {
+    /// Create the view key from the view key pair and the public spend key.
+    pub fn new(view_keypair: &XfrKeyPair, spend_key: &XfrPublicKey) -> Result<Self> {
+        let view_secret = match view_keypair.get_sk_ref() {
+            XfrSecretKey::Secp256k1(sk) => *sk,
+            _ => return Err(eg!(ZeiError::ParameterError)),
+        };
+        Ok(StealthViewKey {
+            view_secret,
+            view_key: as_secp256k1_public_key(view_keypair.get_pk_ref()).c(d!())?,
+            spend_key: as_secp256k1_public_key(spend_key).c(d!())?,
+        })
+    }
+
+    /// Return the stealth address to be published.
+    pub fn get_address(&self) -> StealthAddress {
+        StealthAddress {
+            view_key: XfrPublicKey(XfrPublicKeyInner::Secp256k1(self.view_key)),
+            spend_key: XfrPublicKey(XfrPublicKeyInner::Secp256k1(self.spend_key)),
+        }
+    }
+
+    /// Check whether a one-time key has been derived from this stealth address.
+    pub fn is_owner(&self, one_time_key: &XfrPublicKey, ephemeral_key: &XfrPublicKey) -> bool {
+        self.compute_one_time_key(ephemeral_key)
+            .map(|expected| expected == *one_time_key)
+            .unwrap_or(false)
+    }
+
+    /// Scan a list of records with their ephemeral keys,
+    /// and return the indices of the records that belong to this stealth address.
+    pub fn scan_blind_asset_records(
+        &self,
+        records: &[(BlindAssetRecord, XfrPublicKey)],
+    ) -> Vec<usize> {
+        records
+            .iter()
+            .enumerate()
+            .filter(|(_, (record, ephemeral_key))| self.is_owner(&record.public_key, ephemeral_key))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Recover the key pair of a one-time key, given the spend key pair.
+    pub fn recover_one_time_keypair(
+        &self,
+        spend_keypair: &XfrKeyPair,
+        one_time_key: &XfrPublicKey,
+        ephemeral_key: &XfrPublicKey,
+    ) -> Result<XfrKeyPair> {
+        let spend_secret = match spend_keypair.get_sk_ref() {
+            XfrSecretKey::Secp256k1(sk) => *sk,
+            _ => return Err(eg!(ZeiError::ParameterError)),
+        };
+        if as_secp256k1_public_key(spend_keypair.get_pk_ref()).c(d!())? != self.spend_key {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        if !self.is_owner(one_time_key, ephemeral_key) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+
+        let ephemeral_key = as_secp256k1_public_key(ephemeral_key).c(d!())?;
+        let tweak = one_time_tweak(&ephemeral_key, &self.view_secret).c(d!())?;
+        let mut one_time_secret = spend_secret;
+        one_time_secret
+            .tweak_add_assign(&tweak)
+            .c(d!(ZeiError::ParameterError))?;
+
+        Ok(XfrKeyPair {
+            pub_key: XfrPublicKey(XfrPublicKeyInner::Secp256k1(
+                Secp256k1PublicKey::from_secret_key(&one_time_secret),
+            )),
+            sec_key: XfrSecretKey::Secp256k1(one_time_secret),
+        })
+    }
+
+    fn compute_one_time_key(&self, ephemeral_key: &XfrPublicKey) -> Result<XfrPublicKey> {
+        let ephemeral_key = as_secp256k1_public_key(ephemeral_key).c(d!())?;
+        let tweak = one_time_tweak(&ephemeral_key, &self.view_secret).c(d!())?;
+        let one_time_key = tweak_public_key(&self.spend_key, &tweak).c(d!())?;
+        Ok(XfrPublicKey(XfrPublicKeyInner::Secp256k1(one_time_key)))
+    }
+}
+
+fn as_secp256k1_public_key(pk: &XfrPublicKey) -> Result<Secp256k1PublicKey> {
+    match pk.inner() {
+        XfrPublicKeyInner::Secp256k1(pk) => Ok(*pk),
+        _ => Err(eg!(ZeiError::ParameterError)),
+    }
+}
+
+// Compute H(sk * pk), which is the same for (r, V) and (v, R).
+fn one_time_tweak(pk: &Secp256k1PublicKey, sk: &Secp256k1SecretKey) -> Result<Secp256k1SecretKey> {
+    let mut shared = *pk;
+    shared
+        .tweak_mul_assign(sk)
+        .c(d!(ZeiError::ParameterError))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(STEALTH_TWEAK_DOMAIN);
+    hasher.update(&shared.serialize_compressed()[..]);
+    Secp256k1SecretKey::parse_slice(&hasher.finalize()[..]).c(d!(ZeiError::ParameterError))
+}
+
+// Compute tweak * G + pk.
+fn tweak_public_key(
+    pk: &Secp256k1PublicKey,
+    tweak: &Secp256k1SecretKey,
+) -> Result<Secp256k1PublicKey> {
+    let mut res = *pk;
+    res.tweak_add_assign(tweak)
+        .c(d!(ZeiError::ParameterError))?;
+    Ok(res)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xfr::{
+        asset_record::{open_blind_asset_record, AssetRecordType},
+        sig::XfrKeyPair,
+        stealth::{StealthAddress, StealthViewKey},
+        structs::{AssetRecord, AssetRecordTemplate, AssetType},
+    };
+    use ark_std::test_rng;
+
+    #[test]
+    fn one_time_keys() {
+        let mut prng = test_rng();
+        let view_keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let spend_keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let view_key = StealthViewKey::new(&view_keypair, spend_keypair.get_pk_ref()).unwrap();
+        let address = view_key.get_address();
+        assert_eq!(
+            address,
+            StealthAddress::new(view_keypair.get_pk_ref(), spend_keypair.get_pk_ref()).unwrap()
+        );
+
+        // two outputs to the same address are unlinkable
+        let (one_time_key1, ephemeral_key1) = address.derive_one_time_key(&mut prng).unwrap();
+        let (one_time_key2, ephemeral_key2) = address.derive_one_time_key(&mut prng).unwrap();
+        assert_ne!(one_time_key1, one_time_key2);
+        assert_ne!(one_time_key1, address.spend_key);
+
+        assert!(view_key.is_owner(&one_time_key1, &ephemeral_key1));
+        assert!(view_key.is_owner(&one_time_key2, &ephemeral_key2));
+        assert!(!view_key.is_owner(&one_time_key1, &ephemeral_key2));
+
+        // another receiver does not detect the outputs
+        let other_view_keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let other_view_key =
+            StealthViewKey::new(&other_view_keypair, spend_keypair.get_pk_ref()).unwrap();
+        assert!(!other_view_key.is_owner(&one_time_key1, &ephemeral_key1));
+
+        // the recovered key pair matches the one-time key and can sign
+        let keypair = view_key
+            .recover_one_time_keypair(&spend_keypair, &one_time_key1, &ephemeral_key1)
+            .unwrap();
+        assert_eq!(keypair.get_pk(), one_time_key1);
+        let sig = keypair.sign(b"message").unwrap();
+        assert!(one_time_key1.verify(b"message", &sig).is_ok());
+
+        // the one-time key cannot be recovered with a wrong spend key or ephemeral key
+        assert!(view_key
+            .recover_one_time_keypair(&view_keypair, &one_time_key1, &ephemeral_key1)
+            .is_err());
+        assert!(view_key
+            .recover_one_time_keypair(&spend_keypair, &one_time_key1, &ephemeral_key2)
+            .is_err());
+
+        // only secp256k1 keys are supported
+        let ed25519_keypair = XfrKeyPair::generate_ed25519(&mut prng);
+        assert!(
+            StealthAddress::new(ed25519_keypair.get_pk_ref(), spend_keypair.get_pk_ref()).is_err()
+        );
+        assert!(StealthViewKey::new(&ed25519_keypair, spend_keypair.get_pk_ref()).is_err());
+    }
+
+    #[test]
+    fn scan_stealth_records() {
+        let mut prng = test_rng();
+        let view_keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let spend_keypair = XfrKeyPair::generate_secp256k1(&mut prng);
+        let view_key = StealthViewKey::new(&view_keypair, spend_keypair.get_pk_ref()).unwrap();
+        let address = view_key.get_address();
+        let asset_type = AssetType::from_identical_byte(1u8);
+
+        let mut records = vec![];
+        let mut memos = vec![];
+        for i in 0..4u64 {
+            let (public_key, ephemeral_key) = if i % 2 == 0 {
+                address.derive_one_time_key(&mut prng).unwrap()
+            } else {
+                let other = XfrKeyPair::generate_secp256k1(&mut prng).get_pk();
+                (other, address.derive_one_time_key(&mut prng).unwrap().1)
+            };
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                10 + i,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                public_key,
+            );
+            let record =
+                AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+            records.push((
+                record.open_asset_record.blind_asset_record.clone(),
+                ephemeral_key,
+            ));
+            memos.push(record.owner_memo);
+        }
+
+        let found = view_key.scan_blind_asset_records(&records);
+        assert_eq!(found, vec![0, 2]);
+
+        for i in found {
+            let (record, ephemeral_key) = &records[i];
+            let keypair = view_key
+                .recover_one_time_keypair(&spend_keypair, &record.public_key, ephemeral_key)
+                .unwrap();
+            let oar = open_blind_asset_record(record, &memos[i], &keypair).unwrap();
+            assert_eq!(*oar.get_amount(), 10 + i as u64);
+            assert_eq!(*oar.get_asset_type(), asset_type);
+        }
+    }
+}
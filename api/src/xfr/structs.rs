@@ -6,9 +6,11 @@ use crate::xfr::{
     asset_mixer::AssetMixProof,
     asset_record::AssetRecordType,
     asset_tracer::{RecordDataCiphertext, RecordDataDecKey, RecordDataEncKey},
+    memo_padding::{unpad, MemoPaddingPolicy},
+    multisig::MultiSigAuthorization,
     sig::{KeyType, XfrKeyPair, XfrMultiSig, XfrPublicKey},
+    view_key::{hybrid_decrypt_aead_with_scalar, hybrid_decrypt_with_scalar, ViewKey},
 };
-use bulletproofs::RangeProof;
 use digest::Digest;
 use sha2::Sha512;
 use zei_algebra::{
@@ -18,16 +20,25 @@ use zei_algebra::{
 };
 use zei_crypto::basic::pedersen_comm::PedersenCommitmentRistretto;
 use zei_crypto::basic::{
+    aead::Aes256GcmSivAead,
     chaum_pedersen::ChaumPedersenProofX,
     elgamal::elgamal_key_gen,
     hybrid_encryption::{XPublicKey, XSecretKey, ZeiHybridCiphertext},
     pedersen_comm::PedersenCommitment,
     pedersen_elgamal::PedersenElGamalEqProof,
 };
+use zei_crypto::bulletproofs::range::VersionedRangeProof;
 
 /// Asset Type identifier.
 pub const ASSET_TYPE_LENGTH: usize = 32;
 
+/// The domain separator of the view tags of the owner memos.
+const OWNER_MEMO_VIEW_TAG_DOMAIN: &[u8] = b"Zei Owner Memo View Tag";
+
+/// The AEAD algorithm of the locks of new owner memos. The locks name their algorithm, so that
+/// the memos remain decryptable if it changes.
+type OwnerMemoAead = Aes256GcmSivAead;
+
 #[derive(
     Deserialize, Serialize, Clone, Copy, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord,
 )]
@@ -73,20 +84,28 @@ pub struct XfrNote {
     pub body: XfrBody,
     /// The multisiganture of the senders
     pub multisig: XfrMultiSig,
+    /// The authorizations of the multisig accounts that approve the transfer, ordered by
+    /// their addresses, see [`crate::xfr::multisig`].
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
+    pub account_authorizations: Vec<MultiSigAuthorization>,
 }
 
 /// A confidential transfer body.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct XfrBody {
     /// The list of input (blind) asset records.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub inputs: Vec<BlindAssetRecord>,
     /// The list of output (blind) asset records.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub outputs: Vec<BlindAssetRecord>,
     /// The list of proofs.
     pub proofs: XfrProofs,
     /// The memos for access tracers.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub asset_tracing_memos: Vec<Vec<TracerMemo>>, // each input or output can have a set of tracing memos
     /// The memos for the recipients.
+    #[serde(deserialize_with = "zei_bounded_serde::outputs")]
     pub owners_memos: Vec<Option<OwnerMemo>>, // If confidential amount or asset type, lock the amount and/or asset type to the public key in asset_record
 }
 
@@ -364,6 +383,7 @@ pub struct IdentityRevealPolicy {
     /// The public key of the credential issuer.
     pub cred_issuer_pub_key: ACIssuerPublicKey,
     /// The attribute revealing map.
+    #[serde(deserialize_with = "zei_bounded_serde::attributes")]
     pub reveal_map: Vec<bool>, // i-th is true, if i-th attribute is to be revealed
 }
 
@@ -377,6 +397,7 @@ pub struct TracerMemo {
     /// The ciphertexts of the asset types.
     pub lock_asset_type: Option<RecordDataCiphertext>,
     /// The ciphertexts of the attributes.
+    #[serde(deserialize_with = "zei_bounded_serde::attributes")]
     pub lock_attributes: Vec<AttributeCiphertext>,
     /// A hybrid encryption of amount, asset type, and attributes encrypted above for faster access.
     pub lock_info: ZeiHybridCiphertext,
@@ -389,8 +410,51 @@ pub struct OwnerMemo {
     pub key_type: KeyType,
     /// The random point used to compute the shared point.
     pub blind_share_bytes: Vec<u8>,
-    /// The ciphertext of the memo information.
+    /// The ciphertext of the memo information. The memos with a view tag are encrypted with an
+    /// AEAD and a key-committing scheme, see [`XfrPublicKey::hybrid_encrypt_aead`], and the
+    /// older ones without.
     pub lock_bytes: Vec<u8>,
+    /// The view tag, i.e., a byte of a hash of the shared point, with which the receivers skip
+    /// most of the memos of other receivers without decrypting them.
+    /// The memos created before the view tags have none.
+    pub view_tag: Option<u8>,
+}
+
+/// The options of the owner memo of a new record.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerMemoOptions {
+    /// The padding policy of the memo.
+    pub padding: MemoPaddingPolicy,
+    /// The view public key of the owner, which the memo is addressed to instead of the public
+    /// key of the owner, if any.
+    #[serde(default)]
+    pub view_pub_key: Option<XfrPublicKey>,
+}
+
+impl OwnerMemoOptions {
+    /// Pad the memo according to a padding policy.
+    pub fn with_padding(mut self, padding: MemoPaddingPolicy) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Address the memo to the view public key of the owner, see [`ViewKey::get_view_pk`],
+    /// so that the view key of the owner can open the record.
+    pub fn with_view_key(mut self, view_pub_key: XfrPublicKey) -> Self {
+        self.view_pub_key = Some(view_pub_key);
+        self
+    }
+
+    // Return the key that the memo is addressed to, given the public key of the owner.
+    fn memo_key<'a>(&'a self, pub_key: &'a XfrPublicKey) -> Result<&'a XfrPublicKey> {
+        match self.view_pub_key.as_ref() {
+            Some(view_pub_key) if view_pub_key.to_bytes()[0] == pub_key.to_bytes()[0] => {
+                Ok(view_pub_key)
+            }
+            Some(_) => Err(eg!(ZeiError::ParameterError)),
+            None => Ok(pub_key),
+        }
+    }
 }
 
 impl OwnerMemo {
@@ -400,51 +464,103 @@ impl OwnerMemo {
         amount: u64,
         pub_key: &XfrPublicKey,
     ) -> Result<(Self, (RistrettoScalar, RistrettoScalar))> {
+        OwnerMemo::from_amount_with_options(prng, amount, pub_key, &OwnerMemoOptions::default())
+    }
+
+    /// Construct an `OwnerMemo` for an asset record with only confidential asset type.
+    pub fn from_asset_type<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        asset_type: &AssetType,
+        pub_key: &XfrPublicKey,
+    ) -> Result<(Self, RistrettoScalar)> {
+        OwnerMemo::from_asset_type_with_options(
+            prng,
+            asset_type,
+            pub_key,
+            &OwnerMemoOptions::default(),
+        )
+    }
+
+    /// Construct an `OwnerMemo` for an asset record with both confidential amount and confidential asset type.
+    pub fn from_amount_and_asset_type<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: u64,
+        asset_type: &AssetType,
+        pub_key: &XfrPublicKey,
+    ) -> Result<(Self, (RistrettoScalar, RistrettoScalar), RistrettoScalar)> {
+        OwnerMemo::from_amount_and_asset_type_with_options(
+            prng,
+            amount,
+            asset_type,
+            pub_key,
+            &OwnerMemoOptions::default(),
+        )
+    }
+
+    /// Construct an `OwnerMemo` for an asset record with only confidential amount, with options.
+    pub fn from_amount_with_options<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: u64,
+        pub_key: &XfrPublicKey,
+        options: &OwnerMemoOptions,
+    ) -> Result<(Self, (RistrettoScalar, RistrettoScalar))> {
+        let pub_key = options.memo_key(pub_key).c(d!())?;
         let (key_type, r, blind_share_bytes) = pub_key.random_scalar_with_compressed_point(prng);
         let shared_point =
             OwnerMemo::derive_shared_point(&key_type, &r, &pub_key.as_compressed_point())?;
         let amount_blinds = OwnerMemo::calc_amount_blinds(&shared_point);
 
-        let lock_bytes = pub_key.hybrid_encrypt(prng, &amount.to_be_bytes())?;
+        let lock_bytes = pub_key.hybrid_encrypt_aead::<OwnerMemoAead, _>(
+            prng,
+            &options.padding.pad(&amount.to_be_bytes())?,
+        )?;
         Ok((
             OwnerMemo {
                 key_type,
                 blind_share_bytes,
                 lock_bytes,
+                view_tag: Some(OwnerMemo::calc_view_tag(&shared_point)),
             },
             amount_blinds,
         ))
     }
 
-    /// Construct an `OwnerMemo` for an asset record with only confidential asset type.
-    pub fn from_asset_type<R: CryptoRng + RngCore>(
+    /// Construct an `OwnerMemo` for an asset record with only confidential asset type, with options.
+    pub fn from_asset_type_with_options<R: CryptoRng + RngCore>(
         prng: &mut R,
         asset_type: &AssetType,
         pub_key: &XfrPublicKey,
+        options: &OwnerMemoOptions,
     ) -> Result<(Self, RistrettoScalar)> {
+        let pub_key = options.memo_key(pub_key).c(d!())?;
         let (key_type, r, blind_share_bytes) = pub_key.random_scalar_with_compressed_point(prng);
         let shared_point =
             OwnerMemo::derive_shared_point(&key_type, &r, &pub_key.as_compressed_point())?;
         let asset_type_blind = OwnerMemo::calc_asset_type_blind(&shared_point);
 
-        let lock_bytes = pub_key.hybrid_encrypt(prng, &asset_type.0)?;
+        let lock_bytes = pub_key
+            .hybrid_encrypt_aead::<OwnerMemoAead, _>(prng, &options.padding.pad(&asset_type.0)?)?;
         Ok((
             OwnerMemo {
                 key_type,
                 blind_share_bytes,
                 lock_bytes,
+                view_tag: Some(OwnerMemo::calc_view_tag(&shared_point)),
             },
             asset_type_blind,
         ))
     }
 
-    /// Construct an `OwnerMemo` for an asset record with both confidential amount and confidential asset type.
-    pub fn from_amount_and_asset_type<R: CryptoRng + RngCore>(
+    /// Construct an `OwnerMemo` for an asset record with both confidential amount and confidential
+    /// asset type, with options.
+    pub fn from_amount_and_asset_type_with_options<R: CryptoRng + RngCore>(
         prng: &mut R,
         amount: u64,
         asset_type: &AssetType,
         pub_key: &XfrPublicKey,
+        options: &OwnerMemoOptions,
     ) -> Result<(Self, (RistrettoScalar, RistrettoScalar), RistrettoScalar)> {
+        let pub_key = options.memo_key(pub_key).c(d!())?;
         let (key_type, r, blind_share_bytes) = pub_key.random_scalar_with_compressed_point(prng);
         let shared_point =
             OwnerMemo::derive_shared_point(&key_type, &r, &pub_key.as_compressed_point())?;
@@ -454,12 +570,16 @@ impl OwnerMemo {
         let mut amount_asset_type_plaintext = vec![];
         amount_asset_type_plaintext.extend_from_slice(&amount.to_be_bytes()[..]);
         amount_asset_type_plaintext.extend_from_slice(&asset_type.0[..]);
-        let lock_bytes = pub_key.hybrid_encrypt(prng, &amount_asset_type_plaintext)?;
+        let lock_bytes = pub_key.hybrid_encrypt_aead::<OwnerMemoAead, _>(
+            prng,
+            &options.padding.pad(&amount_asset_type_plaintext)?,
+        )?;
         Ok((
             OwnerMemo {
                 key_type,
                 blind_share_bytes,
                 lock_bytes,
+                view_tag: Some(OwnerMemo::calc_view_tag(&shared_point)),
             },
             amount_blinds,
             asset_type_blind,
@@ -470,6 +590,100 @@ impl OwnerMemo {
     /// returns error if the decrypted bytes length doesn't match.
     pub fn decrypt_amount(&self, keypair: &XfrKeyPair) -> Result<u64> {
         let decrypted_bytes = self.decrypt(&keypair)?;
+        OwnerMemo::parse_amount(&decrypted_bytes)
+    }
+
+    /// Decrypt the `OwnerMemo.lock` which encrypts only the confidential asset type
+    /// returns error if the decrypted bytes length doesn't match.
+    pub fn decrypt_asset_type(&self, keypair: &XfrKeyPair) -> Result<AssetType> {
+        let decrypted_bytes = self.decrypt(&keypair)?;
+        OwnerMemo::parse_asset_type(&decrypted_bytes)
+    }
+
+    /// Decrypt the `OwnerMemo.lock` which encrypts "amount || asset type", both amount and asset type
+    /// are confidential.
+    pub fn decrypt_amount_and_asset_type(&self, keypair: &XfrKeyPair) -> Result<(u64, AssetType)> {
+        let decrypted_bytes = self.decrypt(&keypair)?;
+        OwnerMemo::parse_amount_and_asset_type(&decrypted_bytes)
+    }
+
+    /// Return the amount blind (blind_low, blind_high)
+    pub fn derive_amount_blinds(
+        &self,
+        keypair: &XfrKeyPair,
+    ) -> Result<(RistrettoScalar, RistrettoScalar)> {
+        let (key_type, s) = self.receiver_scalar(keypair).c(d!())?;
+        self.derive_amount_blinds_with_scalar(&key_type, &s)
+    }
+
+    /// Return the asset type blind
+    pub fn derive_asset_type_blind(&self, keypair: &XfrKeyPair) -> Result<RistrettoScalar> {
+        let (key_type, s) = self.receiver_scalar(keypair).c(d!())?;
+        self.derive_asset_type_blind_with_scalar(&key_type, &s)
+    }
+
+    /// Check the view tag of the memo, which is cheaper than decrypting it, and return false
+    /// if the memo is addressed neither to the key pair nor to its view key.
+    /// A memo without a view tag passes the check.
+    pub fn check_view_tag(&self, keypair: &XfrKeyPair) -> bool {
+        self.receiver_scalar(keypair).is_ok()
+    }
+}
+
+impl OwnerMemo {
+    // Decrypt the lock, with the key pair or with its view key.
+    fn decrypt(&self, keypair: &XfrKeyPair) -> Result<Vec<u8>> {
+        let (key_type, s) = self.receiver_scalar(keypair).c(d!())?;
+        self.decrypt_with_scalar(&key_type, &s).c(d!())
+    }
+
+    // Decrypt the lock given the scalar of the receiver, in the format of the memo: with an AEAD
+    // if it has a view tag, and in the legacy format otherwise.
+    pub(crate) fn decrypt_with_scalar(&self, key_type: &KeyType, s: &[u8]) -> Result<Vec<u8>> {
+        match self.view_tag {
+            Some(_) => hybrid_decrypt_aead_with_scalar(key_type, s, &self.lock_bytes).c(d!()),
+            None => hybrid_decrypt_with_scalar(key_type, s, &self.lock_bytes).c(d!()),
+        }
+    }
+
+    // Return the key type and the scalar that the memo is addressed to: the one of the secret key
+    // of the key pair, or the one of its view key. They are told apart by the view tag, and, in
+    // the rare case that both match it, by the decryption of the lock.
+    pub(crate) fn receiver_scalar(&self, keypair: &XfrKeyPair) -> Result<(KeyType, Vec<u8>)> {
+        let (key_type, s) = keypair.sec_key.as_scalar_bytes();
+        let view_key = match self.view_tag {
+            Some(_) => ViewKey::from_keypair(keypair).ok(),
+            None => None,
+        };
+        let mut candidates = vec![];
+        if self.check_view_tag_with_scalar(&key_type, &s).c(d!())? {
+            candidates.push((key_type, s));
+        }
+        if let Some(view_key) = view_key {
+            if self
+                .check_view_tag_with_scalar(&view_key.key_type, &view_key.scalar)
+                .c(d!())?
+            {
+                candidates.push((view_key.key_type.clone(), view_key.scalar.clone()));
+            }
+        }
+        if candidates.len() > 1 {
+            if let Some(i) = candidates
+                .iter()
+                .position(|(key_type, s)| self.decrypt_with_scalar(key_type, s).is_ok())
+            {
+                return Ok(candidates.swap_remove(i));
+            }
+        }
+        candidates
+            .into_iter()
+            .next()
+            .c(d!(ZeiError::DecryptionError))
+    }
+
+    // Parse the decrypted lock which encrypts only the amount.
+    pub(crate) fn parse_amount(decrypted_bytes: &[u8]) -> Result<u64> {
+        let decrypted_bytes = unpad(decrypted_bytes, &[8]).c(d!())?;
         // amount is u64, thus u64.to_be_bytes should be 8 bytes
         if decrypted_bytes.len() != 8 {
             return Err(eg!(ZeiError::InconsistentStructureError));
@@ -479,10 +693,9 @@ impl OwnerMemo {
         Ok(u64::from_be_bytes(amt_be_bytes))
     }
 
-    /// Decrypt the `OwnerMemo.lock` which encrypts only the confidential asset type
-    /// returns error if the decrypted bytes length doesn't match.
-    pub fn decrypt_asset_type(&self, keypair: &XfrKeyPair) -> Result<AssetType> {
-        let decrypted_bytes = self.decrypt(&keypair)?;
+    // Parse the decrypted lock which encrypts only the asset type.
+    pub(crate) fn parse_asset_type(decrypted_bytes: &[u8]) -> Result<AssetType> {
+        let decrypted_bytes = unpad(decrypted_bytes, &[ASSET_TYPE_LENGTH]).c(d!())?;
         if decrypted_bytes.len() != ASSET_TYPE_LENGTH {
             return Err(eg!(ZeiError::InconsistentStructureError));
         }
@@ -491,10 +704,9 @@ impl OwnerMemo {
         Ok(AssetType(asset_type_bytes))
     }
 
-    /// Decrypt the `OwnerMemo.lock` which encrypts "amount || asset type", both amount and asset type
-    /// are confidential.
-    pub fn decrypt_amount_and_asset_type(&self, keypair: &XfrKeyPair) -> Result<(u64, AssetType)> {
-        let decrypted_bytes = self.decrypt(&keypair)?;
+    // Parse the decrypted lock which encrypts "amount || asset type".
+    pub(crate) fn parse_amount_and_asset_type(decrypted_bytes: &[u8]) -> Result<(u64, AssetType)> {
+        let decrypted_bytes = unpad(decrypted_bytes, &[ASSET_TYPE_LENGTH + 8]).c(d!())?;
         if decrypted_bytes.len() != ASSET_TYPE_LENGTH + 8 {
             return Err(eg!(ZeiError::InconsistentStructureError));
         }
@@ -509,28 +721,79 @@ impl OwnerMemo {
         ))
     }
 
-    /// Return the amount blind (blind_low, blind_high)
-    pub fn derive_amount_blinds(
+    // Encode the amount and the asset type that are confidential, before the padding.
+    pub(crate) fn encode_plaintext(
+        amount: Option<u64>,
+        asset_type: Option<&AssetType>,
+    ) -> Result<Vec<u8>> {
+        let mut plaintext = vec![];
+        if let Some(amount) = amount {
+            plaintext.extend_from_slice(&amount.to_be_bytes());
+        }
+        if let Some(asset_type) = asset_type {
+            plaintext.extend_from_slice(&asset_type.0);
+        }
+        if plaintext.is_empty() {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(plaintext)
+    }
+
+    // Parse the padded plaintext of `encode_plaintext`, which is told apart by its length.
+    pub(crate) fn parse_plaintext(padded: &[u8]) -> Result<(Option<u64>, Option<AssetType>)> {
+        let plaintext = unpad(padded, &[8, ASSET_TYPE_LENGTH, 8 + ASSET_TYPE_LENGTH]).c(d!())?;
+        match plaintext.len() {
+            8 => Ok((Some(OwnerMemo::parse_amount(plaintext).c(d!())?), None)),
+            ASSET_TYPE_LENGTH => {
+                let asset_type = OwnerMemo::parse_asset_type(plaintext).c(d!())?;
+                Ok((None, Some(asset_type)))
+            }
+            _ => {
+                let (amount, asset_type) =
+                    OwnerMemo::parse_amount_and_asset_type(plaintext).c(d!())?;
+                Ok((Some(amount), Some(asset_type)))
+            }
+        }
+    }
+
+    // Return the amount blinds given the scalar of the receiver.
+    pub(crate) fn derive_amount_blinds_with_scalar(
         &self,
-        keypair: &XfrKeyPair,
+        key_type: &KeyType,
+        s: &[u8],
     ) -> Result<(RistrettoScalar, RistrettoScalar)> {
-        let (key_type, s) = keypair.sec_key.as_scalar_bytes();
-        let shared_point = OwnerMemo::derive_shared_point(&key_type, &s, &self.blind_share_bytes)?;
+        let shared_point = OwnerMemo::derive_shared_point(key_type, s, &self.blind_share_bytes)?;
         Ok(OwnerMemo::calc_amount_blinds(&shared_point))
     }
 
-    /// Return the asset type blind
-    pub fn derive_asset_type_blind(&self, keypair: &XfrKeyPair) -> Result<RistrettoScalar> {
-        let (key_type, s) = keypair.sec_key.as_scalar_bytes();
-        let shared_point = OwnerMemo::derive_shared_point(&key_type, &s, &self.blind_share_bytes)?;
+    // Return the asset type blind given the scalar of the receiver.
+    pub(crate) fn derive_asset_type_blind_with_scalar(
+        &self,
+        key_type: &KeyType,
+        s: &[u8],
+    ) -> Result<RistrettoScalar> {
+        let shared_point = OwnerMemo::derive_shared_point(key_type, s, &self.blind_share_bytes)?;
         Ok(OwnerMemo::calc_asset_type_blind(&shared_point))
     }
-}
 
-impl OwnerMemo {
-    // Decrypt the lock.
-    fn decrypt(&self, keypair: &XfrKeyPair) -> Result<Vec<u8>> {
-        keypair.hybrid_decrypt(&self.lock_bytes)
+    // Return true if the view tag of the memo, if any, is the one of the scalar of the receiver.
+    pub(crate) fn check_view_tag_with_scalar(&self, key_type: &KeyType, s: &[u8]) -> Result<bool> {
+        match self.view_tag {
+            Some(view_tag) => {
+                let shared_point =
+                    OwnerMemo::derive_shared_point(key_type, s, &self.blind_share_bytes)?;
+                Ok(OwnerMemo::calc_view_tag(&shared_point) == view_tag)
+            }
+            None => Ok(true),
+        }
+    }
+
+    // Given a shared point, calculate the view tag.
+    pub(crate) fn calc_view_tag(shared_point: &[u8]) -> u8 {
+        let mut hasher = Sha512::new();
+        hasher.update(OWNER_MEMO_VIEW_TAG_DOMAIN);
+        hasher.update(shared_point);
+        hasher.finalize()[0]
     }
 
     // Given a shared point, calculate the amount blinds.
@@ -547,7 +810,7 @@ impl OwnerMemo {
     }
 
     // Return the shared point.
-    fn derive_shared_point(key_type: &KeyType, s: &[u8], p: &[u8]) -> Result<Vec<u8>> {
+    pub(crate) fn derive_shared_point(key_type: &KeyType, s: &[u8], p: &[u8]) -> Result<Vec<u8>> {
         match key_type {
             KeyType::Ed25519 => {
                 let scalar = RistrettoScalar::from_bytes(s)?;
@@ -668,9 +931,9 @@ pub struct XfrProofs {
 /// The range proof building block of the amount and asset type part.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct XfrRangeProof {
-    /// The Bulletproofs range proof.
+    /// The range proof, of either backend.
     #[serde(with = "zei_obj_serde")]
-    pub range_proof: RangeProof,
+    pub range_proof: VersionedRangeProof,
     /// Lower 32 bits transfer amount difference commitment.
     pub xfr_diff_commitment_low: CompressedRistretto,
     /// Higher 32 bits transfer amount difference commitment.
@@ -717,7 +980,7 @@ enum CompatibleBlindShare {
 #[serde(untagged)]
 enum CompatibleLock {
     Old(ZeiHybridCiphertext),
-    New(Vec<u8>),
+    New(#[serde(deserialize_with = "zei_bounded_serde::memo_bytes")] Vec<u8>),
 }
 
 use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
@@ -733,6 +996,7 @@ impl<'de> Deserialize<'de> for OwnerMemo {
             BlindShareBytes,
             Lock,
             LockBytes,
+            ViewTag,
         }
 
         impl<'de> Deserialize<'de> for Field {
@@ -762,6 +1026,7 @@ impl<'de> Deserialize<'de> for OwnerMemo {
                             "blind_share_bytes" => Ok(Field::BlindShareBytes),
                             "lock" => Ok(Field::Lock),
                             "lock_bytes" => Ok(Field::LockBytes),
+                            "view_tag" => Ok(Field::ViewTag),
                             _ => Err(de::Error::unknown_field(value, FIELDS)),
                         }
                     }
@@ -805,10 +1070,13 @@ impl<'de> Deserialize<'de> for OwnerMemo {
                     CompatibleLock::Old(k) => k.zei_to_bytes(),
                     CompatibleLock::New(k) => k,
                 };
+                // the memos created before the view tags have no fourth element.
+                let view_tag = seq.next_element::<Option<u8>>()?.flatten();
                 Ok(OwnerMemo {
                     key_type,
                     blind_share_bytes,
                     lock_bytes,
+                    view_tag,
                 })
             }
 
@@ -819,6 +1087,7 @@ impl<'de> Deserialize<'de> for OwnerMemo {
                 let mut key_type = None;
                 let mut blind_share_bytes = None;
                 let mut lock_bytes = None;
+                let mut view_tag = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::KeyType => {
@@ -853,16 +1122,29 @@ impl<'de> Deserialize<'de> for OwnerMemo {
                             }
                             lock_bytes = Some(map.next_value()?);
                         }
+                        Field::ViewTag => {
+                            if view_tag.is_some() {
+                                return Err(de::Error::duplicate_field("view_tag"));
+                            }
+                            view_tag = Some(map.next_value::<Option<u8>>()?);
+                        }
                     }
                 }
                 let key_type = key_type.unwrap_or(KeyType::Ed25519);
                 let blind_share_bytes =
                     blind_share_bytes.ok_or_else(|| de::Error::missing_field("blind_share"))?;
                 let lock_bytes = lock_bytes.ok_or_else(|| de::Error::missing_field("lock"))?;
+                DeserializationLimits::check(
+                    "memo bytes",
+                    deserialization_limits().max_memo_bytes,
+                    lock_bytes.len(),
+                )
+                .map_err(de::Error::custom)?;
                 Ok(OwnerMemo {
                     key_type,
                     blind_share_bytes,
                     lock_bytes,
+                    view_tag: view_tag.flatten(),
                 })
             }
         }
@@ -873,6 +1155,7 @@ impl<'de> Deserialize<'de> for OwnerMemo {
             "blind_share_bytes",
             "lock",
             "lock_bytes",
+            "view_tag",
         ];
         deserializer.deserialize_struct("OwnerMemo", FIELDS, OwnerMemoVisitor)
     }
@@ -1,4 +1,4 @@
-use crate::anon_creds::{self, ac_commit, ACCommitment, Credential};
+use crate::anon_creds::{self, ac_commit, ACCommitment, Attr, Credential};
 use crate::setup::BulletproofParams;
 use crate::xfr::{
     asset_record::AssetRecordType,
@@ -609,6 +609,94 @@ mod multi_asset_no_tracing {
     }
 }
 
+mod range_proof_backends {
+    use super::*;
+    use crate::xfr::{gen_xfr_body_with_params, structs::AssetTypeAndAmountProof};
+    use zei_crypto::bulletproofs::range::{RangeProofBackend, VersionedRangeProof};
+
+    fn range_proof(body: &XfrBody) -> &VersionedRangeProof {
+        match &body.proofs.asset_type_and_amount_proof {
+            AssetTypeAndAmountProof::ConfAmount(proof) => &proof.range_proof,
+            _ => panic!("not a confidential amount transfer"),
+        }
+    }
+
+    #[test]
+    fn bulletproofs_plus_range_proofs() {
+        let mut prng = test_rng();
+        let mut params = BulletproofParams::default();
+        let asset_type = AssetType::from_identical_byte(0u8);
+        let record_type = AssetRecordType::ConfidentialAmount_NonConfidentialAssetType;
+
+        let mut records = |amount: u64, n: usize| {
+            gen_key_pair_vec(n, &mut prng)
+                .iter()
+                .map(|key_pair| {
+                    let template = AssetRecordTemplate::with_no_asset_tracing(
+                        amount,
+                        asset_type,
+                        record_type,
+                        key_pair.pub_key,
+                    );
+                    AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+                })
+                .collect_vec()
+        };
+        let inputs = records(30, 2);
+        let outputs = records(20, 3);
+        let policies = XfrNotePolicies::empty_policies(inputs.len(), outputs.len());
+
+        let legacy_body = gen_xfr_body_with_params(&mut prng, &params, &inputs, &outputs).unwrap();
+        params.range_proof_backend = RangeProofBackend::BulletproofsPlus;
+        let body = gen_xfr_body_with_params(&mut prng, &params, &inputs, &outputs).unwrap();
+        assert_eq!(
+            range_proof(&legacy_body).backend(),
+            RangeProofBackend::Bulletproofs
+        );
+        assert_eq!(
+            range_proof(&body).backend(),
+            RangeProofBackend::BulletproofsPlus
+        );
+        assert!(range_proof(&body).to_bytes().len() < range_proof(&legacy_body).to_bytes().len());
+
+        // proofs of both backends verify, also in the same batch, whatever the backend of the
+        // parameters of the verifier
+        pnk!(verify_xfr_body(
+            &mut prng,
+            &mut params,
+            &body,
+            &policies.to_ref()
+        ));
+        pnk!(batch_verify_xfr_body_asset_records(
+            &mut prng,
+            &mut params,
+            &[&legacy_body, &body]
+        ));
+        params.range_proof_backend = RangeProofBackend::Bulletproofs;
+        pnk!(verify_xfr_body(
+            &mut prng,
+            &mut params,
+            &body,
+            &policies.to_ref()
+        ));
+
+        // the serialization keeps the version of the proofs
+        for body in [&legacy_body, &body] {
+            let json = serde_json::to_string(body).unwrap();
+            assert_eq!(&serde_json::from_str::<XfrBody>(&json).unwrap(), body);
+            let bytes = bincode::serialize(body).unwrap();
+            assert_eq!(&bincode::deserialize::<XfrBody>(&bytes).unwrap(), body);
+        }
+
+        // the proof does not verify for other amounts
+        let mut other_body = body.clone();
+        other_body.outputs[0].amount = legacy_body.outputs[0].amount;
+        assert!(
+            batch_verify_xfr_body_asset_records(&mut prng, &mut params, &[&other_body]).is_err()
+        );
+    }
+}
+
 mod keys {
     use super::*;
 
@@ -667,7 +755,13 @@ mod keys {
             outputs.as_slice(),
             &[], //no keys
         );
-        msg_eq!(ZeiError::ParameterError, xfr_note.unwrap_err());
+        msg_eq!(
+            ZeiError::LengthMismatch {
+                expected: 2,
+                actual: 0
+            },
+            xfr_note.unwrap_err()
+        );
 
         let key1 = XfrKeyPair::generate(&mut prng);
         let key2 = XfrKeyPair::generate(&mut prng);
@@ -678,7 +772,63 @@ mod keys {
             &[&key1, &key2],
         );
 
-        msg_eq!(ZeiError::ParameterError, xfr_note.unwrap_err());
+        msg_eq!(
+            ZeiError::InputError {
+                index: 0,
+                source: Box::new(ZeiError::ParameterError)
+            },
+            xfr_note.unwrap_err()
+        );
+    }
+}
+
+mod deterministic {
+    use super::*;
+    use zei_crypto::deterministic::DeterministicProver;
+
+    fn deterministic_xfr_note(seed: [u8; 32]) -> XfrNote {
+        let mut prng = DeterministicProver::from_seed(seed);
+        let asset_record_type = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let inkeys = gen_key_pair_vec(2, &mut prng);
+        let outkeys = gen_key_pair_vec(2, &mut prng);
+        let template = |amount: u64, byte: u8, keypair: &XfrKeyPair| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                AssetType::from_identical_byte(byte),
+                asset_record_type,
+                keypair.pub_key,
+            )
+        };
+        let (xfr_note, _, _) = create_xfr(
+            &mut prng,
+            &[template(10, 0, &inkeys[0]), template(20, 1, &inkeys[1])],
+            &[template(10, 0, &outkeys[0]), template(20, 1, &outkeys[1])],
+            &[&inkeys[0], &inkeys[1]],
+        );
+        xfr_note
+    }
+
+    #[test]
+    fn deterministic_xfr_note_generation() {
+        let note = deterministic_xfr_note([7u8; 32]);
+        let bytes = bincode::serialize(&note).unwrap();
+        assert_eq!(
+            bincode::serialize(&deterministic_xfr_note([7u8; 32])).unwrap(),
+            bytes
+        );
+        assert_ne!(
+            bincode::serialize(&deterministic_xfr_note([8u8; 32])).unwrap(),
+            bytes
+        );
+
+        let mut params = BulletproofParams::default();
+        assert!(verify_xfr_note(
+            &mut test_rng(),
+            &mut params,
+            &note,
+            &XfrNotePolicies::empty_policies(2, 2).to_ref()
+        )
+        .is_ok());
     }
 }
 
@@ -693,7 +843,7 @@ mod identity_tracing {
 
         let tracer_keys = AssetTracerKeyPair::generate(&mut prng);
 
-        let attrs = vec![1u32, 2, 3, 4];
+        let attrs = (1..=4).map(Attr::U64).collect_vec();
         let (cred_issuer_sk, cred_issuer_pk) = anon_creds::ac_keygen_issuer(&mut prng, 4);
         let (receiver_ac_sk, receiver_ac_pk) =
             anon_creds::ac_keygen_user(&mut prng, &cred_issuer_pk);
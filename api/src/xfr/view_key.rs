@@ -0,0 +1,731 @@
+use crate::anon_xfr::keys::{AXfrPubKey, AXfrSecretKey};
+use crate::xfr::{
+    asset_record::open_blind_asset_record_with_scalar,
+    memo_padding::unpad,
+    sig::{KeyType, XfrKeyPair, XfrPublicKey, XfrPublicKeyInner, XFR_PUBLIC_KEY_LENGTH},
+    structs::{
+        AssetType, BlindAssetRecord, OpenAssetRecord, OwnerMemo, OwnerMemoOptions, XfrAmount,
+        XfrAssetType, XfrNote, ASSET_TYPE_LENGTH,
+    },
+};
+use curve25519_dalek::{constants::ED25519_BASEPOINT_TABLE, scalar::Scalar as DalekScalar};
+use digest::Digest;
+use ed25519_dalek::PublicKey as Ed25519PublicKey;
+use libsecp256k1::{PublicKey as Secp256k1PublicKey, SecretKey as Secp256k1SecretKey};
+#[cfg(feature = "parallel")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use sha2::Sha512;
+use zei_algebra::{
+    fmt,
+    prelude::*,
+    ristretto::RistrettoScalar,
+    secp256k1::{SECP256K1Scalar, SECP256K1G1},
+};
+use zei_crypto::basic::{
+    hybrid_encryption::{
+        hybrid_decrypt_aead, hybrid_decrypt_with_x25519_secret_key, AeadHybridCiphertext,
+        XSecretKey, ZeiHybridCiphertext,
+    },
+    kem::{Secp256k1Kem, X25519Kem},
+    pedersen_comm::PedersenCommitmentRistretto,
+};
+
+/// The length of the view key.
+pub const VIEW_KEY_LENGTH: usize = 1 + 32 + XFR_PUBLIC_KEY_LENGTH; // KeyType + 32 bytes + public key
+
+/// The lengths of the unpadded plaintexts of the owner memos.
+const MEMO_PLAINTEXT_LENS: [usize; 3] = [8, ASSET_TYPE_LENGTH, 8 + ASSET_TYPE_LENGTH];
+
+/// The domain separator of the derivation of the view secrets from the secret keys.
+const VIEW_SECRET_DOMAIN: &[u8] = b"Zei Xfr View Secret";
+
+/// The view key of a receiver of confidential transfers.
+///
+/// Its secret is derived from the secret key of the receiver by a hash with a domain tag, so it
+/// reveals nothing about the secret key, and it can neither sign nor spend. The receiver
+/// publishes the matching view public key, see [`ViewKey::get_view_pk`], and the senders address
+/// the owner memos of the outputs to it with [`OwnerMemoOptions::with_view_key`]. The view key
+/// detects these outputs and opens their amounts and asset types, so that it can be given to
+/// auditors or light wallets that scan the chain. The owner memos addressed to the public key of
+/// the receiver itself can only be opened with its key pair.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ViewKey {
+    pub(crate) key_type: KeyType,
+    pub(crate) scalar: Vec<u8>,
+    pub(crate) pub_key: XfrPublicKey,
+    pub(crate) view_pub_key: XfrPublicKey,
+}
+
+impl fmt::Debug for ViewKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ViewKey")
+            .field("key_type", &self.key_type)
+            .field("pub_key", &self.pub_key)
+            .field("view_pub_key", &self.view_pub_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ViewKey {
+    /// Derive the view key of a key pair.
+    /// Address key pairs cannot receive confidential records and are rejected.
+    pub fn from_keypair(keypair: &XfrKeyPair) -> Result<Self> {
+        let (key_type, sk_scalar) = keypair.get_sk_ref().as_scalar_bytes();
+        let scalar = derive_view_secret(&key_type, &sk_scalar).c(d!())?;
+        ViewKey::from_view_secret(key_type, scalar, keypair.get_pk()).c(d!())
+    }
+
+    // Build the view key from its secret and the public key of the owner.
+    pub(crate) fn from_view_secret(
+        key_type: KeyType,
+        scalar: Vec<u8>,
+        pub_key: XfrPublicKey,
+    ) -> Result<Self> {
+        let view_pub_key = derive_view_public_key(&key_type, &scalar).c(d!())?;
+        Ok(ViewKey {
+            key_type,
+            scalar,
+            pub_key,
+            view_pub_key,
+        })
+    }
+
+    /// Return a reference of the public key of the owner.
+    pub fn get_pk_ref(&self) -> &XfrPublicKey {
+        &self.pub_key
+    }
+
+    /// Return the view public key, which the owner memos are addressed to so that this view key
+    /// can open them.
+    pub fn get_view_pk(&self) -> XfrPublicKey {
+        self.view_pub_key
+    }
+
+    /// Hybrid decryption of a lock addressed to the view public key, see
+    /// [`XfrPublicKey::hybrid_encrypt_aead`].
+    pub fn hybrid_decrypt(&self, lock: &[u8]) -> Result<Vec<u8>> {
+        hybrid_decrypt_aead_with_scalar(&self.key_type, &self.scalar, lock)
+    }
+
+    /// Check whether a record belongs to the owner of this view key.
+    pub fn is_owner(&self, record: &BlindAssetRecord) -> bool {
+        record.public_key == self.pub_key
+    }
+
+    /// Open a blind asset record that belongs to the owner of this view key.
+    pub fn open_blind_asset_record(
+        &self,
+        record: &BlindAssetRecord,
+        owner_memo: &Option<OwnerMemo>,
+    ) -> Result<OpenAssetRecord> {
+        if !self.is_owner(record) {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        open_blind_asset_record_with_scalar(record, owner_memo, &self.key_type, &self.scalar)
+            .c(d!())
+    }
+
+    /// Scan the outputs of a transfer note, and return the index and the opening of each output
+    /// that belongs to the owner of this view key.
+    /// Outputs whose owner memos are missing or cannot be decrypted are skipped.
+    pub fn scan_xfr_note(&self, note: &XfrNote) -> Vec<(usize, OpenAssetRecord)> {
+        note.body
+            .outputs
+            .iter()
+            .zip(note.body.owners_memos.iter())
+            .enumerate()
+            .filter(|(_, (output, _))| self.is_owner(output))
+            .filter_map(|(i, (output, owner_memo))| {
+                self.open_blind_asset_record(output, owner_memo)
+                    .ok()
+                    .map(|oar| (i, oar))
+            })
+            .collect()
+    }
+
+    /// Scan a list of transfer notes, and return the note index, the output index, and the opening
+    /// of each output that belongs to the owner of this view key.
+    pub fn scan_xfr_notes(&self, notes: &[XfrNote]) -> Vec<(usize, usize, OpenAssetRecord)> {
+        notes
+            .iter()
+            .enumerate()
+            .flat_map(|(i, note)| {
+                self.scan_xfr_note(note)
+                    .into_iter()
+                    .map(move |(j, oar)| (i, j, oar))
+            })
+            .collect()
+    }
+}
+
+// Derive the view secret from the scalar of a secret key.
+// For Ed25519 keys, it is a clamped X25519 scalar, which the decryption of the locks over X25519
+// uses as is.
+fn derive_view_secret(key_type: &KeyType, sk_scalar: &[u8]) -> Result<Vec<u8>> {
+    let mut hasher = Sha512::new();
+    hasher.update(VIEW_SECRET_DOMAIN);
+    hasher.update(sk_scalar);
+    let hash = hasher.finalize();
+    match key_type {
+        KeyType::Ed25519 => {
+            let mut bytes = [0u8; 32];
+            bytes.copy_from_slice(&hash[..32]);
+            bytes[0] &= 248;
+            bytes[31] &= 127;
+            bytes[31] |= 64;
+            Ok(bytes.to_vec())
+        }
+        KeyType::Secp256k1 => Ok(SECP256K1Scalar::from_le_bytes_mod_order(&hash).to_bytes()),
+        KeyType::Address => Err(eg!(ZeiError::ParameterError)),
+    }
+}
+
+// Derive the view public key from the view secret.
+fn derive_view_public_key(key_type: &KeyType, scalar: &[u8]) -> Result<XfrPublicKey> {
+    match key_type {
+        KeyType::Ed25519 => {
+            let mut bits = [0u8; 32];
+            bits.copy_from_slice(scalar);
+            let point = &ED25519_BASEPOINT_TABLE * &DalekScalar::from_bits(bits);
+            let pk = Ed25519PublicKey::from_bytes(point.compress().as_bytes())
+                .c(d!(ZeiError::ParameterError))?;
+            Ok(XfrPublicKey(XfrPublicKeyInner::Ed25519(pk)))
+        }
+        KeyType::Secp256k1 => {
+            // the scalars of the algebra are little-endian, while libsecp256k1 is big-endian.
+            let mut be_bytes = scalar.to_vec();
+            be_bytes.reverse();
+            let sk = Secp256k1SecretKey::parse_slice(&be_bytes).c(d!(ZeiError::ParameterError))?;
+            Ok(XfrPublicKey(XfrPublicKeyInner::Secp256k1(
+                Secp256k1PublicKey::from_secret_key(&sk),
+            )))
+        }
+        KeyType::Address => Err(eg!(ZeiError::ParameterError)),
+    }
+}
+
+/// Hybrid decryption of an AEAD lock, i.e., the lock of an owner memo with a view tag, with the
+/// scalar of the secret key or of the view key.
+pub(crate) fn hybrid_decrypt_aead_with_scalar(
+    key_type: &KeyType,
+    scalar: &[u8],
+    lock: &[u8],
+) -> Result<Vec<u8>> {
+    match key_type {
+        KeyType::Ed25519 => {
+            let ctext = AeadHybridCiphertext::<X25519Kem>::zei_from_bytes(lock)
+                .c(d!(ZeiError::DecryptionError))?;
+            let scalar = RistrettoScalar::from_bytes(scalar).c(d!())?;
+            let sk = XSecretKey::zei_from_bytes(&scalar.to_bytes()).c(d!())?;
+            hybrid_decrypt_aead(&ctext, &sk).c(d!(ZeiError::DecryptionError))
+        }
+        KeyType::Secp256k1 => {
+            let ctext = AeadHybridCiphertext::<Secp256k1Kem>::zei_from_bytes(lock)
+                .c(d!(ZeiError::DecryptionError))?;
+            let sk = SECP256K1Scalar::from_bytes(scalar).c(d!())?;
+            hybrid_decrypt_aead(&ctext, &sk).c(d!(ZeiError::DecryptionError))
+        }
+        KeyType::Address => Err(eg!(ZeiError::ParameterError)),
+    }
+}
+
+/// Hybrid decryption of a legacy lock, i.e., the lock of an owner memo without a view tag, with
+/// the scalar of the secret key.
+pub(crate) fn hybrid_decrypt_with_scalar(
+    key_type: &KeyType,
+    scalar: &[u8],
+    lock: &[u8],
+) -> Result<Vec<u8>> {
+    match key_type {
+        KeyType::Ed25519 => {
+            let ctext = ZeiHybridCiphertext::zei_from_bytes(lock).c(d!())?;
+            let scalar = RistrettoScalar::from_bytes(scalar).c(d!())?;
+            let sk = XSecretKey::zei_from_bytes(&scalar.to_bytes()).c(d!())?;
+            Ok(hybrid_decrypt_with_x25519_secret_key(&ctext, &sk))
+        }
+        KeyType::Secp256k1 => {
+            if lock.len() < 33 {
+                return Err(eg!(ZeiError::DecryptionError));
+            }
+            let sk = AXfrSecretKey(SECP256K1Scalar::from_bytes(scalar).c(d!())?);
+            let share = AXfrPubKey(SECP256K1G1::from_compressed_bytes(&lock[0..33]).c(d!())?);
+            sk.decrypt(&share, &lock[33..])
+        }
+        KeyType::Address => Err(eg!(ZeiError::ParameterError)),
+    }
+}
+
+/// The content of an owner memo decrypted by a view key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecryptedMemo {
+    /// The amount, if it is confidential.
+    pub amount: Option<u64>,
+    /// The asset type, if it is confidential.
+    pub asset_type: Option<AssetType>,
+    /// The amount blinds (blind_low, blind_high), if the amount is confidential.
+    pub amount_blinds: Option<(RistrettoScalar, RistrettoScalar)>,
+    /// The asset type blind, if the asset type is confidential.
+    pub asset_type_blind: Option<RistrettoScalar>,
+}
+
+impl DecryptedMemo {
+    /// Check that the decrypted memo opens the commitments of a record.
+    pub fn matches(&self, record: &BlindAssetRecord) -> bool {
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let amount_ok = match (&record.amount, self.amount, self.amount_blinds) {
+            (XfrAmount::Confidential(_), Some(amount), Some((blind_lo, blind_hi))) => {
+                XfrAmount::from_blinds(&pc_gens, amount, &blind_lo, &blind_hi) == record.amount
+            }
+            (XfrAmount::NonConfidential(_), None, None) => true,
+            _ => false,
+        };
+        let asset_type_ok = match (&record.asset_type, self.asset_type, self.asset_type_blind) {
+            (XfrAssetType::Confidential(_), Some(asset_type), Some(blind)) => {
+                XfrAssetType::from_blind(&pc_gens, &asset_type, &blind) == record.asset_type
+            }
+            (XfrAssetType::NonConfidential(_), None, None) => true,
+            _ => false,
+        };
+        amount_ok && asset_type_ok
+    }
+}
+
+impl ViewKey {
+    // Check the view tag of the memo, before decrypting it.
+    // The memos without a view tag precede the view keys, so they are not addressed to one.
+    fn check_memo_tag(&self, memo: &OwnerMemo) -> bool {
+        memo.key_type == self.key_type
+            && memo.view_tag.is_some()
+            && memo
+                .check_view_tag_with_scalar(&self.key_type, &self.scalar)
+                .unwrap_or(false)
+    }
+
+    /// Decrypt an owner memo without the record.
+    /// Return `None` if the memo is not addressed to this view key.
+    ///
+    /// The view tag of the memo is checked before the decryption, which skips most of the memos
+    /// addressed to other keys, and the encryption of the lock is key-committing, so a memo
+    /// addressed to another key is rejected by the decryption.
+    pub fn decrypt_owner_memo(&self, memo: &OwnerMemo) -> Option<DecryptedMemo> {
+        if !self.check_memo_tag(memo) {
+            return None;
+        }
+        let plaintext = self.hybrid_decrypt(&memo.lock_bytes).ok()?;
+        let plaintext = unpad(&plaintext, &MEMO_PLAINTEXT_LENS).ok()?;
+
+        let (amount, asset_type) = match plaintext.len() {
+            8 => (Some(OwnerMemo::parse_amount(&plaintext).ok()?), None),
+            ASSET_TYPE_LENGTH => (None, Some(OwnerMemo::parse_asset_type(&plaintext).ok()?)),
+            _ => {
+                let (amount, asset_type) =
+                    OwnerMemo::parse_amount_and_asset_type(&plaintext).ok()?;
+                (Some(amount), Some(asset_type))
+            }
+        };
+        let amount_blinds = match amount {
+            Some(_) => Some(
+                memo.derive_amount_blinds_with_scalar(&self.key_type, &self.scalar)
+                    .ok()?,
+            ),
+            None => None,
+        };
+        let asset_type_blind = match asset_type {
+            Some(_) => Some(
+                memo.derive_asset_type_blind_with_scalar(&self.key_type, &self.scalar)
+                    .ok()?,
+            ),
+            None => None,
+        };
+
+        Some(DecryptedMemo {
+            amount,
+            asset_type,
+            amount_blinds,
+            asset_type_blind,
+        })
+    }
+}
+
+/// Trial-decrypt a list of owner memos with a view key, in parallel if the `parallel` feature is on.
+/// The result has one entry for each memo, which is `None` if the memo is not addressed to the view key.
+pub fn scan_owner_memos(view_key: &ViewKey, memos: &[OwnerMemo]) -> Vec<Option<DecryptedMemo>> {
+    #[cfg(feature = "parallel")]
+    let iter = memos.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let iter = memos.iter();
+
+    iter.map(|memo| view_key.decrypt_owner_memo(memo)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::xfr::{
+        asset_record::{open_blind_asset_record, AssetRecordType},
+        gen_xfr_note,
+        memo_padding::MemoPaddingPolicy,
+        sig::XfrKeyPair,
+        structs::{AssetRecord, AssetRecordTemplate, AssetType, OwnerMemo, OwnerMemoOptions},
+        view_key::{scan_owner_memos, ViewKey},
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+
+    // Create a record whose owner memo is addressed to the view key of the owner.
+    fn record_to_view_key<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        template: &AssetRecordTemplate,
+        owner: &XfrKeyPair,
+        padding: MemoPaddingPolicy,
+    ) -> AssetRecord {
+        let view_key = ViewKey::from_keypair(owner).unwrap();
+        let options = OwnerMemoOptions::default()
+            .with_padding(padding)
+            .with_view_key(view_key.get_view_pk());
+        AssetRecord::from_template_with_memo_options(prng, template, &options).unwrap()
+    }
+
+    fn check_view_key(sender: XfrKeyPair, receiver: XfrKeyPair, other: XfrKeyPair) {
+        let mut prng = test_rng();
+        let asset_type = AssetType::from_identical_byte(1u8);
+
+        let input = AssetRecord::from_template_no_identity_tracing(
+            &mut prng,
+            &AssetRecordTemplate::with_no_asset_tracing(
+                100,
+                asset_type,
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                sender.get_pk(),
+            ),
+        )
+        .unwrap();
+        let template = |amount: u64, record_type: AssetRecordType, owner: &XfrKeyPair| {
+            AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                owner.get_pk(),
+            )
+        };
+        let outputs = vec![
+            record_to_view_key(
+                &mut prng,
+                &template(
+                    10,
+                    AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                    &receiver,
+                ),
+                &receiver,
+                MemoPaddingPolicy::default(),
+            ),
+            record_to_view_key(
+                &mut prng,
+                &template(
+                    20,
+                    AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+                    &other,
+                ),
+                &other,
+                MemoPaddingPolicy::default(),
+            ),
+            record_to_view_key(
+                &mut prng,
+                &template(
+                    30,
+                    AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+                    &receiver,
+                ),
+                &receiver,
+                MemoPaddingPolicy::default(),
+            ),
+            // a memo addressed to the public key of the receiver, and not to its view key
+            AssetRecord::from_template_no_identity_tracing(
+                &mut prng,
+                &template(
+                    40,
+                    AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                    &receiver,
+                ),
+            )
+            .unwrap(),
+        ];
+        let note = gen_xfr_note(&mut prng, &[input], &outputs, &[&sender]).unwrap();
+
+        let view_key = ViewKey::from_keypair(&receiver).unwrap();
+        assert_eq!(view_key.get_pk_ref(), receiver.get_pk_ref());
+        assert_ne!(view_key.get_view_pk(), receiver.get_pk());
+
+        let found = view_key.scan_xfr_note(&note);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, 0);
+        assert_eq!(*found[0].1.get_amount(), 10);
+        assert_eq!(*found[0].1.get_asset_type(), asset_type);
+        assert_eq!(found[1].0, 2);
+        assert_eq!(*found[1].1.get_amount(), 30);
+        assert_eq!(*found[1].1.get_asset_type(), asset_type);
+
+        // the key pair opens the records addressed to its view key as well as the others
+        for (i, oar) in found.iter() {
+            let expected = open_blind_asset_record(
+                &note.body.outputs[*i],
+                &note.body.owners_memos[*i],
+                &receiver,
+            )
+            .unwrap();
+            assert_eq!(*oar, expected);
+        }
+        let oar =
+            open_blind_asset_record(&note.body.outputs[3], &note.body.owners_memos[3], &receiver)
+                .unwrap();
+        assert_eq!(*oar.get_amount(), 40);
+        assert_eq!(
+            note.body.owners_memos[0]
+                .as_ref()
+                .unwrap()
+                .decrypt_amount_and_asset_type(&receiver)
+                .unwrap(),
+            (10, asset_type)
+        );
+
+        let found = view_key.scan_xfr_notes(&[note.clone(), note.clone()]);
+        assert_eq!(found.len(), 4);
+        assert_eq!((found[3].0, found[3].1), (1, 2));
+
+        // records of other users are not opened
+        assert!(view_key
+            .open_blind_asset_record(&note.body.outputs[1], &note.body.owners_memos[1])
+            .is_err());
+        let other_view_key = ViewKey::from_keypair(&other).unwrap();
+        let found = other_view_key.scan_xfr_note(&note);
+        assert_eq!(found.len(), 1);
+        assert_eq!(*found[0].1.get_amount(), 20);
+
+        // serialization
+        let bytes = view_key.zei_to_bytes();
+        assert_eq!(ViewKey::zei_from_bytes(&bytes).unwrap(), view_key);
+        let json = serde_json::to_string(&view_key).unwrap();
+        assert_eq!(serde_json::from_str::<ViewKey>(&json).unwrap(), view_key);
+    }
+
+    #[test]
+    fn view_key_scanning() {
+        let mut prng = test_rng();
+        check_view_key(
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_ed25519(&mut prng),
+        );
+        check_view_key(
+            XfrKeyPair::generate_secp256k1(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        );
+        check_view_key(
+            XfrKeyPair::generate_secp256k1(&mut prng),
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        );
+    }
+
+    #[test]
+    fn view_key_is_not_the_secret_key() {
+        let mut prng = test_rng();
+        for keypair in [
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        ] {
+            let view_key = ViewKey::from_keypair(&keypair).unwrap();
+            let (_, sk_scalar) = keypair.get_sk_ref().as_scalar_bytes();
+            assert_ne!(view_key.scalar, sk_scalar);
+            assert_eq!(ViewKey::from_keypair(&keypair).unwrap(), view_key);
+
+            // the secret is not printed
+            let debug = format!("{:?}", view_key);
+            assert!(!debug.contains("scalar"));
+            assert!(!debug.contains(&format!("{:?}", view_key.scalar)));
+        }
+    }
+
+    #[test]
+    fn view_key_rejects_address() {
+        let mut prng = test_rng();
+        assert!(ViewKey::from_keypair(&XfrKeyPair::generate_address(&mut prng)).is_err());
+    }
+
+    fn check_scan_owner_memos(receiver: XfrKeyPair, other: XfrKeyPair) {
+        let mut prng = test_rng();
+        let asset_type = AssetType::from_identical_byte(3u8);
+        let record_types = [
+            AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+            AssetRecordType::ConfidentialAmount_NonConfidentialAssetType,
+            AssetRecordType::NonConfidentialAmount_ConfidentialAssetType,
+        ];
+
+        let mut records = vec![];
+        let mut memos = vec![];
+        for i in 0..12usize {
+            let owner = if i % 2 == 0 { &receiver } else { &other };
+            // the second half of the memos are padded
+            let padding = if i < 6 {
+                MemoPaddingPolicy::default()
+            } else {
+                MemoPaddingPolicy::standard()
+            };
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                100 + i as u64,
+                asset_type,
+                record_types[i % 3],
+                owner.get_pk(),
+            );
+            let record = record_to_view_key(&mut prng, &template, owner, padding);
+            records.push(record.open_asset_record.blind_asset_record.clone());
+            memos.push(record.owner_memo.unwrap());
+        }
+
+        // padded memos of the same owner cannot be told apart by their length
+        for i in 8..12usize {
+            assert_eq!(memos[i].lock_bytes.len(), memos[i - 2].lock_bytes.len());
+        }
+
+        let view_key = ViewKey::from_keypair(&receiver).unwrap();
+        let results = scan_owner_memos(&view_key, &memos);
+        assert_eq!(results.len(), memos.len());
+
+        for (i, result) in results.iter().enumerate() {
+            let confirmed = result
+                .as_ref()
+                .map(|memo| memo.matches(&records[i]))
+                .unwrap_or(false);
+            assert_eq!(confirmed, i % 2 == 0);
+
+            if i % 2 == 0 {
+                let memo = result.as_ref().unwrap();
+                let oar = open_blind_asset_record(&records[i], &Some(memos[i].clone()), &receiver)
+                    .unwrap();
+                if records[i].amount.is_confidential() {
+                    assert_eq!(memo.amount, Some(100 + i as u64));
+                    assert_eq!(memo.amount_blinds, Some(oar.amount_blinds));
+                } else {
+                    assert!(memo.amount.is_none());
+                }
+                if records[i].asset_type.is_confidential() {
+                    assert_eq!(memo.asset_type, Some(asset_type));
+                    assert_eq!(memo.asset_type_blind, Some(oar.type_blind));
+                } else {
+                    assert!(memo.asset_type.is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn owner_memo_scanning() {
+        let mut prng = test_rng();
+        check_scan_owner_memos(
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_ed25519(&mut prng),
+        );
+        check_scan_owner_memos(
+            XfrKeyPair::generate_secp256k1(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        );
+        check_scan_owner_memos(
+            XfrKeyPair::generate_secp256k1(&mut prng),
+            XfrKeyPair::generate_ed25519(&mut prng),
+        );
+
+        // the memos to other keys are rejected by the decryption, which is key-committing,
+        // even when their view tag matches
+        for (receiver, other) in [
+            (
+                XfrKeyPair::generate_ed25519(&mut prng),
+                XfrKeyPair::generate_ed25519(&mut prng),
+            ),
+            (
+                XfrKeyPair::generate_secp256k1(&mut prng),
+                XfrKeyPair::generate_secp256k1(&mut prng),
+            ),
+        ] {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                1,
+                AssetType::from_identical_byte(3u8),
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                other.get_pk(),
+            );
+            let record =
+                record_to_view_key(&mut prng, &template, &other, MemoPaddingPolicy::default());
+            let memo = record.owner_memo.unwrap();
+            let view_key = ViewKey::from_keypair(&receiver).unwrap();
+            assert!(view_key.decrypt_owner_memo(&memo).is_none());
+            msg_eq!(
+                ZeiError::DecryptionError,
+                view_key.hybrid_decrypt(&memo.lock_bytes).unwrap_err()
+            );
+            assert!(receiver.hybrid_decrypt_aead(&memo.lock_bytes).is_err());
+            assert!(ViewKey::from_keypair(&other)
+                .unwrap()
+                .hybrid_decrypt(&memo.lock_bytes)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn owner_memo_view_tag() {
+        let mut prng = test_rng();
+        for receiver in [
+            XfrKeyPair::generate_ed25519(&mut prng),
+            XfrKeyPair::generate_secp256k1(&mut prng),
+        ] {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                7,
+                AssetType::from_identical_byte(4u8),
+                AssetRecordType::ConfidentialAmount_ConfidentialAssetType,
+                receiver.get_pk(),
+            );
+            let view_key = ViewKey::from_keypair(&receiver).unwrap();
+            for record in [
+                AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap(),
+                record_to_view_key(&mut prng, &template, &receiver, Default::default()),
+            ] {
+                let bar = record.open_asset_record.blind_asset_record.clone();
+                let memo = record.owner_memo.unwrap();
+                assert!(memo.view_tag.is_some());
+                assert!(memo.check_view_tag(&receiver));
+
+                // the lock is authenticated
+                let mut modified = memo.clone();
+                *modified.lock_bytes.last_mut().unwrap() ^= 1;
+                assert!(view_key.decrypt_owner_memo(&modified).is_none());
+                assert!(open_blind_asset_record(&bar, &Some(modified), &receiver).is_err());
+
+                // a memo whose view tag is not the one of the key is skipped
+                let mut wrong_tag = memo.clone();
+                wrong_tag.view_tag = memo.view_tag.map(|tag| tag ^ 1);
+                assert!(view_key.decrypt_owner_memo(&wrong_tag).is_none());
+                assert!(open_blind_asset_record(&bar, &Some(wrong_tag), &receiver).is_err());
+            }
+
+            // the memos created before the view tags, whose locks are in the legacy format,
+            // are still opened by the key pair
+            let record =
+                AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap();
+            let bar = record.open_asset_record.blind_asset_record.clone();
+            let mut plaintext = 7u64.to_be_bytes().to_vec();
+            plaintext.extend_from_slice(&AssetType::from_identical_byte(4u8).0);
+            let memo = OwnerMemo {
+                lock_bytes: receiver
+                    .get_pk()
+                    .hybrid_encrypt(&mut prng, &plaintext)
+                    .unwrap(),
+                ..record.owner_memo.unwrap()
+            };
+            let mut json = serde_json::to_value(&memo).unwrap();
+            json.as_object_mut().unwrap().remove("view_tag");
+            let legacy: OwnerMemo = serde_json::from_value(json).unwrap();
+            assert!(legacy.view_tag.is_none());
+            assert!(legacy.check_view_tag(&receiver));
+            assert!(view_key.decrypt_owner_memo(&legacy).is_none());
+            assert!(open_blind_asset_record(&bar, &Some(legacy), &receiver).is_ok());
+        }
+    }
+}
@@ -24,7 +24,7 @@ mod smoke_axfr {
             },
             FEE_TYPE, TREE_DEPTH,
         },
-        setup::{ProverParams, VerifierParams},
+        setup::{ParamsId, ProverParams, VerifierParams},
         xfr::{
             asset_record::{build_blind_asset_record, open_blind_asset_record, AssetRecordType},
             sig::{XfrKeyPair, XfrPublicKey},
@@ -197,6 +197,11 @@ mod smoke_axfr {
         err_note.signature = bad_sig;
         assert!(verify_bar_to_abar_note(&verify_params, &err_note, &sender.pub_key).is_err());
 
+        // the note is bound to the identifier of its parameters
+        let mut err_params_id = note.clone();
+        err_params_id.params_id = ParamsId([1u8; 32]);
+        assert!(verify_bar_to_abar_note(&verify_params, &err_params_id, &sender.pub_key).is_err());
+
         #[cfg(feature = "parallel")]
         {
             let mut notes = vec![&note; 6];
@@ -205,6 +210,10 @@ mod smoke_axfr {
 
             notes[5] = &err_note;
             assert!(batch_verify_bar_to_abar_note(&verify_params, &notes, &pub_keys).is_err());
+
+            // the notes and the public keys are not truncated to the shortest
+            notes[5] = &note;
+            assert!(batch_verify_bar_to_abar_note(&verify_params, &notes, &pub_keys[..5]).is_err());
         }
 
         // check open ABAR
@@ -252,6 +261,14 @@ mod smoke_axfr {
             verify_abar_to_ar_note(&verify_params, &note, &proof.root, err_hash.clone()).is_err()
         );
 
+        // the note is bound to the identifier of its parameters
+        let mut err_params_id = note.clone();
+        err_params_id.params_id = ParamsId([1u8; 32]);
+        assert!(
+            verify_abar_to_ar_note(&verify_params, &err_params_id, &proof.root, hash.clone())
+                .is_err()
+        );
+
         let mut err_nullifier = note.clone();
         err_nullifier.body.input = BLSScalar::random(&mut prng);
         assert!(
@@ -303,6 +320,21 @@ mod smoke_axfr {
                 hashes.clone()
             )
             .is_ok());
+            // the notes, the Merkle roots and the hashes are not truncated to the shortest
+            assert!(batch_verify_abar_to_ar_note(
+                &verify_params,
+                &notes,
+                &merkle_roots[..5],
+                hashes.clone()
+            )
+            .is_err());
+            assert!(batch_verify_abar_to_ar_note(
+                &verify_params,
+                &notes,
+                &merkle_roots,
+                hashes[..5].to_vec()
+            )
+            .is_err());
         }
 
         // check open AR
@@ -354,6 +386,14 @@ mod smoke_axfr {
             verify_abar_to_bar_note(&verify_params, &note, &proof.root, err_hash.clone()).is_err()
         );
 
+        // the note is bound to the identifier of its parameters
+        let mut err_params_id = note.clone();
+        err_params_id.params_id = ParamsId([1u8; 32]);
+        assert!(
+            verify_abar_to_bar_note(&verify_params, &err_params_id, &proof.root, hash.clone())
+                .is_err()
+        );
+
         let mut err_nullifier = note.clone();
         err_nullifier.body.input = BLSScalar::random(&mut prng);
         assert!(
@@ -398,10 +438,29 @@ mod smoke_axfr {
             )
             .is_err());
             notes[5] = &note;
-            assert!(
-                batch_verify_abar_to_bar_note(&verify_params, &notes, &merkle_roots, hashes)
-                    .is_ok()
-            );
+            assert!(batch_verify_abar_to_bar_note(
+                &verify_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone()
+            )
+            .is_ok());
+
+            // the notes, the Merkle roots and the hashes are not truncated to the shortest
+            assert!(batch_verify_abar_to_bar_note(
+                &verify_params,
+                &notes,
+                &merkle_roots[..5],
+                hashes.clone()
+            )
+            .is_err());
+            assert!(batch_verify_abar_to_bar_note(
+                &verify_params,
+                &notes,
+                &merkle_roots,
+                hashes[..5].to_vec()
+            )
+            .is_err());
         }
 
         // check open BAR
@@ -544,10 +603,29 @@ mod smoke_axfr {
             let notes = vec![&note; 6];
             let merkle_roots = vec![&root; 6];
             let hashes = vec![hash.clone(); 6];
-            assert!(
-                batch_verify_anon_xfr_note(&verifiers_params, &notes, &merkle_roots, hashes)
-                    .is_ok()
-            );
+            assert!(batch_verify_anon_xfr_note(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes.clone()
+            )
+            .is_ok());
+
+            // the parameters, the notes, the Merkle roots and the hashes are not truncated
+            assert!(batch_verify_anon_xfr_note(
+                &verifiers_params[..5],
+                &notes,
+                &merkle_roots,
+                hashes.clone()
+            )
+            .is_err());
+            assert!(batch_verify_anon_xfr_note(
+                &verifiers_params,
+                &notes,
+                &merkle_roots,
+                hashes[..5].to_vec()
+            )
+            .is_err());
         }
 
         // check abar
@@ -43,6 +43,10 @@ mod smoke_xfr_identity {
         (bar, owner.unwrap())
     }
 
+    fn attrs(values: &[u64]) -> Vec<Attr> {
+        values.iter().map(|v| Attr::U64(*v)).collect()
+    }
+
     fn check_record_data(
         record_data: &RecordData,
         expected_amount: u64,
@@ -91,8 +95,8 @@ mod smoke_xfr_identity {
         let no_policies = TracingPolicies::new();
 
         // credential for input senders
-        let user1_attr = vec![1u32, 2u32, 3u32, 4u32];
-        let user2_attr = vec![11u32, 22u32, 33u32, 44u32];
+        let user1_attr = attrs(&[1, 2, 3, 4]);
+        let user2_attr = attrs(&[11, 22, 33, 44]);
         let cred_sig_user1 = ac_sign(&mut prng, &cred_issuer_sk, &user1_ac_pk, &user1_attr);
         let cred_sig_user2 = ac_sign(&mut prng, &cred_issuer_sk, &user2_ac_pk, &user2_attr);
         let credential_user1 = Credential {
@@ -220,14 +224,14 @@ mod smoke_xfr_identity {
             &records_data[0],
             amount_in1,
             ASSET1_TYPE,
-            vec![1u32, 2],
+            attrs(&[1, 2]),
             &sender1.pub_key,
         );
         check_record_data(
             &records_data[1],
             amount_in2,
             ASSET1_TYPE,
-            vec![11u32, 22],
+            attrs(&[11, 22]),
             &sender2.pub_key,
         );
     }
@@ -269,8 +273,8 @@ mod smoke_xfr_identity {
         let no_policy = TracingPolicies::new();
 
         // credential for receivers
-        let recv1_attr = vec![1u32, 2u32, 3u32, 4u32];
-        let recv2_attr = vec![11u32, 22u32, 33u32, 44u32];
+        let recv1_attr = attrs(&[1, 2, 3, 4]);
+        let recv2_attr = attrs(&[11, 22, 33, 44]);
         let cred_sig_user1 =
             ac_sign(&mut prng, &cred_issuer_sk, &recv_user1_ac_pk, &recv1_attr).unwrap();
         let cred_sig_user2 =
@@ -407,14 +411,14 @@ mod smoke_xfr_identity {
             &records_data[0],
             amount_out1,
             ASSET1_TYPE,
-            vec![2u32, 3, 4],
+            attrs(&[2, 3, 4]),
             &receiver1.pub_key,
         );
         check_record_data(
             &records_data[1],
             amount_out2,
             ASSET1_TYPE,
-            vec![22u32, 33, 44],
+            attrs(&[22, 33, 44]),
             &receiver2.pub_key,
         );
     }
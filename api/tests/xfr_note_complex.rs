@@ -43,6 +43,10 @@ fn conf_blind_asset_record_from_ledger(
     (bar, owner.unwrap())
 }
 
+fn attrs(values: &[u64]) -> Vec<Attr> {
+    values.iter().map(|v| Attr::U64(*v)).collect()
+}
+
 fn check_record_data(
     record_data: &RecordData,
     expected_amount: u64,
@@ -103,10 +107,10 @@ fn complex_transaction() {
     let (user4_ac_sk, user4_ac_pk) = anon_creds::ac_keygen_user(&mut prng, &cred_issuer_pk);
 
     // generate credential for each of the 4 users
-    let user1_attrs = vec![0u32, 1, 2, 3];
-    let user2_attrs = vec![4u32, 5, 6, 7];
-    let user3_attrs = vec![8u32, 9, 10, 11];
-    let user4_attrs = vec![12u32, 13, 14, 15];
+    let user1_attrs = attrs(&[0, 1, 2, 3]);
+    let user2_attrs = attrs(&[4, 5, 6, 7]);
+    let user3_attrs = attrs(&[8, 9, 10, 11]);
+    let user4_attrs = attrs(&[12, 13, 14, 15]);
     let credential_user1 = Credential {
         sig: ac_sign(
             &mut prng,
@@ -387,7 +391,7 @@ fn complex_transaction() {
         &records_data[0],
         amount_asset2_out3,
         ASSET2_TYPE,
-        vec![8u32, 9, 11], // expect first, second and last attribute of user 3
+        attrs(&[8, 9, 11]), // expect first, second and last attribute of user 3
         &user3_key_pair1.pub_key,
     );
 }
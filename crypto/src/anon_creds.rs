@@ -4,12 +4,14 @@
 use crate::{basic::matrix_sigma::SigmaTranscript, confidential_anon_creds::CACTranscript};
 use merlin::Transcript;
 use serde_derive::{Deserialize, Serialize};
-use zei_algebra::{prelude::*, traits::Pairing};
+use zei_algebra::{bls12_381::BLSG1, prelude::*, traits::Pairing};
 
 pub(crate) const REVEAL_PROOF_DOMAIN: &[u8] = b"AC Reveal PoK";
 pub(crate) const REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE: &[u8] = b"AC Reveal PoK Instance";
 pub(crate) const COMMIT_NEW_TRANSCRIPT_INSTANCE: &[u8] = b"AC Commit SoK Instance";
 pub(crate) const POK_LABEL: &[u8] = b"Signature Message";
+pub(crate) const LINK_PROOF_NEW_TRANSCRIPT_INSTANCE: &[u8] = b"AC Link PoK Instance";
+pub(crate) const KEY_EXTENSION_NEW_TRANSCRIPT_INSTANCE: &[u8] = b"AC Issuer Key Extension Instance";
 
 /// Credential issuer public key (`ipk`).
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -23,6 +25,7 @@ pub struct CredentialIssuerPK<G1, G2> {
     /// The public parameter `x G2`.
     pub zz2: G2,
     /// The public parameter for each attribute, `y[i] G2`.
+    #[serde(deserialize_with = "zei_bounded_serde::attributes")]
     pub yy2: Vec<G2>,
 }
 
@@ -41,6 +44,7 @@ pub struct CredentialIssuerSK<G1, S> {
     /// The secret value `x`.
     pub x: S,
     /// The secret key for individual attributes.
+    #[serde(deserialize_with = "zei_bounded_serde::attributes")]
     pub y: Vec<S>,
 }
 
@@ -68,6 +72,10 @@ pub struct Credential<G1, G2, AttrType> {
     /// The credential signature.
     pub sig: CredentialSig<G1>,
     /// The list of all attributes.
+    #[serde(
+        deserialize_with = "zei_bounded_serde::attributes",
+        bound(deserialize = "AttrType: Deserialize<'de>")
+    )]
     pub attrs: Vec<AttrType>,
     /// The issuer public key.
     pub ipk: CredentialIssuerPK<G1, G2>,
@@ -118,6 +126,31 @@ impl<G1: Group> CredentialComm<G1> {
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CredentialUserPK<G1>(pub(crate) G1);
 
+impl<G1> AsRef<G1> for CredentialUserPK<G1> {
+    fn as_ref(&self) -> &G1 {
+        &self.0
+    }
+}
+
+impl TryFrom<BLSG1> for CredentialUserPK<BLSG1> {
+    type Error = Box<dyn RucError>;
+
+    /// Wrap a BLS12-381 G1 element as a user public key, rejecting the identity.
+    fn try_from(point: BLSG1) -> Result<Self> {
+        if point == BLSG1::get_identity() {
+            Err(eg!(ZeiError::ParameterError))
+        } else {
+            Ok(CredentialUserPK(point))
+        }
+    }
+}
+
+impl From<CredentialUserPK<BLSG1>> for BLSG1 {
+    fn from(key: CredentialUserPK<BLSG1>) -> Self {
+        key.0
+    }
+}
+
 /// User secret key (`usk`).
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CredentialUserSK<S>(pub(crate) S);
@@ -131,6 +164,16 @@ pub struct CredentialSigOpenProof<G1, G2, S> {
     pub proof_open: CredentialPoK<G2, S>,
 }
 
+/// Proof that two credentials, possibly granted by different issuers, are granted to the same
+/// user secret key, together with the selective disclosure of the attributes of each credential.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialLinkProof<G1, G2, S> {
+    /// The opening proof of the first credential.
+    pub first: CredentialSigOpenProof<G1, G2, S>,
+    /// The opening proof of the second credential.
+    pub second: CredentialSigOpenProof<G1, G2, S>,
+}
+
 /// Proof that revealed attributes verify a credential commitment signature.
 pub type CredentialCommOpenProof<G2, S> = CredentialPoK<G2, S>;
 
@@ -206,6 +249,137 @@ pub fn issuer_keygen<R: CryptoRng + RngCore, P: Pairing>(
     )
 }
 
+/// A proof that an extended issuer public key keeps all the elements of the original key, and
+/// that the issuer knows the secret `y[i]` of each new attribute, so that a new `y[i] G2` cannot
+/// be chosen as a combination of the existing ones.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialIssuerKeyExtensionProof<G2, S> {
+    /// The commitment `r[i] G2` for each new attribute.
+    pub commitments: Vec<G2>,
+    /// The response `r[i] + c y[i]` for each new attribute.
+    pub responses: Vec<S>,
+}
+
+/// Extend the issuer keys with `num_new_attrs` new attributes, appended after the existing ones.
+///
+/// The credentials granted under the original keys remain valid under the extended keys, as
+/// credentials whose new attributes are all zero, see `extend_credential`. When such a credential
+/// reveals its zero attributes, verifiers can keep claiming only the original attributes.
+pub fn extend_issuer_key<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    isk: &CredentialIssuerSK<P::G1, P::ScalarField>,
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    num_new_attrs: usize,
+) -> (
+    CredentialIssuerSK<P::G1, P::ScalarField>,
+    CredentialIssuerPK<P::G1, P::G2>,
+    CredentialIssuerKeyExtensionProof<P::G2, P::ScalarField>,
+) {
+    let mut new_isk = isk.clone();
+    let mut new_ipk = ipk.clone();
+    let mut blinds = vec![];
+    let mut commitments = vec![];
+    for _ in 0..num_new_attrs {
+        let yi = P::ScalarField::random(prng);
+        new_ipk.yy2.push(ipk.gen2.mul(&yi));
+        new_isk.y.push(yi);
+
+        let ri = P::ScalarField::random(prng);
+        commitments.push(ipk.gen2.mul(&ri));
+        blinds.push(ri);
+    }
+
+    let challenge = key_extension_challenge::<P>(ipk, &new_ipk, &commitments);
+    let responses = blinds
+        .iter()
+        .zip(new_isk.y[ipk.num_attrs()..].iter())
+        .map(|(ri, yi)| ri.add(&challenge.mul(yi)))
+        .collect_vec();
+
+    (
+        new_isk,
+        new_ipk,
+        CredentialIssuerKeyExtensionProof {
+            commitments,
+            responses,
+        },
+    )
+}
+
+/// Verify that `new_ipk` is a correct extension of `ipk`.
+pub fn verify_issuer_key_extension<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    new_ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    proof: &CredentialIssuerKeyExtensionProof<P::G2, P::ScalarField>,
+) -> Result<()> {
+    if !is_key_prefix(ipk, new_ipk) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let num_new_attrs = new_ipk.num_attrs() - ipk.num_attrs();
+    if proof.commitments.len() != num_new_attrs || proof.responses.len() != num_new_attrs {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let challenge = key_extension_challenge::<P>(ipk, new_ipk, &proof.commitments);
+    for ((yy2i, commitment), response) in new_ipk.yy2[ipk.num_attrs()..]
+        .iter()
+        .zip(proof.commitments.iter())
+        .zip(proof.responses.iter())
+    {
+        if ipk.gen2.mul(response) != commitment.add(&yy2i.mul(&challenge)) {
+            return Err(eg!(ZeiError::ArgumentVerificationError));
+        }
+    }
+    Ok(())
+}
+
+/// Convert a credential granted under `credential.ipk` into a credential under the extended
+/// issuer public key `new_ipk`, where the new attributes are zero.
+pub fn extend_credential<P: Pairing>(
+    credential: &Credential<P::G1, P::G2, P::ScalarField>,
+    new_ipk: &CredentialIssuerPK<P::G1, P::G2>,
+) -> Result<Credential<P::G1, P::G2, P::ScalarField>> {
+    if !is_key_prefix(&credential.ipk, new_ipk) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let mut attrs = credential.attrs.clone();
+    attrs.resize(new_ipk.num_attrs(), P::ScalarField::zero());
+    Ok(Credential {
+        sig: credential.sig.clone(),
+        attrs,
+        ipk: new_ipk.clone(),
+    })
+}
+
+// Check that `new_ipk` has the same elements as `ipk`, followed by zero or more attributes.
+fn is_key_prefix<G1: Group, G2: Group>(
+    ipk: &CredentialIssuerPK<G1, G2>,
+    new_ipk: &CredentialIssuerPK<G1, G2>,
+) -> bool {
+    ipk.gen2 == new_ipk.gen2
+        && ipk.xx2 == new_ipk.xx2
+        && ipk.zz1 == new_ipk.zz1
+        && ipk.zz2 == new_ipk.zz2
+        && new_ipk.yy2.starts_with(&ipk.yy2)
+}
+
+fn key_extension_challenge<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    new_ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    commitments: &[P::G2],
+) -> P::ScalarField {
+    let mut transcript = Transcript::new(KEY_EXTENSION_NEW_TRANSCRIPT_INSTANCE);
+    let mut elems = vec![ipk.gen2.clone(), ipk.xx2.clone(), ipk.zz2.clone()];
+    elems.extend_from_slice(&new_ipk.yy2);
+    transcript.init_sigma::<P::G2>(KEY_EXTENSION_NEW_TRANSCRIPT_INSTANCE, &[], &elems);
+    transcript.append_group_element(b"zz1", &ipk.zz1);
+    transcript.append_message(b"num_attrs", &(ipk.num_attrs() as u64).to_le_bytes());
+    for commitment in commitments.iter() {
+        transcript.append_proof_commitment(commitment);
+    }
+    transcript.get_challenge::<P::ScalarField>()
+}
+
 /// Each user can create a pair of keys `(usk, upk)` under a specific issuer. The user secret key
 /// `usk` is used to claim ownership of an issued credential. The user public key `upk` is used by
 /// the public to verify such a claim.
@@ -218,6 +392,15 @@ pub fn user_keygen<R: CryptoRng + RngCore, P: Pairing>(
     (CredentialUserSK(sk), CredentialUserPK(pk))
 }
 
+/// Derive the user public key `upk` of an existing user secret key `usk` under another issuer,
+/// so that the same user can hold credentials from several issuers.
+pub fn user_pk_from_sk<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    usk: &CredentialUserSK<P::ScalarField>,
+) -> CredentialUserPK<P::G1> {
+    CredentialUserPK(ipk.zz1.mul(&usk.0))
+}
+
 /// The credential issuer can use the issuer secret key `isk` to grant a number of attributes (the
 /// contents of the attributes are described in `\vec{attrs}` to a user, given this user's public
 /// key `upk`.
@@ -351,22 +534,7 @@ pub fn open_comm<R: CryptoRng + RngCore, P: Pairing>(
     rand: &CredentialCommRandomizer<P::ScalarField>,
     reveal_map: &[bool],
 ) -> Result<CredentialCommOpenProof<P::G2, P::ScalarField>> {
-    if credential.attrs.len() != reveal_map.len() {
-        return Err(eg!(ZeiError::ParameterError));
-    }
-
-    let revealed_attrs = credential
-        .attrs
-        .iter()
-        .zip(reveal_map.iter())
-        .map(|(attr, b)| {
-            if *b {
-                Attribute::Revealed(*attr)
-            } else {
-                Attribute::Hidden(Some(*attr))
-            }
-        })
-        .collect_vec();
+    let revealed_attrs = apply_reveal_map::<P>(credential, reveal_map).c(d!())?;
 
     let mut transcript = Transcript::new(REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
     init_pok_transcript::<P>(&mut transcript, &credential.ipk, &cm); // public parameters
@@ -402,6 +570,178 @@ pub fn verify_open<P: Pairing>(
     verify_pok::<P>(ipk, cm, proof_open, attrs, &challenge)
 }
 
+/// A verification session for the proofs over one credential commitment `cm`, e.g., the reveal
+/// proofs of an interactive presentation. The session caches the parts of the verification that
+/// only depend on the issuer public key and the commitment: the initial transcripts and the
+/// pairing of the signature with the issuer key, so each proof costs a single pairing.
+pub struct CredentialVerificationSession<'a, P: Pairing> {
+    ipk: &'a CredentialIssuerPK<P::G1, P::G2>,
+    cm: &'a CredentialComm<P::G1>,
+    reveal_transcript: Transcript,
+    commit_transcript: Transcript,
+    sigma2_pairing: P::Gt,
+}
+
+impl<'a, P: Pairing> CredentialVerificationSession<'a, P> {
+    /// Start a session for the commitment `cm` to a credential issued by `ipk`.
+    pub fn new(ipk: &'a CredentialIssuerPK<P::G1, P::G2>, cm: &'a CredentialComm<P::G1>) -> Self {
+        let mut reveal_transcript = Transcript::new(REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
+        init_pok_transcript::<P>(&mut reveal_transcript, ipk, cm);
+        let mut commit_transcript = Transcript::new(COMMIT_NEW_TRANSCRIPT_INSTANCE);
+        init_pok_transcript::<P>(&mut commit_transcript, ipk, cm);
+
+        Self {
+            ipk,
+            cm,
+            reveal_transcript,
+            commit_transcript,
+            sigma2_pairing: P::pairing(&cm.0.sigma2, &ipk.gen2),
+        }
+    }
+
+    /// Verify a reveal proof of the commitment, as in `verify_open`.
+    pub fn verify_open(
+        &self,
+        proof_open: &CredentialCommOpenProof<P::G2, P::ScalarField>,
+        attrs: &[Attribute<P::ScalarField>],
+    ) -> Result<()> {
+        let mut transcript = self.reveal_transcript.clone();
+        transcript.append_proof_commitment(&proof_open.blinding);
+        let challenge = transcript.get_challenge::<P::ScalarField>();
+
+        let rhs = self.sigma2_pairing.mul(&challenge);
+        verify_pok_with_rhs::<P>(self.ipk, self.cm, proof_open, attrs, &challenge, &rhs)
+    }
+
+    /// Check the validity proof of the commitment over the message `m`, as in `check_comm`.
+    pub fn check_comm(
+        &self,
+        proof_valid: &CredentialPoK<P::G2, P::ScalarField>,
+        m: &[u8],
+    ) -> Result<()> {
+        let mut transcript = self.commit_transcript.clone();
+        transcript.append_message(POK_LABEL, m);
+        transcript.append_proof_commitment(&proof_valid.blinding);
+        let challenge = transcript.get_challenge::<P::ScalarField>();
+
+        let attrs: Vec<Attribute<P::ScalarField>> =
+            vec![Attribute::Hidden(None); self.ipk.num_attrs()];
+        let rhs = self.sigma2_pairing.mul(&challenge);
+        verify_pok_with_rhs::<P>(self.ipk, self.cm, proof_valid, &attrs, &challenge, &rhs)
+    }
+}
+
+/// Selectively reveal the attributes of two credentials granted to the same user secret key `usk`,
+/// and prove that they share this key without revealing it. The two credentials can be granted
+/// by different issuers, and each one has its own reveal map.
+pub fn open_linked_credentials<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    usk: &CredentialUserSK<P::ScalarField>,
+    credential1: &Credential<P::G1, P::G2, P::ScalarField>,
+    reveal_map1: &[bool],
+    credential2: &Credential<P::G1, P::G2, P::ScalarField>,
+    reveal_map2: &[bool],
+) -> Result<CredentialLinkProof<P::G1, P::G2, P::ScalarField>> {
+    let attrs1 = apply_reveal_map::<P>(credential1, reveal_map1).c(d!())?;
+    let attrs2 = apply_reveal_map::<P>(credential2, reveal_map2).c(d!())?;
+
+    let rand1 = randomizer_gen::<_, P>(prng);
+    let rand2 = randomizer_gen::<_, P>(prng);
+    let cm1 = CredentialComm::<P::G1>::new(&credential1.sig, &rand1);
+    let cm2 = CredentialComm::<P::G1>::new(&credential2.sig, &rand2);
+
+    let mut transcript = Transcript::new(LINK_PROOF_NEW_TRANSCRIPT_INSTANCE);
+    init_pok_transcript::<P>(&mut transcript, &credential1.ipk, &cm1);
+    init_pok_transcript::<P>(&mut transcript, &credential2.ipk, &cm2);
+
+    // the same blinding of the user secret key in both proofs
+    let beta2 = P::ScalarField::random(prng);
+    let pok_blinding1 = commit_pok::<_, P>(prng, &credential1.ipk, &beta2, &attrs1).c(d!())?;
+    let pok_blinding2 = commit_pok::<_, P>(prng, &credential2.ipk, &beta2, &attrs2).c(d!())?;
+    transcript.append_proof_commitment(&pok_blinding1.blinding);
+    transcript.append_proof_commitment(&pok_blinding2.blinding);
+    let challenge = transcript.get_challenge::<P::ScalarField>();
+
+    let proof_open1 = respond_pok::<P>(&challenge, usk, &rand1.t, &beta2, &attrs1, pok_blinding1);
+    let proof_open2 = respond_pok::<P>(&challenge, usk, &rand2.t, &beta2, &attrs2, pok_blinding2);
+
+    Ok(CredentialLinkProof {
+        first: CredentialSigOpenProof {
+            cm: cm1,
+            proof_open: proof_open1,
+        },
+        second: CredentialSigOpenProof {
+            cm: cm2,
+            proof_open: proof_open2,
+        },
+    })
+}
+
+/// Verify that the claimed attributes `\vec{attrs1}` and `\vec{attrs2}` are signed by the credential
+/// issuers with public keys `ipk1` and `ipk2` respectively, and that both credentials are granted
+/// to the same user secret key.
+pub fn verify_linked_open<P: Pairing>(
+    ipk1: &CredentialIssuerPK<P::G1, P::G2>,
+    attrs1: &[Attribute<P::ScalarField>],
+    ipk2: &CredentialIssuerPK<P::G1, P::G2>,
+    attrs2: &[Attribute<P::ScalarField>],
+    proof: &CredentialLinkProof<P::G1, P::G2, P::ScalarField>,
+) -> Result<()> {
+    // the responses for the user secret key share the blinding and the challenge,
+    // so they are equal if and only if the secret keys are equal
+    if proof.first.proof_open.response_sk != proof.second.proof_open.response_sk {
+        return Err(eg!(ZeiError::IdentityRevealVerifyError));
+    }
+
+    let mut transcript = Transcript::new(LINK_PROOF_NEW_TRANSCRIPT_INSTANCE);
+    init_pok_transcript::<P>(&mut transcript, ipk1, &proof.first.cm);
+    init_pok_transcript::<P>(&mut transcript, ipk2, &proof.second.cm);
+
+    transcript.append_proof_commitment(&proof.first.proof_open.blinding);
+    transcript.append_proof_commitment(&proof.second.proof_open.blinding);
+    let challenge = transcript.get_challenge::<P::ScalarField>();
+
+    verify_pok::<P>(
+        ipk1,
+        &proof.first.cm,
+        &proof.first.proof_open,
+        attrs1,
+        &challenge,
+    )
+    .c(d!())?;
+    verify_pok::<P>(
+        ipk2,
+        &proof.second.cm,
+        &proof.second.proof_open,
+        attrs2,
+        &challenge,
+    )
+    .c(d!())
+}
+
+// Mark each attribute of the credential as revealed or hidden.
+fn apply_reveal_map<P: Pairing>(
+    credential: &Credential<P::G1, P::G2, P::ScalarField>,
+    reveal_map: &[bool],
+) -> Result<Vec<Attribute<P::ScalarField>>> {
+    if credential.attrs.len() != reveal_map.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    Ok(credential
+        .attrs
+        .iter()
+        .zip(reveal_map.iter())
+        .map(|(attr, b)| {
+            if *b {
+                Attribute::Revealed(*attr)
+            } else {
+                Attribute::Hidden(Some(*attr))
+            }
+        })
+        .collect_vec())
+}
+
 pub(super) fn init_pok_transcript<P: Pairing>(
     transcript: &mut Transcript,
     ipk: &CredentialIssuerPK<P::G1, P::G2>,
@@ -426,10 +766,38 @@ fn prove_pok<R: CryptoRng + RngCore, P: Pairing>(
     t: &P::ScalarField,
     attrs: &[Attribute<P::ScalarField>],
 ) -> Result<CredentialPoK<P::G2, P::ScalarField>> {
-    let beta1 = P::ScalarField::random(prng);
     let beta2 = P::ScalarField::random(prng);
+    let pok_blinding = commit_pok::<_, P>(prng, ipk, &beta2, attrs).c(d!())?;
+    transcript.append_proof_commitment(&pok_blinding.blinding);
+    let challenge = transcript.get_challenge::<P::ScalarField>();
+    Ok(respond_pok::<P>(
+        &challenge,
+        usk,
+        t,
+        &beta2,
+        attrs,
+        pok_blinding,
+    ))
+}
+
+/// The blinding of a proof of knowledge, before the challenge is known.
+struct PoKBlinding<G2, S> {
+    blinding: G2,
+    beta1: S,
+    gamma: Vec<S>,
+}
+
+/// Internal function for the first move of a proof of knowledge, where `beta2` blinds the user
+/// secret key. Using the same `beta2` in several proofs with one challenge links their secret keys.
+fn commit_pok<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    beta2: &P::ScalarField,
+    attrs: &[Attribute<P::ScalarField>],
+) -> Result<PoKBlinding<P::G2, P::ScalarField>> {
+    let beta1 = P::ScalarField::random(prng);
     let mut gamma = vec![];
-    let mut blinding = ipk.gen2.mul(&beta1).add(&ipk.zz2.mul(&beta2));
+    let mut blinding = ipk.gen2.mul(&beta1).add(&ipk.zz2.mul(beta2));
     for (yy2i, attr) in ipk.yy2.iter().zip(attrs) {
         match attr {
             Attribute::Hidden(Some(_)) => {
@@ -444,12 +812,26 @@ fn prove_pok<R: CryptoRng + RngCore, P: Pairing>(
             _ => {}
         }
     }
-    transcript.append_proof_commitment(&blinding);
-    let challenge = transcript.get_challenge::<P::ScalarField>();
-    let response_t = challenge.mul(t).add(&beta1); // challenge*t + beta1
-    let response_sk = challenge.mul(&usk.0).add(&beta2);
+    Ok(PoKBlinding {
+        blinding,
+        beta1,
+        gamma,
+    })
+}
+
+/// Internal function for the responses of a proof of knowledge.
+fn respond_pok<P: Pairing>(
+    challenge: &P::ScalarField,
+    usk: &CredentialUserSK<P::ScalarField>,
+    t: &P::ScalarField,
+    beta2: &P::ScalarField,
+    attrs: &[Attribute<P::ScalarField>],
+    pok_blinding: PoKBlinding<P::G2, P::ScalarField>,
+) -> CredentialPoK<P::G2, P::ScalarField> {
+    let response_t = challenge.mul(t).add(&pok_blinding.beta1); // challenge*t + beta1
+    let response_sk = challenge.mul(&usk.0).add(beta2);
     let mut response_attrs = vec![];
-    let mut gamma_iter = gamma.iter();
+    let mut gamma_iter = pok_blinding.gamma.iter();
     for attr_enum in attrs {
         if let Attribute::Hidden(Some(attr)) = attr_enum {
             let gamma = gamma_iter.next().unwrap(); // safe unwrap()
@@ -457,12 +839,12 @@ fn prove_pok<R: CryptoRng + RngCore, P: Pairing>(
             response_attrs.push(resp_attr_i);
         }
     }
-    Ok(CredentialPoK {
-        blinding,
+    CredentialPoK {
+        blinding: pok_blinding.blinding,
         response_t,
         response_sk,
         response_attrs,
-    })
+    }
 }
 
 /// Internal function for verify a proof of knowledge.
@@ -473,6 +855,46 @@ pub(crate) fn verify_pok<P: Pairing>(
     attrs: &[Attribute<P::ScalarField>],
     challenge: &P::ScalarField,
 ) -> Result<()> {
+    let p = pok_pairing_element::<P>(ipk, proof_open, attrs, challenge).c(d!())?;
+
+    // e(\sigma_1, p) = e(\sigma_2, G2)^c, with a single final exponentiation
+    let pairs = [
+        (cm.0.sigma1, p),
+        (cm.0.sigma2.mul(challenge).neg(), ipk.gen2),
+    ];
+    if P::multi_pairing(&pairs) == P::Gt::get_identity() {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::IdentityRevealVerifyError))
+    }
+}
+
+// Verify a proof of knowledge, given the right-hand side `e(\sigma_2, G2)^c` of the pairing check.
+fn verify_pok_with_rhs<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    cm: &CredentialComm<P::G1>,
+    proof_open: &CredentialPoK<P::G2, P::ScalarField>,
+    attrs: &[Attribute<P::ScalarField>],
+    challenge: &P::ScalarField,
+    rhs: &P::Gt,
+) -> Result<()> {
+    let p = pok_pairing_element::<P>(ipk, proof_open, attrs, challenge).c(d!())?;
+    let lhs = P::pairing(&cm.0.sigma1, &p);
+
+    if lhs == *rhs {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::IdentityRevealVerifyError))
+    }
+}
+
+// Compute the element `p` of `G2` paired with `\sigma_1` in the verification of a proof of knowledge.
+fn pok_pairing_element<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    proof_open: &CredentialPoK<P::G2, P::ScalarField>,
+    attrs: &[Attribute<P::ScalarField>],
+    challenge: &P::ScalarField,
+) -> Result<P::G2> {
     // p = X_2*c - proof_blinding + &G2 * r_t + Z2 * r_sk + \sum r_attr_i * Y2_i;
     let minus_one: P::ScalarField = P::ScalarField::one().neg();
     let mut scalars = vec![
@@ -484,13 +906,19 @@ pub(crate) fn verify_pok<P: Pairing>(
 
     let mut resp_attr_iter = proof_open.response_attrs.iter();
 
-    let attrs_times_challenge = attrs
+    // the attributes after the claimed ones are zero, as for the credentials granted under
+    // the key before its extension
+    if attrs.len() > ipk.num_attrs() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let mut attrs_times_challenge = attrs
         .iter()
         .map(|attr| match attr {
             Attribute::Revealed(attr) => Some(attr.mul(challenge)),
             _ => None,
         })
         .collect_vec();
+    attrs_times_challenge.resize(ipk.num_attrs(), Some(P::ScalarField::zero()));
     for attr in attrs_times_challenge.iter() {
         match attr {
             Some(a) => {
@@ -507,16 +935,7 @@ pub(crate) fn verify_pok<P: Pairing>(
     for y in ipk.yy2.iter() {
         elems.push(y);
     }
-    let p = P::G2::multi_exp(scalars.as_slice(), elems.as_slice());
-
-    let lhs = P::pairing(&cm.0.sigma1, &p);
-    let rhs = P::pairing(&cm.0.sigma2.mul(challenge), &ipk.gen2);
-
-    if lhs == rhs {
-        Ok(())
-    } else {
-        Err(eg!(ZeiError::IdentityRevealVerifyError))
-    }
+    Ok(P::G2::multi_exp(scalars.as_slice(), elems.as_slice()))
 }
 
 #[cfg(test)]
@@ -525,6 +944,7 @@ pub(crate) mod credentials_tests {
     use crate::anon_creds::Attribute::{Hidden, Revealed};
     use ark_std::test_rng;
     use zei_algebra::bls12_381::BLSPairingEngine;
+    use zei_algebra::bn254::BN254PairingEngine;
 
     fn check_signatures<P: Pairing>(n: usize) {
         let mut prng = test_rng();
@@ -561,6 +981,8 @@ pub(crate) mod credentials_tests {
         for n in 0..16 {
             check_signatures::<BLSPairingEngine>(n);
         }
+        // BN254, whose pairings can be checked by the Ethereum precompile
+        check_signatures::<BN254PairingEngine>(3);
     }
 
     fn reveal(reveal_map: &[bool]) {
@@ -641,4 +1063,215 @@ pub(crate) mod credentials_tests {
         two_attributes();
         ten_attributes();
     }
+
+    fn grant<R: CryptoRng + RngCore, P: Pairing>(
+        prng: &mut R,
+        isk: &CredentialIssuerSK<P::G1, P::ScalarField>,
+        ipk: &CredentialIssuerPK<P::G1, P::G2>,
+        upk: &CredentialUserPK<P::G1>,
+        n: usize,
+    ) -> Credential<P::G1, P::G2, P::ScalarField> {
+        let attrs = (0..n).map(|_| P::ScalarField::random(prng)).collect_vec();
+        let sig = grant_credential::<_, P>(prng, isk, upk, &attrs).unwrap();
+        Credential {
+            sig,
+            attrs,
+            ipk: ipk.clone(),
+        }
+    }
+
+    #[test]
+    fn test_issuer_key_extension() {
+        type P = BLSPairingEngine;
+        let mut prng = test_rng();
+
+        let (isk, ipk) = issuer_keygen::<_, P>(&mut prng, 2);
+        let (usk, upk) = user_keygen::<_, P>(&mut prng, &ipk);
+        let attrs = vec![
+            <P as Pairing>::ScalarField::from(1u32),
+            <P as Pairing>::ScalarField::from(2u32),
+        ];
+        let sig = grant_credential::<_, P>(&mut prng, &isk, &upk, &attrs).unwrap();
+        let credential = Credential {
+            sig,
+            attrs: attrs.clone(),
+            ipk: ipk.clone(),
+        };
+
+        let (new_isk, new_ipk, proof) = extend_issuer_key::<_, P>(&mut prng, &isk, &ipk, 1);
+        assert_eq!(new_ipk.num_attrs(), 3);
+        assert!(verify_issuer_key_extension::<P>(&ipk, &new_ipk, &proof).is_ok());
+
+        // the new element must be proven
+        let mut bad_ipk = new_ipk.clone();
+        bad_ipk.yy2[2] = ipk.yy2[0].add(&ipk.yy2[1]);
+        assert!(verify_issuer_key_extension::<P>(&ipk, &bad_ipk, &proof).is_err());
+        // the original elements must be kept
+        let mut bad_ipk = new_ipk.clone();
+        bad_ipk.yy2[0] = ipk.yy2[1].clone();
+        assert!(verify_issuer_key_extension::<P>(&ipk, &bad_ipk, &proof).is_err());
+
+        // an existing credential remains valid under the extended key, and revealing its zero
+        // attribute lets verifiers claim only the original attributes
+        let old_credential = extend_credential::<P>(&credential, &new_ipk).unwrap();
+        let reveal_map = [true, false, true];
+        let reveal_sig =
+            open_credential::<_, P>(&mut prng, &usk, &old_credential, &reveal_map).unwrap();
+        let zero = <P as Pairing>::ScalarField::zero();
+        for claimed in [
+            vec![Revealed(attrs[0]), Hidden(None)],
+            vec![Revealed(attrs[0]), Hidden(None), Revealed(zero)],
+        ] {
+            assert!(
+                verify_open::<P>(&new_ipk, &reveal_sig.cm, &reveal_sig.proof_open, &claimed)
+                    .is_ok()
+            );
+        }
+
+        // a new credential can use the new attribute
+        let mut new_attrs = attrs.clone();
+        new_attrs.push(<P as Pairing>::ScalarField::from(3u32));
+        let sig = grant_credential::<_, P>(&mut prng, &new_isk, &upk, &new_attrs).unwrap();
+        let new_credential = Credential {
+            sig,
+            attrs: new_attrs.clone(),
+            ipk: new_ipk.clone(),
+        };
+        let reveal_map = [false, false, true];
+        let reveal_sig =
+            open_credential::<_, P>(&mut prng, &usk, &new_credential, &reveal_map).unwrap();
+        let claimed = [Hidden(None), Hidden(None), Revealed(new_attrs[2])];
+        assert!(
+            verify_open::<P>(&new_ipk, &reveal_sig.cm, &reveal_sig.proof_open, &claimed).is_ok()
+        );
+        // the new attribute is not zero, so it cannot be omitted
+        let claimed = [Hidden(None), Hidden(None)];
+        assert!(
+            verify_open::<P>(&new_ipk, &reveal_sig.cm, &reveal_sig.proof_open, &claimed).is_err()
+        );
+
+        // a credential cannot be moved to an unrelated key
+        let (_, other_ipk) = issuer_keygen::<_, P>(&mut prng, 3);
+        assert!(extend_credential::<P>(&credential, &other_ipk).is_err());
+    }
+
+    #[test]
+    fn test_verification_session() {
+        type P = BLSPairingEngine;
+        let mut prng = test_rng();
+
+        let (isk, ipk) = issuer_keygen::<_, P>(&mut prng, 3);
+        let (usk, upk) = user_keygen::<_, P>(&mut prng, &ipk);
+        let credential = grant::<_, P>(&mut prng, &isk, &ipk, &upk, 3);
+
+        let msg = b"Some message";
+        let (cm, proof_valid, rand) =
+            commit_without_randomizer::<_, P>(&mut prng, &usk, &credential, msg).unwrap();
+        let rand = rand.unwrap();
+
+        let session = CredentialVerificationSession::<P>::new(&ipk, &cm);
+        assert!(session.check_comm(&proof_valid, msg).is_ok());
+        assert!(session
+            .check_comm(&proof_valid, b"Another message")
+            .is_err());
+
+        // several rounds of reveal proofs over the same commitment
+        for reveal_map in [
+            [true, false, false],
+            [false, true, true],
+            [true, true, true],
+        ] {
+            let proof_open =
+                open_comm::<_, P>(&mut prng, &usk, &credential, &cm, &rand, &reveal_map).unwrap();
+            let mut attrs = credential
+                .attrs
+                .iter()
+                .zip(reveal_map.iter())
+                .map(|(a, b)| if *b { Revealed(*a) } else { Hidden(None) })
+                .collect_vec();
+            assert!(session.verify_open(&proof_open, &attrs).is_ok());
+            assert!(verify_open::<P>(&ipk, &cm, &proof_open, &attrs).is_ok());
+
+            attrs[0] = Revealed(<P as Pairing>::ScalarField::random(&mut prng));
+            assert!(session.verify_open(&proof_open, &attrs).is_err());
+        }
+
+        // the session is bound to the commitment
+        let (other_cm, _, _) =
+            commit_without_randomizer::<_, P>(&mut prng, &usk, &credential, msg).unwrap();
+        let other_session = CredentialVerificationSession::<P>::new(&ipk, &other_cm);
+        assert!(other_session.check_comm(&proof_valid, msg).is_err());
+    }
+
+    #[test]
+    fn test_linked_credentials() {
+        type P = BLSPairingEngine;
+        let mut prng = test_rng();
+
+        let (isk1, ipk1) = issuer_keygen::<_, P>(&mut prng, 3);
+        let (isk2, ipk2) = issuer_keygen::<_, P>(&mut prng, 2);
+        let (usk, upk1) = user_keygen::<_, P>(&mut prng, &ipk1);
+        let upk2 = user_pk_from_sk::<P>(&ipk2, &usk);
+
+        let credential1 = grant::<_, P>(&mut prng, &isk1, &ipk1, &upk1, 3);
+        let credential2 = grant::<_, P>(&mut prng, &isk2, &ipk2, &upk2, 2);
+
+        let reveal_map1 = [true, false, false];
+        let reveal_map2 = [false, true];
+        let to_attrs = |credential: &Credential<_, _, _>, reveal_map: &[bool]| {
+            credential
+                .attrs
+                .iter()
+                .zip(reveal_map.iter())
+                .map(|(a, b)| if *b { Revealed(*a) } else { Hidden(None) })
+                .collect_vec()
+        };
+        let attrs1 = to_attrs(&credential1, &reveal_map1);
+        let attrs2 = to_attrs(&credential2, &reveal_map2);
+
+        let proof = open_linked_credentials::<_, P>(
+            &mut prng,
+            &usk,
+            &credential1,
+            &reveal_map1,
+            &credential2,
+            &reveal_map2,
+        )
+        .unwrap();
+        assert!(verify_linked_open::<P>(&ipk1, &attrs1, &ipk2, &attrs2, &proof).is_ok());
+
+        // wrong revealed attributes
+        let wrong_attrs2 = vec![Hidden(None), Revealed(credential2.attrs[0])];
+        assert!(verify_linked_open::<P>(&ipk1, &attrs1, &ipk2, &wrong_attrs2, &proof).is_err());
+
+        // swapped issuers
+        assert!(verify_linked_open::<P>(&ipk2, &attrs2, &ipk1, &attrs1, &proof).is_err());
+
+        // a credential of another user cannot be linked
+        let (other_usk, other_upk) = user_keygen::<_, P>(&mut prng, &ipk2);
+        let other_credential = grant::<_, P>(&mut prng, &isk2, &ipk2, &other_upk, 2);
+        let proof = open_linked_credentials::<_, P>(
+            &mut prng,
+            &usk,
+            &credential1,
+            &reveal_map1,
+            &other_credential,
+            &reveal_map2,
+        )
+        .unwrap();
+        let other_attrs = to_attrs(&other_credential, &reveal_map2);
+        assert!(verify_linked_open::<P>(&ipk1, &attrs1, &ipk2, &other_attrs, &proof).is_err());
+        let other_proof = open_linked_credentials::<_, P>(
+            &mut prng,
+            &other_usk,
+            &credential1,
+            &reveal_map1,
+            &other_credential,
+            &reveal_map2,
+        )
+        .unwrap();
+        assert!(
+            verify_linked_open::<P>(&ipk1, &attrs1, &ipk2, &other_attrs, &other_proof).is_err()
+        );
+    }
 }
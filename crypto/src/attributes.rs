@@ -0,0 +1,283 @@
+//! Typed attributes of anonymous credentials, with canonical encodings into scalars.
+//!
+//! An attribute is encoded as its tag, the length of its payload, and the payload, split
+//! into chunks of at most 30 bytes, each placed in the 31 low bytes of a scalar, which are
+//! below the field size of every scalar field. The first scalar holds the tag, the length,
+//! and the first 29 bytes; the following ones hold a continuation tag and the next 30 bytes.
+//! A list of attributes thus encodes injectively into a list of scalars, and a tracer that
+//! recovers the scalars, e.g., from the lock of a tracer memo, decodes the original attributes.
+
+use zei_algebra::prelude::*;
+
+/// The number of low bytes of a scalar used by the encoding.
+const SCALAR_PAYLOAD_BYTES: usize = 31;
+/// The number of payload bytes in the first scalar of an attribute.
+const FIRST_CHUNK_LEN: usize = SCALAR_PAYLOAD_BYTES - 2;
+/// The number of payload bytes in each of the following scalars.
+const NEXT_CHUNK_LEN: usize = SCALAR_PAYLOAD_BYTES - 1;
+/// The maximal length of the payload of an attribute, e.g., of a string in bytes.
+pub const MAX_ATTRIBUTE_LEN: usize = u8::MAX as usize;
+
+const UTF8_TAG: u8 = 1;
+const DATE_TAG: u8 = 2;
+const U64_TAG: u8 = 3;
+const BYTES32_TAG: u8 = 4;
+const CONTINUATION_TAG: u8 = 0xff;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// A typed attribute of a credential.
+pub enum Attribute {
+    /// A string, of at most `MAX_ATTRIBUTE_LEN` bytes.
+    Utf8(String),
+    /// A date in the Gregorian calendar.
+    Date {
+        /// The year.
+        year: u16,
+        /// The month, from 1 to 12.
+        month: u8,
+        /// The day of the month, from 1.
+        day: u8,
+    },
+    /// An integer.
+    U64(u64),
+    /// A 32-byte value, e.g., a hash or an identifier.
+    Bytes32([u8; 32]),
+}
+
+/// Return the number of days of a month.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl Attribute {
+    /// Return the tag and the payload of the attribute, checking that it is well-formed.
+    fn tag_and_payload(&self) -> Result<(u8, Vec<u8>)> {
+        match self {
+            Attribute::Utf8(s) => {
+                if s.len() > MAX_ATTRIBUTE_LEN {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                Ok((UTF8_TAG, s.as_bytes().to_vec()))
+            }
+            Attribute::Date { year, month, day } => {
+                if *month == 0 || *month > 12 || *day == 0 || *day > days_in_month(*year, *month) {
+                    return Err(eg!(ZeiError::ParameterError));
+                }
+                let mut payload = year.to_le_bytes().to_vec();
+                payload.push(*month);
+                payload.push(*day);
+                Ok((DATE_TAG, payload))
+            }
+            Attribute::U64(v) => Ok((U64_TAG, v.to_le_bytes().to_vec())),
+            Attribute::Bytes32(bytes) => Ok((BYTES32_TAG, bytes.to_vec())),
+        }
+    }
+
+    /// Parse an attribute from its tag and payload, rejecting the malformed ones.
+    fn from_tag_and_payload(tag: u8, payload: &[u8]) -> Result<Self> {
+        let attr = match tag {
+            UTF8_TAG => Attribute::Utf8(
+                String::from_utf8(payload.to_vec()).c(d!(ZeiError::DeserializationError))?,
+            ),
+            DATE_TAG if payload.len() == 4 => Attribute::Date {
+                year: u16::from_le_bytes([payload[0], payload[1]]),
+                month: payload[2],
+                day: payload[3],
+            },
+            U64_TAG if payload.len() == 8 => {
+                let mut bytes = [0u8; 8];
+                bytes.copy_from_slice(payload);
+                Attribute::U64(u64::from_le_bytes(bytes))
+            }
+            BYTES32_TAG if payload.len() == 32 => {
+                let mut bytes = [0u8; 32];
+                bytes.copy_from_slice(payload);
+                Attribute::Bytes32(bytes)
+            }
+            _ => return Err(eg!(ZeiError::DeserializationError)),
+        };
+        // a date must also be valid.
+        attr.tag_and_payload()
+            .c(d!(ZeiError::DeserializationError))?;
+        Ok(attr)
+    }
+
+    /// Return the number of scalars of the encoding of a payload of `len` bytes.
+    fn num_scalars_of_len(len: usize) -> usize {
+        1 + (len.saturating_sub(FIRST_CHUNK_LEN) + NEXT_CHUNK_LEN - 1) / NEXT_CHUNK_LEN
+    }
+
+    /// Return the number of scalars that encode the attribute.
+    pub fn num_scalars(&self) -> Result<usize> {
+        let (_, payload) = self.tag_and_payload().c(d!())?;
+        Ok(Self::num_scalars_of_len(payload.len()))
+    }
+
+    /// Encode the attribute into scalars.
+    pub fn to_scalars<S: Scalar>(&self) -> Result<Vec<S>> {
+        let (tag, payload) = self.tag_and_payload().c(d!())?;
+        let first_len = std::cmp::min(payload.len(), FIRST_CHUNK_LEN);
+
+        let mut first = vec![tag, payload.len() as u8];
+        first.extend_from_slice(&payload[..first_len]);
+        let mut scalars = vec![S::from_le_bytes_mod_order(&first)];
+        for chunk in payload[first_len..].chunks(NEXT_CHUNK_LEN) {
+            let mut bytes = vec![CONTINUATION_TAG];
+            bytes.extend_from_slice(chunk);
+            scalars.push(S::from_le_bytes_mod_order(&bytes));
+        }
+        Ok(scalars)
+    }
+
+    /// Decode an attribute from the first scalars of a list, and return it with the number
+    /// of scalars that it takes. Only the canonical encodings are accepted.
+    pub fn from_scalars<S: Scalar>(scalars: &[S]) -> Result<(Self, usize)> {
+        let first = scalars
+            .first()
+            .c(d!(ZeiError::DeserializationError))
+            .and_then(|s| scalar_payload_bytes(s).c(d!()))?;
+        let (tag, len) = (first[0], first[1] as usize);
+        let n = Self::num_scalars_of_len(len);
+        if tag == CONTINUATION_TAG || scalars.len() < n {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+
+        let first_len = std::cmp::min(len, FIRST_CHUNK_LEN);
+        let mut payload = first[2..2 + first_len].to_vec();
+        if first[2 + first_len..].iter().any(|b| *b != 0) {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        for scalar in scalars[1..n].iter() {
+            let bytes = scalar_payload_bytes(scalar).c(d!())?;
+            let chunk_len = std::cmp::min(len - payload.len(), NEXT_CHUNK_LEN);
+            if bytes[0] != CONTINUATION_TAG || bytes[1 + chunk_len..].iter().any(|b| *b != 0) {
+                return Err(eg!(ZeiError::DeserializationError));
+            }
+            payload.extend_from_slice(&bytes[1..1 + chunk_len]);
+        }
+        Ok((Self::from_tag_and_payload(tag, &payload).c(d!())?, n))
+    }
+}
+
+/// Return the low bytes of a scalar, checking that the high bytes are zero.
+fn scalar_payload_bytes<S: Scalar>(scalar: &S) -> Result<Vec<u8>> {
+    let mut bytes = scalar.to_bytes();
+    if bytes.len() < SCALAR_PAYLOAD_BYTES || bytes[SCALAR_PAYLOAD_BYTES..].iter().any(|b| *b != 0) {
+        return Err(eg!(ZeiError::DeserializationError));
+    }
+    bytes.truncate(SCALAR_PAYLOAD_BYTES);
+    Ok(bytes)
+}
+
+/// Encode a list of attributes into scalars, e.g., the attributes of a credential.
+pub fn encode_attributes<S: Scalar>(attrs: &[Attribute]) -> Result<Vec<S>> {
+    let mut scalars = vec![];
+    for attr in attrs {
+        scalars.extend(attr.to_scalars::<S>().c(d!())?);
+    }
+    Ok(scalars)
+}
+
+/// Decode a list of attributes from their scalars.
+pub fn decode_attributes<S: Scalar>(scalars: &[S]) -> Result<Vec<Attribute>> {
+    let mut attrs = vec![];
+    let mut rest = scalars;
+    while !rest.is_empty() {
+        let (attr, n) = Attribute::from_scalars(rest).c(d!())?;
+        attrs.push(attr);
+        rest = &rest[n..];
+    }
+    Ok(attrs)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_attributes, encode_attributes, Attribute, MAX_ATTRIBUTE_LEN};
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::RistrettoScalar};
+
+    fn attributes() -> Vec<Attribute> {
+        vec![
+            Attribute::Utf8("".to_string()),
+            Attribute::Utf8("Alice".to_string()),
+            Attribute::Utf8("a string that needs several scalars: é, 中文".repeat(3)),
+            Attribute::Utf8("x".repeat(MAX_ATTRIBUTE_LEN)),
+            Attribute::Date {
+                year: 2000,
+                month: 2,
+                day: 29,
+            },
+            Attribute::U64(0),
+            Attribute::U64(u64::MAX),
+            Attribute::Bytes32([0xff; 32]),
+            Attribute::Bytes32([0; 32]),
+        ]
+    }
+
+    fn round_trip<S: Scalar>() {
+        let attrs = attributes();
+        for attr in attrs.iter() {
+            let scalars = attr.to_scalars::<S>().unwrap();
+            assert_eq!(scalars.len(), attr.num_scalars().unwrap());
+            assert_eq!(
+                Attribute::from_scalars(&scalars).unwrap(),
+                (attr.clone(), scalars.len())
+            );
+        }
+        let scalars = encode_attributes::<S>(&attrs).unwrap();
+        assert_eq!(decode_attributes(&scalars).unwrap(), attrs);
+
+        // the encodings of different attributes are different.
+        let encodings = attrs
+            .iter()
+            .map(|attr| attr.to_scalars::<S>().unwrap())
+            .collect_vec();
+        for (i, a) in encodings.iter().enumerate() {
+            assert!(encodings[i + 1..].iter().all(|b| a != b));
+        }
+        assert_ne!(
+            Attribute::U64(0).to_scalars::<S>().unwrap(),
+            Attribute::Utf8("\0\0\0\0\0\0\0\0".to_string())
+                .to_scalars::<S>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn round_trip_bls12_381() {
+        round_trip::<BLSScalar>();
+    }
+
+    #[test]
+    fn round_trip_ristretto() {
+        round_trip::<RistrettoScalar>();
+    }
+
+    #[test]
+    fn malformed_attributes() {
+        assert!(Attribute::Utf8("x".repeat(MAX_ATTRIBUTE_LEN + 1))
+            .to_scalars::<BLSScalar>()
+            .is_err());
+        for (year, month, day) in [(2001, 2, 29), (1900, 2, 29), (2000, 13, 1), (2000, 4, 31)] {
+            assert!(Attribute::Date { year, month, day }
+                .to_scalars::<BLSScalar>()
+                .is_err());
+        }
+
+        // only canonical encodings are decoded.
+        let scalars = Attribute::Bytes32([1; 32])
+            .to_scalars::<BLSScalar>()
+            .unwrap();
+        assert!(Attribute::from_scalars(&scalars[..1]).is_err());
+        assert!(Attribute::from_scalars(&scalars[1..]).is_err());
+        assert!(decode_attributes(&[scalars[0].add(&BLSScalar::from(2u32))]).is_err());
+        let trailing = scalars[1].add(&BLSScalar::from_le_bytes_mod_order(&[0, 0, 0, 0, 0, 1]));
+        assert!(decode_attributes(&[scalars[0], trailing]).is_err());
+        assert!(decode_attributes(&[BLSScalar::one().neg()]).is_err());
+        assert!(decode_attributes(&[BLSScalar::zero()]).is_err());
+    }
+}
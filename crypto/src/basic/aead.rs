@@ -0,0 +1,178 @@
+//! Authenticated encryption with associated data for the symmetric layer of hybrid encryption.
+//!
+//! The ciphertexts of hybrid encryption over an AEAD carry the identifier of their algorithm
+//! in their header, and they are decrypted with the algorithm that they name, so that the
+//! stored memos remain decryptable after a deployment migrates to another algorithm.
+//!
+//! The symmetric keys are derived afresh for each message by a KEM, so that each key only
+//! encrypts one message, and the nonce is fixed to zero.
+
+use aes_gcm_siv::{
+    aead::{Aead as AeadCipher, KeyInit, Payload},
+    Aes256GcmSiv,
+};
+use chacha20poly1305::ChaCha20Poly1305;
+use zei_algebra::prelude::*;
+
+/// The length of the keys of the AEAD algorithms.
+pub const AEAD_KEY_LENGTH: usize = 32;
+/// The length of the nonces of the AEAD algorithms.
+const AEAD_NONCE_LENGTH: usize = 12;
+
+/// The identifiers of the AEAD algorithms, as they appear in the ciphertext headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AeadAlgorithm {
+    /// AES-256-GCM-SIV, which is resistant to the misuse of nonces.
+    Aes256GcmSiv,
+    /// ChaCha20-Poly1305, which is fast without hardware support for AES.
+    ChaCha20Poly1305,
+}
+
+impl AeadAlgorithm {
+    /// Return the identifier of the algorithm in the ciphertext headers.
+    pub fn id(&self) -> u8 {
+        match self {
+            AeadAlgorithm::Aes256GcmSiv => 1,
+            AeadAlgorithm::ChaCha20Poly1305 => 2,
+        }
+    }
+
+    /// Return the algorithm of an identifier, or an error if the identifier is unknown.
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            1 => Ok(AeadAlgorithm::Aes256GcmSiv),
+            2 => Ok(AeadAlgorithm::ChaCha20Poly1305),
+            _ => Err(eg!(ZeiError::DeserializationError)),
+        }
+    }
+
+    /// Decrypt a ciphertext with the algorithm, or return an error if it is not authentic.
+    pub fn decrypt(
+        &self,
+        key: &[u8; AEAD_KEY_LENGTH],
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        match self {
+            AeadAlgorithm::Aes256GcmSiv => Aes256GcmSivAead::decrypt(key, aad, ciphertext),
+            AeadAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305Aead::decrypt(key, aad, ciphertext),
+        }
+    }
+}
+
+/// An AEAD algorithm for the symmetric layer of hybrid encryption.
+pub trait Aead {
+    /// The identifier of the algorithm.
+    const ALGORITHM: AeadAlgorithm;
+
+    /// Encrypt a plaintext under a key that encrypts no other message, and authenticate it
+    /// along with the associated data.
+    fn encrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt a ciphertext, or return an error if it or the associated data is not authentic.
+    fn decrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// AES-256-GCM-SIV.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Aes256GcmSivAead;
+
+impl Aead for Aes256GcmSivAead {
+    const ALGORITHM: AeadAlgorithm = AeadAlgorithm::Aes256GcmSiv;
+
+    fn encrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        Aes256GcmSiv::new(key.into())
+            .encrypt(
+                &[0u8; AEAD_NONCE_LENGTH].into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| eg!(ZeiError::EncryptionError))
+    }
+
+    fn decrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        Aes256GcmSiv::new(key.into())
+            .decrypt(
+                &[0u8; AEAD_NONCE_LENGTH].into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| eg!(ZeiError::DecryptionError))
+    }
+}
+
+/// ChaCha20-Poly1305.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChaCha20Poly1305Aead;
+
+impl Aead for ChaCha20Poly1305Aead {
+    const ALGORITHM: AeadAlgorithm = AeadAlgorithm::ChaCha20Poly1305;
+
+    fn encrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        ChaCha20Poly1305::new(key.into())
+            .encrypt(
+                &[0u8; AEAD_NONCE_LENGTH].into(),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .map_err(|_| eg!(ZeiError::EncryptionError))
+    }
+
+    fn decrypt(key: &[u8; AEAD_KEY_LENGTH], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        ChaCha20Poly1305::new(key.into())
+            .decrypt(
+                &[0u8; AEAD_NONCE_LENGTH].into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| eg!(ZeiError::DecryptionError))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Aead, AeadAlgorithm, Aes256GcmSivAead, ChaCha20Poly1305Aead};
+
+    fn check_aead<A: Aead>() {
+        let key = [7u8; 32];
+        let msg = b"this is a message";
+        let ciphertext = A::encrypt(&key, b"header", msg).unwrap();
+        assert_ne!(&ciphertext[..msg.len()], msg);
+        assert_eq!(A::decrypt(&key, b"header", &ciphertext).unwrap(), msg);
+        assert_eq!(
+            A::ALGORITHM.decrypt(&key, b"header", &ciphertext).unwrap(),
+            msg
+        );
+        assert_eq!(
+            AeadAlgorithm::from_id(A::ALGORITHM.id()).unwrap(),
+            A::ALGORITHM
+        );
+
+        // the key, the associated data and the ciphertext are authenticated
+        assert!(A::decrypt(&[8u8; 32], b"header", &ciphertext).is_err());
+        assert!(A::decrypt(&key, b"other header", &ciphertext).is_err());
+        let mut modified = ciphertext;
+        modified[0] ^= 1;
+        assert!(A::decrypt(&key, b"header", &modified).is_err());
+    }
+
+    #[test]
+    fn aead() {
+        check_aead::<Aes256GcmSivAead>();
+        check_aead::<ChaCha20Poly1305Aead>();
+
+        // the algorithms are not interchangeable
+        let key = [7u8; 32];
+        let ciphertext = Aes256GcmSivAead::encrypt(&key, b"", b"message").unwrap();
+        assert!(ChaCha20Poly1305Aead::decrypt(&key, b"", &ciphertext).is_err());
+        assert!(AeadAlgorithm::from_id(0).is_err());
+    }
+}
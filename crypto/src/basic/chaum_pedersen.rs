@@ -18,6 +18,13 @@ pub struct ChaumPedersenProof {
     pub(crate) z3: RistrettoScalar,
 }
 
+impl ChaumPedersenProof {
+    /// Check if the scalars of the proof are canonical.
+    pub fn is_canonical(&self) -> bool {
+        self.z1.is_canonical() && self.z2.is_canonical() && self.z3.is_canonical()
+    }
+}
+
 fn init_chaum_pedersen(
     transcript: &mut Transcript,
     c1: &RistrettoPoint,
@@ -131,6 +138,13 @@ pub struct ChaumPedersenProofX {
     pub(crate) zero: Option<ChaumPedersenProof>,
 }
 
+impl ChaumPedersenProofX {
+    /// Check if the scalars of the proofs are canonical.
+    pub fn is_canonical(&self) -> bool {
+        self.c1_eq_c2.is_canonical() && self.zero.iter().all(|proof| proof.is_canonical())
+    }
+}
+
 fn init_chaum_pedersen_multiple(transcript: &mut Transcript, commitments: &[RistrettoPoint]) {
     let pc_gens = PedersenCommitmentRistretto::default();
 
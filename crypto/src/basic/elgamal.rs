@@ -1,7 +1,11 @@
+use crate::basic::fixed_base::FixedBasePrecomp;
 use zei_algebra::ristretto::RistrettoPoint;
 use zei_algebra::{
+    bls12_381::BLSG1,
     hash::{Hash, Hasher},
+    jubjub::JubjubPoint,
     prelude::*,
+    secp256k1::SECP256K1G1,
 };
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -27,6 +31,41 @@ impl Hash for ElGamalEncKey<RistrettoPoint> {
     }
 }
 
+impl<G> AsRef<G> for ElGamalEncKey<G> {
+    fn as_ref(&self) -> &G {
+        &self.0
+    }
+}
+
+macro_rules! impl_elgamal_enc_key_conversions {
+    ($g: ty) => {
+        impl TryFrom<$g> for ElGamalEncKey<$g> {
+            type Error = Box<dyn RucError>;
+
+            /// Wrap a group element as an encryption key, rejecting the identity,
+            /// under which every ciphertext would reveal `m * G`.
+            fn try_from(point: $g) -> Result<Self> {
+                if point == <$g>::get_identity() {
+                    Err(eg!(ZeiError::ParameterError))
+                } else {
+                    Ok(ElGamalEncKey(point))
+                }
+            }
+        }
+
+        impl From<ElGamalEncKey<$g>> for $g {
+            fn from(key: ElGamalEncKey<$g>) -> Self {
+                key.0
+            }
+        }
+    };
+}
+
+impl_elgamal_enc_key_conversions!(RistrettoPoint);
+impl_elgamal_enc_key_conversions!(BLSG1);
+impl_elgamal_enc_key_conversions!(SECP256K1G1);
+impl_elgamal_enc_key_conversions!(JubjubPoint);
+
 impl ZeiFromToBytes for ElGamalCiphertext<RistrettoPoint> {
     fn zei_to_bytes(&self) -> Vec<u8> {
         let mut v = vec![];
@@ -35,6 +74,9 @@ impl ZeiFromToBytes for ElGamalCiphertext<RistrettoPoint> {
         v
     }
     fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != 2 * RistrettoPoint::COMPRESSED_LEN {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
         let e1 = RistrettoPoint::from_compressed_bytes(&bytes[0..RistrettoPoint::COMPRESSED_LEN])
             .c(d!(ZeiError::DeserializationError))?;
         let e2 = RistrettoPoint::from_compressed_bytes(&bytes[RistrettoPoint::COMPRESSED_LEN..])
@@ -53,6 +95,19 @@ pub fn elgamal_key_gen<R: CryptoRng + RngCore, G: Group>(
     (secret_key, public_key)
 }
 
+/// Same as [`elgamal_key_gen`], with the precomputed table of the group base.
+pub fn elgamal_key_gen_precomp<R: CryptoRng + RngCore, G: Group>(
+    prng: &mut R,
+    base: &FixedBasePrecomp<G>,
+) -> Result<(ElGamalDecKey<G::ScalarType>, ElGamalEncKey<G>)> {
+    if *base.base() != G::get_base() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let secret_key = ElGamalDecKey(G::ScalarType::random(prng));
+    let public_key = ElGamalEncKey(base.mul(&secret_key.0));
+    Ok((secret_key, public_key))
+}
+
 /// Return an ElGamal ciphertext pair as `(r * G, m * G + r * pk)`, where `G` is a base point on the curve
 pub fn elgamal_encrypt<G: Group>(
     m: &G::ScalarType,
@@ -66,6 +121,41 @@ pub fn elgamal_encrypt<G: Group>(
     ElGamalCiphertext::<G> { e1, e2 }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// The precomputed tables of the group base and an encryption key,
+/// for encrypting many messages under the same key.
+pub struct ElGamalEncKeyPrecomp<G> {
+    base: FixedBasePrecomp<G>,
+    pub_key: FixedBasePrecomp<G>,
+}
+
+impl<G: Group> ElGamalEncKeyPrecomp<G> {
+    /// Precompute the tables of the group base and the encryption key.
+    pub fn new(pub_key: &ElGamalEncKey<G>) -> Self {
+        ElGamalEncKeyPrecomp {
+            base: FixedBasePrecomp::new(&G::get_base()),
+            pub_key: FixedBasePrecomp::new(&pub_key.0),
+        }
+    }
+
+    /// Return the encryption key.
+    pub fn pub_key(&self) -> ElGamalEncKey<G> {
+        ElGamalEncKey(*self.pub_key.base())
+    }
+}
+
+/// Same as [`elgamal_encrypt`], with the precomputed tables of the base and the encryption key.
+pub fn elgamal_encrypt_precomp<G: Group>(
+    m: &G::ScalarType,
+    r: &G::ScalarType,
+    precomp: &ElGamalEncKeyPrecomp<G>,
+) -> ElGamalCiphertext<G> {
+    let e1 = precomp.base.mul(r);
+    let e2 = precomp.base.mul(m).add(&precomp.pub_key.mul(r));
+
+    ElGamalCiphertext::<G> { e1, e2 }
+}
+
 /// Verify that the ElGamal ciphertext encrypts m by checking `ctext.e2 - ctext.e1 * sk = m * G`
 pub fn elgamal_verify<G: Group>(
     m: &G::ScalarType,
@@ -90,10 +180,13 @@ pub fn elgamal_partial_decrypt<G: Group>(
 
 #[cfg(test)]
 mod elgamal_test {
+    use crate::basic::fixed_base::FixedBasePrecomp;
     use ark_std::test_rng;
     use zei_algebra::bls12_381::{BLSGt, BLSG1, BLSG2};
+    use zei_algebra::jubjub::JubjubPoint;
     use zei_algebra::prelude::*;
     use zei_algebra::ristretto::RistrettoPoint;
+    use zei_algebra::secp256k1::SECP256K1G1;
 
     fn verification<G: Group>() {
         let mut prng = test_rng();
@@ -127,12 +220,54 @@ mod elgamal_test {
         pnk!(super::elgamal_verify(&m, &ctext, &secret_key));
     }
 
+    fn precomputed_encryption<G: Group>() {
+        let mut prng = test_rng();
+        let (_, public_key) = super::elgamal_key_gen::<_, G>(&mut prng);
+        let precomp = super::ElGamalEncKeyPrecomp::new(&public_key);
+        assert_eq!(precomp.pub_key(), public_key);
+
+        let m = G::ScalarType::from(u64::MAX);
+        let r = G::ScalarType::random(&mut prng);
+        assert_eq!(
+            super::elgamal_encrypt_precomp(&m, &r, &precomp),
+            super::elgamal_encrypt(&m, &r, &public_key)
+        );
+
+        let base = FixedBasePrecomp::new(&G::get_base());
+        let (secret_key, public_key) = super::elgamal_key_gen_precomp(&mut prng, &base).unwrap();
+        assert_eq!(public_key.0, G::get_base().mul(&secret_key.0));
+        let ctext = super::elgamal_encrypt(&m, &r, &public_key);
+        pnk!(super::elgamal_verify(&m, &ctext, &secret_key));
+
+        // a table of another element is rejected
+        let other = FixedBasePrecomp::new(&public_key.0);
+        assert!(super::elgamal_key_gen_precomp(&mut prng, &other).is_err());
+    }
+
+    fn serialization<G: Group>() {
+        let mut prng = test_rng();
+        let (_, public_key) = super::elgamal_key_gen::<_, G>(&mut prng);
+        let m = G::ScalarType::from(100u32);
+        let r = G::ScalarType::random(&mut prng);
+        let ctext = super::elgamal_encrypt(&m, &r, &public_key);
+
+        let json = serde_json::to_string(&ctext).unwrap();
+        let ctext_json: super::ElGamalCiphertext<G> = serde_json::from_str(&json).unwrap();
+        assert_eq!(ctext_json, ctext);
+
+        let bytes = rmp_serde::to_vec(&ctext).unwrap();
+        let ctext_bytes: super::ElGamalCiphertext<G> = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(ctext_bytes, ctext);
+    }
+
     #[test]
     fn verify() {
         verification::<RistrettoPoint>();
         verification::<BLSG1>();
         verification::<BLSG2>();
         verification::<BLSGt>();
+        verification::<SECP256K1G1>();
+        verification::<JubjubPoint>();
     }
 
     #[test]
@@ -141,5 +276,35 @@ mod elgamal_test {
         decryption::<BLSG1>();
         decryption::<BLSG2>();
         decryption::<BLSGt>();
+        decryption::<SECP256K1G1>();
+    }
+
+    #[test]
+    fn encrypt_precomp() {
+        precomputed_encryption::<RistrettoPoint>();
+        precomputed_encryption::<BLSG1>();
+        precomputed_encryption::<SECP256K1G1>();
+    }
+
+    #[test]
+    fn serde() {
+        serialization::<RistrettoPoint>();
+        serialization::<BLSG1>();
+        serialization::<BLSGt>();
+        serialization::<SECP256K1G1>();
+
+        let ctext = super::ElGamalCiphertext {
+            e1: RistrettoPoint::get_base(),
+            e2: RistrettoPoint::get_base(),
+        };
+        let bytes = ctext.zei_to_bytes();
+        assert_eq!(
+            super::ElGamalCiphertext::<RistrettoPoint>::zei_from_bytes(&bytes).unwrap(),
+            ctext
+        );
+        assert!(super::ElGamalCiphertext::<RistrettoPoint>::zei_from_bytes(
+            &bytes[..bytes.len() - 1]
+        )
+        .is_err());
     }
 }
@@ -0,0 +1,133 @@
+use zei_algebra::{prelude::*, traits::scalar_to_radix_2_power_w};
+
+/// The width, in bits, of the windows of the tables.
+const WINDOW_BITS: usize = 4;
+/// The number of multiples of the base stored for each window, i.e., the largest signed digit.
+const WINDOW_SIZE: usize = 1 << (WINDOW_BITS - 1);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A precomputed table for multiplying a fixed group element by many scalars.
+///
+/// The scalar is written in signed radix 16, and for each digit position `i`, the table holds
+/// the encodings of `j * 16^i * base` for `j` in `-8..=8`, so that a multiplication costs one
+/// addition per digit and no doubling. Building the table costs about as much as five
+/// multiplications, so it pays off for bases that are reused, e.g., the group base or a
+/// tracer's public key.
+///
+/// The multiplication does not branch on the digits and does not index the table by them:
+/// it reads every entry of every row and keeps the selected one with a mask, and it adds an
+/// entry, maybe the identity, for every row. The selected entry is decoded with
+/// [`Group::from_unchecked_bytes`], which is cheap for the affine encodings of the arkworks
+/// groups, but costs a square root per row for Ristretto, whose own multiplication is already
+/// constant-time.
+pub struct FixedBasePrecomp<G> {
+    base: G,
+    table: Vec<Vec<Vec<u8>>>,
+}
+
+impl<G: Group> FixedBasePrecomp<G> {
+    /// Precompute the table of `base`.
+    pub fn new(base: &G) -> Self {
+        // The signed digits have one more position than the bits of the scalar.
+        let num_windows = G::ScalarType::bytes_len() * 8 / WINDOW_BITS + 1;
+
+        let mut table = Vec::with_capacity(num_windows);
+        let mut window_base = *base;
+        for _ in 0..num_windows {
+            let mut multiples = vec![window_base; WINDOW_SIZE];
+            for j in 1..WINDOW_SIZE {
+                multiples[j] = multiples[j - 1].add(&window_base);
+            }
+            // 16 * window_base = 2 * (8 * window_base)
+            window_base = multiples[WINDOW_SIZE - 1].double();
+
+            // the entry `WINDOW_SIZE + j` holds `j * window_base`
+            let row = multiples
+                .iter()
+                .rev()
+                .map(|p| G::get_identity().sub(p))
+                .chain([G::get_identity()])
+                .chain(multiples.iter().copied())
+                .map(|p| p.to_unchecked_bytes())
+                .collect();
+            table.push(row);
+        }
+
+        FixedBasePrecomp { base: *base, table }
+    }
+
+    /// Return the group element of the table.
+    pub fn base(&self) -> &G {
+        &self.base
+    }
+
+    /// Compute `scalar * base`.
+    pub fn mul(&self, scalar: &G::ScalarType) -> G {
+        let digits = scalar_to_radix_2_power_w(scalar, WINDOW_BITS);
+        let mut res = G::get_identity();
+        for (i, row) in self.table.iter().enumerate() {
+            // the recoding drops the leading zero digits
+            let digit = digits.get(i).copied().unwrap_or(0);
+            let index = (WINDOW_SIZE as i64 + digit as i64) as usize;
+            let entry = G::from_unchecked_bytes(&select(row, index)).unwrap(); // safe unwrap
+            res.add_assign(&entry);
+        }
+        res
+    }
+}
+
+/// Return the entry of `row` at `index`, reading all the entries.
+fn select(row: &[Vec<u8>], index: usize) -> Vec<u8> {
+    let mut res = vec![0u8; row[0].len()];
+    for (j, entry) in row.iter().enumerate() {
+        // 0xff if `j == index`, and 0 otherwise
+        let diff = (j ^ index) as u64;
+        let mask = (((diff | diff.wrapping_neg()) >> 63) as u8).wrapping_sub(1);
+        for (r, e) in res.iter_mut().zip(entry.iter()) {
+            *r |= e & mask;
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FixedBasePrecomp;
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::BLSG1, prelude::*, ristretto::RistrettoPoint, secp256k1::SECP256K1G1,
+    };
+
+    fn check_mul<G: Group>() {
+        let mut prng = test_rng();
+        let base = G::random(&mut prng);
+        let precomp = FixedBasePrecomp::new(&base);
+
+        let zero = G::ScalarType::zero();
+        assert_eq!(precomp.mul(&zero), G::get_identity());
+        let minus_one = G::ScalarType::zero().sub(&G::ScalarType::one());
+        assert_eq!(precomp.mul(&minus_one), base.mul(&minus_one));
+        // a scalar whose leading digits are zero
+        let small = G::ScalarType::from(7u32);
+        assert_eq!(precomp.mul(&small), base.mul(&small));
+        for _ in 0..10 {
+            let s = G::ScalarType::random(&mut prng);
+            assert_eq!(precomp.mul(&s), base.mul(&s));
+        }
+    }
+
+    #[test]
+    fn mul_ristretto() {
+        check_mul::<RistrettoPoint>();
+    }
+
+    #[test]
+    fn mul_bls12_381() {
+        check_mul::<BLSG1>();
+    }
+
+    #[test]
+    fn mul_secp256k1() {
+        check_mul::<SECP256K1G1>();
+    }
+}
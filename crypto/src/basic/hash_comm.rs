@@ -0,0 +1,197 @@
+use serde::Serializer;
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+/// The length of a hash commitment.
+pub const HASH_COMMITMENT_LENGTH: usize = 32;
+/// The length of the blinding factor of a hash commitment.
+pub const HASH_COMMITMENT_BLIND_LENGTH: usize = 32;
+
+/// The prefix of every hash commitment.
+const HASH_COMMITMENT_PREFIX: &[u8] = b"Zei Hash Commitment";
+
+/// A hash-based commitment `SHA-256(prefix || len(domain) || domain || blind || message)`.
+///
+/// Unlike Pedersen commitments, it is not homomorphic, but it only relies on the hash function and
+/// costs no group operations. It fits commitments to metadata or policy hashes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HashCommitment(pub [u8; HASH_COMMITMENT_LENGTH]);
+
+/// The blinding factor of a hash commitment, which is revealed with the message to open it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashCommitmentBlind(pub [u8; HASH_COMMITMENT_BLIND_LENGTH]);
+
+impl ZeiFromToBytes for HashCommitment {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != HASH_COMMITMENT_LENGTH {
+            Err(eg!(ZeiError::DeserializationError))
+        } else {
+            let mut array = [0u8; HASH_COMMITMENT_LENGTH];
+            array.copy_from_slice(bytes);
+            Ok(HashCommitment(array))
+        }
+    }
+}
+
+serialize_deserialize!(HashCommitment);
+
+impl ZeiFromToBytes for HashCommitmentBlind {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != HASH_COMMITMENT_BLIND_LENGTH {
+            Err(eg!(ZeiError::DeserializationError))
+        } else {
+            let mut array = [0u8; HASH_COMMITMENT_BLIND_LENGTH];
+            array.copy_from_slice(bytes);
+            Ok(HashCommitmentBlind(array))
+        }
+    }
+}
+
+serialize_deserialize!(HashCommitmentBlind);
+
+impl HashCommitmentBlind {
+    /// Sample a random blinding factor.
+    pub fn random<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        let mut bytes = [0u8; HASH_COMMITMENT_BLIND_LENGTH];
+        prng.fill_bytes(&mut bytes);
+        HashCommitmentBlind(bytes)
+    }
+}
+
+impl HashCommitment {
+    /// Commit to a message under a domain separator, with a fresh blinding factor.
+    pub fn commit<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        domain: &[u8],
+        message: &[u8],
+    ) -> (Self, HashCommitmentBlind) {
+        let blind = HashCommitmentBlind::random(prng);
+        (Self::commit_with_blind(domain, message, &blind), blind)
+    }
+
+    /// Commit to a message under a domain separator, with a given blinding factor.
+    pub fn commit_with_blind(domain: &[u8], message: &[u8], blind: &HashCommitmentBlind) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(HASH_COMMITMENT_PREFIX);
+        hasher.update(&(domain.len() as u64).to_le_bytes());
+        hasher.update(domain);
+        hasher.update(&blind.0);
+        hasher.update(message);
+
+        let mut bytes = [0u8; HASH_COMMITMENT_LENGTH];
+        bytes.copy_from_slice(&hasher.finalize());
+        HashCommitment(bytes)
+    }
+
+    /// Verify the opening of the commitment to a message.
+    pub fn verify(&self, domain: &[u8], message: &[u8], blind: &HashCommitmentBlind) -> Result<()> {
+        if Self::commit_with_blind(domain, message, blind) == *self {
+            Ok(())
+        } else {
+            Err(eg!(ZeiError::CommitmentVerificationError))
+        }
+    }
+
+    /// Commit to a value by its byte representation.
+    pub fn commit_value<R: CryptoRng + RngCore, T: ZeiFromToBytes>(
+        prng: &mut R,
+        domain: &[u8],
+        value: &T,
+    ) -> (Self, HashCommitmentBlind) {
+        Self::commit(prng, domain, &value.zei_to_bytes())
+    }
+
+    /// Verify the opening of the commitment to a value.
+    pub fn verify_value<T: ZeiFromToBytes>(
+        &self,
+        domain: &[u8],
+        value: &T,
+        blind: &HashCommitmentBlind,
+    ) -> Result<()> {
+        self.verify(domain, &value.zei_to_bytes(), blind).c(d!())
+    }
+
+    /// Open the commitment to a value from its byte representation,
+    /// and return the value if the opening is correct.
+    pub fn open_value<T: ZeiFromToBytes>(
+        &self,
+        domain: &[u8],
+        bytes: &[u8],
+        blind: &HashCommitmentBlind,
+    ) -> Result<T> {
+        self.verify(domain, bytes, blind).c(d!())?;
+        T::zei_from_bytes(bytes).c(d!())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HashCommitment, HashCommitmentBlind};
+    use ark_std::test_rng;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn hash_commitment() {
+        let mut prng = test_rng();
+        let (comm, blind) = HashCommitment::commit(&mut prng, b"policy", b"the policy hash");
+        assert!(comm.verify(b"policy", b"the policy hash", &blind).is_ok());
+
+        msg_eq!(
+            ZeiError::CommitmentVerificationError,
+            comm.verify(b"policy", b"another policy hash", &blind)
+                .unwrap_err()
+        );
+        assert!(comm
+            .verify(b"metadata", b"the policy hash", &blind)
+            .is_err());
+        assert!(comm
+            .verify(
+                b"policy",
+                b"the policy hash",
+                &HashCommitmentBlind::random(&mut prng)
+            )
+            .is_err());
+
+        // the domain is length-prefixed, so moving bytes between the domain and the message fails
+        let blind = HashCommitmentBlind::random(&mut prng);
+        let comm = HashCommitment::commit_with_blind(b"ab", b"c", &blind);
+        assert_ne!(comm, HashCommitment::commit_with_blind(b"a", b"bc", &blind));
+
+        // hiding: the same message commits to different values
+        let (comm1, _) = HashCommitment::commit(&mut prng, b"policy", b"message");
+        let (comm2, _) = HashCommitment::commit(&mut prng, b"policy", b"message");
+        assert_ne!(comm1, comm2);
+    }
+
+    #[test]
+    fn typed_hash_commitment() {
+        let mut prng = test_rng();
+        let value = RistrettoScalar::random(&mut prng);
+        let (comm, blind) = HashCommitment::commit_value(&mut prng, b"scalar", &value);
+        assert!(comm.verify_value(b"scalar", &value, &blind).is_ok());
+        assert!(comm
+            .verify_value(b"scalar", &RistrettoScalar::one(), &blind)
+            .is_err());
+
+        let opened: RistrettoScalar = comm
+            .open_value(b"scalar", &value.zei_to_bytes(), &blind)
+            .unwrap();
+        assert_eq!(opened, value);
+
+        let bytes = comm.zei_to_bytes();
+        assert_eq!(HashCommitment::zei_from_bytes(&bytes).unwrap(), comm);
+        let json = serde_json::to_string(&blind).unwrap();
+        assert_eq!(
+            serde_json::from_str::<HashCommitmentBlind>(&json).unwrap(),
+            blind
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::basic::aead::{Aead, AeadAlgorithm};
+use crate::basic::kem::{Kem, X25519Kem, KEM_SHARED_KEY_LENGTH};
 use aes::{
     cipher::{generic_array::GenericArray, KeyIvInit, StreamCipher},
     Aes256,
@@ -13,6 +15,13 @@ use zei_algebra::ristretto::RistrettoScalar;
 
 type Aes256Ctr = ctr::Ctr64BE<Aes256>;
 
+const COMMITTING_ENCRYPTION_KEY_DOMAIN: &[u8] = b"Zei Committing Hybrid Encryption Key";
+const COMMITTING_TAG_DOMAIN: &[u8] = b"Zei Committing Hybrid Encryption Tag";
+const AEAD_KEY_COMMITMENT_DOMAIN: &[u8] = b"Zei AEAD Hybrid Encryption Key Commitment";
+
+/// The length of the tag of a key-committing ciphertext.
+pub const COMMITTING_TAG_LENGTH: usize = 32;
+
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 /// The public key for the hybrid encryption scheme.
@@ -110,6 +119,11 @@ impl ZeiFromToBytes for Ctext {
     }
 
     fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        DeserializationLimits::check(
+            "memo bytes",
+            deserialization_limits().max_memo_bytes,
+            bytes.len(),
+        )?;
         Ok(Ctext(bytes.to_vec()))
     }
 }
@@ -144,17 +158,231 @@ impl ZeiFromToBytes for ZeiHybridCiphertext {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+/// A key-committing ciphertext of hybrid encryption.
+///
+/// The tag is a hash of the symmetric key and of the ciphertext, so the ciphertext cannot be
+/// decrypted under another key than the one it was encrypted with, and it cannot be modified.
+/// This matters when scanning, which decrypts the ciphertexts with many keys.
+pub struct ZeiCommittingCiphertext {
+    pub(crate) ciphertext: Ctext,
+    pub(crate) ephemeral_public_key: XPublicKey,
+    pub(crate) tag: [u8; COMMITTING_TAG_LENGTH],
+}
+
+impl ZeiFromToBytes for ZeiCommittingCiphertext {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        bytes.append(&mut self.ephemeral_public_key.zei_to_bytes());
+        bytes.extend_from_slice(&self.tag);
+        bytes.append(&mut self.ciphertext.zei_to_bytes());
+        bytes
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < 32 + COMMITTING_TAG_LENGTH {
+            Err(eg!(ZeiError::DeserializationError))
+        } else {
+            let ephemeral_public_key = XPublicKey::zei_from_bytes(&bytes[0..32])?;
+            let mut tag = [0u8; COMMITTING_TAG_LENGTH];
+            tag.copy_from_slice(&bytes[32..32 + COMMITTING_TAG_LENGTH]);
+            let ciphertext = Ctext::zei_from_bytes(&bytes[32 + COMMITTING_TAG_LENGTH..])?;
+            Ok(Self {
+                ciphertext,
+                ephemeral_public_key,
+                tag,
+            })
+        }
+    }
+}
+
+/// A key-committing ciphertext of hybrid encryption over a KEM, e.g., `P256Kem`, which is
+/// authenticated as a `ZeiCommittingCiphertext`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct KemCiphertext<K: Kem> {
+    pub(crate) ciphertext: Ctext,
+    pub(crate) encapsulation: K::Encapsulation,
+    pub(crate) tag: [u8; COMMITTING_TAG_LENGTH],
+}
+
+impl<K: Kem> ZeiFromToBytes for KemCiphertext<K> {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = K::encapsulation_to_bytes(&self.encapsulation);
+        bytes.extend_from_slice(&self.tag);
+        bytes.append(&mut self.ciphertext.zei_to_bytes());
+        bytes
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let n = K::ENCAPSULATION_LENGTH;
+        if bytes.len() < n + COMMITTING_TAG_LENGTH {
+            Err(eg!(ZeiError::DeserializationError))
+        } else {
+            let encapsulation = K::encapsulation_from_bytes(&bytes[0..n]).c(d!())?;
+            let mut tag = [0u8; COMMITTING_TAG_LENGTH];
+            tag.copy_from_slice(&bytes[n..n + COMMITTING_TAG_LENGTH]);
+            let ciphertext = Ctext::zei_from_bytes(&bytes[n + COMMITTING_TAG_LENGTH..])?;
+            Ok(Self {
+                ciphertext,
+                encapsulation,
+                tag,
+            })
+        }
+    }
+}
+
+/// Encrypt a message with a key-committing scheme over a KEM
+pub fn hybrid_encrypt_committing<K: Kem, R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pub_key: &K::PublicKey,
+    message: &[u8],
+) -> KemCiphertext<K> {
+    let (key, encapsulation) = K::encapsulate(prng, pub_key);
+    let context = K::encapsulation_to_bytes(&encapsulation);
+    let (ciphertext, tag) = committing_symmetric_encrypt(&key, &context, message);
+    KemCiphertext {
+        ciphertext,
+        encapsulation,
+        tag,
+    }
+}
+
+/// Decrypt a key-committing hybrid ciphertext over a KEM, or return an error if the
+/// ciphertext is not encrypted under the key
+pub fn hybrid_decrypt_committing<K: Kem>(
+    ctext: &KemCiphertext<K>,
+    sec_key: &K::SecretKey,
+) -> Result<Vec<u8>> {
+    let key = K::decapsulate(sec_key, &ctext.encapsulation);
+    let context = K::encapsulation_to_bytes(&ctext.encapsulation);
+    committing_symmetric_decrypt(&key, &context, &ctext.ciphertext, &ctext.tag).c(d!())
+}
+
+/// A key-committing ciphertext of hybrid encryption over a KEM and an AEAD, whose header names
+/// the AEAD algorithm, so that it is decrypted with the algorithm it was encrypted with.
+///
+/// The header, i.e., the identifier of the algorithm and the encapsulation, is authenticated
+/// as the associated data of the AEAD. The AEADs are not key-committing, so the ciphertext
+/// also carries a commitment to the symmetric key and the header, which is checked before the
+/// decryption: a ciphertext cannot be decrypted under another key than the one it was encrypted
+/// with, which matters when scanning, which decrypts the ciphertexts with many keys.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct AeadHybridCiphertext<K: Kem> {
+    pub(crate) algorithm: AeadAlgorithm,
+    pub(crate) encapsulation: K::Encapsulation,
+    pub(crate) key_commitment: [u8; COMMITTING_TAG_LENGTH],
+    pub(crate) ciphertext: Ctext,
+}
+
+impl<K: Kem> AeadHybridCiphertext<K> {
+    /// Return the AEAD algorithm of the ciphertext.
+    pub fn algorithm(&self) -> AeadAlgorithm {
+        self.algorithm
+    }
+
+    fn header(algorithm: AeadAlgorithm, encapsulation: &K::Encapsulation) -> Vec<u8> {
+        let mut header = vec![algorithm.id()];
+        header.append(&mut K::encapsulation_to_bytes(encapsulation));
+        header
+    }
+}
+
+impl<K: Kem> ZeiFromToBytes for AeadHybridCiphertext<K> {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Self::header(self.algorithm, &self.encapsulation);
+        bytes.extend_from_slice(&self.key_commitment);
+        bytes.append(&mut self.ciphertext.zei_to_bytes());
+        bytes
+    }
+
+    fn zei_from_bytes(bytes: &[u8]) -> Result<Self> {
+        let n = 1 + K::ENCAPSULATION_LENGTH;
+        if bytes.len() < n + COMMITTING_TAG_LENGTH {
+            Err(eg!(ZeiError::DeserializationError))
+        } else {
+            let algorithm = AeadAlgorithm::from_id(bytes[0]).c(d!())?;
+            let encapsulation = K::encapsulation_from_bytes(&bytes[1..n]).c(d!())?;
+            let mut key_commitment = [0u8; COMMITTING_TAG_LENGTH];
+            key_commitment.copy_from_slice(&bytes[n..n + COMMITTING_TAG_LENGTH]);
+            let ciphertext = Ctext::zei_from_bytes(&bytes[n + COMMITTING_TAG_LENGTH..])?;
+            Ok(Self {
+                algorithm,
+                encapsulation,
+                key_commitment,
+                ciphertext,
+            })
+        }
+    }
+}
+
+/// Encrypt a message over a KEM with an AEAD algorithm and a key-committing scheme
+pub fn hybrid_encrypt_aead<K: Kem, A: Aead, R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pub_key: &K::PublicKey,
+    message: &[u8],
+) -> Result<AeadHybridCiphertext<K>> {
+    let (key, encapsulation) = K::encapsulate(prng, pub_key);
+    let header = AeadHybridCiphertext::<K>::header(A::ALGORITHM, &encapsulation);
+    let ciphertext = A::encrypt(&committing_encryption_key(&key), &header, message).c(d!())?;
+    Ok(AeadHybridCiphertext {
+        algorithm: A::ALGORITHM,
+        encapsulation,
+        key_commitment: aead_key_commitment(&key, &header),
+        ciphertext: Ctext(ciphertext),
+    })
+}
+
+/// Encrypt a message over Ed25519 with an AEAD algorithm, i.e., over X25519 with the public key
+/// converted from Ed25519
+pub fn hybrid_encrypt_aead_ed25519<A: Aead, R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pub_key: &PublicKey,
+    message: &[u8],
+) -> Result<AeadHybridCiphertext<X25519Kem>> {
+    hybrid_encrypt_aead::<X25519Kem, A, R>(prng, &ed25519_to_x25519_public_key(pub_key), message)
+}
+
+/// Decrypt a hybrid ciphertext over a KEM with the AEAD algorithm named in its header, or
+/// return an error if the ciphertext is not encrypted under the key or was modified
+pub fn hybrid_decrypt_aead<K: Kem>(
+    ctext: &AeadHybridCiphertext<K>,
+    sec_key: &K::SecretKey,
+) -> Result<Vec<u8>> {
+    let key = K::decapsulate(sec_key, &ctext.encapsulation);
+    let header = AeadHybridCiphertext::<K>::header(ctext.algorithm, &ctext.encapsulation);
+    check_tag(&aead_key_commitment(&key, &header), &ctext.key_commitment).c(d!())?;
+    ctext
+        .algorithm
+        .decrypt(
+            &committing_encryption_key(&key),
+            &header,
+            &ctext.ciphertext.0,
+        )
+        .c(d!())
+}
+
+/// Check that a hybrid ciphertext over a KEM and an AEAD is encrypted under the key, without
+/// decrypting it
+pub fn verify_aead_ciphertext<K: Kem>(
+    ctext: &AeadHybridCiphertext<K>,
+    sec_key: &K::SecretKey,
+) -> Result<()> {
+    let key = K::decapsulate(sec_key, &ctext.encapsulation);
+    let header = AeadHybridCiphertext::<K>::header(ctext.algorithm, &ctext.encapsulation);
+    check_tag(&aead_key_commitment(&key, &header), &ctext.key_commitment).c(d!())
+}
+
 /// Encrypt a message over X25519
 pub fn hybrid_encrypt_x25519<R: CryptoRng + RngCore>(
     prng: &mut R,
     pub_key: &XPublicKey,
     message: &[u8],
 ) -> ZeiHybridCiphertext {
-    let (key, ephemeral_key) = symmetric_key_from_x25519_public_key(prng, &pub_key.key);
+    let (key, ephemeral_public_key) = X25519Kem::encapsulate(prng, pub_key);
     let ciphertext = symmetric_encrypt(&key, message);
     ZeiHybridCiphertext {
         ciphertext,
-        ephemeral_public_key: XPublicKey { key: ephemeral_key },
+        ephemeral_public_key,
     }
 }
 
@@ -164,12 +392,13 @@ pub fn hybrid_encrypt_ed25519<R: CryptoRng + RngCore>(
     pub_key: &PublicKey,
     message: &[u8],
 ) -> ZeiHybridCiphertext {
-    let (key, ephemeral_key) = symmetric_key_from_ed25519_public_key(prng, pub_key);
+    let (key, ephemeral_public_key) =
+        X25519Kem::encapsulate(prng, &ed25519_to_x25519_public_key(pub_key));
     let ciphertext = symmetric_encrypt(&key, message);
 
     ZeiHybridCiphertext {
         ciphertext,
-        ephemeral_public_key: XPublicKey { key: ephemeral_key },
+        ephemeral_public_key,
     }
 }
 
@@ -178,7 +407,7 @@ pub fn hybrid_decrypt_with_x25519_secret_key(
     ctext: &ZeiHybridCiphertext,
     sec_key: &XSecretKey,
 ) -> Vec<u8> {
-    let key = symmetric_key_from_x25519_secret_key(&sec_key.key, &ctext.ephemeral_public_key.key);
+    let key = X25519Kem::decapsulate(sec_key, &ctext.ephemeral_public_key);
     symmetric_decrypt(&key, &ctext.ciphertext)
 }
 
@@ -187,48 +416,212 @@ pub fn hybrid_decrypt_with_ed25519_secret_key(
     ctext: &ZeiHybridCiphertext,
     sec_key: &SecretKey,
 ) -> Vec<u8> {
-    let key = symmetric_key_from_ed25519_secret_key(sec_key, &ctext.ephemeral_public_key.key);
+    let key = X25519Kem::decapsulate(
+        &ed25519_to_x25519_secret_key(sec_key),
+        &ctext.ephemeral_public_key,
+    );
     symmetric_decrypt(&key, &ctext.ciphertext)
 }
 
-/// Convert the shared secret to a symmetric key
-fn shared_secret_to_symmetric_key(shared_secret: &x25519_dalek::SharedSecret) -> [u8; 32] {
-    let mut hasher = sha2::Sha256::new();
-    hasher.update(shared_secret.as_bytes());
-    let hash = hasher.finalize();
-    let mut symmetric_key = [0u8; 32];
-    symmetric_key.copy_from_slice(hash.as_slice());
-    symmetric_key
+/// Encrypt a message with a key-committing scheme over X25519
+pub fn hybrid_encrypt_committing_x25519<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pub_key: &XPublicKey,
+    message: &[u8],
+) -> ZeiCommittingCiphertext {
+    let (key, ephemeral_public_key) = X25519Kem::encapsulate(prng, pub_key);
+    let (ciphertext, tag) =
+        committing_symmetric_encrypt(&key, ephemeral_public_key.key.as_bytes(), message);
+    ZeiCommittingCiphertext {
+        ciphertext,
+        ephemeral_public_key,
+        tag,
+    }
 }
 
-/// Derive a symmetric key from an X25519 public key
-fn symmetric_key_from_x25519_public_key<R: CryptoRng + RngCore>(
+/// Encrypt a message with a key-committing scheme over Ed25519
+pub fn hybrid_encrypt_committing_ed25519<R: CryptoRng + RngCore>(
     prng: &mut R,
-    public_key: &x25519_dalek::PublicKey,
-) -> ([u8; 32], x25519_dalek::PublicKey) {
-    // simulate a DH key exchange
-    let ephemeral = x25519_dalek::EphemeralSecret::new(prng);
-    let dh_pk = x25519_dalek::PublicKey::from(&ephemeral);
+    pub_key: &PublicKey,
+    message: &[u8],
+) -> ZeiCommittingCiphertext {
+    let (key, ephemeral_public_key) =
+        X25519Kem::encapsulate(prng, &ed25519_to_x25519_public_key(pub_key));
+    let (ciphertext, tag) =
+        committing_symmetric_encrypt(&key, ephemeral_public_key.key.as_bytes(), message);
+    ZeiCommittingCiphertext {
+        ciphertext,
+        ephemeral_public_key,
+        tag,
+    }
+}
+
+/// Decrypt a key-committing hybrid ciphertext over X25519, or return an error if the
+/// ciphertext is not encrypted under the key
+pub fn hybrid_decrypt_committing_with_x25519_secret_key(
+    ctext: &ZeiCommittingCiphertext,
+    sec_key: &XSecretKey,
+) -> Result<Vec<u8>> {
+    let key = X25519Kem::decapsulate(sec_key, &ctext.ephemeral_public_key);
+    committing_symmetric_decrypt(
+        &key,
+        ctext.ephemeral_public_key.key.as_bytes(),
+        &ctext.ciphertext,
+        &ctext.tag,
+    )
+    .c(d!())
+}
 
-    let shared = ephemeral.diffie_hellman(public_key);
+/// Decrypt a key-committing hybrid ciphertext over Ed25519, or return an error if the
+/// ciphertext is not encrypted under the key
+pub fn hybrid_decrypt_committing_with_ed25519_secret_key(
+    ctext: &ZeiCommittingCiphertext,
+    sec_key: &SecretKey,
+) -> Result<Vec<u8>> {
+    let key = X25519Kem::decapsulate(
+        &ed25519_to_x25519_secret_key(sec_key),
+        &ctext.ephemeral_public_key,
+    );
+    committing_symmetric_decrypt(
+        &key,
+        ctext.ephemeral_public_key.key.as_bytes(),
+        &ctext.ciphertext,
+        &ctext.tag,
+    )
+    .c(d!())
+}
 
-    let symmetric_key = shared_secret_to_symmetric_key(&shared);
-    (symmetric_key, dh_pk)
+/// Check that a key-committing hybrid ciphertext over X25519 is encrypted under the key,
+/// without decrypting it
+pub fn verify_committing_ciphertext_with_x25519_secret_key(
+    ctext: &ZeiCommittingCiphertext,
+    sec_key: &XSecretKey,
+) -> Result<()> {
+    let key = X25519Kem::decapsulate(sec_key, &ctext.ephemeral_public_key);
+    check_committing_tag(
+        &key,
+        ctext.ephemeral_public_key.key.as_bytes(),
+        &ctext.ciphertext,
+        &ctext.tag,
+    )
+    .c(d!())
 }
 
-/// Derive a symmetric key from an Ed25519 public key
-fn symmetric_key_from_ed25519_public_key<R>(
-    prng: &mut R,
-    public_key: &PublicKey,
-) -> ([u8; 32], x25519_dalek::PublicKey)
-where
-    R: CryptoRng + RngCore,
-{
+/// Check that a key-committing hybrid ciphertext over Ed25519 is encrypted under the key,
+/// without decrypting it
+pub fn verify_committing_ciphertext_with_ed25519_secret_key(
+    ctext: &ZeiCommittingCiphertext,
+    sec_key: &SecretKey,
+) -> Result<()> {
+    let key = X25519Kem::decapsulate(
+        &ed25519_to_x25519_secret_key(sec_key),
+        &ctext.ephemeral_public_key,
+    );
+    check_committing_tag(
+        &key,
+        ctext.ephemeral_public_key.key.as_bytes(),
+        &ctext.ciphertext,
+        &ctext.tag,
+    )
+    .c(d!())
+}
+
+/// Encrypt a message under a fresh symmetric key with a key-committing scheme, and return
+/// the ciphertext and its tag, which also binds the context, e.g., the ephemeral public key
+pub fn committing_symmetric_encrypt(
+    key: &[u8; 32],
+    context: &[u8],
+    plaintext: &[u8],
+) -> (Ctext, [u8; COMMITTING_TAG_LENGTH]) {
+    let ciphertext = symmetric_encrypt(&committing_encryption_key(key), plaintext);
+    let tag = committing_tag(key, context, &ciphertext);
+    (ciphertext, tag)
+}
+
+/// Decrypt a ciphertext of the key-committing scheme, or return an error if the tag does
+/// not match the key, the context and the ciphertext
+pub fn committing_symmetric_decrypt(
+    key: &[u8; 32],
+    context: &[u8],
+    ciphertext: &Ctext,
+    tag: &[u8; COMMITTING_TAG_LENGTH],
+) -> Result<Vec<u8>> {
+    check_committing_tag(key, context, ciphertext, tag).c(d!())?;
+    Ok(symmetric_decrypt(
+        &committing_encryption_key(key),
+        ciphertext,
+    ))
+}
+
+// The encryption key and the tag are derived from the symmetric key in separate domains.
+// SHA-512/256 is not subject to length extension, so the tag also authenticates.
+fn committing_encryption_key(key: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = sha2::Sha512_256::new();
+    hasher.update(COMMITTING_ENCRYPTION_KEY_DOMAIN);
+    hasher.update(key);
+    let mut encryption_key = [0u8; 32];
+    encryption_key.copy_from_slice(hasher.finalize().as_slice());
+    encryption_key
+}
+
+fn committing_tag(
+    key: &[u8; 32],
+    context: &[u8],
+    ciphertext: &Ctext,
+) -> [u8; COMMITTING_TAG_LENGTH] {
+    let mut hasher = sha2::Sha512_256::new();
+    hasher.update(COMMITTING_TAG_DOMAIN);
+    hasher.update(key);
+    hasher.update(&(context.len() as u64).to_le_bytes());
+    hasher.update(context);
+    hasher.update(&ciphertext.0);
+    let mut tag = [0u8; COMMITTING_TAG_LENGTH];
+    tag.copy_from_slice(hasher.finalize().as_slice());
+    tag
+}
+
+// The commitment to the key of an AEAD ciphertext, which also binds its header.
+fn aead_key_commitment(key: &[u8; 32], header: &[u8]) -> [u8; COMMITTING_TAG_LENGTH] {
+    let mut hasher = sha2::Sha512_256::new();
+    hasher.update(AEAD_KEY_COMMITMENT_DOMAIN);
+    hasher.update(key);
+    hasher.update(header);
+    let mut commitment = [0u8; COMMITTING_TAG_LENGTH];
+    commitment.copy_from_slice(hasher.finalize().as_slice());
+    commitment
+}
+
+fn check_committing_tag(
+    key: &[u8; 32],
+    context: &[u8],
+    ciphertext: &Ctext,
+    tag: &[u8; COMMITTING_TAG_LENGTH],
+) -> Result<()> {
+    check_tag(&committing_tag(key, context, ciphertext), tag).c(d!())
+}
+
+fn check_tag(
+    expected: &[u8; COMMITTING_TAG_LENGTH],
+    tag: &[u8; COMMITTING_TAG_LENGTH],
+) -> Result<()> {
+    // compare in constant time
+    let diff = expected
+        .iter()
+        .zip(tag.iter())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 {
+        return Err(eg!(ZeiError::DecryptionError));
+    }
+    Ok(())
+}
+
+/// Convert an Ed25519 public key to the X25519 public key of the same secret key
+fn ed25519_to_x25519_public_key(public_key: &PublicKey) -> XPublicKey {
     let pk_curve_point = CompressedEdwardsY::from_slice(public_key.as_bytes());
     let pk_montgomery = pk_curve_point.decompress().unwrap().to_montgomery();
-    let x_public_key = x25519_dalek::PublicKey::from(pk_montgomery.to_bytes());
-
-    symmetric_key_from_x25519_public_key(prng, &x_public_key)
+    XPublicKey {
+        key: x25519_dalek::PublicKey::from(pk_montgomery.to_bytes()),
+    }
 }
 
 fn sec_key_as_scalar(sk: &SecretKey) -> RistrettoScalar {
@@ -239,28 +632,17 @@ fn sec_key_as_scalar(sk: &SecretKey) -> RistrettoScalar {
     RistrettoScalar::from_bytes(&key_bytes).unwrap() // safe unwrap
 }
 
-/// Derive a symmetric key from a secret key over X25519
-fn symmetric_key_from_x25519_secret_key(
-    sec_key: &x25519_dalek::StaticSecret,
-    ephemeral_public_key: &x25519_dalek::PublicKey,
-) -> [u8; 32] {
-    let shared_key = sec_key.diffie_hellman(ephemeral_public_key);
-    shared_secret_to_symmetric_key(&shared_key)
-}
-
-/// Derive a symmetric key from a secret key over Ed25519
-fn symmetric_key_from_ed25519_secret_key(
-    sec_key: &SecretKey,
-    ephemeral_public_key: &x25519_dalek::PublicKey,
-) -> [u8; 32] {
+/// Convert an Ed25519 secret key to an X25519 secret key
+fn ed25519_to_x25519_secret_key(sec_key: &SecretKey) -> XSecretKey {
     let scalar_sec_key = sec_key_as_scalar(sec_key);
     let mut bytes = [0u8; 32];
     bytes.copy_from_slice(scalar_sec_key.to_bytes().as_slice());
-    let x_secret = x25519_dalek::StaticSecret::from(bytes);
-    symmetric_key_from_x25519_secret_key(&x_secret, ephemeral_public_key)
+    XSecretKey {
+        key: x25519_dalek::StaticSecret::from(bytes),
+    }
 }
 
-fn symmetric_encrypt(key: &[u8; 32], plaintext: &[u8]) -> Ctext {
+fn symmetric_encrypt(key: &[u8; KEM_SHARED_KEY_LENGTH], plaintext: &[u8]) -> Ctext {
     let kkey = GenericArray::from_slice(key);
     let ctr = GenericArray::from_slice(&[0u8; 16]); // counter can be zero because key is fresh
     let mut ctext_vec = plaintext.to_vec();
@@ -269,7 +651,7 @@ fn symmetric_encrypt(key: &[u8; 32], plaintext: &[u8]) -> Ctext {
     Ctext(ctext_vec)
 }
 
-fn symmetric_decrypt(key: &[u8; 32], ciphertext: &Ctext) -> Vec<u8> {
+fn symmetric_decrypt(key: &[u8; KEM_SHARED_KEY_LENGTH], ciphertext: &Ctext) -> Vec<u8> {
     let kkey = GenericArray::from_slice(key);
     let ctr = GenericArray::from_slice(&[0u8; 16]);
     let mut plaintext_vec = ciphertext.0.clone();
@@ -289,8 +671,11 @@ mod test {
         let mut prng = test_rng();
         let keypair = Keypair::generate(&mut prng);
         let (from_pk_key, encoded_rand) =
-            symmetric_key_from_ed25519_public_key(&mut prng, &keypair.public);
-        let from_sk_key = symmetric_key_from_ed25519_secret_key(&keypair.secret, &encoded_rand);
+            X25519Kem::encapsulate(&mut prng, &ed25519_to_x25519_public_key(&keypair.public));
+        let from_sk_key = X25519Kem::decapsulate(
+            &ed25519_to_x25519_secret_key(&keypair.secret),
+            &encoded_rand,
+        );
         assert_eq!(from_pk_key, from_sk_key);
     }
 
@@ -317,4 +702,172 @@ mod test {
         let plaintext = hybrid_decrypt_with_ed25519_secret_key(&cipherbox, &key_pair.secret);
         assert_eq!(msg, plaintext.as_slice());
     }
+
+    #[test]
+    fn committing_hybrid_cipher() {
+        let mut prng = test_rng();
+        let key_pair = Keypair::generate(&mut prng);
+        let other_key_pair = Keypair::generate(&mut prng);
+        let msg = b"this is a committed message";
+
+        let cipherbox = hybrid_encrypt_committing_ed25519(&mut prng, &key_pair.public, msg);
+        let plaintext =
+            hybrid_decrypt_committing_with_ed25519_secret_key(&cipherbox, &key_pair.secret)
+                .unwrap();
+        assert_eq!(msg, plaintext.as_slice());
+        assert!(
+            verify_committing_ciphertext_with_ed25519_secret_key(&cipherbox, &key_pair.secret)
+                .is_ok()
+        );
+        let bytes = cipherbox.zei_to_bytes();
+        assert_eq!(
+            ZeiCommittingCiphertext::zei_from_bytes(&bytes).unwrap(),
+            cipherbox
+        );
+
+        // the plain scheme decrypts under any key, the committing one rejects the wrong keys
+        let plain = hybrid_encrypt_ed25519(&mut prng, &key_pair.public, msg);
+        let garbage = hybrid_decrypt_with_ed25519_secret_key(&plain, &other_key_pair.secret);
+        assert_eq!(garbage.len(), msg.len());
+        assert!(hybrid_decrypt_committing_with_ed25519_secret_key(
+            &cipherbox,
+            &other_key_pair.secret
+        )
+        .is_err());
+        assert!(verify_committing_ciphertext_with_ed25519_secret_key(
+            &cipherbox,
+            &other_key_pair.secret
+        )
+        .is_err());
+
+        // a modified ciphertext is rejected
+        let mut modified = cipherbox.clone();
+        modified.ciphertext.0[0] ^= 1;
+        assert!(
+            hybrid_decrypt_committing_with_ed25519_secret_key(&modified, &key_pair.secret).is_err()
+        );
+
+        // as over X25519
+        let sec_key = XSecretKey::new(&mut prng);
+        let other_sec_key = XSecretKey::new(&mut prng);
+        let cipherbox =
+            hybrid_encrypt_committing_x25519(&mut prng, &XPublicKey::from(&sec_key), msg);
+        let plaintext =
+            hybrid_decrypt_committing_with_x25519_secret_key(&cipherbox, &sec_key).unwrap();
+        assert_eq!(msg, plaintext.as_slice());
+        assert!(
+            hybrid_decrypt_committing_with_x25519_secret_key(&cipherbox, &other_sec_key).is_err()
+        );
+        assert!(
+            verify_committing_ciphertext_with_x25519_secret_key(&cipherbox, &other_sec_key)
+                .is_err()
+        );
+    }
+
+    fn check_kem_committing_hybrid_cipher<K: Kem>() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = K::generate_keys(&mut prng);
+        let (other_sec_key, _) = K::generate_keys(&mut prng);
+        let msg = b"this is a message over a KEM";
+
+        let cipherbox = hybrid_encrypt_committing::<K, _>(&mut prng, &pub_key, msg);
+        let plaintext = hybrid_decrypt_committing(&cipherbox, &sec_key).unwrap();
+        assert_eq!(msg, plaintext.as_slice());
+        assert!(hybrid_decrypt_committing(&cipherbox, &other_sec_key).is_err());
+
+        let bytes = cipherbox.zei_to_bytes();
+        let cipherbox_de = KemCiphertext::<K>::zei_from_bytes(&bytes).unwrap();
+        assert_eq!(cipherbox_de, cipherbox);
+        assert!(KemCiphertext::<K>::zei_from_bytes(&bytes[..K::ENCAPSULATION_LENGTH]).is_err());
+
+        let mut modified = cipherbox;
+        modified.ciphertext.0[0] ^= 1;
+        assert!(hybrid_decrypt_committing(&modified, &sec_key).is_err());
+    }
+
+    fn check_aead_hybrid_cipher<K: Kem, A: Aead>() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = K::generate_keys(&mut prng);
+        let (other_sec_key, _) = K::generate_keys(&mut prng);
+        let msg = b"this is a message over an AEAD";
+
+        let cipherbox = hybrid_encrypt_aead::<K, A, _>(&mut prng, &pub_key, msg).unwrap();
+        assert_eq!(cipherbox.algorithm(), A::ALGORITHM);
+        assert_eq!(hybrid_decrypt_aead(&cipherbox, &sec_key).unwrap(), msg);
+        assert!(hybrid_decrypt_aead(&cipherbox, &other_sec_key).is_err());
+        assert!(verify_aead_ciphertext(&cipherbox, &sec_key).is_ok());
+        msg_eq!(
+            ZeiError::DecryptionError,
+            verify_aead_ciphertext(&cipherbox, &other_sec_key).unwrap_err()
+        );
+
+        // the key commitment is checked
+        let mut modified = cipherbox.clone();
+        modified.key_commitment[0] ^= 1;
+        assert!(hybrid_decrypt_aead(&modified, &sec_key).is_err());
+
+        let bytes = cipherbox.zei_to_bytes();
+        let cipherbox_de = AeadHybridCiphertext::<K>::zei_from_bytes(&bytes).unwrap();
+        assert_eq!(hybrid_decrypt_aead(&cipherbox_de, &sec_key).unwrap(), msg);
+
+        // the header is authenticated
+        let mut modified = cipherbox_de.clone();
+        modified.algorithm = match A::ALGORITHM {
+            AeadAlgorithm::Aes256GcmSiv => AeadAlgorithm::ChaCha20Poly1305,
+            AeadAlgorithm::ChaCha20Poly1305 => AeadAlgorithm::Aes256GcmSiv,
+        };
+        assert!(hybrid_decrypt_aead(&modified, &sec_key).is_err());
+        let mut modified = cipherbox_de;
+        modified.ciphertext.0[0] ^= 1;
+        assert!(hybrid_decrypt_aead(&modified, &sec_key).is_err());
+        let mut bytes = bytes;
+        bytes[0] = 0;
+        assert!(AeadHybridCiphertext::<K>::zei_from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn aead_hybrid_cipher() {
+        use crate::basic::aead::{Aes256GcmSivAead, ChaCha20Poly1305Aead};
+        check_aead_hybrid_cipher::<X25519Kem, Aes256GcmSivAead>();
+        check_aead_hybrid_cipher::<X25519Kem, ChaCha20Poly1305Aead>();
+        check_aead_hybrid_cipher::<crate::basic::kem::Secp256k1Kem, Aes256GcmSivAead>();
+        #[cfg(feature = "p256")]
+        check_aead_hybrid_cipher::<crate::basic::kem::P256Kem, ChaCha20Poly1305Aead>();
+    }
+
+    #[test]
+    fn aead_hybrid_cipher_ed25519() {
+        use crate::basic::aead::ChaCha20Poly1305Aead;
+        let mut prng = test_rng();
+        let keypair = Keypair::generate(&mut prng);
+        let msg = b"this is a message over Ed25519";
+        let cipherbox =
+            hybrid_encrypt_aead_ed25519::<ChaCha20Poly1305Aead, _>(&mut prng, &keypair.public, msg)
+                .unwrap();
+        assert_eq!(
+            hybrid_decrypt_aead(&cipherbox, &ed25519_to_x25519_secret_key(&keypair.secret))
+                .unwrap(),
+            msg.to_vec()
+        );
+    }
+
+    #[test]
+    fn kem_committing_hybrid_cipher() {
+        check_kem_committing_hybrid_cipher::<X25519Kem>();
+        #[cfg(feature = "p256")]
+        check_kem_committing_hybrid_cipher::<crate::basic::kem::P256Kem>();
+
+        // over X25519, the ciphertexts are those of the X25519 functions
+        let mut prng = test_rng();
+        let sec_key = XSecretKey::new(&mut prng);
+        let msg = b"this is a message over X25519";
+        let cipherbox =
+            hybrid_encrypt_committing_x25519(&mut prng, &XPublicKey::from(&sec_key), msg);
+        let cipherbox =
+            KemCiphertext::<X25519Kem>::zei_from_bytes(&cipherbox.zei_to_bytes()).unwrap();
+        assert_eq!(
+            hybrid_decrypt_committing(&cipherbox, &sec_key).unwrap(),
+            msg.to_vec()
+        );
+    }
 }
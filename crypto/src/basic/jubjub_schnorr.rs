@@ -0,0 +1,255 @@
+use crate::basic::rescue::RescueInstance;
+use zei_algebra::{
+    bls12_381::BLSScalar,
+    jubjub::{JubjubPoint, JubjubScalar},
+    prelude::*,
+};
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The secret key of a Schnorr signature over Jubjub.
+pub struct JubjubSchnorrSecretKey(pub(crate) JubjubScalar);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The public key `sk * G` of a Schnorr signature over Jubjub.
+pub struct JubjubSchnorrPublicKey(pub JubjubPoint);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A Schnorr signature over Jubjub.
+pub struct JubjubSchnorrSignature {
+    /// `r` = `k * G`
+    pub r: JubjubPoint,
+    /// `s` = `k + c * sk`
+    pub s: JubjubScalar,
+}
+
+impl AsRef<JubjubPoint> for JubjubSchnorrPublicKey {
+    fn as_ref(&self) -> &JubjubPoint {
+        &self.0
+    }
+}
+
+impl JubjubSchnorrSecretKey {
+    /// Return the public key.
+    pub fn to_public(&self) -> JubjubSchnorrPublicKey {
+        JubjubSchnorrPublicKey(JubjubPoint::get_base().mul(&self.0))
+    }
+}
+
+/// Return a key pair as `(sk, pk = sk * G)`.
+pub fn jubjub_schnorr_key_gen<R: CryptoRng + RngCore>(
+    prng: &mut R,
+) -> (JubjubSchnorrSecretKey, JubjubSchnorrPublicKey) {
+    let secret_key = JubjubSchnorrSecretKey(JubjubScalar::random(prng));
+    let public_key = secret_key.to_public();
+    (secret_key, public_key)
+}
+
+/// Compute the challenge of the signature as a BLS scalar:
+/// `Rescue(Rescue(R.x, R.y, pk.x, pk.y)[0], msg, 0, 0)[0]`.
+///
+/// The hash is over the BLS12-381 scalar field, which is the base field of Jubjub,
+/// so that the challenge can be recomputed in a constraint system.
+pub fn jubjub_schnorr_challenge(
+    r: &JubjubPoint,
+    pub_key: &JubjubSchnorrPublicKey,
+    msg: &BLSScalar,
+) -> BLSScalar {
+    let hash = RescueInstance::new();
+    let zero = BLSScalar::zero();
+    let cur = hash.rescue(&[r.get_x(), r.get_y(), pub_key.0.get_x(), pub_key.0.get_y()])[0];
+    hash.rescue(&[cur, *msg, zero, zero])[0]
+}
+
+/// Convert the challenge into a Jubjub scalar, by reading its little-endian bits as an integer.
+fn challenge_to_jubjub_scalar(challenge: &BLSScalar) -> JubjubScalar {
+    JubjubScalar::from_le_bytes_mod_order(&challenge.to_bytes())
+}
+
+/// Sign a message, given as a BLS scalar, and return `(R = k * G, s = k + c * sk)`.
+pub fn jubjub_schnorr_sign<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    sec_key: &JubjubSchnorrSecretKey,
+    msg: &BLSScalar,
+) -> JubjubSchnorrSignature {
+    let k = JubjubScalar::random(prng);
+    let r = JubjubPoint::get_base().mul(&k);
+    let challenge = jubjub_schnorr_challenge(&r, &sec_key.to_public(), msg);
+    let s = k.add(&challenge_to_jubjub_scalar(&challenge).mul(&sec_key.0));
+    JubjubSchnorrSignature { r, s }
+}
+
+/// Verify a signature by checking `s * G = R + c * pk`.
+///
+/// The public key is expected to be in the prime-order subgroup, as the ones
+/// from [`jubjub_schnorr_key_gen`].
+pub fn jubjub_schnorr_verify(
+    pub_key: &JubjubSchnorrPublicKey,
+    msg: &BLSScalar,
+    sig: &JubjubSchnorrSignature,
+) -> Result<()> {
+    let challenge = jubjub_schnorr_challenge(&sig.r, pub_key, msg);
+    let lhs = JubjubPoint::get_base().mul(&sig.s);
+    let rhs = sig
+        .r
+        .add(&pub_key.0.mul(&challenge_to_jubjub_scalar(&challenge)));
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::SignatureError))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A pre-signature, i.e., a Schnorr signature encrypted under an adaptor point `Y = y * G`,
+/// which becomes a signature with the secret `y`, and reveals `y` once the signature is published.
+pub struct JubjubSchnorrPreSignature {
+    /// `r` = `k * G`, the signature having the nonce point `R + Y`
+    pub r: JubjubPoint,
+    /// `s` = `k + c * sk`, where the challenge `c` is over `R + Y`
+    pub s: JubjubScalar,
+}
+
+/// Pre-sign a message under an adaptor point `Y`, and return `(R = k * G, s = k + c * sk)`,
+/// where `c` is the challenge of the nonce point `R + Y`.
+///
+/// In an atomic swap, each party pre-signs its transaction under the same adaptor point, whose
+/// secret only one party knows: publishing the adapted signature of one transaction reveals the
+/// secret, which adapts the pre-signature of the other.
+pub fn jubjub_schnorr_pre_sign<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    sec_key: &JubjubSchnorrSecretKey,
+    msg: &BLSScalar,
+    adaptor: &JubjubPoint,
+) -> JubjubSchnorrPreSignature {
+    let k = JubjubScalar::random(prng);
+    let r = JubjubPoint::get_base().mul(&k);
+    let challenge = jubjub_schnorr_challenge(&r.add(adaptor), &sec_key.to_public(), msg);
+    let s = k.add(&challenge_to_jubjub_scalar(&challenge).mul(&sec_key.0));
+    JubjubSchnorrPreSignature { r, s }
+}
+
+/// Verify a pre-signature under an adaptor point `Y` by checking `s * G = R + c * pk`,
+/// where `c` is the challenge of the nonce point `R + Y`.
+pub fn jubjub_schnorr_pre_verify(
+    pub_key: &JubjubSchnorrPublicKey,
+    msg: &BLSScalar,
+    adaptor: &JubjubPoint,
+    pre_sig: &JubjubSchnorrPreSignature,
+) -> Result<()> {
+    let challenge = jubjub_schnorr_challenge(&pre_sig.r.add(adaptor), pub_key, msg);
+    let lhs = JubjubPoint::get_base().mul(&pre_sig.s);
+    let rhs = pre_sig
+        .r
+        .add(&pub_key.0.mul(&challenge_to_jubjub_scalar(&challenge)));
+    if lhs == rhs {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::SignatureError))
+    }
+}
+
+/// Adapt a pre-signature with the secret `y` of its adaptor point into the signature
+/// `(R + Y, s + y)`.
+pub fn jubjub_schnorr_adapt(
+    pre_sig: &JubjubSchnorrPreSignature,
+    adaptor_secret: &JubjubScalar,
+) -> JubjubSchnorrSignature {
+    JubjubSchnorrSignature {
+        r: pre_sig.r.add(&JubjubPoint::get_base().mul(adaptor_secret)),
+        s: pre_sig.s.add(adaptor_secret),
+    }
+}
+
+/// Extract the secret `y` of the adaptor point `Y` from a pre-signature and the signature
+/// adapted from it, as `y = s' - s`.
+pub fn jubjub_schnorr_extract(
+    pre_sig: &JubjubSchnorrPreSignature,
+    sig: &JubjubSchnorrSignature,
+    adaptor: &JubjubPoint,
+) -> Result<JubjubScalar> {
+    let adaptor_secret = sig.s.sub(&pre_sig.s);
+    if sig.r != pre_sig.r.add(adaptor) || JubjubPoint::get_base().mul(&adaptor_secret) != *adaptor {
+        return Err(eg!(ZeiError::SignatureError));
+    }
+    Ok(adaptor_secret)
+}
+
+#[cfg(test)]
+mod jubjub_schnorr_test {
+    use super::{
+        jubjub_schnorr_adapt, jubjub_schnorr_extract, jubjub_schnorr_key_gen,
+        jubjub_schnorr_pre_sign, jubjub_schnorr_pre_verify, jubjub_schnorr_sign,
+        jubjub_schnorr_verify,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::BLSScalar,
+        jubjub::{JubjubPoint, JubjubScalar},
+        prelude::*,
+    };
+
+    #[test]
+    fn sign_and_verify() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = jubjub_schnorr_key_gen(&mut prng);
+        let msg = BLSScalar::from(42u32);
+        let sig = jubjub_schnorr_sign(&mut prng, &sec_key, &msg);
+        pnk!(jubjub_schnorr_verify(&pub_key, &msg, &sig));
+
+        let wrong_msg = BLSScalar::from(43u32);
+        let err = jubjub_schnorr_verify(&pub_key, &wrong_msg, &sig)
+            .err()
+            .unwrap();
+        msg_eq!(ZeiError::SignatureError, err);
+
+        let (_, other_pub_key) = jubjub_schnorr_key_gen(&mut prng);
+        assert!(jubjub_schnorr_verify(&other_pub_key, &msg, &sig).is_err());
+
+        let mut wrong_sig = sig.clone();
+        wrong_sig.r = wrong_sig.r.add(&JubjubPoint::get_base());
+        assert!(jubjub_schnorr_verify(&pub_key, &msg, &wrong_sig).is_err());
+
+        let json = serde_json::to_string(&sig).unwrap();
+        let sig_json: super::JubjubSchnorrSignature = serde_json::from_str(&json).unwrap();
+        assert_eq!(sig, sig_json);
+    }
+
+    #[test]
+    fn adaptor_signature() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = jubjub_schnorr_key_gen(&mut prng);
+        let msg = BLSScalar::from(42u32);
+        let adaptor_secret = JubjubScalar::random(&mut prng);
+        let adaptor = JubjubPoint::get_base().mul(&adaptor_secret);
+
+        let pre_sig = jubjub_schnorr_pre_sign(&mut prng, &sec_key, &msg, &adaptor);
+        pnk!(jubjub_schnorr_pre_verify(
+            &pub_key, &msg, &adaptor, &pre_sig
+        ));
+
+        // a pre-signature is bound to its adaptor point and is not a signature
+        let other_adaptor = JubjubPoint::get_base().mul(&JubjubScalar::random(&mut prng));
+        msg_eq!(
+            ZeiError::SignatureError,
+            jubjub_schnorr_pre_verify(&pub_key, &msg, &other_adaptor, &pre_sig).unwrap_err()
+        );
+        let pre_sig_as_sig = super::JubjubSchnorrSignature {
+            r: pre_sig.r,
+            s: pre_sig.s,
+        };
+        assert!(jubjub_schnorr_verify(&pub_key, &msg, &pre_sig_as_sig).is_err());
+
+        // the adapted signature verifies, and reveals the secret of the adaptor point
+        let sig = jubjub_schnorr_adapt(&pre_sig, &adaptor_secret);
+        pnk!(jubjub_schnorr_verify(&pub_key, &msg, &sig));
+        assert_eq!(
+            jubjub_schnorr_extract(&pre_sig, &sig, &adaptor).unwrap(),
+            adaptor_secret
+        );
+        assert!(jubjub_schnorr_extract(&pre_sig, &sig, &other_adaptor).is_err());
+
+        // an unrelated signature reveals nothing
+        let other_sig = jubjub_schnorr_sign(&mut prng, &sec_key, &msg);
+        assert!(jubjub_schnorr_extract(&pre_sig, &other_sig, &adaptor).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! Key encapsulation mechanisms.
+//!
+//! A key encapsulation mechanism (KEM) derives a fresh symmetric key for a public key, along
+//! with an encapsulation of it, from which the owner of the secret key derives the same key.
+//! The hybrid encryption schemes are generic over the KEM: X25519 and secp256k1 are always
+//! available, and are the ones used by the owner memos of Ed25519 and secp256k1 keys, while
+//! P-256 is available with the `p256` feature, for the deployments that are required to use NIST
+//! curves.
+
+use crate::basic::hybrid_encryption::{XPublicKey, XSecretKey};
+use sha2::Digest;
+use zei_algebra::{
+    fmt::Debug,
+    prelude::*,
+    secp256k1::{SECP256K1Scalar, SECP256K1G1},
+};
+
+/// The length of the symmetric keys derived by the KEMs.
+pub const KEM_SHARED_KEY_LENGTH: usize = 32;
+
+/// A key encapsulation mechanism.
+pub trait Kem {
+    /// The public key that the symmetric keys are encapsulated for.
+    type PublicKey;
+    /// The secret key that decapsulates the symmetric keys.
+    type SecretKey;
+    /// The encapsulation of a symmetric key, sent along with the ciphertext.
+    type Encapsulation: Clone + Debug + PartialEq + Eq;
+    /// The length of the encoding of an encapsulation.
+    const ENCAPSULATION_LENGTH: usize;
+
+    /// Sample a secret key and return it with its public key.
+    fn generate_keys<R: CryptoRng + RngCore>(prng: &mut R) -> (Self::SecretKey, Self::PublicKey);
+
+    /// Derive a fresh symmetric key for a public key, and return it with its encapsulation.
+    fn encapsulate<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        pub_key: &Self::PublicKey,
+    ) -> ([u8; KEM_SHARED_KEY_LENGTH], Self::Encapsulation);
+
+    /// Derive the symmetric key of an encapsulation with the secret key.
+    fn decapsulate(
+        sec_key: &Self::SecretKey,
+        encapsulation: &Self::Encapsulation,
+    ) -> [u8; KEM_SHARED_KEY_LENGTH];
+
+    /// Encode an encapsulation into `ENCAPSULATION_LENGTH` bytes.
+    fn encapsulation_to_bytes(encapsulation: &Self::Encapsulation) -> Vec<u8>;
+
+    /// Decode an encapsulation, or return an error if the bytes are not a valid encoding.
+    fn encapsulation_from_bytes(bytes: &[u8]) -> Result<Self::Encapsulation>;
+}
+
+/// Hash the shared secret of a Diffie-Hellman key exchange into a symmetric key.
+fn shared_secret_to_symmetric_key(shared_secret: &[u8]) -> [u8; KEM_SHARED_KEY_LENGTH] {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(shared_secret);
+    let mut symmetric_key = [0u8; KEM_SHARED_KEY_LENGTH];
+    symmetric_key.copy_from_slice(hasher.finalize().as_slice());
+    symmetric_key
+}
+
+/// The KEM of an ephemeral Diffie-Hellman key exchange over X25519.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct X25519Kem;
+
+impl Kem for X25519Kem {
+    type PublicKey = XPublicKey;
+    type SecretKey = XSecretKey;
+    type Encapsulation = XPublicKey;
+    const ENCAPSULATION_LENGTH: usize = 32;
+
+    fn generate_keys<R: CryptoRng + RngCore>(prng: &mut R) -> (XSecretKey, XPublicKey) {
+        let sec_key = XSecretKey::new(prng);
+        let pub_key = XPublicKey::from(&sec_key);
+        (sec_key, pub_key)
+    }
+
+    fn encapsulate<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        pub_key: &XPublicKey,
+    ) -> ([u8; KEM_SHARED_KEY_LENGTH], XPublicKey) {
+        let ephemeral = x25519_dalek::EphemeralSecret::new(prng);
+        let ephemeral_public_key = x25519_dalek::PublicKey::from(&ephemeral);
+        let shared = ephemeral.diffie_hellman(&pub_key.key);
+        (
+            shared_secret_to_symmetric_key(shared.as_bytes()),
+            XPublicKey {
+                key: ephemeral_public_key,
+            },
+        )
+    }
+
+    fn decapsulate(
+        sec_key: &XSecretKey,
+        encapsulation: &XPublicKey,
+    ) -> [u8; KEM_SHARED_KEY_LENGTH] {
+        let shared = sec_key.key.diffie_hellman(&encapsulation.key);
+        shared_secret_to_symmetric_key(shared.as_bytes())
+    }
+
+    fn encapsulation_to_bytes(encapsulation: &XPublicKey) -> Vec<u8> {
+        encapsulation.zei_to_bytes()
+    }
+
+    fn encapsulation_from_bytes(bytes: &[u8]) -> Result<XPublicKey> {
+        XPublicKey::zei_from_bytes(bytes).c(d!())
+    }
+}
+
+/// The KEM of an ephemeral Diffie-Hellman key exchange over secp256k1, whose encapsulations
+/// are compressed points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Secp256k1Kem;
+
+impl Kem for Secp256k1Kem {
+    type PublicKey = SECP256K1G1;
+    type SecretKey = SECP256K1Scalar;
+    type Encapsulation = SECP256K1G1;
+    const ENCAPSULATION_LENGTH: usize = 33;
+
+    fn generate_keys<R: CryptoRng + RngCore>(prng: &mut R) -> (SECP256K1Scalar, SECP256K1G1) {
+        let sec_key = SECP256K1Scalar::random(prng);
+        let pub_key = SECP256K1G1::get_base().mul(&sec_key);
+        (sec_key, pub_key)
+    }
+
+    fn encapsulate<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        pub_key: &SECP256K1G1,
+    ) -> ([u8; KEM_SHARED_KEY_LENGTH], SECP256K1G1) {
+        let (ephemeral, encapsulation) = Self::generate_keys(prng);
+        let shared = pub_key.mul(&ephemeral);
+        (
+            shared_secret_to_symmetric_key(&shared.to_compressed_bytes()),
+            encapsulation,
+        )
+    }
+
+    fn decapsulate(
+        sec_key: &SECP256K1Scalar,
+        encapsulation: &SECP256K1G1,
+    ) -> [u8; KEM_SHARED_KEY_LENGTH] {
+        let shared = encapsulation.mul(sec_key);
+        shared_secret_to_symmetric_key(&shared.to_compressed_bytes())
+    }
+
+    fn encapsulation_to_bytes(encapsulation: &SECP256K1G1) -> Vec<u8> {
+        encapsulation.to_compressed_bytes()
+    }
+
+    fn encapsulation_from_bytes(bytes: &[u8]) -> Result<SECP256K1G1> {
+        if bytes.len() != Self::ENCAPSULATION_LENGTH {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        SECP256K1G1::from_compressed_bytes(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+/// The KEM of an ephemeral Diffie-Hellman key exchange over the NIST P-256 curve, whose
+/// encapsulations are compressed SEC1 points.
+#[cfg(feature = "p256")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct P256Kem;
+
+#[cfg(feature = "p256")]
+impl Kem for P256Kem {
+    type PublicKey = p256::PublicKey;
+    type SecretKey = p256::SecretKey;
+    type Encapsulation = p256::PublicKey;
+    const ENCAPSULATION_LENGTH: usize = 33;
+
+    fn generate_keys<R: CryptoRng + RngCore>(prng: &mut R) -> (p256::SecretKey, p256::PublicKey) {
+        let sec_key = p256::SecretKey::random(prng);
+        let pub_key = sec_key.public_key();
+        (sec_key, pub_key)
+    }
+
+    fn encapsulate<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        pub_key: &p256::PublicKey,
+    ) -> ([u8; KEM_SHARED_KEY_LENGTH], p256::PublicKey) {
+        let ephemeral = p256::ecdh::EphemeralSecret::random(prng);
+        let shared = ephemeral.diffie_hellman(pub_key);
+        (
+            shared_secret_to_symmetric_key(shared.raw_secret_bytes().as_slice()),
+            ephemeral.public_key(),
+        )
+    }
+
+    fn decapsulate(
+        sec_key: &p256::SecretKey,
+        encapsulation: &p256::PublicKey,
+    ) -> [u8; KEM_SHARED_KEY_LENGTH] {
+        let shared =
+            p256::ecdh::diffie_hellman(sec_key.to_nonzero_scalar(), encapsulation.as_affine());
+        shared_secret_to_symmetric_key(shared.raw_secret_bytes().as_slice())
+    }
+
+    fn encapsulation_to_bytes(encapsulation: &p256::PublicKey) -> Vec<u8> {
+        use p256::elliptic_curve::sec1::ToEncodedPoint;
+        encapsulation.to_encoded_point(true).as_bytes().to_vec()
+    }
+
+    fn encapsulation_from_bytes(bytes: &[u8]) -> Result<p256::PublicKey> {
+        if bytes.len() != Self::ENCAPSULATION_LENGTH {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        p256::PublicKey::from_sec1_bytes(bytes).c(d!(ZeiError::DeserializationError))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Kem, Secp256k1Kem, X25519Kem};
+    use ark_std::test_rng;
+
+    fn check_kem<K: Kem>() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = K::generate_keys(&mut prng);
+        let (other_sec_key, _) = K::generate_keys(&mut prng);
+
+        let (key, encapsulation) = K::encapsulate(&mut prng, &pub_key);
+        assert_eq!(K::decapsulate(&sec_key, &encapsulation), key);
+        assert_ne!(K::decapsulate(&other_sec_key, &encapsulation), key);
+
+        // the keys are fresh
+        let (other_key, other_encapsulation) = K::encapsulate(&mut prng, &pub_key);
+        assert_ne!(other_key, key);
+        assert_ne!(other_encapsulation, encapsulation);
+
+        let bytes = K::encapsulation_to_bytes(&encapsulation);
+        assert_eq!(bytes.len(), K::ENCAPSULATION_LENGTH);
+        assert_eq!(K::encapsulation_from_bytes(&bytes).unwrap(), encapsulation);
+        assert!(K::encapsulation_from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn x25519_kem() {
+        check_kem::<X25519Kem>();
+    }
+
+    #[test]
+    fn secp256k1_kem() {
+        check_kem::<Secp256k1Kem>();
+    }
+
+    #[cfg(feature = "p256")]
+    #[test]
+    fn p256_kem() {
+        check_kem::<super::P256Kem>();
+    }
+}
@@ -1,16 +1,32 @@
+/// The module for the AEAD algorithms of the symmetric layer of hybrid encryption.
+pub mod aead;
 /// The module for the Chaum-Pedersen protocol.
 pub mod chaum_pedersen;
 /// The module for the ElGamal encryption.
 pub mod elgamal;
+/// The module for the precomputed tables of fixed bases.
+pub mod fixed_base;
+/// The module for hash-based commitments.
+pub mod hash_comm;
 /// The module for hybrid encryption.
 pub mod hybrid_encryption;
 /// The module for the Anemoi-Jive CRH.
 pub mod jive;
+/// The module for Schnorr signatures over Jubjub, which can be verified in a constraint system,
+/// and their adaptor signatures.
+pub mod jubjub_schnorr;
+/// The module for key encapsulation mechanisms, over which hybrid encryption is generic.
+pub mod kem;
 /// The module for the matrix Sigma protocol.
 pub mod matrix_sigma;
-/// The module for the Pedersen commitments over the Ristretto group and secq256k1 group.
+/// The module for the Pedersen commitments over the Ristretto, secq256k1, secp256k1,
+/// and BLS12-381 G1 groups.
 pub mod pedersen_comm;
 /// The module for the equality proof between a Pedersen commitment and an ElGamal ciphertext.
 pub mod pedersen_elgamal;
+/// The module for proxy re-encryption, which delegates decryption within a scope.
+pub mod proxy_reencryption;
 /// The module for the Rescue hash function.
 pub mod rescue;
+/// The module for Shamir secret sharing, for backing up secret keys across custodians.
+pub mod secret_sharing;
@@ -1,6 +1,11 @@
+use crate::basic::fixed_base::FixedBasePrecomp;
 use curve25519_dalek::traits::MultiscalarMul;
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha512};
+use zei_algebra::bls12_381::{BLSScalar, BLSG1};
 use zei_algebra::ops::{Add, Mul};
 use zei_algebra::ristretto::{RistrettoPoint, RistrettoScalar};
+use zei_algebra::secp256k1::{SECP256K1Scalar, SECP256K1G1};
 use zei_algebra::secq256k1::{SECQ256K1Scalar, SECQ256K1G1};
 use zei_algebra::traits::Group;
 
@@ -91,7 +96,11 @@ impl PedersenCommitment<SECQ256K1G1> for PedersenCommitmentSecq256k1 {
     }
 
     fn commit(&self, value: SECQ256K1Scalar, blinding: SECQ256K1Scalar) -> SECQ256K1G1 {
-        self.B.mul(&value).add(&self.B_blinding.mul(&blinding))
+        if SECQ256K1_PRECOMP.has_generators(self) {
+            SECQ256K1_PRECOMP.commit(&value, &blinding)
+        } else {
+            self.B.mul(&value).add(&self.B_blinding.mul(&blinding))
+        }
     }
 }
 
@@ -103,3 +112,192 @@ impl From<&PedersenCommitmentSecq256k1> for ark_bulletproofs_secq256k1::Pedersen
         }
     }
 }
+
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The Pedersen commitment implementation for the secp256k1 group.
+pub struct PedersenCommitmentSecp256k1 {
+    /// The generator for the value part.
+    pub B: SECP256K1G1,
+    /// The generator for the blinding part.
+    pub B_blinding: SECP256K1G1,
+}
+
+impl Default for PedersenCommitmentSecp256k1 {
+    fn default() -> Self {
+        // the blinding generator is sampled by hashing, so nobody knows its discrete log w.r.t. `B`
+        let mut hasher = Sha512::new();
+        hasher.update(b"Zei Pedersen commitment secp256k1 blinding generator");
+        Self {
+            B: SECP256K1G1::get_base(),
+            B_blinding: SECP256K1G1::from_hash(hasher),
+        }
+    }
+}
+
+impl PedersenCommitment<SECP256K1G1> for PedersenCommitmentSecp256k1 {
+    fn generator(&self) -> SECP256K1G1 {
+        self.B
+    }
+
+    fn blinding_generator(&self) -> SECP256K1G1 {
+        self.B_blinding
+    }
+
+    fn commit(&self, value: SECP256K1Scalar, blinding: SECP256K1Scalar) -> SECP256K1G1 {
+        if SECP256K1_PRECOMP.has_generators(self) {
+            SECP256K1_PRECOMP.commit(&value, &blinding)
+        } else {
+            SECP256K1G1::multi_exp(&[&value, &blinding], &[&self.B, &self.B_blinding])
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The Pedersen commitment implementation for the BLS12-381 G1 group.
+pub struct PedersenCommitmentBLS12381 {
+    /// The generator for the value part.
+    pub B: BLSG1,
+    /// The generator for the blinding part.
+    pub B_blinding: BLSG1,
+}
+
+impl Default for PedersenCommitmentBLS12381 {
+    fn default() -> Self {
+        // the blinding generator is sampled by hashing, so nobody knows its discrete log w.r.t. `B`
+        let mut hasher = Sha512::new();
+        hasher.update(b"Zei Pedersen commitment BLS12-381 blinding generator");
+        Self {
+            B: BLSG1::get_base(),
+            B_blinding: BLSG1::from_hash(hasher),
+        }
+    }
+}
+
+impl PedersenCommitment<BLSG1> for PedersenCommitmentBLS12381 {
+    fn generator(&self) -> BLSG1 {
+        self.B
+    }
+
+    fn blinding_generator(&self) -> BLSG1 {
+        self.B_blinding
+    }
+
+    fn commit(&self, value: BLSScalar, blinding: BLSScalar) -> BLSG1 {
+        if BLS12381_PRECOMP.has_generators(self) {
+            BLS12381_PRECOMP.commit(&value, &blinding)
+        } else {
+            BLSG1::multi_exp(&[&value, &blinding], &[&self.B, &self.B_blinding])
+        }
+    }
+}
+
+lazy_static! {
+    // The commitments under the default generators of the arkworks groups, whose generic
+    // multiplications branch on the bits of the scalars, go through constant-time tables.
+    static ref SECQ256K1_PRECOMP: PedersenCommitmentPrecomp<SECQ256K1G1> =
+        PedersenCommitmentPrecomp::new(&PedersenCommitmentSecq256k1::default());
+    static ref SECP256K1_PRECOMP: PedersenCommitmentPrecomp<SECP256K1G1> =
+        PedersenCommitmentPrecomp::new(&PedersenCommitmentSecp256k1::default());
+    static ref BLS12381_PRECOMP: PedersenCommitmentPrecomp<BLSG1> =
+        PedersenCommitmentPrecomp::new(&PedersenCommitmentBLS12381::default());
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// The precomputed tables of the generators of a Pedersen commitment scheme,
+/// for computing many commitments.
+pub struct PedersenCommitmentPrecomp<G> {
+    generator: FixedBasePrecomp<G>,
+    blinding_generator: FixedBasePrecomp<G>,
+}
+
+impl<G: Group> PedersenCommitmentPrecomp<G> {
+    /// Precompute the tables of the generators of the commitment scheme.
+    pub fn new<PC: PedersenCommitment<G>>(pc_gens: &PC) -> Self {
+        PedersenCommitmentPrecomp {
+            generator: FixedBasePrecomp::new(&pc_gens.generator()),
+            blinding_generator: FixedBasePrecomp::new(&pc_gens.blinding_generator()),
+        }
+    }
+
+    /// Return true if the tables are those of the generators of `pc_gens`.
+    pub fn has_generators<PC: PedersenCommitment<G>>(&self, pc_gens: &PC) -> bool {
+        *self.generator.base() == pc_gens.generator()
+            && *self.blinding_generator.base() == pc_gens.blinding_generator()
+    }
+
+    /// Compute the same commitment as the scheme of the generators.
+    pub fn commit(&self, value: &G::ScalarType, blinding: &G::ScalarType) -> G {
+        self.generator
+            .mul(value)
+            .add(&self.blinding_generator.mul(blinding))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        PedersenCommitment, PedersenCommitmentPrecomp, PedersenCommitmentRistretto,
+        PedersenCommitmentSecp256k1,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::prelude::*;
+    use zei_algebra::ristretto::RistrettoScalar;
+    use zei_algebra::secp256k1::SECP256K1Scalar;
+
+    #[test]
+    fn secp256k1_commitment_is_homomorphic() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentSecp256k1::default();
+        assert_ne!(pc_gens.generator(), pc_gens.blinding_generator());
+
+        let v1 = SECP256K1Scalar::from(10u32);
+        let v2 = SECP256K1Scalar::from(32u32);
+        let r1 = SECP256K1Scalar::random(&mut prng);
+        let r2 = SECP256K1Scalar::random(&mut prng);
+
+        let c1 = pc_gens.commit(v1, r1);
+        let c2 = pc_gens.commit(v2, r2);
+        assert_eq!(c1.add(&c2), pc_gens.commit(v1.add(&v2), r1.add(&r2)));
+        assert_ne!(c1, pc_gens.commit(v1, r2));
+    }
+
+    #[test]
+    fn precomputed_commitment() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let precomp = PedersenCommitmentPrecomp::new(&pc_gens);
+        for _ in 0..10 {
+            let v = RistrettoScalar::random(&mut prng);
+            let r = RistrettoScalar::random(&mut prng);
+            assert_eq!(precomp.commit(&v, &r), pc_gens.commit(v, r));
+        }
+        assert!(precomp.has_generators(&pc_gens));
+    }
+
+    #[test]
+    fn default_generators_use_the_tables() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentSecp256k1::default();
+        let v = SECP256K1Scalar::random(&mut prng);
+        let r = SECP256K1Scalar::random(&mut prng);
+        let expected = pc_gens
+            .generator()
+            .mul(&v)
+            .add(&pc_gens.blinding_generator().mul(&r));
+        assert_eq!(pc_gens.commit(v, r), expected);
+
+        // other generators fall back to the generic multiplication
+        let other = PedersenCommitmentSecp256k1 {
+            B: pc_gens.B_blinding,
+            B_blinding: pc_gens.B,
+        };
+        assert!(!PedersenCommitmentPrecomp::new(&pc_gens).has_generators(&other));
+        let expected = other
+            .generator()
+            .mul(&v)
+            .add(&other.blinding_generator().mul(&r));
+        assert_eq!(other.commit(v, r), expected);
+    }
+}
@@ -24,6 +24,13 @@ pub struct PedersenElGamalEqProof {
     c1: RistrettoPoint,
 }
 
+impl PedersenElGamalEqProof {
+    /// Check if the scalars of the proof are canonical.
+    pub fn is_canonical(&self) -> bool {
+        self.z1.is_canonical() && self.z2.is_canonical()
+    }
+}
+
 /// Initialize the transcript for Pedersen-Elgamal equality proof.
 fn init_pedersen_elgamal_transcript(
     transcript: &mut Transcript,
@@ -0,0 +1,444 @@
+//! Threshold proxy re-encryption over the Ristretto group.
+//!
+//! A delegator lets a delegatee decrypt the messages encrypted to it within a scope, e.g., the
+//! owner memos of a period, by handing a share of a re-encryption key to each of several
+//! proxies over private channels. Each proxy transforms the ciphertexts of the scope into a
+//! share of a ciphertext for the delegatee, without learning the messages, and the delegatee
+//! combines `threshold` of these shares to decrypt.
+//!
+//! The scheme is Umbral. A message is encrypted with an AEAD under a key derived from
+//! `(r + u) * h * pk`, where `E = r * G` and `V = u * G` form the capsule, and `h` is a hash of
+//! the public key and the scope, so that a ciphertext is bound to its scope. The re-encryption
+//! key is `sk * h / d`, where `d` hashes a Diffie-Hellman key exchange between an ephemeral key
+//! of the delegator and the key of the delegatee. Since the delegatee recomputes `d`, the
+//! re-encryption key itself would reveal `sk` to the delegatee, so it is never given to anyone:
+//! it is Shamir-shared among the proxies with a threshold of at least two, and each proxy only
+//! multiplies the capsule by its share. The delegatee interpolates the products in the exponent,
+//! and derives the key from the combined capsule and `d`.
+//!
+//! Neither the delegatee, nor fewer than `threshold` proxies, nor the delegatee together with
+//! fewer than `threshold` proxies learn anything about `sk`; the delegatee colluding with
+//! `threshold` proxies recovers it, as in any Umbral deployment.
+//! The capsules carry a Schnorr-like proof of their well-formedness, which the proxies check, so
+//! that they cannot be used to transform arbitrary points.
+
+use crate::basic::aead::{Aead, Aes256GcmSivAead, AEAD_KEY_LENGTH};
+use sha2::{Digest, Sha256, Sha512};
+use zei_algebra::{
+    prelude::*,
+    ristretto::{RistrettoPoint, RistrettoScalar},
+};
+
+const SCOPE_DOMAIN: &[u8] = b"Zei PRE Scope";
+const CAPSULE_DOMAIN: &[u8] = b"Zei PRE Capsule";
+const DELEGATION_DOMAIN: &[u8] = b"Zei PRE Delegation";
+const KEY_DOMAIN: &[u8] = b"Zei PRE Key";
+
+/// The secret key of proxy re-encryption.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreSecretKey(pub(crate) RistrettoScalar);
+
+/// The public key of proxy re-encryption.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrePublicKey(pub RistrettoPoint);
+
+/// The capsule of a ciphertext, from which the symmetric key is derived.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreCapsule {
+    /// `E = r * G`.
+    pub e: RistrettoPoint,
+    /// `V = u * G`.
+    pub v: RistrettoPoint,
+    /// `s = u + r * H(E, V)`, which proves that the capsule was formed by the encryptor.
+    pub s: RistrettoScalar,
+}
+
+/// A ciphertext for the delegator, which the delegator can decrypt, and which a proxy can
+/// re-encrypt for a delegatee within the scope.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreCiphertext {
+    /// The scope of the ciphertext.
+    pub scope: Vec<u8>,
+    /// The capsule of the symmetric key.
+    pub capsule: PreCapsule,
+    /// The AEAD encryption of the message.
+    pub ciphertext: Vec<u8>,
+}
+
+/// A share of the re-encryption key from a delegator to a delegatee for a scope, which is
+/// handed to a single proxy over a private channel.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreReKeyShare {
+    /// The scope of the delegation.
+    pub scope: Vec<u8>,
+    /// The public key of the delegator.
+    pub delegator: PrePublicKey,
+    /// The public key of the delegatee.
+    pub delegatee: PrePublicKey,
+    /// The ephemeral public key of the delegator, `X = x * G`.
+    pub ephemeral_key: RistrettoPoint,
+    /// The number of shares needed by the delegatee.
+    pub threshold: u32,
+    /// The non-zero index of the share.
+    pub index: u32,
+    /// The evaluation at `index` of the sharing polynomial of `sk * h / d`.
+    pub(crate) key: RistrettoScalar,
+}
+
+/// A share of a ciphertext re-encrypted for a delegatee, computed by a single proxy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreReCiphertextShare {
+    /// The scope of the ciphertext.
+    pub scope: Vec<u8>,
+    /// The public key of the delegator.
+    pub delegator: PrePublicKey,
+    /// The ephemeral public key of the delegator in the re-encryption key.
+    pub ephemeral_key: RistrettoPoint,
+    /// The number of shares needed by the delegatee.
+    pub threshold: u32,
+    /// The index of the share of the re-encryption key.
+    pub index: u32,
+    /// `E_i = rk_i * E`.
+    pub e: RistrettoPoint,
+    /// `V_i = rk_i * V`.
+    pub v: RistrettoPoint,
+    /// The AEAD encryption of the message.
+    pub ciphertext: Vec<u8>,
+}
+
+impl PreSecretKey {
+    /// Return the public key.
+    pub fn public_key(&self) -> PrePublicKey {
+        PrePublicKey(RistrettoPoint::get_base().mul(&self.0))
+    }
+}
+
+impl PreCapsule {
+    /// Check the proof of well-formedness of the capsule.
+    pub fn verify(&self) -> Result<()> {
+        let h = capsule_challenge(&self.e, &self.v);
+        if RistrettoPoint::get_base().mul(&self.s) != self.v.add(&self.e.mul(&h)) {
+            return Err(eg!(ZeiError::DecryptionError));
+        }
+        Ok(())
+    }
+}
+
+/// Return a key pair of proxy re-encryption.
+pub fn pre_key_gen<R: CryptoRng + RngCore>(prng: &mut R) -> (PreSecretKey, PrePublicKey) {
+    let sec_key = PreSecretKey(RistrettoScalar::random(prng));
+    let pub_key = sec_key.public_key();
+    (sec_key, pub_key)
+}
+
+/// Encrypt a message for the owner of `pub_key` within a scope.
+pub fn pre_encrypt<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    pub_key: &PrePublicKey,
+    scope: &[u8],
+    message: &[u8],
+) -> Result<PreCiphertext> {
+    let base = RistrettoPoint::get_base();
+    let r = RistrettoScalar::random(prng);
+    let u = RistrettoScalar::random(prng);
+    let e = base.mul(&r);
+    let v = base.mul(&u);
+    let s = u.add(&r.mul(&capsule_challenge(&e, &v)));
+
+    let h = scope_scalar(pub_key, scope);
+    let key = derive_key(&pub_key.0.mul(&r.add(&u).mul(&h)));
+    let ciphertext = Aes256GcmSivAead::encrypt(&key, scope, message).c(d!())?;
+    Ok(PreCiphertext {
+        scope: scope.to_vec(),
+        capsule: PreCapsule { e, v, s },
+        ciphertext,
+    })
+}
+
+/// Decrypt a ciphertext with the secret key of its owner.
+pub fn pre_decrypt(sec_key: &PreSecretKey, ctext: &PreCiphertext) -> Result<Vec<u8>> {
+    ctext.capsule.verify().c(d!())?;
+    let h = scope_scalar(&sec_key.public_key(), &ctext.scope);
+    let point = ctext
+        .capsule
+        .e
+        .add(&ctext.capsule.v)
+        .mul(&sec_key.0.mul(&h));
+    Aes256GcmSivAead::decrypt(&derive_key(&point), &ctext.scope, &ctext.ciphertext).c(d!())
+}
+
+/// Create `num_proxies` shares of the re-encryption key that delegates the decryption of the
+/// ciphertexts of a scope to the owner of `delegatee`, any `threshold` of which are needed.
+/// It requires `2 <= threshold <= num_proxies`, so that no single proxy colluding with the
+/// delegatee learns the secret key.
+pub fn pre_rekey<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    sec_key: &PreSecretKey,
+    delegatee: &PrePublicKey,
+    scope: &[u8],
+    threshold: u32,
+    num_proxies: u32,
+) -> Result<Vec<PreReKeyShare>> {
+    if threshold < 2 || threshold > num_proxies {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let delegator = sec_key.public_key();
+    let x = RistrettoScalar::random(prng);
+    let ephemeral_key = RistrettoPoint::get_base().mul(&x);
+    let d = delegation_scalar(&ephemeral_key, delegatee, &delegatee.0.mul(&x));
+    let h = scope_scalar(&delegator, scope);
+
+    // the sharing polynomial, whose constant coefficient is the re-encryption key
+    let mut coefs = vec![sec_key.0.mul(&h).mul(&d.inv().c(d!())?)];
+    coefs.extend((1..threshold).map(|_| RistrettoScalar::random(prng)));
+
+    Ok((1..=num_proxies)
+        .map(|index| {
+            let point = RistrettoScalar::from(index);
+            let key = coefs
+                .iter()
+                .rev()
+                .fold(RistrettoScalar::zero(), |acc, coef| {
+                    acc.mul(&point).add(coef)
+                });
+            PreReKeyShare {
+                scope: scope.to_vec(),
+                delegator,
+                delegatee: *delegatee,
+                ephemeral_key,
+                threshold,
+                index,
+                key,
+            }
+        })
+        .collect())
+}
+
+/// Re-encrypt a ciphertext of the scope of a share of a re-encryption key, into a share of the
+/// ciphertext for the delegatee.
+pub fn pre_reencrypt(rekey: &PreReKeyShare, ctext: &PreCiphertext) -> Result<PreReCiphertextShare> {
+    if ctext.scope != rekey.scope {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    ctext.capsule.verify().c(d!())?;
+    Ok(PreReCiphertextShare {
+        scope: ctext.scope.clone(),
+        delegator: rekey.delegator,
+        ephemeral_key: rekey.ephemeral_key,
+        threshold: rekey.threshold,
+        index: rekey.index,
+        e: ctext.capsule.e.mul(&rekey.key),
+        v: ctext.capsule.v.mul(&rekey.key),
+        ciphertext: ctext.ciphertext.clone(),
+    })
+}
+
+/// Decrypt a ciphertext from at least `threshold` of its re-encrypted shares, with the secret
+/// key of the delegatee.
+pub fn pre_decrypt_reencrypted(
+    sec_key: &PreSecretKey,
+    shares: &[PreReCiphertextShare],
+) -> Result<Vec<u8>> {
+    let first = shares.first().c(d!(ZeiError::ParameterError))?;
+    if shares.len() < first.threshold as usize {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    for share in shares.iter() {
+        if share.index == 0
+            || share.scope != first.scope
+            || share.delegator != first.delegator
+            || share.ephemeral_key != first.ephemeral_key
+            || share.threshold != first.threshold
+            || share.ciphertext != first.ciphertext
+        {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+    }
+    if shares.iter().map(|share| share.index).unique().count() != shares.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    // interpolate `rk * E` and `rk * V` at zero, in the exponent
+    let mut e = RistrettoPoint::get_identity();
+    let mut v = RistrettoPoint::get_identity();
+    for share_i in shares.iter() {
+        let point_i = RistrettoScalar::from(share_i.index);
+        let mut num = RistrettoScalar::one();
+        let mut den = RistrettoScalar::one();
+        for share_j in shares
+            .iter()
+            .filter(|share_j| share_j.index != share_i.index)
+        {
+            let point_j = RistrettoScalar::from(share_j.index);
+            num = num.mul(&point_j);
+            den = den.mul(&point_j.sub(&point_i));
+        }
+        let coef = num.mul(&den.inv().c(d!())?);
+        e = e.add(&share_i.e.mul(&coef));
+        v = v.add(&share_i.v.mul(&coef));
+    }
+
+    let d = delegation_scalar(
+        &first.ephemeral_key,
+        &sec_key.public_key(),
+        &first.ephemeral_key.mul(&sec_key.0),
+    );
+    let point = e.add(&v).mul(&d);
+    Aes256GcmSivAead::decrypt(&derive_key(&point), &first.scope, &first.ciphertext).c(d!())
+}
+
+/// Hash the public key of the delegator and the scope into the scalar `h`.
+fn scope_scalar(pub_key: &PrePublicKey, scope: &[u8]) -> RistrettoScalar {
+    let mut hasher = Sha512::new();
+    hasher.update(SCOPE_DOMAIN);
+    hasher.update(pub_key.0.to_compressed_bytes());
+    hasher.update((scope.len() as u64).to_le_bytes());
+    hasher.update(scope);
+    RistrettoScalar::from_hash(hasher)
+}
+
+fn capsule_challenge(e: &RistrettoPoint, v: &RistrettoPoint) -> RistrettoScalar {
+    let mut hasher = Sha512::new();
+    hasher.update(CAPSULE_DOMAIN);
+    hasher.update(e.to_compressed_bytes());
+    hasher.update(v.to_compressed_bytes());
+    RistrettoScalar::from_hash(hasher)
+}
+
+/// Hash the Diffie-Hellman key exchange between the delegator and the delegatee into the
+/// scalar `d`.
+fn delegation_scalar(
+    ephemeral_key: &RistrettoPoint,
+    delegatee: &PrePublicKey,
+    shared_point: &RistrettoPoint,
+) -> RistrettoScalar {
+    let mut hasher = Sha512::new();
+    hasher.update(DELEGATION_DOMAIN);
+    hasher.update(ephemeral_key.to_compressed_bytes());
+    hasher.update(delegatee.0.to_compressed_bytes());
+    hasher.update(shared_point.to_compressed_bytes());
+    RistrettoScalar::from_hash(hasher)
+}
+
+fn derive_key(point: &RistrettoPoint) -> [u8; AEAD_KEY_LENGTH] {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_DOMAIN);
+    hasher.update(point.to_compressed_bytes());
+    let mut key = [0u8; AEAD_KEY_LENGTH];
+    key.copy_from_slice(hasher.finalize().as_slice());
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        delegation_scalar, pre_decrypt, pre_decrypt_reencrypted, pre_encrypt, pre_key_gen,
+        pre_reencrypt, pre_rekey, scope_scalar,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{
+        prelude::*,
+        ristretto::{RistrettoPoint, RistrettoScalar},
+    };
+
+    #[test]
+    fn proxy_reencryption() {
+        let mut prng = test_rng();
+        let (owner_sk, owner_pk) = pre_key_gen(&mut prng);
+        let (auditor_sk, auditor_pk) = pre_key_gen(&mut prng);
+        let (other_sk, _) = pre_key_gen(&mut prng);
+        let msg = b"the owner memo of an output";
+
+        let ctext = pre_encrypt(&mut prng, &owner_pk, b"2022-Q1", msg).unwrap();
+        assert_eq!(pre_decrypt(&owner_sk, &ctext).unwrap(), msg);
+        assert!(pre_decrypt(&auditor_sk, &ctext).is_err());
+
+        let rekeys = pre_rekey(&mut prng, &owner_sk, &auditor_pk, b"2022-Q1", 2, 3).unwrap();
+        let re_ctexts = rekeys
+            .iter()
+            .map(|rekey| pre_reencrypt(rekey, &ctext).unwrap())
+            .collect_vec();
+        for subset in re_ctexts.iter().cloned().combinations(2) {
+            assert_eq!(pre_decrypt_reencrypted(&auditor_sk, &subset).unwrap(), msg);
+        }
+        assert!(pre_decrypt_reencrypted(&other_sk, &re_ctexts).is_err());
+
+        // fewer than `threshold` shares, or a repeated share, do not decrypt
+        msg_eq!(
+            ZeiError::ParameterError,
+            pre_decrypt_reencrypted(&auditor_sk, &re_ctexts[..1]).unwrap_err()
+        );
+        let repeated = vec![re_ctexts[0].clone(), re_ctexts[0].clone()];
+        assert!(pre_decrypt_reencrypted(&auditor_sk, &repeated).is_err());
+
+        // a single proxy cannot be trusted with the whole re-encryption key
+        assert!(pre_rekey(&mut prng, &owner_sk, &auditor_pk, b"2022-Q1", 1, 3).is_err());
+        assert!(pre_rekey(&mut prng, &owner_sk, &auditor_pk, b"2022-Q1", 4, 3).is_err());
+
+        // the re-encryption keys are limited to their scope, even if the scope label is forged
+        let other_ctext = pre_encrypt(&mut prng, &owner_pk, b"2022-Q2", msg).unwrap();
+        msg_eq!(
+            ZeiError::ParameterError,
+            pre_reencrypt(&rekeys[0], &other_ctext).unwrap_err()
+        );
+        let mut forged = other_ctext;
+        forged.scope = b"2022-Q1".to_vec();
+        let re_forged = rekeys
+            .iter()
+            .map(|rekey| pre_reencrypt(rekey, &forged).unwrap())
+            .collect_vec();
+        assert!(pre_decrypt_reencrypted(&auditor_sk, &re_forged).is_err());
+
+        // the proxies refuse malformed capsules
+        let mut malformed = ctext;
+        malformed.capsule.s = malformed.capsule.s.add(&RistrettoScalar::one());
+        msg_eq!(
+            ZeiError::DecryptionError,
+            pre_reencrypt(&rekeys[0], &malformed).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn delegatee_cannot_derive_secret_key() {
+        let mut prng = test_rng();
+        let (owner_sk, owner_pk) = pre_key_gen(&mut prng);
+        let (auditor_sk, auditor_pk) = pre_key_gen(&mut prng);
+        let rekeys = pre_rekey(&mut prng, &owner_sk, &auditor_pk, b"2022-Q1", 2, 3).unwrap();
+
+        // the delegatee recomputes `d` and `h`, as it does to decrypt
+        let ephemeral_key = rekeys[0].ephemeral_key;
+        let d = delegation_scalar(
+            &ephemeral_key,
+            &auditor_pk,
+            &ephemeral_key.mul(&auditor_sk.0),
+        );
+        let h = scope_scalar(&owner_pk, b"2022-Q1");
+        let unblind = d.mul(&h.inv().unwrap());
+
+        // the whole re-encryption key would reveal the secret key as `rk * d / h`
+        let rk = rekeys[..2]
+            .iter()
+            .map(|rekey| {
+                let other = rekeys[..2]
+                    .iter()
+                    .find(|other| other.index != rekey.index)
+                    .unwrap();
+                let point_i = RistrettoScalar::from(rekey.index);
+                let point_j = RistrettoScalar::from(other.index);
+                rekey
+                    .key
+                    .mul(&point_j)
+                    .mul(&point_j.sub(&point_i).inv().unwrap())
+            })
+            .fold(RistrettoScalar::zero(), |acc, term| acc.add(&term));
+        assert_eq!(rk.mul(&unblind), owner_sk.0);
+
+        // but the delegatee never gets it, and a single share colluding with the delegatee
+        // does not reveal it either
+        for rekey in rekeys.iter() {
+            let guess = rekey.key.mul(&unblind);
+            assert_ne!(guess, owner_sk.0);
+            assert_ne!(RistrettoPoint::get_base().mul(&guess), owner_pk.0);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+//! Shamir secret sharing, for backing up secret keys across custodians.
+//!
+//! A secret is split into `n` shares, any `threshold` of which recover it, while fewer reveal
+//! nothing about it. Byte strings, such as the encodings of `XfrSecretKey`, are shared byte by
+//! byte over GF(256), and scalars, such as the ElGamal decryption keys of the asset tracers,
+//! are shared over their own field.
+//!
+//! Each share carries a hiding commitment to the secret and an integrity tag over its content,
+//! so that a share corrupted in storage is detected before the recovery, and a recovery from
+//! shares of different secrets, or of the same secret split twice, is rejected instead of
+//! returning a wrong secret. The commitment is a hash of the secret with a random salt, which is
+//! shared together with the secret, so that fewer than `threshold` shares reveal nothing about
+//! the secret, even if it has low entropy.
+
+use crate::basic::elgamal::ElGamalDecKey;
+use sha2::{Digest, Sha256};
+use zei_algebra::prelude::*;
+
+const SECRET_DIGEST_DOMAIN: &[u8] = b"Zei Secret Sharing Secret Digest";
+const SHARE_TAG_DOMAIN: &[u8] = b"Zei Secret Sharing Share Tag";
+const SALT_LEN: usize = 32;
+
+/// A share of a byte string, over GF(256).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteShare {
+    /// The non-zero point at which the sharing polynomials are evaluated.
+    pub index: u8,
+    /// The evaluations of the sharing polynomials, one for each byte of the secret followed by
+    /// one for each byte of the salt.
+    pub value: Vec<u8>,
+    /// The commitment to the secret, i.e., its digest with the salt.
+    pub secret_digest: [u8; 32],
+    /// The integrity tag over the index, the value and the digest of the secret.
+    pub tag: [u8; 32],
+}
+
+/// A share of a scalar, over its field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScalarShare<S> {
+    /// The non-zero point at which the sharing polynomial is evaluated.
+    pub index: u32,
+    /// The evaluation of the sharing polynomial.
+    pub value: S,
+    /// The evaluation of the sharing polynomial of the salt.
+    pub salt: S,
+    /// The commitment to the secret, i.e., its digest with the salt.
+    pub secret_digest: [u8; 32],
+    /// The integrity tag over the index, the value, the salt and the digest of the secret.
+    pub tag: [u8; 32],
+}
+
+impl ByteShare {
+    /// Check the integrity tag of the share.
+    pub fn verify_tag(&self) -> Result<()> {
+        if share_tag(&self.secret_digest, self.index as u32, &self.value) == self.tag {
+            Ok(())
+        } else {
+            Err(eg!(ZeiError::SecretSharingError))
+        }
+    }
+}
+
+impl<S: Scalar> ScalarShare<S> {
+    /// Check the integrity tag of the share.
+    pub fn verify_tag(&self) -> Result<()> {
+        let mut bytes = self.value.to_bytes();
+        bytes.extend_from_slice(&self.salt.to_bytes());
+        if share_tag(&self.secret_digest, self.index, &bytes) == self.tag {
+            Ok(())
+        } else {
+            Err(eg!(ZeiError::SecretSharingError))
+        }
+    }
+}
+
+/// Split a byte string into `n` shares, any `threshold` of which recover it.
+/// It requires `1 <= threshold <= n <= 255`.
+pub fn split_bytes<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    secret: &[u8],
+    threshold: usize,
+    n: usize,
+) -> Result<Vec<ByteShare>> {
+    if threshold == 0 || threshold > n || n > u8::MAX as usize {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let mut salt = [0u8; SALT_LEN];
+    prng.fill_bytes(&mut salt);
+
+    // the coefficients of the sharing polynomial of each byte of the secret and of the salt,
+    // the constant one being the byte
+    let polys = secret
+        .iter()
+        .chain(salt.iter())
+        .map(|byte| {
+            let mut coefs = vec![0u8; threshold];
+            coefs[0] = *byte;
+            prng.fill_bytes(&mut coefs[1..]);
+            coefs
+        })
+        .collect_vec();
+
+    let secret_digest = secret_digest(secret, &salt);
+    Ok((1..=n as u8)
+        .map(|index| {
+            let value = polys
+                .iter()
+                .map(|coefs| {
+                    coefs
+                        .iter()
+                        .rev()
+                        .fold(0u8, |acc, coef| gf256_mul(acc, index) ^ coef)
+                })
+                .collect_vec();
+            let tag = share_tag(&secret_digest, index as u32, &value);
+            ByteShare {
+                index,
+                value,
+                secret_digest,
+                tag,
+            }
+        })
+        .collect())
+}
+
+/// Recover a byte string from at least `threshold` of its shares.
+/// It returns an error if a share is corrupted, if the shares are not of the same secret,
+/// or if there are too few of them.
+pub fn recover_bytes(shares: &[ByteShare]) -> Result<Vec<u8>> {
+    check_shares(
+        shares
+            .iter()
+            .map(|share| (share.index as u32, &share.secret_digest)),
+    )
+    .c(d!())?;
+    for share in shares.iter() {
+        share.verify_tag().c(d!())?;
+    }
+    let len = shares[0].value.len();
+    if len < SALT_LEN || shares.iter().any(|share| share.value.len() != len) {
+        return Err(eg!(ZeiError::SecretSharingError));
+    }
+
+    // the Lagrange coefficients at zero, where the subtraction of GF(256) is the addition
+    let coefs = shares
+        .iter()
+        .map(|share_i| {
+            let (num, den) = shares
+                .iter()
+                .filter(|share_j| share_j.index != share_i.index)
+                .fold((1u8, 1u8), |(num, den), share_j| {
+                    (
+                        gf256_mul(num, share_j.index),
+                        gf256_mul(den, share_j.index ^ share_i.index),
+                    )
+                });
+            gf256_mul(num, gf256_inv(den))
+        })
+        .collect_vec();
+
+    let mut secret = (0..len)
+        .map(|k| {
+            shares
+                .iter()
+                .zip(coefs.iter())
+                .fold(0u8, |acc, (share, coef)| {
+                    acc ^ gf256_mul(share.value[k], *coef)
+                })
+        })
+        .collect_vec();
+
+    let salt = secret.split_off(len - SALT_LEN);
+    check_secret_digest(&secret, &salt, &shares[0].secret_digest).c(d!())?;
+    Ok(secret)
+}
+
+/// Split a scalar into `n` shares, any `threshold` of which recover it.
+/// It requires `1 <= threshold <= n`.
+pub fn split_scalar<R: CryptoRng + RngCore, S: Scalar>(
+    prng: &mut R,
+    secret: &S,
+    threshold: usize,
+    n: u32,
+) -> Result<Vec<ScalarShare<S>>> {
+    if threshold == 0 || threshold > n as usize {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let salt = S::random(prng);
+    let mut coefs = vec![*secret];
+    coefs.extend((1..threshold).map(|_| S::random(prng)));
+    let mut salt_coefs = vec![salt];
+    salt_coefs.extend((1..threshold).map(|_| S::random(prng)));
+
+    let secret_digest = secret_digest(&secret.to_bytes(), &salt.to_bytes());
+    Ok((1..=n)
+        .map(|index| {
+            let point = S::from(index);
+            let eval = |coefs: &[S]| {
+                coefs
+                    .iter()
+                    .rev()
+                    .fold(S::zero(), |acc, coef| acc.mul(&point).add(coef))
+            };
+            let share = ScalarShare {
+                index,
+                value: eval(&coefs),
+                salt: eval(&salt_coefs),
+                secret_digest,
+                tag: [0u8; 32],
+            };
+            let mut bytes = share.value.to_bytes();
+            bytes.extend_from_slice(&share.salt.to_bytes());
+            ScalarShare {
+                tag: share_tag(&secret_digest, index, &bytes),
+                ..share
+            }
+        })
+        .collect())
+}
+
+/// Recover a scalar from at least `threshold` of its shares.
+/// It returns an error if a share is corrupted, if the shares are not of the same secret,
+/// or if there are too few of them.
+pub fn recover_scalar<S: Scalar>(shares: &[ScalarShare<S>]) -> Result<S> {
+    check_shares(
+        shares
+            .iter()
+            .map(|share| (share.index, &share.secret_digest)),
+    )
+    .c(d!())?;
+    for share in shares.iter() {
+        share.verify_tag().c(d!())?;
+    }
+
+    let mut secret = S::zero();
+    let mut salt = S::zero();
+    for share_i in shares.iter() {
+        let point_i = S::from(share_i.index);
+        let mut num = S::one();
+        let mut den = S::one();
+        for share_j in shares
+            .iter()
+            .filter(|share_j| share_j.index != share_i.index)
+        {
+            let point_j = S::from(share_j.index);
+            num = num.mul(&point_j);
+            den = den.mul(&point_j.sub(&point_i));
+        }
+        let coef = num.mul(&den.inv().c(d!())?);
+        secret = secret.add(&share_i.value.mul(&coef));
+        salt = salt.add(&share_i.salt.mul(&coef));
+    }
+
+    check_secret_digest(
+        &secret.to_bytes(),
+        &salt.to_bytes(),
+        &shares[0].secret_digest,
+    )
+    .c(d!())?;
+    Ok(secret)
+}
+
+/// Split an ElGamal decryption key, such as the key of an asset tracer, into `n` shares,
+/// any `threshold` of which recover it.
+pub fn split_elgamal_dec_key<R: CryptoRng + RngCore, S: Scalar>(
+    prng: &mut R,
+    dec_key: &ElGamalDecKey<S>,
+    threshold: usize,
+    n: u32,
+) -> Result<Vec<ScalarShare<S>>> {
+    split_scalar(prng, &dec_key.0, threshold, n).c(d!())
+}
+
+/// Recover an ElGamal decryption key from at least `threshold` of its shares.
+pub fn recover_elgamal_dec_key<S: Scalar>(shares: &[ScalarShare<S>]) -> Result<ElGamalDecKey<S>> {
+    Ok(ElGamalDecKey(recover_scalar(shares).c(d!())?))
+}
+
+/// Check that there are shares, all of the same secret, at distinct non-zero indices.
+fn check_shares<'a>(mut shares: impl Iterator<Item = (u32, &'a [u8; 32])>) -> Result<()> {
+    let (first_index, first_digest) = shares.next().c(d!(ZeiError::SecretSharingError))?;
+    let mut indices = vec![first_index];
+    for (index, digest) in shares {
+        if digest != first_digest {
+            return Err(eg!(ZeiError::SecretSharingError));
+        }
+        indices.push(index);
+    }
+    if indices.contains(&0) || indices.iter().unique().count() != indices.len() {
+        return Err(eg!(ZeiError::SecretSharingError));
+    }
+    Ok(())
+}
+
+/// Check the recovered secret and salt against the digest in the shares, which fails if there
+/// were fewer shares than the threshold.
+fn check_secret_digest(secret: &[u8], salt: &[u8], digest: &[u8; 32]) -> Result<()> {
+    if &secret_digest(secret, salt) == digest {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::SecretSharingError))
+    }
+}
+
+fn secret_digest(secret: &[u8], salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SECRET_DIGEST_DOMAIN);
+    hasher.update((secret.len() as u64).to_le_bytes());
+    hasher.update(secret);
+    hasher.update(salt);
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(hasher.finalize().as_slice());
+    digest
+}
+
+fn share_tag(secret_digest: &[u8; 32], index: u32, value: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(SHARE_TAG_DOMAIN);
+    hasher.update(secret_digest);
+    hasher.update(index.to_le_bytes());
+    hasher.update(value);
+    let mut tag = [0u8; 32];
+    tag.copy_from_slice(hasher.finalize().as_slice());
+    tag
+}
+
+/// Multiply in GF(256), with the AES polynomial `x^8 + x^4 + x^3 + x + 1`, in time
+/// independent of the operands.
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// Invert a non-zero element of GF(256), as `a^254`.
+fn gf256_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut power = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf256_mul(result, power);
+        }
+        power = gf256_mul(power, power);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        gf256_inv, gf256_mul, recover_bytes, recover_elgamal_dec_key, recover_scalar,
+        secret_digest, split_bytes, split_elgamal_dec_key, split_scalar,
+    };
+    use crate::basic::elgamal::elgamal_key_gen;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::RistrettoPoint};
+
+    #[test]
+    fn gf256() {
+        assert_eq!(gf256_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf256_mul(a, gf256_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let mut prng = test_rng();
+        let secret = b"the secret key of a custodian".to_vec();
+        let shares = split_bytes(&mut prng, &secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for subset in shares.iter().cloned().combinations(3) {
+            assert_eq!(recover_bytes(&subset).unwrap(), secret);
+        }
+        assert_eq!(recover_bytes(&shares).unwrap(), secret);
+
+        // the digest is salted, so that it cannot be checked against a guess of the secret
+        assert_ne!(shares[0].secret_digest, secret_digest(&secret, &[]));
+        let other_shares = split_bytes(&mut prng, &secret, 3, 5).unwrap();
+        assert_ne!(shares[0].secret_digest, other_shares[0].secret_digest);
+
+        // too few shares
+        msg_eq!(
+            ZeiError::SecretSharingError,
+            recover_bytes(&shares[..2]).unwrap_err()
+        );
+        assert!(recover_bytes(&[]).is_err());
+
+        // a corrupted share
+        let mut corrupted = shares[..3].to_vec();
+        corrupted[1].value[0] ^= 1;
+        msg_eq!(
+            ZeiError::SecretSharingError,
+            recover_bytes(&corrupted).unwrap_err()
+        );
+
+        // a repeated share
+        let repeated = vec![shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert!(recover_bytes(&repeated).is_err());
+
+        // shares of another sharing of the same secret
+        let mixed = vec![
+            shares[0].clone(),
+            shares[1].clone(),
+            other_shares[2].clone(),
+        ];
+        assert!(recover_bytes(&mixed).is_err());
+
+        // bad parameters
+        assert!(split_bytes(&mut prng, &secret, 0, 5).is_err());
+        assert!(split_bytes(&mut prng, &secret, 6, 5).is_err());
+        assert!(split_bytes(&mut prng, &secret, 3, 256).is_err());
+    }
+
+    #[test]
+    fn scalars() {
+        let mut prng = test_rng();
+        let secret = BLSScalar::random(&mut prng);
+        let shares = split_scalar(&mut prng, &secret, 2, 4).unwrap();
+
+        for subset in shares.iter().cloned().combinations(2) {
+            assert_eq!(recover_scalar(&subset).unwrap(), secret);
+        }
+        msg_eq!(
+            ZeiError::SecretSharingError,
+            recover_scalar(&shares[..1]).unwrap_err()
+        );
+
+        let mut corrupted = shares[..2].to_vec();
+        corrupted[0].value = corrupted[0].value.add(&BLSScalar::one());
+        msg_eq!(
+            ZeiError::SecretSharingError,
+            recover_scalar(&corrupted).unwrap_err()
+        );
+
+        // the digest of a low-entropy secret is salted
+        let secret = BLSScalar::from(7u32);
+        let shares = split_scalar(&mut prng, &secret, 2, 3).unwrap();
+        assert_ne!(
+            shares[0].secret_digest,
+            secret_digest(&secret.to_bytes(), &BLSScalar::zero().to_bytes())
+        );
+        let other_shares = split_scalar(&mut prng, &secret, 2, 3).unwrap();
+        assert_ne!(shares[0].secret_digest, other_shares[0].secret_digest);
+        assert!(recover_scalar(&[shares[0].clone(), other_shares[1].clone()]).is_err());
+
+        // the ElGamal decryption key of an asset tracer
+        let (dec_key, _) = elgamal_key_gen::<_, RistrettoPoint>(&mut prng);
+        let shares = split_elgamal_dec_key(&mut prng, &dec_key, 3, 3).unwrap();
+        assert_eq!(recover_elgamal_dec_key(&shares).unwrap(), dec_key);
+    }
+}
@@ -0,0 +1,351 @@
+//! Module for confidential 64-bit amounts, committed as two 32-bit limbs
+//!
+//! An amount `a = a_lo + 2^32 * a_hi` is committed as the Pedersen commitments `C_lo` and
+//! `C_hi` to its limbs, with the blinds `r_lo` and `r_hi`. The limbs are linked to a commitment
+//! to the whole amount by `C = C_lo + 2^32 * C_hi`, whose blind is `r = r_lo + 2^32 * r_hi`,
+//! and each limb is proven to be in `[0, 2^32)` by a range proof, so that the amount is in
+//! `[0, 2^64)`.
+//!
+//! Sums and differences of amounts, e.g., the difference between the inputs and the outputs of
+//! a transfer, or between a bound and a total, are computed on the linked commitments, where
+//! the lower limbs carry into the higher ones. Such a value is proven to be a 64-bit amount by
+//! splitting it into fresh limbs whose linked blind is the blind of the value, and proving the
+//! range of the limbs: the verifier checks the linking equation against the commitment that
+//! it derives, and the range proof of the limbs.
+
+use crate::basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto};
+use crate::bulletproofs::range::{
+    batch_verify_versioned_ranges, prove_ranges_versioned, RangeProofBackend, VersionedRangeProof,
+};
+use bulletproofs::BulletproofGens;
+use merlin::Transcript;
+use zei_algebra::{
+    prelude::*,
+    ristretto::{CompressedRistretto, RistrettoPoint, RistrettoScalar},
+    utils::min_greater_equal_power_of_two,
+};
+
+/// The number of bits of each limb of an amount.
+pub const AMOUNT_LIMB_BITS: usize = 32;
+/// The weight of the higher limb of an amount, i.e., `2^32`.
+pub const AMOUNT_HIGH_LIMB_WEIGHT: u64 = 1 << AMOUNT_LIMB_BITS;
+
+/// The commitments to the lower and the higher 32 bits of an amount.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmountCommitment {
+    /// The commitment to the lower 32 bits.
+    pub low: CompressedRistretto,
+    /// The commitment to the higher 32 bits.
+    pub high: CompressedRistretto,
+}
+
+/// The opening of the commitments of a confidential amount, for the lower and the higher
+/// 32 bits of the amount.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmountOpening {
+    /// The amount.
+    pub amount: u64,
+    /// The blinds of the lower and the higher 32 bits.
+    pub blinds: (RistrettoScalar, RistrettoScalar),
+}
+
+/// Return the commitment to the whole amount, `C_lo + 2^32 * C_hi`, from the commitments to
+/// its limbs.
+pub fn link_limb_commitments(commitments: &(RistrettoPoint, RistrettoPoint)) -> RistrettoPoint {
+    let weight = RistrettoScalar::from(AMOUNT_HIGH_LIMB_WEIGHT);
+    commitments.0.add(&commitments.1.mul(&weight))
+}
+
+impl AmountCommitment {
+    /// Decompress the commitments to the lower and the higher 32 bits.
+    pub fn decompress(&self) -> Result<(RistrettoPoint, RistrettoPoint)> {
+        let low = self
+            .low
+            .decompress()
+            .c(d!(ZeiError::DecompressElementError))?;
+        let high = self
+            .high
+            .decompress()
+            .c(d!(ZeiError::DecompressElementError))?;
+        Ok((low, high))
+    }
+
+    /// Return the commitment to the whole amount, `C_lo + 2^32 * C_hi`.
+    pub fn link(&self) -> Result<RistrettoPoint> {
+        Ok(link_limb_commitments(&self.decompress().c(d!())?))
+    }
+
+    /// Check that the limbs are linked to a commitment to the whole amount, e.g., the
+    /// commitment to a sum or a difference of amounts derived by the verifier.
+    pub fn verify_link(&self, commitment: &RistrettoPoint) -> Result<()> {
+        if self.link().c(d!())?.compress() != commitment.compress() {
+            return Err(eg!(ZeiError::CommitmentVerificationError));
+        }
+        Ok(())
+    }
+}
+
+impl AmountOpening {
+    /// Open a non-confidential amount, with zero blinds.
+    pub fn non_confidential(amount: u64) -> Self {
+        AmountOpening {
+            amount,
+            blinds: (RistrettoScalar::zero(), RistrettoScalar::zero()),
+        }
+    }
+
+    /// Open an amount with random blinds.
+    pub fn random<R: CryptoRng + RngCore>(prng: &mut R, amount: u64) -> Self {
+        AmountOpening {
+            amount,
+            blinds: (RistrettoScalar::random(prng), RistrettoScalar::random(prng)),
+        }
+    }
+
+    /// Split a value committed as a whole with `blind`, e.g., a sum or a difference of amounts,
+    /// into limbs whose linked blind is `blind`.
+    pub fn split<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        amount: u64,
+        blind: &RistrettoScalar,
+    ) -> Self {
+        let blind_high = RistrettoScalar::random(prng);
+        let blind_low = blind.sub(&blind_high.mul(&RistrettoScalar::from(AMOUNT_HIGH_LIMB_WEIGHT)));
+        AmountOpening {
+            amount,
+            blinds: (blind_low, blind_high),
+        }
+    }
+
+    /// Return the lower and the higher 32 bits of the amount.
+    pub fn limbs(&self) -> (u32, u32) {
+        u64_to_u32_pair(self.amount)
+    }
+
+    /// Return the blind of the commitment to the whole amount, `r_lo + 2^32 * r_hi`.
+    pub fn linked_blind(&self) -> RistrettoScalar {
+        let weight = RistrettoScalar::from(AMOUNT_HIGH_LIMB_WEIGHT);
+        self.blinds.0.add(&self.blinds.1.mul(&weight))
+    }
+
+    /// Commit to the lower and the higher 32 bits of the amount.
+    pub fn commit(
+        &self,
+        pc_gens: &PedersenCommitmentRistretto,
+    ) -> (RistrettoPoint, RistrettoPoint) {
+        let (amount_lo, amount_hi) = self.limbs();
+        (
+            pc_gens.commit(RistrettoScalar::from(amount_lo), self.blinds.0),
+            pc_gens.commit(RistrettoScalar::from(amount_hi), self.blinds.1),
+        )
+    }
+
+    /// Check the opening against the commitments of the lower and the higher 32 bits.
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenCommitmentRistretto,
+        commitments: &(RistrettoPoint, RistrettoPoint),
+    ) -> Result<()> {
+        if self.commit(pc_gens) != *commitments {
+            return Err(eg!(ZeiError::CommitmentVerificationError));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = self.amount.to_le_bytes().to_vec();
+        bytes.extend_from_slice(&self.blinds.0.to_bytes());
+        bytes.extend_from_slice(&self.blinds.1.to_bytes());
+        bytes
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let scalar_len = RistrettoScalar::bytes_len();
+        if bytes.len() != 8 + 2 * scalar_len {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let mut amount = [0u8; 8];
+        amount.copy_from_slice(&bytes[..8]);
+        let blinds = (
+            RistrettoScalar::from_bytes_canonical(&bytes[8..8 + scalar_len]).c(d!())?,
+            RistrettoScalar::from_bytes_canonical(&bytes[8 + scalar_len..]).c(d!())?,
+        );
+        Ok(AmountOpening {
+            amount: u64::from_le_bytes(amount),
+            blinds,
+        })
+    }
+}
+
+/// Prove that the openings are of 64-bit amounts, by an aggregated range proof of their limbs,
+/// padded with commitments to zero to a power of two. Return the proof and the commitments
+/// of the amounts, in the order of the openings.
+pub fn prove_amounts<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    backend: RangeProofBackend,
+    bp_gens: &BulletproofGens,
+    transcript: &mut Transcript,
+    openings: &[AmountOpening],
+) -> Result<(VersionedRangeProof, Vec<AmountCommitment>)> {
+    if openings.is_empty() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let num_limbs = min_greater_equal_power_of_two((2 * openings.len()) as u32) as usize;
+    let mut values = Vec::with_capacity(num_limbs);
+    let mut blinds = Vec::with_capacity(num_limbs);
+    for opening in openings {
+        let (amount_lo, amount_hi) = opening.limbs();
+        values.push(amount_lo as u64);
+        values.push(amount_hi as u64);
+        blinds.push(opening.blinds.0);
+        blinds.push(opening.blinds.1);
+    }
+    values.resize(num_limbs, 0u64);
+    blinds.resize(num_limbs, RistrettoScalar::zero());
+
+    let (proof, coms) = prove_ranges_versioned(
+        prng,
+        backend,
+        bp_gens,
+        transcript,
+        &values,
+        &blinds,
+        AMOUNT_LIMB_BITS,
+    )
+    .c(d!(ZeiError::RangeProofProveError))?;
+    let commitments = coms
+        .chunks(2)
+        .take(openings.len())
+        .map(|pair| AmountCommitment {
+            low: pair[0],
+            high: pair[1],
+        })
+        .collect_vec();
+    Ok((proof, commitments))
+}
+
+/// Batch-verify proofs that commitments are of 64-bit amounts, as computed by `prove_amounts`.
+/// State of transcripts should match the state just before each proof was computed
+pub fn batch_verify_amounts<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    bp_gens: &BulletproofGens,
+    proofs: &[&VersionedRangeProof],
+    transcripts: &mut [Transcript],
+    commitments: &[&[AmountCommitment]],
+) -> Result<()> {
+    if commitments.iter().any(|coms| coms.is_empty()) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let limbs = commitments
+        .iter()
+        .map(|coms| {
+            let num_limbs = min_greater_equal_power_of_two((2 * coms.len()) as u32) as usize;
+            let mut limbs = coms.iter().flat_map(|c| [c.low, c.high]).collect_vec();
+            limbs.resize(num_limbs, CompressedRistretto::identity());
+            limbs
+        })
+        .collect_vec();
+    let limbs = limbs.iter().map(|l| l.as_slice()).collect_vec();
+    batch_verify_versioned_ranges(prng, bp_gens, proofs, transcripts, &limbs, AMOUNT_LIMB_BITS)
+        .c(d!(ZeiError::RangeProofVerifyError))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        batch_verify_amounts, link_limb_commitments, prove_amounts, AmountCommitment, AmountOpening,
+    };
+    use crate::basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto};
+    use crate::bulletproofs::range::RangeProofBackend;
+    use ark_std::test_rng;
+    use bulletproofs::BulletproofGens;
+    use merlin::Transcript;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn amount_commitments() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let opening = AmountOpening::random(&mut prng, (7u64 << 32) + 5);
+        assert_eq!(opening.limbs(), (5, 7));
+
+        let commitments = opening.commit(&pc_gens);
+        assert!(opening.verify(&pc_gens, &commitments).is_ok());
+        msg_eq!(
+            ZeiError::CommitmentVerificationError,
+            opening
+                .verify(&pc_gens, &(commitments.1, commitments.0))
+                .unwrap_err()
+        );
+
+        // the limbs are linked to the commitment to the whole amount
+        let linked = link_limb_commitments(&commitments);
+        assert_eq!(
+            linked,
+            pc_gens.commit(
+                RistrettoScalar::from(opening.amount),
+                opening.linked_blind()
+            )
+        );
+        let compressed = AmountCommitment {
+            low: commitments.0.compress(),
+            high: commitments.1.compress(),
+        };
+        assert!(compressed.verify_link(&linked).is_ok());
+        assert!(compressed.verify_link(&commitments.0).is_err());
+
+        let bytes = opening.to_bytes();
+        assert_eq!(AmountOpening::from_bytes(&bytes).unwrap(), opening);
+        assert!(AmountOpening::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn carried_amounts() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let bp_gens = BulletproofGens::new(32, 8);
+
+        // the lower limbs of the sum carry into the higher one
+        let a = AmountOpening::random(&mut prng, (3u64 << 32) + u32::MAX as u64);
+        let b = AmountOpening::random(&mut prng, 5);
+        let sum_com = link_limb_commitments(&a.commit(&pc_gens))
+            .add(&link_limb_commitments(&b.commit(&pc_gens)));
+        let sum_blind = a.linked_blind().add(&b.linked_blind());
+        let sum = AmountOpening::split(&mut prng, a.amount + b.amount, &sum_blind);
+        assert_eq!(sum.limbs(), (4, 4));
+
+        for backend in [
+            RangeProofBackend::Bulletproofs,
+            RangeProofBackend::BulletproofsPlus,
+        ] {
+            let openings = [a, b, sum];
+            let mut transcript = Transcript::new(b"Test Amounts");
+            let (proof, coms) =
+                prove_amounts(&mut prng, backend, &bp_gens, &mut transcript, &openings).unwrap();
+            assert_eq!(coms.len(), 3);
+            assert!(coms[2].verify_link(&sum_com).is_ok());
+
+            let mut transcripts = [Transcript::new(b"Test Amounts")];
+            assert!(batch_verify_amounts(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut transcripts,
+                &[&coms]
+            )
+            .is_ok());
+
+            // the commitments of other amounts are rejected
+            let mut transcripts = [Transcript::new(b"Test Amounts")];
+            let swapped = [coms[1], coms[0], coms[2]];
+            assert!(batch_verify_amounts(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut transcripts,
+                &[&swapped]
+            )
+            .is_err());
+        }
+    }
+}
@@ -1,3 +1,6 @@
+pub mod amount;
 pub mod mix;
+pub mod plus;
 pub mod range;
 pub mod scalar_mul;
+pub mod streaming;
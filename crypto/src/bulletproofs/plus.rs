@@ -0,0 +1,717 @@
+//! Module for the Bulletproofs+ range proof scheme
+//!
+//! Bulletproofs+ (Chung, Han, Ju, Kim, Seo, 2020) replaces the inner product argument of
+//! Bulletproofs with a weighted inner product argument, which drops three of the elements of an
+//! aggregated range proof and turns its verification into a multi-scalar multiplication with
+//! fewer terms. The proofs use the same generators as `bulletproofs::BulletproofGens` and
+//! the same Pedersen commitments as `bulletproofs::PedersenGens`, so that both backends prove
+//! the ranges of the same commitments.
+
+use crate::bulletproofs::streaming::{
+    append_point, challenge_scalar, random_scalar, sum_of_powers, validate_and_append_point,
+    GeneratorsChain,
+};
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, MultiscalarMul, VartimeMultiscalarMul},
+};
+use merlin::Transcript;
+use zei_algebra::prelude::*;
+use zei_algebra::ristretto::{
+    CompressedRistretto as ZeiCompressedRistretto, RistrettoScalar as ZeiScalar,
+};
+
+const RANGE_PROOF_PLUS_DOMAIN: &[u8] = b"rangeproof plus v1";
+const WEIGHTED_INNER_PRODUCT_DOMAIN: &[u8] = b"wip v1";
+
+/// A Bulletproofs+ range proof, aggregated over a power-of-two number of values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeProofPlus {
+    a: CompressedRistretto,
+    wip: WeightedInnerProductProof,
+}
+
+/// The weighted inner product argument of a range proof.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WeightedInnerProductProof {
+    a1: CompressedRistretto,
+    b: CompressedRistretto,
+    r1: Scalar,
+    s1: Scalar,
+    d1: Scalar,
+    l_vec: Vec<CompressedRistretto>,
+    r_vec: Vec<CompressedRistretto>,
+}
+
+/// The verification equation of a proof, as the scalars of a multi-scalar multiplication.
+struct VerificationScalars {
+    g_scalars: Vec<Scalar>,
+    h_scalars: Vec<Scalar>,
+    base_scalar: Scalar,
+    blinding_scalar: Scalar,
+    proof_scalars: Vec<Scalar>,
+    proof_points: Vec<CompressedRistretto>,
+}
+
+impl RangeProofPlus {
+    /// Serialize the proof as `A || A1 || B || r1 || s1 || d1 || L_0 || R_0 || ... `.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 * (6 + 2 * self.wip.l_vec.len()));
+        bytes.extend_from_slice(self.a.as_bytes());
+        bytes.extend_from_slice(self.wip.a1.as_bytes());
+        bytes.extend_from_slice(self.wip.b.as_bytes());
+        bytes.extend_from_slice(self.wip.r1.as_bytes());
+        bytes.extend_from_slice(self.wip.s1.as_bytes());
+        bytes.extend_from_slice(self.wip.d1.as_bytes());
+        for (l, r) in self.wip.l_vec.iter().zip(self.wip.r_vec.iter()) {
+            bytes.extend_from_slice(l.as_bytes());
+            bytes.extend_from_slice(r.as_bytes());
+        }
+        bytes
+    }
+
+    /// Deserialize a proof, rejecting non-canonical scalars.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() % 32 != 0 || bytes.len() < 6 * 32 || (bytes.len() / 32 - 6) % 2 != 0 {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let point = |i: usize| CompressedRistretto::from_slice(&bytes[i * 32..(i + 1) * 32]);
+        let scalar = |i: usize| {
+            let mut array = [0u8; 32];
+            array.copy_from_slice(&bytes[i * 32..(i + 1) * 32]);
+            Scalar::from_canonical_bytes(array).c(d!(ZeiError::DeserializationError))
+        };
+
+        let lg_n = (bytes.len() / 32 - 6) / 2;
+        if lg_n >= 32 {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let mut l_vec = Vec::with_capacity(lg_n);
+        let mut r_vec = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            l_vec.push(point(6 + 2 * i));
+            r_vec.push(point(7 + 2 * i));
+        }
+
+        Ok(RangeProofPlus {
+            a: point(0),
+            wip: WeightedInnerProductProof {
+                a1: point(1),
+                b: point(2),
+                r1: scalar(3)?,
+                s1: scalar(4)?,
+                d1: scalar(5)?,
+                l_vec,
+                r_vec,
+            },
+        })
+    }
+
+    /// Replay the transcript of the proof, and return its verification equation.
+    fn verification_scalars(
+        &self,
+        transcript: &mut Transcript,
+        commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<VerificationScalars> {
+        let m = commitments.len();
+        let nm = n * m;
+        let wip = &self.wip;
+        let lg_nm = wip.l_vec.len();
+        if lg_nm >= 32 || nm != 1 << lg_nm || wip.r_vec.len() != lg_nm {
+            return Err(eg!(ZeiError::RangeProofVerifyError));
+        }
+
+        // 1. replay the transcript of the range proof
+        append_domain(transcript, n, m);
+        for v in commitments.iter() {
+            append_point(transcript, b"V", v);
+        }
+        validate_and_append_point(transcript, b"A", &self.a).c(d!())?;
+        let y = challenge_scalar(transcript, b"y");
+        let z = challenge_scalar(transcript, b"z");
+        let zz = z * z;
+
+        // 2. replay the transcript of the weighted inner product argument
+        transcript.append_message(b"dom-sep", WEIGHTED_INNER_PRODUCT_DOMAIN);
+        transcript.append_u64(b"n", nm as u64);
+        let mut challenges = Vec::with_capacity(lg_nm);
+        for (l, r) in wip.l_vec.iter().zip(wip.r_vec.iter()) {
+            validate_and_append_point(transcript, b"L", l).c(d!())?;
+            validate_and_append_point(transcript, b"R", r).c(d!())?;
+            challenges.push(challenge_scalar(transcript, b"e"));
+        }
+        validate_and_append_point(transcript, b"A1", &wip.a1).c(d!())?;
+        validate_and_append_point(transcript, b"B", &wip.b).c(d!())?;
+        let e = challenge_scalar(transcript, b"e");
+        let e_sq = e * e;
+
+        // 3. the scalars of the folded generators, where `s[i]` multiplies the i-th `G`
+        // and `s[nm - 1 - i]`, its inverse, the i-th `H`
+        let mut challenges_inv = challenges.clone();
+        let all_inv = Scalar::batch_invert(&mut challenges_inv);
+        let challenges_sq = challenges.iter().map(|c| c * c).collect_vec();
+        let challenges_inv_sq = challenges_inv.iter().map(|c| c * c).collect_vec();
+        let mut s = Vec::with_capacity(nm);
+        s.push(all_inv);
+        for i in 1..nm {
+            let lg_i = (usize::BITS - 1 - i.leading_zeros()) as usize;
+            s.push(s[i - (1 << lg_i)] * challenges_sq[lg_nm - 1 - lg_i]);
+        }
+
+        // 4. the scalars of the generators
+        let y_powers = powers(&y, nm + 2);
+        let y_inv = y.invert();
+        let mut y_inv_power = Scalar::one();
+        let mut z_powers = Vec::with_capacity(m);
+        let mut z_power = Scalar::one();
+        let mut g_scalars = Vec::with_capacity(nm);
+        let mut h_scalars = Vec::with_capacity(nm);
+        for j in 0..m {
+            z_power *= zz;
+            z_powers.push(z_power);
+            let mut power_of_2 = Scalar::one();
+            for k in 0..n {
+                let i = j * n + k;
+                g_scalars.push(-z * e_sq - wip.r1 * e * s[i] * y_inv_power);
+                h_scalars.push(
+                    e_sq * (z_power * power_of_2 * y_powers[nm - i] + z)
+                        - wip.s1 * e * s[nm - 1 - i],
+                );
+                y_inv_power *= y_inv;
+                power_of_2 += power_of_2;
+            }
+        }
+
+        // 5. the scalars of the bases and of the elements of the proof
+        let sum_2 = if n == 64 {
+            Scalar::from(u64::MAX)
+        } else {
+            Scalar::from((1u64 << n) - 1)
+        };
+        let sum_z: Scalar = z_powers.iter().sum();
+        let zeta = (z - zz) * y * sum_of_powers(&y, nm) - z * y_powers[nm + 1] * sum_2 * sum_z;
+        let base_scalar = e_sq * zeta - wip.r1 * y * wip.s1;
+        let blinding_scalar = -wip.d1;
+
+        let proof_scalars = [e_sq, e, Scalar::one()]
+            .into_iter()
+            .chain(challenges_sq.iter().map(|c| e_sq * c))
+            .chain(challenges_inv_sq.iter().map(|c| e_sq * c))
+            .chain(z_powers.iter().map(|z_j| e_sq * z_j * y_powers[nm + 1]))
+            .collect_vec();
+        let proof_points = [self.a, wip.a1, wip.b]
+            .into_iter()
+            .chain(wip.l_vec.iter().cloned())
+            .chain(wip.r_vec.iter().cloned())
+            .chain(commitments.iter().cloned())
+            .collect_vec();
+
+        Ok(VerificationScalars {
+            g_scalars,
+            h_scalars,
+            base_scalar,
+            blinding_scalar,
+            proof_scalars,
+            proof_points,
+        })
+    }
+}
+
+impl ZeiFromToBytes for RangeProofPlus {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+    fn zei_from_bytes(bytes: &[u8]) -> Result<RangeProofPlus> {
+        RangeProofPlus::from_bytes(bytes)
+    }
+}
+
+impl WeightedInnerProductProof {
+    /// Prove the knowledge of `a`, `b` and `alpha` such that
+    /// `P = <a, G> + <b, H> + (sum_i a_i * b_i * y^{i+1}) * g + alpha * h`.
+    #[allow(clippy::too_many_arguments)]
+    fn prove<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        transcript: &mut Transcript,
+        pc_gens: &PedersenGens,
+        y: &Scalar,
+        mut g_vec: Vec<RistrettoPoint>,
+        mut h_vec: Vec<RistrettoPoint>,
+        mut a_vec: Vec<Scalar>,
+        mut b_vec: Vec<Scalar>,
+        mut alpha: Scalar,
+    ) -> WeightedInnerProductProof {
+        let mut n = g_vec.len();
+        let (g, h) = (pc_gens.B, pc_gens.B_blinding);
+        let y_powers = powers(y, n + 1);
+
+        transcript.append_message(b"dom-sep", WEIGHTED_INNER_PRODUCT_DOMAIN);
+        transcript.append_u64(b"n", n as u64);
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut l_vec = Vec::with_capacity(lg_n);
+        let mut r_vec = Vec::with_capacity(lg_n);
+        while n > 1 {
+            n /= 2;
+            let (a1, a2) = a_vec.split_at(n);
+            let (b1, b2) = b_vec.split_at(n);
+            let (g1, g2) = g_vec.split_at(n);
+            let (h1, h2) = h_vec.split_at(n);
+            let y_n = y_powers[n];
+            let y_inv_n = y_n.invert();
+
+            let c_l = weighted_inner_product(a1, b2, &y_powers);
+            let c_r = y_n * weighted_inner_product(a2, b1, &y_powers);
+            let d_l = random_scalar(prng);
+            let d_r = random_scalar(prng);
+
+            let l = RistrettoPoint::multiscalar_mul(
+                a1.iter()
+                    .map(|a| a * y_inv_n)
+                    .chain(b2.iter().cloned())
+                    .chain([c_l, d_l]),
+                g2.iter().chain(h1.iter()).chain([&g, &h]),
+            )
+            .compress();
+            let r = RistrettoPoint::multiscalar_mul(
+                a2.iter()
+                    .map(|a| a * y_n)
+                    .chain(b1.iter().cloned())
+                    .chain([c_r, d_r]),
+                g1.iter().chain(h2.iter()).chain([&g, &h]),
+            )
+            .compress();
+            append_point(transcript, b"L", &l);
+            append_point(transcript, b"R", &r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let e = challenge_scalar(transcript, b"e");
+            let e_inv = e.invert();
+            let (e_y_inv_n, e_inv_y_n) = (e * y_inv_n, e_inv * y_n);
+
+            a_vec = (0..n).map(|i| a1[i] * e + a2[i] * e_inv_y_n).collect_vec();
+            b_vec = (0..n).map(|i| b1[i] * e_inv + b2[i] * e).collect_vec();
+            g_vec = (0..n)
+                .map(|i| {
+                    RistrettoPoint::vartime_multiscalar_mul([e_inv, e_y_inv_n], [g1[i], g2[i]])
+                })
+                .collect_vec();
+            h_vec = (0..n)
+                .map(|i| RistrettoPoint::vartime_multiscalar_mul([e, e_inv], [h1[i], h2[i]]))
+                .collect_vec();
+            alpha += d_l * e * e + d_r * e_inv * e_inv;
+        }
+
+        let (a, b) = (a_vec[0], b_vec[0]);
+        let r = random_scalar(prng);
+        let s = random_scalar(prng);
+        let delta = random_scalar(prng);
+        let eta = random_scalar(prng);
+        let a1 = RistrettoPoint::multiscalar_mul(
+            [r, s, y * (r * b + s * a), delta],
+            [g_vec[0], h_vec[0], g, h],
+        )
+        .compress();
+        let b_point = RistrettoPoint::multiscalar_mul([y * r * s, eta], [g, h]).compress();
+        append_point(transcript, b"A1", &a1);
+        append_point(transcript, b"B", &b_point);
+        let e = challenge_scalar(transcript, b"e");
+
+        WeightedInnerProductProof {
+            a1,
+            b: b_point,
+            r1: r + a * e,
+            s1: s + b * e,
+            d1: eta + delta * e + alpha * e * e,
+            l_vec,
+            r_vec,
+        }
+    }
+}
+
+/// Compute `[1, x, x^2, ..., x^{n-1}]`.
+fn powers(x: &Scalar, n: usize) -> Vec<Scalar> {
+    let mut powers = Vec::with_capacity(n);
+    let mut power = Scalar::one();
+    for _ in 0..n {
+        powers.push(power);
+        power *= x;
+    }
+    powers
+}
+
+/// Compute `sum_i a_i * b_i * y^{i+1}`, where `y_powers` are the powers of `y`.
+fn weighted_inner_product(a: &[Scalar], b: &[Scalar], y_powers: &[Scalar]) -> Scalar {
+    a.iter()
+        .zip(b.iter())
+        .zip(y_powers[1..].iter())
+        .map(|((a, b), y)| a * b * y)
+        .sum()
+}
+
+fn append_domain(transcript: &mut Transcript, n: usize, m: usize) {
+    transcript.append_message(b"dom-sep", RANGE_PROOF_PLUS_DOMAIN);
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", m as u64);
+}
+
+/// Check that the generators cover `m` values of `n` bits, for the sizes supported by the
+/// `bulletproofs` crate.
+fn check_sizes(bp_gens: &BulletproofGens, n: usize, m: usize) -> Result<()> {
+    if !(n == 8 || n == 16 || n == 32 || n == 64) || !m.is_power_of_two() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    if bp_gens.gens_capacity < n || bp_gens.party_capacity < m {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    Ok(())
+}
+
+/// The first `n` generators `G` and `H` of each of the first `m` parties of
+/// `bulletproofs::BulletproofGens`, in the order of the bits of the aggregated values.
+fn party_generators(n: usize, m: usize) -> (Vec<RistrettoPoint>, Vec<RistrettoPoint>) {
+    let mut g_vec = Vec::with_capacity(n * m);
+    let mut h_vec = Vec::with_capacity(n * m);
+    for j in 0..m {
+        let mut g_chain = GeneratorsChain::for_party(b'G', j);
+        let mut h_chain = GeneratorsChain::for_party(b'H', j);
+        for _ in 0..n {
+            g_vec.push(g_chain.next_point());
+            h_vec.push(h_chain.next_point());
+        }
+    }
+    (g_vec, h_vec)
+}
+
+/// Generate a Bulletproofs+ range proof that values committed using `blindings`
+/// are within [0..2^{`log_range_upper_bound`}-1].
+/// The number of values must be a power of two.
+pub fn prove_ranges_plus<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    bp_gens: &BulletproofGens,
+    transcript: &mut Transcript,
+    values: &[u64],
+    blindings: &[ZeiScalar],
+    log_range_upper_bound: usize,
+) -> Result<(RangeProofPlus, Vec<ZeiCompressedRistretto>)> {
+    let n = log_range_upper_bound;
+    let m = values.len();
+    check_sizes(bp_gens, n, m).c(d!(ZeiError::RangeProofProveError))?;
+    if blindings.len() != m || (n < 64 && values.iter().any(|v| v >> n != 0)) {
+        return Err(eg!(ZeiError::RangeProofProveError));
+    }
+    let nm = n * m;
+    let pc_gens = PedersenGens::default();
+    let (g_vec, h_vec) = party_generators(n, m);
+
+    // 1. commit to the values and to their bits
+    let commitments = values
+        .iter()
+        .zip(blindings.iter())
+        .map(|(v, gamma)| pc_gens.commit(Scalar::from(*v), gamma.0).compress())
+        .collect_vec();
+    append_domain(transcript, n, m);
+    for v in commitments.iter() {
+        append_point(transcript, b"V", v);
+    }
+
+    let a_l = values
+        .iter()
+        .flat_map(|v| (0..n).map(move |k| Scalar::from((v >> k) & 1)))
+        .collect_vec();
+    let a_r = a_l.iter().map(|a| a - Scalar::one()).collect_vec();
+    let alpha = random_scalar(prng);
+    let a = RistrettoPoint::multiscalar_mul(
+        a_l.iter().chain(a_r.iter()).chain([&alpha]),
+        g_vec
+            .iter()
+            .chain(h_vec.iter())
+            .chain([&pc_gens.B_blinding]),
+    )
+    .compress();
+    append_point(transcript, b"A", &a);
+    let y = challenge_scalar(transcript, b"y");
+    let z = challenge_scalar(transcript, b"z");
+    let zz = z * z;
+
+    // 2. the witness of the weighted inner product argument
+    let y_powers = powers(&y, nm + 2);
+    let mut a_hat = Vec::with_capacity(nm);
+    let mut b_hat = Vec::with_capacity(nm);
+    let mut alpha_hat = alpha;
+    let mut z_power = Scalar::one();
+    for (j, gamma) in blindings.iter().enumerate() {
+        z_power *= zz;
+        alpha_hat += z_power * y_powers[nm + 1] * gamma.0;
+        let mut power_of_2 = Scalar::one();
+        for k in 0..n {
+            let i = j * n + k;
+            a_hat.push(a_l[i] - z);
+            b_hat.push(a_r[i] + z_power * power_of_2 * y_powers[nm - i] + z);
+            power_of_2 += power_of_2;
+        }
+    }
+
+    // 3. the weighted inner product argument
+    let wip = WeightedInnerProductProof::prove(
+        prng, transcript, &pc_gens, &y, g_vec, h_vec, a_hat, b_hat, alpha_hat,
+    );
+
+    let commitments = commitments
+        .into_iter()
+        .map(ZeiCompressedRistretto)
+        .collect_vec();
+    Ok((RangeProofPlus { a, wip }, commitments))
+}
+
+/// Batch-verify a set of Bulletproofs+ range proofs
+/// State of transcripts should match the state just before each proof was computed
+pub fn batch_verify_ranges_plus<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    bp_gens: &BulletproofGens,
+    proofs: &[&RangeProofPlus],
+    transcripts: &mut [Transcript],
+    commitments: &[&[ZeiCompressedRistretto]],
+    log_range_upper_bound: usize,
+) -> Result<()> {
+    if proofs.len() != transcripts.len() || proofs.len() != commitments.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let n = log_range_upper_bound;
+    for slice in commitments {
+        check_sizes(bp_gens, n, slice.len()).c(d!(ZeiError::RangeProofVerifyError))?;
+    }
+    let max_m = commitments.iter().map(|c| c.len()).max().unwrap_or(0);
+
+    // 1. combine the verification equations with random weights
+    let mut g_scalars = vec![Scalar::zero(); n * max_m];
+    let mut h_scalars = vec![Scalar::zero(); n * max_m];
+    let mut base_scalar = Scalar::zero();
+    let mut blinding_scalar = Scalar::zero();
+    let mut proof_scalars = vec![];
+    let mut proof_points = vec![];
+    for ((proof, transcript), slice) in proofs
+        .iter()
+        .zip(transcripts.iter_mut())
+        .zip(commitments.iter())
+    {
+        let slice = slice.iter().map(|x| x.0).collect_vec();
+        let scalars = proof.verification_scalars(transcript, &slice, n).c(d!())?;
+        let c = random_scalar(prng);
+        for (acc, s) in g_scalars.iter_mut().zip(scalars.g_scalars.iter()) {
+            *acc += c * s;
+        }
+        for (acc, s) in h_scalars.iter_mut().zip(scalars.h_scalars.iter()) {
+            *acc += c * s;
+        }
+        base_scalar += c * scalars.base_scalar;
+        blinding_scalar += c * scalars.blinding_scalar;
+        proof_scalars.extend(scalars.proof_scalars.iter().map(|s| c * s));
+        proof_points.extend(scalars.proof_points.iter().map(|p| p.decompress()));
+    }
+
+    // 2. check the combined equation
+    let pc_gens = PedersenGens::default();
+    let (g_vec, h_vec) = party_generators(n, max_m);
+    let acc = RistrettoPoint::optional_multiscalar_mul(
+        g_scalars
+            .into_iter()
+            .chain(h_scalars)
+            .chain([base_scalar, blinding_scalar])
+            .chain(proof_scalars),
+        g_vec
+            .into_iter()
+            .chain(h_vec)
+            .chain([pc_gens.B, pc_gens.B_blinding])
+            .map(Some)
+            .chain(proof_points),
+    )
+    .c(d!(ZeiError::RangeProofVerifyError))?;
+
+    if acc.is_identity() {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::RangeProofVerifyError))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bulletproofs::{
+        plus::{batch_verify_ranges_plus, prove_ranges_plus, RangeProofPlus},
+        range::prove_ranges,
+    };
+    use ark_std::test_rng;
+    use bulletproofs::BulletproofGens;
+    use merlin::Transcript;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn range_proof_plus() {
+        let mut prng = test_rng();
+        let bp_gens = BulletproofGens::new(64, 8);
+
+        for (m, n) in [(1usize, 64usize), (2, 32), (8, 64), (4, 8)] {
+            let values = (0..m)
+                .map(|_| prng.next_u64() >> (64 - n))
+                .collect::<Vec<u64>>();
+            let blindings = (0..m)
+                .map(|_| RistrettoScalar::random(&mut prng))
+                .collect::<Vec<RistrettoScalar>>();
+            let (proof, commitments) = prove_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &mut Transcript::new(b"test"),
+                &values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+
+            // the same commitments as, and a smaller proof than, the Bulletproofs backend
+            let (bp_proof, bp_commitments) = prove_ranges(
+                &bp_gens,
+                &mut Transcript::new(b"test"),
+                &values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+            assert_eq!(commitments, bp_commitments);
+            assert!(proof.to_bytes().len() < bp_proof.to_bytes().len());
+            assert_eq!(
+                RangeProofPlus::from_bytes(&proof.to_bytes()).unwrap(),
+                proof
+            );
+
+            assert!(batch_verify_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut [Transcript::new(b"test")],
+                &[&commitments],
+                n
+            )
+            .is_ok());
+            assert!(batch_verify_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut [Transcript::new(b"another test")],
+                &[&commitments],
+                n
+            )
+            .is_err());
+
+            // commitments to other values
+            let other_values = values.iter().map(|v| v ^ 1).collect::<Vec<u64>>();
+            let (_, wrong_commitments) = prove_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &mut Transcript::new(b"test"),
+                &other_values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+            assert!(batch_verify_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut [Transcript::new(b"test")],
+                &[&wrong_commitments],
+                n
+            )
+            .is_err());
+
+            // a tampered proof
+            let mut bytes = proof.to_bytes();
+            bytes[3 * 32] ^= 1;
+            let tampered = RangeProofPlus::from_bytes(&bytes).unwrap();
+            assert!(batch_verify_ranges_plus(
+                &mut prng,
+                &bp_gens,
+                &[&tampered],
+                &mut [Transcript::new(b"test")],
+                &[&commitments],
+                n
+            )
+            .is_err());
+        }
+
+        // values out of the range, and sizes out of the generators
+        let blindings = [RistrettoScalar::random(&mut prng)];
+        assert!(prove_ranges_plus(
+            &mut prng,
+            &bp_gens,
+            &mut Transcript::new(b"test"),
+            &[1 << 32],
+            &blindings,
+            32
+        )
+        .is_err());
+        assert!(prove_ranges_plus(
+            &mut prng,
+            &BulletproofGens::new(32, 1),
+            &mut Transcript::new(b"test"),
+            &[1],
+            &blindings,
+            64
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn batch_range_proof_plus() {
+        let mut prng = test_rng();
+        let bp_gens = BulletproofGens::new(32, 4);
+
+        let blindings = (0..4)
+            .map(|_| RistrettoScalar::random(&mut prng))
+            .collect::<Vec<RistrettoScalar>>();
+        let (proof1, commitments1) = prove_ranges_plus(
+            &mut prng,
+            &bp_gens,
+            &mut Transcript::new(b"test1"),
+            &[1, 2, 3, 4],
+            &blindings,
+            32,
+        )
+        .unwrap();
+        let (proof2, commitments2) = prove_ranges_plus(
+            &mut prng,
+            &bp_gens,
+            &mut Transcript::new(b"test2"),
+            &[5, 6],
+            &blindings[..2],
+            32,
+        )
+        .unwrap();
+
+        let mut transcripts = [Transcript::new(b"test1"), Transcript::new(b"test2")];
+        assert!(batch_verify_ranges_plus(
+            &mut prng,
+            &bp_gens,
+            &[&proof1, &proof2],
+            &mut transcripts,
+            &[&commitments1, &commitments2],
+            32
+        )
+        .is_ok());
+
+        let mut transcripts = [Transcript::new(b"test1"), Transcript::new(b"test2")];
+        assert!(batch_verify_ranges_plus(
+            &mut prng,
+            &bp_gens,
+            &[&proof1, &proof2],
+            &mut transcripts,
+            &[&commitments2, &commitments1],
+            32
+        )
+        .is_err());
+    }
+}
@@ -2,6 +2,7 @@
 //!
 //! This is mostly a wrapper.
 
+use crate::bulletproofs::plus::{batch_verify_ranges_plus, prove_ranges_plus, RangeProofPlus};
 use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
 use merlin::Transcript;
 use zei_algebra::prelude::*;
@@ -32,6 +33,32 @@ pub fn prove_ranges(
     Ok((proof, commitments))
 }
 
+/// Generate a Bulletproof range proof as in `prove_ranges`, with the randomness of `prng`
+/// instead of the thread RNG, e.g., to reproduce a proof from a seed.
+pub fn prove_ranges_with_rng<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    bp_gens: &BulletproofGens,
+    transcript: &mut Transcript,
+    values: &[u64],
+    blindings: &[Scalar],
+    log_range_upper_bound: usize,
+) -> Result<(RangeProof, Vec<CompressedRistretto>)> {
+    let blindings = blindings.iter().map(|s| s.0).collect_vec();
+    let pc_gens = PedersenGens::default();
+    let (proof, coms) = RangeProof::prove_multiple_with_rng(
+        bp_gens,
+        &pc_gens,
+        transcript,
+        values,
+        &blindings,
+        log_range_upper_bound,
+        prng,
+    )
+    .c(d!(ZeiError::RangeProofProveError))?;
+    let commitments = coms.iter().map(|x| CompressedRistretto(*x)).collect_vec();
+    Ok((proof, commitments))
+}
+
 /// Batch-verify a set bulletproof range proofs
 /// State of transcripts should match the state just before each proof was computed
 pub fn batch_verify_ranges<R: CryptoRng + RngCore>(
@@ -64,3 +91,175 @@ pub fn batch_verify_ranges<R: CryptoRng + RngCore>(
     )
     .c(d!(ZeiError::RangeProofVerifyError))
 }
+
+/// The version byte that prefixes the Bulletproofs+ range proofs in `VersionedRangeProof`.
+/// The Bulletproofs range proofs have no prefix, and their length is a multiple of 32.
+const RANGE_PROOF_PLUS_VERSION: u8 = 2;
+
+/// The backends of the range proofs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RangeProofBackend {
+    /// Bulletproofs.
+    #[default]
+    Bulletproofs,
+    /// Bulletproofs+, with smaller proofs and a faster verification.
+    BulletproofsPlus,
+}
+
+/// A range proof of either backend, whose byte representation keeps the format of the
+/// Bulletproofs range proofs, so that proofs created before Bulletproofs+ still verify.
+#[derive(Clone, Debug)]
+pub enum VersionedRangeProof {
+    /// A Bulletproofs range proof.
+    Bulletproofs(RangeProof),
+    /// A Bulletproofs+ range proof.
+    BulletproofsPlus(RangeProofPlus),
+}
+
+impl VersionedRangeProof {
+    /// Return the backend of the proof.
+    pub fn backend(&self) -> RangeProofBackend {
+        match self {
+            VersionedRangeProof::Bulletproofs(_) => RangeProofBackend::Bulletproofs,
+            VersionedRangeProof::BulletproofsPlus(_) => RangeProofBackend::BulletproofsPlus,
+        }
+    }
+
+    /// Serialize the proof, with a version byte for Bulletproofs+.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            VersionedRangeProof::Bulletproofs(proof) => proof.to_bytes(),
+            VersionedRangeProof::BulletproofsPlus(proof) => {
+                let mut bytes = vec![RANGE_PROOF_PLUS_VERSION];
+                bytes.extend_from_slice(&proof.to_bytes());
+                bytes
+            }
+        }
+    }
+
+    /// Deserialize a proof of either backend.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() % 32 == 0 {
+            RangeProof::from_bytes(bytes)
+                .map(VersionedRangeProof::Bulletproofs)
+                .map_err(|_| eg!(ZeiError::DeserializationError))
+        } else if bytes[0] == RANGE_PROOF_PLUS_VERSION {
+            RangeProofPlus::from_bytes(&bytes[1..])
+                .map(VersionedRangeProof::BulletproofsPlus)
+                .c(d!())
+        } else {
+            Err(eg!(ZeiError::DeserializationError))
+        }
+    }
+}
+
+impl PartialEq for VersionedRangeProof {
+    fn eq(&self, other: &VersionedRangeProof) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for VersionedRangeProof {}
+
+impl ZeiFromToBytes for VersionedRangeProof {
+    fn zei_to_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+    fn zei_from_bytes(bytes: &[u8]) -> Result<VersionedRangeProof> {
+        VersionedRangeProof::from_bytes(bytes)
+    }
+}
+
+/// Generate a range proof as in `prove_ranges_with_rng`, with the given backend.
+pub fn prove_ranges_versioned<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    backend: RangeProofBackend,
+    bp_gens: &BulletproofGens,
+    transcript: &mut Transcript,
+    values: &[u64],
+    blindings: &[Scalar],
+    log_range_upper_bound: usize,
+) -> Result<(VersionedRangeProof, Vec<CompressedRistretto>)> {
+    match backend {
+        RangeProofBackend::Bulletproofs => prove_ranges_with_rng(
+            prng,
+            bp_gens,
+            transcript,
+            values,
+            blindings,
+            log_range_upper_bound,
+        )
+        .map(|(proof, coms)| (VersionedRangeProof::Bulletproofs(proof), coms))
+        .c(d!()),
+        RangeProofBackend::BulletproofsPlus => prove_ranges_plus(
+            prng,
+            bp_gens,
+            transcript,
+            values,
+            blindings,
+            log_range_upper_bound,
+        )
+        .map(|(proof, coms)| (VersionedRangeProof::BulletproofsPlus(proof), coms))
+        .c(d!()),
+    }
+}
+
+/// Batch-verify a set of range proofs of either backend, each backend in its own batch.
+/// State of transcripts should match the state just before each proof was computed
+pub fn batch_verify_versioned_ranges<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    bp_gens: &BulletproofGens,
+    proofs: &[&VersionedRangeProof],
+    transcripts: &mut [Transcript],
+    commitments: &[&[CompressedRistretto]],
+    log_range_upper_bound: usize,
+) -> Result<()> {
+    if proofs.len() != transcripts.len() || proofs.len() != commitments.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let mut bp_proofs = vec![];
+    let mut bp_transcripts = vec![];
+    let mut bp_commitments = vec![];
+    let mut plus_proofs = vec![];
+    let mut plus_transcripts = vec![];
+    let mut plus_commitments = vec![];
+    for ((proof, transcript), slice) in proofs
+        .iter()
+        .zip(transcripts.iter())
+        .zip(commitments.iter())
+    {
+        match proof {
+            VersionedRangeProof::Bulletproofs(proof) => {
+                bp_proofs.push(proof);
+                bp_transcripts.push(transcript.clone());
+                bp_commitments.push(*slice);
+            }
+            VersionedRangeProof::BulletproofsPlus(proof) => {
+                plus_proofs.push(proof);
+                plus_transcripts.push(transcript.clone());
+                plus_commitments.push(*slice);
+            }
+        }
+    }
+
+    if !bp_proofs.is_empty() {
+        batch_verify_ranges(
+            prng,
+            bp_gens,
+            &bp_proofs,
+            &mut bp_transcripts,
+            &bp_commitments,
+            log_range_upper_bound,
+        )
+        .c(d!())?;
+    }
+    batch_verify_ranges_plus(
+        prng,
+        bp_gens,
+        &plus_proofs,
+        &mut plus_transcripts,
+        &plus_commitments,
+        log_range_upper_bound,
+    )
+    .c(d!())
+}
@@ -0,0 +1,467 @@
+//! Module for the streaming verification of aggregated Bulletproof range proofs
+//!
+//! The verifier of the `bulletproofs` crate checks an aggregated range proof with one
+//! multi-scalar multiplication over all the `2 * n * m` generators, which requires the
+//! generators, the scalars, and the temporary buffers of the multiplication to be held in
+//! memory at the same time. For proofs aggregating hundreds of outputs, this dominates the
+//! memory usage of the verifier.
+//!
+//! This module verifies the same equation, but splits the multiplication into chunks of a
+//! fixed size and sums up the partial results. The generators are derived chunk by chunk from
+//! the same SHAKE256 generator chain as `bulletproofs::BulletproofGens`, and the verification
+//! scalars are computed on the fly, so the peak memory only depends on the chunk size and the
+//! logarithm of the aggregation size.
+
+use bulletproofs::{PedersenGens, RangeProof};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
+use merlin::Transcript;
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+use zei_algebra::prelude::*;
+use zei_algebra::ristretto::CompressedRistretto as ZeiCompressedRistretto;
+
+/// The default number of generators processed at once.
+pub const DEFAULT_STREAMING_CHUNK_SIZE: usize = 256;
+
+/// The generator chain of `bulletproofs::BulletproofGens` for a given label.
+pub(crate) struct GeneratorsChain {
+    reader: <Shake256 as ExtendableOutput>::Reader,
+}
+
+impl GeneratorsChain {
+    fn new(label: &[u8]) -> Self {
+        let mut shake = Shake256::default();
+        shake.update(b"GeneratorsChain");
+        shake.update(label);
+        GeneratorsChain {
+            reader: shake.finalize_xof(),
+        }
+    }
+
+    /// The chain of the `G` or `H` generators of the party `party_index`.
+    pub(crate) fn for_party(prefix: u8, party_index: usize) -> Self {
+        let mut label = [prefix, 0, 0, 0, 0];
+        label[1..5].copy_from_slice(&(party_index as u32).to_le_bytes());
+        Self::new(&label)
+    }
+
+    pub(crate) fn next_point(&mut self) -> RistrettoPoint {
+        let mut uniform_bytes = [0u8; 64];
+        self.reader.read(&mut uniform_bytes);
+        RistrettoPoint::from_uniform_bytes(&uniform_bytes)
+    }
+}
+
+/// The sections of a range proof, in the order of its byte representation.
+struct RangeProofSections {
+    a: CompressedRistretto,
+    s: CompressedRistretto,
+    t_1: CompressedRistretto,
+    t_2: CompressedRistretto,
+    t_x: Scalar,
+    t_x_blinding: Scalar,
+    e_blinding: Scalar,
+    l_vec: Vec<CompressedRistretto>,
+    r_vec: Vec<CompressedRistretto>,
+    ipp_a: Scalar,
+    ipp_b: Scalar,
+}
+
+impl RangeProofSections {
+    fn parse(proof: &RangeProof) -> Result<Self> {
+        let bytes = proof.to_bytes();
+        if bytes.len() % 32 != 0 || bytes.len() < 11 * 32 || (bytes.len() / 32 - 9) % 2 != 0 {
+            return Err(eg!(ZeiError::DeserializationError));
+        }
+        let point = |i: usize| CompressedRistretto::from_slice(&bytes[i * 32..(i + 1) * 32]);
+        let scalar = |i: usize| {
+            let mut array = [0u8; 32];
+            array.copy_from_slice(&bytes[i * 32..(i + 1) * 32]);
+            Scalar::from_canonical_bytes(array).c(d!(ZeiError::DeserializationError))
+        };
+
+        let lg_n = (bytes.len() / 32 - 9) / 2;
+        let mut l_vec = Vec::with_capacity(lg_n);
+        let mut r_vec = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            l_vec.push(point(7 + 2 * i));
+            r_vec.push(point(8 + 2 * i));
+        }
+
+        Ok(RangeProofSections {
+            a: point(0),
+            s: point(1),
+            t_1: point(2),
+            t_2: point(3),
+            t_x: scalar(4)?,
+            t_x_blinding: scalar(5)?,
+            e_blinding: scalar(6)?,
+            l_vec,
+            r_vec,
+            ipp_a: scalar(7 + 2 * lg_n)?,
+            ipp_b: scalar(8 + 2 * lg_n)?,
+        })
+    }
+}
+
+pub(crate) fn append_point(
+    transcript: &mut Transcript,
+    label: &'static [u8],
+    point: &CompressedRistretto,
+) {
+    transcript.append_message(label, point.as_bytes());
+}
+
+pub(crate) fn validate_and_append_point(
+    transcript: &mut Transcript,
+    label: &'static [u8],
+    point: &CompressedRistretto,
+) -> Result<()> {
+    if point.is_identity() {
+        return Err(eg!(ZeiError::RangeProofVerifyError));
+    }
+    append_point(transcript, label, point);
+    Ok(())
+}
+
+fn append_scalar(transcript: &mut Transcript, label: &'static [u8], scalar: &Scalar) {
+    transcript.append_message(label, scalar.as_bytes());
+}
+
+pub(crate) fn challenge_scalar(transcript: &mut Transcript, label: &'static [u8]) -> Scalar {
+    let mut buf = [0u8; 64];
+    transcript.challenge_bytes(label, &mut buf);
+    Scalar::from_bytes_mod_order_wide(&buf)
+}
+
+pub(crate) fn random_scalar<R: CryptoRng + RngCore>(prng: &mut R) -> Scalar {
+    let mut buf = [0u8; 64];
+    prng.fill_bytes(&mut buf);
+    Scalar::from_bytes_mod_order_wide(&buf)
+}
+
+/// Compute `sum_{i=0}^{n-1} x^i`.
+pub(crate) fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
+    let mut sum = Scalar::zero();
+    let mut power = Scalar::one();
+    for _ in 0..n {
+        sum += power;
+        power *= x;
+    }
+    sum
+}
+
+/// Verify an aggregated Bulletproof range proof with a bounded amount of memory,
+/// processing `chunk_size` generators at a time.
+/// The state of the transcript should match the state just before the proof was computed.
+pub fn verify_range_streaming<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    proof: &RangeProof,
+    transcript: &mut Transcript,
+    commitments: &[ZeiCompressedRistretto],
+    log_range_upper_bound: usize,
+    chunk_size: usize,
+) -> Result<()> {
+    let n = log_range_upper_bound;
+    let m = commitments.len();
+    if !(n == 8 || n == 16 || n == 32 || n == 64) || m == 0 || chunk_size == 0 {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let proof = RangeProofSections::parse(proof).c(d!())?;
+    let nm = n * m;
+    let lg_nm = proof.l_vec.len();
+    if lg_nm >= 32 || nm != 1 << lg_nm {
+        return Err(eg!(ZeiError::RangeProofVerifyError));
+    }
+
+    // 1. replay the transcript of the range proof
+    transcript.append_message(b"dom-sep", b"rangeproof v1");
+    transcript.append_u64(b"n", n as u64);
+    transcript.append_u64(b"m", m as u64);
+    for v in commitments.iter() {
+        append_point(transcript, b"V", &v.0);
+    }
+    validate_and_append_point(transcript, b"A", &proof.a).c(d!())?;
+    validate_and_append_point(transcript, b"S", &proof.s).c(d!())?;
+    let y = challenge_scalar(transcript, b"y");
+    let z = challenge_scalar(transcript, b"z");
+    let zz = z * z;
+    validate_and_append_point(transcript, b"T_1", &proof.t_1).c(d!())?;
+    validate_and_append_point(transcript, b"T_2", &proof.t_2).c(d!())?;
+    let x = challenge_scalar(transcript, b"x");
+    append_scalar(transcript, b"t_x", &proof.t_x);
+    append_scalar(transcript, b"t_x_blinding", &proof.t_x_blinding);
+    append_scalar(transcript, b"e_blinding", &proof.e_blinding);
+    let w = challenge_scalar(transcript, b"w");
+    let c = random_scalar(prng);
+
+    // 2. replay the transcript of the inner product proof
+    transcript.append_message(b"dom-sep", b"ipp v1");
+    transcript.append_u64(b"n", nm as u64);
+    // the challenges are squared after their inverses are computed
+    let mut u_sq = Vec::with_capacity(lg_nm);
+    for (l, r) in proof.l_vec.iter().zip(proof.r_vec.iter()) {
+        validate_and_append_point(transcript, b"L", l).c(d!())?;
+        validate_and_append_point(transcript, b"R", r).c(d!())?;
+        u_sq.push(challenge_scalar(transcript, b"u"));
+    }
+    let mut u_inv_sq = u_sq.clone();
+    let all_inv = Scalar::batch_invert(&mut u_inv_sq);
+    for (u, u_inv) in u_sq.iter_mut().zip(u_inv_sq.iter_mut()) {
+        *u = *u * *u;
+        *u_inv = *u_inv * *u_inv;
+    }
+    // the i-th verification scalar of the inner product proof, without storing all of them
+    let s_at = |i: usize| {
+        let mut s = all_inv;
+        for (j, u) in u_sq.iter().rev().enumerate() {
+            if (i >> j) & 1 == 1 {
+                s *= u;
+            }
+        }
+        s
+    };
+
+    // 3. the part of the verification equation that does not involve the generators
+    let sum_2 = if n == 64 {
+        Scalar::from(u64::MAX)
+    } else {
+        Scalar::from((1u64 << n) - 1)
+    };
+    let delta = (z - zz) * sum_of_powers(&y, nm) - z * zz * sum_2 * sum_of_powers(&z, m);
+    let (a, b) = (proof.ipp_a, proof.ipp_b);
+    let basepoint_scalar = w * (proof.t_x - a * b) + c * (delta - proof.t_x);
+
+    let mut z_powers = Vec::with_capacity(m);
+    let mut z_power = Scalar::one();
+    for _ in 0..m {
+        z_powers.push(z_power);
+        z_power *= z;
+    }
+
+    let pc_gens = PedersenGens::default();
+    let fixed_scalars = [Scalar::one(), x, c * x, c * x * x]
+        .into_iter()
+        .chain(u_sq.iter().cloned())
+        .chain(u_inv_sq.iter().cloned())
+        .chain([-proof.e_blinding - c * proof.t_x_blinding, basepoint_scalar])
+        .chain(z_powers.iter().map(|z_j| c * zz * z_j));
+    let fixed_points = [proof.a, proof.s, proof.t_1, proof.t_2]
+        .into_iter()
+        .chain(proof.l_vec.iter().cloned())
+        .chain(proof.r_vec.iter().cloned())
+        .map(|p| p.decompress())
+        .chain([Some(pc_gens.B_blinding), Some(pc_gens.B)])
+        .chain(commitments.iter().map(|v| v.0.decompress()));
+    let mut acc = RistrettoPoint::optional_multiscalar_mul(fixed_scalars, fixed_points)
+        .c(d!(ZeiError::RangeProofVerifyError))?;
+
+    // 4. the part of the verification equation over the generators, chunk by chunk
+    let y_inv = y.invert();
+    let mut y_inv_power = Scalar::one();
+    let mut scalars = Vec::with_capacity(2 * chunk_size);
+    let mut points = Vec::with_capacity(2 * chunk_size);
+    let mut i = 0usize;
+    for (j, z_j) in z_powers.iter().enumerate() {
+        let mut g_chain = GeneratorsChain::for_party(b'G', j);
+        let mut h_chain = GeneratorsChain::for_party(b'H', j);
+        let mut power_of_2 = Scalar::one();
+        for _ in 0..n {
+            scalars.push(-z - a * s_at(i));
+            points.push(g_chain.next_point());
+            scalars.push(z + y_inv_power * (zz * z_j * power_of_2 - b * s_at(nm - 1 - i)));
+            points.push(h_chain.next_point());
+
+            if points.len() >= 2 * chunk_size {
+                acc += RistrettoPoint::vartime_multiscalar_mul(scalars.drain(..), points.drain(..));
+            }
+            i += 1;
+            y_inv_power *= y_inv;
+            power_of_2 += power_of_2;
+        }
+    }
+    if !points.is_empty() {
+        acc += RistrettoPoint::vartime_multiscalar_mul(scalars.drain(..), points.drain(..));
+    }
+
+    if acc.is_identity() {
+        Ok(())
+    } else {
+        Err(eg!(ZeiError::RangeProofVerifyError))
+    }
+}
+
+/// Verify a set of aggregated Bulletproof range proofs one after the other with a bounded
+/// amount of memory. State of transcripts should match the state just before each proof was computed.
+pub fn batch_verify_ranges_streaming<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    proofs: &[&RangeProof],
+    transcripts: &mut [Transcript],
+    commitments: &[&[ZeiCompressedRistretto]],
+    log_range_upper_bound: usize,
+    chunk_size: usize,
+) -> Result<()> {
+    if proofs.len() != transcripts.len() || proofs.len() != commitments.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    for ((proof, transcript), commitments) in proofs
+        .iter()
+        .zip(transcripts.iter_mut())
+        .zip(commitments.iter())
+    {
+        verify_range_streaming(
+            prng,
+            proof,
+            transcript,
+            commitments,
+            log_range_upper_bound,
+            chunk_size,
+        )
+        .c(d!())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bulletproofs::{
+        range::{batch_verify_ranges, prove_ranges},
+        streaming::{
+            batch_verify_ranges_streaming, verify_range_streaming, DEFAULT_STREAMING_CHUNK_SIZE,
+        },
+    };
+    use ark_std::test_rng;
+    use bulletproofs::BulletproofGens;
+    use merlin::Transcript;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn streaming_range_verification() {
+        let mut prng = test_rng();
+        let bp_gens = BulletproofGens::new(64, 8);
+
+        for (m, n) in [(1usize, 64usize), (2, 32), (8, 64), (4, 16)] {
+            let values = (0..m)
+                .map(|_| prng.next_u64() >> (64 - n))
+                .collect::<Vec<u64>>();
+            let blindings = (0..m)
+                .map(|_| RistrettoScalar::random(&mut prng))
+                .collect::<Vec<RistrettoScalar>>();
+            let (proof, commitments) = prove_ranges(
+                &bp_gens,
+                &mut Transcript::new(b"test"),
+                &values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+
+            let mut transcripts = [Transcript::new(b"test")];
+            assert!(batch_verify_ranges(
+                &mut prng,
+                &bp_gens,
+                &[&proof],
+                &mut transcripts,
+                &[&commitments],
+                n
+            )
+            .is_ok());
+
+            for chunk_size in [1, 7, DEFAULT_STREAMING_CHUNK_SIZE] {
+                assert!(verify_range_streaming(
+                    &mut prng,
+                    &proof,
+                    &mut Transcript::new(b"test"),
+                    &commitments,
+                    n,
+                    chunk_size
+                )
+                .is_ok());
+            }
+
+            assert!(verify_range_streaming(
+                &mut prng,
+                &proof,
+                &mut Transcript::new(b"another test"),
+                &commitments,
+                n,
+                DEFAULT_STREAMING_CHUNK_SIZE
+            )
+            .is_err());
+
+            // commitments to other values
+            let other_values = values.iter().map(|v| v ^ 1).collect::<Vec<u64>>();
+            let (_, wrong_commitments) = prove_ranges(
+                &bp_gens,
+                &mut Transcript::new(b"test"),
+                &other_values,
+                &blindings,
+                n,
+            )
+            .unwrap();
+            assert!(verify_range_streaming(
+                &mut prng,
+                &proof,
+                &mut Transcript::new(b"test"),
+                &wrong_commitments,
+                n,
+                DEFAULT_STREAMING_CHUNK_SIZE
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn batch_streaming_range_verification() {
+        let mut prng = test_rng();
+        let bp_gens = BulletproofGens::new(32, 4);
+
+        let blindings = (0..4)
+            .map(|_| RistrettoScalar::random(&mut prng))
+            .collect::<Vec<RistrettoScalar>>();
+        let (proof1, commitments1) = prove_ranges(
+            &bp_gens,
+            &mut Transcript::new(b"test1"),
+            &[1, 2, 3, 4],
+            &blindings,
+            32,
+        )
+        .unwrap();
+        let (proof2, commitments2) = prove_ranges(
+            &bp_gens,
+            &mut Transcript::new(b"test2"),
+            &[5, 6],
+            &blindings[..2],
+            32,
+        )
+        .unwrap();
+
+        let mut transcripts = [Transcript::new(b"test1"), Transcript::new(b"test2")];
+        assert!(batch_verify_ranges_streaming(
+            &mut prng,
+            &[&proof1, &proof2],
+            &mut transcripts,
+            &[&commitments1, &commitments2],
+            32,
+            16
+        )
+        .is_ok());
+
+        let mut transcripts = [Transcript::new(b"test1"), Transcript::new(b"test2")];
+        assert!(batch_verify_ranges_streaming(
+            &mut prng,
+            &[&proof1, &proof2],
+            &mut transcripts,
+            &[&commitments2, &commitments1],
+            32,
+            16
+        )
+        .is_err());
+    }
+}
@@ -2,17 +2,21 @@ use crate::anon_creds::{
     verify_pok, Attribute,
     Attribute::{Hidden, Revealed},
     Credential, CredentialComm, CredentialCommRandomizer, CredentialIssuerPK, CredentialPoK,
-    CredentialUserSK, POK_LABEL,
+    CredentialSig, CredentialUserSK, POK_LABEL,
 };
 use crate::basic::{
-    elgamal::{elgamal_encrypt, ElGamalCiphertext, ElGamalEncKey},
+    elgamal::{elgamal_encrypt, ElGamalCiphertext, ElGamalDecKey, ElGamalEncKey},
     matrix_sigma::SigmaTranscript,
 };
+use digest::Digest;
 use merlin::Transcript;
 use zei_algebra::{prelude::*, traits::Pairing};
 
 const CAC_REVEAL_PROOF_DOMAIN: &[u8] = b"Confidential AC Reveal PoK";
 const CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE: &[u8] = b"Confidential AC Reveal PoK New Instance";
+const CAC_AGGREGATION_WEIGHT: &[u8] = b"Confidential AC aggregation weight";
+const CAC_BATCHING_WEIGHT: &[u8] = b"Confidential AC batching weight";
+const CAC_DESIGNATED_VERIFIER_KEY: &[u8] = b"Confidential AC designated verifier key";
 
 /// The transcript methods used in confidential anonymous credentials.
 pub trait CACTranscript {
@@ -39,6 +43,8 @@ pub trait CACTranscript {
     fn append_ciphertext<P: Pairing>(&mut self, ct: &ElGamalCiphertext<P::G1>);
     /// Append a commitment to the transcript.
     fn append_commitment<P: Pairing>(&mut self, cm: &CredentialComm<P::G1>);
+    /// Derive `n` scalars from the transcript, for combining equations.
+    fn get_weights<S: Scalar>(&mut self, label: &'static [u8], n: usize) -> Vec<S>;
 }
 
 impl CACTranscript for Transcript {
@@ -97,16 +103,33 @@ impl CACTranscript for Transcript {
         self.append_group_element(b"sigma1", &cm.0.sigma1);
         self.append_group_element(b"sigma2", &cm.0.sigma2);
     }
+    fn get_weights<S: Scalar>(&mut self, label: &'static [u8], n: usize) -> Vec<S> {
+        (0..n)
+            .map(|_| {
+                let mut buffer = [0u8; 32];
+                self.challenge_bytes(label, &mut buffer);
+                let mut hash = sha2::Sha512::new();
+                hash.update(&buffer[..]);
+                S::from_hash(hash)
+            })
+            .collect()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 /// Confidential anonymous credential reveal proof
+///
+/// The proof commitments of the first components `r * G` of the ciphertexts are aggregated
+/// into one, with weights derived from the transcript before the commitments, so that the
+/// proof has one group element plus one per revealed attribute, instead of two per attribute.
 pub struct CACPoK<G1, G2, S> {
     /// The proof of knowledge.
     pub pok: CredentialPoK<G2, S>,
-    /// The randomizers for the individual attributes.
-    pub cm_ct: Vec<ElGamalCiphertext<G1>>,
-    /// The responses for individual attributes.
+    /// The aggregated commitment for the first components of the ciphertexts.
+    pub cm_e1: G1,
+    /// The commitments for the second components of the ciphertexts of individual attributes.
+    pub cm_e2: Vec<G1>,
+    /// The responses for the randomness of the ciphertexts of individual attributes.
     pub response_rands: Vec<S>,
 }
 
@@ -126,6 +149,40 @@ impl<G1: Group, G2: Group, S: Scalar> ConfidentialAC<G1, G2, S> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// A proof of knowledge of the secret key of the designated verifier, which the prover simulates.
+pub struct CACTrapdoorProof<G1, S> {
+    /// The commitment, `response * G - challenge * vk`.
+    pub commitment: G1,
+    /// The part of the challenge of the reveal proof that is left to the trapdoor.
+    pub challenge: S,
+    /// The response.
+    pub response: S,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Confidential anonymous credential reveal proof for a designated verifier.
+///
+/// It proves that the ciphertexts encrypt the attributes of the credential, or that the prover
+/// knows the secret key of the verifier, which is the trapdoor: the challenge is split between
+/// the reveal proof and the simulated proof of the trapdoor. The verifier, who can simulate
+/// such a proof for any attributes, is convinced, but the proof convinces nobody else.
+pub struct CACDesignatedPoK<G1, G2, S> {
+    /// The reveal proof, for its part of the challenge.
+    pub pok: CACPoK<G1, G2, S>,
+    /// The simulated proof of knowledge of the trapdoor.
+    pub trapdoor: CACTrapdoorProof<G1, S>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Confidential anonymous credentials for a designated verifier (attributes and a proof).
+pub struct DesignatedConfidentialAC<G1, G2, S> {
+    /// The ciphertexts of the revealed attributes.
+    pub cts: Vec<ElGamalCiphertext<G1>>,
+    /// The proof of knowledge.
+    pub pok: CACDesignatedPoK<G1, G2, S>,
+}
+
 /// Selectively open some attributes committed in `cm` to ciphertexts, where `\vec{attrs}` lists
 /// the attributes, `cm` is the commitment, `rand` is the randomizer used in the commitment,
 /// `\vec{reveal_map}` describes whether an attribute should be revealed or not, `ek` is the
@@ -141,6 +198,63 @@ pub fn confidential_open_comm<R: CryptoRng + RngCore, P: Pairing>(
     ek: &ElGamalEncKey<P::G1>,
     m: &[u8],
 ) -> Result<ConfidentialAC<P::G1, P::G2, P::ScalarField>> {
+    let (cts, pok, _) = confidential_open_comm_with_trapdoor::<_, P>(
+        prng, usk, credential, cm, rand, reveal_map, ek, m, None,
+    )
+    .c(d!())?;
+    Ok(ConfidentialAC { cts, pok })
+}
+
+/// Selectively open some attributes committed in `cm` to ciphertexts as in
+/// `confidential_open_comm`, with a proof that only convinces the verifier whose ElGamal
+/// encryption key is `verifier_key`, so that the verifier cannot show it to third parties.
+#[allow(clippy::too_many_arguments)]
+pub fn confidential_designated_open_comm<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    usk: &CredentialUserSK<P::ScalarField>,
+    credential: &Credential<P::G1, P::G2, P::ScalarField>,
+    cm: &CredentialComm<P::G1>,
+    rand: &CredentialCommRandomizer<P::ScalarField>,
+    reveal_map: &[bool],
+    ek: &ElGamalEncKey<P::G1>,
+    verifier_key: &ElGamalEncKey<P::G1>,
+    m: &[u8],
+) -> Result<DesignatedConfidentialAC<P::G1, P::G2, P::ScalarField>> {
+    let (cts, pok, trapdoor) = confidential_open_comm_with_trapdoor::<_, P>(
+        prng,
+        usk,
+        credential,
+        cm,
+        rand,
+        reveal_map,
+        ek,
+        m,
+        Some(verifier_key),
+    )
+    .c(d!())?;
+    let trapdoor = trapdoor.c(d!(ZeiError::ParameterError))?;
+    Ok(DesignatedConfidentialAC {
+        cts,
+        pok: CACDesignatedPoK { pok, trapdoor },
+    })
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn confidential_open_comm_with_trapdoor<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    usk: &CredentialUserSK<P::ScalarField>,
+    credential: &Credential<P::G1, P::G2, P::ScalarField>,
+    cm: &CredentialComm<P::G1>,
+    rand: &CredentialCommRandomizer<P::ScalarField>,
+    reveal_map: &[bool],
+    ek: &ElGamalEncKey<P::G1>,
+    m: &[u8],
+    verifier_key: Option<&ElGamalEncKey<P::G1>>,
+) -> Result<(
+    Vec<ElGamalCiphertext<P::G1>>,
+    CACPoK<P::G1, P::G2, P::ScalarField>,
+    Option<CACTrapdoorProof<P::G1, P::ScalarField>>,
+)> {
     // 1. create ciphertext for all revealed attributes
     let mut cts = vec![];
     let mut ct_rands = vec![];
@@ -167,7 +281,7 @@ pub fn confidential_open_comm<R: CryptoRng + RngCore, P: Pairing>(
         }
     }
     let mut transcript = Transcript::new(CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
-    let pok = confidential_prove_pok::<_, P>(
+    let (pok, trapdoor) = confidential_prove_pok::<_, P>(
         &mut transcript,
         prng,
         usk,
@@ -179,9 +293,10 @@ pub fn confidential_open_comm<R: CryptoRng + RngCore, P: Pairing>(
         cts.as_slice(),
         ct_rands.as_slice(),
         m,
+        verifier_key,
     );
 
-    Ok(ConfidentialAC { cts, pok })
+    Ok((cts, pok, trapdoor))
 }
 
 /// Verify a confidential selective opening, that is, the ElGamal ciphertexts `\vec{ct}` correctly
@@ -194,6 +309,167 @@ pub fn confidential_verify_open<P: Pairing>(
     cts: &[ElGamalCiphertext<P::G1>],
     pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
     m: &[u8],
+) -> Result<()> {
+    check_open_lengths::<P>(ipk, reveal_map, cts, pok).c(d!())?;
+    let mut transcript = Transcript::new(CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
+    confidential_verify_pok::<P>(&mut transcript, ipk, ek, cm, cts, pok, reveal_map, m, None)
+        .c(d!())
+}
+
+/// Verify a confidential selective opening for a designated verifier, whose ElGamal encryption
+/// key is `verifier_key`, as in `confidential_verify_open`.
+#[allow(clippy::too_many_arguments)]
+pub fn confidential_designated_verify_open<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    ek: &ElGamalEncKey<P::G1>,
+    verifier_key: &ElGamalEncKey<P::G1>,
+    reveal_map: &[bool],
+    cm: &CredentialComm<P::G1>,
+    cts: &[ElGamalCiphertext<P::G1>],
+    pok: &CACDesignatedPoK<P::G1, P::G2, P::ScalarField>,
+    m: &[u8],
+) -> Result<()> {
+    check_open_lengths::<P>(ipk, reveal_map, cts, &pok.pok).c(d!())?;
+    let mut transcript = Transcript::new(CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
+    confidential_verify_pok::<P>(
+        &mut transcript,
+        ipk,
+        ek,
+        cm,
+        cts,
+        &pok.pok,
+        reveal_map,
+        m,
+        Some((verifier_key, &pok.trapdoor)),
+    )
+    .c(d!())
+}
+
+/// Simulate a confidential selective opening for the designated verifier with decryption key
+/// `verifier_dk`, which reveals `attrs` at the positions set in `reveal_map`, for a commitment
+/// to no credential of the issuer. It shows that the proofs for a designated verifier are not
+/// transferable: the verifier could have produced them.
+pub fn confidential_designated_simulate_open<R: CryptoRng + RngCore, P: Pairing>(
+    prng: &mut R,
+    verifier_dk: &ElGamalDecKey<P::ScalarField>,
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    ek: &ElGamalEncKey<P::G1>,
+    reveal_map: &[bool],
+    attrs: &[P::ScalarField],
+    m: &[u8],
+) -> Result<(
+    CredentialComm<P::G1>,
+    DesignatedConfidentialAC<P::G1, P::G2, P::ScalarField>,
+)> {
+    if reveal_map.len() != ipk.num_attrs()
+        || reveal_map.iter().filter(|b| **b).count() != attrs.len()
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let base = P::G1::get_base();
+    let verifier_key = ElGamalEncKey(base.mul(&verifier_dk.0));
+    let cts = attrs
+        .iter()
+        .map(|attr| elgamal_encrypt(attr, &P::ScalarField::random(prng), ek))
+        .collect_vec();
+    // a commitment `(sigma1, k * sigma1)` whose discrete logarithm `k` is known
+    let k = P::ScalarField::random(prng);
+    let sigma1 = base.mul(&P::ScalarField::random(prng));
+    let cm = CredentialComm(CredentialSig {
+        sigma1,
+        sigma2: sigma1.mul(&k),
+    });
+
+    let mut transcript = Transcript::new(CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
+    transcript.cac_init::<P>(ipk, ek, &cm, &cts);
+    transcript.append_message(POK_LABEL, m);
+    transcript.append_group_element(CAC_DESIGNATED_VERIFIER_KEY, &verifier_key.0);
+    let weights = transcript.get_weights::<P::ScalarField>(CAC_AGGREGATION_WEIGHT, cts.len());
+
+    // the reveal proof is simulated for a challenge chosen in advance
+    let challenge = P::ScalarField::random(prng);
+    let response_t = P::ScalarField::random(prng);
+    let response_sk = P::ScalarField::random(prng);
+    let response_attrs = (0..ipk.num_attrs())
+        .map(|_| P::ScalarField::random(prng))
+        .collect_vec();
+    let response_rands = (0..cts.len())
+        .map(|_| P::ScalarField::random(prng))
+        .collect_vec();
+
+    let revealed_responses = response_attrs
+        .iter()
+        .zip(reveal_map.iter())
+        .filter(|(_, b)| **b)
+        .map(|(z, _)| z);
+    let mut cm_e2 = vec![];
+    let mut rand_aggregated = P::ScalarField::zero();
+    let mut e1_aggregated = P::G1::get_identity();
+    for (ct, z_attr, z_rand, weight) in izip!(
+        cts.iter(),
+        revealed_responses,
+        response_rands.iter(),
+        weights.iter()
+    ) {
+        let e2 = base
+            .mul(z_attr)
+            .add(&ek.0.mul(z_rand))
+            .sub(&ct.e2.mul(&challenge));
+        transcript.append_proof_commitment(&e2);
+        cm_e2.push(e2);
+        rand_aggregated.add_assign(&weight.mul(z_rand));
+        e1_aggregated = e1_aggregated.add(&ct.e1.mul(weight));
+    }
+    let cm_e1 = base
+        .mul(&rand_aggregated)
+        .sub(&e1_aggregated.mul(&challenge));
+    transcript.append_proof_commitment(&cm_e1);
+
+    // e(sigma1, p) = e(sigma2, G2)^c holds for p = (k * c) * G2
+    let mut blinding = ipk
+        .xx2
+        .mul(&challenge)
+        .add(&ipk.gen2.mul(&response_t.sub(&k.mul(&challenge))))
+        .add(&ipk.zz2.mul(&response_sk));
+    for (y2, z_attr) in ipk.yy2.iter().zip(response_attrs.iter()) {
+        blinding = blinding.add(&y2.mul(z_attr));
+    }
+    transcript.append_proof_commitment(&blinding);
+
+    // the proof of the trapdoor is honest, for the rest of the challenge
+    let r = P::ScalarField::random(prng);
+    let commitment = base.mul(&r);
+    transcript.append_proof_commitment(&commitment);
+    let trapdoor_challenge = transcript.get_challenge::<P::ScalarField>().sub(&challenge);
+    let response = trapdoor_challenge.mul(&verifier_dk.0).add(&r);
+
+    let pok = CACDesignatedPoK {
+        pok: CACPoK {
+            pok: CredentialPoK {
+                blinding,
+                response_t,
+                response_sk,
+                response_attrs,
+            },
+            cm_e1,
+            cm_e2,
+            response_rands,
+        },
+        trapdoor: CACTrapdoorProof {
+            commitment,
+            challenge: trapdoor_challenge,
+            response,
+        },
+    };
+    Ok((cm, DesignatedConfidentialAC { cts, pok }))
+}
+
+// Check the lengths of the ciphertexts and of the proof against the reveal map.
+fn check_open_lengths<P: Pairing>(
+    ipk: &CredentialIssuerPK<P::G1, P::G2>,
+    reveal_map: &[bool],
+    cts: &[ElGamalCiphertext<P::G1>],
+    pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
 ) -> Result<()> {
     let n = cts.len();
     let revealed_count = reveal_map
@@ -203,18 +479,16 @@ pub fn confidential_verify_open<P: Pairing>(
         return Err(eg!(ZeiError::ParameterError));
     }
     if n > ipk.num_attrs()
-        || n != pok.cm_ct.len()
+        || n != pok.cm_e2.len()
         || n != pok.response_rands.len()
         || n != revealed_count
     {
         return Err(eg!(ZeiError::IdentityRevealVerifyError));
     }
-
-    let mut transcript = Transcript::new(CAC_REVEAL_PROOF_NEW_TRANSCRIPT_INSTANCE);
-
-    confidential_verify_pok::<P>(&mut transcript, ipk, ek, cm, cts, pok, reveal_map, m).c(d!())
+    Ok(())
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub(crate) fn confidential_prove_pok<R: CryptoRng + RngCore, P: Pairing>(
     transcript: &mut Transcript,
     prng: &mut R,
@@ -227,15 +501,25 @@ pub(crate) fn confidential_prove_pok<R: CryptoRng + RngCore, P: Pairing>(
     cts: &[ElGamalCiphertext<P::G1>],
     ct_rands: &[P::ScalarField],
     m: &[u8],
-) -> CACPoK<P::G1, P::G2, P::ScalarField> {
+    verifier_key: Option<&ElGamalEncKey<P::G1>>,
+) -> (
+    CACPoK<P::G1, P::G2, P::ScalarField>,
+    Option<CACTrapdoorProof<P::G1, P::ScalarField>>,
+) {
     transcript.cac_init::<P>(ipk, ek, cm, cts);
     transcript.append_message(POK_LABEL, m); // SoK
+    if let Some(vk) = verifier_key {
+        transcript.append_group_element(CAC_DESIGNATED_VERIFIER_KEY, &vk.0);
+    }
+    let weights = transcript.get_weights::<P::ScalarField>(CAC_AGGREGATION_WEIGHT, cts.len());
     let r_t = P::ScalarField::random(prng);
     let r_sk = P::ScalarField::random(prng);
     let mut r_attrs = vec![];
     let mut r_rands = vec![];
     let mut blinding = ipk.gen2.mul(&r_t).add(&ipk.zz2.mul(&r_sk));
-    let mut cm_cts = vec![];
+    let mut r_rand_aggregated = P::ScalarField::zero();
+    let mut cm_e2 = vec![];
+    let mut weights_iter = weights.iter();
     for (y2_i, attr) in ipk.yy2.iter().zip(attrs.iter()) {
         let r_attr = P::ScalarField::random(prng);
         let elem = y2_i.mul(&r_attr);
@@ -243,15 +527,34 @@ pub(crate) fn confidential_prove_pok<R: CryptoRng + RngCore, P: Pairing>(
         if let Revealed(_) = attr {
             let r_rand = P::ScalarField::random(prng);
             let ct_cm = elgamal_encrypt(&r_attr, &r_rand, ek);
-            transcript.append_proof_commitment(&ct_cm.e1);
             transcript.append_proof_commitment(&ct_cm.e2);
-            cm_cts.push(ct_cm);
+            cm_e2.push(ct_cm.e2);
+            if let Some(weight) = weights_iter.next() {
+                r_rand_aggregated.add_assign(&weight.mul(&r_rand));
+            }
             r_rands.push(r_rand);
         };
         r_attrs.push(r_attr);
     }
+    let cm_e1 = P::G1::get_base().mul(&r_rand_aggregated);
+    transcript.append_proof_commitment(&cm_e1);
     transcript.append_proof_commitment(&blinding);
-    let challenge = transcript.get_challenge::<P::ScalarField>();
+    // the proof of the trapdoor is simulated, and takes its part of the challenge
+    let trapdoor = verifier_key.map(|vk| {
+        let challenge = P::ScalarField::random(prng);
+        let response = P::ScalarField::random(prng);
+        let commitment = P::G1::get_base().mul(&response).sub(&vk.0.mul(&challenge));
+        transcript.append_proof_commitment(&commitment);
+        CACTrapdoorProof {
+            commitment,
+            challenge,
+            response,
+        }
+    });
+    let mut challenge = transcript.get_challenge::<P::ScalarField>();
+    if let Some(trapdoor) = &trapdoor {
+        challenge = challenge.sub(&trapdoor.challenge);
+    }
     let response_t = challenge.mul(rand.t).add(&r_t);
     let response_sk = challenge.mul(&usk.0).add(&r_sk);
     let mut response_attrs = vec![];
@@ -273,16 +576,20 @@ pub(crate) fn confidential_prove_pok<R: CryptoRng + RngCore, P: Pairing>(
         let response_rand = challenge.mul(ct_rand).add(r_rand);
         response_rands.push(response_rand);
     }
-    CACPoK {
-        pok: CredentialPoK {
-            blinding,
-            response_t,
-            response_sk,
-            response_attrs,
+    (
+        CACPoK {
+            pok: CredentialPoK {
+                blinding,
+                response_t,
+                response_sk,
+                response_attrs,
+            },
+            cm_e1,
+            cm_e2,
+            response_rands,
         },
-        cm_ct: cm_cts,
-        response_rands,
-    }
+        trapdoor,
+    )
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -295,17 +602,40 @@ fn confidential_verify_pok<P: Pairing>(
     pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
     reveal_map: &[bool],
     m: &[u8],
+    trapdoor: Option<(
+        &ElGamalEncKey<P::G1>,
+        &CACTrapdoorProof<P::G1, P::ScalarField>,
+    )>,
 ) -> Result<()> {
     transcript.cac_init::<P>(ipk, ek, cm, cts);
     transcript.append_message(POK_LABEL, m);
+    if let Some((vk, _)) = trapdoor {
+        transcript.append_group_element(CAC_DESIGNATED_VERIFIER_KEY, &vk.0);
+    }
+    let weights = transcript.get_weights::<P::ScalarField>(CAC_AGGREGATION_WEIGHT, cts.len());
 
-    for ct in pok.cm_ct.iter() {
-        transcript.append_proof_commitment(&ct.e1);
-        transcript.append_proof_commitment(&ct.e2);
+    for e2 in pok.cm_e2.iter() {
+        transcript.append_proof_commitment(e2);
     }
+    transcript.append_proof_commitment(&pok.cm_e1);
     transcript.append_proof_commitment(&pok.pok.blinding);
 
-    let challenge = transcript.get_challenge::<P::ScalarField>();
+    if let Some((_, trapdoor)) = trapdoor {
+        transcript.append_proof_commitment(&trapdoor.commitment);
+    }
+    let mut challenge = transcript.get_challenge::<P::ScalarField>();
+    if let Some((vk, trapdoor)) = trapdoor {
+        challenge = challenge.sub(&trapdoor.challenge);
+        // response * G = commitment + challenge * vk
+        let lhs = P::G1::get_base().mul(&trapdoor.response);
+        let rhs = trapdoor.commitment.add(&vk.0.mul(&trapdoor.challenge));
+        if lhs != rhs {
+            return Err(eg!(ZeiError::IdentityRevealVerifyError));
+        }
+    }
+    // one weight for each second component, and one for the aggregated first components.
+    let batching_weights =
+        transcript.get_weights::<P::ScalarField>(CAC_BATCHING_WEIGHT, cts.len() + 1);
 
     let mut attr_resps = vec![];
     for (z_attr, b) in pok.pok.response_attrs.iter().zip(reveal_map.iter()) {
@@ -314,12 +644,13 @@ fn confidential_verify_pok<P: Pairing>(
         }
     }
 
-    verify_ciphertext::<P>(
+    verify_ciphertexts::<P>(
         &challenge,
+        &weights,
+        &batching_weights,
         cts,
-        pok.cm_ct.as_slice(),
+        pok,
         attr_resps.as_slice(),
-        pok.response_rands.as_slice(),
         ek,
     )
     .c(d!())?;
@@ -329,22 +660,54 @@ fn confidential_verify_pok<P: Pairing>(
     verify_pok::<P>(ipk, cm, &pok.pok, hidden_attrs.as_slice(), &challenge).c(d!())
 }
 
-fn verify_ciphertext<P: Pairing>(
+/// Verify the ciphertexts of the revealed attributes with a single multi-exponentiation,
+/// which combines with the batching weights the equation of the aggregated first components
+/// `sum_i w_i * z_rand_i * G = c * sum_i w_i * ct_i.e1 + cm_e1` and the equations of the
+/// second components `z_attr_i * G + z_rand_i * ek = c * ct_i.e2 + cm_e2_i`.
+fn verify_ciphertexts<P: Pairing>(
     challenge: &P::ScalarField,
+    weights: &[P::ScalarField],
+    batching_weights: &[P::ScalarField],
     cts: &[ElGamalCiphertext<P::G1>],
-    ct_cms: &[ElGamalCiphertext<P::G1>],
+    pok: &CACPoK<P::G1, P::G2, P::ScalarField>,
     attrs: &[&P::ScalarField],
-    rands: &[P::ScalarField],
     ek: &ElGamalEncKey<P::G1>,
 ) -> Result<()> {
-    for (ct, ct_cm, attr, rand) in izip!(cts.iter(), ct_cms.iter(), attrs.iter(), rands.iter()) {
-        let enc = elgamal_encrypt::<P::G1>(attr, rand, ek);
-        if enc.e1 != ct.e1.mul(challenge).add(&ct_cm.e1) {
-            return Err(eg!(ZeiError::IdentityRevealVerifyError));
-        }
-        if enc.e2 != ct.e2.mul(challenge).add(&ct_cm.e2) {
-            return Err(eg!(ZeiError::IdentityRevealVerifyError));
-        }
+    let (e1_weight, e2_weights) = batching_weights
+        .split_last()
+        .c(d!(ZeiError::IdentityRevealVerifyError))?;
+
+    let base = P::G1::get_base();
+    let mut base_scalar = P::ScalarField::zero();
+    let mut ek_scalar = P::ScalarField::zero();
+    let mut scalars = vec![e1_weight.neg()];
+    let mut elems = vec![&pok.cm_e1];
+    for (ct, cm_e2, attr, rand, weight, e2_weight) in izip!(
+        cts.iter(),
+        pok.cm_e2.iter(),
+        attrs.iter(),
+        pok.response_rands.iter(),
+        weights.iter(),
+        e2_weights.iter()
+    ) {
+        let e1_factor = e1_weight.mul(weight);
+        base_scalar.add_assign(&e1_factor.mul(rand).add(&e2_weight.mul(*attr)));
+        ek_scalar.add_assign(&e2_weight.mul(rand));
+        scalars.push(e1_factor.mul(challenge).neg());
+        elems.push(&ct.e1);
+        scalars.push(e2_weight.mul(challenge).neg());
+        elems.push(&ct.e2);
+        scalars.push(e2_weight.neg());
+        elems.push(cm_e2);
+    }
+    scalars.push(base_scalar);
+    elems.push(&base);
+    scalars.push(ek_scalar);
+    elems.push(&ek.0);
+
+    let result = P::G1::multi_exp(&scalars.iter().collect_vec(), &elems);
+    if result != P::G1::get_identity() {
+        return Err(eg!(ZeiError::IdentityRevealVerifyError));
     }
     Ok(())
 }
@@ -355,20 +718,16 @@ pub(crate) mod test_helper {
         check_comm, commit_without_randomizer, grant_credential, issuer_keygen, user_keygen,
         Credential,
     };
+    use crate::attributes::{encode_attributes, Attribute};
     use crate::basic::elgamal::elgamal_key_gen;
-    use crate::confidential_anon_creds::{confidential_open_comm, confidential_verify_open};
+    use crate::confidential_anon_creds::{
+        confidential_designated_open_comm, confidential_designated_simulate_open,
+        confidential_designated_verify_open, confidential_open_comm, confidential_verify_open,
+    };
     use ark_std::test_rng;
     use zei_algebra::prelude::*;
     use zei_algebra::traits::Pairing;
 
-    pub(super) fn byte_slice_to_scalar<S: Scalar>(slice: &[u8]) -> S {
-        use digest::Digest;
-        use sha2::Sha512;
-        let mut hasher = Sha512::new();
-        hasher.update(slice);
-        S::from_hash(hasher)
-    }
-
     pub(crate) fn test_confidential_ac_reveal<P: Pairing>(reveal_map: &[bool]) {
         let proof_msg = b"Some message";
         let credential_addr = b"Some address";
@@ -378,10 +737,10 @@ pub(crate) mod test_helper {
         let (usk, upk) = user_keygen::<_, P>(&mut prng, &ipk);
         let (_, ek) = elgamal_key_gen::<_, P::G1>(&mut prng);
 
-        let mut attrs = Vec::new();
-        for i in 0..num_attr {
-            attrs.push(byte_slice_to_scalar(format!("attr{}!", i).as_bytes()));
-        }
+        let attrs = (0..num_attr)
+            .map(|i| Attribute::Utf8(format!("attr{}!", i)))
+            .collect_vec();
+        let attrs = encode_attributes::<P::ScalarField>(&attrs).unwrap();
 
         let sig = grant_credential::<_, P>(&mut prng, &isk, &upk, &attrs[..]).unwrap();
         let credential = Credential {
@@ -513,12 +872,163 @@ pub(crate) mod test_helper {
             res.unwrap_err(),
             "proof should fail, bad sok message"
         );
+
+        // The first components of the ciphertexts are aggregated
+        let revealed_count = reveal_map.iter().filter(|b| **b).count();
+        assert_eq!(conf_reveal_proof.pok.cm_e2.len(), revealed_count);
+
+        // Tampered ciphertexts, which are checked together
+        for i in 0..revealed_count {
+            let mut tampered_cts = conf_reveal_proof.cts.clone();
+            tampered_cts[i].e1 = tampered_cts[i].e1.add(&P::G1::get_base());
+            let res = confidential_verify_open::<P>(
+                &ipk,
+                &ek,
+                &reveal_map,
+                &cm,
+                &tampered_cts,
+                &conf_reveal_proof.pok,
+                proof_msg,
+            );
+            msg_eq!(
+                ZeiError::IdentityRevealVerifyError,
+                res.unwrap_err(),
+                "proof should fail, tampered ciphertext"
+            );
+        }
+        if revealed_count > 1 {
+            let mut swapped_cts = conf_reveal_proof.cts.clone();
+            swapped_cts.swap(0, 1);
+            assert!(confidential_verify_open::<P>(
+                &ipk,
+                &ek,
+                &reveal_map,
+                &cm,
+                &swapped_cts,
+                &conf_reveal_proof.pok,
+                proof_msg,
+            )
+            .is_err());
+        }
+    }
+
+    pub(crate) fn test_designated_confidential_ac_reveal<P: Pairing>(reveal_map: &[bool]) {
+        let proof_msg = b"Some message";
+        let num_attr = reveal_map.len();
+        let mut prng = test_rng();
+        let (isk, ipk) = issuer_keygen::<_, P>(&mut prng, num_attr);
+        let (usk, upk) = user_keygen::<_, P>(&mut prng, &ipk);
+        let (_, ek) = elgamal_key_gen::<_, P::G1>(&mut prng);
+        let (verifier_dk, verifier_key) = elgamal_key_gen::<_, P::G1>(&mut prng);
+
+        let attrs = (0..num_attr)
+            .map(|i| P::ScalarField::from(i as u32))
+            .collect_vec();
+        let sig = grant_credential::<_, P>(&mut prng, &isk, &upk, &attrs[..]).unwrap();
+        let credential = Credential {
+            sig,
+            attrs,
+            ipk: ipk.clone(),
+        };
+        let (cm, _, rand) =
+            commit_without_randomizer::<_, P>(&mut prng, &usk, &credential, b"Some address")
+                .unwrap();
+        let rand = rand.unwrap();
+
+        let reveal = confidential_designated_open_comm::<_, P>(
+            &mut prng,
+            &usk,
+            &credential,
+            &cm,
+            &rand,
+            reveal_map,
+            &ek,
+            &verifier_key,
+            proof_msg,
+        )
+        .unwrap();
+        assert!(confidential_designated_verify_open::<P>(
+            &ipk,
+            &ek,
+            &verifier_key,
+            reveal_map,
+            &cm,
+            &reveal.cts,
+            &reveal.pok,
+            proof_msg,
+        )
+        .is_ok());
+
+        // the proof is bound to the designated verifier
+        let (_, other_key) = elgamal_key_gen::<_, P::G1>(&mut prng);
+        assert!(confidential_designated_verify_open::<P>(
+            &ipk,
+            &ek,
+            &other_key,
+            reveal_map,
+            &cm,
+            &reveal.cts,
+            &reveal.pok,
+            proof_msg,
+        )
+        .is_err());
+        assert!(confidential_verify_open::<P>(
+            &ipk,
+            &ek,
+            reveal_map,
+            &cm,
+            &reveal.cts,
+            &reveal.pok.pok,
+            proof_msg,
+        )
+        .is_err());
+
+        // the verifier can produce a valid proof for attributes that were never granted
+        let fake_attrs = reveal_map
+            .iter()
+            .filter(|b| **b)
+            .map(|_| P::ScalarField::random(&mut prng))
+            .collect_vec();
+        let (fake_cm, fake_reveal) = confidential_designated_simulate_open::<_, P>(
+            &mut prng,
+            &verifier_dk,
+            &ipk,
+            &ek,
+            reveal_map,
+            &fake_attrs,
+            proof_msg,
+        )
+        .unwrap();
+        assert!(confidential_designated_verify_open::<P>(
+            &ipk,
+            &ek,
+            &verifier_key,
+            reveal_map,
+            &fake_cm,
+            &fake_reveal.cts,
+            &fake_reveal.pok,
+            proof_msg,
+        )
+        .is_ok());
+        assert!(confidential_designated_verify_open::<P>(
+            &ipk,
+            &ek,
+            &verifier_key,
+            reveal_map,
+            &fake_cm,
+            &fake_reveal.cts,
+            &fake_reveal.pok,
+            b"Some other message",
+        )
+        .is_err());
     }
 }
 
 #[cfg(test)]
 mod test_bls12_381 {
-    use crate::confidential_anon_creds::test_helper::test_confidential_ac_reveal;
+    use crate::confidential_anon_creds::test_helper::{
+        test_confidential_ac_reveal, test_designated_confidential_ac_reveal,
+    };
     use zei_algebra::bls12_381::BLSPairingEngine;
 
     #[test]
@@ -562,4 +1072,10 @@ mod test_bls12_381 {
             false, true, false, true, false, true, false, true, false, true,
         ]);
     }
+
+    #[test]
+    fn designated_confidential_reveal() {
+        test_designated_confidential_ac_reveal::<BLSPairingEngine>(&[false, true, true]);
+        test_designated_confidential_ac_reveal::<BLSPairingEngine>(&[true; 5]);
+    }
 }
@@ -0,0 +1,95 @@
+//! The module for deterministic provers, whose randomness is derived from a seed.
+//!
+//! Signing and proving with a [`DeterministicProver`] gives the same bytes on every run, which
+//! makes it possible to compare the serialization of notes and proofs across versions in tests.
+//! It must never be used in production, nor with a seed known to anyone else: whoever knows the
+//! seed can recover the blinding factors and therefore the witness of every proof.
+
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use zei_algebra::prelude::*;
+
+/// The length of the seed of a deterministic prover.
+pub const DETERMINISTIC_SEED_LENGTH: usize = 32;
+
+/// An RNG for reproducible signing and proving, derived from a seed or a transcript.
+#[derive(Clone, Debug)]
+pub struct DeterministicProver {
+    rng: ChaChaRng,
+}
+
+impl DeterministicProver {
+    /// Create a deterministic prover from a seed.
+    pub fn from_seed(seed: [u8; DETERMINISTIC_SEED_LENGTH]) -> Self {
+        DeterministicProver {
+            rng: ChaChaRng::from_seed(seed),
+        }
+    }
+
+    /// Create a deterministic prover from the state of a transcript, under `label`.
+    /// The transcript itself is left unchanged.
+    pub fn from_transcript(transcript: &Transcript, label: &'static [u8]) -> Self {
+        let mut transcript = transcript.clone();
+        let mut seed = [0u8; DETERMINISTIC_SEED_LENGTH];
+        transcript.challenge_bytes(label, &mut seed);
+        Self::from_seed(seed)
+    }
+
+    /// Derive an independent deterministic prover under `label`, e.g., for a sub-proof,
+    /// so that the randomness of the sub-proof does not depend on its position.
+    pub fn fork(&mut self, label: &'static [u8]) -> Self {
+        let mut transcript = Transcript::new(b"Zei Deterministic Prover");
+        let mut state = [0u8; DETERMINISTIC_SEED_LENGTH];
+        self.rng.fill_bytes(&mut state);
+        transcript.append_message(b"state", &state);
+        Self::from_transcript(&transcript, label)
+    }
+}
+
+impl RngCore for DeterministicProver {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for DeterministicProver {}
+
+#[cfg(test)]
+mod test {
+    use crate::deterministic::DeterministicProver;
+    use merlin::Transcript;
+    use zei_algebra::prelude::*;
+
+    #[test]
+    fn deterministic_prover() {
+        let mut a = DeterministicProver::from_seed([1u8; 32]);
+        let mut b = DeterministicProver::from_seed([1u8; 32]);
+        let mut c = DeterministicProver::from_seed([2u8; 32]);
+        let (x, y, z) = (a.next_u64(), b.next_u64(), c.next_u64());
+        assert_eq!(x, y);
+        assert_ne!(x, z);
+
+        let mut fork_a = a.fork(b"sub-proof");
+        let mut fork_b = b.fork(b"sub-proof");
+        assert_eq!(fork_a.next_u64(), fork_b.next_u64());
+        assert_ne!(a.fork(b"sub-proof").next_u64(), b.fork(b"other").next_u64());
+
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_message(b"message", b"hello");
+        let mut from_transcript = DeterministicProver::from_transcript(&transcript, b"prover");
+        let mut again = DeterministicProver::from_transcript(&transcript, b"prover");
+        assert_eq!(from_transcript.next_u64(), again.next_u64());
+    }
+}
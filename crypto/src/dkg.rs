@@ -0,0 +1,672 @@
+//! The module for distributed key generation (DKG) of threshold BLS and Schnorr keys.
+//!
+//! The protocol is the joint-Feldman DKG of Pedersen: each of the `n` participants deals a
+//! random secret with Feldman verifiable secret sharing, and the key of the group is the sum of
+//! the secrets of the qualified dealers, so that no dealer ever knows it. Any `threshold` of the
+//! key shares suffice to sign, while fewer learn nothing about the key.
+//!
+//! A [`DkgParticipant`] goes through three rounds:
+//! 1. [`DkgParticipant::deal`] returns the commitment to its polynomial, which is broadcast,
+//!    and a share for each participant, which is sent to it over a private channel;
+//! 2. [`DkgParticipant::complain`] checks the shares received against the commitments, and
+//!    returns a complaint, to broadcast, against each dealer whose share is missing or invalid;
+//! 3. [`DkgParticipant::respond`] reveals the shares of the complainers against the participant,
+//!    which are broadcast.
+//!
+//! [`DkgParticipant::finish`] then returns the [`ThresholdKeyShare`] of the participant. A dealer
+//! is disqualified if a complaint against it is not answered by a valid share, and the broadcast
+//! messages make up a [`DkgTranscript`], from which anyone can recompute the qualified dealers,
+//! the key of the group, and the verification keys of the participants.
+//!
+//! The protocol is generic over the group of the public keys. The aliases at the bottom of the
+//! module use BLS12-381 G2, so that the signatures are in G1, and Ristretto for threshold
+//! Schnorr signatures, see [`crate::threshold_sig`].
+
+use zei_algebra::{bls12_381::BLSG2, collections::BTreeMap, prelude::*, ristretto::RistrettoPoint};
+
+/// The commitment of a dealer to the coefficients of its sharing polynomial.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgCommitment<G> {
+    /// The index of the dealer, from 1 to `n`.
+    pub dealer: u32,
+    /// The coefficients times the base of the group, starting from the constant one.
+    pub coefs: Vec<G>,
+}
+
+/// The share of a participant in the secret of a dealer, sent over a private channel.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgShare<S> {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// The index of the recipient.
+    pub recipient: u32,
+    /// The evaluation of the sharing polynomial of the dealer at the index of the recipient.
+    pub value: S,
+}
+
+/// A complaint against a dealer whose share is missing or invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DkgComplaint {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// The index of the complainer.
+    pub complainer: u32,
+}
+
+/// The answer of a dealer to a complaint, which reveals the share of the complainer.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgComplaintResponse<S> {
+    /// The index of the dealer.
+    pub dealer: u32,
+    /// The index of the complainer.
+    pub complainer: u32,
+    /// The share of the complainer.
+    pub value: S,
+}
+
+/// The broadcast messages of a key generation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgTranscript<G: Group> {
+    /// The number of shares needed to sign.
+    pub threshold: u32,
+    /// The number of participants.
+    pub n: u32,
+    /// The commitments of the dealers.
+    pub commitments: Vec<DkgCommitment<G>>,
+    /// The complaints against the dealers.
+    pub complaints: Vec<DkgComplaint>,
+    /// The answers of the dealers to the complaints.
+    pub responses: Vec<DkgComplaintResponse<G::ScalarType>>,
+}
+
+/// The public outcome of a key generation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgPublicOutput<G> {
+    /// The indices of the qualified dealers, in increasing order.
+    pub qualified: Vec<u32>,
+    /// The public key of the group.
+    pub public_key: G,
+    /// The verification key of each participant, i.e., its key share times the base of the
+    /// group, the one of the participant `i` being at the position `i - 1`.
+    pub verification_keys: Vec<G>,
+}
+
+/// The key share of a participant, any `threshold` of which can sign for the group.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdKeyShare<G: Group> {
+    /// The index of the participant.
+    pub index: u32,
+    /// The secret key share of the participant.
+    pub secret_share: G::ScalarType,
+    /// The public outcome of the key generation.
+    pub output: DkgPublicOutput<G>,
+}
+
+/// The round of a key generation that a participant is in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DkgPhase {
+    /// The participant collects the commitments and the shares of the dealers.
+    Sharing,
+    /// The participant collects the complaints.
+    Complaining,
+    /// The participant collects the answers to the complaints.
+    Responding,
+    /// The key share of the participant was computed.
+    Finished,
+}
+
+/// A participant of a key generation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgParticipant<G: Group> {
+    index: u32,
+    phase: DkgPhase,
+    coefs: Vec<G::ScalarType>,
+    /// The shares received from the dealers, by the index of the dealer.
+    shares: BTreeMap<u32, G::ScalarType>,
+    transcript: DkgTranscript<G>,
+}
+
+impl<G: Group> DkgTranscript<G> {
+    /// Create an empty transcript for `n` participants, any `threshold` of which can sign.
+    pub fn new(threshold: u32, n: u32) -> Result<Self> {
+        if threshold == 0 || threshold > n {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        Ok(DkgTranscript {
+            threshold,
+            n,
+            commitments: vec![],
+            complaints: vec![],
+            responses: vec![],
+        })
+    }
+
+    /// Return the commitment of a dealer, if it was received.
+    pub fn commitment(&self, dealer: u32) -> Option<&DkgCommitment<G>> {
+        self.commitments.iter().find(|c| c.dealer == dealer)
+    }
+
+    /// Verify the transcript, and return the qualified dealers, the public key of the group,
+    /// and the verification keys of the participants.
+    pub fn verify(&self) -> Result<DkgPublicOutput<G>> {
+        for commitment in self.commitments.iter() {
+            self.check_commitment(commitment).c(d!())?;
+        }
+        if !self.commitments.iter().map(|c| c.dealer).all_unique() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        for complaint in self.complaints.iter() {
+            self.check_complaint(complaint).c(d!())?;
+        }
+        if !self.complaints.iter().all_unique() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        for response in self.responses.iter() {
+            self.check_response(response).c(d!())?;
+        }
+
+        let qualified = self
+            .commitments
+            .iter()
+            .filter(|commitment| {
+                self.complaints
+                    .iter()
+                    .filter(|complaint| complaint.dealer == commitment.dealer)
+                    .all(|complaint| {
+                        self.responses.iter().any(|response| {
+                            response.dealer == complaint.dealer
+                                && response.complainer == complaint.complainer
+                                && verify_share(commitment, complaint.complainer, &response.value)
+                        })
+                    })
+            })
+            .collect_vec();
+        if qualified.is_empty() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+
+        let public_key = qualified.iter().map(|c| c.coefs[0]).sum::<G>();
+        let verification_keys = (1..=self.n)
+            .map(|index| {
+                qualified
+                    .iter()
+                    .map(|c| eval_commitment(c, index))
+                    .sum::<G>()
+            })
+            .collect_vec();
+        let mut qualified = qualified.iter().map(|c| c.dealer).collect_vec();
+        qualified.sort_unstable();
+        Ok(DkgPublicOutput {
+            qualified,
+            public_key,
+            verification_keys,
+        })
+    }
+
+    fn check_index(&self, index: u32) -> Result<()> {
+        if index == 0 || index > self.n {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+
+    fn check_commitment(&self, commitment: &DkgCommitment<G>) -> Result<()> {
+        self.check_index(commitment.dealer).c(d!())?;
+        if commitment.coefs.len() != self.threshold as usize {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+
+    fn check_complaint(&self, complaint: &DkgComplaint) -> Result<()> {
+        self.check_index(complaint.dealer).c(d!())?;
+        self.check_index(complaint.complainer).c(d!())?;
+        if complaint.dealer == complaint.complainer || self.commitment(complaint.dealer).is_none() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+
+    fn check_response(&self, response: &DkgComplaintResponse<G::ScalarType>) -> Result<()> {
+        let complaint = DkgComplaint {
+            dealer: response.dealer,
+            complainer: response.complainer,
+        };
+        if !self.complaints.contains(&complaint) {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+}
+
+impl<G: Group> DkgParticipant<G> {
+    /// Start the key generation of the participant `index`, from 1 to `n`, with `n`
+    /// participants, any `threshold` of which can sign.
+    pub fn new<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        index: u32,
+        threshold: u32,
+        n: u32,
+    ) -> Result<Self> {
+        let transcript = DkgTranscript::new(threshold, n).c(d!())?;
+        transcript
+            .check_index(index)
+            .c(d!(ZeiError::ParameterError))?;
+        let coefs = (0..threshold)
+            .map(|_| G::ScalarType::random(prng))
+            .collect_vec();
+        Ok(DkgParticipant {
+            index,
+            phase: DkgPhase::Sharing,
+            coefs,
+            shares: BTreeMap::new(),
+            transcript,
+        })
+    }
+
+    /// Return the index of the participant.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Return the round that the participant is in.
+    pub fn phase(&self) -> DkgPhase {
+        self.phase
+    }
+
+    /// Return the broadcast messages received so far.
+    pub fn transcript(&self) -> &DkgTranscript<G> {
+        &self.transcript
+    }
+
+    /// Return the commitment of the participant, to broadcast, and the shares of the other
+    /// participants, to send over private channels.
+    pub fn deal(&mut self) -> Result<(DkgCommitment<G>, Vec<DkgShare<G::ScalarType>>)> {
+        self.check_phase(DkgPhase::Sharing).c(d!())?;
+        let base = G::get_base();
+        let commitment = DkgCommitment {
+            dealer: self.index,
+            coefs: self.coefs.iter().map(|coef| base.mul(coef)).collect(),
+        };
+        let shares = (1..=self.transcript.n)
+            .map(|recipient| DkgShare {
+                dealer: self.index,
+                recipient,
+                value: eval_poly(&self.coefs, recipient),
+            })
+            .collect_vec();
+
+        self.receive_commitment(&commitment).c(d!())?;
+        let (own_share, other_shares) = shares
+            .into_iter()
+            .partition::<Vec<_>, _>(|share| share.recipient == self.index);
+        self.receive_share(&own_share[0]).c(d!())?;
+        Ok((commitment, other_shares))
+    }
+
+    /// Process the commitment of a dealer.
+    pub fn receive_commitment(&mut self, commitment: &DkgCommitment<G>) -> Result<()> {
+        self.check_phase(DkgPhase::Sharing).c(d!())?;
+        self.transcript.check_commitment(commitment).c(d!())?;
+        match self.transcript.commitment(commitment.dealer) {
+            Some(received) if received == commitment => Ok(()),
+            Some(_) => Err(eg!(ZeiError::DkgError)),
+            None => {
+                self.transcript.commitments.push(commitment.clone());
+                Ok(())
+            }
+        }
+    }
+
+    /// Process the share of the participant in the secret of a dealer.
+    pub fn receive_share(&mut self, share: &DkgShare<G::ScalarType>) -> Result<()> {
+        self.check_phase(DkgPhase::Sharing).c(d!())?;
+        self.transcript.check_index(share.dealer).c(d!())?;
+        if share.recipient != self.index {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        match self.shares.get(&share.dealer) {
+            Some(received) if *received == share.value => Ok(()),
+            Some(_) => Err(eg!(ZeiError::DkgError)),
+            None => {
+                self.shares.insert(share.dealer, share.value);
+                Ok(())
+            }
+        }
+    }
+
+    /// End the sharing round, and return the complaints against the dealers whose share is
+    /// missing or does not match their commitment, to broadcast.
+    /// The dealers that did not broadcast a commitment are disqualified without a complaint.
+    pub fn complain(&mut self) -> Result<Vec<DkgComplaint>> {
+        self.check_phase(DkgPhase::Sharing).c(d!())?;
+        let index = self.index;
+        let complaints = self
+            .transcript
+            .commitments
+            .iter()
+            .filter(|commitment| match self.shares.get(&commitment.dealer) {
+                Some(share) => !verify_share(commitment, index, share),
+                None => true,
+            })
+            .map(|commitment| DkgComplaint {
+                dealer: commitment.dealer,
+                complainer: index,
+            })
+            .collect_vec();
+        for complaint in complaints.iter() {
+            self.shares.remove(&complaint.dealer);
+        }
+        self.transcript.complaints.extend_from_slice(&complaints);
+        self.phase = DkgPhase::Complaining;
+        Ok(complaints)
+    }
+
+    /// Process a complaint of another participant.
+    pub fn receive_complaint(&mut self, complaint: &DkgComplaint) -> Result<()> {
+        self.check_phase(DkgPhase::Complaining).c(d!())?;
+        if complaint.complainer == self.index {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        self.transcript.check_complaint(complaint).c(d!())?;
+        if !self.transcript.complaints.contains(complaint) {
+            self.transcript.complaints.push(*complaint);
+        }
+        Ok(())
+    }
+
+    /// End the complaint round, and return the shares of the complainers against the
+    /// participant, to broadcast.
+    pub fn respond(&mut self) -> Result<Vec<DkgComplaintResponse<G::ScalarType>>> {
+        self.check_phase(DkgPhase::Complaining).c(d!())?;
+        let responses = self
+            .transcript
+            .complaints
+            .iter()
+            .filter(|complaint| complaint.dealer == self.index)
+            .map(|complaint| DkgComplaintResponse {
+                dealer: self.index,
+                complainer: complaint.complainer,
+                value: eval_poly(&self.coefs, complaint.complainer),
+            })
+            .collect_vec();
+        self.transcript.responses.extend_from_slice(&responses);
+        self.phase = DkgPhase::Responding;
+        Ok(responses)
+    }
+
+    /// Process the answer of another dealer to a complaint.
+    pub fn receive_response(
+        &mut self,
+        response: &DkgComplaintResponse<G::ScalarType>,
+    ) -> Result<()> {
+        self.check_phase(DkgPhase::Responding).c(d!())?;
+        if response.dealer == self.index {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        self.transcript.check_response(response).c(d!())?;
+        if !self.transcript.responses.contains(response) {
+            self.transcript.responses.push(response.clone());
+        }
+        Ok(())
+    }
+
+    /// End the key generation, and return the key share of the participant.
+    pub fn finish(&mut self) -> Result<ThresholdKeyShare<G>> {
+        self.check_phase(DkgPhase::Responding).c(d!())?;
+        let output = self.transcript.verify().c(d!())?;
+
+        // the shares of the complaints of the participant are taken from the valid answers
+        for response in self.transcript.responses.iter() {
+            if response.complainer == self.index {
+                let commitment = self.transcript.commitment(response.dealer).c(d!())?;
+                if verify_share(commitment, self.index, &response.value) {
+                    self.shares.insert(response.dealer, response.value);
+                }
+            }
+        }
+        let mut secret_share = G::ScalarType::zero();
+        for dealer in output.qualified.iter() {
+            let share = self.shares.get(dealer).c(d!(ZeiError::DkgError))?;
+            secret_share = secret_share.add(share);
+        }
+
+        let key_share = ThresholdKeyShare {
+            index: self.index,
+            secret_share,
+            output,
+        };
+        key_share.verify().c(d!())?;
+        self.phase = DkgPhase::Finished;
+        Ok(key_share)
+    }
+
+    fn check_phase(&self, phase: DkgPhase) -> Result<()> {
+        if self.phase != phase {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+}
+
+impl<G: Group> ThresholdKeyShare<G> {
+    /// Return the verification key of the participant.
+    pub fn verification_key(&self) -> &G {
+        &self.output.verification_keys[self.index as usize - 1]
+    }
+
+    /// Check that the secret key share matches the verification key of the participant.
+    pub fn verify(&self) -> Result<()> {
+        if self.index == 0 || self.index as usize > self.output.verification_keys.len() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        if G::get_base().mul(&self.secret_share) != *self.verification_key() {
+            return Err(eg!(ZeiError::DkgError));
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate a polynomial at a participant index.
+fn eval_poly<S: Scalar>(coefs: &[S], index: u32) -> S {
+    let point = S::from(index);
+    coefs
+        .iter()
+        .rev()
+        .fold(S::zero(), |acc, coef| acc.mul(&point).add(coef))
+}
+
+/// Evaluate the committed polynomial of a dealer at a participant index, in the exponent.
+fn eval_commitment<G: Group>(commitment: &DkgCommitment<G>, index: u32) -> G {
+    let point = G::ScalarType::from(index);
+    commitment
+        .coefs
+        .iter()
+        .rev()
+        .fold(G::get_identity(), |acc, coef| acc.mul(&point).add(coef))
+}
+
+/// Check the share of a participant against the commitment of the dealer.
+fn verify_share<G: Group>(
+    commitment: &DkgCommitment<G>,
+    index: u32,
+    share: &G::ScalarType,
+) -> bool {
+    G::get_base().mul(share) == eval_commitment(commitment, index)
+}
+
+/// The commitment of a dealer of a threshold BLS key.
+pub type BLSDkgCommitment = DkgCommitment<BLSG2>;
+/// The transcript of the key generation of a threshold BLS key.
+pub type BLSDkgTranscript = DkgTranscript<BLSG2>;
+/// A participant of the key generation of a threshold BLS key.
+pub type BLSDkgParticipant = DkgParticipant<BLSG2>;
+/// A threshold BLS key share.
+pub type BLSThresholdKeyShare = ThresholdKeyShare<BLSG2>;
+
+/// A participant of the key generation of a threshold Schnorr key.
+pub type RistrettoDkgParticipant = DkgParticipant<RistrettoPoint>;
+/// A threshold Schnorr key share.
+pub type RistrettoThresholdKeyShare = ThresholdKeyShare<RistrettoPoint>;
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BLSDkgParticipant, BLSThresholdKeyShare, DkgComplaint, DkgComplaintResponse, DkgPhase,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::{BLSScalar, BLSG2},
+        prelude::*,
+    };
+
+    /// Run a key generation, where the dealer `cheater` sends an invalid share to the
+    /// participant 2, and answers the complaint if `cheater_responds`.
+    fn run_dkg(
+        threshold: u32,
+        n: u32,
+        cheater: Option<u32>,
+        cheater_responds: bool,
+    ) -> Vec<(BLSDkgParticipant, BLSThresholdKeyShare)> {
+        let mut prng = test_rng();
+        let mut participants = (1..=n)
+            .map(|index| BLSDkgParticipant::new(&mut prng, index, threshold, n).unwrap())
+            .collect_vec();
+
+        let dealings = participants
+            .iter_mut()
+            .map(|p| p.deal().unwrap())
+            .collect_vec();
+        for (commitment, shares) in dealings.iter() {
+            for share in shares.iter() {
+                let mut share = share.clone();
+                if Some(share.dealer) == cheater && share.recipient == 2 {
+                    share.value = share.value.add(&BLSScalar::one());
+                }
+                let recipient = &mut participants[share.recipient as usize - 1];
+                recipient.receive_commitment(commitment).unwrap();
+                recipient.receive_share(&share).unwrap();
+            }
+        }
+
+        let complaints: Vec<DkgComplaint> = participants
+            .iter_mut()
+            .flat_map(|p| p.complain().unwrap())
+            .collect();
+        for p in participants.iter_mut() {
+            for complaint in complaints.iter().filter(|c| c.complainer != p.index()) {
+                p.receive_complaint(complaint).unwrap();
+            }
+        }
+
+        let responses: Vec<DkgComplaintResponse<BLSScalar>> = participants
+            .iter_mut()
+            .flat_map(|p| {
+                let responses = p.respond().unwrap();
+                if Some(p.index()) == cheater && !cheater_responds {
+                    vec![]
+                } else {
+                    responses
+                }
+            })
+            .collect();
+        for p in participants.iter_mut() {
+            for response in responses.iter().filter(|r| r.dealer != p.index()) {
+                p.receive_response(response).unwrap();
+            }
+        }
+
+        participants
+            .into_iter()
+            .map(|mut p| {
+                let key_share = p.finish().unwrap();
+                (p, key_share)
+            })
+            .collect()
+    }
+
+    fn check_key_shares(results: &[(BLSDkgParticipant, BLSThresholdKeyShare)], threshold: u32) {
+        let output = &results[0].1.output;
+        for (participant, key_share) in results.iter() {
+            assert_eq!(participant.phase(), DkgPhase::Finished);
+            assert_eq!(&key_share.output, output);
+            assert_eq!(&participant.transcript().verify().unwrap(), output);
+            assert!(key_share.verify().is_ok());
+        }
+
+        // any `threshold` of the key shares interpolate the secret key of the group
+        for subset in results.iter().combinations(threshold as usize) {
+            let mut secret_key = BLSScalar::zero();
+            for (_, share_i) in subset.iter() {
+                let mut coef = BLSScalar::one();
+                for (_, share_j) in subset.iter().filter(|(_, s)| s.index != share_i.index) {
+                    let point_i = BLSScalar::from(share_i.index);
+                    let point_j = BLSScalar::from(share_j.index);
+                    coef = coef.mul(&point_j.mul(&point_j.sub(&point_i).inv().unwrap()));
+                }
+                secret_key = secret_key.add(&share_i.secret_share.mul(&coef));
+            }
+            assert_eq!(BLSG2::get_base().mul(&secret_key), output.public_key);
+        }
+    }
+
+    #[test]
+    fn honest_dkg() {
+        let results = run_dkg(3, 4, None, true);
+        assert_eq!(results[0].1.output.qualified, vec![1, 2, 3, 4]);
+        assert!(results[0].0.transcript().complaints.is_empty());
+        check_key_shares(&results, 3);
+    }
+
+    #[test]
+    fn dkg_with_complaints() {
+        // the cheating dealer answers the complaint and remains qualified
+        let results = run_dkg(3, 4, Some(3), true);
+        assert_eq!(results[0].0.transcript().complaints.len(), 1);
+        assert_eq!(results[0].1.output.qualified, vec![1, 2, 3, 4]);
+        check_key_shares(&results, 3);
+
+        // the cheating dealer does not answer the complaint and is disqualified
+        // the view of the cheating dealer, which kept its own answer, is left out
+        let results = run_dkg(3, 4, Some(3), false)
+            .into_iter()
+            .filter(|(p, _)| p.index() != 3)
+            .collect_vec();
+        assert_eq!(results[0].1.output.qualified, vec![1, 2, 4]);
+        check_key_shares(&results, 3);
+    }
+
+    #[test]
+    fn dkg_messages() {
+        let mut prng = test_rng();
+        assert!(BLSDkgParticipant::new(&mut prng, 0, 2, 3).is_err());
+        assert!(BLSDkgParticipant::new(&mut prng, 4, 2, 3).is_err());
+        assert!(BLSDkgParticipant::new(&mut prng, 1, 4, 3).is_err());
+
+        let mut p1 = BLSDkgParticipant::new(&mut prng, 1, 2, 3).unwrap();
+        let mut p2 = BLSDkgParticipant::new(&mut prng, 2, 2, 3).unwrap();
+        let (commitment, shares) = p2.deal().unwrap();
+
+        // a share for another participant, and a different commitment of the same dealer
+        msg_eq!(
+            ZeiError::DkgError,
+            p1.receive_share(&shares[1]).unwrap_err()
+        );
+        let mut other_commitment = commitment.clone();
+        other_commitment.coefs.reverse();
+        p1.receive_commitment(&commitment).unwrap();
+        msg_eq!(
+            ZeiError::DkgError,
+            p1.receive_commitment(&other_commitment).unwrap_err()
+        );
+
+        // the messages of a round are refused in the other rounds
+        p1.deal().unwrap();
+        p1.complain().unwrap();
+        msg_eq!(
+            ZeiError::DkgError,
+            p1.receive_share(&shares[0]).unwrap_err()
+        );
+        msg_eq!(ZeiError::DkgError, p1.finish().unwrap_err());
+    }
+}
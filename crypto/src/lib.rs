@@ -25,6 +25,8 @@ extern crate serde_derive;
 
 /// The module for anonymous credentials.
 pub mod anon_creds;
+/// The module for typed attributes of credentials and their encodings into scalars.
+pub mod attributes;
 /// The basic cryptographic primitives.
 pub mod basic;
 /// The library for Bulletproofs.
@@ -33,5 +35,17 @@ pub mod bulletproofs;
 pub mod confidential_anon_creds;
 /// The module for the delegated Schnorr protocol.
 pub mod delegated_schnorr;
+/// The module for deterministic provers for reproducible tests.
+pub mod deterministic;
+/// The module for distributed key generation of threshold BLS and Schnorr keys.
+pub mod dkg;
 /// The module for field simulation.
 pub mod field_simulation;
+/// The module for zero-knowledge proofs that relate commitments in different groups.
+pub mod proofs;
+/// The module for prover sessions that refuse to reuse randomness.
+pub mod prover_session;
+/// The module for threshold BLS and Schnorr signatures with the key shares of a key generation.
+pub mod threshold_sig;
+/// The module for time-lock encryption of escrowed amount openings.
+pub mod timelock;
@@ -0,0 +1,414 @@
+use crate::basic::matrix_sigma::SigmaTranscript;
+use crate::basic::pedersen_comm::PedersenCommitment;
+use merlin::Transcript;
+use zei_algebra::{bls12_381::BLSG1, prelude::*, ristretto::RistrettoPoint};
+
+/// The maximal number of bits of the committed value.
+pub const CROSS_GROUP_MAX_BITS: usize = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The proof that the commitments of a bit in both groups open to the same bit, which is
+/// an OR proof of "both open to 0" and "both open to 1".
+///
+/// The challenges are 128-bit integers, which are smaller than the orders of both groups,
+/// so that each of them is a valid challenge in both scalar fields.
+pub struct CrossGroupBitProof<G1: Group, G2: Group> {
+    /// The commitment of the bit in the first group.
+    pub com1: G1,
+    /// The commitment of the bit in the second group.
+    pub com2: G2,
+    /// The challenges of the branches for the bits 0 and 1,
+    /// whose XOR is the Fiat-Shamir challenge.
+    pub challenges: [u128; 2],
+    /// The responses of the branches in the first group.
+    pub responses1: [G1::ScalarType; 2],
+    /// The responses of the branches in the second group.
+    pub responses2: [G2::ScalarType; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The proof that two Pedersen commitments in different groups open to the same value,
+/// by committing to each bit of the value in both groups.
+pub struct CrossGroupEqualityProof<G1: Group, G2: Group> {
+    /// The proofs of the bits, from the least significant bit.
+    pub bits: Vec<CrossGroupBitProof<G1, G2>>,
+}
+
+/// The equality proof between a commitment over BLS12-381 G1, whose scalars are BLS scalars,
+/// and a commitment over Ristretto.
+pub type BLSRistrettoEqualityProof = CrossGroupEqualityProof<BLSG1, RistrettoPoint>;
+
+/// Initialize the transcript for the cross-group equality proof.
+fn init_cross_group_transcript<G1: Group, G2: Group, PC1, PC2>(
+    transcript: &mut Transcript,
+    pc_gens1: &PC1,
+    pc_gens2: &PC2,
+    com1: &G1,
+    com2: &G2,
+    n_bits: usize,
+) where
+    PC1: PedersenCommitment<G1>,
+    PC2: PedersenCommitment<G2>,
+{
+    transcript.append_message(
+        b"Cross group equality domain",
+        b"Cross group equality v.0.1",
+    );
+    transcript.append_u64(b"number of bits", n_bits as u64);
+    transcript.append_group_element(b"generator 1", &pc_gens1.generator());
+    transcript.append_group_element(b"blinding generator 1", &pc_gens1.blinding_generator());
+    transcript.append_group_element(b"generator 2", &pc_gens2.generator());
+    transcript.append_group_element(b"blinding generator 2", &pc_gens2.blinding_generator());
+    transcript.append_group_element(b"commitment 1", com1);
+    transcript.append_group_element(b"commitment 2", com2);
+}
+
+/// Append the commitments and the announcements of a bit, and return the challenge of the bit.
+fn bit_challenge<G1: Group, G2: Group>(
+    transcript: &mut Transcript,
+    com1: &G1,
+    com2: &G2,
+    announcements1: &[G1; 2],
+    announcements2: &[G2; 2],
+) -> u128 {
+    transcript.append_group_element(b"bit commitment 1", com1);
+    transcript.append_group_element(b"bit commitment 2", com2);
+    for (a1, a2) in announcements1.iter().zip(announcements2.iter()) {
+        transcript.append_proof_commitment(a1);
+        transcript.append_proof_commitment(a2);
+    }
+    let mut buffer = [0u8; 16];
+    transcript.challenge_bytes(b"cross group bit challenge", &mut buffer);
+    u128::from_le_bytes(buffer)
+}
+
+/// Return `2^i` as a scalar.
+fn two_pow<S: Scalar>(i: usize) -> S {
+    S::from(1u64 << i)
+}
+
+/// Return the announcement `z * H - c * (com - bit * G)` that makes the branch of `bit` accept.
+fn announcement<G: Group, PC: PedersenCommitment<G>>(
+    pc_gens: &PC,
+    com: &G,
+    bit: u32,
+    challenge: u128,
+    response: &G::ScalarType,
+) -> G {
+    let statement = com.sub(&pc_gens.generator().mul(&G::ScalarType::from(bit)));
+    pc_gens
+        .blinding_generator()
+        .mul(response)
+        .sub(&statement.mul(&G::ScalarType::from_u128(challenge)))
+}
+
+/// Split the blinding of a commitment into the blindings of the bits, such that
+/// `sum_i 2^i * blinds[i] = blind`.
+fn bit_blinds<R: CryptoRng + RngCore, S: Scalar>(
+    prng: &mut R,
+    blind: &S,
+    n_bits: usize,
+) -> Result<Vec<S>> {
+    let mut blinds: Vec<S> = (0..n_bits - 1).map(|_| S::random(prng)).collect();
+    let mut last = *blind;
+    for (i, b) in blinds.iter().enumerate() {
+        last.sub_assign(&two_pow::<S>(i).mul(b));
+    }
+    blinds.push(last.mul(&two_pow::<S>(n_bits - 1).inv().c(d!())?));
+    Ok(blinds)
+}
+
+/// Prove that `pc_gens1.commit(value, blind1)` and `pc_gens2.commit(value, blind2)`
+/// commit to the same value, which has at most `n_bits` bits.
+#[allow(clippy::too_many_arguments)]
+pub fn cross_group_equality_prove<R, G1, G2, PC1, PC2>(
+    transcript: &mut Transcript,
+    prng: &mut R,
+    pc_gens1: &PC1,
+    pc_gens2: &PC2,
+    value: u64,
+    blind1: &G1::ScalarType,
+    blind2: &G2::ScalarType,
+    n_bits: usize,
+) -> Result<CrossGroupEqualityProof<G1, G2>>
+where
+    R: CryptoRng + RngCore,
+    G1: Group,
+    G2: Group,
+    PC1: PedersenCommitment<G1>,
+    PC2: PedersenCommitment<G2>,
+{
+    if n_bits == 0 || n_bits > CROSS_GROUP_MAX_BITS {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    if n_bits < CROSS_GROUP_MAX_BITS && value >> n_bits != 0 {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    let com1 = pc_gens1.commit(G1::ScalarType::from(value), *blind1);
+    let com2 = pc_gens2.commit(G2::ScalarType::from(value), *blind2);
+    init_cross_group_transcript(transcript, pc_gens1, pc_gens2, &com1, &com2, n_bits);
+
+    let blinds1 = bit_blinds(prng, blind1, n_bits).c(d!())?;
+    let blinds2 = bit_blinds(prng, blind2, n_bits).c(d!())?;
+
+    let mut bits = Vec::with_capacity(n_bits);
+    for (i, (s1, s2)) in blinds1.iter().zip(blinds2.iter()).enumerate() {
+        let bit = ((value >> i) & 1) as usize;
+        let com1 = pc_gens1.commit(G1::ScalarType::from(bit as u32), *s1);
+        let com2 = pc_gens2.commit(G2::ScalarType::from(bit as u32), *s2);
+
+        // simulate the branch of the other bit.
+        let mut challenges = [0u128; 2];
+        let mut responses1 = [G1::ScalarType::zero(); 2];
+        let mut responses2 = [G2::ScalarType::zero(); 2];
+        let mut announcements1 = [G1::get_identity(); 2];
+        let mut announcements2 = [G2::get_identity(); 2];
+
+        let other = 1 - bit;
+        challenges[other] = prng.gen();
+        responses1[other] = G1::ScalarType::random(prng);
+        responses2[other] = G2::ScalarType::random(prng);
+        announcements1[other] = announcement(
+            pc_gens1,
+            &com1,
+            other as u32,
+            challenges[other],
+            &responses1[other],
+        );
+        announcements2[other] = announcement(
+            pc_gens2,
+            &com2,
+            other as u32,
+            challenges[other],
+            &responses2[other],
+        );
+
+        // commit for the branch of the actual bit.
+        let k1 = G1::ScalarType::random(prng);
+        let k2 = G2::ScalarType::random(prng);
+        announcements1[bit] = pc_gens1.blinding_generator().mul(&k1);
+        announcements2[bit] = pc_gens2.blinding_generator().mul(&k2);
+
+        let challenge = bit_challenge(transcript, &com1, &com2, &announcements1, &announcements2);
+        challenges[bit] = challenge ^ challenges[other];
+        responses1[bit] = k1.add(&G1::ScalarType::from_u128(challenges[bit]).mul(s1));
+        responses2[bit] = k2.add(&G2::ScalarType::from_u128(challenges[bit]).mul(s2));
+
+        bits.push(CrossGroupBitProof {
+            com1,
+            com2,
+            challenges,
+            responses1,
+            responses2,
+        });
+    }
+
+    Ok(CrossGroupEqualityProof { bits })
+}
+
+/// Verify that the commitments `com1` and `com2` open to the same value of at most `n_bits` bits.
+pub fn cross_group_equality_verify<G1, G2, PC1, PC2>(
+    transcript: &mut Transcript,
+    pc_gens1: &PC1,
+    pc_gens2: &PC2,
+    com1: &G1,
+    com2: &G2,
+    n_bits: usize,
+    proof: &CrossGroupEqualityProof<G1, G2>,
+) -> Result<()>
+where
+    G1: Group,
+    G2: Group,
+    PC1: PedersenCommitment<G1>,
+    PC2: PedersenCommitment<G2>,
+{
+    if n_bits == 0 || n_bits > CROSS_GROUP_MAX_BITS || proof.bits.len() != n_bits {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    // the bit commitments add up to the commitments.
+    let sum1 = proof
+        .bits
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| bit.com1.mul(&two_pow::<G1::ScalarType>(i)))
+        .sum::<G1>();
+    let sum2 = proof
+        .bits
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| bit.com2.mul(&two_pow::<G2::ScalarType>(i)))
+        .sum::<G2>();
+    if sum1 != *com1 || sum2 != *com2 {
+        return Err(eg!(ZeiError::ZKProofVerificationError));
+    }
+
+    init_cross_group_transcript(transcript, pc_gens1, pc_gens2, com1, com2, n_bits);
+    for bit in proof.bits.iter() {
+        let announcements1 = [0, 1].map(|b: usize| {
+            announcement(
+                pc_gens1,
+                &bit.com1,
+                b as u32,
+                bit.challenges[b],
+                &bit.responses1[b],
+            )
+        });
+        let announcements2 = [0, 1].map(|b: usize| {
+            announcement(
+                pc_gens2,
+                &bit.com2,
+                b as u32,
+                bit.challenges[b],
+                &bit.responses2[b],
+            )
+        });
+        let challenge = bit_challenge(
+            transcript,
+            &bit.com1,
+            &bit.com2,
+            &announcements1,
+            &announcements2,
+        );
+        if challenge != bit.challenges[0] ^ bit.challenges[1] {
+            return Err(eg!(ZeiError::ZKProofVerificationError));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        cross_group_equality_prove, cross_group_equality_verify, BLSRistrettoEqualityProof,
+    };
+    use crate::basic::pedersen_comm::{
+        PedersenCommitment, PedersenCommitmentBLS12381, PedersenCommitmentRistretto,
+    };
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn bls_ristretto_equality() {
+        let mut prng = test_rng();
+        let pc_gens1 = PedersenCommitmentBLS12381::default();
+        let pc_gens2 = PedersenCommitmentRistretto::default();
+        let value = 0x1234_5678_9abc_def0u64;
+        let blind1 = BLSScalar::random(&mut prng);
+        let blind2 = RistrettoScalar::random(&mut prng);
+        let com1 = pc_gens1.commit(BLSScalar::from(value), blind1);
+        let com2 = pc_gens2.commit(RistrettoScalar::from(value), blind2);
+
+        let mut transcript = Transcript::new(b"test");
+        let proof: BLSRistrettoEqualityProof = pnk!(cross_group_equality_prove(
+            &mut transcript,
+            &mut prng,
+            &pc_gens1,
+            &pc_gens2,
+            value,
+            &blind1,
+            &blind2,
+            64,
+        ));
+
+        let mut transcript = Transcript::new(b"test");
+        pnk!(cross_group_equality_verify(
+            &mut transcript,
+            &pc_gens1,
+            &pc_gens2,
+            &com1,
+            &com2,
+            64,
+            &proof,
+        ));
+
+        // a commitment to another value is rejected.
+        let other = pc_gens2.commit(RistrettoScalar::from(value + 1), blind2);
+        let mut transcript = Transcript::new(b"test");
+        let err = cross_group_equality_verify(
+            &mut transcript,
+            &pc_gens1,
+            &pc_gens2,
+            &com1,
+            &other,
+            64,
+            &proof,
+        )
+        .unwrap_err();
+        msg_eq!(ZeiError::ZKProofVerificationError, err);
+
+        // a bit that is not in {0, 1} is rejected, even if the sum is preserved.
+        let mut bad_proof = proof.clone();
+        let g1 = pc_gens1.generator();
+        let g2 = pc_gens2.generator();
+        bad_proof.bits[0].com1 = bad_proof.bits[0].com1.add(&g1.double());
+        bad_proof.bits[1].com1 = bad_proof.bits[1].com1.sub(&g1);
+        bad_proof.bits[0].com2 = bad_proof.bits[0].com2.add(&g2.double());
+        bad_proof.bits[1].com2 = bad_proof.bits[1].com2.sub(&g2);
+        let mut transcript = Transcript::new(b"test");
+        assert!(cross_group_equality_verify(
+            &mut transcript,
+            &pc_gens1,
+            &pc_gens2,
+            &com1,
+            &com2,
+            64,
+            &bad_proof,
+        )
+        .is_err());
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let proof_json: BLSRistrettoEqualityProof = serde_json::from_str(&json).unwrap();
+        assert_eq!(proof, proof_json);
+    }
+
+    #[test]
+    fn value_out_of_range() {
+        let mut prng = test_rng();
+        let pc_gens1 = PedersenCommitmentBLS12381::default();
+        let pc_gens2 = PedersenCommitmentRistretto::default();
+        let blind1 = BLSScalar::random(&mut prng);
+        let blind2 = RistrettoScalar::random(&mut prng);
+
+        let mut transcript = Transcript::new(b"test");
+        let res: Result<BLSRistrettoEqualityProof> = cross_group_equality_prove(
+            &mut transcript,
+            &mut prng,
+            &pc_gens1,
+            &pc_gens2,
+            256,
+            &blind1,
+            &blind2,
+            8,
+        );
+        msg_eq!(ZeiError::ParameterError, res.unwrap_err());
+
+        // a proof for 32 bits does not pass as a proof for 64 bits.
+        let mut transcript = Transcript::new(b"test");
+        let proof: BLSRistrettoEqualityProof = pnk!(cross_group_equality_prove(
+            &mut transcript,
+            &mut prng,
+            &pc_gens1,
+            &pc_gens2,
+            255,
+            &blind1,
+            &blind2,
+            32,
+        ));
+        let com1 = pc_gens1.commit(BLSScalar::from(255u32), blind1);
+        let com2 = pc_gens2.commit(RistrettoScalar::from(255u32), blind2);
+        let mut transcript = Transcript::new(b"test");
+        assert!(cross_group_equality_verify(
+            &mut transcript,
+            &pc_gens1,
+            &pc_gens2,
+            &com1,
+            &com2,
+            64,
+            &proof,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,4 @@
+/// The module for the equality proof between Pedersen commitments in two different groups.
+pub mod cross_group_equality;
+/// The module for the one-out-of-many proof that one of the commitments in a list opens to zero.
+pub mod one_out_of_many;
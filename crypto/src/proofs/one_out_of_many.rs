@@ -0,0 +1,360 @@
+use crate::basic::matrix_sigma::SigmaTranscript;
+use crate::basic::pedersen_comm::PedersenCommitment;
+use merlin::Transcript;
+use zei_algebra::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// The proof of Groth and Kohlweiss that one of the commitments in a list opens to zero,
+/// in base 2: the prover commits to each bit `l_j` of the index `l` of the commitment, and
+/// proves that the polynomials `p_i(x) = prod_j f_{j, i_j}(x)`, of degree `m` for `i = l`
+/// only, weight the commitments into a commitment to zero.
+///
+/// With `m` the number of bits of the indices, the proof has `4m` group elements and `3m + 1`
+/// scalars, for any number of commitments up to `2^m`, and needs no trusted setup.
+pub struct OneOutOfManyProof<G: Group> {
+    /// The commitments `l_j * G + r_j * H` to the bits of the index.
+    pub bit_commitments: Vec<G>,
+    /// The commitments `a_j * G + s_j * H` to the masks of the bits.
+    pub mask_commitments: Vec<G>,
+    /// The commitments `l_j * a_j * G + t_j * H`, which show that the bits are in {0, 1}.
+    pub product_commitments: Vec<G>,
+    /// The commitments `sum_i p_{i, k} * C_i + rho_k * H` to the coefficients of degree `k < m`.
+    pub coefficient_commitments: Vec<G>,
+    /// The masked bits `f_j = l_j * x + a_j`.
+    pub f: Vec<G::ScalarType>,
+    /// The responses `r_j * x + s_j`.
+    pub z_a: Vec<G::ScalarType>,
+    /// The responses `r_j * (x - f_j) + t_j`.
+    pub z_b: Vec<G::ScalarType>,
+    /// The response `r * x^m - sum_k rho_k * x^k`.
+    pub z_d: G::ScalarType,
+}
+
+/// Return the number of bits of the indices of `n` commitments, which is at least 1.
+fn index_bits(n: usize) -> usize {
+    std::cmp::max(1, (usize::BITS - (n - 1).leading_zeros()) as usize)
+}
+
+/// Initialize the transcript for the one-out-of-many proof.
+fn init_one_out_of_many_transcript<G: Group, PC: PedersenCommitment<G>>(
+    transcript: &mut Transcript,
+    pc_gens: &PC,
+    commitments: &[G],
+) {
+    transcript.append_message(b"One out of many domain", b"One out of many v.0.1");
+    transcript.append_u64(b"number of commitments", commitments.len() as u64);
+    transcript.append_group_element(b"generator", &pc_gens.generator());
+    transcript.append_group_element(b"blinding generator", &pc_gens.blinding_generator());
+    for c in commitments {
+        transcript.append_group_element(b"commitment", c);
+    }
+}
+
+/// Append the commitments of the proof, and return the challenge `x`.
+fn one_out_of_many_challenge<G: Group>(
+    transcript: &mut Transcript,
+    proof: &OneOutOfManyProof<G>,
+) -> G::ScalarType {
+    for ((cl, ca), cb) in proof
+        .bit_commitments
+        .iter()
+        .zip(proof.mask_commitments.iter())
+        .zip(proof.product_commitments.iter())
+    {
+        transcript.append_proof_commitment(cl);
+        transcript.append_proof_commitment(ca);
+        transcript.append_proof_commitment(cb);
+    }
+    for cd in proof.coefficient_commitments.iter() {
+        transcript.append_proof_commitment(cd);
+    }
+    transcript.get_challenge::<G::ScalarType>()
+}
+
+/// Multiply a polynomial, given by its coefficients from degree 0, by `a + b * x`.
+fn mul_linear<S: Scalar>(poly: &[S], a: &S, b: &S) -> Vec<S> {
+    let mut result = vec![S::zero(); poly.len() + 1];
+    for (k, coef) in poly.iter().enumerate() {
+        result[k].add_assign(&coef.mul(a));
+        result[k + 1].add_assign(&coef.mul(b));
+    }
+    result
+}
+
+/// Prove that `commitments[index]` is `pc_gens.commit(0, blind)`, without revealing `index`.
+pub fn one_out_of_many_prove<R, G, PC>(
+    transcript: &mut Transcript,
+    prng: &mut R,
+    pc_gens: &PC,
+    commitments: &[G],
+    index: usize,
+    blind: &G::ScalarType,
+) -> Result<OneOutOfManyProof<G>>
+where
+    R: CryptoRng + RngCore,
+    G: Group,
+    PC: PedersenCommitment<G>,
+{
+    if index >= commitments.len()
+        || commitments[index] != pc_gens.commit(G::ScalarType::zero(), *blind)
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let m = index_bits(commitments.len());
+    init_one_out_of_many_transcript(transcript, pc_gens, commitments);
+
+    let bits = (0..m)
+        .map(|j| G::ScalarType::from(((index >> j) & 1) as u32))
+        .collect_vec();
+    let sample = |prng: &mut R| (0..m).map(|_| G::ScalarType::random(prng)).collect_vec();
+    let r = sample(prng);
+    let a = sample(prng);
+    let s = sample(prng);
+    let t = sample(prng);
+    let rho = sample(prng);
+
+    let mut bit_commitments = Vec::with_capacity(m);
+    let mut mask_commitments = Vec::with_capacity(m);
+    let mut product_commitments = Vec::with_capacity(m);
+    for (l_j, r_j, a_j, s_j, t_j) in izip!(bits.iter(), r.iter(), a.iter(), s.iter(), t.iter()) {
+        bit_commitments.push(pc_gens.commit(*l_j, *r_j));
+        mask_commitments.push(pc_gens.commit(*a_j, *s_j));
+        product_commitments.push(pc_gens.commit(l_j.mul(a_j), *t_j));
+    }
+
+    // the coefficients of `p_i(x) = prod_j f_{j, i_j}(x)`, with `f_{j, 1}(x) = l_j * x + a_j`
+    // and `f_{j, 0}(x) = x - f_{j, 1}(x)`.
+    let one = G::ScalarType::one();
+    let mut coefficient_commitments = rho
+        .iter()
+        .map(|rho_k| pc_gens.blinding_generator().mul(rho_k))
+        .collect_vec();
+    for (i, c) in commitments.iter().enumerate() {
+        let mut poly = vec![one];
+        for (j, (l_j, a_j)) in bits.iter().zip(a.iter()).enumerate() {
+            poly = if (i >> j) & 1 == 1 {
+                mul_linear(&poly, a_j, l_j)
+            } else {
+                mul_linear(&poly, &a_j.neg(), &one.sub(l_j))
+            };
+        }
+        for (cd, coef) in coefficient_commitments.iter_mut().zip(poly.iter()) {
+            *cd = cd.add(&c.mul(coef));
+        }
+    }
+
+    let mut proof = OneOutOfManyProof {
+        bit_commitments,
+        mask_commitments,
+        product_commitments,
+        coefficient_commitments,
+        f: vec![],
+        z_a: vec![],
+        z_b: vec![],
+        z_d: G::ScalarType::zero(),
+    };
+    let x = one_out_of_many_challenge(transcript, &proof);
+
+    for (l_j, r_j, a_j, s_j, t_j) in izip!(bits.iter(), r.iter(), a.iter(), s.iter(), t.iter()) {
+        let f = l_j.mul(&x).add(a_j);
+        proof.z_a.push(r_j.mul(&x).add(s_j));
+        proof.z_b.push(r_j.mul(&x.sub(&f)).add(t_j));
+        proof.f.push(f);
+    }
+    let mut x_pow = one;
+    let mut z_d = G::ScalarType::zero();
+    for rho_k in rho.iter() {
+        z_d.sub_assign(&rho_k.mul(&x_pow));
+        x_pow.mul_assign(&x);
+    }
+    proof.z_d = z_d.add(&blind.mul(&x_pow));
+
+    Ok(proof)
+}
+
+/// Verify that one of the commitments opens to zero.
+pub fn one_out_of_many_verify<G, PC>(
+    transcript: &mut Transcript,
+    pc_gens: &PC,
+    commitments: &[G],
+    proof: &OneOutOfManyProof<G>,
+) -> Result<()>
+where
+    G: Group,
+    PC: PedersenCommitment<G>,
+{
+    if commitments.is_empty() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let m = index_bits(commitments.len());
+    if proof.bit_commitments.len() != m
+        || proof.mask_commitments.len() != m
+        || proof.product_commitments.len() != m
+        || proof.coefficient_commitments.len() != m
+        || proof.f.len() != m
+        || proof.z_a.len() != m
+        || proof.z_b.len() != m
+    {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+
+    init_one_out_of_many_transcript(transcript, pc_gens, commitments);
+    let x = one_out_of_many_challenge(transcript, proof);
+
+    // the bits are committed, and are in {0, 1}.
+    for (cl, ca, cb, f, z_a, z_b) in izip!(
+        proof.bit_commitments.iter(),
+        proof.mask_commitments.iter(),
+        proof.product_commitments.iter(),
+        proof.f.iter(),
+        proof.z_a.iter(),
+        proof.z_b.iter()
+    ) {
+        if cl.mul(&x).add(ca) != pc_gens.commit(*f, *z_a)
+            || cl.mul(&x.sub(f)).add(cb) != pc_gens.commit(G::ScalarType::zero(), *z_b)
+        {
+            return Err(eg!(ZeiError::ZKProofVerificationError));
+        }
+    }
+
+    // `sum_i p_i(x) * C_i - sum_k x^k * D_k` is a commitment to zero.
+    let mut scalars = Vec::with_capacity(commitments.len() + m);
+    for i in 0..commitments.len() {
+        let mut p = G::ScalarType::one();
+        for (j, f) in proof.f.iter().enumerate() {
+            if (i >> j) & 1 == 1 {
+                p.mul_assign(f);
+            } else {
+                p.mul_assign(&x.sub(f));
+            }
+        }
+        scalars.push(p);
+    }
+    let mut x_pow = G::ScalarType::one();
+    for _ in 0..m {
+        scalars.push(x_pow.neg());
+        x_pow.mul_assign(&x);
+    }
+    let elems = commitments
+        .iter()
+        .chain(proof.coefficient_commitments.iter())
+        .collect_vec();
+    let lhs = G::multi_exp(&scalars.iter().collect_vec(), &elems);
+    if lhs != pc_gens.commit(G::ScalarType::zero(), proof.z_d) {
+        return Err(eg!(ZeiError::ZKProofVerificationError));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{one_out_of_many_prove, one_out_of_many_verify, OneOutOfManyProof};
+    use crate::basic::pedersen_comm::{
+        PedersenCommitment, PedersenCommitmentBLS12381, PedersenCommitmentRistretto,
+    };
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use zei_algebra::{
+        bls12_381::BLSG1,
+        prelude::*,
+        ristretto::{RistrettoPoint, RistrettoScalar},
+    };
+
+    fn one_out_of_many<G: Group, PC: PedersenCommitment<G>>() {
+        let mut prng = test_rng();
+        let pc_gens = PC::default();
+
+        for n in [1, 2, 5, 8] {
+            let mut commitments = (0..n)
+                .map(|i| {
+                    pc_gens.commit(
+                        G::ScalarType::from(i as u32 + 1),
+                        G::ScalarType::random(&mut prng),
+                    )
+                })
+                .collect_vec();
+            let index = n / 2;
+            let blind = G::ScalarType::random(&mut prng);
+            commitments[index] = pc_gens.commit(G::ScalarType::zero(), blind);
+
+            let mut transcript = Transcript::new(b"test");
+            let proof = pnk!(one_out_of_many_prove(
+                &mut transcript,
+                &mut prng,
+                &pc_gens,
+                &commitments,
+                index,
+                &blind,
+            ));
+            let mut transcript = Transcript::new(b"test");
+            pnk!(one_out_of_many_verify(
+                &mut transcript,
+                &pc_gens,
+                &commitments,
+                &proof
+            ));
+
+            // the commitment at the index must open to zero.
+            let mut transcript = Transcript::new(b"test");
+            let res = one_out_of_many_prove(
+                &mut transcript,
+                &mut prng,
+                &pc_gens,
+                &commitments,
+                (index + 1) % n,
+                &blind,
+            );
+            assert_eq!(res.is_err(), n > 1);
+
+            // the proof is bound to the list.
+            let mut other = commitments.clone();
+            other[index] = pc_gens.commit(G::ScalarType::one(), blind);
+            let mut transcript = Transcript::new(b"test");
+            let err =
+                one_out_of_many_verify(&mut transcript, &pc_gens, &other, &proof).unwrap_err();
+            msg_eq!(ZeiError::ZKProofVerificationError, err);
+
+            let mut bad_proof: OneOutOfManyProof<G> = proof.clone();
+            bad_proof.z_d = bad_proof.z_d.add(&G::ScalarType::one());
+            let mut transcript = Transcript::new(b"test");
+            assert!(
+                one_out_of_many_verify(&mut transcript, &pc_gens, &commitments, &bad_proof)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn one_out_of_many_ristretto() {
+        one_out_of_many::<RistrettoPoint, PedersenCommitmentRistretto>();
+    }
+
+    #[test]
+    fn one_out_of_many_bls12_381() {
+        one_out_of_many::<BLSG1, PedersenCommitmentBLS12381>();
+    }
+
+    #[test]
+    fn proof_for_a_longer_list() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let blind = RistrettoScalar::random(&mut prng);
+        let commitments = vec![pc_gens.commit(RistrettoScalar::zero(), blind); 3];
+
+        // a proof for 3 commitments has 2 bits, and is not valid for more commitments.
+        let mut transcript = Transcript::new(b"test");
+        let proof = pnk!(one_out_of_many_prove(
+            &mut transcript,
+            &mut prng,
+            &pc_gens,
+            &commitments,
+            2,
+            &blind,
+        ));
+        let mut longer = commitments.clone();
+        longer.extend_from_slice(&commitments[..2]);
+        let mut transcript = Transcript::new(b"test");
+        let err = one_out_of_many_verify(&mut transcript, &pc_gens, &longer, &proof).unwrap_err();
+        msg_eq!(ZeiError::ParameterError, err);
+    }
+}
@@ -0,0 +1,176 @@
+//! The module for prover sessions, which refuse to reuse the randomness of the prover.
+//!
+//! Proving twice with the same randomness, e.g., when a proof is retried with a cloned RNG after
+//! an error, can leak the witness through related blinding factors. Each prover invocation
+//! therefore runs in a [`ProverSession`], started from a [`ProverSessions`] registry: the session
+//! draws a nonce from the RNG, and the registry refuses any session whose nonce was already seen,
+//! which happens exactly when the RNG is in a state that was already used.
+
+use zei_algebra::{collections::HashSet, prelude::*};
+
+/// The length of the nonce of a prover session.
+pub const SESSION_NONCE_LENGTH: usize = 32;
+
+/// The nonce of a prover session, drawn from the RNG of the prover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SessionNonce(pub [u8; SESSION_NONCE_LENGTH]);
+
+/// A registry of the sessions of a prover.
+///
+/// Reuse is only detected among the sessions started from the same registry, so a prover should
+/// keep one registry for all its proofs.
+#[derive(Clone, Debug, Default)]
+pub struct ProverSessions {
+    used_nonces: HashSet<SessionNonce>,
+}
+
+/// A prover session, which provides the randomness for a single prover invocation.
+pub struct ProverSession<'a, R> {
+    prng: &'a mut R,
+    nonce: SessionNonce,
+}
+
+impl ProverSessions {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of sessions started so far.
+    pub fn len(&self) -> usize {
+        self.used_nonces.len()
+    }
+
+    /// Return true if no session was started.
+    pub fn is_empty(&self) -> bool {
+        self.used_nonces.is_empty()
+    }
+
+    /// Start a session with the RNG, and fail if the RNG is in a state that was already used
+    /// by another session.
+    pub fn start<'a, R: CryptoRng + RngCore>(
+        &mut self,
+        prng: &'a mut R,
+    ) -> Result<ProverSession<'a, R>> {
+        let mut bytes = [0u8; SESSION_NONCE_LENGTH];
+        prng.fill_bytes(&mut bytes);
+        let nonce = SessionNonce(bytes);
+        if !self.used_nonces.insert(nonce) {
+            return Err(eg!(ZeiError::RandomnessReuseError));
+        }
+        Ok(ProverSession { prng, nonce })
+    }
+}
+
+impl<'a, R: CryptoRng + RngCore> ProverSession<'a, R> {
+    /// Return the nonce of the session.
+    pub fn nonce(&self) -> &SessionNonce {
+        &self.nonce
+    }
+
+    /// Run a prover with the randomness of the session.
+    /// The session is consumed, even if the prover fails, so a retry needs a new session.
+    pub fn prove<T, F>(mut self, prover: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        prover(&mut self).c(d!())
+    }
+}
+
+impl<'a, R: CryptoRng + RngCore> RngCore for ProverSession<'a, R> {
+    fn next_u32(&mut self) -> u32 {
+        self.prng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.prng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.prng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.prng.try_fill_bytes(dest)
+    }
+}
+
+impl<'a, R: CryptoRng + RngCore> CryptoRng for ProverSession<'a, R> {}
+
+#[cfg(test)]
+mod test {
+    use crate::bulletproofs::range::{batch_verify_ranges, prove_ranges_with_rng};
+    use crate::prover_session::ProverSessions;
+    use bulletproofs::BulletproofGens;
+    use merlin::Transcript;
+    use rand_chacha::ChaChaRng;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn prover_session() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let mut cloned_prng = prng.clone();
+
+        let mut sessions = ProverSessions::new();
+        assert!(sessions.is_empty());
+        let nonce = *sessions.start(&mut prng).unwrap().nonce();
+
+        // the cloned RNG is in a state that was already used
+        msg_eq!(
+            ZeiError::RandomnessReuseError,
+            sessions.start(&mut cloned_prng).unwrap_err()
+        );
+
+        // the original RNG has moved on
+        let next_nonce = *sessions.start(&mut prng).unwrap().nonce();
+        assert_ne!(nonce, next_nonce);
+        assert_eq!(sessions.len(), 2);
+
+        // another registry does not know the sessions of this one
+        let mut other_sessions = ProverSessions::new();
+        assert_eq!(
+            *other_sessions
+                .start(&mut ChaChaRng::from_seed([0u8; 32]))
+                .unwrap()
+                .nonce(),
+            nonce
+        );
+    }
+
+    #[test]
+    fn prove_in_session() {
+        let mut prng = ChaChaRng::from_seed([1u8; 32]);
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blindings = vec![RistrettoScalar::random(&mut prng)];
+
+        let mut sessions = ProverSessions::new();
+        let mut retry_prng = prng.clone();
+        let (proof, commitments) = sessions
+            .start(&mut prng)
+            .unwrap()
+            .prove(|session| {
+                prove_ranges_with_rng(
+                    session,
+                    &bp_gens,
+                    &mut Transcript::new(b"Test"),
+                    &[42],
+                    &blindings,
+                    32,
+                )
+            })
+            .unwrap();
+        assert!(batch_verify_ranges(
+            &mut prng,
+            &bp_gens,
+            &[&proof],
+            &mut [Transcript::new(b"Test")],
+            &[&commitments],
+            32
+        )
+        .is_ok());
+
+        // a retry from the same RNG state is refused
+        assert!(sessions.start(&mut retry_prng).is_err());
+    }
+}
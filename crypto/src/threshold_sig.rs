@@ -0,0 +1,440 @@
+//! The module for threshold signatures with the key shares of a distributed key generation.
+//!
+//! Any `threshold` of the participants of a key generation, see [`crate::dkg`], sign a message
+//! for the key of the group, in one of two schemes:
+//! - threshold BLS over BLS12-381, with the keys in G2 and the signatures in G1: each signer
+//!   returns a [`BLSPartialSignature`] without any interaction;
+//! - threshold Schnorr over Ristretto: the signers first commit to their nonces, and only reveal
+//!   them once every commitment is known, so that no signer chooses its nonce after seeing the
+//!   others. Each signer then returns its response to the challenge of the common nonce.
+//!
+//! Each partial signature is checked against the verification key of its signer, so that an
+//! invalid one is attributed to its signer, and the combination of the partial signatures is a
+//! plain BLS or Schnorr signature under the key of the group.
+//!
+//! A [`SchnorrNonce`] must never answer two challenges, which would reveal the key share:
+//! [`schnorr_sign_share`] consumes it, and the nonce cannot be cloned.
+
+use crate::dkg::{BLSThresholdKeyShare, DkgPublicOutput, RistrettoThresholdKeyShare};
+use sha2::{Digest, Sha256, Sha512};
+use zei_algebra::{
+    bls12_381::{BLSPairingEngine, BLSScalar, BLSG1, BLSG2},
+    prelude::*,
+    ristretto::{RistrettoPoint, RistrettoScalar},
+    traits::Pairing,
+};
+
+const BLS_MESSAGE_DOMAIN: &[u8] = b"Zei Threshold BLS Message";
+const SCHNORR_COMMITMENT_DOMAIN: &[u8] = b"Zei Threshold Schnorr Nonce Commitment";
+const SCHNORR_CHALLENGE_DOMAIN: &[u8] = b"Zei Threshold Schnorr Challenge";
+
+/// Check that the signers are distinct participants of the key generation, in increasing order.
+pub fn check_signers<G>(output: &DkgPublicOutput<G>, signers: &[u32]) -> Result<()> {
+    if signers.is_empty() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let n = output.verification_keys.len() as u32;
+    if signers[0] == 0 || signers[signers.len() - 1] > n {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    if signers.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    Ok(())
+}
+
+/// Return the Lagrange coefficient of the signer `index` for interpolating at zero the
+/// polynomial shared among the `signers`.
+pub fn lagrange_coefficient<S: Scalar>(index: u32, signers: &[u32]) -> Result<S> {
+    if !signers.contains(&index) {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let point_i = S::from(index);
+    let mut numerator = S::one();
+    let mut denominator = S::one();
+    for other in signers.iter().filter(|other| **other != index) {
+        let point_j = S::from(*other);
+        numerator = numerator.mul(&point_j);
+        denominator = denominator.mul(&point_j.sub(&point_i));
+    }
+    Ok(numerator.mul(&denominator.inv().c(d!(ZeiError::ParameterError))?))
+}
+
+/// The signature of a participant of a threshold BLS key on a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BLSPartialSignature {
+    /// The index of the signer.
+    pub signer: u32,
+    /// The signature of the message with the key share of the signer.
+    pub signature: BLSG1,
+}
+
+/// Hash a message to the point of G1 that a BLS signature multiplies.
+pub fn bls_hash_message(message: &[u8]) -> BLSG1 {
+    let mut hasher = Sha512::new();
+    hasher.update(BLS_MESSAGE_DOMAIN);
+    hasher.update(message);
+    BLSG1::from_hash(hasher)
+}
+
+/// Sign a message with a threshold BLS key share.
+pub fn bls_sign_share(key_share: &BLSThresholdKeyShare, message: &[u8]) -> BLSPartialSignature {
+    BLSPartialSignature {
+        signer: key_share.index,
+        signature: bls_hash_message(message).mul(&key_share.secret_share),
+    }
+}
+
+/// Verify the partial signature of a participant against its verification key.
+pub fn bls_verify_partial(
+    output: &DkgPublicOutput<BLSG2>,
+    message: &[u8],
+    partial: &BLSPartialSignature,
+) -> Result<()> {
+    let verification_key = output
+        .verification_keys
+        .get((partial.signer as usize).wrapping_sub(1))
+        .c(d!(ZeiError::ParameterError))?;
+    bls_verify(verification_key, message, &partial.signature).c(d!())
+}
+
+/// Combine the partial signatures of the signers, in the increasing order of the signers, into
+/// the signature of the group, after checking each of them. The combination is checked as well,
+/// which fails if there are fewer than `threshold` signers.
+pub fn bls_combine(
+    output: &DkgPublicOutput<BLSG2>,
+    message: &[u8],
+    partials: &[BLSPartialSignature],
+) -> Result<BLSG1> {
+    let signers = partials.iter().map(|partial| partial.signer).collect_vec();
+    check_signers(output, &signers).c(d!())?;
+    let mut signature = BLSG1::get_identity();
+    for partial in partials.iter() {
+        bls_verify_partial(output, message, partial).c(d!())?;
+        let coef: BLSScalar = lagrange_coefficient(partial.signer, &signers).c(d!())?;
+        signature = signature.add(&partial.signature.mul(&coef));
+    }
+    bls_verify(&output.public_key, message, &signature).c(d!())?;
+    Ok(signature)
+}
+
+/// Verify a BLS signature of a message.
+pub fn bls_verify(public_key: &BLSG2, message: &[u8], signature: &BLSG1) -> Result<()> {
+    let lhs = BLSPairingEngine::pairing(signature, &BLSG2::get_base());
+    let rhs = BLSPairingEngine::pairing(&bls_hash_message(message), public_key);
+    if lhs != rhs {
+        return Err(eg!(ZeiError::SignatureError));
+    }
+    Ok(())
+}
+
+/// The commitment of a signer to its nonce of a threshold Schnorr signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NonceCommitment(pub [u8; 32]);
+
+/// The secret nonce of a signer of a threshold Schnorr signature, used for a single signature.
+#[derive(Serialize, Deserialize)]
+pub struct SchnorrNonce {
+    secret: RistrettoScalar,
+    public: RistrettoPoint,
+}
+
+impl SchnorrNonce {
+    /// Sample a nonce.
+    pub fn random<R: CryptoRng + RngCore>(prng: &mut R) -> Self {
+        let secret = RistrettoScalar::random(prng);
+        SchnorrNonce {
+            secret,
+            public: RistrettoPoint::get_base().mul(&secret),
+        }
+    }
+
+    /// Return the public nonce, to reveal once every commitment is known.
+    pub fn public(&self) -> &RistrettoPoint {
+        &self.public
+    }
+
+    /// Return the commitment of the signer `signer` to the nonce.
+    pub fn commitment(&self, signer: u32) -> NonceCommitment {
+        schnorr_nonce_commitment(signer, &self.public)
+    }
+}
+
+/// Compute the commitment of the signer `signer` to its public nonce.
+pub fn schnorr_nonce_commitment(signer: u32, nonce: &RistrettoPoint) -> NonceCommitment {
+    let mut hasher = Sha256::new();
+    hasher.update(SCHNORR_COMMITMENT_DOMAIN);
+    hasher.update(signer.to_le_bytes());
+    hasher.update(nonce.to_compressed_bytes());
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&hasher.finalize());
+    NonceCommitment(commitment)
+}
+
+/// Check the revealed nonces against the commitments of the same signers, in the same order,
+/// and return the common nonce, i.e., their sum.
+pub fn schnorr_group_nonce(
+    commitments: &[(u32, NonceCommitment)],
+    nonces: &[(u32, RistrettoPoint)],
+) -> Result<RistrettoPoint> {
+    if commitments.len() != nonces.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let mut group_nonce = RistrettoPoint::get_identity();
+    for ((signer, commitment), (revealer, nonce)) in commitments.iter().zip(nonces.iter()) {
+        if signer != revealer {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        if schnorr_nonce_commitment(*signer, nonce) != *commitment {
+            return Err(eg!(ZeiError::SignatureError));
+        }
+        group_nonce = group_nonce.add(nonce);
+    }
+    Ok(group_nonce)
+}
+
+/// Compute the challenge of a Schnorr signature.
+pub fn schnorr_challenge(
+    public_key: &RistrettoPoint,
+    group_nonce: &RistrettoPoint,
+    message: &[u8],
+) -> RistrettoScalar {
+    let mut hasher = Sha512::new();
+    hasher.update(SCHNORR_CHALLENGE_DOMAIN);
+    hasher.update(public_key.to_compressed_bytes());
+    hasher.update(group_nonce.to_compressed_bytes());
+    hasher.update(message);
+    RistrettoScalar::from_hash(hasher)
+}
+
+/// Answer the challenge of the common nonce with a threshold Schnorr key share, which consumes
+/// the nonce of the signer.
+pub fn schnorr_sign_share(
+    key_share: &RistrettoThresholdKeyShare,
+    signers: &[u32],
+    nonce: SchnorrNonce,
+    group_nonce: &RistrettoPoint,
+    message: &[u8],
+) -> Result<RistrettoScalar> {
+    check_signers(&key_share.output, signers).c(d!())?;
+    let coef: RistrettoScalar = lagrange_coefficient(key_share.index, signers).c(d!())?;
+    let challenge = schnorr_challenge(&key_share.output.public_key, group_nonce, message);
+    Ok(nonce
+        .secret
+        .add(&challenge.mul(&coef).mul(&key_share.secret_share)))
+}
+
+/// Verify the response of a signer against its nonce and its verification key.
+pub fn schnorr_verify_partial(
+    output: &DkgPublicOutput<RistrettoPoint>,
+    signers: &[u32],
+    signer: u32,
+    nonce: &RistrettoPoint,
+    group_nonce: &RistrettoPoint,
+    message: &[u8],
+    response: &RistrettoScalar,
+) -> Result<()> {
+    check_signers(output, signers).c(d!())?;
+    let coef: RistrettoScalar = lagrange_coefficient(signer, signers).c(d!())?;
+    let challenge = schnorr_challenge(&output.public_key, group_nonce, message);
+    let verification_key = &output.verification_keys[signer as usize - 1];
+    let expected = nonce.add(&verification_key.mul(&challenge.mul(&coef)));
+    if RistrettoPoint::get_base().mul(response) != expected {
+        return Err(eg!(ZeiError::SignatureError));
+    }
+    Ok(())
+}
+
+/// A Schnorr signature under the key of the group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdSchnorrSignature {
+    /// The common nonce.
+    pub nonce: RistrettoPoint,
+    /// The sum of the responses of the signers.
+    pub response: RistrettoScalar,
+}
+
+/// Combine the responses of the signers into the signature of the group, after checking each
+/// of them against the nonce of its signer. The nonces and the responses are in the order of the
+/// signers.
+pub fn schnorr_combine(
+    output: &DkgPublicOutput<RistrettoPoint>,
+    nonces: &[(u32, RistrettoPoint)],
+    message: &[u8],
+    responses: &[(u32, RistrettoScalar)],
+) -> Result<ThresholdSchnorrSignature> {
+    let signers = nonces.iter().map(|(signer, _)| *signer).collect_vec();
+    check_signers(output, &signers).c(d!())?;
+    if responses.len() != nonces.len() {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let group_nonce: RistrettoPoint = nonces.iter().map(|(_, nonce)| nonce).sum();
+    let mut response = RistrettoScalar::zero();
+    for ((signer, nonce), (responder, partial)) in nonces.iter().zip(responses.iter()) {
+        if signer != responder {
+            return Err(eg!(ZeiError::ParameterError));
+        }
+        schnorr_verify_partial(
+            output,
+            &signers,
+            *signer,
+            nonce,
+            &group_nonce,
+            message,
+            partial,
+        )
+        .c(d!())?;
+        response = response.add(partial);
+    }
+    let signature = ThresholdSchnorrSignature {
+        nonce: group_nonce,
+        response,
+    };
+    schnorr_verify(&output.public_key, message, &signature).c(d!())?;
+    Ok(signature)
+}
+
+/// Verify a Schnorr signature of a message.
+pub fn schnorr_verify(
+    public_key: &RistrettoPoint,
+    message: &[u8],
+    signature: &ThresholdSchnorrSignature,
+) -> Result<()> {
+    let challenge = schnorr_challenge(public_key, &signature.nonce, message);
+    let expected = signature.nonce.add(&public_key.mul(&challenge));
+    if RistrettoPoint::get_base().mul(&signature.response) != expected {
+        return Err(eg!(ZeiError::SignatureError));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::dkg::{DkgParticipant, ThresholdKeyShare};
+    use crate::threshold_sig::{
+        bls_combine, bls_sign_share, bls_verify, bls_verify_partial, schnorr_combine,
+        schnorr_group_nonce, schnorr_sign_share, schnorr_verify, SchnorrNonce,
+    };
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::{BLSG1, BLSG2},
+        prelude::*,
+        ristretto::{RistrettoPoint, RistrettoScalar},
+    };
+
+    fn honest_dkg<G: Group>(threshold: u32, n: u32) -> Vec<ThresholdKeyShare<G>> {
+        let mut prng = test_rng();
+        let mut participants = (1..=n)
+            .map(|index| DkgParticipant::<G>::new(&mut prng, index, threshold, n).unwrap())
+            .collect_vec();
+        let dealings = participants
+            .iter_mut()
+            .map(|p| p.deal().unwrap())
+            .collect_vec();
+        for (commitment, shares) in dealings.iter() {
+            for share in shares.iter() {
+                let recipient = &mut participants[share.recipient as usize - 1];
+                recipient.receive_commitment(commitment).unwrap();
+                recipient.receive_share(share).unwrap();
+            }
+        }
+        for p in participants.iter_mut() {
+            assert!(p.complain().unwrap().is_empty());
+            assert!(p.respond().unwrap().is_empty());
+        }
+        participants
+            .iter_mut()
+            .map(|p| p.finish().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn threshold_bls() {
+        let shares = honest_dkg::<BLSG2>(3, 5);
+        let output = &shares[0].output;
+        let message = b"threshold BLS";
+
+        let partials = [0, 2, 4]
+            .iter()
+            .map(|i| bls_sign_share(&shares[*i], message))
+            .collect_vec();
+        for partial in partials.iter() {
+            assert!(bls_verify_partial(output, message, partial).is_ok());
+            assert!(bls_verify_partial(output, b"another message", partial).is_err());
+        }
+        let signature = bls_combine(output, message, &partials).unwrap();
+        assert!(bls_verify(&output.public_key, message, &signature).is_ok());
+        assert!(bls_verify(&output.public_key, b"another message", &signature).is_err());
+
+        // any other subset gives the same signature
+        let other_partials = [1, 2, 3]
+            .iter()
+            .map(|i| bls_sign_share(&shares[*i], message))
+            .collect_vec();
+        assert_eq!(
+            bls_combine(output, message, &other_partials).unwrap(),
+            signature
+        );
+
+        // too few signers, an invalid partial signature, or a repeated signer are rejected
+        assert!(bls_combine(output, message, &partials[..2]).is_err());
+        let mut invalid = partials.clone();
+        invalid[1].signature = invalid[1].signature.add(&BLSG1::get_base());
+        assert!(bls_combine(output, message, &invalid).is_err());
+        let repeated = [partials[0], partials[0], partials[1]];
+        assert!(bls_combine(output, message, &repeated).is_err());
+    }
+
+    #[test]
+    fn threshold_schnorr() {
+        let mut prng = test_rng();
+        let shares = honest_dkg::<RistrettoPoint>(2, 3);
+        let output = &shares[0].output;
+        let message = b"threshold Schnorr";
+        let signers = [1u32, 3];
+
+        let nonces = signers
+            .iter()
+            .map(|_| SchnorrNonce::random(&mut prng))
+            .collect_vec();
+        let commitments = signers
+            .iter()
+            .zip(nonces.iter())
+            .map(|(signer, nonce)| (*signer, nonce.commitment(*signer)))
+            .collect_vec();
+        let revealed = signers
+            .iter()
+            .zip(nonces.iter())
+            .map(|(signer, nonce)| (*signer, *nonce.public()))
+            .collect_vec();
+
+        // the revealed nonces must open the commitments
+        let group_nonce = schnorr_group_nonce(&commitments, &revealed).unwrap();
+        let mut substituted = revealed.clone();
+        substituted[1].1 = RistrettoPoint::get_base();
+        assert!(schnorr_group_nonce(&commitments, &substituted).is_err());
+        assert!(schnorr_group_nonce(&commitments, &revealed[..1]).is_err());
+
+        let responses = signers
+            .iter()
+            .zip(nonces.into_iter())
+            .map(|(signer, nonce)| {
+                let share = &shares[*signer as usize - 1];
+                let response =
+                    schnorr_sign_share(share, &signers, nonce, &group_nonce, message).unwrap();
+                (*signer, response)
+            })
+            .collect_vec();
+        let signature = schnorr_combine(output, &revealed, message, &responses).unwrap();
+        assert_eq!(signature.nonce, group_nonce);
+        assert!(schnorr_verify(&output.public_key, message, &signature).is_ok());
+        assert!(schnorr_verify(&output.public_key, b"another message", &signature).is_err());
+
+        // an invalid response is rejected
+        let mut invalid = responses.clone();
+        invalid[0].1 = invalid[0].1.add(&RistrettoScalar::one());
+        assert!(schnorr_combine(output, &revealed, message, &invalid).is_err());
+        // a single signer is below the threshold
+        assert!(schnorr_combine(output, &revealed[..1], message, &responses[..1]).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! The module for time-lock encryption, for escrowed openings of confidential amounts.
+//!
+//! A message is encrypted under a key derived from the solution of an RSW time-lock puzzle:
+//! `y = x^(2^T) mod N`, where `N` is an RSA modulus. The creator of the puzzle knows the
+//! factorization of `N`, and computes `y` quickly by reducing `2^T` modulo `phi(N)`, while anyone
+//! else needs `T` sequential squarings, so that the message stays locked for about that long.
+//!
+//! The solver proves its solution with the proof of exponentiation of Wesolowski, so that the
+//! other parties, e.g., the validators releasing an escrowed transfer, check it with a couple of
+//! exponentiations instead of `T` squarings. An escrowed transfer locks the opening of its amount,
+//! i.e., the amount and its blinds, which the recipient checks against the amount commitments.
+
+use crate::basic::aead::{Aead, Aes256GcmSivAead, AEAD_KEY_LENGTH};
+use crate::basic::pedersen_comm::PedersenCommitmentRistretto;
+use num_bigint::{BigUint, RandBigInt};
+use sha2::{Digest, Sha256};
+use zei_algebra::{prelude::*, ristretto::RistrettoPoint};
+
+pub use crate::bulletproofs::amount::AmountOpening;
+
+/// The default length of the RSA moduli of the puzzles, in bits.
+pub const TIMELOCK_MODULUS_BITS: u64 = 2048;
+/// The length of the primes of the proofs of exponentiation, in bits.
+const CHALLENGE_BITS: usize = 128;
+/// The number of rounds of the Miller-Rabin primality test.
+const MILLER_RABIN_ROUNDS: usize = 40;
+/// The small primes that the candidates are sieved with before the Miller-Rabin test.
+const SMALL_PRIMES: [u32; 15] = [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53];
+
+const KEY_DOMAIN: &[u8] = b"Zei Timelock Key";
+const CHALLENGE_DOMAIN: &[u8] = b"Zei Timelock Challenge";
+
+/// An RSW time-lock puzzle, which is solved by computing `base^(2^delay) mod modulus`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockPuzzle {
+    /// The RSA modulus.
+    pub modulus: BigUint,
+    /// The base of the puzzle.
+    pub base: BigUint,
+    /// The number of sequential squarings.
+    pub delay: u64,
+}
+
+/// The solution of a puzzle, with its proof of exponentiation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockSolution {
+    /// `base^(2^delay) mod modulus`.
+    pub output: BigUint,
+    /// The proof of Wesolowski, `base^floor(2^delay / l) mod modulus`.
+    pub proof: BigUint,
+}
+
+/// A message locked by a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockCiphertext {
+    /// The puzzle.
+    pub puzzle: TimelockPuzzle,
+    /// The AEAD encryption of the message, under the key derived from the solution.
+    pub ciphertext: Vec<u8>,
+}
+
+impl TimelockPuzzle {
+    /// Solve the puzzle with `delay` sequential squarings, and prove the solution.
+    pub fn solve(&self) -> TimelockSolution {
+        let mut output = self.base.clone();
+        for _ in 0..self.delay {
+            output = output.modpow(&BigUint::from(2u32), &self.modulus);
+        }
+
+        // compute `base^floor(2^delay / l)` by long division of `2^delay` by `l`, bit by bit
+        let l = self.challenge(&output);
+        let two = BigUint::from(2u32);
+        let mut remainder = BigUint::one();
+        let mut proof = BigUint::one();
+        for _ in 0..self.delay {
+            remainder <<= 1;
+            proof = proof.modpow(&two, &self.modulus);
+            if remainder >= l {
+                remainder -= &l;
+                proof = proof * &self.base % &self.modulus;
+            }
+        }
+        TimelockSolution { output, proof }
+    }
+
+    /// Check a solution with its proof, i.e., that `proof^l * base^(2^delay mod l) = output`.
+    pub fn verify_solution(&self, solution: &TimelockSolution) -> Result<()> {
+        if solution.output >= self.modulus || solution.proof >= self.modulus {
+            return Err(eg!(ZeiError::TimelockSolutionError));
+        }
+        let l = self.challenge(&solution.output);
+        let r = BigUint::from(2u32).modpow(&BigUint::from(self.delay), &l);
+        let lhs = solution.proof.modpow(&l, &self.modulus) * self.base.modpow(&r, &self.modulus)
+            % &self.modulus;
+        if lhs != solution.output {
+            return Err(eg!(ZeiError::TimelockSolutionError));
+        }
+        Ok(())
+    }
+
+    /// Hash the puzzle and its output to a prime of `CHALLENGE_BITS` bits.
+    fn challenge(&self, output: &BigUint) -> BigUint {
+        let mut counter = 0u64;
+        loop {
+            let mut hasher = Sha256::new();
+            hasher.update(CHALLENGE_DOMAIN);
+            for value in [&self.modulus, &self.base, output] {
+                let bytes = value.to_bytes_le();
+                hasher.update((bytes.len() as u64).to_le_bytes());
+                hasher.update(&bytes);
+            }
+            hasher.update(self.delay.to_le_bytes());
+            hasher.update(counter.to_le_bytes());
+            let mut candidate = BigUint::from_bytes_le(&hasher.finalize()[..CHALLENGE_BITS / 8]);
+            candidate.set_bit(CHALLENGE_BITS as u64 - 1, true);
+            candidate.set_bit(0, true);
+            let witnesses = (2..2 + MILLER_RABIN_ROUNDS as u32)
+                .map(BigUint::from)
+                .collect_vec();
+            if is_probable_prime(&candidate, &witnesses) {
+                return candidate;
+            }
+            counter += 1;
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        for value in [&self.modulus, &self.base] {
+            let value_bytes = value.to_bytes_le();
+            bytes.extend_from_slice(&(value_bytes.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&value_bytes);
+        }
+        bytes.extend_from_slice(&self.delay.to_le_bytes());
+        bytes
+    }
+}
+
+impl TimelockCiphertext {
+    /// Decrypt the message with a solution of the puzzle, after checking its proof.
+    pub fn decrypt(&self, solution: &TimelockSolution) -> Result<Vec<u8>> {
+        self.puzzle.verify_solution(solution).c(d!())?;
+        let key = derive_key(&solution.output);
+        Aes256GcmSivAead::decrypt(&key, &self.puzzle.to_bytes(), &self.ciphertext).c(d!())
+    }
+
+    /// Decrypt an amount opening with a solution of the puzzle, and check it against the
+    /// commitments of the lower and the higher 32 bits of the amount.
+    pub fn decrypt_amount_opening(
+        &self,
+        solution: &TimelockSolution,
+        pc_gens: &PedersenCommitmentRistretto,
+        commitments: &(RistrettoPoint, RistrettoPoint),
+    ) -> Result<AmountOpening> {
+        let bytes = self.decrypt(solution).c(d!())?;
+        let opening = AmountOpening::from_bytes(&bytes).c(d!())?;
+        opening.verify(pc_gens, commitments).c(d!())?;
+        Ok(opening)
+    }
+}
+
+/// Lock a message for about `delay` sequential squarings, with a fresh RSA modulus of
+/// `modulus_bits` bits, e.g., `TIMELOCK_MODULUS_BITS`.
+pub fn timelock_encrypt<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    message: &[u8],
+    delay: u64,
+    modulus_bits: u64,
+) -> Result<TimelockCiphertext> {
+    if modulus_bits < 256 || modulus_bits % 2 != 0 {
+        return Err(eg!(ZeiError::ParameterError));
+    }
+    let p = random_prime(prng, modulus_bits / 2);
+    let q = loop {
+        let q = random_prime(prng, modulus_bits / 2);
+        if q != p {
+            break q;
+        }
+    };
+    let modulus = &p * &q;
+    let phi = (&p - 1u32) * (&q - 1u32);
+    let base = prng.gen_biguint_range(&BigUint::from(2u32), &modulus);
+    let puzzle = TimelockPuzzle {
+        modulus,
+        base,
+        delay,
+    };
+
+    // the trapdoor: `2^delay` reduced modulo the order of the group
+    let exponent = BigUint::from(2u32).modpow(&BigUint::from(delay), &phi);
+    let output = puzzle.base.modpow(&exponent, &puzzle.modulus);
+    let key = derive_key(&output);
+    let ciphertext = Aes256GcmSivAead::encrypt(&key, &puzzle.to_bytes(), message).c(d!())?;
+    Ok(TimelockCiphertext { puzzle, ciphertext })
+}
+
+/// Lock the opening of the commitments of a confidential amount for about `delay` sequential
+/// squarings.
+pub fn timelock_encrypt_amount_opening<R: CryptoRng + RngCore>(
+    prng: &mut R,
+    opening: &AmountOpening,
+    delay: u64,
+    modulus_bits: u64,
+) -> Result<TimelockCiphertext> {
+    timelock_encrypt(prng, &opening.to_bytes(), delay, modulus_bits).c(d!())
+}
+
+fn derive_key(output: &BigUint) -> [u8; AEAD_KEY_LENGTH] {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_DOMAIN);
+    hasher.update(output.to_bytes_le());
+    let mut key = [0u8; AEAD_KEY_LENGTH];
+    key.copy_from_slice(hasher.finalize().as_slice());
+    key
+}
+
+/// Sample a random prime of exactly `bits` bits.
+fn random_prime<R: CryptoRng + RngCore>(prng: &mut R, bits: u64) -> BigUint {
+    loop {
+        let mut candidate = prng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        let upper = &candidate - 3u32;
+        let witnesses = (0..MILLER_RABIN_ROUNDS)
+            .map(|_| prng.gen_biguint_range(&BigUint::from(2u32), &upper))
+            .collect_vec();
+        if is_probable_prime(&candidate, &witnesses) {
+            return candidate;
+        }
+    }
+}
+
+/// Test the primality of an odd number larger than the small primes, with the Miller-Rabin
+/// test for each of the witnesses, which are between 2 and `n - 2`.
+fn is_probable_prime(n: &BigUint, witnesses: &[BigUint]) -> bool {
+    if SMALL_PRIMES.iter().any(|p| (n % *p).is_zero()) {
+        return false;
+    }
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    let two = BigUint::from(2u32);
+    'witnesses: for witness in witnesses.iter() {
+        let mut x = witness.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witnesses;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::{timelock_encrypt, timelock_encrypt_amount_opening, AmountOpening};
+    use crate::basic::pedersen_comm::{PedersenCommitment, PedersenCommitmentRistretto};
+    use ark_std::test_rng;
+    use num_bigint::BigUint;
+    use zei_algebra::{prelude::*, ristretto::RistrettoScalar};
+
+    #[test]
+    fn timelock() {
+        let mut prng = test_rng();
+        let msg = b"released after the delay";
+        let ctext = timelock_encrypt(&mut prng, msg, 1000, 512).unwrap();
+
+        let solution = ctext.puzzle.solve();
+        assert!(ctext.puzzle.verify_solution(&solution).is_ok());
+        assert_eq!(ctext.decrypt(&solution).unwrap(), msg);
+
+        // a wrong output, or a wrong proof, is rejected before the decryption
+        let mut bad_solution = solution.clone();
+        bad_solution.output += 1u32;
+        msg_eq!(
+            ZeiError::TimelockSolutionError,
+            ctext.decrypt(&bad_solution).unwrap_err()
+        );
+        let mut bad_solution = solution;
+        bad_solution.proof = BigUint::one();
+        msg_eq!(
+            ZeiError::TimelockSolutionError,
+            ctext.decrypt(&bad_solution).unwrap_err()
+        );
+
+        assert!(timelock_encrypt(&mut prng, msg, 1000, 128).is_err());
+    }
+
+    #[test]
+    fn escrowed_amount() {
+        let mut prng = test_rng();
+        let pc_gens = PedersenCommitmentRistretto::default();
+        let opening = AmountOpening {
+            amount: (7u64 << 32) + 5,
+            blinds: (
+                RistrettoScalar::random(&mut prng),
+                RistrettoScalar::random(&mut prng),
+            ),
+        };
+        let commitments = (
+            pc_gens.commit(RistrettoScalar::from(5u32), opening.blinds.0),
+            pc_gens.commit(RistrettoScalar::from(7u32), opening.blinds.1),
+        );
+
+        let ctext = timelock_encrypt_amount_opening(&mut prng, &opening, 100, 512).unwrap();
+        let solution = ctext.puzzle.solve();
+        assert_eq!(
+            ctext
+                .decrypt_amount_opening(&solution, &pc_gens, &commitments)
+                .unwrap(),
+            opening
+        );
+
+        // the opening of other commitments is rejected
+        let other_commitments = (commitments.1, commitments.0);
+        msg_eq!(
+            ZeiError::CommitmentVerificationError,
+            ctext
+                .decrypt_amount_opening(&solution, &pc_gens, &other_commitments)
+                .unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,479 @@
+//! The C ABI of the Zei library, for wallets that link it natively, e.g., on iOS and Android.
+//!
+//! Every function returns a [`ZeiStatus`], and writes its result, if any, to a [`ZeiBuffer`]
+//! owned by the library, which the caller must release with [`zei_buffer_free`].
+//! Keys and signatures use their canonical byte representations, while structures such as
+//! asset records, memos and notes are serialized with bincode.
+//! The randomness is provided by the caller as a 32-byte seed, which must be freshly sampled
+//! from a secure source for each call.
+//! The library is built with `panic = 'abort'`, so that a panic cannot unwind across the C ABI;
+//! every malformed argument is reported as a status instead.
+//!
+//! With the `python` feature, the library is also a Python extension module, `zei_ffi`,
+//! for audit scripts that read keys, notes, tracing memos and credentials as they are
+//! published, i.e., in JSON.
+#![deny(missing_docs)]
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use zei::anon_xfr::{
+    decrypt_memo,
+    keys::AXfrKeyPair,
+    structs::{AnonAssetRecord, AxfrOwnerMemo},
+};
+use zei::setup::BulletproofParams;
+use zei::xfr::{
+    asset_record::open_blind_asset_record,
+    gen_xfr_note,
+    sig::{XfrKeyPair, XfrPublicKey, XfrSignature},
+    structs::{AssetRecord, BlindAssetRecord, OwnerMemo, XfrNote, ASSET_TYPE_LENGTH},
+    verify_xfr_note, XfrNotePolicies,
+};
+use zei_algebra::{bls12_381::BLS12_381_SCALAR_LEN, prelude::*};
+
+/// Module for the Python bindings.
+#[cfg(feature = "python")]
+pub mod python;
+
+/// The length of the seeds from which the randomness of a call is derived.
+pub const ZEI_SEED_LENGTH: usize = 32;
+
+/// The status of a call. The values are stable across versions.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeiStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A pointer argument is null.
+    NullPointer = 1,
+    /// An argument could not be deserialized.
+    DeserializationError = 2,
+    /// An argument is invalid for the operation, e.g., a key pair does not own an input.
+    ParameterError = 3,
+    /// A signature or a note is invalid.
+    VerificationError = 4,
+    /// A memo could not be decrypted with the key pair.
+    DecryptionError = 5,
+    /// The library failed internally, e.g., a result could not be serialized.
+    InternalError = 6,
+}
+
+/// A byte buffer allocated by the library.
+#[repr(C)]
+#[derive(Debug)]
+pub struct ZeiBuffer {
+    /// The pointer to the bytes.
+    pub data: *mut u8,
+    /// The number of bytes.
+    pub len: usize,
+}
+
+/// The opening of an anonymous asset record.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ZeiAnonOpening {
+    /// The amount.
+    pub amount: u64,
+    /// The asset type.
+    pub asset_type: [u8; ASSET_TYPE_LENGTH],
+    /// The blinding factor of the commitment, in little-endian.
+    pub blind: [u8; BLS12_381_SCALAR_LEN],
+}
+
+/// Release a buffer returned by the library. A null buffer is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn zei_buffer_free(buffer: ZeiBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// Generate a key pair for confidential transfers.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_keypair_generate(
+    seed: *const u8,
+    out_keypair: *mut ZeiBuffer,
+) -> ZeiStatus {
+    run(|| {
+        let mut prng = prng_from_seed(seed)?;
+        let keypair = XfrKeyPair::generate(&mut prng);
+        write_buffer(out_keypair, keypair.zei_to_bytes())
+    })
+}
+
+/// Return the public key of a key pair for confidential transfers.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_keypair_public_key(
+    keypair: *const u8,
+    keypair_len: usize,
+    out_pub_key: *mut ZeiBuffer,
+) -> ZeiStatus {
+    run(|| {
+        let keypair = xfr_keypair(keypair, keypair_len)?;
+        write_buffer(out_pub_key, keypair.get_pk().to_bytes().to_vec())
+    })
+}
+
+/// Sign a message with a key pair for confidential transfers.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_sign(
+    keypair: *const u8,
+    keypair_len: usize,
+    message: *const u8,
+    message_len: usize,
+    out_signature: *mut ZeiBuffer,
+) -> ZeiStatus {
+    run(|| {
+        let keypair = xfr_keypair(keypair, keypair_len)?;
+        let message = input(message, message_len)?;
+        let signature = keypair
+            .sign(message)
+            .map_err(|_| ZeiStatus::ParameterError)?;
+        write_buffer(out_signature, signature.to_bytes().to_vec())
+    })
+}
+
+/// Verify a signature of a message under a public key for confidential transfers.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_verify(
+    pub_key: *const u8,
+    pub_key_len: usize,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+    signature_len: usize,
+) -> ZeiStatus {
+    run(|| {
+        let pub_key = XfrPublicKey::from_bytes(input(pub_key, pub_key_len)?)
+            .map_err(|_| ZeiStatus::DeserializationError)?;
+        let signature = XfrSignature::from_bytes(input(signature, signature_len)?)
+            .map_err(|_| ZeiStatus::DeserializationError)?;
+        pub_key
+            .verify(input(message, message_len)?, &signature)
+            .map_err(|_| ZeiStatus::VerificationError)
+    })
+}
+
+/// Generate a confidential transfer note, from the bincode serializations of the lists of the
+/// input and output asset records and of the list of the input key pairs.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_note_generate(
+    seed: *const u8,
+    inputs: *const u8,
+    inputs_len: usize,
+    outputs: *const u8,
+    outputs_len: usize,
+    keypairs: *const u8,
+    keypairs_len: usize,
+    out_note: *mut ZeiBuffer,
+) -> ZeiStatus {
+    run(|| {
+        let mut prng = prng_from_seed(seed)?;
+        let inputs: Vec<AssetRecord> = deserialize(inputs, inputs_len)?;
+        let outputs: Vec<AssetRecord> = deserialize(outputs, outputs_len)?;
+        let keypairs: Vec<XfrKeyPair> = deserialize(keypairs, keypairs_len)?;
+        let note = gen_xfr_note(&mut prng, &inputs, &outputs, &keypairs.iter().collect_vec())
+            .map_err(|_| ZeiStatus::ParameterError)?;
+        write_buffer(out_note, serialize(&note)?)
+    })
+}
+
+/// Verify a confidential transfer note without tracing policies, from its bincode serialization.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_note_verify(
+    seed: *const u8,
+    note: *const u8,
+    note_len: usize,
+) -> ZeiStatus {
+    run(|| {
+        let mut prng = prng_from_seed(seed)?;
+        let note: XfrNote = deserialize(note, note_len)?;
+        let policies =
+            XfrNotePolicies::empty_policies(note.body.inputs.len(), note.body.outputs.len());
+        verify_xfr_note(
+            &mut prng,
+            &mut BulletproofParams::default(),
+            &note,
+            &policies.to_ref(),
+        )
+        .map_err(|_| ZeiStatus::VerificationError)
+    })
+}
+
+/// Open a confidential asset record with its owner memo, which can be absent for
+/// non-confidential records. The open asset record is returned in its bincode serialization.
+#[no_mangle]
+pub unsafe extern "C" fn zei_xfr_owner_memo_decrypt(
+    record: *const u8,
+    record_len: usize,
+    owner_memo: *const u8,
+    owner_memo_len: usize,
+    keypair: *const u8,
+    keypair_len: usize,
+    out_record: *mut ZeiBuffer,
+) -> ZeiStatus {
+    run(|| {
+        let record: BlindAssetRecord = deserialize(record, record_len)?;
+        let owner_memo: Option<OwnerMemo> = deserialize(owner_memo, owner_memo_len)?;
+        let keypair = xfr_keypair(keypair, keypair_len)?;
+        let open_record = open_blind_asset_record(&record, &owner_memo, &keypair)
+            .map_err(|_| ZeiStatus::DecryptionError)?;
+        write_buffer(out_record, serialize(&open_record)?)
+    })
+}
+
+/// Decrypt the owner memo of an anonymous asset record, and check it against the commitment.
+#[no_mangle]
+pub unsafe extern "C" fn zei_axfr_owner_memo_decrypt(
+    record: *const u8,
+    record_len: usize,
+    owner_memo: *const u8,
+    owner_memo_len: usize,
+    keypair: *const u8,
+    keypair_len: usize,
+    out_opening: *mut ZeiAnonOpening,
+) -> ZeiStatus {
+    run(|| {
+        if out_opening.is_null() {
+            return Err(ZeiStatus::NullPointer);
+        }
+        let record: AnonAssetRecord = deserialize(record, record_len)?;
+        let owner_memo: AxfrOwnerMemo = deserialize(owner_memo, owner_memo_len)?;
+        let keypair = AXfrKeyPair::zei_from_bytes(input(keypair, keypair_len)?)
+            .map_err(|_| ZeiStatus::DeserializationError)?;
+        let (amount, asset_type, blind) =
+            decrypt_memo(&owner_memo, &keypair, &record).map_err(|_| ZeiStatus::DecryptionError)?;
+        let mut opening = ZeiAnonOpening {
+            amount,
+            asset_type: asset_type.0,
+            blind: [0u8; BLS12_381_SCALAR_LEN],
+        };
+        opening.blind.copy_from_slice(&blind.to_bytes());
+        *out_opening = opening;
+        Ok(())
+    })
+}
+
+fn run<F: FnOnce() -> core::result::Result<(), ZeiStatus>>(f: F) -> ZeiStatus {
+    match f() {
+        Ok(()) => ZeiStatus::Ok,
+        Err(status) => status,
+    }
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> core::result::Result<&'a [u8], ZeiStatus> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(ZeiStatus::NullPointer);
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+unsafe fn write_buffer(out: *mut ZeiBuffer, bytes: Vec<u8>) -> core::result::Result<(), ZeiStatus> {
+    if out.is_null() {
+        return Err(ZeiStatus::NullPointer);
+    }
+    let len = bytes.len();
+    let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+    *out = ZeiBuffer { data, len };
+    Ok(())
+}
+
+unsafe fn prng_from_seed(seed: *const u8) -> core::result::Result<ChaChaRng, ZeiStatus> {
+    let mut bytes = [0u8; ZEI_SEED_LENGTH];
+    bytes.copy_from_slice(input(seed, ZEI_SEED_LENGTH)?);
+    Ok(ChaChaRng::from_seed(bytes))
+}
+
+unsafe fn xfr_keypair(data: *const u8, len: usize) -> core::result::Result<XfrKeyPair, ZeiStatus> {
+    XfrKeyPair::zei_from_bytes(input(data, len)?).map_err(|_| ZeiStatus::DeserializationError)
+}
+
+unsafe fn deserialize<T: for<'de> Deserialize<'de>>(
+    data: *const u8,
+    len: usize,
+) -> core::result::Result<T, ZeiStatus> {
+    bincode::deserialize(input(data, len)?).map_err(|_| ZeiStatus::DeserializationError)
+}
+
+fn serialize<T: Serialize>(value: &T) -> core::result::Result<Vec<u8>, ZeiStatus> {
+    bincode::serialize(value).map_err(|_| ZeiStatus::InternalError)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use ark_std::test_rng;
+    use zei::xfr::{
+        asset_record::AssetRecordType,
+        structs::{AssetRecordTemplate, AssetType},
+    };
+
+    fn empty_buffer() -> ZeiBuffer {
+        ZeiBuffer {
+            data: std::ptr::null_mut(),
+            len: 0,
+        }
+    }
+
+    unsafe fn take(buffer: ZeiBuffer) -> Vec<u8> {
+        let bytes = std::slice::from_raw_parts(buffer.data, buffer.len).to_vec();
+        zei_buffer_free(buffer);
+        bytes
+    }
+
+    #[test]
+    fn ffi_sign_and_verify() {
+        unsafe {
+            let seed = [1u8; ZEI_SEED_LENGTH];
+            let mut keypair = empty_buffer();
+            assert_eq!(
+                zei_xfr_keypair_generate(seed.as_ptr(), &mut keypair),
+                ZeiStatus::Ok
+            );
+            let keypair = take(keypair);
+            let mut pub_key = empty_buffer();
+            assert_eq!(
+                zei_xfr_keypair_public_key(keypair.as_ptr(), keypair.len(), &mut pub_key),
+                ZeiStatus::Ok
+            );
+            let pub_key = take(pub_key);
+
+            let message = b"message";
+            let mut signature = empty_buffer();
+            assert_eq!(
+                zei_xfr_sign(
+                    keypair.as_ptr(),
+                    keypair.len(),
+                    message.as_ptr(),
+                    message.len(),
+                    &mut signature
+                ),
+                ZeiStatus::Ok
+            );
+            let signature = take(signature);
+            let verify = |message: &[u8]| {
+                zei_xfr_verify(
+                    pub_key.as_ptr(),
+                    pub_key.len(),
+                    message.as_ptr(),
+                    message.len(),
+                    signature.as_ptr(),
+                    signature.len(),
+                )
+            };
+            assert_eq!(verify(message), ZeiStatus::Ok);
+            assert_eq!(verify(b"other message"), ZeiStatus::VerificationError);
+
+            assert_eq!(
+                zei_xfr_keypair_public_key(std::ptr::null(), 10, &mut empty_buffer()),
+                ZeiStatus::NullPointer
+            );
+            assert_eq!(
+                zei_xfr_keypair_public_key(keypair.as_ptr(), 3, &mut empty_buffer()),
+                ZeiStatus::DeserializationError
+            );
+        }
+    }
+
+    #[test]
+    fn ffi_note_and_memo() {
+        let mut prng = test_rng();
+        let sender = XfrKeyPair::generate(&mut prng);
+        let receiver = XfrKeyPair::generate(&mut prng);
+        let asset_type = AssetType::from_identical_byte(1);
+        let record_type = AssetRecordType::ConfidentialAmount_ConfidentialAssetType;
+        let record = |amount: u64, keypair: &XfrKeyPair| {
+            let template = AssetRecordTemplate::with_no_asset_tracing(
+                amount,
+                asset_type,
+                record_type,
+                keypair.get_pk(),
+            );
+            AssetRecord::from_template_no_identity_tracing(&mut prng, &template).unwrap()
+        };
+        let inputs = bincode::serialize(&vec![record(10, &sender)]).unwrap();
+        let outputs = bincode::serialize(&vec![record(10, &receiver)]).unwrap();
+        let keypairs = bincode::serialize(&vec![sender]).unwrap();
+
+        unsafe {
+            let seed = [2u8; ZEI_SEED_LENGTH];
+            let mut note = empty_buffer();
+            assert_eq!(
+                zei_xfr_note_generate(
+                    seed.as_ptr(),
+                    inputs.as_ptr(),
+                    inputs.len(),
+                    outputs.as_ptr(),
+                    outputs.len(),
+                    keypairs.as_ptr(),
+                    keypairs.len(),
+                    &mut note
+                ),
+                ZeiStatus::Ok
+            );
+            let note_bytes = take(note);
+            assert_eq!(
+                zei_xfr_note_verify(seed.as_ptr(), note_bytes.as_ptr(), note_bytes.len()),
+                ZeiStatus::Ok
+            );
+
+            let note: XfrNote = bincode::deserialize(&note_bytes).unwrap();
+            let mut tampered = note.clone();
+            tampered.body.outputs.reverse();
+            tampered.body.outputs.push(note.body.outputs[0].clone());
+            let tampered = bincode::serialize(&tampered).unwrap();
+            assert_eq!(
+                zei_xfr_note_verify(seed.as_ptr(), tampered.as_ptr(), tampered.len()),
+                ZeiStatus::VerificationError
+            );
+
+            let output = bincode::serialize(&note.body.outputs[0]).unwrap();
+            let memo = bincode::serialize(&note.body.owners_memos[0]).unwrap();
+            let decrypt = |keypair: &XfrKeyPair, out: &mut ZeiBuffer| {
+                let keypair = keypair.zei_to_bytes();
+                zei_xfr_owner_memo_decrypt(
+                    output.as_ptr(),
+                    output.len(),
+                    memo.as_ptr(),
+                    memo.len(),
+                    keypair.as_ptr(),
+                    keypair.len(),
+                    out,
+                )
+            };
+            let mut open_record = empty_buffer();
+            assert_eq!(decrypt(&receiver, &mut open_record), ZeiStatus::Ok);
+            zei_buffer_free(open_record);
+            let other = XfrKeyPair::generate(&mut prng);
+            assert_eq!(
+                decrypt(&other, &mut empty_buffer()),
+                ZeiStatus::DecryptionError
+            );
+
+            // a truncated lock is reported, not a panic
+            let mut truncated = note.body.owners_memos[0].clone().unwrap();
+            truncated.lock_bytes.truncate(20);
+            let truncated = bincode::serialize(&Some(truncated)).unwrap();
+            let keypair = receiver.zei_to_bytes();
+            assert_eq!(
+                zei_xfr_owner_memo_decrypt(
+                    output.as_ptr(),
+                    output.len(),
+                    truncated.as_ptr(),
+                    truncated.len(),
+                    keypair.as_ptr(),
+                    keypair.len(),
+                    &mut empty_buffer(),
+                ),
+                ZeiStatus::DecryptionError
+            );
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use zei::anon_creds::{
+    ac_verify, ACCommitment, ACIssuerPublicKey, ACRevealProof, ACVerificationPolicy, Attr,
+};
+use zei::setup::BulletproofParams;
+use zei::xfr::{
+    sig::{XfrKeyPair, XfrPublicKey, XfrSignature},
+    structs::{AssetTracerKeyPair, XfrBody, XfrNote},
+    trace_assets, verify_xfr_note, XfrNotePolicies,
+};
+use zei_algebra::prelude::*;
+
+/// A key pair for confidential transfers.
+#[pyclass(name = "XfrKeyPair")]
+pub struct PyXfrKeyPair {
+    inner: XfrKeyPair,
+}
+
+#[pymethods]
+impl PyXfrKeyPair {
+    /// Generate a key pair, from a 32-byte seed if given, or else from `os.urandom`.
+    #[staticmethod]
+    #[args(seed = "None")]
+    fn generate(py: Python<'_>, seed: Option<&[u8]>) -> PyResult<Self> {
+        let mut prng = prng(py, seed)?;
+        Ok(PyXfrKeyPair {
+            inner: XfrKeyPair::generate(&mut prng),
+        })
+    }
+
+    /// Parse a key pair from its bytes.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        let inner = XfrKeyPair::zei_from_bytes(bytes).map_err(value_error)?;
+        Ok(PyXfrKeyPair { inner })
+    }
+
+    /// Return the bytes of the key pair.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.zei_to_bytes())
+    }
+
+    /// Return the bytes of the public key.
+    fn public_key<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.get_pk().to_bytes())
+    }
+
+    /// Sign a message.
+    fn sign<'py>(&self, py: Python<'py>, message: &[u8]) -> PyResult<&'py PyBytes> {
+        let signature = self.inner.sign(message).map_err(value_error)?;
+        Ok(PyBytes::new(py, &signature.to_bytes()))
+    }
+
+    /// Check a signature of a message under the bytes of a public key.
+    #[staticmethod]
+    fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> PyResult<bool> {
+        let public_key = XfrPublicKey::from_bytes(public_key).map_err(value_error)?;
+        let signature = XfrSignature::from_bytes(signature).map_err(value_error)?;
+        Ok(public_key.verify(message, &signature).is_ok())
+    }
+}
+
+/// Check a confidential transfer note in JSON, without tracing policies.
+#[pyfunction]
+fn verify_xfr_note_json(py: Python<'_>, note: &str) -> PyResult<bool> {
+    let note: XfrNote = from_json(note)?;
+    let policies = XfrNotePolicies::empty_policies(note.body.inputs.len(), note.body.outputs.len());
+    let mut prng = prng(py, None)?;
+    Ok(verify_xfr_note(
+        &mut prng,
+        &mut BulletproofParams::default(),
+        &note,
+        &policies.to_ref(),
+    )
+    .is_ok())
+}
+
+/// Decrypt the tracing memos of a confidential transfer body in JSON with the key pair of an
+/// asset tracer in JSON. Return, for each traced record, the amount, the asset type,
+/// the identity attributes in JSON and the bytes of the public key of the owner.
+#[pyfunction]
+#[allow(clippy::type_complexity)]
+fn trace_xfr_body_json<'py>(
+    py: Python<'py>,
+    body: &str,
+    tracer_keypair: &str,
+) -> PyResult<Vec<(u64, &'py PyBytes, Vec<String>, &'py PyBytes)>> {
+    let body: XfrBody = from_json(body)?;
+    let tracer_keypair: AssetTracerKeyPair = from_json(tracer_keypair)?;
+    let records = trace_assets(&body, &tracer_keypair).map_err(value_error)?;
+    records
+        .into_iter()
+        .map(|(amount, asset_type, attrs, pub_key)| {
+            Ok((
+                amount,
+                PyBytes::new(py, &asset_type.0),
+                attrs
+                    .iter()
+                    .map(to_json)
+                    .collect::<PyResult<Vec<String>>>()?,
+                PyBytes::new(py, &pub_key.to_bytes()),
+            ))
+        })
+        .collect()
+}
+
+/// Check the reveal proof of an anonymous credential for some attributes in JSON, where the
+/// hidden attributes are `None`, given the issuer public key, the commitment and the proof
+/// in JSON, under the verification policy in JSON, if any, or the default policy.
+#[pyfunction(policy = "None")]
+fn verify_credential_json(
+    issuer_pub_key: &str,
+    attrs: Vec<Option<&str>>,
+    commitment: &str,
+    proof: &str,
+    policy: Option<&str>,
+) -> PyResult<bool> {
+    let attrs = attrs
+        .into_iter()
+        .map(|attr| attr.map(from_json::<Attr>).transpose())
+        .collect::<PyResult<Vec<Option<Attr>>>>()?;
+    let issuer_pub_key: ACIssuerPublicKey = from_json(issuer_pub_key)?;
+    let commitment: ACCommitment = from_json(commitment)?;
+    let proof: ACRevealProof = from_json(proof)?;
+    let policy: ACVerificationPolicy = match policy {
+        Some(policy) => from_json(policy)?,
+        None => ACVerificationPolicy::default(),
+    };
+    Ok(ac_verify(&issuer_pub_key, &attrs, &commitment, &proof, &policy).is_ok())
+}
+
+/// The Python module.
+#[pymodule]
+fn zei_ffi(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyXfrKeyPair>()?;
+    m.add_function(wrap_pyfunction!(verify_xfr_note_json, m)?)?;
+    m.add_function(wrap_pyfunction!(trace_xfr_body_json, m)?)?;
+    m.add_function(wrap_pyfunction!(verify_credential_json, m)?)?;
+    Ok(())
+}
+
+fn value_error<E: core::fmt::Debug>(err: E) -> PyErr {
+    PyValueError::new_err(format!("{:?}", err))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json).map_err(value_error)
+}
+
+fn to_json<T: Serialize>(value: &T) -> PyResult<String> {
+    serde_json::to_string(value).map_err(value_error)
+}
+
+/// The verification randomness must not be known to the prover,
+/// so it is taken from `os.urandom` unless a seed is given.
+fn prng(py: Python<'_>, seed: Option<&[u8]>) -> PyResult<ChaChaRng> {
+    let mut bytes = [0u8; crate::ZEI_SEED_LENGTH];
+    match seed {
+        Some(seed) => {
+            if seed.len() != crate::ZEI_SEED_LENGTH {
+                return Err(PyValueError::new_err("the seed must have 32 bytes"));
+            }
+            bytes.copy_from_slice(seed);
+        }
+        None => {
+            let random: Vec<u8> = py
+                .import("os")?
+                .call_method1("urandom", (crate::ZEI_SEED_LENGTH,))?
+                .extract()?;
+            bytes.copy_from_slice(&random);
+        }
+    }
+    Ok(ChaChaRng::from_seed(bytes))
+}
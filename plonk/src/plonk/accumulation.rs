@@ -0,0 +1,371 @@
+//! An accumulation scheme for TurboPlonk proofs over KZG, for block-level verification.
+//!
+//! Verifying a proof ends with a KZG pairing check. The accumulation scheme folds these checks
+//! into a [`PlonkAccumulator`], which is a single pairing check of the same form:
+//! - [`aggregate_proofs`] (the accumulation prover) verifies the proofs up to their final
+//!   pairing checks, and adds these checks to a previous accumulator, with the powers of a
+//!   Fiat-Shamir challenge that depends on the previous accumulator and on every new check;
+//! - [`verify_accumulation`] (the accumulation verifier) recomputes the same folding, without
+//!   any pairing, and checks that it gives the claimed accumulator;
+//! - [`PlonkAccumulator::decide`] (the decider) checks the accumulator with two pairings.
+//!
+//! The accumulator is a public object: a block producer can aggregate the proofs of a block and
+//! publish the accumulator, the verifiers of each block only check the folding, and the pairings
+//! of any number of blocks are checked once, on the last accumulator. If any folded proof is
+//! invalid, the accumulator is valid with probability at most `N / |F|` for `N` folded checks.
+//!
+//! The accumulator is not a succinct proof: the accumulation verifier still reads every proof
+//! and does the group operations of its verification, only the pairings are saved.
+
+use crate::plonk::{
+    constraint_system::ConstraintSystem,
+    errors::PlonkError,
+    indexer::{PlonkPf, PlonkVK},
+    verifier::prepare_opening,
+};
+use crate::poly_commit::kzg_poly_com::{KZGCommitmentSchemeBLS, KZGPairingClaim};
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+/// An accumulator of the final pairing checks of TurboPlonk proofs over KZG.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlonkAccumulator {
+    claim: KZGPairingClaim,
+    num_proofs: u64,
+}
+
+impl PlonkAccumulator {
+    /// Create an empty accumulator, to which the first proofs are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the number of accumulated proofs.
+    pub fn num_proofs(&self) -> u64 {
+        self.num_proofs
+    }
+
+    /// Check all the accumulated proofs with a single pairing check.
+    pub fn decide(&self, pcs: &KZGCommitmentSchemeBLS) -> Result<()> {
+        if self.num_proofs == 0 {
+            return Err(eg!(PlonkError::FuncParamsError));
+        }
+        if self.claim.is_valid(pcs) {
+            Ok(())
+        } else {
+            Err(eg!(PlonkError::VerificationError))
+        }
+    }
+}
+
+/// A TurboPlonk proof to be accumulated, with what is needed to verify it.
+pub struct PlonkAccumulationInstance<'a, CS> {
+    /// The transcript, in the state expected by the verifier of this proof.
+    pub transcript: Transcript,
+    /// The constraint system.
+    pub cs: &'a CS,
+    /// The verifier parameters.
+    pub verifier_params: &'a PlonkVK<KZGCommitmentSchemeBLS>,
+    /// The public inputs.
+    pub public_inputs: &'a [BLSScalar],
+    /// The proof.
+    pub proof: &'a PlonkPf<KZGCommitmentSchemeBLS>,
+}
+
+/// The folding of new proofs into a previous accumulator, which is the same for the
+/// accumulation prover and the accumulation verifier.
+pub struct PlonkAccumulation {
+    previous: PlonkAccumulator,
+    claims: Vec<KZGPairingClaim>,
+}
+
+impl PlonkAccumulation {
+    /// Start adding proofs to the accumulator `previous`.
+    pub fn new(previous: &PlonkAccumulator) -> Self {
+        PlonkAccumulation {
+            previous: previous.clone(),
+            claims: vec![],
+        }
+    }
+
+    /// Verify a proof up to its final pairing check, and add that check.
+    pub fn add_proof<CS: ConstraintSystem<Field = BLSScalar>>(
+        &mut self,
+        transcript: &mut Transcript,
+        pcs: &KZGCommitmentSchemeBLS,
+        cs: &CS,
+        verifier_params: &PlonkVK<KZGCommitmentSchemeBLS>,
+        pi: &[BLSScalar],
+        proof: &PlonkPf<KZGCommitmentSchemeBLS>,
+    ) -> Result<()> {
+        if pi.len() != verifier_params.public_vars_constraint_indices.len() {
+            return Err(eg!(PlonkError::FuncParamsError));
+        }
+
+        let opening = prepare_opening(transcript, pcs, cs, verifier_params, pi, proof);
+        self.claims.push(pcs.pairing_claim_diff_points(
+            &opening.cm_vec,
+            &opening.point_vec,
+            &opening.eval_vec,
+            &opening.proofs,
+            &opening.challenge,
+        ));
+        Ok(())
+    }
+
+    /// Fold the added checks into the previous accumulator, with the powers of the challenge
+    /// `rho`, i.e., `previous + rho * claim_1 + ... + rho^N * claim_N`.
+    pub fn finish(self) -> PlonkAccumulator {
+        let rho = self.challenge();
+        let mut accumulator = self.previous;
+        let mut weight = rho;
+        for claim in self.claims.iter() {
+            accumulator.claim.accumulate(claim, &weight);
+            weight.mul_assign(&rho);
+        }
+        accumulator.num_proofs += self.claims.len() as u64;
+        accumulator
+    }
+
+    /// Derive the challenge from the previous accumulator and every new check.
+    fn challenge(&self) -> BLSScalar {
+        let mut transcript = Transcript::new(b"Zei Plonk Accumulation");
+        transcript.append_u64(b"previous proofs", self.previous.num_proofs);
+        append_claim(&mut transcript, &self.previous.claim);
+        transcript.append_u64(b"new proofs", self.claims.len() as u64);
+        for claim in self.claims.iter() {
+            append_claim(&mut transcript, claim);
+        }
+        let mut seed = [0u8; 32];
+        transcript.challenge_bytes(b"rho", &mut seed);
+        BLSScalar::random(&mut ChaChaRng::from_seed(seed))
+    }
+}
+
+fn append_claim(transcript: &mut Transcript, claim: &KZGPairingClaim) {
+    transcript.append_message(b"left", &claim.left.to_compressed_bytes());
+    transcript.append_message(b"right", &claim.right.to_compressed_bytes());
+}
+
+/// Add a list of proofs, which must all be verified with the same KZG parameters, to the
+/// accumulator `previous`, which is `PlonkAccumulator::new()` for the first proofs.
+pub fn aggregate_proofs<CS: ConstraintSystem<Field = BLSScalar>>(
+    pcs: &KZGCommitmentSchemeBLS,
+    previous: &PlonkAccumulator,
+    instances: Vec<PlonkAccumulationInstance<CS>>,
+) -> Result<PlonkAccumulator> {
+    if instances.is_empty() {
+        return Err(eg!(PlonkError::FuncParamsError));
+    }
+    let mut accumulation = PlonkAccumulation::new(previous);
+    for mut instance in instances {
+        accumulation
+            .add_proof(
+                &mut instance.transcript,
+                pcs,
+                instance.cs,
+                instance.verifier_params,
+                instance.public_inputs,
+                instance.proof,
+            )
+            .c(d!())?;
+    }
+    Ok(accumulation.finish())
+}
+
+/// Check that `accumulator` adds the proofs to the accumulator `previous`, without deciding it.
+pub fn verify_accumulation<CS: ConstraintSystem<Field = BLSScalar>>(
+    pcs: &KZGCommitmentSchemeBLS,
+    previous: &PlonkAccumulator,
+    instances: Vec<PlonkAccumulationInstance<CS>>,
+    accumulator: &PlonkAccumulator,
+) -> Result<()> {
+    if aggregate_proofs(pcs, previous, instances).c(d!())? != *accumulator {
+        return Err(eg!(PlonkError::VerificationError));
+    }
+    Ok(())
+}
+
+/// Check that `accumulator` adds the proofs to the accumulator `previous`, and decide it.
+/// The previous accumulator is decided as well, so it does not need to be trusted.
+pub fn verify_aggregated<CS: ConstraintSystem<Field = BLSScalar>>(
+    pcs: &KZGCommitmentSchemeBLS,
+    previous: &PlonkAccumulator,
+    instances: Vec<PlonkAccumulationInstance<CS>>,
+    accumulator: &PlonkAccumulator,
+) -> Result<()> {
+    verify_accumulation(pcs, previous, instances, accumulator).c(d!())?;
+    accumulator.decide(pcs).c(d!())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::plonk::{
+        accumulation::{
+            aggregate_proofs, verify_accumulation, verify_aggregated, PlonkAccumulationInstance,
+            PlonkAccumulator,
+        },
+        constraint_system::{ConstraintSystem, TurboCS},
+        indexer::{indexer, PlonkPf, PlonkVK},
+        prover::prover,
+        verifier::verifier,
+    };
+    use crate::poly_commit::kzg_poly_com::{KZGCommitmentScheme, KZGCommitmentSchemeBLS};
+    use ark_std::test_rng;
+    use merlin::Transcript;
+    use zei_algebra::{bls12_381::BLSScalar, prelude::*};
+
+    fn build_cs(a: u32, b: u32) -> (TurboCS<BLSScalar>, Vec<BLSScalar>) {
+        // (a + b) * b = c, where c is public
+        let mut cs = TurboCS::new();
+        let a_var = cs.new_variable(BLSScalar::from(a));
+        let b_var = cs.new_variable(BLSScalar::from(b));
+        let sum_var = cs.add(a_var, b_var);
+        let c_var = cs.mul(sum_var, b_var);
+        cs.prepare_pi_variable(c_var);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+        (cs, witness)
+    }
+
+    fn instances<'a>(
+        proofs: &'a [PlonkPf<KZGCommitmentSchemeBLS>],
+        cs: &'a TurboCS<BLSScalar>,
+        verifier_params: &'a PlonkVK<KZGCommitmentSchemeBLS>,
+        public_inputs: &'a [Vec<BLSScalar>],
+    ) -> Vec<PlonkAccumulationInstance<'a, TurboCS<BLSScalar>>> {
+        proofs
+            .iter()
+            .zip(public_inputs.iter())
+            .map(|(proof, pi)| PlonkAccumulationInstance {
+                transcript: Transcript::new(b"TestAccumulation"),
+                cs,
+                verifier_params,
+                public_inputs: pi,
+                proof,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plonk_accumulation() {
+        let mut prng = test_rng();
+        let pcs: KZGCommitmentSchemeBLS = KZGCommitmentScheme::new(20, &mut prng);
+
+        let (cs, _) = build_cs(0, 0);
+        let prover_params = indexer(&cs, &pcs).unwrap();
+        let verifier_params = prover_params.get_verifier_params();
+
+        let values = [(3u32, 4u32), (5, 1), (2, 7), (6, 6)];
+        let mut proofs = vec![];
+        let mut public_inputs = vec![];
+        for (a, b) in values.iter() {
+            let (_, witness) = build_cs(*a, *b);
+            let mut transcript = Transcript::new(b"TestAccumulation");
+            proofs.push(
+                prover(
+                    &mut prng,
+                    &mut transcript,
+                    &pcs,
+                    &cs,
+                    &prover_params,
+                    &witness,
+                )
+                .unwrap(),
+            );
+            public_inputs.push(vec![BLSScalar::from((a + b) * b)]);
+        }
+
+        // a first block of three proofs, then a block of one proof
+        let empty = PlonkAccumulator::new();
+        let first = aggregate_proofs(
+            &pcs,
+            &empty,
+            instances(&proofs[..3], &cs, &verifier_params, &public_inputs[..3]),
+        )
+        .unwrap();
+        assert_eq!(first.num_proofs(), 3);
+        assert!(verify_aggregated(
+            &pcs,
+            &empty,
+            instances(&proofs[..3], &cs, &verifier_params, &public_inputs[..3]),
+            &first
+        )
+        .is_ok());
+        let second = aggregate_proofs(
+            &pcs,
+            &first,
+            instances(&proofs[3..], &cs, &verifier_params, &public_inputs[3..]),
+        )
+        .unwrap();
+        assert_eq!(second.num_proofs(), 4);
+        assert!(verify_accumulation(
+            &pcs,
+            &first,
+            instances(&proofs[3..], &cs, &verifier_params, &public_inputs[3..]),
+            &second
+        )
+        .is_ok());
+        assert!(second.decide(&pcs).is_ok());
+
+        // the accumulator must fold exactly the given proofs into the given previous accumulator
+        assert!(verify_accumulation(
+            &pcs,
+            &empty,
+            instances(&proofs[3..], &cs, &verifier_params, &public_inputs[3..]),
+            &second
+        )
+        .is_err());
+        assert!(verify_accumulation(
+            &pcs,
+            &first,
+            instances(&proofs[..3], &cs, &verifier_params, &public_inputs[..3]),
+            &second
+        )
+        .is_err());
+
+        // a single wrong statement makes the accumulator, and every later one, fail to decide
+        let mut wrong_public_inputs = public_inputs.clone();
+        wrong_public_inputs[1][0] = BLSScalar::from(7u32);
+        let wrong = aggregate_proofs(
+            &pcs,
+            &empty,
+            instances(
+                &proofs[..3],
+                &cs,
+                &verifier_params,
+                &wrong_public_inputs[..3],
+            ),
+        )
+        .unwrap();
+        assert!(wrong.decide(&pcs).is_err());
+        let later = aggregate_proofs(
+            &pcs,
+            &wrong,
+            instances(&proofs[3..], &cs, &verifier_params, &public_inputs[3..]),
+        )
+        .unwrap();
+        assert!(later.decide(&pcs).is_err());
+        for (i, (proof, pi)) in proofs.iter().zip(wrong_public_inputs.iter()).enumerate() {
+            let mut transcript = Transcript::new(b"TestAccumulation");
+            let res = verifier(&mut transcript, &pcs, &cs, &verifier_params, pi, proof);
+            assert_eq!(res.is_ok(), i != 1);
+        }
+
+        // the public inputs must match the constraint system
+        let mut short_public_inputs = public_inputs.clone();
+        short_public_inputs[0].clear();
+        assert!(aggregate_proofs(
+            &pcs,
+            &empty,
+            instances(&proofs, &cs, &verifier_params, &short_public_inputs)
+        )
+        .is_err());
+
+        // an empty accumulator does not verify anything, and nothing is added without proofs
+        assert!(empty.decide(&pcs).is_err());
+        let no_instances: Vec<PlonkAccumulationInstance<TurboCS<BLSScalar>>> = vec![];
+        assert!(aggregate_proofs(&pcs, &empty, no_instances).is_err());
+    }
+}
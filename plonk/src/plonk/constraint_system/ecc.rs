@@ -80,7 +80,7 @@ const EDWARDS_D: [u8; 32] = [
 /// The function compute
 /// {4^i * [G]}_{i=0..n-1}, {2 * 4^i * [G]}_{i=0..n-1}, and {3 * 4^i * [G]}_{i=0..n-1}
 /// [G] is represented in extended form because doubling/addition is more efficient.
-fn compute_base_multiples(base: JubjubPoint, n: usize) -> Vec<Vec<JubjubPoint>> {
+pub(crate) fn compute_base_multiples(base: JubjubPoint, n: usize) -> Vec<Vec<JubjubPoint>> {
     let mut bases = vec![vec![], vec![], vec![]];
     let mut point = base;
     for i in 0..n {
@@ -110,6 +110,33 @@ impl TurboCS<BLSScalar> {
         self.prepare_pi_variable(point_var.1);
     }
 
+    /// Create variables for a point, together with its value.
+    pub fn new_extended_point_variable(&mut self, point: &JubjubPoint) -> ExtendedPointVar {
+        let point_var = self.new_point_variable(Point::from(point));
+        ExtendedPointVar(point_var, *point)
+    }
+
+    /// Insert a constraint that a point is on the curve:
+    /// -x^2 + y^2 = 1 + d * x^2 * y^2
+    pub fn insert_on_curve_check(&mut self, point_var: &PointVar) {
+        let edwards_d = BLSScalar::from_bytes(&EDWARDS_D[..]).unwrap();
+        let x_sq = self.mul(point_var.0, point_var.0);
+        let y_sq = self.mul(point_var.1, point_var.1);
+        let x_sq_y_sq = self.mul(x_sq, y_sq);
+        let zero = BLSScalar::zero();
+        let one = BLSScalar::one();
+        let zero_var = self.zero_var();
+        let one_var = self.one_var();
+        self.insert_lc_gate(
+            &[x_sq, y_sq, x_sq_y_sq, zero_var],
+            one_var,
+            one.neg(),
+            one,
+            edwards_d.neg(),
+            zero,
+        );
+    }
+
     /// Insert a curve addition gate: (x1, y1) + (x2, y2) = (x3, y3)
     ///
     /// x-coordinate constraint:
@@ -322,11 +349,52 @@ impl TurboCS<BLSScalar> {
         }
         p_var_ext.0
     }
+
+    /// Variable-base scalar multiplication:
+    /// Given a point variable `[P]` and the little-endian boolean variables
+    /// `[b0, ..., b_{n-1}]` of a scalar s, returns `s * [P]` by double-and-add.
+    /// The bits should already be boolean constrained, e.g. by `range_check`.
+    pub fn var_base_scalar_mul(
+        &mut self,
+        base: &ExtendedPointVar,
+        b_scalar_var: &[VarIndex],
+    ) -> ExtendedPointVar {
+        assert!(!b_scalar_var.is_empty(), "the scalar has no bits");
+        let n_bits = b_scalar_var.len();
+
+        let mut p_var_ext = self.ecc_select_or_identity(base, b_scalar_var[n_bits - 1]);
+        for &bit in b_scalar_var[..n_bits - 1].iter().rev() {
+            p_var_ext = self.ecc_add(&p_var_ext.0, &p_var_ext.0, &p_var_ext.1, &p_var_ext.1);
+            let tmp_var_ext = self.ecc_select_or_identity(base, bit);
+            p_var_ext = self.ecc_add(&p_var_ext.0, &tmp_var_ext.0, &p_var_ext.1, &tmp_var_ext.1);
+        }
+        p_var_ext
+    }
+
+    /// Given a point variable `[P]` and a boolean variable b, returns `[P]` if b = 1,
+    /// and the identity (0, 1) if b = 0.
+    fn ecc_select_or_identity(
+        &mut self,
+        point: &ExtendedPointVar,
+        bit: VarIndex,
+    ) -> ExtendedPointVar {
+        let zero_var = self.zero_var();
+        let one_var = self.one_var();
+        let x = self.select(zero_var, point.0 .0, bit);
+        let y = self.select(one_var, point.0 .1, bit);
+        let value = if self.witness[bit] == BLSScalar::zero() {
+            JubjubPoint::get_identity()
+        } else {
+            point.1
+        };
+        ExtendedPointVar(PointVar(x, y), value)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use crate::plonk::constraint_system::{ecc::Point, TurboCS};
+    use ark_std::test_rng;
     use zei_algebra::{
         bls12_381::BLSScalar,
         jubjub::{JubjubPoint, JubjubScalar},
@@ -415,4 +483,45 @@ mod test {
         witness[p_out_var.1] = base_point.1;
         assert!(cs.verify_witness(&witness[..], &[]).is_err());
     }
+
+    #[test]
+    fn test_var_base_scalar_mul() {
+        let mut prng = test_rng();
+        let mut cs = TurboCS::new();
+        let base_ext = JubjubPoint::random(&mut prng);
+        let jubjub_scalar = JubjubScalar::random(&mut prng);
+        let scalar = BLSScalar::from_bytes(&jubjub_scalar.to_bytes()).unwrap();
+        let p_out_ext = base_ext.mul(&jubjub_scalar);
+
+        // build circuit
+        let base_var = cs.new_extended_point_variable(&base_ext);
+        cs.insert_on_curve_check(base_var.get_var());
+        let scalar_var = cs.new_variable(scalar);
+        let b_scalar_var = cs.range_check(scalar_var, 256);
+        let p_out_var = cs.var_base_scalar_mul(&base_var, &b_scalar_var);
+        assert_eq!(p_out_var.get_point(), &p_out_ext);
+        let p_out_var = p_out_var.into_point_var();
+        let mut witness = cs.get_and_clear_witness();
+        assert_eq!(witness[p_out_var.get_x()], p_out_ext.get_x());
+        assert_eq!(witness[p_out_var.get_y()], p_out_ext.get_y());
+        pnk!(cs.verify_witness(&witness[..], &[]));
+
+        // wrong witness: point = base * (scalar + 1)
+        let p_out_plus_point = Point::from(&p_out_ext.add(&base_ext));
+        witness[p_out_var.get_x()] = p_out_plus_point.0;
+        witness[p_out_var.get_y()] = p_out_plus_point.1;
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
+
+    #[test]
+    fn test_on_curve_check() {
+        let mut cs = TurboCS::new();
+        let point_var = cs.new_point_variable(Point::from(&JubjubPoint::get_base()));
+        cs.insert_on_curve_check(&point_var);
+        let mut witness = cs.get_and_clear_witness();
+        pnk!(cs.verify_witness(&witness[..], &[]));
+
+        witness[point_var.get_x()].add_assign(&BLSScalar::one());
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
 }
@@ -0,0 +1,114 @@
+use crate::plonk::constraint_system::{
+    ecc::{compute_base_multiples, ExtendedPointVar, PointVar},
+    TurboCS, VarIndex,
+};
+use zei_algebra::{
+    bls12_381::BLSScalar,
+    jubjub::{JubjubPoint, JubjubScalar},
+    prelude::*,
+};
+
+/// The number of bits of the randomness of the ElGamal encryption,
+/// which covers the order of the Jubjub scalar field.
+const RANDOMNESS_BITS: usize = 256;
+
+/// The witness indices of an ElGamal ciphertext over Jubjub.
+pub struct ElGamalCiphertextVar {
+    /// `e1` = `r * G`
+    pub e1: PointVar,
+    /// `e2` = `m * G + r * pk`
+    pub e2: PointVar,
+}
+
+impl TurboCS<BLSScalar> {
+    /// Given a randomness variable r and an encryption key variable `[pk]`,
+    /// returns `(r * [G], r * [pk])`, i.e., the first half of an ElGamal ciphertext and the
+    /// point shared with the holder of the decryption key, who computes it as `sk * (r * [G])`.
+    pub fn elgamal_key_agreement(
+        &mut self,
+        r_var: VarIndex,
+        pub_key_var: &ExtendedPointVar,
+    ) -> (PointVar, ExtendedPointVar) {
+        let b_r_var = self.range_check(r_var, RANDOMNESS_BITS);
+        let bases = compute_base_multiples(JubjubPoint::get_base(), RANDOMNESS_BITS >> 1);
+        let r_g = self.scalar_mul_with_bases(&bases[0], &bases[1], &bases[2], &b_r_var);
+        let r_pk = self.var_base_scalar_mul(pub_key_var, &b_r_var);
+        (r_g, r_pk)
+    }
+
+    /// ElGamal encryption over Jubjub:
+    /// Given an `m_bits`-bit message variable m, a randomness variable r, and an
+    /// encryption key variable `[pk]`, returns `(r * [G], m * [G] + r * [pk])`,
+    /// the same as `elgamal_encrypt` over Jubjub.
+    /// `m_bits` should be a positive even number.
+    pub fn elgamal_encrypt(
+        &mut self,
+        m_var: VarIndex,
+        m_bits: usize,
+        r_var: VarIndex,
+        pub_key_var: &ExtendedPointVar,
+    ) -> ElGamalCiphertextVar {
+        let base = JubjubPoint::get_base();
+
+        // 1. compute `r * G` and `r * pk`.
+        let (e1, r_pk) = self.elgamal_key_agreement(r_var, pub_key_var);
+
+        // 2. compute `m * G + r * pk`.
+        let m_g_var = self.scalar_mul(base, m_var, m_bits);
+        let m = JubjubScalar::from_le_bytes_mod_order(&self.witness[m_var].to_bytes());
+        let e2 = self.ecc_add(&m_g_var, r_pk.get_var(), &base.mul(&m), r_pk.get_point());
+
+        ElGamalCiphertextVar {
+            e1,
+            e2: e2.into_point_var(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::plonk::constraint_system::{ecc::Point, TurboCS};
+    use ark_std::test_rng;
+    use zei_algebra::{
+        bls12_381::BLSScalar,
+        jubjub::{JubjubPoint, JubjubScalar},
+        prelude::*,
+    };
+    use zei_crypto::basic::elgamal::{elgamal_encrypt, elgamal_key_gen};
+
+    #[test]
+    fn test_elgamal_encrypt() {
+        let mut prng = test_rng();
+        let (_, pub_key) = elgamal_key_gen::<_, JubjubPoint>(&mut prng);
+        let m = JubjubScalar::from(u32::MAX);
+        let r = JubjubScalar::random(&mut prng);
+        let ctext = elgamal_encrypt(&m, &r, &pub_key);
+
+        let mut cs = TurboCS::new();
+        let m_var = cs.new_variable(BLSScalar::from(u32::MAX));
+        let r_var = cs.new_variable(BLSScalar::from_bytes(&r.to_bytes()).unwrap());
+        let pub_key_var = cs.new_extended_point_variable(&pub_key.0);
+        let ctext_var = cs.elgamal_encrypt(m_var, 32, r_var, &pub_key_var);
+        let mut witness = cs.get_and_clear_witness();
+        assert_eq!(witness[ctext_var.e1.get_x()], ctext.e1.get_x());
+        assert_eq!(witness[ctext_var.e1.get_y()], ctext.e1.get_y());
+        assert_eq!(witness[ctext_var.e2.get_x()], ctext.e2.get_x());
+        assert_eq!(witness[ctext_var.e2.get_y()], ctext.e2.get_y());
+        pnk!(cs.verify_witness(&witness[..], &[]));
+
+        // wrong witness: e2 encrypts m + 1
+        let wrong_e2 = Point::from(&ctext.e2.add(&JubjubPoint::get_base()));
+        witness[ctext_var.e2.get_x()] = *wrong_e2.get_x();
+        witness[ctext_var.e2.get_y()] = *wrong_e2.get_y();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+
+        // a message beyond `m_bits` bits is rejected
+        let mut cs = TurboCS::new();
+        let m_var = cs.new_variable(BLSScalar::from(1u64 << 32));
+        let r_var = cs.new_variable(BLSScalar::from_bytes(&r.to_bytes()).unwrap());
+        let pub_key_var = cs.new_extended_point_variable(&pub_key.0);
+        let _ = cs.elgamal_encrypt(m_var, 32, r_var, &pub_key_var);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
+}
@@ -12,6 +12,12 @@ pub mod turbo;
 /// Module for ECC.
 pub mod ecc;
 
+/// Module for Schnorr signatures over Jubjub.
+pub mod schnorr;
+
+/// Module for ElGamal encryption over Jubjub.
+pub mod elgamal;
+
 /// Default used constraint system.
 #[doc(hidden)]
 pub use turbo::TurboCS;
@@ -93,6 +99,7 @@ pub trait ConstraintSystem: Sized {
     /// Map the witnesses into the wires of the circuit.
     /// The (i * size + j)-th output element is the value of the i-th wire on the j-th gate.
     fn extend_witness(&self, witness: &[Self::Field]) -> Vec<Self::Field> {
+        zei_algebra::trace_stage!("witness generation");
         let mut extended = Vec::with_capacity(Self::n_wires_per_gate() * self.size());
         for wire_slice in self.wiring().iter() {
             for index in wire_slice.iter() {
@@ -0,0 +1,129 @@
+use crate::plonk::constraint_system::{ecc::ExtendedPointVar, rescue::StateVar, TurboCS, VarIndex};
+use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubPoint, prelude::*};
+use zei_crypto::basic::jubjub_schnorr::{JubjubSchnorrPublicKey, JubjubSchnorrSignature};
+
+/// The number of bits used for the Jubjub scalars in the signature gadget.
+const SCALAR_BITS: usize = 256;
+
+/// The witness indices of a Schnorr signature over Jubjub.
+pub struct JubjubSchnorrSignatureVar {
+    /// The point `R` with its value.
+    pub r: ExtendedPointVar,
+    /// The scalar `s`, as a BLS scalar.
+    pub s: VarIndex,
+}
+
+impl TurboCS<BLSScalar> {
+    /// Create variables for a Schnorr public key over Jubjub.
+    pub fn new_jubjub_schnorr_public_key_variable(
+        &mut self,
+        pub_key: &JubjubSchnorrPublicKey,
+    ) -> ExtendedPointVar {
+        self.new_extended_point_variable(&pub_key.0)
+    }
+
+    /// Create variables for a Schnorr signature over Jubjub.
+    pub fn new_jubjub_schnorr_signature_variable(
+        &mut self,
+        sig: &JubjubSchnorrSignature,
+    ) -> JubjubSchnorrSignatureVar {
+        let r = self.new_extended_point_variable(&sig.r);
+        // The Jubjub scalar field is smaller than the BLS12-381 scalar field.
+        let s = self.new_variable(BLSScalar::from_bytes(&sig.s.to_bytes()).unwrap());
+        JubjubSchnorrSignatureVar { r, s }
+    }
+
+    /// Enforce that a Schnorr signature over Jubjub is valid for a message variable, following
+    /// `jubjub_schnorr_verify`:
+    /// 1. Check that `pk` and `R` are on the curve.
+    /// 2. Recompute the challenge `c = Rescue(Rescue(R.x, R.y, pk.x, pk.y)[0], msg, 0, 0)[0]`.
+    /// 3. Check that `s * G = R + c * pk`.
+    ///
+    /// As in the native verification, the public key is expected to be in the prime-order
+    /// subgroup. The binary decomposition of `c` is not forced to be canonical, which only
+    /// allows a prover to aim at `c + p` or `c + 2p` as well, where `p` is the BLS12-381
+    /// scalar field modulus.
+    pub fn verify_jubjub_schnorr(
+        &mut self,
+        pub_key_var: &ExtendedPointVar,
+        msg_var: VarIndex,
+        sig_var: &JubjubSchnorrSignatureVar,
+    ) {
+        let pk = pub_key_var.get_var();
+        let r = sig_var.r.get_var();
+        self.insert_on_curve_check(pk);
+        self.insert_on_curve_check(r);
+
+        // 1. compute the challenge.
+        let zero_var = self.zero_var();
+        let cur = self.rescue_hash(&StateVar::new([
+            r.get_x(),
+            r.get_y(),
+            pk.get_x(),
+            pk.get_y(),
+        ]))[0];
+        let challenge_var = self.rescue_hash(&StateVar::new([cur, msg_var, zero_var, zero_var]))[0];
+
+        // 2. compute `R + c * pk`.
+        let b_challenge_var = self.range_check(challenge_var, SCALAR_BITS);
+        let c_pk = self.var_base_scalar_mul(pub_key_var, &b_challenge_var);
+        let rhs = self.ecc_add(r, c_pk.get_var(), sig_var.r.get_point(), c_pk.get_point());
+
+        // 3. compute `s * G` and compare.
+        let lhs = self.scalar_mul(JubjubPoint::get_base(), sig_var.s, SCALAR_BITS);
+        self.equal(lhs.get_x(), rhs.get_var().get_x());
+        self.equal(lhs.get_y(), rhs.get_var().get_y());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::plonk::constraint_system::TurboCS;
+    use ark_std::test_rng;
+    use zei_algebra::{bls12_381::BLSScalar, jubjub::JubjubPoint, prelude::*};
+    use zei_crypto::basic::jubjub_schnorr::{
+        jubjub_schnorr_key_gen, jubjub_schnorr_sign, jubjub_schnorr_verify,
+    };
+
+    #[test]
+    fn test_verify_jubjub_schnorr() {
+        let mut prng = test_rng();
+        let (sec_key, pub_key) = jubjub_schnorr_key_gen(&mut prng);
+        let msg = BLSScalar::random(&mut prng);
+        let sig = jubjub_schnorr_sign(&mut prng, &sec_key, &msg);
+        pnk!(jubjub_schnorr_verify(&pub_key, &msg, &sig));
+
+        let mut cs = TurboCS::new();
+        let pub_key_var = cs.new_jubjub_schnorr_public_key_variable(&pub_key);
+        let msg_var = cs.new_variable(msg);
+        let sig_var = cs.new_jubjub_schnorr_signature_variable(&sig);
+        cs.verify_jubjub_schnorr(&pub_key_var, msg_var, &sig_var);
+        let mut witness = cs.get_and_clear_witness();
+        pnk!(cs.verify_witness(&witness[..], &[]));
+
+        // wrong witness: a different message
+        witness[msg_var].add_assign(&BLSScalar::one());
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+
+        // a signature under another key is rejected
+        let (_, other_pub_key) = jubjub_schnorr_key_gen(&mut prng);
+        let mut cs = TurboCS::new();
+        let pub_key_var = cs.new_jubjub_schnorr_public_key_variable(&other_pub_key);
+        let msg_var = cs.new_variable(msg);
+        let sig_var = cs.new_jubjub_schnorr_signature_variable(&sig);
+        cs.verify_jubjub_schnorr(&pub_key_var, msg_var, &sig_var);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+
+        // a tampered `R` is rejected
+        let mut bad_sig = sig.clone();
+        bad_sig.r = bad_sig.r.add(&JubjubPoint::get_base());
+        let mut cs = TurboCS::new();
+        let pub_key_var = cs.new_jubjub_schnorr_public_key_variable(&pub_key);
+        let msg_var = cs.new_variable(msg);
+        let sig_var = cs.new_jubjub_schnorr_signature_variable(&bad_sig);
+        cs.verify_jubjub_schnorr(&pub_key_var, msg_var, &sig_var);
+        let witness = cs.get_and_clear_witness();
+        assert!(cs.verify_witness(&witness[..], &[]).is_err());
+    }
+}
@@ -8,7 +8,9 @@ use crate::poly_commit::{
     pcs::{HomomorphicPolyComElem, PolyComScheme},
 };
 #[cfg(feature = "parallel")]
-use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
 use std::cmp::min;
 use zei_algebra::prelude::*;
 
@@ -146,6 +148,73 @@ pub(super) fn pi_poly<PCS: PolyComScheme>(
     )
 }
 
+/// Interpolate the witness polynomials of each wire, and commit to them in the Lagrange basis
+/// if `lagrange_pcs` is provided. The polynomials are not hidden yet, which is done later
+/// with the random blinds drawn in order.
+#[cfg(not(feature = "parallel"))]
+pub(super) fn interpolate_witness_polys<PCS: PolyComScheme>(
+    root: &PCS::Field,
+    extended_witness: &[PCS::Field],
+    n_wires_per_gate: usize,
+    n_constraints: usize,
+    lagrange_pcs: Option<&PCS>,
+) -> Result<Vec<(FpPolynomial<PCS::Field>, Option<PCS::Commitment>)>> {
+    zei_algebra::trace_stage!("witness interpolation");
+    (0..n_wires_per_gate)
+        .map(|i| {
+            interpolate_witness_poly(
+                root,
+                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
+                n_constraints,
+                lagrange_pcs,
+            )
+        })
+        .collect()
+}
+
+/// Interpolate the witness polynomials of each wire, and commit to them in the Lagrange basis
+/// if `lagrange_pcs` is provided. The polynomials are not hidden yet, which is done later
+/// with the random blinds drawn in order.
+#[cfg(feature = "parallel")]
+pub(super) fn interpolate_witness_polys<PCS: PolyComScheme>(
+    root: &PCS::Field,
+    extended_witness: &[PCS::Field],
+    n_wires_per_gate: usize,
+    n_constraints: usize,
+    lagrange_pcs: Option<&PCS>,
+) -> Result<Vec<(FpPolynomial<PCS::Field>, Option<PCS::Commitment>)>> {
+    zei_algebra::trace_stage!("witness interpolation");
+    (0..n_wires_per_gate)
+        .into_par_iter()
+        .map(|i| {
+            interpolate_witness_poly(
+                root,
+                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
+                n_constraints,
+                lagrange_pcs,
+            )
+        })
+        .collect()
+}
+
+fn interpolate_witness_poly<PCS: PolyComScheme>(
+    root: &PCS::Field,
+    evals: &[PCS::Field],
+    n_constraints: usize,
+    lagrange_pcs: Option<&PCS>,
+) -> Result<(FpPolynomial<PCS::Field>, Option<PCS::Commitment>)> {
+    let f_coefs = FpPolynomial::ffti(root, evals, n_constraints);
+    let cm = match lagrange_pcs {
+        Some(lagrange_pcs) => Some(
+            lagrange_pcs
+                .commit(&FpPolynomial::from_coefs(evals.to_vec()))
+                .c(d!(PlonkError::CommitmentError))?,
+        ),
+        None => None,
+    };
+    Ok((f_coefs, cm))
+}
+
 /// Add a random degree `num_hide_points`+`zeroing_degree` polynomial
 /// that vanishes on X^{zeroing_degree} -1. Goal is to randomize
 /// `polynomial` maintaining output values for elements in a sub group
@@ -178,6 +247,7 @@ pub(super) fn z_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     w: &[PCS::Field],
     challenges: &PlonkChallenges<PCS::Field>,
 ) -> FpPolynomial<PCS::Field> {
+    zei_algebra::trace_stage!("z polynomial");
     let n_wires_per_gate = CS::n_wires_per_gate();
     let (beta, gamma) = challenges.get_beta_gamma().unwrap();
     let mut z_evals = vec![];
@@ -234,6 +304,7 @@ pub(super) fn t_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     challenges: &PlonkChallenges<PCS::Field>,
     pi: &FpPolynomial<PCS::Field>,
 ) -> Result<FpPolynomial<PCS::Field>> {
+    zei_algebra::trace_stage!("t polynomial");
     let n = cs.size();
     let m = cs.quot_eval_dom_size();
     let factor = m / n;
@@ -541,6 +612,7 @@ pub(super) fn r_poly<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field
     z_h_eval_zeta: &PCS::Field,
     n_t_polys: usize,
 ) -> FpPolynomial<PCS::Field> {
+    zei_algebra::trace_stage!("r polynomial");
     let w = CS::eval_selector_multipliers(w_polys_eval_zeta).unwrap(); // safe unwrap
     r_poly_or_comm::<PCS::Field, FpPolynomial<PCS::Field>>(
         &w,
@@ -696,6 +768,7 @@ pub(crate) fn split_t_and_commit<R: CryptoRng + RngCore, PCS: PolyComScheme>(
     n_wires_per_gate: usize,
     n: usize,
 ) -> Result<(Vec<PCS::Commitment>, Vec<FpPolynomial<PCS::Field>>)> {
+    zei_algebra::trace_stage!("t polynomial commitment");
     let mut cm_t_vec = vec![];
     let mut t_polys = vec![];
     let coefs_len = t.get_coefs_ref().len();
@@ -3,6 +3,9 @@
 /// Module for help functions.
 pub(crate) mod helpers;
 
+/// Module for the accumulation of proofs.
+pub mod accumulation;
+
 /// Module for the constraint system.
 pub mod constraint_system;
 
@@ -2,8 +2,8 @@ use crate::plonk::{
     constraint_system::ConstraintSystem,
     errors::PlonkError,
     helpers::{
-        first_lagrange_poly, hide_polynomial, pi_poly, r_poly, split_t_and_commit, t_poly, z_poly,
-        PlonkChallenges,
+        first_lagrange_poly, hide_polynomial, interpolate_witness_polys, pi_poly, r_poly,
+        split_t_and_commit, t_poly, z_poly, PlonkChallenges,
     },
     indexer::{PlonkPK, PlonkPf, PlonkProof},
     transcript::{
@@ -99,6 +99,7 @@ pub fn prover_with_lagrange<
     prover_params: &PlonkPK<PCS>,
     w: &[PCS::Field],
 ) -> Result<PlonkPf<PCS>> {
+    zei_algebra::trace_stage!("plonk prover");
     if cs.is_verifier_only() {
         return Err(eg!(PlonkError::FuncParamsError));
     }
@@ -136,38 +137,23 @@ pub fn prover_with_lagrange<
     let n_wires_per_gate = CS::n_wires_per_gate();
     let mut w_polys = vec![];
     let mut cm_w_vec = vec![];
-    if let Some(lagrange_pcs) = lagrange_pcs {
-        for i in 0..n_wires_per_gate {
-            let f_eval = FpPolynomial::from_coefs(
-                extended_witness[i * n_constraints..(i + 1) * n_constraints].to_vec(),
-            );
-            let mut f_coefs = FpPolynomial::ffti(
-                root,
-                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
-                n_constraints,
-            );
-            let blinds = hide_polynomial(prng, &mut f_coefs, 1, n_constraints);
-            let cm_w = lagrange_pcs
-                .commit(&f_eval)
-                .c(d!(PlonkError::CommitmentError))?;
-            let cm_w = pcs.apply_blind_factors(&cm_w, &blinds, n_constraints);
-            transcript.append_commitment::<PCS::Commitment>(&cm_w);
-            w_polys.push(f_coefs);
-            cm_w_vec.push(cm_w);
-        }
-    } else {
-        for i in 0..n_wires_per_gate {
-            let mut f_coefs = FpPolynomial::ffti(
-                root,
-                &extended_witness[i * n_constraints..(i + 1) * n_constraints],
-                n_constraints,
-            );
-            let _ = hide_polynomial(prng, &mut f_coefs, 1, n_constraints);
-            let cm_w = pcs.commit(&f_coefs).c(d!(PlonkError::CommitmentError))?;
-            transcript.append_commitment::<PCS::Commitment>(&cm_w);
-            w_polys.push(f_coefs);
-            cm_w_vec.push(cm_w);
-        }
+    let interpolated = interpolate_witness_polys(
+        root,
+        &extended_witness,
+        n_wires_per_gate,
+        n_constraints,
+        lagrange_pcs,
+    )
+    .c(d!())?;
+    for (mut f_coefs, cm_w) in interpolated {
+        let blinds = hide_polynomial(prng, &mut f_coefs, 1, n_constraints);
+        let cm_w = match cm_w {
+            Some(cm_w) => pcs.apply_blind_factors(&cm_w, &blinds, n_constraints),
+            None => pcs.commit(&f_coefs).c(d!(PlonkError::CommitmentError))?,
+        };
+        transcript.append_commitment::<PCS::Commitment>(&cm_w);
+        w_polys.push(f_coefs);
+        cm_w_vec.push(cm_w);
     }
 
     // 2. get challenges beta and gamma
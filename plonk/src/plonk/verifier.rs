@@ -22,6 +22,38 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
     pi: &[PCS::Field],
     proof: &PlonkPf<PCS>,
 ) -> Result<()> {
+    zei_algebra::trace_stage!("plonk verifier");
+    let opening = prepare_opening(transcript, pcs, cs, verifier_params, pi, proof);
+    pcs.batch_verify_diff_points(
+        transcript,
+        &opening.cm_vec,
+        verifier_params.cs_size + 2,
+        &opening.point_vec,
+        &opening.eval_vec,
+        &opening.proofs,
+        &opening.challenge,
+    )
+    .c(d!(PlonkError::VerificationError))
+}
+
+/// The opening proofs that a Plonk proof reduces to, at the end of the verification.
+pub(crate) struct PlonkOpening<PCS: PolyComScheme> {
+    pub(crate) cm_vec: Vec<PCS::Commitment>,
+    pub(crate) point_vec: Vec<PCS::Field>,
+    pub(crate) eval_vec: Vec<PCS::Field>,
+    pub(crate) proofs: Vec<PCS::Commitment>,
+    pub(crate) challenge: PCS::Field,
+}
+
+/// Run the verification of a proof, except for the final check of the opening proofs.
+pub(crate) fn prepare_opening<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
+    transcript: &mut Transcript,
+    pcs: &PCS,
+    cs: &CS,
+    verifier_params: &PlonkVK<PCS>,
+    pi: &[PCS::Field],
+    proof: &PlonkPf<PCS>,
+) -> PlonkOpening<PCS> {
     transcript_init_plonk(transcript, verifier_params, pi);
     let mut challenges = PlonkChallenges::new();
     // 1. compute all challenges such as gamma, beta, alpha, zeta and u.
@@ -88,19 +120,16 @@ pub fn verifier<PCS: PolyComScheme, CS: ConstraintSystem<Field = PCS::Field>>(
         &zeta,
         &values[..],
     );
-    pcs.batch_verify_diff_points(
-        transcript,
-        &[comm, proof.cm_z.clone()],
-        verifier_params.cs_size + 2,
-        &[zeta.clone(), zeta_omega.clone()],
-        &[val, proof.z_eval_zeta_omega],
-        &[
+    PlonkOpening {
+        cm_vec: vec![comm, proof.cm_z.clone()],
+        point_vec: vec![zeta.clone(), zeta_omega],
+        eval_vec: vec![val, proof.z_eval_zeta_omega],
+        proofs: vec![
             proof.opening_witness_zeta.clone(),
             proof.opening_witness_zeta_omega.clone(),
         ],
-        challenges.get_u().unwrap(),
-    )
-    .c(d!(PlonkError::VerificationError))
+        challenge: *challenges.get_u().unwrap(),
+    }
 }
 
 fn compute_challenges<PCS: PolyComScheme>(
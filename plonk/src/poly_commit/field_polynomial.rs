@@ -514,6 +514,7 @@ impl<F: Scalar> FpPolynomial<F> {
     /// Compute the FFT of the polynomial using given n-th root of unity
     /// n is with the form 2^k or 3 * 2^k.
     pub fn fft_with_unity_root(&self, root: &F, num_points: usize) -> Vec<F> {
+        zei_algebra::trace_stage!("fft");
         assert!(
             num_points.is_power_of_two()
                 || ((num_points % 3 == 0) && (num_points / 3).is_power_of_two())
@@ -535,6 +536,7 @@ impl<F: Scalar> FpPolynomial<F> {
     /// Compute the polynomial given its evaluation values at the n n-th root of unity given a
     /// primitive n-th root of unity.
     pub fn ffti(root: &F, values: &[F], len: usize) -> Self {
+        zei_algebra::trace_stage!("inverse fft");
         let mut values: Vec<&F> = values.iter().collect();
         let zero = F::zero();
         values.resize(len, &zero);
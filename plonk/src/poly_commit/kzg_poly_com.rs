@@ -5,7 +5,7 @@ use crate::poly_commit::{
 };
 use merlin::Transcript;
 use zei_algebra::{
-    bls12_381::{BLSPairingEngine, BLSScalar, BLSG1},
+    bls12_381::{BLSGt, BLSPairingEngine, BLSScalar, BLSG1},
     prelude::*,
     traits::Pairing,
 };
@@ -204,6 +204,77 @@ impl<P: Pairing> KZGCommitmentScheme<P> {
 /// KZG commitment scheme over theb BLS12-381 curve
 pub type KZGCommitmentSchemeBLS = KZGCommitmentScheme<BLSPairingEngine>;
 
+/// The pairing check `e(left, [s]_2) = e(right, [1]_2)` that a batch of KZG opening proofs
+/// reduces to. Claims can be accumulated with random weights, and checked together.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KZGPairingClaim {
+    /// The element paired with `[s]_2`.
+    pub left: BLSG1,
+    /// The element paired with `[1]_2`.
+    pub right: BLSG1,
+}
+
+impl KZGPairingClaim {
+    /// Add the claim `other` with the weight `weight`.
+    pub fn accumulate(&mut self, other: &KZGPairingClaim, weight: &BLSScalar) {
+        self.left.add_assign(&other.left.mul(weight));
+        self.right.add_assign(&other.right.mul(weight));
+    }
+
+    /// Check the claim with a product of two pairings.
+    pub fn is_valid(&self, pcs: &KZGCommitmentSchemeBLS) -> bool {
+        let pairs = [
+            (self.left, pcs.public_parameter_group_2[1]),
+            (self.right.neg(), pcs.public_parameter_group_2[0]),
+        ];
+        BLSPairingEngine::multi_pairing(&pairs) == BLSGt::get_identity()
+    }
+}
+
+impl KZGCommitmentSchemeBLS {
+    /// Reduce the batch of opening proofs at different points to a pairing claim,
+    /// as in `batch_verify_diff_points`, without checking it.
+    pub fn pairing_claim_diff_points(
+        &self,
+        cm_vec: &[KZGCommitment<BLSG1>],
+        point_vec: &[BLSScalar],
+        eval_vec: &[BLSScalar],
+        proofs: &[KZGCommitment<BLSG1>],
+        challenge: &BLSScalar,
+    ) -> KZGPairingClaim {
+        assert!(proofs.len() > 0);
+        assert_eq!(proofs.len(), point_vec.len());
+        assert_eq!(proofs.len(), eval_vec.len());
+        assert_eq!(proofs.len(), cm_vec.len());
+
+        let g1_0 = self.public_parameter_group_1[0].clone();
+
+        let mut left_first = proofs[0].0.clone();
+        let mut right_first = proofs[0].0.mul(&point_vec[0]);
+        let mut right_first_val = eval_vec[0].clone();
+        let mut right_first_comm = cm_vec[0].0.clone();
+
+        let mut cur_challenge = challenge.clone();
+        for i in 1..proofs.len() {
+            let new_comm = proofs[i].0.mul(&cur_challenge);
+
+            left_first.add_assign(&new_comm);
+            right_first.add_assign(&new_comm.mul(&point_vec[i]));
+            right_first_val.add_assign(&eval_vec[i].mul(&cur_challenge));
+            right_first_comm.add_assign(&cm_vec[i].0.mul(&cur_challenge));
+
+            cur_challenge.mul_assign(&challenge);
+        }
+        right_first.sub_assign(&g1_0.mul(&right_first_val));
+        right_first.add_assign(&right_first_comm);
+
+        KZGPairingClaim {
+            left: left_first,
+            right: right_first,
+        }
+    }
+}
+
 impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
     type Field = BLSScalar;
     type Commitment = KZGCommitment<BLSG1>;
@@ -213,6 +284,7 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
     }
 
     fn commit(&self, polynomial: &FpPolynomial<BLSScalar>) -> Result<Self::Commitment> {
+        zei_algebra::trace_stage!("msm");
         let coefs = polynomial.get_coefs_ref();
 
         let degree = polynomial.degree();
@@ -299,22 +371,24 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
         eval: &Self::Field,
         proof: &Self::Commitment,
     ) -> Result<()> {
+        zei_algebra::trace_stage!("pairing check");
         let g1_0 = self.public_parameter_group_1[0].clone();
         let g2_0 = self.public_parameter_group_2[0].clone();
         let g2_1 = self.public_parameter_group_2[1].clone();
 
         let x_minus_point_group_element_group_2 = &g2_1.sub(&g2_0.mul(point));
 
-        let left_pairing_eval = if eval.is_zero() {
-            BLSPairingEngine::pairing(&cm.0, &g2_0)
+        let left = if eval.is_zero() {
+            cm.0
         } else {
-            BLSPairingEngine::pairing(&cm.0.sub(&g1_0.mul(eval)), &g2_0)
+            cm.0.sub(&g1_0.mul(eval))
         };
 
-        let right_pairing_eval =
-            BLSPairingEngine::pairing(&proof.0, &x_minus_point_group_element_group_2);
-
-        if left_pairing_eval == right_pairing_eval {
+        let pairs = [
+            (left, g2_0),
+            (proof.0.neg(), *x_minus_point_group_element_group_2),
+        ];
+        if BLSPairingEngine::multi_pairing(&pairs) == BLSGt::get_identity() {
             Ok(())
         } else {
             Err(eg!(PolyComSchemeError::PCSProveEvalError))
@@ -331,41 +405,9 @@ impl<'b> PolyComScheme for KZGCommitmentSchemeBLS {
         proofs: &[Self::Commitment],
         challenge: &Self::Field,
     ) -> Result<()> {
-        assert!(proofs.len() > 0);
-        assert_eq!(proofs.len(), point_vec.len());
-        assert_eq!(proofs.len(), eval_vec.len());
-        assert_eq!(proofs.len(), cm_vec.len());
-
-        let g1_0 = self.public_parameter_group_1[0].clone();
-        let g2_0 = self.public_parameter_group_2[0].clone();
-        let g2_1 = self.public_parameter_group_2[1].clone();
-
-        let left_second = g2_1;
-        let right_second = g2_0;
-
-        let mut left_first = proofs[0].0.clone();
-        let mut right_first = proofs[0].0.mul(&point_vec[0]);
-        let mut right_first_val = eval_vec[0].clone();
-        let mut right_first_comm = cm_vec[0].0.clone();
-
-        let mut cur_challenge = challenge.clone();
-        for i in 1..proofs.len() {
-            let new_comm = proofs[i].0.mul(&cur_challenge);
-
-            left_first.add_assign(&new_comm);
-            right_first.add_assign(&new_comm.mul(&point_vec[i]));
-            right_first_val.add_assign(&eval_vec[i].mul(&cur_challenge));
-            right_first_comm.add_assign(&cm_vec[i].0.mul(&cur_challenge));
-
-            cur_challenge.mul_assign(&challenge);
-        }
-        right_first.sub_assign(&g1_0.mul(&right_first_val));
-        right_first.add_assign(&right_first_comm);
-
-        let left_pairing_eval = BLSPairingEngine::pairing(&left_first, &left_second);
-        let right_pairing_eval = BLSPairingEngine::pairing(&right_first, &right_second);
-
-        if left_pairing_eval == right_pairing_eval {
+        zei_algebra::trace_stage!("batched pairing check");
+        let claim = self.pairing_claim_diff_points(cm_vec, point_vec, eval_vec, proofs, challenge);
+        if claim.is_valid(self) {
             Ok(())
         } else {
             Err(eg!(PolyComSchemeError::PCSProveEvalError))